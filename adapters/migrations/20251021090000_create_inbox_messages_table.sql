@@ -0,0 +1,11 @@
+-- Add migration script here
+-- Messages whose handlers fail are kept as dead letters, with the error.
+CREATE TABLE inbox_messages (
+    id UUID PRIMARY KEY,
+    event_type VARCHAR NOT NULL,
+    event_payload VARCHAR NOT NULL,
+    received_at TIMESTAMPTZ NOT NULL,
+    processed_at TIMESTAMPTZ NULL,
+    failed_at TIMESTAMPTZ NULL,
+    error VARCHAR NULL
+)
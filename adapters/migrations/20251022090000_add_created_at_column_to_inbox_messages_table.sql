@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE inbox_messages
+ADD COLUMN created_at TIMESTAMPTZ NULL;
@@ -0,0 +1,48 @@
+-- Add migration script here
+-- Messages are read by projections in (transaction_id, position) order, only
+-- from transactions older than any still running, so that messages committed
+-- late are never skipped. Existing messages keep their creation order.
+ALTER TABLE outbox_messages
+ADD COLUMN transaction_id BIGINT NOT NULL DEFAULT 0,
+ADD COLUMN position BIGSERIAL NOT NULL;
+
+UPDATE outbox_messages
+SET position = ordered.position
+FROM (
+    SELECT id, ROW_NUMBER() OVER (ORDER BY created_at, id) AS position FROM outbox_messages
+) AS ordered
+WHERE outbox_messages.id = ordered.id;
+SELECT setval(
+    pg_get_serial_sequence('outbox_messages', 'position'),
+    (SELECT COALESCE(MAX(position), 0) + 1 FROM outbox_messages),
+    false
+);
+
+ALTER TABLE outbox_messages
+ALTER COLUMN transaction_id SET DEFAULT pg_current_xact_id()::text::bigint;
+CREATE INDEX outbox_messages_transaction_id_position_idx
+ON outbox_messages (transaction_id, position);
+
+CREATE TABLE projection_checkpoints (
+    projection_name VARCHAR PRIMARY KEY,
+    last_transaction_id BIGINT NOT NULL,
+    last_position BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE customer_order_counts (
+    customer_id UUID PRIMARY KEY,
+    order_count BIGINT NOT NULL
+);
+
+CREATE TABLE order_totals (
+    order_id UUID PRIMARY KEY,
+    customer_id UUID NULL,
+    total DOUBLE PRECISION NOT NULL,
+    item_count INTEGER NOT NULL
+);
+
+CREATE TABLE revenue_per_day (
+    day DATE PRIMARY KEY,
+    revenue DOUBLE PRECISION NOT NULL
+);
@@ -0,0 +1,15 @@
+CREATE TABLE events (
+    stream_id UUID NOT NULL,
+    version BIGINT NOT NULL,
+    event_type TEXT NOT NULL,
+    event_payload TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (stream_id, version)
+);
+
+CREATE TABLE snapshots (
+    stream_id UUID PRIMARY KEY,
+    version BIGINT NOT NULL,
+    snapshot_payload TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
@@ -0,0 +1,93 @@
+-- Countries are stored as ISO 3166-1 alpha-2 codes from now on. Names and
+-- aliases of countries are mapped to their code, anything else is moved to ZZ,
+-- the unknown country. Values that are changed are kept in the legacy columns.
+ALTER TABLE customers RENAME COLUMN state TO country;
+ALTER TABLE customers ADD COLUMN legacy_state VARCHAR NULL;
+ALTER TABLE customers ADD COLUMN legacy_zip_code VARCHAR NULL;
+UPDATE customers SET legacy_state = country, legacy_zip_code = zip_code;
+UPDATE customers SET zip_code = UPPER(TRIM(zip_code));
+UPDATE customers SET country = CASE UPPER(REGEXP_REPLACE(TRIM(country), '[.\s]+', ' ', 'g'))
+    WHEN 'USA' THEN 'US'
+    WHEN 'U S' THEN 'US'
+    WHEN 'U S A' THEN 'US'
+    WHEN 'UNITED STATES' THEN 'US'
+    WHEN 'UNITED STATES OF AMERICA' THEN 'US'
+    WHEN 'UK' THEN 'GB'
+    WHEN 'U K' THEN 'GB'
+    WHEN 'UNITED KINGDOM' THEN 'GB'
+    WHEN 'GREAT BRITAIN' THEN 'GB'
+    WHEN 'ENGLAND' THEN 'GB'
+    WHEN 'ITALY' THEN 'IT'
+    WHEN 'ITALIA' THEN 'IT'
+    WHEN 'GERMANY' THEN 'DE'
+    WHEN 'DEUTSCHLAND' THEN 'DE'
+    WHEN 'FRANCE' THEN 'FR'
+    WHEN 'SPAIN' THEN 'ES'
+    WHEN 'ESPAÑA' THEN 'ES'
+    WHEN 'NETHERLANDS' THEN 'NL'
+    WHEN 'THE NETHERLANDS' THEN 'NL'
+    WHEN 'HOLLAND' THEN 'NL'
+    WHEN 'PORTUGAL' THEN 'PT'
+    WHEN 'SWITZERLAND' THEN 'CH'
+    WHEN 'AUSTRIA' THEN 'AT'
+    WHEN 'BELGIUM' THEN 'BE'
+    WHEN 'CANADA' THEN 'CA'
+    WHEN 'MEXICO' THEN 'MX'
+    WHEN 'BRAZIL' THEN 'BR'
+    WHEN 'JAPAN' THEN 'JP'
+    WHEN 'AUSTRALIA' THEN 'AU'
+    WHEN 'INDIA' THEN 'IN'
+    ELSE UPPER(TRIM(country))
+END;
+UPDATE customers SET country = 'ZZ' WHERE country NOT IN (
+    'AD', 'AE', 'AF', 'AG', 'AI', 'AL', 'AM', 'AO', 'AQ', 'AR', 'AS', 'AT', 'AU', 'AW', 'AX', 'AZ',
+    'BA', 'BB', 'BD', 'BE', 'BF', 'BG', 'BH', 'BI', 'BJ', 'BL', 'BM', 'BN', 'BO', 'BQ', 'BR', 'BS',
+    'BT', 'BV', 'BW', 'BY', 'BZ', 'CA', 'CC', 'CD', 'CF', 'CG', 'CH', 'CI', 'CK', 'CL', 'CM', 'CN',
+    'CO', 'CR', 'CU', 'CV', 'CW', 'CX', 'CY', 'CZ', 'DE', 'DJ', 'DK', 'DM', 'DO', 'DZ', 'EC', 'EE',
+    'EG', 'EH', 'ER', 'ES', 'ET', 'FI', 'FJ', 'FK', 'FM', 'FO', 'FR', 'GA', 'GB', 'GD', 'GE', 'GF',
+    'GG', 'GH', 'GI', 'GL', 'GM', 'GN', 'GP', 'GQ', 'GR', 'GS', 'GT', 'GU', 'GW', 'GY', 'HK', 'HM',
+    'HN', 'HR', 'HT', 'HU', 'ID', 'IE', 'IL', 'IM', 'IN', 'IO', 'IQ', 'IR', 'IS', 'IT', 'JE', 'JM',
+    'JO', 'JP', 'KE', 'KG', 'KH', 'KI', 'KM', 'KN', 'KP', 'KR', 'KW', 'KY', 'KZ', 'LA', 'LB', 'LC',
+    'LI', 'LK', 'LR', 'LS', 'LT', 'LU', 'LV', 'LY', 'MA', 'MC', 'MD', 'ME', 'MF', 'MG', 'MH', 'MK',
+    'ML', 'MM', 'MN', 'MO', 'MP', 'MQ', 'MR', 'MS', 'MT', 'MU', 'MV', 'MW', 'MX', 'MY', 'MZ', 'NA',
+    'NC', 'NE', 'NF', 'NG', 'NI', 'NL', 'NO', 'NP', 'NR', 'NU', 'NZ', 'OM', 'PA', 'PE', 'PF', 'PG',
+    'PH', 'PK', 'PL', 'PM', 'PN', 'PR', 'PS', 'PT', 'PW', 'PY', 'QA', 'RE', 'RO', 'RS', 'RU', 'RW',
+    'SA', 'SB', 'SC', 'SD', 'SE', 'SG', 'SH', 'SI', 'SJ', 'SK', 'SL', 'SM', 'SN', 'SO', 'SR', 'SS',
+    'ST', 'SV', 'SX', 'SY', 'SZ', 'TC', 'TD', 'TF', 'TG', 'TH', 'TJ', 'TK', 'TL', 'TM', 'TN', 'TO',
+    'TR', 'TT', 'TV', 'TW', 'TZ', 'UA', 'UG', 'UM', 'US', 'UY', 'UZ', 'VA', 'VC', 'VE', 'VG', 'VI',
+    'VN', 'VU', 'WF', 'WS', 'YE', 'YT', 'ZA', 'ZM', 'ZW'
+);
+-- Postal codes that don't fit the format of their country leave it unknown.
+UPDATE customers SET country = 'ZZ' WHERE NOT zip_code ~ CASE country
+    WHEN 'US' THEN '^([0-9]{5}|[0-9]{5}-[0-9]{4})$'
+    WHEN 'CA' THEN '^[A-Z][0-9][A-Z] [0-9][A-Z][0-9]$'
+    WHEN 'GB' THEN '^[A-Z]{1,2}([0-9]{1,2}|[0-9][A-Z]) [0-9][A-Z]{2}$'
+    WHEN 'NL' THEN '^[0-9]{4} [A-Z]{2}$'
+    WHEN 'PT' THEN '^[0-9]{4}-[0-9]{3}$'
+    WHEN 'BR' THEN '^[0-9]{5}-[0-9]{3}$'
+    WHEN 'JP' THEN '^[0-9]{3}-[0-9]{4}$'
+    WHEN 'PL' THEN '^[0-9]{2}-[0-9]{3}$'
+    WHEN 'SE' THEN '^[0-9]{3} [0-9]{2}$'
+    WHEN 'IN' THEN '^[0-9]{6}$'
+    WHEN 'IT' THEN '^[0-9]{5}$'
+    WHEN 'DE' THEN '^[0-9]{5}$'
+    WHEN 'FR' THEN '^[0-9]{5}$'
+    WHEN 'ES' THEN '^[0-9]{5}$'
+    WHEN 'FI' THEN '^[0-9]{5}$'
+    WHEN 'MX' THEN '^[0-9]{5}$'
+    WHEN 'AT' THEN '^[0-9]{4}$'
+    WHEN 'AU' THEN '^[0-9]{4}$'
+    WHEN 'BE' THEN '^[0-9]{4}$'
+    WHEN 'CH' THEN '^[0-9]{4}$'
+    WHEN 'DK' THEN '^[0-9]{4}$'
+    WHEN 'NO' THEN '^[0-9]{4}$'
+    WHEN 'NZ' THEN '^[0-9]{4}$'
+    ELSE '^.*$'
+END;
+-- Anywhere else a postal code is up to 10 letters, digits, spaces or dashes.
+UPDATE customers SET zip_code = COALESCE(
+    NULLIF(LEFT(TRIM(REGEXP_REPLACE(zip_code, '[^A-Z0-9 -]', '', 'g')), 10), ''),
+    '-'
+);
+UPDATE customers SET legacy_state = NULL WHERE legacy_state = country;
+UPDATE customers SET legacy_zip_code = NULL WHERE legacy_zip_code = zip_code;
@@ -0,0 +1,6 @@
+-- Existing customers get a placeholder email on the reserved .invalid domain.
+ALTER TABLE customers ADD COLUMN email VARCHAR;
+UPDATE customers SET email = id || '@unknown.invalid';
+ALTER TABLE customers ALTER COLUMN email SET NOT NULL;
+ALTER TABLE customers ADD COLUMN phone VARCHAR;
+CREATE UNIQUE INDEX customers_email_idx ON customers (LOWER(email));
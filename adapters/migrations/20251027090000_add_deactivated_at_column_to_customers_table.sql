@@ -0,0 +1,2 @@
+-- Deactivated customers are kept, so orders keep pointing to them.
+ALTER TABLE customers ADD COLUMN deactivated_at TIMESTAMPTZ;
@@ -0,0 +1,13 @@
+CREATE TABLE customer_addresses (
+    id UUID PRIMARY KEY,
+    customer_id UUID NOT NULL,
+    street VARCHAR NOT NULL,
+    city VARCHAR NOT NULL,
+    zip_code VARCHAR NOT NULL,
+    country VARCHAR NOT NULL,
+    is_default_shipping BOOLEAN NOT NULL DEFAULT FALSE,
+    is_default_billing BOOLEAN NOT NULL DEFAULT FALSE
+);
+CREATE INDEX customer_addresses_customer_id_idx ON customer_addresses (customer_id);
+CREATE UNIQUE INDEX customer_addresses_default_shipping_idx ON customer_addresses (customer_id) WHERE is_default_shipping;
+CREATE UNIQUE INDEX customer_addresses_default_billing_idx ON customer_addresses (customer_id) WHERE is_default_billing;
@@ -0,0 +1,9 @@
+-- Addresses of the customer when the order was placed; empty for older orders.
+ALTER TABLE orders ADD COLUMN shipping_street VARCHAR;
+ALTER TABLE orders ADD COLUMN shipping_city VARCHAR;
+ALTER TABLE orders ADD COLUMN shipping_zip_code VARCHAR;
+ALTER TABLE orders ADD COLUMN shipping_country VARCHAR;
+ALTER TABLE orders ADD COLUMN billing_street VARCHAR;
+ALTER TABLE orders ADD COLUMN billing_city VARCHAR;
+ALTER TABLE orders ADD COLUMN billing_zip_code VARCHAR;
+ALTER TABLE orders ADD COLUMN billing_country VARCHAR;
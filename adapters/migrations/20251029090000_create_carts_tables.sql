@@ -0,0 +1,15 @@
+CREATE TABLE carts (
+    id UUID PRIMARY KEY,
+    customer_id UUID,
+    expires_at TIMESTAMPTZ NOT NULL,
+    checked_out_at TIMESTAMPTZ,
+    version BIGINT NOT NULL
+);
+CREATE UNIQUE INDEX carts_customer_id_idx ON carts (customer_id) WHERE customer_id IS NOT NULL;
+CREATE TABLE cart_items (
+    cart_id UUID NOT NULL REFERENCES carts (id) ON DELETE CASCADE,
+    product_id UUID NOT NULL,
+    quantity INT NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (cart_id, product_id)
+);
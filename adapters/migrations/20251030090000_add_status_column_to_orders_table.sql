@@ -0,0 +1,3 @@
+-- Orders created before the checkout existed were already confirmed.
+ALTER TABLE orders ADD COLUMN status VARCHAR NOT NULL DEFAULT 'confirmed';
+ALTER TABLE orders ALTER COLUMN status SET DEFAULT 'pending';
@@ -0,0 +1,10 @@
+CREATE TABLE checkout_sagas (
+    order_id UUID PRIMARY KEY,
+    state VARCHAR NOT NULL,
+    reservation_id UUID,
+    authorization_id VARCHAR,
+    failure_reason VARCHAR,
+    deadline TIMESTAMPTZ NOT NULL,
+    version BIGINT NOT NULL
+);
+CREATE INDEX checkout_sagas_deadline_idx ON checkout_sagas (deadline) WHERE state NOT IN ('confirmed', 'cancelled');
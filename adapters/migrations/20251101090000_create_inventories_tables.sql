@@ -0,0 +1,21 @@
+CREATE TABLE inventories (
+    product_id UUID NOT NULL,
+    warehouse_id UUID NOT NULL,
+    on_hand INT NOT NULL,
+    reserved INT NOT NULL,
+    version BIGINT NOT NULL,
+    PRIMARY KEY (product_id, warehouse_id),
+    CHECK (reserved >= 0 AND reserved <= on_hand)
+);
+CREATE TABLE stock_reservations (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL UNIQUE,
+    state VARCHAR NOT NULL
+);
+CREATE TABLE stock_reservation_items (
+    reservation_id UUID NOT NULL REFERENCES stock_reservations (id) ON DELETE CASCADE,
+    product_id UUID NOT NULL,
+    warehouse_id UUID NOT NULL,
+    quantity INT NOT NULL CHECK (quantity > 0),
+    PRIMARY KEY (reservation_id, product_id, warehouse_id)
+);
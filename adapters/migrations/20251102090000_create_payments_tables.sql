@@ -0,0 +1,17 @@
+CREATE TABLE payments (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL UNIQUE,
+    amount DOUBLE PRECISION NOT NULL CHECK (amount > 0),
+    state VARCHAR NOT NULL,
+    authorization_id VARCHAR UNIQUE,
+    captured_amount DOUBLE PRECISION NOT NULL,
+    failure_reason VARCHAR,
+    version BIGINT NOT NULL
+);
+CREATE TABLE payment_refunds (
+    payment_id UUID NOT NULL REFERENCES payments (id) ON DELETE CASCADE,
+    idempotency_key VARCHAR NOT NULL,
+    amount DOUBLE PRECISION NOT NULL CHECK (amount > 0),
+    position INT NOT NULL,
+    PRIMARY KEY (payment_id, idempotency_key)
+);
@@ -0,0 +1,17 @@
+CREATE TABLE returns (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL,
+    reason VARCHAR NOT NULL,
+    state VARCHAR NOT NULL,
+    rejection_reason VARCHAR,
+    refund_amount DOUBLE PRECISION NOT NULL,
+    version BIGINT NOT NULL
+);
+CREATE INDEX returns_order_id_idx ON returns (order_id);
+CREATE TABLE return_items (
+    return_id UUID NOT NULL REFERENCES returns (id) ON DELETE CASCADE,
+    product_id UUID NOT NULL,
+    quantity INT NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (return_id, product_id)
+);
@@ -0,0 +1,32 @@
+CREATE TABLE promotions (
+    id UUID PRIMARY KEY,
+    code VARCHAR(32) NOT NULL UNIQUE,
+    kind VARCHAR NOT NULL,
+    value DOUBLE PRECISION,
+    product_id UUID,
+    buy_quantity INT,
+    get_quantity INT,
+    min_spend DOUBLE PRECISION,
+    valid_from TIMESTAMPTZ,
+    valid_until TIMESTAMPTZ,
+    max_uses_per_customer BIGINT
+);
+CREATE TABLE promotion_redemptions (
+    promotion_id UUID NOT NULL REFERENCES promotions (id),
+    customer_id UUID NOT NULL,
+    order_id UUID NOT NULL,
+    PRIMARY KEY (promotion_id, order_id)
+);
+CREATE INDEX promotion_redemptions_customer_id_idx ON promotion_redemptions (promotion_id, customer_id);
+CREATE TABLE order_promotions (
+    order_id UUID NOT NULL REFERENCES orders (id) ON DELETE CASCADE,
+    code VARCHAR(32) NOT NULL,
+    kind VARCHAR NOT NULL,
+    value DOUBLE PRECISION,
+    product_id UUID,
+    buy_quantity INT,
+    get_quantity INT,
+    min_spend DOUBLE PRECISION,
+    position INT NOT NULL,
+    PRIMARY KEY (order_id, code)
+);
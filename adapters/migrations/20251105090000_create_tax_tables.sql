@@ -0,0 +1,19 @@
+ALTER TABLE orders ADD COLUMN pricing_mode VARCHAR NOT NULL DEFAULT 'exclusive';
+CREATE TABLE order_tax_rates (
+    order_id UUID NOT NULL REFERENCES orders (id) ON DELETE CASCADE,
+    product_id UUID NOT NULL,
+    category VARCHAR NOT NULL,
+    rate DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (order_id, product_id)
+);
+CREATE TABLE tax_rules (
+    country VARCHAR(2) NOT NULL,
+    region VARCHAR NOT NULL DEFAULT '',
+    category VARCHAR NOT NULL,
+    rate DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (country, region, category)
+);
+CREATE TABLE product_tax_categories (
+    product_id UUID PRIMARY KEY,
+    category VARCHAR NOT NULL
+);
@@ -0,0 +1,36 @@
+CREATE TABLE shipping_methods (
+    id UUID PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    carrier VARCHAR NOT NULL,
+    kind VARCHAR NOT NULL,
+    amount DOUBLE PRECISION NOT NULL,
+    per_kg DOUBLE PRECISION,
+    threshold DOUBLE PRECISION
+);
+-- Orders keep the method they are shipped with, priced once in their total.
+ALTER TABLE orders
+    ADD COLUMN shipping_method_id UUID,
+    ADD COLUMN shipping_carrier VARCHAR,
+    ADD COLUMN shipping_rate_kind VARCHAR,
+    ADD COLUMN shipping_amount DOUBLE PRECISION,
+    ADD COLUMN shipping_per_kg DOUBLE PRECISION,
+    ADD COLUMN shipping_threshold DOUBLE PRECISION,
+    ADD COLUMN shipping_weight DOUBLE PRECISION;
+CREATE TABLE shipments (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL,
+    shipping_method_id UUID NOT NULL REFERENCES shipping_methods (id),
+    carrier VARCHAR NOT NULL,
+    weight DOUBLE PRECISION NOT NULL,
+    tracking_number VARCHAR,
+    state VARCHAR NOT NULL,
+    version BIGINT NOT NULL
+);
+CREATE INDEX shipments_order_id_idx ON shipments (order_id);
+CREATE TABLE shipment_items (
+    shipment_id UUID NOT NULL REFERENCES shipments (id) ON DELETE CASCADE,
+    product_id UUID NOT NULL,
+    quantity INT NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (shipment_id, product_id)
+);
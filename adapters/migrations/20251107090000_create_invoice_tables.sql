@@ -0,0 +1,40 @@
+-- Last number given to invoices of a kind in a fiscal year. Numbers are taken
+-- by updating the row in the transaction inserting the invoice, which holds
+-- its lock until commit: concurrent invoices wait and a rollback frees the
+-- number, so there are no gaps.
+CREATE TABLE invoice_sequences (
+    kind VARCHAR NOT NULL,
+    fiscal_year INT NOT NULL,
+    last_number BIGINT NOT NULL,
+    PRIMARY KEY (kind, fiscal_year)
+);
+CREATE TABLE invoices (
+    id UUID PRIMARY KEY,
+    order_id UUID NOT NULL,
+    customer_id UUID NOT NULL,
+    kind VARCHAR NOT NULL,
+    fiscal_year INT NOT NULL,
+    sequence BIGINT NOT NULL,
+    billing_address VARCHAR,
+    credited_invoice_number VARCHAR,
+    refund_key VARCHAR,
+    subtotal DOUBLE PRECISION NOT NULL,
+    discount_total DOUBLE PRECISION NOT NULL,
+    tax_total DOUBLE PRECISION NOT NULL,
+    total DOUBLE PRECISION NOT NULL,
+    issued_at TIMESTAMPTZ NOT NULL,
+    UNIQUE (kind, fiscal_year, sequence)
+);
+CREATE UNIQUE INDEX invoices_order_id_idx ON invoices (order_id) WHERE kind = 'invoice';
+CREATE UNIQUE INDEX invoices_refund_key_idx ON invoices (order_id, refund_key) WHERE kind = 'credit_note';
+CREATE TABLE invoice_lines (
+    invoice_id UUID NOT NULL REFERENCES invoices (id) ON DELETE CASCADE,
+    position INT NOT NULL,
+    product_id UUID,
+    quantity INT NOT NULL,
+    unit_price DOUBLE PRECISION NOT NULL,
+    amount DOUBLE PRECISION NOT NULL,
+    tax_rate DOUBLE PRECISION,
+    tax_amount DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (invoice_id, position)
+);
@@ -0,0 +1,20 @@
+ALTER TABLE customers ADD COLUMN currency VARCHAR(3) NOT NULL DEFAULT 'EUR';
+ALTER TABLE orders ADD COLUMN currency VARCHAR(3) NOT NULL DEFAULT 'EUR';
+ALTER TABLE orders ADD COLUMN exchange_rate DOUBLE PRECISION NOT NULL DEFAULT 1;
+CREATE TABLE exchange_rates (
+    currency VARCHAR(3) NOT NULL,
+    effective_from DATE NOT NULL,
+    rate DOUBLE PRECISION NOT NULL CHECK (rate > 0),
+    PRIMARY KEY (currency, effective_from)
+);
+-- Base currency (EUR) prices are kept on the products themselves.
+CREATE TABLE product_prices (
+    product_id UUID NOT NULL,
+    currency VARCHAR(3) NOT NULL CHECK (currency <> 'EUR'),
+    amount DOUBLE PRECISION NOT NULL CHECK (amount >= 0),
+    PRIMARY KEY (product_id, currency)
+);
+ALTER TABLE order_totals ADD COLUMN currency VARCHAR(3) NOT NULL DEFAULT 'EUR';
+ALTER TABLE order_totals ADD COLUMN exchange_rate DOUBLE PRECISION NOT NULL DEFAULT 1;
+ALTER TABLE order_totals
+    ADD COLUMN base_total DOUBLE PRECISION GENERATED ALWAYS AS (total / exchange_rate) STORED;
@@ -0,0 +1,30 @@
+CREATE TABLE categories (
+    id UUID PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    parent_id UUID REFERENCES categories (id)
+);
+CREATE INDEX categories_parent_id_idx ON categories (parent_id);
+CREATE TABLE products (
+    id UUID PRIMARY KEY,
+    name VARCHAR NOT NULL,
+    category_id UUID REFERENCES categories (id),
+    version BIGINT NOT NULL
+);
+CREATE INDEX products_category_id_idx ON products (category_id);
+CREATE TABLE product_variants (
+    id UUID PRIMARY KEY,
+    product_id UUID NOT NULL REFERENCES products (id) ON DELETE CASCADE,
+    position INT NOT NULL,
+    sku VARCHAR NOT NULL UNIQUE,
+    price DOUBLE PRECISION NOT NULL CHECK (price >= 0)
+);
+CREATE INDEX product_variants_product_id_idx ON product_variants (product_id);
+CREATE TABLE product_attributes (
+    product_id UUID NOT NULL REFERENCES products (id) ON DELETE CASCADE,
+    variant_id UUID REFERENCES product_variants (id) ON DELETE CASCADE,
+    position INT NOT NULL,
+    name VARCHAR NOT NULL,
+    kind VARCHAR NOT NULL,
+    value VARCHAR NOT NULL
+);
+CREATE INDEX product_attributes_product_id_idx ON product_attributes (product_id);
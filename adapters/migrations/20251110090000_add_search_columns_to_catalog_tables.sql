@@ -0,0 +1,8 @@
+ALTER TABLE products ADD COLUMN search_vector TSVECTOR
+    GENERATED ALWAYS AS (setweight(to_tsvector('english', name), 'A')) STORED;
+CREATE INDEX products_search_vector_idx ON products USING GIN (search_vector);
+ALTER TABLE product_attributes ADD COLUMN search_vector TSVECTOR
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', CASE WHEN kind = 'text' THEN value ELSE '' END), 'B')
+    ) STORED;
+CREATE INDEX product_attributes_search_vector_idx ON product_attributes USING GIN (search_vector);
@@ -0,0 +1,13 @@
+CREATE TABLE reviews (
+    id UUID PRIMARY KEY,
+    product_id UUID NOT NULL REFERENCES products (id),
+    customer_id UUID NOT NULL,
+    rating INT NOT NULL CHECK (rating BETWEEN 1 AND 5),
+    text VARCHAR NOT NULL,
+    verified_purchase BOOLEAN NOT NULL,
+    status VARCHAR NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    version BIGINT NOT NULL,
+    UNIQUE (product_id, customer_id)
+);
+CREATE INDEX reviews_product_id_status_idx ON reviews (product_id, status);
@@ -1,17 +1,29 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use diesel::{
     pg::PgConnection,
     r2d2::{ConnectionManager, Pool},
-    Insertable, QueryDsl, Queryable, RunQueryDsl, Selectable, SelectableHelper,
+    upsert::excluded,
+    Connection, ExpressionMethods, Insertable, OptionalExtension, QueryDsl, Queryable, RunQueryDsl,
+    Selectable, SelectableHelper,
 };
-use domain::repositories::{
-    customer_repository::CustomerRepositoryError,
-    transactional_repository::TransactionalRepositoryError,
+use domain::{
+    entities::address_book::AddressBook,
+    repositories::{
+        customer_repository::CustomerRepositoryError,
+        transactional_repository::TransactionalRepositoryError,
+    },
+    value_objects::{
+        AddressId, CountryCode, Currency, Email, InvalidValueError, PersonName, PhoneNumber,
+        PostalCode,
+    },
 };
 use uuid::Uuid;
 
 use crate::schema;
 
+diesel::define_sql_function!(fn lower(x: diesel::sql_types::Text) -> diesel::sql_types::Text);
+
 #[derive(Queryable, Selectable, Insertable, Debug)]
 #[diesel(table_name = crate::schema::customers)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
@@ -19,8 +31,13 @@ struct Customer {
     id: Uuid,
     first_name: String,
     last_name: String,
+    email: String,
+    phone: Option<String>,
     #[diesel(embed)]
     address: Address,
+    currency: String,
+    deactivated_at: Option<DateTime<Utc>>,
+    version: i64,
 }
 
 #[derive(Queryable, Selectable, Insertable, Debug)]
@@ -30,32 +47,57 @@ struct Address {
     street: String,
     city: String,
     zip_code: String,
-    state: String,
+    country: String,
+}
+
+#[derive(Queryable, Selectable, Insertable, Debug)]
+#[diesel(table_name = crate::schema::customer_addresses)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+struct CustomerAddress {
+    id: Uuid,
+    customer_id: Uuid,
+    street: String,
+    city: String,
+    zip_code: String,
+    country: String,
+    is_default_shipping: bool,
+    is_default_billing: bool,
 }
 
 pub struct PgCustomerRepository {
     pub connection_pool: Pool<ConnectionManager<PgConnection>>,
 }
 
-impl From<Customer> for domain::entities::customer::Customer {
-    fn from(value: Customer) -> Self {
-        domain::entities::customer::Customer {
+impl TryFrom<Customer> for domain::entities::customer::Customer {
+    type Error = InvalidValueError;
+
+    fn try_from(value: Customer) -> Result<Self, Self::Error> {
+        Ok(domain::entities::customer::Customer {
             id: domain::value_objects::CustomerId(value.id),
-            first_name: value.first_name.clone(),
-            last_name: value.last_name.clone(),
-            address: value.address.into(),
-        }
+            first_name: PersonName::parse(&value.first_name)?,
+            last_name: PersonName::parse(&value.last_name)?,
+            email: Email::parse(&value.email)?,
+            phone: value.phone.as_deref().map(PhoneNumber::parse).transpose()?,
+            address: value.address.try_into()?,
+            address_book: AddressBook::new(),
+            currency: Currency::parse(&value.currency)?,
+            deactivated_at: value.deactivated_at,
+            version: value.version,
+        })
     }
 }
 
-impl From<Address> for domain::value_objects::Address {
-    fn from(value: Address) -> Self {
-        domain::value_objects::Address {
+impl TryFrom<Address> for domain::value_objects::Address {
+    type Error = InvalidValueError;
+
+    fn try_from(value: Address) -> Result<Self, Self::Error> {
+        let country = CountryCode::parse(&value.country)?;
+        Ok(domain::value_objects::Address {
             street: value.street,
             city: value.city,
-            zip_code: value.zip_code,
-            state: value.state,
-        }
+            zip_code: PostalCode::parse(&value.zip_code, &country)?,
+            country,
+        })
     }
 }
 
@@ -88,7 +130,36 @@ impl domain::repositories::customer_repository::CustomerRepository for PgCustome
             .first(&mut connection)
             .map_err(|_| CustomerRepositoryError::CustomerNotFoundError)?;
 
-        Ok(Some(customer.into()))
+        let mut customer: domain::entities::customer::Customer =
+            customer.try_into().map_err(|e: InvalidValueError| {
+                CustomerRepositoryError::CustomerNotReadError(e.to_string())
+            })?;
+        customer.address_book = find_address_book(&mut connection, customer.id.0)?;
+        Ok(Some(customer))
+    }
+
+    async fn find_by_email(
+        &self,
+        email: &domain::value_objects::Email,
+    ) -> Result<Option<domain::entities::customer::Customer>, CustomerRepositoryError> {
+        let mut connection = self.create_connection()?;
+
+        let customer = schema::customers::dsl::customers
+            .filter(lower(schema::customers::dsl::email).eq(email.as_str()))
+            .select(Customer::as_select())
+            .first(&mut connection)
+            .optional()
+            .map_err(|e| CustomerRepositoryError::CustomerNotReadError(e.to_string()))?;
+
+        let Some(customer) = customer else {
+            return Ok(None);
+        };
+        let mut customer: domain::entities::customer::Customer =
+            customer.try_into().map_err(|e: InvalidValueError| {
+                CustomerRepositoryError::CustomerNotReadError(e.to_string())
+            })?;
+        customer.address_book = find_address_book(&mut connection, customer.id.0)?;
+        Ok(Some(customer))
     }
 
     async fn save(
@@ -97,6 +168,173 @@ impl domain::repositories::customer_repository::CustomerRepository for PgCustome
     ) -> Result<domain::entities::customer::Customer, CustomerRepositoryError> {
         todo!();
     }
+
+    async fn update(
+        &self,
+        customer: domain::entities::customer::Customer,
+    ) -> Result<domain::entities::customer::Customer, CustomerRepositoryError> {
+        let mut connection = self.create_connection()?;
+
+        let updated = connection
+            .transaction::<_, diesel::result::Error, _>(|connection| {
+                let updated_rows = diesel::update(
+                    schema::customers::dsl::customers
+                        .find(customer.id.0)
+                        .filter(schema::customers::dsl::version.eq(customer.version)),
+                )
+                .set((
+                    schema::customers::dsl::first_name.eq(customer.first_name.as_str()),
+                    schema::customers::dsl::last_name.eq(customer.last_name.as_str()),
+                    schema::customers::dsl::email.eq(customer.email.as_str()),
+                    schema::customers::dsl::phone
+                        .eq(customer.phone.as_ref().map(PhoneNumber::as_str)),
+                    schema::customers::dsl::street.eq(&customer.address.street),
+                    schema::customers::dsl::city.eq(&customer.address.city),
+                    schema::customers::dsl::zip_code.eq(customer.address.zip_code.as_str()),
+                    schema::customers::dsl::country.eq(customer.address.country.as_str()),
+                    schema::customers::dsl::version.eq(customer.version + 1),
+                ))
+                .execute(connection)?;
+                if updated_rows == 0 {
+                    return Ok(false);
+                }
+                save_address_book(connection, &customer)?;
+                Ok(true)
+            })
+            .map_err(|_| CustomerRepositoryError::CustomerNotSavedError)?;
+        if !updated {
+            if self.find_by_id(customer.id.clone()).await?.is_none() {
+                return Err(CustomerRepositoryError::CustomerNotFoundError);
+            }
+            return Err(CustomerRepositoryError::ConcurrencyConflict);
+        }
+
+        Ok(domain::entities::customer::Customer {
+            version: customer.version + 1,
+            ..customer
+        })
+    }
+
+    async fn delete(
+        &self,
+        customer: domain::entities::customer::Customer,
+    ) -> Result<domain::entities::customer::Customer, CustomerRepositoryError> {
+        let mut connection = self.create_connection()?;
+
+        let updated_rows = diesel::update(
+            schema::customers::dsl::customers
+                .find(customer.id.0)
+                .filter(schema::customers::dsl::version.eq(customer.version)),
+        )
+        .set((
+            schema::customers::dsl::deactivated_at.eq(customer.deactivated_at),
+            schema::customers::dsl::version.eq(customer.version + 1),
+        ))
+        .execute(&mut connection)
+        .map_err(|_| CustomerRepositoryError::CustomerNotSavedError)?;
+        if updated_rows == 0 {
+            if self.find_by_id(customer.id.clone()).await?.is_none() {
+                return Err(CustomerRepositoryError::CustomerNotFoundError);
+            }
+            return Err(CustomerRepositoryError::ConcurrencyConflict);
+        }
+
+        Ok(domain::entities::customer::Customer {
+            version: customer.version + 1,
+            ..customer
+        })
+    }
+}
+
+fn find_address_book(
+    connection: &mut PgConnection,
+    customer_id: Uuid,
+) -> Result<AddressBook, CustomerRepositoryError> {
+    use schema::customer_addresses::dsl;
+
+    let rows = dsl::customer_addresses
+        .filter(dsl::customer_id.eq(customer_id))
+        .order(dsl::id)
+        .select(CustomerAddress::as_select())
+        .load(connection)
+        .map_err(|e| CustomerRepositoryError::CustomerNotReadError(e.to_string()))?;
+
+    let default_shipping = rows
+        .iter()
+        .find(|row| row.is_default_shipping)
+        .map(|row| AddressId(row.id));
+    let default_billing = rows
+        .iter()
+        .find(|row| row.is_default_billing)
+        .map(|row| AddressId(row.id));
+    let addresses = rows
+        .into_iter()
+        .map(|row| {
+            Ok(domain::entities::address_book::CustomerAddress {
+                id: AddressId(row.id),
+                address: Address {
+                    street: row.street,
+                    city: row.city,
+                    zip_code: row.zip_code,
+                    country: row.country,
+                }
+                .try_into()?,
+            })
+        })
+        .collect::<Result<Vec<_>, InvalidValueError>>()
+        .map_err(|e| CustomerRepositoryError::CustomerNotReadError(e.to_string()))?;
+    AddressBook::restore(addresses, default_shipping, default_billing)
+        .map_err(|e| CustomerRepositoryError::CustomerNotReadError(e.to_string()))
+}
+
+/// Replaces the stored address book with the customer's one.
+fn save_address_book(
+    connection: &mut PgConnection,
+    customer: &domain::entities::customer::Customer,
+) -> Result<(), diesel::result::Error> {
+    use schema::customer_addresses::dsl;
+
+    let address_book = &customer.address_book;
+    let address_ids: Vec<Uuid> = address_book
+        .as_slice()
+        .iter()
+        .map(|customer_address| customer_address.id.0)
+        .collect();
+    diesel::delete(
+        dsl::customer_addresses
+            .filter(dsl::customer_id.eq(customer.id.0))
+            .filter(dsl::id.ne_all(&address_ids)),
+    )
+    .execute(connection)?;
+    // Defaults are cleared first, so that moving one never matches two rows.
+    diesel::update(dsl::customer_addresses.filter(dsl::customer_id.eq(customer.id.0)))
+        .set((
+            dsl::is_default_shipping.eq(false),
+            dsl::is_default_billing.eq(false),
+        ))
+        .execute(connection)?;
+
+    for customer_address in address_book.as_slice() {
+        diesel::insert_into(schema::customer_addresses::table)
+            .values(&CustomerAddress {
+                id: customer_address.id.0,
+                customer_id: customer.id.0,
+                street: customer_address.address.street.clone(),
+                city: customer_address.address.city.clone(),
+                zip_code: customer_address.address.zip_code.to_string(),
+                country: customer_address.address.country.to_string(),
+                is_default_shipping: address_book.default_shipping() == Some(&customer_address.id),
+                is_default_billing: address_book.default_billing() == Some(&customer_address.id),
+            })
+            .on_conflict(dsl::id)
+            .do_update()
+            .set((
+                dsl::is_default_shipping.eq(excluded(dsl::is_default_shipping)),
+                dsl::is_default_billing.eq(excluded(dsl::is_default_billing)),
+            ))
+            .execute(connection)?;
+    }
+    Ok(())
 }
 
 impl PgCustomerRepository {
@@ -144,12 +382,12 @@ mod test {
             .unwrap();
 
         assert_eq!(CustomerId(customer_id), customer.id);
-        assert_eq!("John", customer.first_name);
-        assert_eq!("Appleseed", customer.last_name);
+        assert_eq!("John", customer.first_name.as_str());
+        assert_eq!("Appleseed", customer.last_name.as_str());
         assert_eq!("22 Elm Street".to_string(), customer.address.street);
         assert_eq!("Castle Rock".to_string(), customer.address.city);
-        assert_eq!("666".to_string(), customer.address.zip_code);
-        assert_eq!("US".to_string(), customer.address.state);
+        assert_eq!("04401", customer.address.zip_code.as_str());
+        assert_eq!("US", customer.address.country.as_str());
     }
 
     fn save_a_customer_on_db(connection_pool: &Pool<ConnectionManager<PgConnection>>) -> Uuid {
@@ -159,12 +397,17 @@ mod test {
                 id: customer_id,
                 first_name: "John".to_string(),
                 last_name: "Appleseed".to_string(),
+                email: format!("{}@example.com", customer_id),
+                phone: None,
                 address: Address {
                     street: "22 Elm Street".to_string(),
                     city: "Castle Rock".to_string(),
-                    zip_code: "666".to_string(),
-                    state: "US".to_string(),
+                    zip_code: "04401".to_string(),
+                    country: "US".to_string(),
                 },
+                currency: "EUR".to_string(),
+                deactivated_at: None,
+                version: 1,
             })
             .execute(&mut connection_pool.get().unwrap())
             .expect("Error saving customer on DB");
@@ -1,15 +1,27 @@
 use async_trait::async_trait;
 use diesel::{
     r2d2::{ConnectionManager, Pool},
-    ExpressionMethods, Insertable, PgConnection, QueryDsl, Queryable, RunQueryDsl, Selectable,
-    SelectableHelper,
+    Connection, ExpressionMethods, Insertable, PgConnection, QueryDsl, Queryable, RunQueryDsl,
+    Selectable, SelectableHelper,
 };
 use domain::{
+    entities::{
+        currency::OrderCurrency,
+        order::{OrderAddresses, OrderEvent},
+        order_lines::OrderLines,
+        outbox::PromotionPayload,
+        promotion::AppliedPromotion,
+        shipping_method::{OrderShipping, ShippingRate},
+        tax::{TaxRate, Taxes},
+    },
     repositories::{
         order_repository::OrderRepositoryError,
         transactional_repository::TransactionalRepositoryError,
     },
-    value_objects::OrderId,
+    value_objects::{
+        Address, CountryCode, Currency, CustomerId, InvalidValueError, OrderId, PostalCode,
+        ProductId, ShippingMethodId,
+    },
 };
 use uuid::Uuid;
 
@@ -21,6 +33,26 @@ use crate::schema;
 struct Order {
     pub id: Uuid,
     pub customer_id: Uuid,
+    pub version: i64,
+    pub status: String,
+    pub shipping_street: Option<String>,
+    pub shipping_city: Option<String>,
+    pub shipping_zip_code: Option<String>,
+    pub shipping_country: Option<String>,
+    pub billing_street: Option<String>,
+    pub billing_city: Option<String>,
+    pub billing_zip_code: Option<String>,
+    pub billing_country: Option<String>,
+    pub pricing_mode: String,
+    pub currency: String,
+    pub exchange_rate: f64,
+    pub shipping_method_id: Option<Uuid>,
+    pub shipping_carrier: Option<String>,
+    pub shipping_rate_kind: Option<String>,
+    pub shipping_amount: Option<f64>,
+    pub shipping_per_kg: Option<f64>,
+    pub shipping_threshold: Option<f64>,
+    pub shipping_weight: Option<f64>,
 }
 
 #[derive(Queryable, Selectable, Insertable)]
@@ -33,6 +65,31 @@ struct OrderItem {
     pub quantity: i32,
 }
 
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::order_promotions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+struct OrderPromotion {
+    pub order_id: Uuid,
+    pub code: String,
+    pub kind: String,
+    pub value: Option<f64>,
+    pub product_id: Option<Uuid>,
+    pub buy_quantity: Option<i32>,
+    pub get_quantity: Option<i32>,
+    pub min_spend: Option<f64>,
+    pub position: i32,
+}
+
+#[derive(Queryable, Selectable, Insertable)]
+#[diesel(table_name = crate::schema::order_tax_rates)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+struct OrderTaxRate {
+    pub order_id: Uuid,
+    pub product_id: Uuid,
+    pub category: String,
+    pub rate: f64,
+}
+
 pub struct PgOrderRepository {
     pub connection_pool: Pool<ConnectionManager<PgConnection>>,
 }
@@ -57,14 +114,39 @@ impl domain::repositories::order_repository::OrderRepository for PgOrderReposito
         order: domain::entities::order::Order,
     ) -> Result<domain::entities::order::Order, OrderRepositoryError> {
         let mut connection = self.create_connection()?;
+        let shipping = order.addresses().map(|addresses| &addresses.shipping);
+        let billing = order.addresses().map(|addresses| &addresses.billing);
+        let shipping_method = order.shipping();
+        let rate_amounts = shipping_method.map(|shipping| shipping.rate.amounts());
         diesel::insert_into(schema::orders::table)
             .values(&Order {
-                id: order.id.0,
-                customer_id: order.customer_id.0,
+                id: order.id().0,
+                customer_id: order.customer_id().0,
+                version: 1,
+                status: order.status().to_string(),
+                shipping_street: shipping.map(|address| address.street.clone()),
+                shipping_city: shipping.map(|address| address.city.clone()),
+                shipping_zip_code: shipping.map(|address| address.zip_code.to_string()),
+                shipping_country: shipping.map(|address| address.country.to_string()),
+                billing_street: billing.map(|address| address.street.clone()),
+                billing_city: billing.map(|address| address.city.clone()),
+                billing_zip_code: billing.map(|address| address.zip_code.to_string()),
+                billing_country: billing.map(|address| address.country.to_string()),
+                pricing_mode: order.taxes().pricing_mode.to_string(),
+                currency: order.currency().currency.to_string(),
+                exchange_rate: order.currency().exchange_rate,
+                shipping_method_id: shipping_method.map(|shipping| shipping.shipping_method_id.0),
+                shipping_carrier: shipping_method.map(|shipping| shipping.carrier.clone()),
+                shipping_rate_kind: shipping_method
+                    .map(|shipping| shipping.rate.kind().to_string()),
+                shipping_amount: rate_amounts.map(|(amount, _, _)| amount),
+                shipping_per_kg: rate_amounts.and_then(|(_, per_kg, _)| per_kg),
+                shipping_threshold: rate_amounts.and_then(|(_, _, threshold)| threshold),
+                shipping_weight: shipping_method.map(|shipping| shipping.weight),
             })
             .execute(&mut connection)
             .map_err(|_| OrderRepositoryError::OrderNotSavedError)?;
-        Ok(order)
+        Ok(order.with_version(1))
     }
 
     async fn find_by_id(
@@ -81,22 +163,84 @@ impl domain::repositories::order_repository::OrderRepository for PgOrderReposito
             .first(&mut connection)
             .map_err(|_| OrderRepositoryError::OrderNotFoundError)?;
 
-        let mut order: domain::entities::order::Order = order.into();
-
-        if let Ok(order_items) = schema::order_items::dsl::order_items
-            .filter(schema::order_items::dsl::order_id.eq(searched_order_id))
-            .select(OrderItem::as_select())
-            .get_results(&mut connection)
-        {
-            order_items.iter().for_each(|order_item| {
-                order.add(domain::value_objects::OrderItem {
-                    price: order_item.price,
-                    quantity: order_item.quantity,
-                    product_id: domain::value_objects::ProductId(order_item.product_id),
-                })
-            });
+        let order_items: Vec<domain::value_objects::OrderItem> =
+            schema::order_items::dsl::order_items
+                .filter(schema::order_items::dsl::order_id.eq(searched_order_id))
+                .select(OrderItem::as_select())
+                .get_results(&mut connection)
+                .map_err(|_| OrderRepositoryError::OrderItemsNotReadError)?
+                .into_iter()
+                .map(Into::into)
+                .collect();
+        let order_lines = OrderLines::try_from(order_items)
+            .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))?;
+        let addresses = order
+            .addresses()
+            .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))?;
+        let status = order
+            .status
+            .parse()
+            .map_err(OrderRepositoryError::OrderNotReadError)?;
+        let promotions = find_order_promotions(&mut connection, searched_order_id)
+            .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))?
+            .iter()
+            .map(AppliedPromotion::try_from)
+            .collect::<Result<_, _>>()
+            .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))?;
+        let taxes = Taxes {
+            pricing_mode: order
+                .pricing_mode
+                .parse()
+                .map_err(OrderRepositoryError::OrderNotReadError)?,
+            rates: find_order_tax_rates(&mut connection, searched_order_id)
+                .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))?,
         };
-        Ok(Some(order))
+        let currency = OrderCurrency {
+            currency: Currency::parse(&order.currency)
+                .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))?,
+            exchange_rate: order.exchange_rate,
+        };
+        let shipping = order
+            .shipping()
+            .map_err(OrderRepositoryError::OrderNotReadError)?;
+
+        let order = domain::entities::order::Order::restore(
+            domain::value_objects::OrderId(order.id),
+            domain::value_objects::CustomerId(order.customer_id),
+            order_lines,
+            addresses,
+            order.version,
+        )
+        .with_status(status)
+        .with_promotions(promotions)
+        .with_taxes(taxes)
+        .with_currency(currency);
+        Ok(Some(match shipping {
+            Some(shipping) => order.with_shipping(shipping),
+            None => order,
+        }))
+    }
+
+    async fn find_by_customer_id(
+        &self,
+        customer_id: CustomerId,
+    ) -> Result<Vec<domain::entities::order::Order>, OrderRepositoryError> {
+        let mut connection = self.create_connection()?;
+
+        let order_ids: Vec<Uuid> = schema::orders::dsl::orders
+            .filter(schema::orders::dsl::customer_id.eq(customer_id.0))
+            .order(schema::orders::dsl::id)
+            .select(schema::orders::dsl::id)
+            .load(&mut connection)
+            .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))?;
+
+        let mut orders = Vec::with_capacity(order_ids.len());
+        for order_id in order_ids {
+            if let Some(order) = self.find_by_id(OrderId(order_id)).await? {
+                orders.push(order);
+            }
+        }
+        Ok(orders)
     }
 
     async fn update(
@@ -105,21 +249,193 @@ impl domain::repositories::order_repository::OrderRepository for PgOrderReposito
     ) -> Result<domain::entities::order::Order, OrderRepositoryError> {
         let mut connection = self.create_connection()?;
 
-        order.order_items.iter().for_each(|order_item| {
-            let _ = diesel::insert_into(schema::order_items::table)
-                .values(&OrderItem {
-                    order_id: order.id.0,
-                    product_id: order_item.product_id.0,
-                    price: order_item.price,
-                    quantity: order_item.quantity,
-                })
-                .execute(&mut connection);
-        });
+        let updated = connection
+            .transaction::<_, diesel::result::Error, _>(|connection| {
+                let updated_rows = diesel::update(
+                    schema::orders::dsl::orders
+                        .find(order.id().0)
+                        .filter(schema::orders::dsl::version.eq(order.version())),
+                )
+                .set((
+                    schema::orders::dsl::version.eq(order.version() + 1),
+                    schema::orders::dsl::status.eq(order.status().to_string()),
+                ))
+                .execute(connection)?;
+                if updated_rows == 0 {
+                    return Ok(false);
+                }
+
+                let stored_items: Vec<domain::value_objects::OrderItem> =
+                    schema::order_items::dsl::order_items
+                        .filter(schema::order_items::dsl::order_id.eq(order.id().0))
+                        .select(OrderItem::as_select())
+                        .get_results(connection)?
+                        .into_iter()
+                        .map(Into::into)
+                        .collect();
+                for change in order.changes_since(&stored_items) {
+                    apply_change(connection, order.id().0, change)?;
+                }
 
-        Ok(order)
+                let stored_promotions = find_order_promotions(connection, order.id().0)?
+                    .iter()
+                    .map(AppliedPromotion::try_from)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| diesel::result::Error::DeserializationError(Box::new(e)))?;
+                for change in order.promotions_since(&stored_promotions) {
+                    apply_change(connection, order.id().0, change)?;
+                }
+
+                let stored_taxes = Taxes {
+                    pricing_mode: order.taxes().pricing_mode,
+                    rates: find_order_tax_rates(connection, order.id().0)?,
+                };
+                if let Some(change) = order.taxes_change_since(&stored_taxes) {
+                    apply_change(connection, order.id().0, change)?;
+                }
+                Ok(true)
+            })
+            .map_err(|_| OrderRepositoryError::OrderNotSavedError)?;
+        if !updated {
+            if self.find_by_id(order.id().clone()).await?.is_none() {
+                return Err(OrderRepositoryError::OrderNotFoundError);
+            }
+            return Err(OrderRepositoryError::ConcurrencyConflict);
+        }
+
+        let version = order.version() + 1;
+        Ok(order.with_version(version))
     }
 }
 
+/// Promotions in the order they were applied.
+fn find_order_promotions(
+    connection: &mut PgConnection,
+    order_id: Uuid,
+) -> Result<Vec<OrderPromotion>, diesel::result::Error> {
+    use schema::order_promotions::dsl;
+
+    dsl::order_promotions
+        .filter(dsl::order_id.eq(order_id))
+        .order(dsl::position)
+        .select(OrderPromotion::as_select())
+        .get_results(connection)
+}
+
+fn find_order_tax_rates(
+    connection: &mut PgConnection,
+    order_id: Uuid,
+) -> Result<Vec<TaxRate>, diesel::result::Error> {
+    use schema::order_tax_rates::dsl;
+
+    dsl::order_tax_rates
+        .filter(dsl::order_id.eq(order_id))
+        .order(dsl::product_id)
+        .select(OrderTaxRate::as_select())
+        .get_results(connection)?
+        .into_iter()
+        .map(|rate| {
+            Ok(TaxRate {
+                product_id: ProductId(rate.product_id),
+                category: rate
+                    .category
+                    .parse()
+                    .map_err(|e: String| diesel::result::Error::DeserializationError(e.into()))?,
+                rate: rate.rate,
+            })
+        })
+        .collect()
+}
+
+fn apply_change(
+    connection: &mut PgConnection,
+    order_id: Uuid,
+    change: OrderEvent,
+) -> Result<(), diesel::result::Error> {
+    use schema::order_items::dsl;
+
+    match change {
+        OrderEvent::ProductAddedToOrder {
+            product_id,
+            price,
+            quantity,
+        } => diesel::insert_into(schema::order_items::table)
+            .values(&OrderItem {
+                order_id,
+                product_id: product_id.0,
+                price,
+                quantity,
+            })
+            .execute(connection)?,
+        OrderEvent::ProductRemovedFromOrder { product_id } => diesel::delete(
+            dsl::order_items
+                .filter(dsl::order_id.eq(order_id))
+                .filter(dsl::product_id.eq(product_id.0)),
+        )
+        .execute(connection)?,
+        OrderEvent::ProductQuantityChanged {
+            product_id,
+            quantity,
+        } => diesel::update(
+            dsl::order_items
+                .filter(dsl::order_id.eq(order_id))
+                .filter(dsl::product_id.eq(product_id.0)),
+        )
+        .set(dsl::quantity.eq(quantity))
+        .execute(connection)?,
+        OrderEvent::PromotionApplied { promotion } => {
+            use schema::order_promotions::dsl as promotions;
+
+            let position: i64 = promotions::order_promotions
+                .filter(promotions::order_id.eq(order_id))
+                .count()
+                .get_result(connection)?;
+            let payload = PromotionPayload::from(&promotion);
+            diesel::insert_into(schema::order_promotions::table)
+                .values(&OrderPromotion {
+                    order_id,
+                    code: payload.code,
+                    kind: payload.kind,
+                    value: payload.value,
+                    product_id: promotion
+                        .discount
+                        .product_id()
+                        .map(|product_id| product_id.0),
+                    buy_quantity: payload.buy,
+                    get_quantity: payload.get,
+                    min_spend: payload.min_spend,
+                    position: position as i32,
+                })
+                .execute(connection)?
+        }
+        OrderEvent::TaxesChanged { taxes } => {
+            use schema::order_tax_rates::dsl as rates;
+
+            diesel::update(schema::orders::dsl::orders.find(order_id))
+                .set(schema::orders::dsl::pricing_mode.eq(taxes.pricing_mode.to_string()))
+                .execute(connection)?;
+            diesel::delete(rates::order_tax_rates.filter(rates::order_id.eq(order_id)))
+                .execute(connection)?;
+            diesel::insert_into(schema::order_tax_rates::table)
+                .values(
+                    taxes
+                        .rates
+                        .iter()
+                        .map(|rate| OrderTaxRate {
+                            order_id,
+                            product_id: rate.product_id.0,
+                            category: rate.category.to_string(),
+                            rate: rate.rate,
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .execute(connection)?
+        }
+        OrderEvent::OrderCreated { .. } | OrderEvent::OrderStatusChanged { .. } => 0,
+    };
+    Ok(())
+}
+
 impl PgOrderRepository {
     fn create_connection(
         &self,
@@ -133,12 +449,96 @@ impl PgOrderRepository {
     }
 }
 
-impl From<Order> for domain::entities::order::Order {
-    fn from(value: Order) -> Self {
-        domain::entities::order::Order {
-            id: domain::value_objects::OrderId(value.id),
-            customer_id: domain::value_objects::CustomerId(value.customer_id),
-            order_items: vec![],
+impl Order {
+    fn addresses(&self) -> Result<Option<OrderAddresses>, InvalidValueError> {
+        let shipping = address(
+            &self.shipping_street,
+            &self.shipping_city,
+            &self.shipping_zip_code,
+            &self.shipping_country,
+        )?;
+        let billing = address(
+            &self.billing_street,
+            &self.billing_city,
+            &self.billing_zip_code,
+            &self.billing_country,
+        )?;
+        Ok(shipping
+            .zip(billing)
+            .map(|(shipping, billing)| OrderAddresses { shipping, billing }))
+    }
+
+    fn shipping(&self) -> Result<Option<OrderShipping>, String> {
+        let (Some(shipping_method_id), Some(carrier), Some(kind)) = (
+            self.shipping_method_id,
+            &self.shipping_carrier,
+            &self.shipping_rate_kind,
+        ) else {
+            return Ok(None);
+        };
+        Ok(Some(OrderShipping {
+            shipping_method_id: ShippingMethodId(shipping_method_id),
+            carrier: carrier.clone(),
+            rate: ShippingRate::parse(
+                kind,
+                self.shipping_amount,
+                self.shipping_per_kg,
+                self.shipping_threshold,
+            )
+            .map_err(|e| e.to_string())?,
+            weight: self.shipping_weight.unwrap_or_default(),
+        }))
+    }
+}
+
+fn address(
+    street: &Option<String>,
+    city: &Option<String>,
+    zip_code: &Option<String>,
+    country: &Option<String>,
+) -> Result<Option<Address>, InvalidValueError> {
+    let (Some(street), Some(city), Some(zip_code), Some(country)) =
+        (street, city, zip_code, country)
+    else {
+        return Ok(None);
+    };
+    let country = CountryCode::parse(country)?;
+    Ok(Some(Address {
+        street: street.clone(),
+        city: city.clone(),
+        zip_code: PostalCode::parse(zip_code, &country)?,
+        country,
+    }))
+}
+
+impl From<&OrderPromotion> for PromotionPayload {
+    fn from(value: &OrderPromotion) -> Self {
+        PromotionPayload {
+            code: value.code.clone(),
+            kind: value.kind.clone(),
+            value: value.value,
+            product_id: value.product_id.map(|product_id| product_id.to_string()),
+            buy: value.buy_quantity,
+            get: value.get_quantity,
+            min_spend: value.min_spend,
+        }
+    }
+}
+
+impl TryFrom<&OrderPromotion> for AppliedPromotion {
+    type Error = domain::entities::promotion::PromotionError;
+
+    fn try_from(value: &OrderPromotion) -> Result<Self, Self::Error> {
+        AppliedPromotion::try_from(&PromotionPayload::from(value))
+    }
+}
+
+impl From<OrderItem> for domain::value_objects::OrderItem {
+    fn from(value: OrderItem) -> Self {
+        domain::value_objects::OrderItem {
+            price: value.price,
+            quantity: value.quantity,
+            product_id: domain::value_objects::ProductId(value.product_id),
         }
     }
 }
@@ -171,7 +571,10 @@ mod test {
             .await
             .unwrap()
             .unwrap();
-        assert_eq!(domain::value_objects::OrderId(order_id), order_from_db.id);
+        assert_eq!(
+            &domain::value_objects::OrderId(order_id),
+            order_from_db.id()
+        );
     }
 
     #[tokio::test]
@@ -182,22 +585,21 @@ mod test {
         let repository = PgOrderRepository {
             connection_pool: common::test::create_diesel_connection_pool(),
         };
-        let _ = repository
+        let mut order = repository
             .save(domain::entities::order::Order::create(
                 domain::value_objects::OrderId(order_id),
                 domain::value_objects::CustomerId(customer_id),
             ))
-            .await;
+            .await
+            .unwrap();
 
-        let mut order = domain::entities::order::Order::create(
-            domain::value_objects::OrderId(order_id),
-            domain::value_objects::CustomerId(customer_id),
-        );
-        order.add_multiple(vec![domain::value_objects::OrderItem {
-            price: 10.0,
-            quantity: 1,
-            product_id: domain::value_objects::ProductId(product_id),
-        }]);
+        order
+            .add_multiple(vec![domain::value_objects::OrderItem {
+                price: 10.0,
+                quantity: 1,
+                product_id: domain::value_objects::ProductId(product_id),
+            }])
+            .unwrap();
         let result = repository.update(order).await;
 
         assert!(result.is_ok());
@@ -206,6 +608,6 @@ mod test {
             .await
             .unwrap()
             .unwrap();
-        assert_eq!(1, order_from_db.order_items.len());
+        assert_eq!(1, order_from_db.order_items().len());
     }
 }
@@ -0,0 +1,284 @@
+use async_trait::async_trait;
+use domain::{
+    gateways::payment_gateway::{PaymentGateway, PaymentGatewayError},
+    value_objects::{AuthorizationId, OrderId},
+};
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+
+/// Header the provider uses to make a request once however many times it is sent.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Payment provider reached over HTTP, e.g. a local stub of the real one.
+pub struct HttpPaymentGateway {
+    base_url: String,
+    client: Client,
+}
+
+impl HttpPaymentGateway {
+    pub fn new(base_url: String) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client: Client::new(),
+        }
+    }
+
+    /// `authorization_id` is the one the request is about, if any: it is
+    /// reported as not found when the provider answers 404.
+    async fn post(
+        &self,
+        path: &str,
+        authorization_id: Option<&AuthorizationId>,
+        idempotency_key: &str,
+        body: &impl Serialize,
+    ) -> Result<String, PaymentGatewayError> {
+        let body = serde_json::to_string(body)
+            .map_err(|e| PaymentGatewayError::PaymentProviderUnavailableError(e.to_string()))?;
+        let response = self
+            .client
+            .post(format!("{}{}", self.base_url, path))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(IDEMPOTENCY_KEY_HEADER, idempotency_key)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| PaymentGatewayError::PaymentProviderUnavailableError(e.to_string()))?;
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| PaymentGatewayError::PaymentProviderUnavailableError(e.to_string()))?;
+        if status.is_success() {
+            return Ok(body);
+        }
+
+        let message = serde_json::from_str::<ErrorResponse>(&body)
+            .map(|error| error.message)
+            .unwrap_or(body);
+        Err(match (status, authorization_id) {
+            (StatusCode::PAYMENT_REQUIRED, _) => PaymentGatewayError::PaymentDeclinedError(message),
+            (StatusCode::NOT_FOUND, Some(authorization_id)) => {
+                PaymentGatewayError::AuthorizationNotFoundError(authorization_id.clone())
+            }
+            (StatusCode::CONFLICT | StatusCode::UNPROCESSABLE_ENTITY, _) => {
+                PaymentGatewayError::PaymentRejectedError(message)
+            }
+            _ => PaymentGatewayError::PaymentProviderUnavailableError(format!(
+                "{}: {}",
+                status, message
+            )),
+        })
+    }
+
+    async fn post_to_authorization(
+        &self,
+        authorization_id: &AuthorizationId,
+        operation: &str,
+        idempotency_key: &str,
+        body: &impl Serialize,
+    ) -> Result<(), PaymentGatewayError> {
+        let path = format!("/authorizations/{}/{}", authorization_id.0, operation);
+        self.post(&path, Some(authorization_id), idempotency_key, body)
+            .await
+            .map(|_| ())
+    }
+}
+
+#[derive(Serialize)]
+struct AuthorizationRequest {
+    order_id: String,
+    amount: f64,
+}
+
+#[derive(Deserialize)]
+struct AuthorizationResponse {
+    id: String,
+}
+
+#[derive(Serialize)]
+struct AmountRequest {
+    amount: f64,
+}
+
+#[derive(Serialize)]
+struct EmptyRequest {}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    message: String,
+}
+
+/// The order id is the idempotency key of authorizations, as an order is
+/// paid once. Captures and voids are keyed by authorization.
+#[async_trait]
+impl PaymentGateway for HttpPaymentGateway {
+    async fn authorize(
+        &mut self,
+        order_id: &OrderId,
+        amount: f64,
+    ) -> Result<AuthorizationId, PaymentGatewayError> {
+        let order_id = order_id.0.to_string();
+        let request = AuthorizationRequest {
+            order_id: order_id.clone(),
+            amount,
+        };
+        let body = self
+            .post("/authorizations", None, &order_id, &request)
+            .await?;
+        serde_json::from_str::<AuthorizationResponse>(&body)
+            .map(|response| AuthorizationId(response.id))
+            .map_err(|e| PaymentGatewayError::PaymentProviderUnavailableError(e.to_string()))
+    }
+
+    async fn capture(
+        &mut self,
+        authorization_id: &AuthorizationId,
+        amount: f64,
+    ) -> Result<(), PaymentGatewayError> {
+        let idempotency_key = format!("capture_{}", authorization_id.0);
+        self.post_to_authorization(
+            authorization_id,
+            "capture",
+            &idempotency_key,
+            &AmountRequest { amount },
+        )
+        .await
+    }
+
+    async fn void(
+        &mut self,
+        authorization_id: &AuthorizationId,
+    ) -> Result<(), PaymentGatewayError> {
+        let idempotency_key = format!("void_{}", authorization_id.0);
+        self.post_to_authorization(authorization_id, "void", &idempotency_key, &EmptyRequest {})
+            .await
+    }
+
+    async fn refund(
+        &mut self,
+        authorization_id: &AuthorizationId,
+        amount: f64,
+        idempotency_key: &str,
+    ) -> Result<(), PaymentGatewayError> {
+        self.post_to_authorization(
+            authorization_id,
+            "refunds",
+            idempotency_key,
+            &AmountRequest { amount },
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn authorizes_a_payment_keyed_by_order() {
+        let order_id = OrderId(Uuid::new_v4());
+        let (base_url, requests) = stub(201, r#"{"id":"auth_1"}"#).await;
+        let mut gateway = HttpPaymentGateway::new(base_url);
+
+        let authorization_id = gateway.authorize(&order_id, 10.5).await.unwrap();
+
+        assert_eq!(AuthorizationId("auth_1".to_string()), authorization_id);
+        let request = requests.lock().unwrap()[0].to_lowercase();
+        assert!(request.starts_with("post /authorizations "));
+        assert!(request.contains(&format!("idempotency-key: {}", order_id.0)));
+        assert!(request.contains(r#""amount":10.5"#));
+    }
+
+    #[tokio::test]
+    async fn maps_the_provider_responses_to_errors() {
+        let authorization_id = AuthorizationId("auth_1".to_string());
+        let (base_url, _) = stub(402, r#"{"message":"Insufficient funds"}"#).await;
+        assert_eq!(
+            Err(PaymentGatewayError::PaymentDeclinedError(
+                "Insufficient funds".to_string()
+            )),
+            HttpPaymentGateway::new(base_url)
+                .authorize(&OrderId(Uuid::new_v4()), 10.0)
+                .await
+        );
+
+        let (base_url, requests) = stub(404, "").await;
+        assert_eq!(
+            Err(PaymentGatewayError::AuthorizationNotFoundError(
+                authorization_id.clone()
+            )),
+            HttpPaymentGateway::new(base_url)
+                .refund(&authorization_id, 5.0, "refund_1")
+                .await
+        );
+        let request = requests.lock().unwrap()[0].to_lowercase();
+        assert!(request.starts_with("post /authorizations/auth_1/refunds "));
+        assert!(request.contains("idempotency-key: refund_1"));
+
+        let (base_url, _) = stub(503, "").await;
+        assert!(matches!(
+            HttpPaymentGateway::new(base_url)
+                .void(&authorization_id)
+                .await,
+            Err(PaymentGatewayError::PaymentProviderUnavailableError(_))
+        ));
+    }
+
+    /// Answers every request with the status and body given, keeping the
+    /// requests received.
+    async fn stub(status: u16, body: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let received = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let request = read_request(&mut stream).await;
+                received.lock().unwrap().push(request);
+                let response = format!(
+                    "HTTP/1.1 {} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (base_url, requests)
+    }
+
+    async fn read_request(stream: &mut tokio::net::TcpStream) -> String {
+        let mut request = vec![];
+        let mut buffer = [0; 1024];
+        loop {
+            let read = stream.read(&mut buffer).await.unwrap();
+            request.extend_from_slice(&buffer[..read]);
+            let text = String::from_utf8_lossy(&request).to_string();
+            if let Some(end) = text.find("\r\n\r\n") {
+                let content_length = text[..end]
+                    .lines()
+                    .find_map(|line| {
+                        line.to_lowercase()
+                            .strip_prefix("content-length:")
+                            .map(|length| length.trim().parse::<usize>().unwrap())
+                    })
+                    .unwrap_or(0);
+                if request.len() >= end + 4 + content_length {
+                    return text;
+                }
+            }
+            if read == 0 {
+                return text;
+            }
+        }
+    }
+}
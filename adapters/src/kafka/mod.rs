@@ -1,15 +1,22 @@
 use std::time::Duration;
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use domain::{
     entities::outbox::OutboxMessage,
     publishers::outbox_publisher::{OutboxMessagePublisher, OutboxMessagePublisherError},
 };
 use rdkafka::{
+    message::{Header, OwnedHeaders},
     producer::{FutureProducer, FutureRecord},
     ClientConfig,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+/// Header carrying the outbox message id. It never changes between publishing
+/// attempts, so consumers can use it to discard redelivered events.
+pub const EVENT_ID_HEADER: &str = "event_id";
+pub const EVENT_TYPE_HEADER: &str = "event_type";
 
 pub struct KafkaOutboxMessagePublisher {
     bootstrap_servers: String,
@@ -32,16 +39,33 @@ impl KafkaOutboxMessagePublisher {
             event_id: outbox_message.id().to_string(),
             event_type: outbox_message.event_type().to_string(),
             event_payload: outbox_message.event_payload(),
+            created_at: Some(outbox_message.created_at()),
         })
         .map_err(|e| OutboxMessagePublisherError(e.to_string()))
     }
+
+    fn to_kafka_headers(&self, outbox_message: &OutboxMessage) -> OwnedHeaders {
+        OwnedHeaders::new()
+            .insert(Header {
+                key: EVENT_ID_HEADER,
+                value: Some(&outbox_message.id().to_string()),
+            })
+            .insert(Header {
+                key: EVENT_TYPE_HEADER,
+                value: Some(&outbox_message.event_type().to_string()),
+            })
+    }
 }
 
-#[derive(Serialize)]
-struct KafkaMessage {
-    event_id: String,
-    event_type: String,
-    event_payload: String,
+/// Envelope of every record published from the outbox.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct KafkaMessage {
+    pub event_id: String,
+    pub event_type: String,
+    pub event_payload: String,
+    /// When the event was stored in the outbox, missing in older records.
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
 }
 
 #[async_trait]
@@ -62,7 +86,8 @@ impl OutboxMessagePublisher for KafkaOutboxMessagePublisher {
             .send(
                 FutureRecord::to(&self.topic)
                     .payload(&kafka_message)
-                    .key(&outbox_message.id().to_string()),
+                    .key(&outbox_message.id().to_string())
+                    .headers(self.to_kafka_headers(&outbox_message)),
                 Duration::from_secs(0),
             )
             .await
@@ -74,13 +99,16 @@ impl OutboxMessagePublisher for KafkaOutboxMessagePublisher {
 #[cfg(test)]
 mod test {
     use domain::{
-        entities::{customer::Customer, outbox::OutboxMessage},
+        entities::{address_book::AddressBook, customer::Customer, outbox::OutboxMessage},
         publishers::outbox_publisher::OutboxMessagePublisher,
-        value_objects::{Address, CustomerId},
+        value_objects::{
+            Address, CountryCode, Currency, CustomerId, Email, PersonName, PostalCode,
+        },
     };
+    use rdkafka::message::Headers;
     use uuid::Uuid;
 
-    use crate::kafka::KafkaOutboxMessagePublisher;
+    use crate::kafka::{KafkaOutboxMessagePublisher, EVENT_ID_HEADER, EVENT_TYPE_HEADER};
 
     #[tokio::test]
     async fn publishes_on_kafka() {
@@ -89,14 +117,20 @@ mod test {
 
         let message = OutboxMessage::customer_created_event(&Customer {
             id: CustomerId(Uuid::new_v4()),
-            first_name: "Mario".to_string(),
-            last_name: "Rossi".to_string(),
+            first_name: PersonName::parse("Mario").unwrap(),
+            last_name: PersonName::parse("Rossi").unwrap(),
+            email: Email::parse("mario.rossi@example.com").unwrap(),
+            phone: None,
             address: Address {
                 street: "customer street".to_string(),
                 city: "customer city".to_string(),
-                zip_code: "customer zip code".to_string(),
-                state: "customer state".to_string(),
+                zip_code: PostalCode::parse("04401", &CountryCode::parse("US").unwrap()).unwrap(),
+                country: CountryCode::parse("US").unwrap(),
             },
+            address_book: AddressBook::new(),
+            currency: Currency::Eur,
+            deactivated_at: None,
+            version: 0,
         })
         .unwrap();
 
@@ -104,4 +138,37 @@ mod test {
 
         assert!(result.await.is_ok());
     }
+
+    #[test]
+    fn adds_event_id_and_type_headers() {
+        let publisher =
+            KafkaOutboxMessagePublisher::new("localhost:19092".to_string(), "topic".to_string());
+        let message = OutboxMessage::customer_created_event(&Customer {
+            id: CustomerId(Uuid::new_v4()),
+            first_name: PersonName::parse("Mario").unwrap(),
+            last_name: PersonName::parse("Rossi").unwrap(),
+            email: Email::parse("mario.rossi@example.com").unwrap(),
+            phone: None,
+            address: Address {
+                street: "customer street".to_string(),
+                city: "customer city".to_string(),
+                zip_code: PostalCode::parse("04401", &CountryCode::parse("US").unwrap()).unwrap(),
+                country: CountryCode::parse("US").unwrap(),
+            },
+            address_book: AddressBook::new(),
+            currency: Currency::Eur,
+            deactivated_at: None,
+            version: 0,
+        })
+        .unwrap();
+
+        let headers = publisher.to_kafka_headers(&message);
+
+        let event_id = headers.get(0);
+        assert_eq!(EVENT_ID_HEADER, event_id.key);
+        assert_eq!(Some(message.id().to_string().as_bytes()), event_id.value);
+        let event_type = headers.get(1);
+        assert_eq!(EVENT_TYPE_HEADER, event_type.key);
+        assert_eq!(Some("customer_created".as_bytes()), event_type.value);
+    }
 }
@@ -1,5 +1,6 @@
 mod common;
 pub mod diesel;
+pub mod http;
 pub mod kafka;
 mod schema;
 pub mod sqlx;
@@ -1,5 +1,18 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    customer_addresses (id) {
+        id -> Uuid,
+        customer_id -> Uuid,
+        street -> Varchar,
+        city -> Varchar,
+        zip_code -> Varchar,
+        country -> Varchar,
+        is_default_shipping -> Bool,
+        is_default_billing -> Bool,
+    }
+}
+
 diesel::table! {
     customers (id) {
         id -> Uuid,
@@ -8,7 +21,12 @@ diesel::table! {
         street -> Varchar,
         city -> Varchar,
         zip_code -> Varchar,
-        state -> Varchar,
+        country -> Varchar,
+        version -> Int8,
+        email -> Varchar,
+        phone -> Nullable<Varchar>,
+        deactivated_at -> Nullable<Timestamptz>,
+        currency -> Varchar,
     }
 }
 
@@ -21,11 +39,65 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    order_promotions (order_id, code) {
+        order_id -> Uuid,
+        #[max_length = 32]
+        code -> Varchar,
+        kind -> Varchar,
+        value -> Nullable<Float8>,
+        product_id -> Nullable<Uuid>,
+        buy_quantity -> Nullable<Int4>,
+        get_quantity -> Nullable<Int4>,
+        min_spend -> Nullable<Float8>,
+        position -> Int4,
+    }
+}
+
+diesel::table! {
+    order_tax_rates (order_id, product_id) {
+        order_id -> Uuid,
+        product_id -> Uuid,
+        category -> Varchar,
+        rate -> Float8,
+    }
+}
+
 diesel::table! {
     orders (id) {
         id -> Uuid,
         customer_id -> Uuid,
+        version -> Int8,
+        status -> Varchar,
+        shipping_street -> Nullable<Varchar>,
+        shipping_city -> Nullable<Varchar>,
+        shipping_zip_code -> Nullable<Varchar>,
+        shipping_country -> Nullable<Varchar>,
+        billing_street -> Nullable<Varchar>,
+        billing_city -> Nullable<Varchar>,
+        billing_zip_code -> Nullable<Varchar>,
+        billing_country -> Nullable<Varchar>,
+        pricing_mode -> Varchar,
+        currency -> Varchar,
+        exchange_rate -> Float8,
+        shipping_method_id -> Nullable<Uuid>,
+        shipping_carrier -> Nullable<Varchar>,
+        shipping_rate_kind -> Nullable<Varchar>,
+        shipping_amount -> Nullable<Float8>,
+        shipping_per_kg -> Nullable<Float8>,
+        shipping_threshold -> Nullable<Float8>,
+        shipping_weight -> Nullable<Float8>,
     }
 }
 
-diesel::allow_tables_to_appear_in_same_query!(customers, order_items, orders,);
+diesel::joinable!(order_promotions -> orders (order_id));
+diesel::joinable!(order_tax_rates -> orders (order_id));
+
+diesel::allow_tables_to_appear_in_same_query!(
+    customer_addresses,
+    customers,
+    order_items,
+    order_promotions,
+    order_tax_rates,
+    orders,
+);
@@ -1,4 +1,31 @@
+pub mod pg_cart_repository;
+pub mod pg_catalog_repository;
+pub mod pg_checkout_saga_repository;
+pub mod pg_currency_repository;
+pub mod pg_customer_data_repository;
 pub mod pg_customer_repository;
+pub mod pg_event_store;
+pub mod pg_inventory_repository;
+pub mod pg_invoice_repository;
 pub mod pg_order_repository;
 pub mod pg_outbox_message_repository;
+pub mod pg_payment_repository;
+pub mod pg_product_search;
+pub mod pg_promotion_repository;
+pub mod pg_return_repository;
+pub mod pg_review_repository;
+pub mod pg_shipment_repository;
+pub mod pg_shipping_method_repository;
+pub mod pg_tax_calculator;
 pub mod pg_transactional_repository;
+
+use domain::value_objects::InvalidValueError;
+
+/// Stored values are checked like new ones, so a bad row can't become an entity.
+fn invalid(column: impl Into<String>) -> impl Fn(InvalidValueError) -> sqlx::Error {
+    let column = column.into();
+    move |error| sqlx::Error::ColumnDecode {
+        index: column.clone(),
+        source: Box::new(error),
+    }
+}
@@ -0,0 +1,267 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::{
+    entities::{cart::Cart, order_lines::OrderLines},
+    repositories::cart_repository::CartRepositoryError,
+    value_objects::{CartId, CustomerId, OrderItem, ProductId},
+};
+use sqlx::{postgres::PgRow, PgConnection, PgExecutor, Pool, Postgres, Row};
+use uuid::Uuid;
+
+pub struct PgCartRepository {
+    pool: Pool<Postgres>,
+}
+
+impl PgCartRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    async fn find_one(&self, query: &str, id: Uuid) -> Result<Option<Cart>, CartRepositoryError> {
+        let row = sqlx::query(query)
+            .bind(id)
+            .try_map(|row: PgRow| {
+                Ok((
+                    CartId(row.try_get("id")?),
+                    row.try_get::<Option<Uuid>, _>("customer_id")?
+                        .map(CustomerId),
+                    row.try_get::<DateTime<Utc>, _>("expires_at")?,
+                    row.try_get::<Option<DateTime<Utc>>, _>("checked_out_at")?,
+                    row.try_get::<i64, _>("version")?,
+                ))
+            })
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| CartRepositoryError::CartNotReadError(e.to_string()))?;
+        let Some((cart_id, customer_id, expires_at, checked_out_at, version)) = row else {
+            return Ok(None);
+        };
+
+        let cart_items = find_cart_items(&self.pool, cart_id.0).await?;
+        let cart_lines = OrderLines::try_from(cart_items)
+            .map_err(|e| CartRepositoryError::CartNotReadError(e.to_string()))?;
+
+        Ok(Some(Cart::restore(
+            cart_id,
+            customer_id,
+            cart_lines,
+            expires_at,
+            checked_out_at,
+            version,
+        )))
+    }
+}
+
+#[async_trait]
+impl domain::repositories::cart_repository::CartRepository for PgCartRepository {
+    async fn find_by_id(&self, id: CartId) -> Result<Option<Cart>, CartRepositoryError> {
+        self.find_one("SELECT * FROM carts WHERE id = $1", id.0)
+            .await
+    }
+
+    async fn find_by_customer_id(
+        &self,
+        customer_id: CustomerId,
+    ) -> Result<Option<Cart>, CartRepositoryError> {
+        self.find_one("SELECT * FROM carts WHERE customer_id = $1", customer_id.0)
+            .await
+    }
+
+    async fn save(&self, cart: Cart) -> Result<Cart, CartRepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| CartRepositoryError::CartNotSavedError)?;
+        sqlx::query(
+            "INSERT INTO carts (id, customer_id, expires_at, version) VALUES ($1, $2, $3, 1)",
+        )
+        .bind(cart.id().0)
+        .bind(cart.customer_id().map(|customer_id| customer_id.0))
+        .bind(cart.expires_at())
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| CartRepositoryError::CartNotSavedError)?;
+        insert_cart_items(&mut tx, &cart).await?;
+        tx.commit()
+            .await
+            .map_err(|_| CartRepositoryError::CartNotSavedError)?;
+
+        Ok(cart.with_version(1))
+    }
+
+    async fn update(&self, cart: Cart) -> Result<Cart, CartRepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| CartRepositoryError::CartNotSavedError)?;
+        let result = sqlx::query(
+            r#"
+            UPDATE carts
+            SET customer_id = $3, expires_at = $4, checked_out_at = $5, version = version + 1
+            WHERE id = $1 AND version = $2
+            "#,
+        )
+        .bind(cart.id().0)
+        .bind(cart.version())
+        .bind(cart.customer_id().map(|customer_id| customer_id.0))
+        .bind(cart.expires_at())
+        .bind(cart.checked_out_at())
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| CartRepositoryError::CartNotSavedError)?;
+        if result.rows_affected() == 0 {
+            return Err(CartRepositoryError::ConcurrencyConflict);
+        }
+
+        // Carts are small: their items are simply rewritten.
+        sqlx::query("DELETE FROM cart_items WHERE cart_id = $1")
+            .bind(cart.id().0)
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| CartRepositoryError::CartNotSavedError)?;
+        insert_cart_items(&mut tx, &cart).await?;
+        tx.commit()
+            .await
+            .map_err(|_| CartRepositoryError::CartNotSavedError)?;
+
+        let version = cart.version() + 1;
+        Ok(cart.with_version(version))
+    }
+
+    async fn delete(&self, id: CartId) -> Result<(), CartRepositoryError> {
+        sqlx::query("DELETE FROM carts WHERE id = $1")
+            .bind(id.0)
+            .execute(&self.pool)
+            .await
+            .map_err(|_| CartRepositoryError::CartNotDeletedError)?;
+        Ok(())
+    }
+}
+
+async fn find_cart_items<'e>(
+    executor: impl PgExecutor<'e>,
+    cart_id: Uuid,
+) -> Result<Vec<OrderItem>, CartRepositoryError> {
+    sqlx::query("SELECT * FROM cart_items WHERE cart_id = $1 ORDER BY product_id")
+        .bind(cart_id)
+        .try_map(|row: PgRow| {
+            Ok(OrderItem {
+                price: row.try_get("price")?,
+                quantity: row.try_get("quantity")?,
+                product_id: ProductId(row.try_get("product_id")?),
+            })
+        })
+        .fetch_all(executor)
+        .await
+        .map_err(|e| CartRepositoryError::CartNotReadError(e.to_string()))
+}
+
+async fn insert_cart_items(
+    connection: &mut PgConnection,
+    cart: &Cart,
+) -> Result<(), CartRepositoryError> {
+    for item in cart.cart_lines().iter() {
+        sqlx::query(
+            "INSERT INTO cart_items (cart_id, product_id, quantity, price) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(cart.id().0)
+        .bind(item.product_id.0)
+        .bind(item.quantity)
+        .bind(item.price)
+        .execute(&mut *connection)
+        .await
+        .map_err(|_| CartRepositoryError::CartNotSavedError)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::common::test;
+    use domain::repositories::cart_repository::CartRepository;
+
+    #[tokio::test]
+    async fn saves_and_updates_a_cart() {
+        let cart_id = CartId(Uuid::new_v4());
+        let customer_id = CustomerId(Uuid::new_v4());
+        let repository = PgCartRepository::new(test::create_sqlx_connection_pool().await);
+        let mut cart = repository
+            .save(Cart::new(cart_id.clone(), None, Utc::now()))
+            .await
+            .unwrap();
+
+        cart.add(order_item(), Utc::now()).unwrap();
+        cart.assign_to(customer_id.clone(), Utc::now()).unwrap();
+        repository.update(cart).await.unwrap();
+
+        let cart_from_db = repository
+            .find_by_customer_id(customer_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(&cart_id, cart_from_db.id());
+        assert_eq!(1, cart_from_db.cart_lines().len());
+        assert_eq!(2, cart_from_db.version());
+    }
+
+    #[tokio::test]
+    async fn rejects_updates_of_a_stale_cart() {
+        let cart_id = CartId(Uuid::new_v4());
+        let repository = PgCartRepository::new(test::create_sqlx_connection_pool().await);
+        let mut first = repository
+            .save(Cart::new(cart_id.clone(), None, Utc::now()))
+            .await
+            .unwrap();
+        let mut second = repository
+            .find_by_id(cart_id.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        first.add(order_item(), Utc::now()).unwrap();
+        second.add(order_item(), Utc::now()).unwrap();
+
+        repository.update(first).await.unwrap();
+        let result = repository.update(second).await;
+
+        assert!(matches!(
+            result,
+            Err(CartRepositoryError::ConcurrencyConflict)
+        ));
+        repository.delete(cart_id.clone()).await.unwrap();
+        assert!(repository.find_by_id(cart_id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn stores_the_checkout_of_a_cart() {
+        let cart_id = CartId(Uuid::new_v4());
+        let repository = PgCartRepository::new(test::create_sqlx_connection_pool().await);
+        let mut cart = repository
+            .save(Cart::new(
+                cart_id.clone(),
+                Some(CustomerId(Uuid::new_v4())),
+                Utc::now(),
+            ))
+            .await
+            .unwrap();
+        cart.add(order_item(), Utc::now()).unwrap();
+        let mut cart = repository.update(cart).await.unwrap();
+
+        cart.checkout(Utc::now()).unwrap();
+        repository.update(cart).await.unwrap();
+
+        let cart_from_db = repository.find_by_id(cart_id).await.unwrap().unwrap();
+        assert!(cart_from_db.is_checked_out());
+    }
+
+    fn order_item() -> OrderItem {
+        OrderItem {
+            price: 10.0,
+            quantity: 1,
+            product_id: ProductId(Uuid::new_v4()),
+        }
+    }
+}
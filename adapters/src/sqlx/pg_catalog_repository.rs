@@ -0,0 +1,408 @@
+use async_trait::async_trait;
+use domain::{
+    entities::{
+        category::Category,
+        product::{Attribute, AttributeValue, Product, Variant},
+    },
+    repositories::catalog_repository::CatalogRepositoryError,
+    value_objects::{CatalogProductId, CategoryId, ProductId, Sku},
+};
+use sqlx::{postgres::PgRow, Pool, Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use super::invalid;
+
+const UNIQUE_VIOLATION: &str = "23505";
+const SKU_CONSTRAINT: &str = "product_variants_sku_key";
+
+pub struct PgCatalogRepository {
+    pool: Pool<Postgres>,
+}
+
+impl PgCatalogRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    async fn with_details(&self, row: PgRow) -> Result<Product, CatalogRepositoryError> {
+        let not_read = |e: sqlx::Error| CatalogRepositoryError::ProductNotReadError(e.to_string());
+        let product_id: Uuid = row.try_get("id").map_err(not_read)?;
+        let attributes: Vec<(Option<Uuid>, Attribute)> =
+            sqlx::query("SELECT * FROM product_attributes WHERE product_id = $1 ORDER BY position")
+                .bind(product_id)
+                .try_map(|row: PgRow| {
+                    let name: &str = row.try_get("name")?;
+                    let value = AttributeValue::parse(row.try_get("kind")?, row.try_get("value")?)
+                        .and_then(|value| Attribute::new(name, value))
+                        .map_err(|error| sqlx::Error::ColumnDecode {
+                            index: "value".to_string(),
+                            source: Box::new(error),
+                        })?;
+                    Ok((row.try_get("variant_id")?, value))
+                })
+                .fetch_all(&self.pool)
+                .await
+                .map_err(not_read)?;
+        let attributes_of = |variant_id: Option<Uuid>| {
+            attributes
+                .iter()
+                .filter(|(id, _)| *id == variant_id)
+                .map(|(_, attribute)| attribute.clone())
+                .collect::<Vec<_>>()
+        };
+        let variants =
+            sqlx::query("SELECT * FROM product_variants WHERE product_id = $1 ORDER BY position")
+                .bind(product_id)
+                .try_map(|row: PgRow| {
+                    let id: Uuid = row.try_get("id")?;
+                    Ok(Variant {
+                        id: ProductId(id),
+                        sku: Sku::parse(row.try_get("sku")?).map_err(invalid("sku"))?,
+                        price: row.try_get("price")?,
+                        attributes: attributes_of(Some(id)),
+                    })
+                })
+                .fetch_all(&self.pool)
+                .await
+                .map_err(not_read)?;
+
+        Ok(Product::restore(
+            CatalogProductId(product_id),
+            row.try_get("name").map_err(not_read)?,
+            row.try_get::<Option<Uuid>, _>("category_id")
+                .map_err(not_read)?
+                .map(CategoryId),
+            attributes_of(None),
+            variants,
+            row.try_get("version").map_err(not_read)?,
+        ))
+    }
+}
+
+#[async_trait]
+impl domain::repositories::catalog_repository::CatalogRepository for PgCatalogRepository {
+    async fn find_category(
+        &self,
+        id: CategoryId,
+    ) -> Result<Option<Category>, CatalogRepositoryError> {
+        sqlx::query("SELECT * FROM categories WHERE id = $1")
+            .bind(id.0)
+            .try_map(|row: PgRow| {
+                Category::new(
+                    CategoryId(row.try_get("id")?),
+                    row.try_get("name")?,
+                    row.try_get::<Option<Uuid>, _>("parent_id")?.map(CategoryId),
+                )
+                .map_err(|error| sqlx::Error::ColumnDecode {
+                    index: "name".to_string(),
+                    source: Box::new(error),
+                })
+            })
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| CatalogRepositoryError::CategoryNotReadError(e.to_string()))
+    }
+
+    async fn save_category(&self, category: Category) -> Result<Category, CatalogRepositoryError> {
+        sqlx::query("INSERT INTO categories (id, name, parent_id) VALUES ($1, $2, $3)")
+            .bind(category.id().0)
+            .bind(category.name())
+            .bind(category.parent_id().map(|parent_id| parent_id.0))
+            .execute(&self.pool)
+            .await
+            .map_err(|e| match e.as_database_error().and_then(|e| e.code()) {
+                Some(code) if code == UNIQUE_VIOLATION => {
+                    CatalogRepositoryError::CategoryAlreadyExistsError
+                }
+                _ => CatalogRepositoryError::CategoryNotSavedError,
+            })?;
+        Ok(category)
+    }
+
+    async fn find_product(
+        &self,
+        id: CatalogProductId,
+    ) -> Result<Option<Product>, CatalogRepositoryError> {
+        let row = sqlx::query("SELECT * FROM products WHERE id = $1")
+            .bind(id.0)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| CatalogRepositoryError::ProductNotReadError(e.to_string()))?;
+        match row {
+            Some(row) => Ok(Some(self.with_details(row).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_variant(&self, id: ProductId) -> Result<Option<Variant>, CatalogRepositoryError> {
+        let product_id: Option<Uuid> =
+            sqlx::query_scalar("SELECT product_id FROM product_variants WHERE id = $1")
+                .bind(id.0)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| CatalogRepositoryError::ProductNotReadError(e.to_string()))?;
+        let Some(product_id) = product_id else {
+            return Ok(None);
+        };
+        Ok(self
+            .find_product(CatalogProductId(product_id))
+            .await?
+            .and_then(|product| {
+                product
+                    .variants()
+                    .iter()
+                    .find(|variant| variant.id == id)
+                    .cloned()
+            }))
+    }
+
+    async fn find_products_in_category(
+        &self,
+        category_id: CategoryId,
+    ) -> Result<Vec<Product>, CatalogRepositoryError> {
+        let rows = sqlx::query(
+            r#"
+            WITH RECURSIVE tree (id) AS (
+                SELECT id FROM categories WHERE id = $1
+                UNION ALL
+                SELECT categories.id FROM categories JOIN tree ON categories.parent_id = tree.id
+            )
+            SELECT * FROM products WHERE category_id IN (SELECT id FROM tree) ORDER BY name, id
+            "#,
+        )
+        .bind(category_id.0)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| CatalogRepositoryError::ProductNotReadError(e.to_string()))?;
+        let mut products = vec![];
+        for row in rows {
+            products.push(self.with_details(row).await?);
+        }
+        Ok(products)
+    }
+
+    async fn save_product(&self, product: Product) -> Result<Product, CatalogRepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| CatalogRepositoryError::ProductNotSavedError)?;
+        sqlx::query("INSERT INTO products (id, name, category_id, version) VALUES ($1, $2, $3, 1)")
+            .bind(product.id().0)
+            .bind(product.name())
+            .bind(product.category_id().map(|category_id| category_id.0))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| match e.as_database_error().and_then(|e| e.code()) {
+                Some(code) if code == UNIQUE_VIOLATION => {
+                    CatalogRepositoryError::ProductAlreadyExistsError
+                }
+                _ => CatalogRepositoryError::ProductNotSavedError,
+            })?;
+        save_details(&mut tx, &product).await?;
+        tx.commit()
+            .await
+            .map_err(|_| CatalogRepositoryError::ProductNotSavedError)?;
+        Ok(product.with_version(1))
+    }
+
+    async fn update_product(&self, product: Product) -> Result<Product, CatalogRepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| CatalogRepositoryError::ProductNotSavedError)?;
+        let updated = sqlx::query(
+            r#"
+            UPDATE products SET name = $3, category_id = $4, version = version + 1
+            WHERE id = $1 AND version = $2
+            "#,
+        )
+        .bind(product.id().0)
+        .bind(product.version())
+        .bind(product.name())
+        .bind(product.category_id().map(|category_id| category_id.0))
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| CatalogRepositoryError::ProductNotSavedError)?;
+        if updated.rows_affected() == 0 {
+            return Err(CatalogRepositoryError::ConcurrencyConflict);
+        }
+        sqlx::query("DELETE FROM product_variants WHERE product_id = $1")
+            .bind(product.id().0)
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| CatalogRepositoryError::ProductNotSavedError)?;
+        sqlx::query("DELETE FROM product_attributes WHERE product_id = $1")
+            .bind(product.id().0)
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| CatalogRepositoryError::ProductNotSavedError)?;
+        save_details(&mut tx, &product).await?;
+        tx.commit()
+            .await
+            .map_err(|_| CatalogRepositoryError::ProductNotSavedError)?;
+        let version = product.version() + 1;
+        Ok(product.with_version(version))
+    }
+}
+
+/// Inserts the variants and attributes of the product.
+async fn save_details(
+    tx: &mut Transaction<'_, Postgres>,
+    product: &Product,
+) -> Result<(), CatalogRepositoryError> {
+    for (position, variant) in product.variants().iter().enumerate() {
+        sqlx::query(
+            r#"
+            INSERT INTO product_variants (id, product_id, position, sku, price)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(variant.id.0)
+        .bind(product.id().0)
+        .bind(position as i32)
+        .bind(variant.sku.as_str())
+        .bind(variant.price)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| match e.as_database_error() {
+            Some(e) if e.constraint() == Some(SKU_CONSTRAINT) => {
+                CatalogRepositoryError::SkuAlreadyExistsError
+            }
+            Some(e) if e.code().is_some_and(|code| code == UNIQUE_VIOLATION) => {
+                CatalogRepositoryError::VariantAlreadyExistsError
+            }
+            _ => CatalogRepositoryError::ProductNotSavedError,
+        })?;
+    }
+
+    let mut attributes: Vec<(Option<Uuid>, &Attribute)> = product
+        .attributes()
+        .iter()
+        .map(|attribute| (None, attribute))
+        .collect();
+    for variant in product.variants() {
+        for attribute in &variant.attributes {
+            attributes.push((Some(variant.id.0), attribute));
+        }
+    }
+    for (position, (variant_id, attribute)) in attributes.into_iter().enumerate() {
+        sqlx::query(
+            r#"
+            INSERT INTO product_attributes (product_id, variant_id, position, name, kind, value)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(product.id().0)
+        .bind(variant_id)
+        .bind(position as i32)
+        .bind(&attribute.name)
+        .bind(attribute.value.kind())
+        .bind(attribute.value.to_string())
+        .execute(&mut **tx)
+        .await
+        .map_err(|_| CatalogRepositoryError::ProductNotSavedError)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::common::test;
+    use domain::repositories::catalog_repository::CatalogRepository;
+
+    #[tokio::test]
+    async fn saves_a_product_with_its_variants_and_attributes() {
+        let repository = PgCatalogRepository::new(test::create_sqlx_connection_pool().await);
+        let mut product = Product::new(CatalogProductId(Uuid::new_v4()), "T-shirt", None).unwrap();
+        let variant_id = ProductId(Uuid::new_v4());
+        let sku = format!("TS-{}", Uuid::new_v4());
+        product.add_variant(variant_id.clone(), &sku, 19.9).unwrap();
+        let attribute = |name, kind, value| {
+            Attribute::new(name, AttributeValue::parse(kind, value).unwrap()).unwrap()
+        };
+        product
+            .set_attribute(None, attribute("organic", "boolean", "true"))
+            .unwrap();
+        product
+            .set_attribute(Some(&variant_id), attribute("chest", "number", "96.5"))
+            .unwrap();
+
+        let product = repository.save_product(product).await.unwrap();
+        let mut other = Product::new(CatalogProductId(Uuid::new_v4()), "Polo", None).unwrap();
+        other
+            .add_variant(ProductId(Uuid::new_v4()), &sku, 29.9)
+            .unwrap();
+
+        assert!(matches!(
+            repository.save_product(other).await,
+            Err(CatalogRepositoryError::SkuAlreadyExistsError)
+        ));
+        assert_eq!(
+            Some(product.clone()),
+            repository.find_product(product.id().clone()).await.unwrap()
+        );
+        assert_eq!(
+            Some(&product.variants()[0]),
+            repository
+                .find_variant(variant_id.clone())
+                .await
+                .unwrap()
+                .as_ref()
+        );
+        assert_eq!(
+            None,
+            repository
+                .find_variant(ProductId(Uuid::new_v4()))
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn finds_the_products_of_a_category_and_its_subcategories() {
+        let repository = PgCatalogRepository::new(test::create_sqlx_connection_pool().await);
+        let category = |parent_id: Option<&CategoryId>| {
+            Category::new(CategoryId(Uuid::new_v4()), "Category", parent_id.cloned()).unwrap()
+        };
+        let clothing = repository.save_category(category(None)).await.unwrap();
+        let shirts = repository
+            .save_category(category(Some(clothing.id())))
+            .await
+            .unwrap();
+        let shoes = repository.save_category(category(None)).await.unwrap();
+        for (name, category) in [("Shirt", &shirts), ("Jacket", &clothing), ("Boot", &shoes)] {
+            let product = Product::new(
+                CatalogProductId(Uuid::new_v4()),
+                name,
+                Some(category.id().clone()),
+            )
+            .unwrap();
+            repository.save_product(product).await.unwrap();
+        }
+
+        let products = repository
+            .find_products_in_category(clothing.id().clone())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            vec!["Jacket", "Shirt"],
+            products
+                .iter()
+                .map(|product| product.name())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            1,
+            repository
+                .find_products_in_category(shirts.id().clone())
+                .await
+                .unwrap()
+                .len()
+        );
+    }
+}
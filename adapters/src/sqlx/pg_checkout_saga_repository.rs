@@ -0,0 +1,298 @@
+use super::{
+    pg_order_repository::update_order, pg_outbox_message_repository::insert_message,
+    pg_transactional_repository::PgTransactionalRepository,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::{
+    entities::checkout_saga::{CheckoutSaga, CheckoutSagaState},
+    repositories::{
+        checkout_saga_repository::CheckoutSagaRepositoryError,
+        order_repository::OrderRepositoryError,
+        transactional_repository::TransactionalRepositoryError,
+    },
+    services::checkout_saga_service::CheckoutStep,
+    value_objects::{AuthorizationId, OrderId, ReservationId},
+};
+use sqlx::{postgres::PgRow, PgConnection, PgExecutor, Pool, Postgres, Row};
+use uuid::Uuid;
+
+pub struct PgCheckoutSagaRepository<'a> {
+    pool: Pool<Postgres>,
+    transactional: PgTransactionalRepository<'a>,
+}
+
+impl<'a> PgCheckoutSagaRepository<'a> {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        let transactional = PgTransactionalRepository::new(pool.clone());
+        Self {
+            pool,
+            transactional,
+        }
+    }
+}
+
+#[async_trait]
+impl<'a> domain::repositories::transactional_repository::TransactionalRepository
+    for PgCheckoutSagaRepository<'a>
+{
+    async fn begin_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        self.transactional.begin_transaction().await
+    }
+    async fn commit_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        self.transactional.commit_transaction().await
+    }
+    async fn rollback_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        self.transactional.rollback_transaction().await
+    }
+}
+
+#[async_trait]
+impl<'a> domain::repositories::checkout_saga_repository::CheckoutSagaRepository
+    for PgCheckoutSagaRepository<'a>
+{
+    async fn find_by_order_id(
+        &self,
+        order_id: OrderId,
+    ) -> Result<Option<CheckoutSaga>, CheckoutSagaRepositoryError> {
+        sqlx::query("SELECT * FROM checkout_sagas WHERE order_id = $1")
+            .bind(order_id.0)
+            .try_map(checkout_saga_from_row)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| CheckoutSagaRepositoryError::SagaNotReadError(e.to_string()))
+    }
+
+    async fn find_timed_out(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<CheckoutSaga>, CheckoutSagaRepositoryError> {
+        sqlx::query(
+            r#"
+            SELECT * FROM checkout_sagas
+            WHERE deadline <= $1 AND state NOT IN ('confirmed', 'cancelled')
+            ORDER BY deadline
+            "#,
+        )
+        .bind(now)
+        .try_map(checkout_saga_from_row)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| CheckoutSagaRepositoryError::SagaNotReadError(e.to_string()))
+    }
+
+    async fn save(&self, saga: CheckoutSaga) -> Result<CheckoutSaga, CheckoutSagaRepositoryError> {
+        insert_saga(&self.pool, &saga).await?;
+        Ok(saga.with_version(1))
+    }
+
+    async fn update(
+        &self,
+        saga: CheckoutSaga,
+    ) -> Result<CheckoutSaga, CheckoutSagaRepositoryError> {
+        update_saga(&self.pool, &saga).await?;
+        let version = saga.version() + 1;
+        Ok(saga.with_version(version))
+    }
+}
+
+/// Stores a step of the checkout on the caller's transaction, so that it is
+/// committed together with the caller's own writes: the saga, saved if just
+/// started, the order if changed and the event of the step.
+pub async fn save_checkout_step(
+    connection: &mut PgConnection,
+    step: &CheckoutStep,
+) -> Result<(), CheckoutSagaRepositoryError> {
+    if let Some(order) = &step.order {
+        update_order(&mut *connection, order)
+            .await
+            .map_err(|e| match e {
+                OrderRepositoryError::ConcurrencyConflict => {
+                    CheckoutSagaRepositoryError::ConcurrencyConflict
+                }
+                _ => CheckoutSagaRepositoryError::SagaNotSavedError,
+            })?;
+    }
+    if step.saga.version() == 0 {
+        insert_saga(&mut *connection, &step.saga).await?;
+    } else {
+        update_saga(&mut *connection, &step.saga).await?;
+    }
+    insert_message(&mut *connection, &step.message)
+        .await
+        .map_err(|_| CheckoutSagaRepositoryError::SagaNotSavedError)
+}
+
+async fn insert_saga<'e>(
+    executor: impl PgExecutor<'e>,
+    saga: &CheckoutSaga,
+) -> Result<(), CheckoutSagaRepositoryError> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO checkout_sagas
+            (order_id, state, reservation_id, authorization_id, failure_reason, deadline, version)
+        VALUES ($1, $2, $3, $4, $5, $6, 1)
+        ON CONFLICT (order_id) DO NOTHING
+        "#,
+    )
+    .bind(saga.order_id().0)
+    .bind(saga.state().to_string())
+    .bind(saga.reservation_id().map(|reservation_id| reservation_id.0))
+    .bind(
+        saga.authorization_id()
+            .map(|authorization_id| authorization_id.0.clone()),
+    )
+    .bind(saga.failure_reason())
+    .bind(saga.deadline())
+    .execute(executor)
+    .await
+    .map_err(|_| CheckoutSagaRepositoryError::SagaNotSavedError)?;
+    if result.rows_affected() == 0 {
+        return Err(CheckoutSagaRepositoryError::ConcurrencyConflict);
+    }
+    Ok(())
+}
+
+async fn update_saga<'e>(
+    executor: impl PgExecutor<'e>,
+    saga: &CheckoutSaga,
+) -> Result<(), CheckoutSagaRepositoryError> {
+    let result = sqlx::query(
+        r#"
+        UPDATE checkout_sagas
+        SET state = $3, reservation_id = $4, authorization_id = $5, failure_reason = $6,
+            deadline = $7, version = version + 1
+        WHERE order_id = $1 AND version = $2
+        "#,
+    )
+    .bind(saga.order_id().0)
+    .bind(saga.version())
+    .bind(saga.state().to_string())
+    .bind(saga.reservation_id().map(|reservation_id| reservation_id.0))
+    .bind(
+        saga.authorization_id()
+            .map(|authorization_id| authorization_id.0.clone()),
+    )
+    .bind(saga.failure_reason())
+    .bind(saga.deadline())
+    .execute(executor)
+    .await
+    .map_err(|_| CheckoutSagaRepositoryError::SagaNotSavedError)?;
+    if result.rows_affected() == 0 {
+        return Err(CheckoutSagaRepositoryError::ConcurrencyConflict);
+    }
+    Ok(())
+}
+
+fn checkout_saga_from_row(row: PgRow) -> Result<CheckoutSaga, sqlx::Error> {
+    let state: CheckoutSagaState =
+        row.try_get::<&str, _>("state")?
+            .parse()
+            .map_err(|error: String| sqlx::Error::ColumnDecode {
+                index: "state".to_string(),
+                source: error.into(),
+            })?;
+    Ok(CheckoutSaga::restore(
+        OrderId(row.try_get("order_id")?),
+        state,
+        row.try_get::<Option<Uuid>, _>("reservation_id")?
+            .map(ReservationId),
+        row.try_get::<Option<String>, _>("authorization_id")?
+            .map(AuthorizationId),
+        row.try_get("failure_reason")?,
+        row.try_get("deadline")?,
+        row.try_get("version")?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::common::test;
+    use chrono::Duration;
+    use domain::{
+        entities::{checkout_saga::CHECKOUT_STEP_TIMEOUT_MINUTES, outbox::OutboxMessage},
+        repositories::checkout_saga_repository::CheckoutSagaRepository,
+    };
+
+    #[tokio::test]
+    async fn saves_and_updates_a_checkout_saga() {
+        let order_id = OrderId(Uuid::new_v4());
+        let reservation_id = ReservationId(Uuid::new_v4());
+        let repository = PgCheckoutSagaRepository::new(test::create_sqlx_connection_pool().await);
+        let mut saga = repository
+            .save(CheckoutSaga::start(order_id.clone(), Utc::now()))
+            .await
+            .unwrap();
+
+        saga.stock_reserved(reservation_id.clone(), Utc::now())
+            .unwrap();
+        repository.update(saga).await.unwrap();
+
+        let saga_from_db = repository
+            .find_by_order_id(order_id.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(CheckoutSagaState::AuthorizingPayment, saga_from_db.state());
+        assert_eq!(Some(&reservation_id), saga_from_db.reservation_id());
+        assert_eq!(2, saga_from_db.version());
+        assert!(matches!(
+            repository
+                .save(CheckoutSaga::start(order_id, Utc::now()))
+                .await,
+            Err(CheckoutSagaRepositoryError::ConcurrencyConflict)
+        ));
+    }
+
+    #[tokio::test]
+    async fn finds_sagas_past_their_deadline() {
+        let order_id = OrderId(Uuid::new_v4());
+        let started_at = Utc::now() - Duration::days(365);
+        let repository = PgCheckoutSagaRepository::new(test::create_sqlx_connection_pool().await);
+        let saga = repository
+            .save(CheckoutSaga::start(order_id.clone(), started_at))
+            .await
+            .unwrap();
+
+        let timed_out = repository
+            .find_timed_out(started_at + Duration::minutes(CHECKOUT_STEP_TIMEOUT_MINUTES))
+            .await
+            .unwrap();
+        assert!(timed_out.iter().any(|saga| saga.order_id() == &order_id));
+
+        let mut saga = saga;
+        saga.cancel("Checkout timed out", Utc::now()).unwrap();
+        repository.update(saga).await.unwrap();
+        let timed_out = repository.find_timed_out(Utc::now()).await.unwrap();
+        assert!(timed_out.iter().all(|saga| saga.order_id() != &order_id));
+    }
+
+    #[tokio::test]
+    async fn saves_a_checkout_step_on_the_callers_transaction() {
+        let order_id = OrderId(Uuid::new_v4());
+        let pool = test::create_sqlx_connection_pool().await;
+        let repository = PgCheckoutSagaRepository::new(pool.clone());
+        let step = || CheckoutStep {
+            saga: CheckoutSaga::start(order_id.clone(), Utc::now()),
+            order: None,
+            message: OutboxMessage::order_confirmed_event(&order_id).unwrap(),
+        };
+
+        let mut tx = pool.begin().await.unwrap();
+        save_checkout_step(&mut tx, &step()).await.unwrap();
+        tx.rollback().await.unwrap();
+        assert!(repository
+            .find_by_order_id(order_id.clone())
+            .await
+            .unwrap()
+            .is_none());
+
+        let mut tx = pool.begin().await.unwrap();
+        save_checkout_step(&mut tx, &step()).await.unwrap();
+        tx.commit().await.unwrap();
+        let saga_from_db = repository.find_by_order_id(order_id).await.unwrap();
+        assert_eq!(Some(1), saga_from_db.map(|saga| saga.version()));
+    }
+}
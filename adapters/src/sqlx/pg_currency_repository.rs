@@ -0,0 +1,197 @@
+use super::invalid;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use domain::{
+    entities::currency::{ExchangeRate, ProductPrice},
+    repositories::currency_repository::CurrencyRepositoryError,
+    value_objects::{Currency, ProductId},
+};
+use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+
+pub struct PgCurrencyRepository {
+    pool: Pool<Postgres>,
+}
+
+impl PgCurrencyRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl domain::repositories::currency_repository::CurrencyRepository for PgCurrencyRepository {
+    async fn save_rates(&self, rates: Vec<ExchangeRate>) -> Result<(), CurrencyRepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| CurrencyRepositoryError::ExchangeRatesNotSavedError)?;
+        for rate in rates {
+            sqlx::query(
+                r#"
+                INSERT INTO exchange_rates (currency, effective_from, rate) VALUES ($1, $2, $3)
+                ON CONFLICT (currency, effective_from) DO UPDATE SET rate = EXCLUDED.rate
+                "#,
+            )
+            .bind(rate.currency().as_str())
+            .bind(rate.effective_from())
+            .bind(rate.rate())
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| CurrencyRepositoryError::ExchangeRatesNotSavedError)?;
+        }
+        tx.commit()
+            .await
+            .map_err(|_| CurrencyRepositoryError::ExchangeRatesNotSavedError)
+    }
+
+    async fn find_rate(
+        &self,
+        currency: Currency,
+        day: NaiveDate,
+    ) -> Result<Option<ExchangeRate>, CurrencyRepositoryError> {
+        sqlx::query(
+            r#"
+            SELECT * FROM exchange_rates
+            WHERE currency = $1 AND effective_from <= $2
+            ORDER BY effective_from DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(currency.as_str())
+        .bind(day)
+        .try_map(|row: PgRow| {
+            ExchangeRate::new(
+                currency_from_row(&row)?,
+                row.try_get("effective_from")?,
+                row.try_get("rate")?,
+            )
+            .map_err(|error| sqlx::Error::ColumnDecode {
+                index: "rate".to_string(),
+                source: Box::new(error),
+            })
+        })
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| CurrencyRepositoryError::ExchangeRateNotReadError(e.to_string()))
+    }
+
+    async fn save_price(&self, price: ProductPrice) -> Result<(), CurrencyRepositoryError> {
+        sqlx::query(
+            r#"
+            INSERT INTO product_prices (product_id, currency, amount) VALUES ($1, $2, $3)
+            ON CONFLICT (product_id, currency) DO UPDATE SET amount = EXCLUDED.amount
+            "#,
+        )
+        .bind(price.product_id.0)
+        .bind(price.currency.as_str())
+        .bind(price.amount)
+        .execute(&self.pool)
+        .await
+        .map_err(|_| CurrencyRepositoryError::PriceNotSavedError)?;
+        Ok(())
+    }
+
+    async fn find_prices(
+        &self,
+        product_id: ProductId,
+    ) -> Result<Vec<ProductPrice>, CurrencyRepositoryError> {
+        // The price in the base currency is the one of the variant.
+        sqlx::query(
+            r#"
+            SELECT product_id, currency, amount FROM product_prices WHERE product_id = $1
+            UNION ALL
+            SELECT id, $2, price FROM product_variants WHERE id = $1
+            ORDER BY currency
+            "#,
+        )
+        .bind(product_id.0)
+        .bind(Currency::BASE.as_str())
+        .try_map(|row: PgRow| {
+            Ok(ProductPrice {
+                product_id: ProductId(row.try_get("product_id")?),
+                currency: currency_from_row(&row)?,
+                amount: row.try_get("amount")?,
+            })
+        })
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| CurrencyRepositoryError::PriceNotReadError(e.to_string()))
+    }
+}
+
+fn currency_from_row(row: &PgRow) -> Result<Currency, sqlx::Error> {
+    Currency::parse(row.try_get("currency")?).map_err(invalid("currency"))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::{common::test, sqlx::pg_catalog_repository::PgCatalogRepository};
+    use domain::{
+        entities::product::Product,
+        repositories::{
+            catalog_repository::CatalogRepository, currency_repository::CurrencyRepository,
+        },
+        value_objects::CatalogProductId,
+    };
+    use uuid::Uuid;
+
+    /// Rates of 1999 are saved only here, so earlier runs leave the same ones.
+    #[tokio::test]
+    async fn finds_the_latest_rate_effective_on_a_day() {
+        let repository = PgCurrencyRepository::new(test::create_sqlx_connection_pool().await);
+        let day = |month, day| NaiveDate::from_ymd_opt(1999, month, day).unwrap();
+        repository
+            .save_rates(vec![
+                ExchangeRate::new(Currency::Gbp, day(1, 1), 0.70).unwrap(),
+                ExchangeRate::new(Currency::Gbp, day(2, 1), 0.71).unwrap(),
+            ])
+            .await
+            .unwrap();
+        repository
+            .save_rates(vec![
+                ExchangeRate::new(Currency::Gbp, day(2, 1), 0.72).unwrap()
+            ])
+            .await
+            .unwrap();
+
+        let rate = |on| repository.find_rate(Currency::Gbp, on);
+        assert_eq!(0.70, rate(day(1, 31)).await.unwrap().unwrap().rate());
+        assert_eq!(0.72, rate(day(3, 1)).await.unwrap().unwrap().rate());
+        assert!(rate(day(1, 1).pred_opt().unwrap()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn saves_one_price_per_currency_besides_the_price_of_the_variant() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let repository = PgCurrencyRepository::new(pool.clone());
+        let product_id = ProductId(Uuid::new_v4());
+        let mut product = Product::new(CatalogProductId(Uuid::new_v4()), "Mug", None).unwrap();
+        product
+            .add_variant(product_id.clone(), &format!("MUG-{}", product_id.0), 10.0)
+            .unwrap();
+        PgCatalogRepository::new(pool)
+            .save_product(product)
+            .await
+            .unwrap();
+        for (currency, amount) in [(Currency::Usd, 12.0), (Currency::Usd, 11.5)] {
+            repository
+                .save_price(ProductPrice::new(product_id.clone(), currency, amount).unwrap())
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            vec![(Currency::Eur, 10.0), (Currency::Usd, 11.5)],
+            repository
+                .find_prices(product_id)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|price| (price.currency, price.amount))
+                .collect::<Vec<_>>()
+        );
+    }
+}
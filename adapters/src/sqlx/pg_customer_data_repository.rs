@@ -0,0 +1,224 @@
+use async_trait::async_trait;
+use domain::{
+    entities::{customer::Customer, outbox::OutboxMessage},
+    repositories::{
+        customer_data_repository::CustomerDataRepositoryError,
+        customer_repository::CustomerRepositoryError,
+    },
+};
+use sqlx::{Pool, Postgres};
+
+use super::{
+    pg_customer_repository::erase_customer,
+    pg_order_repository::erase_order_addresses,
+    pg_outbox_message_repository::{insert_message, update_payload},
+};
+
+pub struct PgCustomerDataRepository {
+    pool: Pool<Postgres>,
+}
+
+impl PgCustomerDataRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl domain::repositories::customer_data_repository::CustomerDataRepository
+    for PgCustomerDataRepository
+{
+    async fn erase(
+        &self,
+        customer: Customer,
+        anonymized_events: Vec<OutboxMessage>,
+        erased_event: OutboxMessage,
+    ) -> Result<Customer, CustomerDataRepositoryError> {
+        let not_erased = |e: &dyn std::error::Error| {
+            CustomerDataRepositoryError::CustomerNotErasedError(e.to_string())
+        };
+        let mut tx = self.pool.begin().await.map_err(|e| not_erased(&e))?;
+
+        erase_customer(&mut tx, &customer)
+            .await
+            .map_err(|e| match e {
+                CustomerRepositoryError::CustomerNotFoundError => {
+                    CustomerDataRepositoryError::CustomerNotFoundError
+                }
+                CustomerRepositoryError::ConcurrencyConflict => {
+                    CustomerDataRepositoryError::ConcurrencyConflict
+                }
+                e => not_erased(&e),
+            })?;
+        erase_order_addresses(&mut tx, &customer.id)
+            .await
+            .map_err(|e| not_erased(&e))?;
+        for event in &anonymized_events {
+            update_payload(&mut tx, event)
+                .await
+                .map_err(|e| not_erased(&e))?;
+        }
+        insert_message(&mut *tx, &erased_event)
+            .await
+            .map_err(|e| not_erased(&e))?;
+
+        tx.commit().await.map_err(|e| not_erased(&e))?;
+        Ok(Customer {
+            version: customer.version + 1,
+            ..customer
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use domain::{
+        entities::{
+            address_book::AddressBook,
+            customer::Customer,
+            order::{Order, OrderAddresses},
+            outbox::OutboxMessage,
+        },
+        repositories::{
+            customer_data_repository::{CustomerDataRepository, CustomerDataRepositoryError},
+            customer_repository::CustomerRepository,
+            order_repository::OrderRepository,
+            outbox_repository::OutboxMessageRepository,
+        },
+        value_objects::{
+            Address, AddressId, CountryCode, Currency, CustomerId, Email, OrderId, PersonName,
+            PhoneNumber, PostalCode,
+        },
+    };
+    use uuid::Uuid;
+
+    use crate::{
+        common::test,
+        sqlx::{
+            pg_customer_repository::PgCustomerRepository, pg_order_repository::PgOrderRepository,
+            pg_outbox_message_repository::PgOutboxMessageRepository,
+        },
+    };
+
+    use super::PgCustomerDataRepository;
+
+    #[tokio::test]
+    async fn erases_a_customer_with_its_orders_and_events() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let customer_repository = PgCustomerRepository::new(pool.clone());
+        let order_repository = PgOrderRepository::new(pool.clone());
+        let outbox_message_repository = PgOutboxMessageRepository::new(pool.clone());
+        let customer_id = Uuid::new_v4();
+        let mut customer = customer_repository
+            .save(create_sample_customer(customer_id))
+            .await
+            .unwrap();
+        customer
+            .add_address(AddressId(Uuid::new_v4()), customer.address.clone())
+            .unwrap();
+        let mut customer = customer_repository.update(customer).await.unwrap();
+        let order = order_repository
+            .save(
+                Order::create(OrderId(Uuid::new_v4()), customer.id.clone()).with_addresses(
+                    OrderAddresses {
+                        shipping: customer.address.clone(),
+                        billing: customer.address.clone(),
+                    },
+                ),
+            )
+            .await
+            .unwrap();
+        let event = outbox_message_repository
+            .save(OutboxMessage::customer_created_event(&customer).unwrap())
+            .await
+            .unwrap();
+        customer.erase(chrono::Utc::now());
+        let erased_event = OutboxMessage::customer_erased_event(&customer).unwrap();
+
+        PgCustomerDataRepository::new(pool.clone())
+            .erase(customer, vec![event.anonymized().unwrap()], erased_event)
+            .await
+            .unwrap();
+
+        let customer = customer_repository
+            .find_by_id(CustomerId(customer_id))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!("erased", customer.first_name.as_str());
+        assert_eq!(None, customer.phone);
+        assert_eq!("erased", customer.address.street);
+        assert!(customer.address_book.is_empty());
+        assert!(!customer.is_active());
+        assert_eq!(3, customer.version);
+        let order = order_repository
+            .find_by_id(order.id().clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!("erased", order.addresses().unwrap().shipping.street);
+        let events = outbox_message_repository
+            .find_by_customer_id(&customer.id)
+            .await
+            .unwrap();
+        assert!(events
+            .iter()
+            .all(|event| !event.event_payload().contains("Appleseed")));
+    }
+
+    #[tokio::test]
+    async fn erases_nothing_if_the_customer_changed_since_it_was_read() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let customer_repository = PgCustomerRepository::new(pool.clone());
+        let outbox_message_repository = PgOutboxMessageRepository::new(pool.clone());
+        let customer_id = Uuid::new_v4();
+        let customer = customer_repository
+            .save(create_sample_customer(customer_id))
+            .await
+            .unwrap();
+        let event = outbox_message_repository
+            .save(OutboxMessage::customer_created_event(&customer).unwrap())
+            .await
+            .unwrap();
+        customer_repository.update(customer).await.unwrap();
+        let mut stale = create_sample_customer(customer_id);
+        stale.version = 1;
+        stale.erase(chrono::Utc::now());
+        let erased_event = OutboxMessage::customer_erased_event(&stale).unwrap();
+
+        let result = PgCustomerDataRepository::new(pool.clone())
+            .erase(stale, vec![event.anonymized().unwrap()], erased_event)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(CustomerDataRepositoryError::ConcurrencyConflict)
+        ));
+        let events = outbox_message_repository
+            .find_by_customer_id(&CustomerId(customer_id))
+            .await
+            .unwrap();
+        assert_eq!(1, events.len());
+        assert!(events[0].event_payload().contains("Appleseed"));
+    }
+
+    fn create_sample_customer(customer_id: Uuid) -> Customer {
+        Customer {
+            id: CustomerId(customer_id),
+            first_name: PersonName::parse("John").unwrap(),
+            last_name: PersonName::parse("Appleseed").unwrap(),
+            email: Email::parse(&format!("{}@example.com", customer_id)).unwrap(),
+            phone: Some(PhoneNumber::parse("+1 207 555 0100").unwrap()),
+            address: Address {
+                street: "22 Elm Street".to_string(),
+                city: "Castle Rock".to_string(),
+                zip_code: PostalCode::parse("04401", &CountryCode::parse("US").unwrap()).unwrap(),
+                country: CountryCode::parse("US").unwrap(),
+            },
+            address_book: AddressBook::new(),
+            currency: Currency::Eur,
+            deactivated_at: None,
+            version: 0,
+        }
+    }
+}
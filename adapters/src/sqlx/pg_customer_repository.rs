@@ -1,14 +1,23 @@
-use super::pg_transactional_repository::PgTransactionalRepository;
+use super::{invalid, pg_transactional_repository::PgTransactionalRepository};
 use async_trait::async_trait;
 use domain::{
-    entities::customer::Customer,
+    entities::{
+        address_book::{AddressBook, CustomerAddress},
+        customer::Customer,
+    },
     repositories::{
         customer_repository::CustomerRepositoryError,
         transactional_repository::TransactionalRepositoryError,
     },
-    value_objects::{Address, CustomerId},
+    value_objects::{
+        Address, AddressId, CountryCode, Currency, CustomerId, Email, PersonName, PhoneNumber,
+        PostalCode,
+    },
 };
-use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+use sqlx::{postgres::PgRow, PgConnection, PgExecutor, Pool, Postgres, Row};
+use uuid::Uuid;
+
+const UNIQUE_VIOLATION: &str = "23505";
 
 pub struct PgCustomerRepository<'a> {
     pool: Pool<Postgres>,
@@ -48,55 +57,336 @@ impl<'a> domain::repositories::customer_repository::CustomerRepository
         &self,
         id: CustomerId,
     ) -> Result<Option<Customer>, CustomerRepositoryError> {
-        let uuid = id.0;
-        let customer = sqlx::query("SELECT * FROM customers where id = $1")
-            .bind(uuid)
-            .try_map(|row: PgRow| {
-                Ok(Customer {
-                    id: CustomerId(uuid),
-                    first_name: row.try_get("first_name")?,
-                    last_name: row.try_get("last_name")?,
-                    address: Address {
-                        street: row.try_get("street")?,
-                        city: row.try_get("city")?,
-                        zip_code: row.try_get("zip_code")?,
-                        state: row.try_get("state")?,
-                    },
-                })
-            })
+        let mut customer = sqlx::query("SELECT * FROM customers where id = $1")
+            .bind(id.0)
+            .try_map(customer_from_row)
             .fetch_one(&self.pool)
             .await
-            .map_err(|_| CustomerRepositoryError::CustomerNotFoundError)?;
+            .map_err(|e| match e {
+                sqlx::Error::ColumnDecode { .. } => {
+                    CustomerRepositoryError::CustomerNotReadError(e.to_string())
+                }
+                _ => CustomerRepositoryError::CustomerNotFoundError,
+            })?;
+        customer.address_book = find_address_book(&self.pool, customer.id.0).await?;
         return Ok(Some(customer));
     }
 
+    async fn find_by_email(
+        &self,
+        email: &Email,
+    ) -> Result<Option<Customer>, CustomerRepositoryError> {
+        let customer = sqlx::query("SELECT * FROM customers WHERE LOWER(email) = LOWER($1)")
+            .bind(email.as_str())
+            .try_map(customer_from_row)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| CustomerRepositoryError::CustomerNotReadError(e.to_string()))?;
+        let Some(mut customer) = customer else {
+            return Ok(None);
+        };
+        customer.address_book = find_address_book(&self.pool, customer.id.0).await?;
+        Ok(Some(customer))
+    }
+
     async fn save(&self, customer: Customer) -> Result<Customer, CustomerRepositoryError> {
-        sqlx::query(
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| CustomerRepositoryError::CustomerNotSavedError)?;
+        let version = sqlx::query(
             r#"
-        INSERT INTO customers (id, first_name, last_name, street, city, zip_code, state)
-        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        INSERT INTO customers (
+            id, first_name, last_name, street, city, zip_code, country, email, phone, currency
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
         ON CONFLICT (id) DO UPDATE
         SET first_name = EXCLUDED.first_name,
             last_name = EXCLUDED.last_name,
+            email = EXCLUDED.email,
+            phone = EXCLUDED.phone,
             street = EXCLUDED.street,
             city = EXCLUDED.city,
             zip_code = EXCLUDED.zip_code,
-            state = EXCLUDED.state
+            country = EXCLUDED.country,
+            currency = EXCLUDED.currency
+        RETURNING version
         "#,
         )
         .bind(customer.id.0)
-        .bind(&customer.first_name)
-        .bind(&customer.last_name)
+        .bind(customer.first_name.as_str())
+        .bind(customer.last_name.as_str())
         .bind(&customer.address.street)
         .bind(&customer.address.city)
-        .bind(&customer.address.zip_code)
-        .bind(&customer.address.state)
+        .bind(customer.address.zip_code.as_str())
+        .bind(customer.address.country.as_str())
+        .bind(customer.email.as_str())
+        .bind(customer.phone.as_ref().map(PhoneNumber::as_str))
+        .bind(customer.currency.as_str())
+        .fetch_one(&mut *tx)
+        .await
+        .and_then(|row| row.try_get("version"))
+        .map_err(|e| match e.as_database_error().and_then(|e| e.code()) {
+            Some(code) if code == UNIQUE_VIOLATION => {
+                CustomerRepositoryError::CustomerAlreadyExists
+            }
+            _ => CustomerRepositoryError::CustomerNotSavedError,
+        })?;
+        save_address_book(&mut tx, &customer).await?;
+        tx.commit()
+            .await
+            .map_err(|_| CustomerRepositoryError::CustomerNotSavedError)?;
+
+        Ok(Customer {
+            version,
+            ..customer
+        })
+    }
+
+    async fn update(&self, customer: Customer) -> Result<Customer, CustomerRepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| CustomerRepositoryError::CustomerNotSavedError)?;
+        let result = sqlx::query(
+            r#"
+        UPDATE customers
+        SET first_name = $3,
+            last_name = $4,
+            street = $5,
+            city = $6,
+            zip_code = $7,
+            country = $8,
+            email = $9,
+            phone = $10,
+            version = version + 1
+        WHERE id = $1 AND version = $2
+        "#,
+        )
+        .bind(customer.id.0)
+        .bind(customer.version)
+        .bind(customer.first_name.as_str())
+        .bind(customer.last_name.as_str())
+        .bind(&customer.address.street)
+        .bind(&customer.address.city)
+        .bind(customer.address.zip_code.as_str())
+        .bind(customer.address.country.as_str())
+        .bind(customer.email.as_str())
+        .bind(customer.phone.as_ref().map(PhoneNumber::as_str))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e.as_database_error().and_then(|e| e.code()) {
+            Some(code) if code == UNIQUE_VIOLATION => {
+                CustomerRepositoryError::CustomerAlreadyExists
+            }
+            _ => CustomerRepositoryError::CustomerNotSavedError,
+        })?;
+
+        if result.rows_affected() == 0 {
+            if self.find_by_id(customer.id.clone()).await?.is_none() {
+                return Err(CustomerRepositoryError::CustomerNotFoundError);
+            }
+            return Err(CustomerRepositoryError::ConcurrencyConflict);
+        }
+        save_address_book(&mut tx, &customer).await?;
+        tx.commit()
+            .await
+            .map_err(|_| CustomerRepositoryError::CustomerNotSavedError)?;
+        Ok(Customer {
+            version: customer.version + 1,
+            ..customer
+        })
+    }
+
+    async fn delete(&self, customer: Customer) -> Result<Customer, CustomerRepositoryError> {
+        let result = sqlx::query(
+            "UPDATE customers SET deactivated_at = $3, version = version + 1 WHERE id = $1 AND version = $2",
+        )
+        .bind(customer.id.0)
+        .bind(customer.version)
+        .bind(customer.deactivated_at)
         .execute(&self.pool)
         .await
         .map_err(|_| CustomerRepositoryError::CustomerNotSavedError)?;
 
-        Ok(customer)
+        if result.rows_affected() == 0 {
+            if self.find_by_id(customer.id.clone()).await?.is_none() {
+                return Err(CustomerRepositoryError::CustomerNotFoundError);
+            }
+            return Err(CustomerRepositoryError::ConcurrencyConflict);
+        }
+        Ok(Customer {
+            version: customer.version + 1,
+            ..customer
+        })
+    }
+}
+
+/// Overwrites the stored personal data with the one of the erased `customer`
+/// and removes its address book, on the caller's transaction.
+pub(crate) async fn erase_customer(
+    connection: &mut PgConnection,
+    customer: &Customer,
+) -> Result<(), CustomerRepositoryError> {
+    let result = sqlx::query(
+        r#"
+        UPDATE customers
+        SET first_name = $3,
+            last_name = $4,
+            street = $5,
+            city = $6,
+            email = $7,
+            phone = NULL,
+            deactivated_at = $8,
+            version = version + 1
+        WHERE id = $1 AND version = $2
+        "#,
+    )
+    .bind(customer.id.0)
+    .bind(customer.version)
+    .bind(customer.first_name.as_str())
+    .bind(customer.last_name.as_str())
+    .bind(&customer.address.street)
+    .bind(&customer.address.city)
+    .bind(customer.email.as_str())
+    .bind(customer.deactivated_at)
+    .execute(&mut *connection)
+    .await
+    .map_err(|_| CustomerRepositoryError::CustomerNotSavedError)?;
+
+    if result.rows_affected() == 0 {
+        let exists: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM customers WHERE id = $1)")
+                .bind(customer.id.0)
+                .fetch_one(&mut *connection)
+                .await
+                .map_err(|e| CustomerRepositoryError::CustomerNotReadError(e.to_string()))?;
+        if !exists {
+            return Err(CustomerRepositoryError::CustomerNotFoundError);
+        }
+        return Err(CustomerRepositoryError::ConcurrencyConflict);
     }
+    save_address_book(connection, customer).await
+}
+
+fn customer_from_row(row: PgRow) -> Result<Customer, sqlx::Error> {
+    let country = CountryCode::parse(row.try_get("country")?).map_err(invalid("country"))?;
+    Ok(Customer {
+        id: CustomerId(row.try_get("id")?),
+        first_name: PersonName::parse(row.try_get("first_name")?).map_err(invalid("first_name"))?,
+        last_name: PersonName::parse(row.try_get("last_name")?).map_err(invalid("last_name"))?,
+        email: Email::parse(row.try_get("email")?).map_err(invalid("email"))?,
+        phone: row
+            .try_get::<Option<&str>, _>("phone")?
+            .map(PhoneNumber::parse)
+            .transpose()
+            .map_err(invalid("phone"))?,
+        address: Address {
+            street: row.try_get("street")?,
+            city: row.try_get("city")?,
+            zip_code: PostalCode::parse(row.try_get("zip_code")?, &country)
+                .map_err(invalid("zip_code"))?,
+            country,
+        },
+        address_book: AddressBook::new(),
+        currency: Currency::parse(row.try_get("currency")?).map_err(invalid("currency"))?,
+        deactivated_at: row.try_get("deactivated_at")?,
+        version: row.try_get("version")?,
+    })
+}
+
+async fn find_address_book<'e>(
+    executor: impl PgExecutor<'e>,
+    customer_id: Uuid,
+) -> Result<AddressBook, CustomerRepositoryError> {
+    let rows = sqlx::query("SELECT * FROM customer_addresses WHERE customer_id = $1 ORDER BY id")
+        .bind(customer_id)
+        .try_map(|row: PgRow| {
+            let country =
+                CountryCode::parse(row.try_get("country")?).map_err(invalid("country"))?;
+            let customer_address = CustomerAddress {
+                id: AddressId(row.try_get("id")?),
+                address: Address {
+                    street: row.try_get("street")?,
+                    city: row.try_get("city")?,
+                    zip_code: PostalCode::parse(row.try_get("zip_code")?, &country)
+                        .map_err(invalid("zip_code"))?,
+                    country,
+                },
+            };
+            let is_default_shipping: bool = row.try_get("is_default_shipping")?;
+            let is_default_billing: bool = row.try_get("is_default_billing")?;
+            Ok((customer_address, is_default_shipping, is_default_billing))
+        })
+        .fetch_all(executor)
+        .await
+        .map_err(|e| CustomerRepositoryError::CustomerNotReadError(e.to_string()))?;
+
+    let default_id = |is_default: fn(&(CustomerAddress, bool, bool)) -> bool| {
+        rows.iter()
+            .find(|row| is_default(row))
+            .map(|(customer_address, _, _)| customer_address.id.clone())
+    };
+    let default_shipping = default_id(|(_, is_default, _)| *is_default);
+    let default_billing = default_id(|(_, _, is_default)| *is_default);
+    let addresses = rows.into_iter().map(|(address, _, _)| address).collect();
+    AddressBook::restore(addresses, default_shipping, default_billing)
+        .map_err(|e| CustomerRepositoryError::CustomerNotReadError(e.to_string()))
+}
+
+/// Replaces the stored address book with the customer's one.
+async fn save_address_book(
+    connection: &mut PgConnection,
+    customer: &Customer,
+) -> Result<(), CustomerRepositoryError> {
+    let address_book = &customer.address_book;
+    let address_ids: Vec<Uuid> = address_book
+        .as_slice()
+        .iter()
+        .map(|customer_address| customer_address.id.0)
+        .collect();
+    sqlx::query("DELETE FROM customer_addresses WHERE customer_id = $1 AND NOT (id = ANY($2))")
+        .bind(customer.id.0)
+        .bind(&address_ids)
+        .execute(&mut *connection)
+        .await
+        .map_err(|_| CustomerRepositoryError::CustomerNotSavedError)?;
+    // Defaults are cleared first, so that moving one never matches two rows.
+    sqlx::query(
+        "UPDATE customer_addresses SET is_default_shipping = FALSE, is_default_billing = FALSE WHERE customer_id = $1",
+    )
+    .bind(customer.id.0)
+    .execute(&mut *connection)
+    .await
+    .map_err(|_| CustomerRepositoryError::CustomerNotSavedError)?;
+
+    for customer_address in address_book.as_slice() {
+        let address = &customer_address.address;
+        sqlx::query(
+            r#"
+            INSERT INTO customer_addresses
+                (id, customer_id, street, city, zip_code, country, is_default_shipping, is_default_billing)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (id) DO UPDATE
+            SET is_default_shipping = EXCLUDED.is_default_shipping,
+                is_default_billing = EXCLUDED.is_default_billing
+            "#,
+        )
+        .bind(customer_address.id.0)
+        .bind(customer.id.0)
+        .bind(&address.street)
+        .bind(&address.city)
+        .bind(address.zip_code.as_str())
+        .bind(address.country.as_str())
+        .bind(address_book.default_shipping() == Some(&customer_address.id))
+        .bind(address_book.default_billing() == Some(&customer_address.id))
+        .execute(&mut *connection)
+        .await
+        .map_err(|_| CustomerRepositoryError::CustomerNotSavedError)?;
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -105,9 +395,12 @@ mod test {
     use super::*;
     use crate::common::test;
     use domain::{
-        entities::customer::Customer,
+        entities::{address_book::AddressBook, customer::Customer},
         repositories::customer_repository::CustomerRepository,
-        value_objects::{Address, CustomerId},
+        value_objects::{
+            Address, AddressId, CountryCode, Currency, CustomerId, Email, PersonName, PhoneNumber,
+            PostalCode,
+        },
     };
     use uuid::Uuid;
 
@@ -141,25 +434,222 @@ mod test {
             .unwrap();
 
         assert_eq!(CustomerId(customer_id), customer.id);
-        assert_eq!("John", customer.first_name);
-        assert_eq!("Appleseed", customer.last_name);
+        assert_eq!("John", customer.first_name.as_str());
+        assert_eq!("Appleseed", customer.last_name.as_str());
+        assert_eq!(
+            format!("{}@example.com", customer_id),
+            customer.email.as_str()
+        );
+        assert_eq!(
+            Some("+12075550100"),
+            customer.phone.as_ref().map(PhoneNumber::as_str)
+        );
         assert_eq!("22 Elm Street".to_string(), customer.address.street);
         assert_eq!("Castle Rock".to_string(), customer.address.city);
-        assert_eq!("666".to_string(), customer.address.zip_code);
-        assert_eq!("US".to_string(), customer.address.state);
+        assert_eq!("04401", customer.address.zip_code.as_str());
+        assert_eq!("US", customer.address.country.as_str());
+    }
+
+    #[tokio::test]
+    async fn find_customer_by_email_regardless_of_case() {
+        let repository = PgCustomerRepository::new(test::create_sqlx_connection_pool().await);
+        let customer_id = Uuid::new_v4();
+        repository
+            .save(create_sample_customer(customer_id))
+            .await
+            .unwrap();
+
+        let customer = repository
+            .find_by_email(&Email::parse(&format!("{}@EXAMPLE.com", customer_id)).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(Some(CustomerId(customer_id)), customer.map(|c| c.id));
+        let missing = repository
+            .find_by_email(&Email::parse("nobody@example.com").unwrap())
+            .await
+            .unwrap();
+        assert!(missing.is_none());
+    }
+
+    #[tokio::test]
+    async fn rejects_a_customer_with_an_email_already_in_use() {
+        let repository = PgCustomerRepository::new(test::create_sqlx_connection_pool().await);
+        let first = repository
+            .save(create_sample_customer(Uuid::new_v4()))
+            .await
+            .unwrap();
+
+        let result = repository
+            .save(Customer {
+                email: first.email.clone(),
+                ..create_sample_customer(Uuid::new_v4())
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(CustomerRepositoryError::CustomerAlreadyExists)
+        ));
+    }
+
+    #[tokio::test]
+    async fn updates_a_customer() {
+        let repository = PgCustomerRepository::new(test::create_sqlx_connection_pool().await);
+        let customer_id = Uuid::new_v4();
+        let customer = repository
+            .save(create_sample_customer(customer_id))
+            .await
+            .unwrap();
+
+        let updated = repository
+            .update(Customer {
+                first_name: PersonName::parse("Jane").unwrap(),
+                ..customer
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(2, updated.version);
+        let customer = repository
+            .find_by_id(CustomerId(customer_id))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!("Jane", customer.first_name.as_str());
+        assert_eq!(2, customer.version);
+    }
+
+    #[tokio::test]
+    async fn rejects_concurrent_updates_of_a_customer() {
+        let repository = PgCustomerRepository::new(test::create_sqlx_connection_pool().await);
+        let customer_id = Uuid::new_v4();
+        repository
+            .save(create_sample_customer(customer_id))
+            .await
+            .unwrap();
+        let first = repository
+            .find_by_id(CustomerId(customer_id))
+            .await
+            .unwrap()
+            .unwrap();
+        let second = repository
+            .find_by_id(CustomerId(customer_id))
+            .await
+            .unwrap()
+            .unwrap();
+
+        let (first, second) = tokio::join!(
+            repository.update(Customer {
+                first_name: PersonName::parse("Jane").unwrap(),
+                ..first
+            }),
+            repository.update(Customer {
+                first_name: PersonName::parse("Joan").unwrap(),
+                ..second
+            })
+        );
+
+        let conflicts = [&first, &second]
+            .iter()
+            .filter(|result| matches!(result, Err(CustomerRepositoryError::ConcurrencyConflict)))
+            .count();
+        assert_eq!(1, conflicts);
+        assert!(first.is_ok() || second.is_ok());
+    }
+
+    #[tokio::test]
+    async fn does_not_update_a_missing_customer() {
+        let repository = PgCustomerRepository::new(test::create_sqlx_connection_pool().await);
+
+        assert!(matches!(
+            repository
+                .update(create_sample_customer(Uuid::new_v4()))
+                .await,
+            Err(CustomerRepositoryError::CustomerNotFoundError)
+        ));
+    }
+
+    #[tokio::test]
+    async fn soft_deletes_a_customer() {
+        let repository = PgCustomerRepository::new(test::create_sqlx_connection_pool().await);
+        let customer_id = Uuid::new_v4();
+        let mut customer = repository
+            .save(create_sample_customer(customer_id))
+            .await
+            .unwrap();
+        customer.deactivate(chrono::Utc::now());
+
+        let deleted = repository.delete(customer).await.unwrap();
+
+        assert_eq!(2, deleted.version);
+        let customer = repository
+            .find_by_id(CustomerId(customer_id))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!customer.is_active());
+        assert_eq!(2, customer.version);
+    }
+
+    #[tokio::test]
+    async fn saves_the_address_book_of_a_customer() {
+        let repository = PgCustomerRepository::new(test::create_sqlx_connection_pool().await);
+        let customer_id = Uuid::new_v4();
+        let mut customer = repository
+            .save(create_sample_customer(customer_id))
+            .await
+            .unwrap();
+        let home = AddressId(Uuid::new_v4());
+        let office = AddressId(Uuid::new_v4());
+        customer
+            .add_address(home.clone(), customer.address.clone())
+            .unwrap();
+        customer
+            .add_address(office.clone(), customer.address.clone())
+            .unwrap();
+        customer.set_default_billing_address(&office).unwrap();
+        repository.update(customer).await.unwrap();
+
+        let mut customer = repository
+            .find_by_id(CustomerId(customer_id))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(2, customer.address_book.as_slice().len());
+        assert_eq!(Some(&home), customer.address_book.default_shipping());
+        assert_eq!(Some(&office), customer.address_book.default_billing());
+
+        customer.remove_address(&home).unwrap();
+        repository.update(customer).await.unwrap();
+
+        let customer = repository
+            .find_by_id(CustomerId(customer_id))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(1, customer.address_book.as_slice().len());
+        assert_eq!(None, customer.address_book.default_shipping());
+        assert_eq!(Some(&office), customer.address_book.default_billing());
     }
 
     fn create_sample_customer(customer_id: Uuid) -> Customer {
         Customer {
             id: CustomerId(customer_id),
-            first_name: "John".to_string(),
-            last_name: "Appleseed".to_string(),
+            first_name: PersonName::parse("John").unwrap(),
+            last_name: PersonName::parse("Appleseed").unwrap(),
+            email: Email::parse(&format!("{}@example.com", customer_id)).unwrap(),
+            phone: Some(PhoneNumber::parse("+1 207 555 0100").unwrap()),
             address: Address {
                 street: "22 Elm Street".to_string(),
                 city: "Castle Rock".to_string(),
-                zip_code: "666".to_string(),
-                state: "US".to_string(),
+                zip_code: PostalCode::parse("04401", &CountryCode::parse("US").unwrap()).unwrap(),
+                country: CountryCode::parse("US").unwrap(),
             },
+            address_book: AddressBook::new(),
+            currency: Currency::Eur,
+            deactivated_at: None,
+            version: 0,
         }
     }
 }
@@ -0,0 +1,234 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use domain::repositories::event_store::{EventStoreError, NewEvent, Snapshot, StoredEvent};
+use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+use uuid::Uuid;
+
+const UNIQUE_VIOLATION: &str = "23505";
+
+pub struct PgEventStore {
+    pool: Pool<Postgres>,
+}
+
+impl PgEventStore {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl domain::repositories::event_store::EventStore for PgEventStore {
+    async fn append(
+        &self,
+        stream_id: Uuid,
+        expected_version: i64,
+        events: Vec<NewEvent>,
+    ) -> Result<i64, EventStoreError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| EventStoreError::EventsNotSavedError(e.to_string()))?;
+
+        let actual: i64 =
+            sqlx::query("SELECT COALESCE(MAX(version), 0) FROM events WHERE stream_id = $1")
+                .bind(stream_id)
+                .fetch_one(&mut *tx)
+                .await
+                .and_then(|row| row.try_get(0))
+                .map_err(|e| EventStoreError::EventsNotSavedError(e.to_string()))?;
+        if actual != expected_version {
+            return Err(EventStoreError::VersionConflictError {
+                expected: expected_version,
+                actual,
+            });
+        }
+
+        let mut version = expected_version;
+        for event in events {
+            version += 1;
+            // The primary key catches writers that read the same version concurrently.
+            sqlx::query(
+                r#"
+                INSERT INTO events (stream_id, version, event_type, event_payload, created_at)
+                VALUES ($1, $2, $3, $4, $5)
+            "#,
+            )
+            .bind(stream_id)
+            .bind(version)
+            .bind(event.event_type)
+            .bind(event.event_payload)
+            .bind(Utc::now())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| match e.as_database_error().and_then(|e| e.code()) {
+                Some(code) if code == UNIQUE_VIOLATION => EventStoreError::VersionConflictError {
+                    expected: expected_version,
+                    actual: version,
+                },
+                _ => EventStoreError::EventsNotSavedError(e.to_string()),
+            })?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| EventStoreError::EventsNotSavedError(e.to_string()))?;
+        Ok(version)
+    }
+
+    async fn load(
+        &self,
+        stream_id: Uuid,
+        after_version: i64,
+    ) -> Result<Vec<StoredEvent>, EventStoreError> {
+        sqlx::query(
+            r#"
+            SELECT * FROM events
+            WHERE stream_id = $1 AND version > $2
+            ORDER BY version
+        "#,
+        )
+        .bind(stream_id)
+        .bind(after_version)
+        .try_map(|row: PgRow| {
+            Ok(StoredEvent {
+                stream_id: row.try_get("stream_id")?,
+                version: row.try_get("version")?,
+                event_type: row.try_get("event_type")?,
+                event_payload: row.try_get("event_payload")?,
+                created_at: row.try_get("created_at")?,
+            })
+        })
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| EventStoreError::EventsNotReadError(e.to_string()))
+    }
+
+    async fn save_snapshot(&self, snapshot: Snapshot) -> Result<(), EventStoreError> {
+        sqlx::query(
+            r#"
+            INSERT INTO snapshots (stream_id, version, snapshot_payload, created_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (stream_id) DO UPDATE
+            SET version = EXCLUDED.version,
+                snapshot_payload = EXCLUDED.snapshot_payload,
+                created_at = EXCLUDED.created_at
+            WHERE snapshots.version < EXCLUDED.version
+        "#,
+        )
+        .bind(snapshot.stream_id)
+        .bind(snapshot.version)
+        .bind(snapshot.snapshot_payload)
+        .bind(snapshot.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| EventStoreError::SnapshotNotSavedError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn load_snapshot(&self, stream_id: Uuid) -> Result<Option<Snapshot>, EventStoreError> {
+        sqlx::query("SELECT * FROM snapshots WHERE stream_id = $1")
+            .bind(stream_id)
+            .try_map(|row: PgRow| {
+                Ok(Snapshot {
+                    stream_id: row.try_get("stream_id")?,
+                    version: row.try_get("version")?,
+                    snapshot_payload: row.try_get("snapshot_payload")?,
+                    created_at: row.try_get("created_at")?,
+                })
+            })
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| EventStoreError::SnapshotNotReadError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{SubsecRound, Utc};
+    use domain::repositories::event_store::{EventStore, EventStoreError, NewEvent, Snapshot};
+    use uuid::Uuid;
+
+    use crate::{common::test, sqlx::pg_event_store::PgEventStore};
+
+    #[tokio::test]
+    async fn appends_and_loads_events() {
+        let event_store = PgEventStore::new(test::create_sqlx_connection_pool().await);
+        let stream_id = Uuid::new_v4();
+
+        let version = event_store
+            .append(stream_id, 0, vec![new_event("first"), new_event("second")])
+            .await
+            .unwrap();
+        let version = event_store
+            .append(stream_id, version, vec![new_event("third")])
+            .await
+            .unwrap();
+
+        assert_eq!(3, version);
+        let events = event_store.load(stream_id, 1).await.unwrap();
+        assert_eq!(2, events.len());
+        assert_eq!(2, events[0].version);
+        assert_eq!("second", events[0].event_type);
+        assert_eq!(3, events[1].version);
+        assert_eq!("third", events[1].event_type);
+    }
+
+    #[tokio::test]
+    async fn rejects_events_appended_at_a_stale_version() {
+        let event_store = PgEventStore::new(test::create_sqlx_connection_pool().await);
+        let stream_id = Uuid::new_v4();
+        event_store
+            .append(stream_id, 0, vec![new_event("first")])
+            .await
+            .unwrap();
+
+        let result = event_store
+            .append(stream_id, 0, vec![new_event("second")])
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(EventStoreError::VersionConflictError {
+                expected: 0,
+                actual: 1
+            })
+        ));
+        assert_eq!(1, event_store.load(stream_id, 0).await.unwrap().len());
+    }
+
+    #[tokio::test]
+    async fn saves_and_loads_the_latest_snapshot() {
+        let event_store = PgEventStore::new(test::create_sqlx_connection_pool().await);
+        let stream_id = Uuid::new_v4();
+        let snapshot = Snapshot {
+            stream_id,
+            version: 20,
+            snapshot_payload: "{}".to_string(),
+            created_at: Utc::now().trunc_subsecs(6),
+        };
+        event_store.save_snapshot(snapshot.clone()).await.unwrap();
+        event_store
+            .save_snapshot(Snapshot {
+                version: 10,
+                ..snapshot.clone()
+            })
+            .await
+            .unwrap();
+
+        let loaded = event_store.load_snapshot(stream_id).await.unwrap();
+
+        assert_eq!(Some(snapshot), loaded);
+        assert_eq!(
+            None,
+            event_store.load_snapshot(Uuid::new_v4()).await.unwrap()
+        );
+    }
+
+    fn new_event(event_type: &str) -> NewEvent {
+        NewEvent {
+            event_type: event_type.to_string(),
+            event_payload: "{}".to_string(),
+        }
+    }
+}
@@ -0,0 +1,350 @@
+use super::pg_transactional_repository::PgTransactionalRepository;
+use async_trait::async_trait;
+use domain::{
+    entities::inventory::{Inventory, ReservedStock, StockReservation, StockReservationState},
+    repositories::{
+        inventory_repository::InventoryRepositoryError,
+        transactional_repository::TransactionalRepositoryError,
+    },
+    value_objects::{OrderId, ProductId, ReservationId, WarehouseId},
+};
+use sqlx::{postgres::PgRow, PgConnection, Pool, Postgres, Row};
+use uuid::Uuid;
+
+pub struct PgInventoryRepository<'a> {
+    pool: Pool<Postgres>,
+    transactional: PgTransactionalRepository<'a>,
+}
+
+impl<'a> PgInventoryRepository<'a> {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        let transactional = PgTransactionalRepository::new(pool.clone());
+        Self {
+            pool,
+            transactional,
+        }
+    }
+
+    async fn find_one_reservation(
+        &self,
+        query: &str,
+        id: Uuid,
+    ) -> Result<Option<StockReservation>, InventoryRepositoryError> {
+        let row = sqlx::query(query)
+            .bind(id)
+            .try_map(|row: PgRow| {
+                let state: StockReservationState = row
+                    .try_get::<&str, _>("state")?
+                    .parse()
+                    .map_err(|error: String| sqlx::Error::ColumnDecode {
+                        index: "state".to_string(),
+                        source: error.into(),
+                    })?;
+                Ok((
+                    ReservationId(row.try_get("id")?),
+                    OrderId(row.try_get("order_id")?),
+                    state,
+                ))
+            })
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| InventoryRepositoryError::InventoryNotReadError(e.to_string()))?;
+        let Some((reservation_id, order_id, state)) = row else {
+            return Ok(None);
+        };
+
+        let lines = sqlx::query(
+            r#"
+            SELECT * FROM stock_reservation_items
+            WHERE reservation_id = $1
+            ORDER BY product_id, warehouse_id
+            "#,
+        )
+        .bind(reservation_id.0)
+        .try_map(|row: PgRow| {
+            Ok(ReservedStock {
+                product_id: ProductId(row.try_get("product_id")?),
+                warehouse_id: WarehouseId(row.try_get("warehouse_id")?),
+                quantity: row.try_get("quantity")?,
+            })
+        })
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| InventoryRepositoryError::InventoryNotReadError(e.to_string()))?;
+
+        Ok(Some(StockReservation::restore(
+            reservation_id,
+            order_id,
+            lines,
+            state,
+        )))
+    }
+}
+
+#[async_trait]
+impl<'a> domain::repositories::transactional_repository::TransactionalRepository
+    for PgInventoryRepository<'a>
+{
+    async fn begin_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        self.transactional.begin_transaction().await
+    }
+    async fn commit_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        self.transactional.commit_transaction().await
+    }
+    async fn rollback_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        self.transactional.rollback_transaction().await
+    }
+}
+
+#[async_trait]
+impl<'a> domain::repositories::inventory_repository::InventoryRepository
+    for PgInventoryRepository<'a>
+{
+    async fn find_by_product_id(
+        &self,
+        product_id: ProductId,
+    ) -> Result<Vec<Inventory>, InventoryRepositoryError> {
+        sqlx::query("SELECT * FROM inventories WHERE product_id = $1 ORDER BY warehouse_id")
+            .bind(product_id.0)
+            .try_map(|row: PgRow| {
+                Ok(Inventory::restore(
+                    ProductId(row.try_get("product_id")?),
+                    WarehouseId(row.try_get("warehouse_id")?),
+                    row.try_get("on_hand")?,
+                    row.try_get("reserved")?,
+                    row.try_get("version")?,
+                ))
+            })
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| InventoryRepositoryError::InventoryNotReadError(e.to_string()))
+    }
+
+    async fn save(
+        &self,
+        inventories: Vec<Inventory>,
+    ) -> Result<Vec<Inventory>, InventoryRepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| InventoryRepositoryError::InventoryNotSavedError)?;
+        let inventories = save_inventories(&mut tx, inventories).await?;
+        tx.commit()
+            .await
+            .map_err(|_| InventoryRepositoryError::InventoryNotSavedError)?;
+        Ok(inventories)
+    }
+
+    async fn find_reservation(
+        &self,
+        id: ReservationId,
+    ) -> Result<Option<StockReservation>, InventoryRepositoryError> {
+        self.find_one_reservation("SELECT * FROM stock_reservations WHERE id = $1", id.0)
+            .await
+    }
+
+    async fn find_reservation_by_order_id(
+        &self,
+        order_id: OrderId,
+    ) -> Result<Option<StockReservation>, InventoryRepositoryError> {
+        self.find_one_reservation(
+            "SELECT * FROM stock_reservations WHERE order_id = $1",
+            order_id.0,
+        )
+        .await
+    }
+
+    async fn save_reservation(
+        &self,
+        reservation: StockReservation,
+        inventories: Vec<Inventory>,
+    ) -> Result<Vec<Inventory>, InventoryRepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| InventoryRepositoryError::InventoryNotSavedError)?;
+
+        let updated = sqlx::query("UPDATE stock_reservations SET state = $2 WHERE id = $1")
+            .bind(reservation.id().0)
+            .bind(reservation.state().to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| InventoryRepositoryError::InventoryNotSavedError)?;
+        if updated.rows_affected() == 0 {
+            insert_reservation(&mut tx, &reservation).await?;
+        }
+
+        let inventories = save_inventories(&mut tx, inventories).await?;
+        tx.commit()
+            .await
+            .map_err(|_| InventoryRepositoryError::InventoryNotSavedError)?;
+        Ok(inventories)
+    }
+}
+
+async fn insert_reservation(
+    connection: &mut PgConnection,
+    reservation: &StockReservation,
+) -> Result<(), InventoryRepositoryError> {
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO stock_reservations (id, order_id, state) VALUES ($1, $2, $3)
+        ON CONFLICT (order_id) DO NOTHING
+        "#,
+    )
+    .bind(reservation.id().0)
+    .bind(reservation.order_id().0)
+    .bind(reservation.state().to_string())
+    .execute(&mut *connection)
+    .await
+    .map_err(|_| InventoryRepositoryError::InventoryNotSavedError)?;
+    if inserted.rows_affected() == 0 {
+        return Err(InventoryRepositoryError::ConcurrencyConflict);
+    }
+
+    for line in reservation.lines() {
+        sqlx::query(
+            r#"
+            INSERT INTO stock_reservation_items (reservation_id, product_id, warehouse_id, quantity)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(reservation.id().0)
+        .bind(line.product_id.0)
+        .bind(line.warehouse_id.0)
+        .bind(line.quantity)
+        .execute(&mut *connection)
+        .await
+        .map_err(|_| InventoryRepositoryError::InventoryNotSavedError)?;
+    }
+    Ok(())
+}
+
+/// Each update applies only to the version read, so stock changed meanwhile
+/// is never overwritten. The table constraints keep quantities from going
+/// negative anyway.
+async fn save_inventories(
+    connection: &mut PgConnection,
+    inventories: Vec<Inventory>,
+) -> Result<Vec<Inventory>, InventoryRepositoryError> {
+    let mut saved = vec![];
+    for inventory in inventories {
+        let result = if inventory.version() == 0 {
+            sqlx::query(
+                r#"
+                INSERT INTO inventories (product_id, warehouse_id, on_hand, reserved, version)
+                VALUES ($1, $2, $3, $4, 1)
+                ON CONFLICT (product_id, warehouse_id) DO NOTHING
+                "#,
+            )
+            .bind(inventory.product_id().0)
+            .bind(inventory.warehouse_id().0)
+            .bind(inventory.on_hand())
+            .bind(inventory.reserved())
+            .execute(&mut *connection)
+            .await
+        } else {
+            sqlx::query(
+                r#"
+                UPDATE inventories SET on_hand = $4, reserved = $5, version = version + 1
+                WHERE product_id = $1 AND warehouse_id = $2 AND version = $3
+                "#,
+            )
+            .bind(inventory.product_id().0)
+            .bind(inventory.warehouse_id().0)
+            .bind(inventory.version())
+            .bind(inventory.on_hand())
+            .bind(inventory.reserved())
+            .execute(&mut *connection)
+            .await
+        }
+        .map_err(|_| InventoryRepositoryError::InventoryNotSavedError)?;
+        if result.rows_affected() == 0 {
+            return Err(InventoryRepositoryError::ConcurrencyConflict);
+        }
+
+        let version = inventory.version() + 1;
+        saved.push(inventory.with_version(version));
+    }
+    Ok(saved)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::common::test;
+    use domain::repositories::inventory_repository::InventoryRepository;
+
+    #[tokio::test]
+    async fn saves_a_reservation_with_the_inventories_it_changed() {
+        let product_id = ProductId(Uuid::new_v4());
+        let warehouse_id = WarehouseId(Uuid::new_v4());
+        let order_id = OrderId(Uuid::new_v4());
+        let repository = PgInventoryRepository::new(test::create_sqlx_connection_pool().await);
+        let mut inventory = Inventory::new(product_id.clone(), warehouse_id.clone());
+        inventory.adjust(5).unwrap();
+        let mut inventory = repository.save(vec![inventory]).await.unwrap().remove(0);
+
+        inventory.reserve(2).unwrap();
+        let reservation = StockReservation::new(
+            ReservationId(Uuid::new_v4()),
+            order_id.clone(),
+            vec![ReservedStock {
+                product_id: product_id.clone(),
+                warehouse_id,
+                quantity: 2,
+            }],
+        );
+        repository
+            .save_reservation(reservation.clone(), vec![inventory])
+            .await
+            .unwrap();
+
+        let inventory_from_db = repository.find_by_product_id(product_id).await.unwrap();
+        assert_eq!(3, inventory_from_db[0].available());
+        assert_eq!(2, inventory_from_db[0].version());
+        let reservation_from_db = repository
+            .find_reservation_by_order_id(order_id)
+            .await
+            .unwrap();
+        assert_eq!(Some(reservation), reservation_from_db);
+    }
+
+    #[tokio::test]
+    async fn never_lets_concurrent_reservations_oversell() {
+        let product_id = ProductId(Uuid::new_v4());
+        let repository = PgInventoryRepository::new(test::create_sqlx_connection_pool().await);
+        let mut inventory = Inventory::new(product_id.clone(), WarehouseId(Uuid::new_v4()));
+        inventory.adjust(1).unwrap();
+        let inventory = repository.save(vec![inventory]).await.unwrap().remove(0);
+        let mut first = inventory.clone();
+        let mut second = inventory.clone();
+        first.reserve(1).unwrap();
+        second.reserve(1).unwrap();
+
+        let (first, second) =
+            tokio::join!(repository.save(vec![first]), repository.save(vec![second]));
+
+        let conflicts = [&first, &second]
+            .iter()
+            .filter(|result| matches!(result, Err(InventoryRepositoryError::ConcurrencyConflict)))
+            .count();
+        assert_eq!(1, conflicts);
+        let oversold = Inventory::restore(
+            product_id.clone(),
+            inventory.warehouse_id().clone(),
+            1,
+            2,
+            2,
+        );
+        assert!(matches!(
+            repository.save(vec![oversold]).await,
+            Err(InventoryRepositoryError::InventoryNotSavedError)
+        ));
+        let inventory_from_db = repository.find_by_product_id(product_id).await.unwrap();
+        assert_eq!(0, inventory_from_db[0].available());
+    }
+}
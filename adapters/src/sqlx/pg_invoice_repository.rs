@@ -0,0 +1,291 @@
+use async_trait::async_trait;
+use domain::{
+    entities::invoice::{Invoice, InvoiceKind, InvoiceLine, InvoiceNumber, InvoiceTotals},
+    repositories::invoice_repository::InvoiceRepositoryError,
+    value_objects::{CustomerId, InvoiceId, OrderId, ProductId},
+};
+use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+use uuid::Uuid;
+
+const UNIQUE_VIOLATION: &str = "23505";
+
+pub struct PgInvoiceRepository {
+    pool: Pool<Postgres>,
+}
+
+impl PgInvoiceRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    async fn with_lines(&self, row: PgRow) -> Result<Invoice, InvoiceRepositoryError> {
+        let invoice_id: Uuid = row
+            .try_get("id")
+            .map_err(|e| InvoiceRepositoryError::InvoiceNotReadError(e.to_string()))?;
+        let lines =
+            sqlx::query("SELECT * FROM invoice_lines WHERE invoice_id = $1 ORDER BY position")
+                .bind(invoice_id)
+                .try_map(|row: PgRow| {
+                    Ok(InvoiceLine {
+                        product_id: row.try_get::<Option<Uuid>, _>("product_id")?.map(ProductId),
+                        quantity: row.try_get("quantity")?,
+                        unit_price: row.try_get("unit_price")?,
+                        amount: row.try_get("amount")?,
+                        tax_rate: row.try_get("tax_rate")?,
+                        tax_amount: row.try_get("tax_amount")?,
+                    })
+                })
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| InvoiceRepositoryError::InvoiceNotReadError(e.to_string()))?;
+
+        invoice_from_row(row, lines)
+            .map_err(|e| InvoiceRepositoryError::InvoiceNotReadError(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl domain::repositories::invoice_repository::InvoiceRepository for PgInvoiceRepository {
+    /// The number is taken from `invoice_sequences` in the transaction
+    /// inserting the invoice: the row stays locked until commit, and a
+    /// rollback gives the number back.
+    async fn issue(&self, invoice: Invoice) -> Result<Invoice, InvoiceRepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| InvoiceRepositoryError::InvoiceNotSavedError)?;
+        let sequence: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO invoice_sequences (kind, fiscal_year, last_number) VALUES ($1, $2, 1)
+            ON CONFLICT (kind, fiscal_year)
+            DO UPDATE SET last_number = invoice_sequences.last_number + 1
+            RETURNING last_number
+            "#,
+        )
+        .bind(invoice.kind().to_string())
+        .bind(invoice.fiscal_year())
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|_| InvoiceRepositoryError::InvoiceNotSavedError)?;
+        let invoice = invoice.with_sequence(sequence);
+
+        let totals = invoice.totals();
+        sqlx::query(
+            r#"
+            INSERT INTO invoices (
+                id, order_id, customer_id, kind, fiscal_year, sequence, billing_address,
+                credited_invoice_number, refund_key, subtotal, discount_total, tax_total, total,
+                issued_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            "#,
+        )
+        .bind(invoice.id().0)
+        .bind(invoice.order_id().0)
+        .bind(invoice.customer_id().0)
+        .bind(invoice.kind().to_string())
+        .bind(invoice.fiscal_year())
+        .bind(sequence)
+        .bind(invoice.billing_address())
+        .bind(invoice.credited_invoice().map(|number| number.to_string()))
+        .bind(invoice.refund_key())
+        .bind(totals.subtotal)
+        .bind(totals.discount_total)
+        .bind(totals.tax_total)
+        .bind(totals.total)
+        .bind(invoice.issued_at())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e.as_database_error().and_then(|e| e.code()) {
+            Some(code) if code == UNIQUE_VIOLATION => {
+                InvoiceRepositoryError::InvoiceAlreadyIssuedError
+            }
+            _ => InvoiceRepositoryError::InvoiceNotSavedError,
+        })?;
+
+        for (position, line) in invoice.lines().iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO invoice_lines (
+                    invoice_id, position, product_id, quantity, unit_price, amount, tax_rate,
+                    tax_amount
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#,
+            )
+            .bind(invoice.id().0)
+            .bind(position as i32)
+            .bind(line.product_id.as_ref().map(|product_id| product_id.0))
+            .bind(line.quantity)
+            .bind(line.unit_price)
+            .bind(line.amount)
+            .bind(line.tax_rate)
+            .bind(line.tax_amount)
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| InvoiceRepositoryError::InvoiceNotSavedError)?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|_| InvoiceRepositoryError::InvoiceNotSavedError)?;
+        Ok(invoice)
+    }
+
+    async fn find_by_order_id(
+        &self,
+        order_id: OrderId,
+    ) -> Result<Vec<Invoice>, InvoiceRepositoryError> {
+        let rows = sqlx::query("SELECT * FROM invoices WHERE order_id = $1 ORDER BY issued_at, id")
+            .bind(order_id.0)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| InvoiceRepositoryError::InvoiceNotReadError(e.to_string()))?;
+        let mut invoices = vec![];
+        for row in rows {
+            invoices.push(self.with_lines(row).await?);
+        }
+        Ok(invoices)
+    }
+}
+
+fn invoice_from_row(row: PgRow, lines: Vec<InvoiceLine>) -> Result<Invoice, sqlx::Error> {
+    let decode = |index: &str| {
+        let index = index.to_string();
+        move |error: String| sqlx::Error::ColumnDecode {
+            index,
+            source: error.into(),
+        }
+    };
+    let kind: InvoiceKind = row
+        .try_get::<&str, _>("kind")?
+        .parse()
+        .map_err(decode("kind"))?;
+    let credited_invoice = row
+        .try_get::<Option<&str>, _>("credited_invoice_number")?
+        .map(str::parse)
+        .transpose()
+        .map_err(decode("credited_invoice_number"))?;
+    Ok(Invoice::restore(
+        InvoiceId(row.try_get("id")?),
+        OrderId(row.try_get("order_id")?),
+        CustomerId(row.try_get("customer_id")?),
+        InvoiceNumber {
+            kind,
+            fiscal_year: row.try_get("fiscal_year")?,
+            sequence: row.try_get("sequence")?,
+        },
+        row.try_get("billing_address")?,
+        credited_invoice,
+        row.try_get("refund_key")?,
+        lines,
+        InvoiceTotals {
+            subtotal: row.try_get("subtotal")?,
+            discount_total: row.try_get("discount_total")?,
+            tax_total: row.try_get("tax_total")?,
+            total: row.try_get("total")?,
+        },
+        row.try_get("issued_at")?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::common::test;
+    use chrono::{TimeZone, Utc};
+    use domain::{
+        entities::order::Order, repositories::invoice_repository::InvoiceRepository,
+        value_objects::OrderItem,
+    };
+
+    #[tokio::test]
+    async fn issues_an_invoice_and_its_credit_notes_once() {
+        let repository = PgInvoiceRepository::new(test::create_sqlx_connection_pool().await);
+        let order = order();
+        let invoice = repository
+            .issue(Invoice::for_order(
+                InvoiceId(Uuid::new_v4()),
+                &order,
+                Utc::now(),
+            ))
+            .await
+            .unwrap();
+        let credit_note = Invoice::credit_note(
+            InvoiceId(Uuid::new_v4()),
+            &invoice,
+            "return_1",
+            5.0,
+            Utc::now(),
+        )
+        .unwrap();
+        let credit_note = repository.issue(credit_note.clone()).await.unwrap();
+
+        assert!(matches!(
+            repository
+                .issue(Invoice::for_order(
+                    InvoiceId(Uuid::new_v4()),
+                    &order,
+                    Utc::now(),
+                ))
+                .await,
+            Err(InvoiceRepositoryError::InvoiceAlreadyIssuedError)
+        ));
+        let invoices = repository
+            .find_by_order_id(order.id().clone())
+            .await
+            .unwrap();
+        assert_eq!(2, invoices.len());
+        assert_eq!(invoice.number(), invoices[0].number());
+        assert_eq!(invoice.lines(), invoices[0].lines());
+        assert_eq!(
+            credit_note.credited_invoice(),
+            invoices[1].credited_invoice()
+        );
+        assert_eq!(Some("return_1"), invoices[1].refund_key());
+    }
+
+    /// Invoices of 1999 are issued only here, so their numbers follow each
+    /// other even with earlier runs.
+    #[tokio::test]
+    async fn numbers_invoices_issued_concurrently_without_gaps() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let issued_at = Utc.with_ymd_and_hms(1999, 6, 1, 12, 0, 0).unwrap();
+        let issues = (0..8).map(|_| {
+            let repository = PgInvoiceRepository::new(pool.clone());
+            tokio::spawn(async move {
+                repository
+                    .issue(Invoice::for_order(
+                        InvoiceId(Uuid::new_v4()),
+                        &order(),
+                        issued_at,
+                    ))
+                    .await
+                    .unwrap()
+            })
+        });
+
+        let mut sequences = vec![];
+        for issue in issues.collect::<Vec<_>>() {
+            sequences.push(issue.await.unwrap().number().unwrap().sequence);
+        }
+
+        sequences.sort();
+        let first = sequences[0];
+        assert_eq!((first..first + 8).collect::<Vec<_>>(), sequences);
+    }
+
+    fn order() -> Order {
+        let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()));
+        order
+            .add(OrderItem {
+                price: 9.99,
+                quantity: 2,
+                product_id: ProductId(Uuid::new_v4()),
+            })
+            .unwrap();
+        order
+    }
+}
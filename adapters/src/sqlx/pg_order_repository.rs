@@ -1,14 +1,26 @@
-use super::pg_transactional_repository::PgTransactionalRepository;
+use super::{invalid, pg_transactional_repository::PgTransactionalRepository};
 use async_trait::async_trait;
 use domain::{
-    entities::order::Order,
+    entities::{
+        currency::OrderCurrency,
+        order::{Order, OrderAddresses, OrderEvent, OrderStatus},
+        order_lines::OrderLines,
+        outbox::PromotionPayload,
+        promotion::AppliedPromotion,
+        shipping_method::{OrderShipping, ShippingRate},
+        tax::{TaxRate, Taxes},
+    },
     repositories::{
         order_repository::OrderRepositoryError,
         transactional_repository::TransactionalRepositoryError,
     },
-    value_objects::{CustomerId, OrderId, OrderItem, ProductId},
+    value_objects::{
+        Address, CountryCode, Currency, CustomerId, OrderId, OrderItem, PostalCode, ProductId,
+        ShippingMethodId, ERASED,
+    },
 };
-use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+use sqlx::{postgres::PgRow, PgConnection, PgExecutor, Pool, Postgres, Row};
+use uuid::Uuid;
 
 pub struct PgOrderRepository<'a> {
     pool: Pool<Postgres>,
@@ -44,60 +56,460 @@ impl<'a> domain::repositories::transactional_repository::TransactionalRepository
 impl<'a> domain::repositories::order_repository::OrderRepository for PgOrderRepository<'a> {
     async fn find_by_id(&self, id: OrderId) -> Result<Option<Order>, OrderRepositoryError> {
         let uuid = id.0;
-        let mut order = sqlx::query("SELECT * FROM orders where id = $1")
-            .bind(uuid)
-            .try_map(|row: PgRow| {
-                Ok(Order {
-                    id: OrderId(uuid),
-                    customer_id: CustomerId(row.try_get("customer_id")?),
-                    order_items: vec![],
+        let (customer_id, addresses, status, pricing_mode, currency, shipping, version) =
+            sqlx::query("SELECT * FROM orders where id = $1")
+                .bind(uuid)
+                .try_map(|row: PgRow| {
+                    Ok((
+                        CustomerId(row.try_get("customer_id")?),
+                        order_addresses_from_row(&row)?,
+                        order_status_from_row(&row)?,
+                        row.try_get::<String, _>("pricing_mode")?,
+                        order_currency_from_row(&row)?,
+                        order_shipping_from_row(&row)?,
+                        row.try_get("version")?,
+                    ))
                 })
-            })
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|_| OrderRepositoryError::OrderNotFoundError)?;
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| match e {
+                    sqlx::Error::ColumnDecode { .. } => {
+                        OrderRepositoryError::OrderNotReadError(e.to_string())
+                    }
+                    _ => OrderRepositoryError::OrderNotFoundError,
+                })?;
 
-        let order_items = sqlx::query("SELECT * FROM order_items WHERE order_id = $1")
-            .bind(uuid)
-            .try_map(|row: PgRow| {
-                Ok(OrderItem {
-                    price: row.try_get("price")?,
-                    quantity: row.try_get("quantity")?,
-                    product_id: ProductId(row.try_get("product_id")?),
-                })
-            })
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|_| OrderRepositoryError::OrderItemsNotReadError)?;
+        let order_items = find_order_items(&self.pool, uuid).await?;
+        let order_lines = OrderLines::try_from(order_items)
+            .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))?;
+        let promotions = find_order_promotions(&self.pool, uuid).await?;
+        let taxes = Taxes {
+            pricing_mode: pricing_mode
+                .parse()
+                .map_err(OrderRepositoryError::OrderNotReadError)?,
+            rates: find_order_tax_rates(&self.pool, uuid).await?,
+        };
 
-        order.add_multiple(order_items);
+        let order = Order::restore(OrderId(uuid), customer_id, order_lines, addresses, version)
+            .with_status(status)
+            .with_promotions(promotions)
+            .with_taxes(taxes)
+            .with_currency(currency);
+        Ok(Some(match shipping {
+            Some(shipping) => order.with_shipping(shipping),
+            None => order,
+        }))
+    }
+
+    async fn find_by_customer_id(
+        &self,
+        customer_id: CustomerId,
+    ) -> Result<Vec<Order>, OrderRepositoryError> {
+        let order_ids: Vec<Uuid> =
+            sqlx::query_scalar("SELECT id FROM orders WHERE customer_id = $1 ORDER BY id")
+                .bind(customer_id.0)
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))?;
 
-        return Ok(Some(order));
+        let mut orders = Vec::with_capacity(order_ids.len());
+        for order_id in order_ids {
+            if let Some(order) = self.find_by_id(OrderId(order_id)).await? {
+                orders.push(order);
+            }
+        }
+        Ok(orders)
     }
 
     async fn save(&self, order: Order) -> Result<Order, OrderRepositoryError> {
-        sqlx::query("INSERT INTO orders (id, customer_id) VALUES ($1, $2)")
-            .bind(order.id.0)
-            .bind(order.customer_id.0)
-            .execute(&self.pool)
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| OrderRepositoryError::OrderNotSavedError)?;
+        let shipping = order.addresses().map(|addresses| &addresses.shipping);
+        let billing = order.addresses().map(|addresses| &addresses.billing);
+        let shipping_method = order.shipping();
+        let rate_amounts = shipping_method.map(|shipping| shipping.rate.amounts());
+        sqlx::query(
+            r#"
+            INSERT INTO orders (
+                id, customer_id, version, status,
+                shipping_street, shipping_city, shipping_zip_code, shipping_country,
+                billing_street, billing_city, billing_zip_code, billing_country,
+                currency, exchange_rate,
+                shipping_method_id, shipping_carrier, shipping_rate_kind, shipping_amount,
+                shipping_per_kg, shipping_threshold, shipping_weight
+            )
+            VALUES (
+                $1, $2, 1, $11, $3, $4, $5, $6, $7, $8, $9, $10, $12, $13,
+                $14, $15, $16, $17, $18, $19, $20
+            )
+            "#,
+        )
+        .bind(order.id().0)
+        .bind(order.customer_id().0)
+        .bind(shipping.map(|address| &address.street))
+        .bind(shipping.map(|address| &address.city))
+        .bind(shipping.map(|address| address.zip_code.as_str()))
+        .bind(shipping.map(|address| address.country.as_str()))
+        .bind(billing.map(|address| &address.street))
+        .bind(billing.map(|address| &address.city))
+        .bind(billing.map(|address| address.zip_code.as_str()))
+        .bind(billing.map(|address| address.country.as_str()))
+        .bind(order.status().to_string())
+        .bind(order.currency().currency.as_str())
+        .bind(order.currency().exchange_rate)
+        .bind(shipping_method.map(|shipping| shipping.shipping_method_id.0))
+        .bind(shipping_method.map(|shipping| &shipping.carrier))
+        .bind(shipping_method.map(|shipping| shipping.rate.kind()))
+        .bind(rate_amounts.map(|(amount, _, _)| amount))
+        .bind(rate_amounts.and_then(|(_, per_kg, _)| per_kg))
+        .bind(rate_amounts.and_then(|(_, _, threshold)| threshold))
+        .bind(shipping_method.map(|shipping| shipping.weight))
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| OrderRepositoryError::OrderNotSavedError)?;
+        let mut changes = order.changes_since(&[]);
+        changes.extend(order.promotions_since(&[]));
+        changes.extend(order.taxes_change_since(&Taxes::default()));
+        apply_changes(&mut tx, order.id(), changes).await?;
+        tx.commit()
             .await
             .map_err(|_| OrderRepositoryError::OrderNotSavedError)?;
-        Ok(order)
+
+        Ok(order.with_version(1))
     }
 
     async fn update(&self, order: Order) -> Result<Order, OrderRepositoryError> {
-        for order_item in &order.order_items {
-            sqlx::query("INSERT INTO order_items (order_id, product_id, quantity, price) VALUES ($1, $2, $3, $4)")
-                .bind(order.id.0)
-                .bind(order_item.product_id.0)
-                .bind(order_item.quantity)
-                .bind(order_item.price)
-                .execute(&self.pool)
-                .await
-                .map_err(|_| OrderRepositoryError::OrderNotSavedError)?;
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| OrderRepositoryError::OrderNotSavedError)?;
+        update_order(&mut tx, &order).await?;
+        tx.commit()
+            .await
+            .map_err(|_| OrderRepositoryError::OrderNotSavedError)?;
+
+        let version = order.version() + 1;
+        Ok(order.with_version(version))
+    }
+}
+
+/// Stores the changes of the order, on the caller's transaction. Fails with
+/// `ConcurrencyConflict` if the stored order is no longer at `order.version`.
+pub(crate) async fn update_order(
+    connection: &mut PgConnection,
+    order: &Order,
+) -> Result<(), OrderRepositoryError> {
+    // Also locks the order, so the items read below can't change until commit.
+    let result = sqlx::query(
+        "UPDATE orders SET version = version + 1, status = $3 WHERE id = $1 AND version = $2",
+    )
+    .bind(order.id().0)
+    .bind(order.version())
+    .bind(order.status().to_string())
+    .execute(&mut *connection)
+    .await
+    .map_err(|_| OrderRepositoryError::OrderNotSavedError)?;
+    if result.rows_affected() == 0 {
+        let exists = sqlx::query("SELECT 1 FROM orders WHERE id = $1")
+            .bind(order.id().0)
+            .fetch_optional(&mut *connection)
+            .await
+            .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))?
+            .is_some();
+        if !exists {
+            return Err(OrderRepositoryError::OrderNotFoundError);
         }
-        Ok(order)
+        return Err(OrderRepositoryError::ConcurrencyConflict);
     }
+
+    let stored_items = find_order_items(&mut *connection, order.id().0).await?;
+    let stored_promotions = find_order_promotions(&mut *connection, order.id().0).await?;
+    // The pricing mode is chosen when the order is created.
+    let stored_taxes = Taxes {
+        pricing_mode: order.taxes().pricing_mode,
+        rates: find_order_tax_rates(&mut *connection, order.id().0).await?,
+    };
+    let mut changes = order.changes_since(&stored_items);
+    changes.extend(order.promotions_since(&stored_promotions));
+    changes.extend(order.taxes_change_since(&stored_taxes));
+    apply_changes(connection, order.id(), changes).await
+}
+
+/// Replaces street and city of the addresses recorded in the customer's
+/// orders with `ERASED`, on the caller's transaction. Items and prices are kept.
+pub(crate) async fn erase_order_addresses(
+    connection: &mut PgConnection,
+    customer_id: &CustomerId,
+) -> Result<(), OrderRepositoryError> {
+    sqlx::query(
+        r#"
+        UPDATE orders
+        SET shipping_street = $2, shipping_city = $2, billing_street = $2, billing_city = $2
+        WHERE customer_id = $1 AND shipping_street IS NOT NULL
+        "#,
+    )
+    .bind(customer_id.0)
+    .bind(ERASED)
+    .execute(connection)
+    .await
+    .map_err(|_| OrderRepositoryError::OrderNotSavedError)?;
+    Ok(())
+}
+
+/// Moves the order to its next version on the caller's transaction, so that
+/// records checked against the order at `version` are written at most once.
+/// Returns whether the order was still at `version`.
+pub(crate) async fn bump_order_version(
+    connection: &mut PgConnection,
+    order_id: &OrderId,
+    version: i64,
+) -> Result<bool, sqlx::Error> {
+    let result =
+        sqlx::query("UPDATE orders SET version = version + 1 WHERE id = $1 AND version = $2")
+            .bind(order_id.0)
+            .bind(version)
+            .execute(connection)
+            .await?;
+    Ok(result.rows_affected() == 1)
+}
+
+fn order_status_from_row(row: &PgRow) -> Result<OrderStatus, sqlx::Error> {
+    row.try_get::<&str, _>("status")?
+        .parse()
+        .map_err(|error: String| sqlx::Error::ColumnDecode {
+            index: "status".to_string(),
+            source: error.into(),
+        })
+}
+
+fn order_currency_from_row(row: &PgRow) -> Result<OrderCurrency, sqlx::Error> {
+    Ok(OrderCurrency {
+        currency: Currency::parse(row.try_get("currency")?).map_err(invalid("currency"))?,
+        exchange_rate: row.try_get("exchange_rate")?,
+    })
+}
+
+fn order_shipping_from_row(row: &PgRow) -> Result<Option<OrderShipping>, sqlx::Error> {
+    let Some(shipping_method_id) = row.try_get::<Option<Uuid>, _>("shipping_method_id")? else {
+        return Ok(None);
+    };
+    let rate = ShippingRate::parse(
+        row.try_get("shipping_rate_kind")?,
+        row.try_get("shipping_amount")?,
+        row.try_get("shipping_per_kg")?,
+        row.try_get("shipping_threshold")?,
+    )
+    .map_err(|error| sqlx::Error::ColumnDecode {
+        index: "shipping_rate_kind".to_string(),
+        source: error.into(),
+    })?;
+    Ok(Some(OrderShipping {
+        shipping_method_id: ShippingMethodId(shipping_method_id),
+        carrier: row.try_get("shipping_carrier")?,
+        rate,
+        weight: row.try_get("shipping_weight")?,
+    }))
+}
+
+fn order_addresses_from_row(row: &PgRow) -> Result<Option<OrderAddresses>, sqlx::Error> {
+    let (Some(shipping), Some(billing)) = (
+        address_from_row(row, "shipping")?,
+        address_from_row(row, "billing")?,
+    ) else {
+        return Ok(None);
+    };
+    Ok(Some(OrderAddresses { shipping, billing }))
+}
+
+/// Reads the address stored in the columns starting with `prefix`, if any.
+fn address_from_row(row: &PgRow, prefix: &str) -> Result<Option<Address>, sqlx::Error> {
+    let column = |name: &str| format!("{}_{}", prefix, name);
+    let Some(country) = row.try_get::<Option<&str>, _>(column("country").as_str())? else {
+        return Ok(None);
+    };
+    let country = CountryCode::parse(country).map_err(invalid(column("country")))?;
+    Ok(Some(Address {
+        street: row.try_get(column("street").as_str())?,
+        city: row.try_get(column("city").as_str())?,
+        zip_code: PostalCode::parse(row.try_get(column("zip_code").as_str())?, &country)
+            .map_err(invalid(column("zip_code")))?,
+        country,
+    }))
+}
+
+async fn find_order_items<'e>(
+    executor: impl PgExecutor<'e>,
+    order_id: Uuid,
+) -> Result<Vec<OrderItem>, OrderRepositoryError> {
+    sqlx::query("SELECT * FROM order_items WHERE order_id = $1")
+        .bind(order_id)
+        .try_map(|row: PgRow| {
+            Ok(OrderItem {
+                price: row.try_get("price")?,
+                quantity: row.try_get("quantity")?,
+                product_id: ProductId(row.try_get("product_id")?),
+            })
+        })
+        .fetch_all(executor)
+        .await
+        .map_err(|_| OrderRepositoryError::OrderItemsNotReadError)
+}
+
+/// Promotions in the order they were applied.
+async fn find_order_promotions<'e>(
+    executor: impl PgExecutor<'e>,
+    order_id: Uuid,
+) -> Result<Vec<AppliedPromotion>, OrderRepositoryError> {
+    let payloads =
+        sqlx::query("SELECT * FROM order_promotions WHERE order_id = $1 ORDER BY position")
+            .bind(order_id)
+            .try_map(|row: PgRow| {
+                Ok(PromotionPayload {
+                    code: row.try_get("code")?,
+                    kind: row.try_get("kind")?,
+                    value: row.try_get("value")?,
+                    product_id: row
+                        .try_get::<Option<Uuid>, _>("product_id")?
+                        .map(|product_id| product_id.to_string()),
+                    buy: row.try_get("buy_quantity")?,
+                    get: row.try_get("get_quantity")?,
+                    min_spend: row.try_get("min_spend")?,
+                })
+            })
+            .fetch_all(executor)
+            .await
+            .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))?;
+    payloads
+        .iter()
+        .map(AppliedPromotion::try_from)
+        .collect::<Result<_, _>>()
+        .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))
+}
+
+async fn find_order_tax_rates<'e>(
+    executor: impl PgExecutor<'e>,
+    order_id: Uuid,
+) -> Result<Vec<TaxRate>, OrderRepositoryError> {
+    sqlx::query("SELECT * FROM order_tax_rates WHERE order_id = $1 ORDER BY product_id")
+        .bind(order_id)
+        .try_map(|row: PgRow| {
+            Ok(TaxRate {
+                product_id: ProductId(row.try_get("product_id")?),
+                category: row.try_get::<&str, _>("category")?.parse().map_err(
+                    |error: String| sqlx::Error::ColumnDecode {
+                        index: "category".to_string(),
+                        source: error.into(),
+                    },
+                )?,
+                rate: row.try_get("rate")?,
+            })
+        })
+        .fetch_all(executor)
+        .await
+        .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))
+}
+
+/// Replaces the pricing mode and the tax rates of the order.
+async fn replace_taxes(
+    connection: &mut PgConnection,
+    order_id: &OrderId,
+    taxes: &Taxes,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE orders SET pricing_mode = $2 WHERE id = $1")
+        .bind(order_id.0)
+        .bind(taxes.pricing_mode.to_string())
+        .execute(&mut *connection)
+        .await?;
+    sqlx::query("DELETE FROM order_tax_rates WHERE order_id = $1")
+        .bind(order_id.0)
+        .execute(&mut *connection)
+        .await?;
+    for rate in &taxes.rates {
+        sqlx::query(
+            "INSERT INTO order_tax_rates (order_id, product_id, category, rate) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(order_id.0)
+        .bind(rate.product_id.0)
+        .bind(rate.category.to_string())
+        .bind(rate.rate)
+        .execute(&mut *connection)
+        .await?;
+    }
+    Ok(())
+}
+
+async fn apply_changes(
+    connection: &mut PgConnection,
+    order_id: &OrderId,
+    changes: Vec<OrderEvent>,
+) -> Result<(), OrderRepositoryError> {
+    for change in changes {
+        let query = match change {
+            OrderEvent::ProductAddedToOrder {
+                product_id,
+                price,
+                quantity,
+            } => sqlx::query(
+                "INSERT INTO order_items (order_id, product_id, quantity, price) VALUES ($1, $2, $3, $4)",
+            )
+            .bind(order_id.0)
+            .bind(product_id.0)
+            .bind(quantity)
+            .bind(price),
+            OrderEvent::ProductRemovedFromOrder { product_id } => {
+                sqlx::query("DELETE FROM order_items WHERE order_id = $1 AND product_id = $2")
+                    .bind(order_id.0)
+                    .bind(product_id.0)
+            }
+            OrderEvent::ProductQuantityChanged {
+                product_id,
+                quantity,
+            } => sqlx::query(
+                "UPDATE order_items SET quantity = $3 WHERE order_id = $1 AND product_id = $2",
+            )
+            .bind(order_id.0)
+            .bind(product_id.0)
+            .bind(quantity),
+            OrderEvent::PromotionApplied { promotion } => {
+                let payload = PromotionPayload::from(&promotion);
+                sqlx::query(
+                    r#"
+                    INSERT INTO order_promotions (
+                        order_id, code, kind, value, product_id, buy_quantity, get_quantity,
+                        min_spend, position
+                    )
+                    SELECT $1, $2, $3, $4, $5, $6, $7, $8, COUNT(*)::INT
+                    FROM order_promotions WHERE order_id = $1
+                    "#,
+                )
+                .bind(order_id.0)
+                .bind(payload.code)
+                .bind(payload.kind)
+                .bind(payload.value)
+                .bind(promotion.discount.product_id().map(|product_id| product_id.0))
+                .bind(payload.buy)
+                .bind(payload.get)
+                .bind(payload.min_spend)
+            }
+            OrderEvent::TaxesChanged { taxes } => {
+                replace_taxes(connection, order_id, &taxes)
+                    .await
+                    .map_err(|_| OrderRepositoryError::OrderNotSavedError)?;
+                continue;
+            }
+            // The status is written with the order row.
+            OrderEvent::OrderCreated { .. } | OrderEvent::OrderStatusChanged { .. } => continue,
+        };
+        query
+            .execute(&mut *connection)
+            .await
+            .map_err(|_| OrderRepositoryError::OrderNotSavedError)?;
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -105,7 +517,13 @@ mod test {
 
     use super::*;
     use crate::common::test;
-    use domain::repositories::order_repository::OrderRepository;
+    use domain::{
+        entities::{
+            promotion::Discount,
+            tax::{PricingMode, TaxCategory},
+        },
+        repositories::order_repository::OrderRepository,
+    };
     use uuid::Uuid;
 
     #[tokio::test]
@@ -126,7 +544,10 @@ mod test {
             .await
             .unwrap()
             .unwrap();
-        assert_eq!(domain::value_objects::OrderId(order_id), order_from_db.id);
+        assert_eq!(
+            &domain::value_objects::OrderId(order_id),
+            order_from_db.id()
+        );
     }
 
     #[tokio::test]
@@ -135,22 +556,21 @@ mod test {
         let customer_id = Uuid::new_v4();
         let product_id = Uuid::new_v4();
         let repository = PgOrderRepository::new(test::create_sqlx_connection_pool().await);
-        let _ = repository
+        let mut order = repository
             .save(domain::entities::order::Order::create(
                 domain::value_objects::OrderId(order_id),
                 domain::value_objects::CustomerId(customer_id),
             ))
-            .await;
+            .await
+            .unwrap();
 
-        let mut order = domain::entities::order::Order::create(
-            domain::value_objects::OrderId(order_id),
-            domain::value_objects::CustomerId(customer_id),
-        );
-        order.add_multiple(vec![domain::value_objects::OrderItem {
-            price: 10.0,
-            quantity: 1,
-            product_id: domain::value_objects::ProductId(product_id),
-        }]);
+        order
+            .add_multiple(vec![domain::value_objects::OrderItem {
+                price: 10.0,
+                quantity: 1,
+                product_id: domain::value_objects::ProductId(product_id),
+            }])
+            .unwrap();
         let result = repository.update(order).await;
 
         assert!(result.is_ok());
@@ -159,6 +579,231 @@ mod test {
             .await
             .unwrap()
             .unwrap();
-        assert_eq!(1, order_from_db.order_items.len());
+        assert_eq!(1, order_from_db.order_items().len());
+    }
+
+    #[tokio::test]
+    async fn persists_changes_to_order_items() {
+        let order_id = domain::value_objects::OrderId(Uuid::new_v4());
+        let repository = PgOrderRepository::new(test::create_sqlx_connection_pool().await);
+        let kept = order_item();
+        let removed = order_item();
+        let mut order = domain::entities::order::Order::create(
+            order_id.clone(),
+            domain::value_objects::CustomerId(Uuid::new_v4()),
+        );
+        order
+            .add_multiple(vec![kept.clone(), removed.clone()])
+            .unwrap();
+        let mut order = repository.save(order).await.unwrap();
+
+        let added = order_item();
+        order.add(added.clone()).unwrap();
+        order.add(kept.clone()).unwrap();
+        order.remove(&removed.product_id).unwrap();
+        let mut order = repository.update(order).await.unwrap();
+        order.add(added.clone()).unwrap();
+        repository.update(order).await.unwrap();
+
+        let mut order_items = repository
+            .find_by_id(order_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .order_items()
+            .to_vec();
+        order_items.sort_by_key(|item| item.product_id.0 == kept.product_id.0);
+        assert_eq!(
+            vec![
+                domain::value_objects::OrderItem {
+                    quantity: 2,
+                    ..added
+                },
+                domain::value_objects::OrderItem {
+                    quantity: 2,
+                    ..kept
+                },
+            ],
+            order_items
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_concurrent_updates_of_an_order() {
+        let order_id = domain::value_objects::OrderId(Uuid::new_v4());
+        let repository = PgOrderRepository::new(test::create_sqlx_connection_pool().await);
+        repository
+            .save(domain::entities::order::Order::create(
+                order_id.clone(),
+                domain::value_objects::CustomerId(Uuid::new_v4()),
+            ))
+            .await
+            .unwrap();
+        let mut first = repository
+            .find_by_id(order_id.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        let mut second = repository
+            .find_by_id(order_id.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        first.add(order_item()).unwrap();
+        second.add(order_item()).unwrap();
+
+        let (first, second) = tokio::join!(repository.update(first), repository.update(second));
+
+        let conflicts = [&first, &second]
+            .iter()
+            .filter(|result| matches!(result, Err(OrderRepositoryError::ConcurrencyConflict)))
+            .count();
+        assert_eq!(1, conflicts);
+        let order_from_db = repository.find_by_id(order_id).await.unwrap().unwrap();
+        assert_eq!(1, order_from_db.order_items().len());
+        assert_eq!(2, order_from_db.version());
+    }
+
+    #[tokio::test]
+    async fn does_not_update_a_missing_order() {
+        let repository = PgOrderRepository::new(test::create_sqlx_connection_pool().await);
+        let order = domain::entities::order::Order::create(
+            domain::value_objects::OrderId(Uuid::new_v4()),
+            domain::value_objects::CustomerId(Uuid::new_v4()),
+        );
+
+        assert!(matches!(
+            repository.update(order).await,
+            Err(OrderRepositoryError::OrderNotFoundError)
+        ));
+    }
+
+    #[tokio::test]
+    async fn persists_the_status_of_an_order() {
+        let order_id = domain::value_objects::OrderId(Uuid::new_v4());
+        let repository = PgOrderRepository::new(test::create_sqlx_connection_pool().await);
+        let mut order = repository
+            .save(domain::entities::order::Order::create(
+                order_id.clone(),
+                domain::value_objects::CustomerId(Uuid::new_v4()),
+            ))
+            .await
+            .unwrap();
+
+        order.place().unwrap();
+        order.confirm().unwrap();
+        repository.update(order).await.unwrap();
+
+        let order_from_db = repository.find_by_id(order_id).await.unwrap().unwrap();
+        assert_eq!(OrderStatus::Confirmed, order_from_db.status());
+    }
+
+    #[tokio::test]
+    async fn persists_the_promotions_of_an_order() {
+        let order_id = domain::value_objects::OrderId(Uuid::new_v4());
+        let repository = PgOrderRepository::new(test::create_sqlx_connection_pool().await);
+        let mut order = domain::entities::order::Order::create(
+            order_id.clone(),
+            domain::value_objects::CustomerId(Uuid::new_v4()),
+        );
+        order.add(order_item()).unwrap();
+        let mut order = repository.save(order).await.unwrap();
+
+        for (code, discount) in [
+            ("TEN", Discount::Percentage(10.0)),
+            ("SHIP", Discount::FreeShipping),
+        ] {
+            order
+                .apply_promotion(AppliedPromotion {
+                    code: domain::value_objects::CouponCode::parse(code).unwrap(),
+                    discount,
+                    min_spend: Some(5.0),
+                })
+                .unwrap();
+        }
+        let order = repository.update(order).await.unwrap();
+
+        let order_from_db = repository.find_by_id(order_id).await.unwrap().unwrap();
+        assert_eq!(order.promotions(), order_from_db.promotions());
+        assert_eq!(9.0, order_from_db.total_price());
+    }
+
+    #[tokio::test]
+    async fn persists_the_taxes_of_an_order() {
+        let order_id = domain::value_objects::OrderId(Uuid::new_v4());
+        let repository = PgOrderRepository::new(test::create_sqlx_connection_pool().await);
+        let item = order_item();
+        let mut order = domain::entities::order::Order::create(
+            order_id.clone(),
+            domain::value_objects::CustomerId(Uuid::new_v4()),
+        )
+        .with_taxes(Taxes::new(PricingMode::Inclusive));
+        order.add(item.clone()).unwrap();
+        let mut order = repository.save(order).await.unwrap();
+
+        order.set_tax_rates(vec![TaxRate {
+            product_id: item.product_id.clone(),
+            category: TaxCategory::Reduced,
+            rate: 25.0,
+        }]);
+        let order = repository.update(order).await.unwrap();
+
+        let order_from_db = repository.find_by_id(order_id).await.unwrap().unwrap();
+        assert_eq!(order.taxes(), order_from_db.taxes());
+        assert_eq!(2.0, order_from_db.tax_lines()[0].amount);
+        assert_eq!(10.0, order_from_db.total_price());
+    }
+
+    #[tokio::test]
+    async fn persists_the_currency_of_an_order() {
+        let order_id = domain::value_objects::OrderId(Uuid::new_v4());
+        let repository = PgOrderRepository::new(test::create_sqlx_connection_pool().await);
+        let currency = OrderCurrency {
+            currency: Currency::Usd,
+            exchange_rate: 1.25,
+        };
+        let order = domain::entities::order::Order::create(
+            order_id.clone(),
+            domain::value_objects::CustomerId(Uuid::new_v4()),
+        )
+        .with_currency(currency);
+        repository.save(order).await.unwrap();
+
+        let order_from_db = repository.find_by_id(order_id).await.unwrap().unwrap();
+        assert_eq!(&currency, order_from_db.currency());
+    }
+
+    #[tokio::test]
+    async fn persists_the_shipping_method_of_an_order() {
+        let order_id = domain::value_objects::OrderId(Uuid::new_v4());
+        let repository = PgOrderRepository::new(test::create_sqlx_connection_pool().await);
+        let shipping = OrderShipping {
+            shipping_method_id: ShippingMethodId(Uuid::new_v4()),
+            carrier: "UPS".to_string(),
+            rate: ShippingRate::WeightBased {
+                base: 3.0,
+                per_kg: 1.5,
+            },
+            weight: 2.0,
+        };
+        let mut order = domain::entities::order::Order::create(
+            order_id.clone(),
+            domain::value_objects::CustomerId(Uuid::new_v4()),
+        )
+        .with_shipping(shipping.clone());
+        order.add(order_item()).unwrap();
+        repository.save(order).await.unwrap();
+
+        let order_from_db = repository.find_by_id(order_id).await.unwrap().unwrap();
+        assert_eq!(Some(&shipping), order_from_db.shipping());
+        assert_eq!(16.0, order_from_db.total_price());
+    }
+
+    fn order_item() -> domain::value_objects::OrderItem {
+        domain::value_objects::OrderItem {
+            price: 10.0,
+            quantity: 1,
+            product_id: domain::value_objects::ProductId(Uuid::new_v4()),
+        }
     }
 }
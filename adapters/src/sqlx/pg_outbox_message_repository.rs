@@ -1,9 +1,11 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use domain::{
-    entities::outbox::OutboxMessage, repositories::outbox_repository::OutboxMessageRepositoryError,
+    entities::outbox::OutboxMessage,
+    repositories::outbox_repository::{OutboxMessageRepositoryError, OutboxPosition},
+    value_objects::CustomerId,
 };
-use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+use sqlx::{postgres::PgRow, PgConnection, PgExecutor, Pool, Postgres, Row};
 use uuid::Uuid;
 
 pub struct PgOutboxMessageRepository {
@@ -24,23 +26,7 @@ impl domain::repositories::outbox_repository::OutboxMessageRepository
         &self,
         message: OutboxMessage,
     ) -> Result<OutboxMessage, OutboxMessageRepositoryError> {
-        sqlx::query(
-            r#"
-            INSERT INTO outbox_messages (id, event_type, event_payload, created_at, processed_at)
-            VALUES ($1, $2, $3, $4, $5) 
-        "#,
-        )
-        .bind(&message.id())
-        .bind(&message.event_type().to_string())
-        .bind(&message.event_payload())
-        .bind(&message.created_at())
-        .bind(&message.processed_at())
-        .execute(&self.pool)
-        .await
-        .map_err(|error| {
-            OutboxMessageRepositoryError::OutboxMessageNotSavedError(error.to_string())
-        })?;
-
+        insert_message(&self.pool, &message).await?;
         Ok(message)
     }
 
@@ -48,30 +34,7 @@ impl domain::repositories::outbox_repository::OutboxMessageRepository
         &self,
     ) -> Result<Option<Vec<OutboxMessage>>, OutboxMessageRepositoryError> {
         let messages = sqlx::query("SELECT * FROM outbox_messages WHERE processed_at IS NULL")
-            .try_map(|row: PgRow| {
-                let id: Uuid = row.try_get("id")?;
-                let event_type_string: String = row.try_get("event_type")?;
-                let event_type =
-                    event_type_string
-                        .parse()
-                        .map_err(|_| sqlx::Error::ColumnDecode {
-                            index: "event_type".to_string(),
-                            source: Box::new(std::io::Error::new(
-                                std::io::ErrorKind::InvalidData,
-                                "Invalid event type",
-                            )),
-                        })?;
-                let event_payload = row.try_get("event_payload")?;
-                let created_at = row.try_get("created_at")?;
-                let processed_at = row.try_get("processed_at")?;
-                Ok(OutboxMessage::new(
-                    id,
-                    event_type,
-                    event_payload,
-                    created_at,
-                    processed_at,
-                ))
-            })
+            .try_map(to_outbox_message)
             .fetch_all(&self.pool)
             .await
             .map_err(|_| OutboxMessageRepositoryError::OutboxMessagesNotReadError)?;
@@ -79,6 +42,64 @@ impl domain::repositories::outbox_repository::OutboxMessageRepository
         Ok(Some(messages))
     }
 
+    async fn find_after(
+        &self,
+        after: Option<OutboxPosition>,
+        limit: i64,
+    ) -> Result<Vec<(OutboxPosition, OutboxMessage)>, OutboxMessageRepositoryError> {
+        let after = after.unwrap_or_default();
+        sqlx::query(
+            r#"
+            SELECT * FROM outbox_messages
+            WHERE (transaction_id, position) > ($1, $2)
+              AND transaction_id < pg_snapshot_xmin(pg_current_snapshot())::text::bigint
+            ORDER BY transaction_id, position
+            LIMIT $3
+        "#,
+        )
+        .bind(after.transaction_id)
+        .bind(after.position)
+        .bind(limit)
+        .try_map(|row: PgRow| {
+            let position = OutboxPosition {
+                transaction_id: row.try_get("transaction_id")?,
+                position: row.try_get("position")?,
+            };
+            Ok((position, to_outbox_message(row)?))
+        })
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| OutboxMessageRepositoryError::OutboxMessagesNotReadError)
+    }
+
+    async fn find_by_customer_id(
+        &self,
+        customer_id: &CustomerId,
+    ) -> Result<Vec<OutboxMessage>, OutboxMessageRepositoryError> {
+        sqlx::query(
+            r#"
+            WITH customer_orders AS (
+                SELECT id::text AS order_id FROM orders WHERE customer_id = $2
+                UNION
+                SELECT event_payload::jsonb ->> 'id' FROM outbox_messages
+                WHERE event_type = 'order_created'
+                  AND event_payload::jsonb ->> 'customer_id' = $1
+            )
+            SELECT * FROM outbox_messages
+            WHERE event_payload::jsonb ->> 'id' = $1
+               OR event_payload::jsonb ->> 'customer_id' = $1
+               OR event_payload::jsonb ->> 'order_id' IN (SELECT order_id FROM customer_orders)
+            ORDER BY created_at, id
+        "#,
+        )
+        .bind(customer_id.0.to_string())
+        .bind(customer_id.0)
+        .try_map(to_outbox_message)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| OutboxMessageRepositoryError::OutboxMessagesNotReadError)
+    }
+
     async fn set_processed(
         &self,
         message_id: Uuid,
@@ -91,8 +112,8 @@ impl domain::repositories::outbox_repository::OutboxMessageRepository
             WHERE id = $1
         "#,
         )
-        .bind(&message_id)
-        .bind(&Some(processed_at))
+        .bind(message_id)
+        .bind(Some(processed_at))
         .execute(&self.pool)
         .await
         .map_err(|error| {
@@ -103,17 +124,86 @@ impl domain::repositories::outbox_repository::OutboxMessageRepository
     }
 }
 
+pub(crate) async fn insert_message<'e>(
+    executor: impl PgExecutor<'e>,
+    message: &OutboxMessage,
+) -> Result<(), OutboxMessageRepositoryError> {
+    sqlx::query(
+        r#"
+        INSERT INTO outbox_messages (id, event_type, event_payload, created_at, processed_at)
+        VALUES ($1, $2, $3, $4, $5)
+    "#,
+    )
+    .bind(message.id())
+    .bind(message.event_type().to_string())
+    .bind(message.event_payload())
+    .bind(message.created_at())
+    .bind(message.processed_at())
+    .execute(executor)
+    .await
+    .map_err(|error| OutboxMessageRepositoryError::OutboxMessageNotSavedError(error.to_string()))?;
+    Ok(())
+}
+
+/// Replaces the payload of a stored message, on the caller's transaction.
+pub(crate) async fn update_payload(
+    connection: &mut PgConnection,
+    message: &OutboxMessage,
+) -> Result<(), OutboxMessageRepositoryError> {
+    sqlx::query("UPDATE outbox_messages SET event_payload = $2 WHERE id = $1")
+        .bind(message.id())
+        .bind(message.event_payload())
+        .execute(connection)
+        .await
+        .map_err(|error| {
+            OutboxMessageRepositoryError::OutboxMessageNotSavedError(error.to_string())
+        })?;
+    Ok(())
+}
+
+fn to_outbox_message(row: PgRow) -> Result<OutboxMessage, sqlx::Error> {
+    let id: Uuid = row.try_get("id")?;
+    let event_type_string: String = row.try_get("event_type")?;
+    let event_type = event_type_string
+        .parse()
+        .map_err(|_| sqlx::Error::ColumnDecode {
+            index: "event_type".to_string(),
+            source: Box::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Invalid event type",
+            )),
+        })?;
+    let event_payload = row.try_get("event_payload")?;
+    let created_at = row.try_get("created_at")?;
+    let processed_at = row.try_get("processed_at")?;
+    Ok(OutboxMessage::new(
+        id,
+        event_type,
+        event_payload,
+        created_at,
+        processed_at,
+    ))
+}
+
 #[cfg(test)]
 mod test {
-    use chrono::Utc;
+    use chrono::{DateTime, SubsecRound, Utc};
     use domain::{
-        entities::{customer::Customer, outbox::OutboxMessage},
-        repositories::outbox_repository::OutboxMessageRepository,
-        value_objects::{Address, CustomerId},
+        entities::{
+            address_book::AddressBook, customer::Customer, order::Order, outbox::OutboxMessage,
+        },
+        repositories::outbox_repository::{OutboxMessageRepository, OutboxPosition},
+        value_objects::{
+            Address, CountryCode, Currency, CustomerId, Email, OrderId, OrderItem, PersonName,
+            PostalCode, ProductId,
+        },
     };
     use uuid::Uuid;
 
-    use crate::{common::test, sqlx::pg_outbox_message_repository::PgOutboxMessageRepository};
+    use crate::{
+        common::test,
+        sqlx::pg_outbox_message_repository::{update_payload, PgOutboxMessageRepository},
+    };
 
     #[tokio::test]
     async fn save_message() {
@@ -161,6 +251,93 @@ mod test {
         assert!(!unsent_messages.iter().any(|m| m.id() == sent_message.id()));
     }
 
+    #[tokio::test]
+    async fn find_messages_after_a_given_one() {
+        let repository = PgOutboxMessageRepository {
+            pool: test::create_sqlx_connection_pool().await,
+        };
+        let first = save_message_created_at(&repository, Utc::now().trunc_subsecs(6)).await;
+        let second = save_message_created_at(&repository, Utc::now().trunc_subsecs(6)).await;
+        let messages = find_until_visible(&repository, None, &[first.id(), second.id()]).await;
+        let (first_position, _) = messages.iter().find(|(_, m)| m.id() == first.id()).unwrap();
+
+        let messages = repository
+            .find_after(Some(*first_position), i64::MAX)
+            .await
+            .unwrap();
+
+        assert!(!messages.iter().any(|(_, m)| m.id() == first.id()));
+        assert!(messages.iter().any(|(_, m)| m.id() == second.id()));
+        assert!(messages.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[tokio::test]
+    async fn does_not_find_messages_after_those_of_running_transactions() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let repository = PgOutboxMessageRepository { pool: pool.clone() };
+        let mut tx = pool.begin().await.unwrap();
+        let late = OutboxMessage::customer_created_event(&create_customer()).unwrap();
+        sqlx::query(
+            "INSERT INTO outbox_messages (id, event_type, event_payload, created_at) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(late.id())
+        .bind(late.event_type().to_string())
+        .bind(late.event_payload())
+        .bind(late.created_at())
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        let committed = save_unprocessed_message(&repository).await;
+
+        let messages = repository.find_after(None, i64::MAX).await.unwrap();
+        assert!(!messages.iter().any(|(_, m)| m.id() == committed.id()));
+
+        tx.commit().await.unwrap();
+        let messages = find_until_visible(&repository, None, &[late.id(), committed.id()]).await;
+        let position_of = |id| messages.iter().find(|(_, m)| m.id() == id).unwrap().0;
+        assert!(position_of(late.id()) < position_of(committed.id()));
+    }
+
+    #[tokio::test]
+    async fn find_and_anonymize_the_messages_of_a_customer() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let repository = PgOutboxMessageRepository { pool: pool.clone() };
+        let customer = create_customer();
+        let order = Order::create(OrderId(Uuid::new_v4()), customer.id.clone());
+        let messages = [
+            OutboxMessage::customer_created_event(&customer).unwrap(),
+            OutboxMessage::order_created_event(&order).unwrap(),
+            OutboxMessage::product_removed_from_order_event(
+                order.id(),
+                &OrderItem {
+                    product_id: ProductId(Uuid::new_v4()),
+                    price: 9.99,
+                    quantity: 1,
+                },
+                order.currency(),
+            )
+            .unwrap(),
+        ];
+        for message in &messages {
+            repository.save(message.clone()).await.unwrap();
+        }
+        save_unprocessed_message(&repository).await;
+
+        let found = repository.find_by_customer_id(&customer.id).await.unwrap();
+        assert_eq!(
+            messages.iter().map(|m| m.id()).collect::<Vec<_>>(),
+            found.iter().map(|m| m.id()).collect::<Vec<_>>()
+        );
+
+        let mut connection = pool.acquire().await.unwrap();
+        update_payload(&mut connection, &messages[0].anonymized().unwrap())
+            .await
+            .unwrap();
+
+        let found = repository.find_by_customer_id(&customer.id).await.unwrap();
+        assert!(!found[0].event_payload().contains("Appleseed"));
+    }
+
     async fn save_unprocessed_message(repository: &PgOutboxMessageRepository) -> OutboxMessage {
         let message = OutboxMessage::customer_created_event(&create_customer()).unwrap();
         repository
@@ -170,6 +347,45 @@ mod test {
         message
     }
 
+    async fn save_message_created_at(
+        repository: &PgOutboxMessageRepository,
+        created_at: DateTime<Utc>,
+    ) -> OutboxMessage {
+        let event = OutboxMessage::customer_created_event(&create_customer()).unwrap();
+        let message = OutboxMessage::new(
+            event.id(),
+            event.event_type(),
+            event.event_payload(),
+            created_at,
+            None,
+        );
+        repository
+            .save(message.clone())
+            .await
+            .expect("Error saving messages during test setup");
+        message
+    }
+
+    /// Messages of transactions run by concurrent tests are left out until
+    /// those end, so waits for the given ones to be found.
+    async fn find_until_visible(
+        repository: &PgOutboxMessageRepository,
+        after: Option<OutboxPosition>,
+        ids: &[Uuid],
+    ) -> Vec<(OutboxPosition, OutboxMessage)> {
+        for _ in 0..100 {
+            let messages = repository.find_after(after, i64::MAX).await.unwrap();
+            if ids
+                .iter()
+                .all(|id| messages.iter().any(|(_, m)| m.id() == *id))
+            {
+                return messages;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        panic!("Messages not found");
+    }
+
     async fn save_processed_message(repository: &PgOutboxMessageRepository) -> OutboxMessage {
         let mut message = OutboxMessage::customer_created_event(&create_customer()).unwrap();
         message.set_processed_at(Utc::now());
@@ -194,14 +410,20 @@ mod test {
     fn create_customer() -> Customer {
         Customer {
             id: CustomerId(Uuid::new_v4()),
-            first_name: "my_customer_first_name".to_string(),
-            last_name: "my_customer_last_name".to_string(),
+            first_name: PersonName::parse("John").unwrap(),
+            last_name: PersonName::parse("Appleseed").unwrap(),
+            email: Email::parse("mario.rossi@example.com").unwrap(),
+            phone: None,
             address: Address {
-                street: "my_customer_street".to_string(),
-                city: "my_customer_city".to_string(),
-                zip_code: "my_customer_zip_code".to_string(),
-                state: "my_customer_state".to_string(),
+                street: "22 Elm Street".to_string(),
+                city: "Castle Rock".to_string(),
+                zip_code: PostalCode::parse("04401", &CountryCode::parse("US").unwrap()).unwrap(),
+                country: CountryCode::parse("US").unwrap(),
             },
+            address_book: AddressBook::new(),
+            currency: Currency::Eur,
+            deactivated_at: None,
+            version: 0,
         }
     }
 }
@@ -0,0 +1,296 @@
+use super::pg_transactional_repository::PgTransactionalRepository;
+use async_trait::async_trait;
+use domain::{
+    entities::payment::{Payment, PaymentState, Refund},
+    repositories::{
+        payment_repository::PaymentRepositoryError,
+        transactional_repository::TransactionalRepositoryError,
+    },
+    value_objects::{AuthorizationId, OrderId, PaymentId},
+};
+use sqlx::{postgres::PgRow, PgConnection, Pool, Postgres, Row};
+use uuid::Uuid;
+
+pub struct PgPaymentRepository<'a> {
+    pool: Pool<Postgres>,
+    transactional: PgTransactionalRepository<'a>,
+}
+
+impl<'a> PgPaymentRepository<'a> {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        let transactional = PgTransactionalRepository::new(pool.clone());
+        Self {
+            pool,
+            transactional,
+        }
+    }
+
+    /// Reads the refunds of the payment found, if any.
+    async fn with_refunds(
+        &self,
+        row: Result<Option<PgRow>, sqlx::Error>,
+    ) -> Result<Option<Payment>, PaymentRepositoryError> {
+        let row = row.map_err(|e| PaymentRepositoryError::PaymentNotReadError(e.to_string()))?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let payment_id: Uuid = row
+            .try_get("id")
+            .map_err(|e| PaymentRepositoryError::PaymentNotReadError(e.to_string()))?;
+
+        let refunds =
+            sqlx::query("SELECT * FROM payment_refunds WHERE payment_id = $1 ORDER BY position")
+                .bind(payment_id)
+                .try_map(|row: PgRow| {
+                    Ok(Refund {
+                        idempotency_key: row.try_get("idempotency_key")?,
+                        amount: row.try_get("amount")?,
+                    })
+                })
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| PaymentRepositoryError::PaymentNotReadError(e.to_string()))?;
+
+        payment_from_row(row, refunds)
+            .map(Some)
+            .map_err(|e| PaymentRepositoryError::PaymentNotReadError(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl<'a> domain::repositories::transactional_repository::TransactionalRepository
+    for PgPaymentRepository<'a>
+{
+    async fn begin_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        self.transactional.begin_transaction().await
+    }
+    async fn commit_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        self.transactional.commit_transaction().await
+    }
+    async fn rollback_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        self.transactional.rollback_transaction().await
+    }
+}
+
+#[async_trait]
+impl<'a> domain::repositories::payment_repository::PaymentRepository for PgPaymentRepository<'a> {
+    async fn find_by_order_id(
+        &self,
+        order_id: OrderId,
+    ) -> Result<Option<Payment>, PaymentRepositoryError> {
+        let row = sqlx::query("SELECT * FROM payments WHERE order_id = $1")
+            .bind(order_id.0)
+            .fetch_optional(&self.pool)
+            .await;
+        self.with_refunds(row).await
+    }
+
+    async fn find_by_authorization_id(
+        &self,
+        authorization_id: AuthorizationId,
+    ) -> Result<Option<Payment>, PaymentRepositoryError> {
+        let row = sqlx::query("SELECT * FROM payments WHERE authorization_id = $1")
+            .bind(authorization_id.0)
+            .fetch_optional(&self.pool)
+            .await;
+        self.with_refunds(row).await
+    }
+
+    async fn save(&self, payment: Payment) -> Result<Payment, PaymentRepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| PaymentRepositoryError::PaymentNotSavedError)?;
+        let result = sqlx::query(
+            r#"
+            INSERT INTO payments
+                (id, order_id, amount, state, authorization_id, captured_amount, failure_reason, version)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, 1)
+            ON CONFLICT (order_id) DO NOTHING
+            "#,
+        )
+        .bind(payment.id().0)
+        .bind(payment.order_id().0)
+        .bind(payment.amount())
+        .bind(payment.state().to_string())
+        .bind(payment.authorization_id().map(|authorization_id| authorization_id.0.clone()))
+        .bind(payment.captured_amount())
+        .bind(payment.failure_reason())
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| PaymentRepositoryError::PaymentNotSavedError)?;
+        if result.rows_affected() == 0 {
+            return Err(PaymentRepositoryError::ConcurrencyConflict);
+        }
+
+        insert_refunds(&mut tx, &payment).await?;
+        tx.commit()
+            .await
+            .map_err(|_| PaymentRepositoryError::PaymentNotSavedError)?;
+        Ok(payment.with_version(1))
+    }
+
+    async fn update(&self, payment: Payment) -> Result<Payment, PaymentRepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| PaymentRepositoryError::PaymentNotSavedError)?;
+        let result = sqlx::query(
+            r#"
+            UPDATE payments
+            SET state = $3, authorization_id = $4, captured_amount = $5, failure_reason = $6,
+                version = version + 1
+            WHERE id = $1 AND version = $2
+            "#,
+        )
+        .bind(payment.id().0)
+        .bind(payment.version())
+        .bind(payment.state().to_string())
+        .bind(
+            payment
+                .authorization_id()
+                .map(|authorization_id| authorization_id.0.clone()),
+        )
+        .bind(payment.captured_amount())
+        .bind(payment.failure_reason())
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| PaymentRepositoryError::PaymentNotSavedError)?;
+        if result.rows_affected() == 0 {
+            return Err(PaymentRepositoryError::ConcurrencyConflict);
+        }
+
+        insert_refunds(&mut tx, &payment).await?;
+        tx.commit()
+            .await
+            .map_err(|_| PaymentRepositoryError::PaymentNotSavedError)?;
+        let version = payment.version() + 1;
+        Ok(payment.with_version(version))
+    }
+}
+
+/// Refunds are never changed once made, so only the new ones are inserted.
+async fn insert_refunds(
+    connection: &mut PgConnection,
+    payment: &Payment,
+) -> Result<(), PaymentRepositoryError> {
+    for (position, refund) in payment.refunds().iter().enumerate() {
+        sqlx::query(
+            r#"
+            INSERT INTO payment_refunds (payment_id, idempotency_key, amount, position)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (payment_id, idempotency_key) DO NOTHING
+            "#,
+        )
+        .bind(payment.id().0)
+        .bind(&refund.idempotency_key)
+        .bind(refund.amount)
+        .bind(position as i32)
+        .execute(&mut *connection)
+        .await
+        .map_err(|_| PaymentRepositoryError::PaymentNotSavedError)?;
+    }
+    Ok(())
+}
+
+fn payment_from_row(row: PgRow, refunds: Vec<Refund>) -> Result<Payment, sqlx::Error> {
+    let state: PaymentState =
+        row.try_get::<&str, _>("state")?
+            .parse()
+            .map_err(|error: String| sqlx::Error::ColumnDecode {
+                index: "state".to_string(),
+                source: error.into(),
+            })?;
+    Ok(Payment::restore(
+        PaymentId(row.try_get("id")?),
+        OrderId(row.try_get("order_id")?),
+        row.try_get("amount")?,
+        state,
+        row.try_get::<Option<String>, _>("authorization_id")?
+            .map(AuthorizationId),
+        row.try_get("captured_amount")?,
+        refunds,
+        row.try_get("failure_reason")?,
+        row.try_get("version")?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::common::test;
+    use domain::{
+        entities::order::{Order, OrderStatus},
+        repositories::payment_repository::PaymentRepository,
+        value_objects::CustomerId,
+    };
+
+    #[tokio::test]
+    async fn saves_and_updates_a_payment_with_its_refunds() {
+        let order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()))
+            .with_status(OrderStatus::Pending);
+        let authorization_id = AuthorizationId(format!("auth_{}", order.id().0.simple()));
+        let repository = PgPaymentRepository::new(test::create_sqlx_connection_pool().await);
+        let mut payment = repository
+            .save(Payment::new(PaymentId(Uuid::new_v4()), &order, 10.0).unwrap())
+            .await
+            .unwrap();
+
+        payment.authorized(authorization_id.clone()).unwrap();
+        payment.capture(OrderStatus::Confirmed, 10.0).unwrap();
+        payment.refund("first", 3.0).unwrap();
+        let mut payment = repository.update(payment).await.unwrap();
+        payment.refund("second", 7.0).unwrap();
+        repository.update(payment).await.unwrap();
+
+        let payment_from_db = repository
+            .find_by_authorization_id(authorization_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(PaymentState::Refunded, payment_from_db.state());
+        assert_eq!(
+            vec!["first", "second"],
+            payment_from_db
+                .refunds()
+                .iter()
+                .map(|refund| refund.idempotency_key.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(3, payment_from_db.version());
+    }
+
+    #[tokio::test]
+    async fn keeps_one_payment_per_order() {
+        let order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()))
+            .with_status(OrderStatus::Pending);
+        let repository = PgPaymentRepository::new(test::create_sqlx_connection_pool().await);
+        let payment = repository
+            .save(Payment::new(PaymentId(Uuid::new_v4()), &order, 10.0).unwrap())
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            repository
+                .save(Payment::new(PaymentId(Uuid::new_v4()), &order, 10.0).unwrap())
+                .await,
+            Err(PaymentRepositoryError::ConcurrencyConflict)
+        ));
+        let mut stale = payment.clone();
+        stale.failed("declined").unwrap();
+        repository.update(payment).await.unwrap();
+        assert!(matches!(
+            repository.update(stale).await,
+            Err(PaymentRepositoryError::ConcurrencyConflict)
+        ));
+        let payment_from_db = repository
+            .find_by_order_id(order.id().clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(PaymentState::Pending, payment_from_db.state());
+    }
+}
@@ -0,0 +1,261 @@
+use async_trait::async_trait;
+use domain::{
+    gateways::product_search::{
+        CategoryFacet, PriceFacet, ProductSearch, ProductSearchError, SearchHit, SearchQuery,
+        SearchResult, SearchSort, MAX_HITS, PRICE_BUCKETS,
+    },
+    value_objects::{CatalogProductId, CategoryId},
+};
+use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+use uuid::Uuid;
+
+/// Products matching the query, with `$1` the text, `$2` the category, `$3`
+/// and `$4` the price range. Texts match the `search_vector` of products and
+/// of their text attributes, ranked with the name above attributes.
+const MATCHES: &str = r#"
+    WITH RECURSIVE tree (id) AS (
+        SELECT id FROM categories WHERE id = $2
+        UNION ALL
+        SELECT categories.id FROM categories JOIN tree ON categories.parent_id = tree.id
+    ),
+    matches AS (
+        SELECT * FROM (
+            SELECT
+                products.id,
+                products.name,
+                products.category_id,
+                (
+                    SELECT MIN(price) FROM product_variants
+                    WHERE product_id = products.id
+                    AND price BETWEEN COALESCE($3, 0) AND COALESCE($4, 'Infinity')
+                ) AS price,
+                ts_rank(products.search_vector, query) + COALESCE((
+                    SELECT MAX(ts_rank(search_vector, query)) FROM product_attributes
+                    WHERE product_id = products.id
+                ), 0) AS rank
+            FROM products, websearch_to_tsquery('english', $1) query
+            WHERE (
+                $1 = ''
+                OR products.search_vector @@ query
+                OR EXISTS (
+                    SELECT 1 FROM product_attributes
+                    WHERE product_id = products.id AND search_vector @@ query
+                )
+            )
+            AND ($2::UUID IS NULL OR products.category_id IN (SELECT id FROM tree))
+        ) candidates
+        WHERE ($3::FLOAT8 IS NULL AND $4::FLOAT8 IS NULL) OR price IS NOT NULL
+    )
+"#;
+
+/// Searches the catalog with Postgres full-text search.
+pub struct PgProductSearch {
+    pool: Pool<Postgres>,
+}
+
+impl PgProductSearch {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ProductSearch for PgProductSearch {
+    async fn search(&self, query: &SearchQuery) -> Result<SearchResult, ProductSearchError> {
+        let unavailable =
+            |e: sqlx::Error| ProductSearchError::SearchUnavailableError(e.to_string());
+        let order_by = match query.sort {
+            SearchSort::Relevance => "rank DESC, name, id",
+            SearchSort::PriceAsc => "price ASC NULLS LAST, name, id",
+            SearchSort::PriceDesc => "price DESC NULLS LAST, name, id",
+            SearchSort::Name => "name, id",
+        };
+        let category_id = query.category_id.as_ref().map(|category_id| category_id.0);
+
+        let hits = sqlx::query(&format!(
+            "{} SELECT * FROM matches ORDER BY {} LIMIT $5",
+            MATCHES, order_by
+        ))
+        .bind(&query.text)
+        .bind(category_id)
+        .bind(query.min_price)
+        .bind(query.max_price)
+        .bind(MAX_HITS)
+        .try_map(|row: PgRow| {
+            Ok(SearchHit {
+                product_id: CatalogProductId(row.try_get("id")?),
+                name: row.try_get("name")?,
+                category_id: row
+                    .try_get::<Option<Uuid>, _>("category_id")?
+                    .map(CategoryId),
+                price: row.try_get("price")?,
+                rank: row.try_get::<f32, _>("rank")?.into(),
+            })
+        })
+        .fetch_all(&self.pool)
+        .await
+        .map_err(unavailable)?;
+
+        let category_facets = sqlx::query(&format!(
+            r#"
+            {}
+            SELECT categories.id, categories.name, COUNT(*) AS count
+            FROM matches JOIN categories ON categories.id = matches.category_id
+            GROUP BY categories.id, categories.name
+            ORDER BY count DESC, categories.name
+            "#,
+            MATCHES
+        ))
+        .bind(&query.text)
+        .bind(category_id)
+        .bind(query.min_price)
+        .bind(query.max_price)
+        .try_map(|row: PgRow| {
+            Ok(CategoryFacet {
+                category_id: CategoryId(row.try_get("id")?),
+                name: row.try_get("name")?,
+                count: row.try_get("count")?,
+            })
+        })
+        .fetch_all(&self.pool)
+        .await
+        .map_err(unavailable)?;
+
+        // width_bucket numbers the buckets from 1, prices below the first
+        // bound being in bucket 0.
+        let bucket_counts: Vec<(i32, i64)> = sqlx::query_as(&format!(
+            r#"
+            {}
+            SELECT width_bucket(price, $5) AS bucket, COUNT(*) AS count
+            FROM matches WHERE price IS NOT NULL
+            GROUP BY bucket
+            "#,
+            MATCHES
+        ))
+        .bind(&query.text)
+        .bind(category_id)
+        .bind(query.min_price)
+        .bind(query.max_price)
+        .bind(PRICE_BUCKETS.to_vec())
+        .fetch_all(&self.pool)
+        .await
+        .map_err(unavailable)?;
+        let price_facets = PRICE_BUCKETS
+            .iter()
+            .enumerate()
+            .map(|(index, from)| PriceFacet {
+                from: *from,
+                to: PRICE_BUCKETS.get(index + 1).copied(),
+                count: bucket_counts
+                    .iter()
+                    .find(|(bucket, _)| *bucket as usize == index + 1)
+                    .map_or(0, |(_, count)| *count),
+            })
+            .collect();
+
+        Ok(SearchResult {
+            hits,
+            category_facets,
+            price_facets,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::{common::test, sqlx::pg_catalog_repository::PgCatalogRepository};
+    use domain::{
+        entities::{
+            category::Category,
+            product::{Attribute, AttributeValue, Product},
+        },
+        repositories::catalog_repository::CatalogRepository,
+        value_objects::ProductId,
+    };
+
+    /// Products are in a category of their own, so earlier runs don't match.
+    #[tokio::test]
+    async fn searches_products_by_text_with_facets() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let catalog_repository = PgCatalogRepository::new(pool.clone());
+        let category = catalog_repository
+            .save_category(Category::new(CategoryId(Uuid::new_v4()), "Shirts", None).unwrap())
+            .await
+            .unwrap();
+        let mut product_ids = vec![];
+        for (name, colour, prices) in [
+            ("Linen shirt", "red", vec![45.0, 60.0]),
+            ("Oxford shirt", "blue", vec![30.0]),
+            ("Wool scarf", "red", vec![20.0]),
+        ] {
+            let mut product = Product::new(
+                CatalogProductId(Uuid::new_v4()),
+                name,
+                Some(category.id().clone()),
+            )
+            .unwrap();
+            for price in prices {
+                product
+                    .add_variant(
+                        ProductId(Uuid::new_v4()),
+                        &Uuid::new_v4().to_string(),
+                        price,
+                    )
+                    .unwrap();
+            }
+            product
+                .set_attribute(
+                    None,
+                    Attribute::new("colour", AttributeValue::Text(colour.to_string())).unwrap(),
+                )
+                .unwrap();
+            product_ids.push(product.id().clone());
+            catalog_repository.save_product(product).await.unwrap();
+        }
+        let search = PgProductSearch::new(pool);
+        let query = |text: &str, min_price, sort| SearchQuery {
+            text: text.to_string(),
+            category_id: Some(category.id().clone()),
+            min_price,
+            max_price: None,
+            sort,
+        };
+
+        let result = search
+            .search(&query("shirts", None, SearchSort::PriceAsc))
+            .await
+            .unwrap();
+        assert_eq!(
+            vec![product_ids[1].clone(), product_ids[0].clone()],
+            result
+                .hits
+                .iter()
+                .map(|hit| hit.product_id.clone())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(2, result.category_facets[0].count);
+        assert_eq!(
+            vec![0, 2, 0, 0],
+            result
+                .price_facets
+                .iter()
+                .map(|facet| facet.count)
+                .collect::<Vec<_>>()
+        );
+
+        let result = search
+            .search(&query("red", Some(50.0), SearchSort::Relevance))
+            .await
+            .unwrap();
+        assert_eq!(1, result.hits.len());
+        assert_eq!(Some(60.0), result.hits[0].price);
+
+        let result = search
+            .search(&query("", None, SearchSort::Name))
+            .await
+            .unwrap();
+        assert_eq!(3, result.hits.len());
+    }
+}
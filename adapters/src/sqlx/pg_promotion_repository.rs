@@ -0,0 +1,259 @@
+use super::invalid;
+use async_trait::async_trait;
+use domain::{
+    entities::promotion::{Discount, Promotion, PromotionConditions},
+    repositories::promotion_repository::PromotionRepositoryError,
+    value_objects::{CouponCode, CustomerId, OrderId, ProductId, PromotionId},
+};
+use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+
+const UNIQUE_VIOLATION: &str = "23505";
+
+pub struct PgPromotionRepository {
+    pool: Pool<Postgres>,
+}
+
+impl PgPromotionRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl domain::repositories::promotion_repository::PromotionRepository for PgPromotionRepository {
+    async fn find_by_code(
+        &self,
+        code: CouponCode,
+    ) -> Result<Option<Promotion>, PromotionRepositoryError> {
+        sqlx::query("SELECT * FROM promotions WHERE code = $1")
+            .bind(code.as_str())
+            .try_map(promotion_from_row)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| PromotionRepositoryError::PromotionNotReadError(e.to_string()))
+    }
+
+    async fn save(&self, promotion: Promotion) -> Result<Promotion, PromotionRepositoryError> {
+        let (value, buy, get) = match promotion.discount() {
+            Discount::Percentage(value) | Discount::Fixed(value) => (Some(*value), None, None),
+            Discount::BuyXGetY { buy, get, .. } => (None, Some(*buy), Some(*get)),
+            Discount::FreeShipping => (None, None, None),
+        };
+        let conditions = promotion.conditions();
+        sqlx::query(
+            r#"
+            INSERT INTO promotions (
+                id, code, kind, value, product_id, buy_quantity, get_quantity,
+                min_spend, valid_from, valid_until, max_uses_per_customer
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#,
+        )
+        .bind(promotion.id().0)
+        .bind(promotion.code().as_str())
+        .bind(promotion.discount().kind())
+        .bind(value)
+        .bind(
+            promotion
+                .discount()
+                .product_id()
+                .map(|product_id| product_id.0),
+        )
+        .bind(buy)
+        .bind(get)
+        .bind(conditions.min_spend)
+        .bind(conditions.valid_from)
+        .bind(conditions.valid_until)
+        .bind(conditions.max_uses_per_customer)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e.as_database_error().and_then(|e| e.code()) {
+            Some(code) if code == UNIQUE_VIOLATION => {
+                PromotionRepositoryError::PromotionAlreadyExistsError
+            }
+            _ => PromotionRepositoryError::PromotionNotSavedError,
+        })?;
+        Ok(promotion)
+    }
+
+    async fn count_redemptions(
+        &self,
+        promotion_id: PromotionId,
+        customer_id: CustomerId,
+    ) -> Result<i64, PromotionRepositoryError> {
+        sqlx::query_scalar(
+            "SELECT COUNT(*) FROM promotion_redemptions WHERE promotion_id = $1 AND customer_id = $2",
+        )
+        .bind(promotion_id.0)
+        .bind(customer_id.0)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| PromotionRepositoryError::PromotionNotReadError(e.to_string()))
+    }
+
+    async fn redeem(
+        &self,
+        promotion: &Promotion,
+        customer_id: CustomerId,
+        order_id: OrderId,
+    ) -> Result<(), PromotionRepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| PromotionRepositoryError::PromotionNotSavedError)?;
+        // Locks the promotion, so concurrent redemptions are counted one at a time.
+        sqlx::query("SELECT id FROM promotions WHERE id = $1 FOR UPDATE")
+            .bind(promotion.id().0)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| PromotionRepositoryError::PromotionNotReadError(e.to_string()))?;
+        if let Some(max_uses) = promotion.conditions().max_uses_per_customer {
+            let uses: i64 = sqlx::query_scalar(
+                "SELECT COUNT(*) FROM promotion_redemptions WHERE promotion_id = $1 AND customer_id = $2",
+            )
+            .bind(promotion.id().0)
+            .bind(customer_id.0)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(|e| PromotionRepositoryError::PromotionNotReadError(e.to_string()))?;
+            if uses >= max_uses {
+                return Err(PromotionRepositoryError::UsageLimitReachedError);
+            }
+        }
+        sqlx::query(
+            "INSERT INTO promotion_redemptions (promotion_id, customer_id, order_id) VALUES ($1, $2, $3)",
+        )
+        .bind(promotion.id().0)
+        .bind(customer_id.0)
+        .bind(order_id.0)
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| PromotionRepositoryError::PromotionNotSavedError)?;
+        tx.commit()
+            .await
+            .map_err(|_| PromotionRepositoryError::PromotionNotSavedError)
+    }
+
+    async fn release(
+        &self,
+        promotion_id: PromotionId,
+        order_id: OrderId,
+    ) -> Result<(), PromotionRepositoryError> {
+        sqlx::query("DELETE FROM promotion_redemptions WHERE promotion_id = $1 AND order_id = $2")
+            .bind(promotion_id.0)
+            .bind(order_id.0)
+            .execute(&self.pool)
+            .await
+            .map_err(|_| PromotionRepositoryError::PromotionNotSavedError)?;
+        Ok(())
+    }
+}
+
+fn promotion_from_row(row: PgRow) -> Result<Promotion, sqlx::Error> {
+    let decode = |error: domain::entities::promotion::PromotionError| sqlx::Error::ColumnDecode {
+        index: "kind".to_string(),
+        source: Box::new(error),
+    };
+    let discount = Discount::parse(
+        row.try_get("kind")?,
+        row.try_get("value")?,
+        row.try_get::<Option<_>, _>("product_id")?.map(ProductId),
+        row.try_get("buy_quantity")?,
+        row.try_get("get_quantity")?,
+    )
+    .map_err(decode)?;
+    Promotion::new(
+        PromotionId(row.try_get("id")?),
+        CouponCode::parse(row.try_get("code")?).map_err(invalid("code"))?,
+        discount,
+        PromotionConditions {
+            min_spend: row.try_get("min_spend")?,
+            valid_from: row.try_get("valid_from")?,
+            valid_until: row.try_get("valid_until")?,
+            max_uses_per_customer: row.try_get("max_uses_per_customer")?,
+        },
+    )
+    .map_err(decode)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::common::test;
+    use domain::repositories::promotion_repository::PromotionRepository;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn saves_a_promotion_and_finds_it_by_code() {
+        let repository = PgPromotionRepository::new(test::create_sqlx_connection_pool().await);
+        let promotion = promotion(
+            Discount::BuyXGetY {
+                product_id: ProductId(Uuid::new_v4()),
+                buy: 2,
+                get: 1,
+            },
+            None,
+        );
+        repository.save(promotion.clone()).await.unwrap();
+
+        let promotion_from_db = repository
+            .find_by_code(promotion.code().clone())
+            .await
+            .unwrap();
+
+        assert_eq!(Some(promotion.clone()), promotion_from_db);
+        assert!(matches!(
+            repository.save(promotion).await,
+            Err(PromotionRepositoryError::PromotionAlreadyExistsError)
+        ));
+    }
+
+    #[tokio::test]
+    async fn redeems_a_promotion_up_to_its_usage_limit() {
+        let repository = PgPromotionRepository::new(test::create_sqlx_connection_pool().await);
+        let promotion = repository
+            .save(promotion(Discount::Percentage(10.0), Some(1)))
+            .await
+            .unwrap();
+        let customer_id = CustomerId(Uuid::new_v4());
+        let order_id = OrderId(Uuid::new_v4());
+
+        repository
+            .redeem(&promotion, customer_id.clone(), order_id.clone())
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository
+                .redeem(&promotion, customer_id.clone(), OrderId(Uuid::new_v4()))
+                .await,
+            Err(PromotionRepositoryError::UsageLimitReachedError)
+        ));
+        repository
+            .release(promotion.id().clone(), order_id)
+            .await
+            .unwrap();
+
+        let uses = repository
+            .count_redemptions(promotion.id().clone(), customer_id)
+            .await
+            .unwrap();
+        assert_eq!(0, uses);
+    }
+
+    fn promotion(discount: Discount, max_uses_per_customer: Option<i64>) -> Promotion {
+        let id = Uuid::new_v4();
+        Promotion::new(
+            PromotionId(id),
+            CouponCode::parse(&id.simple().to_string()).unwrap(),
+            discount,
+            PromotionConditions {
+                min_spend: Some(20.0),
+                max_uses_per_customer,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    }
+}
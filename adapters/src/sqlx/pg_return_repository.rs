@@ -0,0 +1,406 @@
+use super::{
+    pg_order_repository::bump_order_version, pg_transactional_repository::PgTransactionalRepository,
+};
+use async_trait::async_trait;
+use domain::{
+    entities::{
+        order_lines::OrderLines,
+        returns::{Return, ReturnState},
+    },
+    repositories::{
+        return_repository::ReturnRepositoryError,
+        transactional_repository::TransactionalRepositoryError,
+    },
+    value_objects::{OrderId, OrderItem, ProductId, ReturnId},
+};
+use sqlx::{postgres::PgRow, PgConnection, Pool, Postgres, Row};
+use uuid::Uuid;
+
+pub struct PgReturnRepository<'a> {
+    pool: Pool<Postgres>,
+    transactional: PgTransactionalRepository<'a>,
+}
+
+impl<'a> PgReturnRepository<'a> {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        let transactional = PgTransactionalRepository::new(pool.clone());
+        Self {
+            pool,
+            transactional,
+        }
+    }
+
+    /// Reads the items of a return row.
+    async fn with_items(&self, row: PgRow) -> Result<Return, ReturnRepositoryError> {
+        let return_id: Uuid = row
+            .try_get("id")
+            .map_err(|e| ReturnRepositoryError::ReturnNotReadError(e.to_string()))?;
+        let return_items =
+            sqlx::query("SELECT * FROM return_items WHERE return_id = $1 ORDER BY product_id")
+                .bind(return_id)
+                .try_map(|row: PgRow| {
+                    Ok(OrderItem {
+                        price: row.try_get("price")?,
+                        quantity: row.try_get("quantity")?,
+                        product_id: ProductId(row.try_get("product_id")?),
+                    })
+                })
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| ReturnRepositoryError::ReturnNotReadError(e.to_string()))?;
+        let items = OrderLines::try_from(return_items)
+            .map_err(|e| ReturnRepositoryError::ReturnNotReadError(e.to_string()))?;
+
+        return_from_row(row, items)
+            .map_err(|e| ReturnRepositoryError::ReturnNotReadError(e.to_string()))
+    }
+
+    /// Updates the return, moving the order to its next version too if
+    /// `order_version` is given.
+    async fn write(
+        &self,
+        order_return: Return,
+        order_version: Option<i64>,
+    ) -> Result<Return, ReturnRepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| ReturnRepositoryError::ReturnNotSavedError)?;
+        if let Some(order_version) = order_version {
+            check_order_version(&mut tx, &order_return, order_version).await?;
+        }
+        let result = sqlx::query(
+            r#"
+            UPDATE returns
+            SET state = $3, rejection_reason = $4, refund_amount = $5, version = version + 1
+            WHERE id = $1 AND version = $2
+            "#,
+        )
+        .bind(order_return.id().0)
+        .bind(order_return.version())
+        .bind(order_return.state().to_string())
+        .bind(order_return.rejection_reason())
+        .bind(order_return.refund_amount())
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| ReturnRepositoryError::ReturnNotSavedError)?;
+        if result.rows_affected() == 0 {
+            return Err(ReturnRepositoryError::ConcurrencyConflict);
+        }
+
+        // Like carts, returns are small: their items are simply rewritten.
+        sqlx::query("DELETE FROM return_items WHERE return_id = $1")
+            .bind(order_return.id().0)
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| ReturnRepositoryError::ReturnNotSavedError)?;
+        insert_return_items(&mut tx, &order_return).await?;
+        tx.commit()
+            .await
+            .map_err(|_| ReturnRepositoryError::ReturnNotSavedError)?;
+
+        let version = order_return.version() + 1;
+        Ok(order_return.with_version(version))
+    }
+}
+
+#[async_trait]
+impl<'a> domain::repositories::transactional_repository::TransactionalRepository
+    for PgReturnRepository<'a>
+{
+    async fn begin_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        self.transactional.begin_transaction().await
+    }
+    async fn commit_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        self.transactional.commit_transaction().await
+    }
+    async fn rollback_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        self.transactional.rollback_transaction().await
+    }
+}
+
+#[async_trait]
+impl<'a> domain::repositories::return_repository::ReturnRepository for PgReturnRepository<'a> {
+    async fn find_by_id(&self, id: ReturnId) -> Result<Option<Return>, ReturnRepositoryError> {
+        let row = sqlx::query("SELECT * FROM returns WHERE id = $1")
+            .bind(id.0)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| ReturnRepositoryError::ReturnNotReadError(e.to_string()))?;
+        match row {
+            Some(row) => self.with_items(row).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_by_order_id(
+        &self,
+        order_id: OrderId,
+    ) -> Result<Vec<Return>, ReturnRepositoryError> {
+        let rows = sqlx::query("SELECT * FROM returns WHERE order_id = $1 ORDER BY id")
+            .bind(order_id.0)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ReturnRepositoryError::ReturnNotReadError(e.to_string()))?;
+        let mut returns = vec![];
+        for row in rows {
+            returns.push(self.with_items(row).await?);
+        }
+        Ok(returns)
+    }
+
+    async fn save(
+        &self,
+        order_return: Return,
+        order_version: i64,
+    ) -> Result<Return, ReturnRepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| ReturnRepositoryError::ReturnNotSavedError)?;
+        check_order_version(&mut tx, &order_return, order_version).await?;
+        let result = sqlx::query(
+            r#"
+            INSERT INTO returns (
+                id, order_id, reason, state, rejection_reason, refund_amount, version
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, 1)
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+        .bind(order_return.id().0)
+        .bind(order_return.order_id().0)
+        .bind(order_return.reason())
+        .bind(order_return.state().to_string())
+        .bind(order_return.rejection_reason())
+        .bind(order_return.refund_amount())
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| ReturnRepositoryError::ReturnNotSavedError)?;
+        if result.rows_affected() == 0 {
+            return Err(ReturnRepositoryError::ConcurrencyConflict);
+        }
+
+        insert_return_items(&mut tx, &order_return).await?;
+        tx.commit()
+            .await
+            .map_err(|_| ReturnRepositoryError::ReturnNotSavedError)?;
+        Ok(order_return.with_version(1))
+    }
+
+    async fn update(&self, order_return: Return) -> Result<Return, ReturnRepositoryError> {
+        self.write(order_return, None).await
+    }
+
+    async fn update_items(
+        &self,
+        order_return: Return,
+        order_version: i64,
+    ) -> Result<Return, ReturnRepositoryError> {
+        self.write(order_return, Some(order_version)).await
+    }
+}
+
+async fn check_order_version(
+    connection: &mut PgConnection,
+    order_return: &Return,
+    order_version: i64,
+) -> Result<(), ReturnRepositoryError> {
+    let bumped = bump_order_version(connection, order_return.order_id(), order_version)
+        .await
+        .map_err(|_| ReturnRepositoryError::ReturnNotSavedError)?;
+    if !bumped {
+        return Err(ReturnRepositoryError::ConcurrencyConflict);
+    }
+    Ok(())
+}
+
+async fn insert_return_items(
+    connection: &mut PgConnection,
+    order_return: &Return,
+) -> Result<(), ReturnRepositoryError> {
+    for item in order_return.items().iter() {
+        sqlx::query(
+            "INSERT INTO return_items (return_id, product_id, quantity, price) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(order_return.id().0)
+        .bind(item.product_id.0)
+        .bind(item.quantity)
+        .bind(item.price)
+        .execute(&mut *connection)
+        .await
+        .map_err(|_| ReturnRepositoryError::ReturnNotSavedError)?;
+    }
+    Ok(())
+}
+
+fn return_from_row(row: PgRow, items: OrderLines) -> Result<Return, sqlx::Error> {
+    let state: ReturnState =
+        row.try_get::<&str, _>("state")?
+            .parse()
+            .map_err(|error: String| sqlx::Error::ColumnDecode {
+                index: "state".to_string(),
+                source: error.into(),
+            })?;
+    Ok(Return::restore(
+        ReturnId(row.try_get("id")?),
+        OrderId(row.try_get("order_id")?),
+        items,
+        row.try_get("reason")?,
+        state,
+        row.try_get("rejection_reason")?,
+        row.try_get("refund_amount")?,
+        row.try_get("version")?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::{common::test, sqlx::pg_order_repository::PgOrderRepository};
+    use domain::{
+        entities::{
+            order::{Order, OrderStatus},
+            shipment::{Shipment, ShipmentState},
+        },
+        repositories::{order_repository::OrderRepository, return_repository::ReturnRepository},
+        value_objects::{CustomerId, ShipmentId, ShippingMethodId},
+    };
+
+    #[tokio::test]
+    async fn saves_and_updates_a_return_with_its_items() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let order = order(&pool).await;
+        let repository = PgReturnRepository::new(pool);
+        let mut order_return = Return::request(
+            ReturnId(Uuid::new_v4()),
+            &order,
+            &delivered(&order),
+            "Wrong size",
+        )
+        .unwrap();
+        order_return.add(&order, &[], product_id(1), 1).unwrap();
+        let mut order_return = repository
+            .save(order_return, order.version())
+            .await
+            .unwrap();
+
+        order_return.add(&order, &[], product_id(2), 2).unwrap();
+        order_return = repository
+            .update_items(order_return, order.version() + 1)
+            .await
+            .unwrap();
+        order_return.approve().unwrap();
+        let order_return = repository.update(order_return).await.unwrap();
+
+        let returns_from_db = repository
+            .find_by_order_id(order.id().clone())
+            .await
+            .unwrap();
+        assert_eq!(vec![order_return], returns_from_db);
+        assert_eq!(3, returns_from_db[0].version());
+        assert_eq!(ReturnState::Approved, returns_from_db[0].state());
+    }
+
+    #[tokio::test]
+    async fn rejects_updates_of_a_stale_return() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let order = order(&pool).await;
+        let return_id = ReturnId(Uuid::new_v4());
+        let repository = PgReturnRepository::new(pool);
+        let mut order_return =
+            Return::request(return_id.clone(), &order, &delivered(&order), "").unwrap();
+        order_return.add(&order, &[], product_id(1), 1).unwrap();
+        let order_return = repository
+            .save(order_return, order.version())
+            .await
+            .unwrap();
+        assert!(matches!(
+            repository
+                .save(order_return.clone(), order.version() + 1)
+                .await,
+            Err(ReturnRepositoryError::ConcurrencyConflict)
+        ));
+
+        let mut stale = order_return.clone();
+        stale.reject("Worn").unwrap();
+        let mut approved = order_return;
+        approved.approve().unwrap();
+        repository.update(approved).await.unwrap();
+
+        assert!(matches!(
+            repository.update(stale).await,
+            Err(ReturnRepositoryError::ConcurrencyConflict)
+        ));
+        let return_from_db = repository.find_by_id(return_id).await.unwrap().unwrap();
+        assert_eq!(ReturnState::Approved, return_from_db.state());
+    }
+
+    #[tokio::test]
+    async fn rejects_returns_checked_against_a_stale_order() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let order = order(&pool).await;
+        let repository = PgReturnRepository::new(pool);
+        let mut first =
+            Return::request(ReturnId(Uuid::new_v4()), &order, &delivered(&order), "").unwrap();
+        first.add(&order, &[], product_id(1), 1).unwrap();
+        let mut second =
+            Return::request(ReturnId(Uuid::new_v4()), &order, &delivered(&order), "").unwrap();
+        second.add(&order, &[], product_id(1), 1).unwrap();
+        repository.save(first, order.version()).await.unwrap();
+
+        assert!(matches!(
+            repository.save(second, order.version()).await,
+            Err(ReturnRepositoryError::ConcurrencyConflict)
+        ));
+        let returns_from_db = repository
+            .find_by_order_id(order.id().clone())
+            .await
+            .unwrap();
+        assert_eq!(1, returns_from_db.len());
+    }
+
+    async fn order(pool: &Pool<Postgres>) -> Order {
+        let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()));
+        order
+            .add_multiple(vec![
+                OrderItem {
+                    price: 10.0,
+                    quantity: 1,
+                    product_id: product_id(1),
+                },
+                OrderItem {
+                    price: 5.5,
+                    quantity: 2,
+                    product_id: product_id(2),
+                },
+            ])
+            .unwrap();
+        PgOrderRepository::new(pool.clone())
+            .save(order.with_status(OrderStatus::Confirmed))
+            .await
+            .unwrap()
+    }
+
+    /// Returns are requested once the order is delivered.
+    fn delivered(order: &Order) -> Vec<Shipment> {
+        vec![Shipment::restore(
+            ShipmentId(Uuid::new_v4()),
+            order.id().clone(),
+            ShippingMethodId(Uuid::new_v4()),
+            "DHL".to_string(),
+            OrderLines::new(),
+            1.0,
+            Some("JD0001".to_string()),
+            ShipmentState::Delivered,
+            3,
+        )]
+    }
+
+    fn product_id(n: u128) -> ProductId {
+        ProductId(Uuid::from_u128(n))
+    }
+}
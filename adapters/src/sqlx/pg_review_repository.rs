@@ -0,0 +1,262 @@
+use super::pg_transactional_repository::PgTransactionalRepository;
+use async_trait::async_trait;
+use domain::{
+    entities::review::{Rating, Review, ReviewStatus},
+    repositories::{
+        review_repository::ReviewRepositoryError,
+        transactional_repository::TransactionalRepositoryError,
+    },
+    value_objects::{CatalogProductId, CustomerId, ReviewId},
+};
+use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+
+const UNIQUE_VIOLATION: &str = "23505";
+const PRIMARY_KEY_CONSTRAINT: &str = "reviews_pkey";
+
+pub struct PgReviewRepository<'a> {
+    pool: Pool<Postgres>,
+    transactional: PgTransactionalRepository<'a>,
+}
+
+impl<'a> PgReviewRepository<'a> {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        let transactional = PgTransactionalRepository::new(pool.clone());
+        Self {
+            pool,
+            transactional,
+        }
+    }
+}
+
+#[async_trait]
+impl<'a> domain::repositories::transactional_repository::TransactionalRepository
+    for PgReviewRepository<'a>
+{
+    async fn begin_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        self.transactional.begin_transaction().await
+    }
+    async fn commit_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        self.transactional.commit_transaction().await
+    }
+    async fn rollback_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        self.transactional.rollback_transaction().await
+    }
+}
+
+#[async_trait]
+impl<'a> domain::repositories::review_repository::ReviewRepository for PgReviewRepository<'a> {
+    async fn find_by_id(&self, id: ReviewId) -> Result<Option<Review>, ReviewRepositoryError> {
+        sqlx::query("SELECT * FROM reviews WHERE id = $1")
+            .bind(id.0)
+            .try_map(review_from_row)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| ReviewRepositoryError::ReviewNotReadError(e.to_string()))
+    }
+
+    async fn find_by_product_id(
+        &self,
+        product_id: CatalogProductId,
+        status: ReviewStatus,
+    ) -> Result<Vec<Review>, ReviewRepositoryError> {
+        sqlx::query(
+            "SELECT * FROM reviews WHERE product_id = $1 AND status = $2 ORDER BY created_at DESC, id",
+        )
+        .bind(product_id.0)
+        .bind(status.to_string())
+        .try_map(review_from_row)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ReviewRepositoryError::ReviewNotReadError(e.to_string()))
+    }
+
+    async fn rating_of(
+        &self,
+        product_id: CatalogProductId,
+    ) -> Result<Rating, ReviewRepositoryError> {
+        sqlx::query(
+            "SELECT AVG(rating)::FLOAT8 AS average, COUNT(*) AS count FROM reviews WHERE product_id = $1 AND status = $2",
+        )
+        .bind(product_id.0)
+        .bind(ReviewStatus::Approved.to_string())
+        .try_map(|row: PgRow| {
+            Ok(Rating {
+                average: row.try_get("average")?,
+                count: row.try_get("count")?,
+            })
+        })
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| ReviewRepositoryError::ReviewNotReadError(e.to_string()))
+    }
+
+    async fn save(&self, review: Review) -> Result<Review, ReviewRepositoryError> {
+        sqlx::query(
+            r#"
+            INSERT INTO reviews (
+                id, product_id, customer_id, rating, text, verified_purchase, status, created_at,
+                version
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 1)
+            "#,
+        )
+        .bind(review.id().0)
+        .bind(review.product_id().0)
+        .bind(review.customer_id().0)
+        .bind(review.rating())
+        .bind(review.text())
+        .bind(review.verified_purchase())
+        .bind(review.status().to_string())
+        .bind(review.created_at())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e.as_database_error() {
+            Some(e) if e.constraint() == Some(PRIMARY_KEY_CONSTRAINT) => {
+                ReviewRepositoryError::ReviewAlreadyExistsError
+            }
+            Some(e) if e.code().is_some_and(|code| code == UNIQUE_VIOLATION) => {
+                ReviewRepositoryError::ProductAlreadyReviewedError
+            }
+            _ => ReviewRepositoryError::ReviewNotSavedError,
+        })?;
+        Ok(review.with_version(1))
+    }
+
+    async fn update(&self, review: Review) -> Result<Review, ReviewRepositoryError> {
+        let result = sqlx::query(
+            "UPDATE reviews SET status = $3, version = version + 1 WHERE id = $1 AND version = $2",
+        )
+        .bind(review.id().0)
+        .bind(review.version())
+        .bind(review.status().to_string())
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ReviewRepositoryError::ReviewNotSavedError)?;
+        if result.rows_affected() == 0 {
+            return Err(ReviewRepositoryError::ConcurrencyConflict);
+        }
+
+        let version = review.version() + 1;
+        Ok(review.with_version(version))
+    }
+}
+
+fn review_from_row(row: PgRow) -> Result<Review, sqlx::Error> {
+    let status: ReviewStatus =
+        row.try_get::<&str, _>("status")?
+            .parse()
+            .map_err(|error: String| sqlx::Error::ColumnDecode {
+                index: "status".to_string(),
+                source: error.into(),
+            })?;
+    Ok(Review::restore(
+        ReviewId(row.try_get("id")?),
+        CatalogProductId(row.try_get("product_id")?),
+        CustomerId(row.try_get("customer_id")?),
+        row.try_get("rating")?,
+        row.try_get("text")?,
+        row.try_get("verified_purchase")?,
+        status,
+        row.try_get("created_at")?,
+        row.try_get("version")?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::{common::test, sqlx::pg_catalog_repository::PgCatalogRepository};
+    use chrono::Utc;
+    use domain::{
+        entities::product::Product,
+        repositories::{
+            catalog_repository::CatalogRepository, review_repository::ReviewRepository,
+        },
+    };
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn averages_the_approved_reviews_of_a_product() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let product = PgCatalogRepository::new(pool.clone())
+            .save_product(Product::new(CatalogProductId(Uuid::new_v4()), "T-shirt", None).unwrap())
+            .await
+            .unwrap();
+        let repository = PgReviewRepository::new(pool);
+        let submit = |customer_id: &CustomerId, rating| {
+            Review::submit(
+                ReviewId(Uuid::new_v4()),
+                &product,
+                customer_id.clone(),
+                rating,
+                "Nice",
+                &[],
+                Utc::now(),
+            )
+            .unwrap()
+        };
+        for (rating, approved) in [(5, true), (2, true), (1, false)] {
+            let mut review = repository
+                .save(submit(&CustomerId(Uuid::new_v4()), rating))
+                .await
+                .unwrap();
+            if approved {
+                review.approve().unwrap();
+            } else {
+                review.reject().unwrap();
+            }
+            let review = repository.update(review).await.unwrap();
+            assert_eq!(2, review.version());
+        }
+
+        let rating = repository.rating_of(product.id().clone()).await.unwrap();
+        assert_eq!(Some(3.5), rating.average);
+        assert_eq!(2, rating.count);
+        let approved = repository
+            .find_by_product_id(product.id().clone(), ReviewStatus::Approved)
+            .await
+            .unwrap();
+        assert_eq!(2, approved.len());
+        let review = repository
+            .find_by_id(approved[0].id().clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(ReviewStatus::Approved, review.status());
+    }
+
+    #[tokio::test]
+    async fn saves_one_review_per_customer_and_product() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let product = PgCatalogRepository::new(pool.clone())
+            .save_product(Product::new(CatalogProductId(Uuid::new_v4()), "T-shirt", None).unwrap())
+            .await
+            .unwrap();
+        let repository = PgReviewRepository::new(pool);
+        let customer_id = CustomerId(Uuid::new_v4());
+        let review = |id| {
+            Review::submit(
+                id,
+                &product,
+                customer_id.clone(),
+                4,
+                "Nice",
+                &[],
+                Utc::now(),
+            )
+            .unwrap()
+        };
+        let review_id = ReviewId(Uuid::new_v4());
+        repository.save(review(review_id.clone())).await.unwrap();
+
+        assert!(matches!(
+            repository.save(review(review_id)).await,
+            Err(ReviewRepositoryError::ReviewAlreadyExistsError)
+        ));
+        assert!(matches!(
+            repository.save(review(ReviewId(Uuid::new_v4()))).await,
+            Err(ReviewRepositoryError::ProductAlreadyReviewedError)
+        ));
+    }
+}
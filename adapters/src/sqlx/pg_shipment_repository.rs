@@ -0,0 +1,412 @@
+use super::{
+    pg_order_repository::bump_order_version, pg_transactional_repository::PgTransactionalRepository,
+};
+use async_trait::async_trait;
+use domain::{
+    entities::{
+        order_lines::OrderLines,
+        shipment::{Shipment, ShipmentState},
+    },
+    repositories::{
+        shipment_repository::ShipmentRepositoryError,
+        transactional_repository::TransactionalRepositoryError,
+    },
+    value_objects::{OrderId, OrderItem, ProductId, ShipmentId, ShippingMethodId},
+};
+use sqlx::{postgres::PgRow, PgConnection, Pool, Postgres, Row};
+use uuid::Uuid;
+
+pub struct PgShipmentRepository<'a> {
+    pool: Pool<Postgres>,
+    transactional: PgTransactionalRepository<'a>,
+}
+
+impl<'a> PgShipmentRepository<'a> {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        let transactional = PgTransactionalRepository::new(pool.clone());
+        Self {
+            pool,
+            transactional,
+        }
+    }
+
+    /// Reads the items of a shipment row.
+    async fn with_items(&self, row: PgRow) -> Result<Shipment, ShipmentRepositoryError> {
+        let shipment_id: Uuid = row
+            .try_get("id")
+            .map_err(|e| ShipmentRepositoryError::ShipmentNotReadError(e.to_string()))?;
+        let shipment_items =
+            sqlx::query("SELECT * FROM shipment_items WHERE shipment_id = $1 ORDER BY product_id")
+                .bind(shipment_id)
+                .try_map(|row: PgRow| {
+                    Ok(OrderItem {
+                        price: row.try_get("price")?,
+                        quantity: row.try_get("quantity")?,
+                        product_id: ProductId(row.try_get("product_id")?),
+                    })
+                })
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| ShipmentRepositoryError::ShipmentNotReadError(e.to_string()))?;
+        let items = OrderLines::try_from(shipment_items)
+            .map_err(|e| ShipmentRepositoryError::ShipmentNotReadError(e.to_string()))?;
+
+        shipment_from_row(row, items)
+            .map_err(|e| ShipmentRepositoryError::ShipmentNotReadError(e.to_string()))
+    }
+
+    /// Updates the shipment, moving the order to its next version too if
+    /// `order_version` is given.
+    async fn write(
+        &self,
+        shipment: Shipment,
+        order_version: Option<i64>,
+    ) -> Result<Shipment, ShipmentRepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| ShipmentRepositoryError::ShipmentNotSavedError)?;
+        if let Some(order_version) = order_version {
+            check_order_version(&mut tx, &shipment, order_version).await?;
+        }
+        let result = sqlx::query(
+            r#"
+            UPDATE shipments SET tracking_number = $3, state = $4, version = version + 1
+            WHERE id = $1 AND version = $2
+            "#,
+        )
+        .bind(shipment.id().0)
+        .bind(shipment.version())
+        .bind(shipment.tracking_number())
+        .bind(shipment.state().to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| ShipmentRepositoryError::ShipmentNotSavedError)?;
+        if result.rows_affected() == 0 {
+            return Err(ShipmentRepositoryError::ConcurrencyConflict);
+        }
+
+        // Like returns, shipments are small: their items are simply rewritten.
+        sqlx::query("DELETE FROM shipment_items WHERE shipment_id = $1")
+            .bind(shipment.id().0)
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| ShipmentRepositoryError::ShipmentNotSavedError)?;
+        insert_shipment_items(&mut tx, &shipment).await?;
+        tx.commit()
+            .await
+            .map_err(|_| ShipmentRepositoryError::ShipmentNotSavedError)?;
+
+        let version = shipment.version() + 1;
+        Ok(shipment.with_version(version))
+    }
+}
+
+#[async_trait]
+impl<'a> domain::repositories::transactional_repository::TransactionalRepository
+    for PgShipmentRepository<'a>
+{
+    async fn begin_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        self.transactional.begin_transaction().await
+    }
+    async fn commit_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        self.transactional.commit_transaction().await
+    }
+    async fn rollback_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        self.transactional.rollback_transaction().await
+    }
+}
+
+#[async_trait]
+impl<'a> domain::repositories::shipment_repository::ShipmentRepository
+    for PgShipmentRepository<'a>
+{
+    async fn find_by_id(
+        &self,
+        id: ShipmentId,
+    ) -> Result<Option<Shipment>, ShipmentRepositoryError> {
+        let row = sqlx::query("SELECT * FROM shipments WHERE id = $1")
+            .bind(id.0)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| ShipmentRepositoryError::ShipmentNotReadError(e.to_string()))?;
+        match row {
+            Some(row) => self.with_items(row).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    async fn find_by_order_id(
+        &self,
+        order_id: OrderId,
+    ) -> Result<Vec<Shipment>, ShipmentRepositoryError> {
+        let rows = sqlx::query("SELECT * FROM shipments WHERE order_id = $1 ORDER BY id")
+            .bind(order_id.0)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ShipmentRepositoryError::ShipmentNotReadError(e.to_string()))?;
+        let mut shipments = vec![];
+        for row in rows {
+            shipments.push(self.with_items(row).await?);
+        }
+        Ok(shipments)
+    }
+
+    async fn save(
+        &self,
+        shipment: Shipment,
+        order_version: i64,
+    ) -> Result<Shipment, ShipmentRepositoryError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|_| ShipmentRepositoryError::ShipmentNotSavedError)?;
+        check_order_version(&mut tx, &shipment, order_version).await?;
+        let result = sqlx::query(
+            r#"
+            INSERT INTO shipments (
+                id, order_id, shipping_method_id, carrier, weight, tracking_number, state, version
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, 1)
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+        .bind(shipment.id().0)
+        .bind(shipment.order_id().0)
+        .bind(shipment.shipping_method_id().0)
+        .bind(shipment.carrier())
+        .bind(shipment.weight())
+        .bind(shipment.tracking_number())
+        .bind(shipment.state().to_string())
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| ShipmentRepositoryError::ShipmentNotSavedError)?;
+        if result.rows_affected() == 0 {
+            return Err(ShipmentRepositoryError::ConcurrencyConflict);
+        }
+
+        insert_shipment_items(&mut tx, &shipment).await?;
+        tx.commit()
+            .await
+            .map_err(|_| ShipmentRepositoryError::ShipmentNotSavedError)?;
+        Ok(shipment.with_version(1))
+    }
+
+    async fn update(&self, shipment: Shipment) -> Result<Shipment, ShipmentRepositoryError> {
+        self.write(shipment, None).await
+    }
+
+    async fn update_items(
+        &self,
+        shipment: Shipment,
+        order_version: i64,
+    ) -> Result<Shipment, ShipmentRepositoryError> {
+        self.write(shipment, Some(order_version)).await
+    }
+}
+
+async fn check_order_version(
+    connection: &mut PgConnection,
+    shipment: &Shipment,
+    order_version: i64,
+) -> Result<(), ShipmentRepositoryError> {
+    let bumped = bump_order_version(connection, shipment.order_id(), order_version)
+        .await
+        .map_err(|_| ShipmentRepositoryError::ShipmentNotSavedError)?;
+    if !bumped {
+        return Err(ShipmentRepositoryError::ConcurrencyConflict);
+    }
+    Ok(())
+}
+
+async fn insert_shipment_items(
+    connection: &mut PgConnection,
+    shipment: &Shipment,
+) -> Result<(), ShipmentRepositoryError> {
+    for item in shipment.items().iter() {
+        sqlx::query(
+            "INSERT INTO shipment_items (shipment_id, product_id, quantity, price) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(shipment.id().0)
+        .bind(item.product_id.0)
+        .bind(item.quantity)
+        .bind(item.price)
+        .execute(&mut *connection)
+        .await
+        .map_err(|_| ShipmentRepositoryError::ShipmentNotSavedError)?;
+    }
+    Ok(())
+}
+
+fn shipment_from_row(row: PgRow, items: OrderLines) -> Result<Shipment, sqlx::Error> {
+    let state: ShipmentState =
+        row.try_get::<&str, _>("state")?
+            .parse()
+            .map_err(|error: String| sqlx::Error::ColumnDecode {
+                index: "state".to_string(),
+                source: error.into(),
+            })?;
+    Ok(Shipment::restore(
+        ShipmentId(row.try_get("id")?),
+        OrderId(row.try_get("order_id")?),
+        ShippingMethodId(row.try_get("shipping_method_id")?),
+        row.try_get("carrier")?,
+        items,
+        row.try_get("weight")?,
+        row.try_get("tracking_number")?,
+        state,
+        row.try_get("version")?,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::{
+        common::test,
+        sqlx::{
+            pg_order_repository::PgOrderRepository,
+            pg_shipping_method_repository::PgShippingMethodRepository,
+        },
+    };
+    use domain::{
+        entities::{
+            order::{Order, OrderStatus},
+            shipping_method::{ShippingMethod, ShippingRate},
+        },
+        repositories::{
+            order_repository::OrderRepository, shipment_repository::ShipmentRepository,
+            shipping_method_repository::ShippingMethodRepository,
+        },
+        value_objects::CustomerId,
+    };
+
+    #[tokio::test]
+    async fn saves_and_updates_a_shipment_with_its_items() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let order = order(&pool).await;
+        let shipping_method = shipping_method(&pool).await;
+        let repository = PgShipmentRepository::new(pool);
+        let mut shipment =
+            Shipment::prepare(ShipmentId(Uuid::new_v4()), &order, &shipping_method, 1.2).unwrap();
+        shipment.add(&order, &[], product_id(1), 1).unwrap();
+        let mut shipment = repository.save(shipment, order.version()).await.unwrap();
+
+        shipment.add(&order, &[], product_id(2), 2).unwrap();
+        shipment = repository
+            .update_items(shipment, order.version() + 1)
+            .await
+            .unwrap();
+        shipment.ship("1Z999AA10123456784").unwrap();
+        let shipment = repository.update(shipment).await.unwrap();
+
+        let shipments_from_db = repository
+            .find_by_order_id(order.id().clone())
+            .await
+            .unwrap();
+        assert_eq!(vec![shipment], shipments_from_db);
+        assert_eq!(3, shipments_from_db[0].version());
+        assert_eq!(
+            Some("1Z999AA10123456784"),
+            shipments_from_db[0].tracking_number()
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_updates_of_a_stale_shipment() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let order = order(&pool).await;
+        let shipping_method = shipping_method(&pool).await;
+        let shipment_id = ShipmentId(Uuid::new_v4());
+        let repository = PgShipmentRepository::new(pool);
+        let mut shipment =
+            Shipment::prepare(shipment_id.clone(), &order, &shipping_method, 1.0).unwrap();
+        shipment.add(&order, &[], product_id(1), 1).unwrap();
+        let shipment = repository.save(shipment, order.version()).await.unwrap();
+        assert!(matches!(
+            repository.save(shipment.clone(), order.version() + 1).await,
+            Err(ShipmentRepositoryError::ConcurrencyConflict)
+        ));
+
+        let mut stale = shipment.clone();
+        stale.ship("STALE").unwrap();
+        let mut shipped = shipment;
+        shipped.ship("FRESH").unwrap();
+        repository.update(shipped).await.unwrap();
+
+        assert!(matches!(
+            repository.update(stale).await,
+            Err(ShipmentRepositoryError::ConcurrencyConflict)
+        ));
+        let shipment_from_db = repository.find_by_id(shipment_id).await.unwrap().unwrap();
+        assert_eq!(Some("FRESH"), shipment_from_db.tracking_number());
+    }
+
+    #[tokio::test]
+    async fn rejects_shipments_checked_against_a_stale_order() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let order = order(&pool).await;
+        let shipping_method = shipping_method(&pool).await;
+        let repository = PgShipmentRepository::new(pool);
+        let mut first =
+            Shipment::prepare(ShipmentId(Uuid::new_v4()), &order, &shipping_method, 1.0).unwrap();
+        first.add(&order, &[], product_id(1), 1).unwrap();
+        let mut second =
+            Shipment::prepare(ShipmentId(Uuid::new_v4()), &order, &shipping_method, 1.0).unwrap();
+        second.add(&order, &[], product_id(1), 1).unwrap();
+        repository.save(first, order.version()).await.unwrap();
+
+        assert!(matches!(
+            repository.save(second, order.version()).await,
+            Err(ShipmentRepositoryError::ConcurrencyConflict)
+        ));
+        let shipments_from_db = repository
+            .find_by_order_id(order.id().clone())
+            .await
+            .unwrap();
+        assert_eq!(1, shipments_from_db.len());
+    }
+
+    async fn shipping_method(pool: &Pool<Postgres>) -> ShippingMethod {
+        let shipping_method = ShippingMethod::new(
+            ShippingMethodId(Uuid::new_v4()),
+            "Standard",
+            "UPS",
+            ShippingRate::Flat(4.9),
+        )
+        .unwrap();
+        PgShippingMethodRepository::new(pool.clone())
+            .save(shipping_method)
+            .await
+            .unwrap()
+    }
+
+    async fn order(pool: &Pool<Postgres>) -> Order {
+        let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()));
+        order
+            .add_multiple(vec![
+                OrderItem {
+                    price: 10.0,
+                    quantity: 1,
+                    product_id: product_id(1),
+                },
+                OrderItem {
+                    price: 5.5,
+                    quantity: 2,
+                    product_id: product_id(2),
+                },
+            ])
+            .unwrap();
+        PgOrderRepository::new(pool.clone())
+            .save(order.with_status(OrderStatus::Confirmed))
+            .await
+            .unwrap()
+    }
+
+    fn product_id(n: u128) -> ProductId {
+        ProductId(Uuid::from_u128(n))
+    }
+}
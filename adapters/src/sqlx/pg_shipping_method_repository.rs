@@ -0,0 +1,141 @@
+use async_trait::async_trait;
+use domain::{
+    entities::shipping_method::{ShippingMethod, ShippingRate},
+    repositories::shipping_method_repository::ShippingMethodRepositoryError,
+    value_objects::ShippingMethodId,
+};
+use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+
+const UNIQUE_VIOLATION: &str = "23505";
+
+pub struct PgShippingMethodRepository {
+    pool: Pool<Postgres>,
+}
+
+impl PgShippingMethodRepository {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl domain::repositories::shipping_method_repository::ShippingMethodRepository
+    for PgShippingMethodRepository
+{
+    async fn find_by_id(
+        &self,
+        id: ShippingMethodId,
+    ) -> Result<Option<ShippingMethod>, ShippingMethodRepositoryError> {
+        sqlx::query("SELECT * FROM shipping_methods WHERE id = $1")
+            .bind(id.0)
+            .try_map(shipping_method_from_row)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| ShippingMethodRepositoryError::ShippingMethodNotReadError(e.to_string()))
+    }
+
+    async fn find_all(&self) -> Result<Vec<ShippingMethod>, ShippingMethodRepositoryError> {
+        sqlx::query("SELECT * FROM shipping_methods ORDER BY name, id")
+            .try_map(shipping_method_from_row)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ShippingMethodRepositoryError::ShippingMethodNotReadError(e.to_string()))
+    }
+
+    async fn save(
+        &self,
+        shipping_method: ShippingMethod,
+    ) -> Result<ShippingMethod, ShippingMethodRepositoryError> {
+        let (amount, per_kg, threshold) = match shipping_method.rate() {
+            ShippingRate::Flat(amount) => (*amount, None, None),
+            ShippingRate::WeightBased { base, per_kg } => (*base, Some(*per_kg), None),
+            ShippingRate::FreeOver { amount, threshold } => (*amount, None, Some(*threshold)),
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO shipping_methods (id, name, carrier, kind, amount, per_kg, threshold)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(shipping_method.id().0)
+        .bind(shipping_method.name())
+        .bind(shipping_method.carrier())
+        .bind(shipping_method.rate().kind())
+        .bind(amount)
+        .bind(per_kg)
+        .bind(threshold)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e.as_database_error().and_then(|e| e.code()) {
+            Some(code) if code == UNIQUE_VIOLATION => {
+                ShippingMethodRepositoryError::ShippingMethodAlreadyExistsError
+            }
+            _ => ShippingMethodRepositoryError::ShippingMethodNotSavedError,
+        })?;
+        Ok(shipping_method)
+    }
+}
+
+fn shipping_method_from_row(row: PgRow) -> Result<ShippingMethod, sqlx::Error> {
+    let decode =
+        |error: domain::entities::shipping_method::ShippingMethodError| sqlx::Error::ColumnDecode {
+            index: "kind".to_string(),
+            source: Box::new(error),
+        };
+    let rate = ShippingRate::parse(
+        row.try_get("kind")?,
+        row.try_get("amount")?,
+        row.try_get("per_kg")?,
+        row.try_get("threshold")?,
+    )
+    .map_err(decode)?;
+    ShippingMethod::new(
+        ShippingMethodId(row.try_get("id")?),
+        row.try_get("name")?,
+        row.try_get("carrier")?,
+        rate,
+    )
+    .map_err(decode)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::common::test;
+    use domain::repositories::shipping_method_repository::ShippingMethodRepository;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn saves_shipping_methods_and_finds_them() {
+        let repository = PgShippingMethodRepository::new(test::create_sqlx_connection_pool().await);
+        let shipping_method = ShippingMethod::new(
+            ShippingMethodId(Uuid::new_v4()),
+            "Express",
+            "DHL",
+            ShippingRate::WeightBased {
+                base: 9.9,
+                per_kg: 2.0,
+            },
+        )
+        .unwrap();
+        repository.save(shipping_method.clone()).await.unwrap();
+
+        assert!(matches!(
+            repository.save(shipping_method.clone()).await,
+            Err(ShippingMethodRepositoryError::ShippingMethodAlreadyExistsError)
+        ));
+        assert_eq!(
+            Some(shipping_method.clone()),
+            repository
+                .find_by_id(shipping_method.id().clone())
+                .await
+                .unwrap()
+        );
+        assert!(repository
+            .find_all()
+            .await
+            .unwrap()
+            .contains(&shipping_method));
+    }
+}
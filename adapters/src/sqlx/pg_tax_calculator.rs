@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use domain::{
+    entities::tax::{TaxCategory, TaxRate},
+    gateways::tax_calculator::{TaxCalculator, TaxCalculatorError, TaxRule, TaxRuleTable},
+    value_objects::{Address, OrderItem},
+};
+use sqlx::{postgres::PgRow, Pool, Postgres, Row};
+use uuid::Uuid;
+
+/// Finds rates in the `tax_rules` table and product categories in
+/// `product_tax_categories`.
+pub struct PgTaxCalculator {
+    pool: Pool<Postgres>,
+}
+
+impl PgTaxCalculator {
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl TaxCalculator for PgTaxCalculator {
+    async fn tax_rates(
+        &self,
+        address: &Address,
+        items: &[OrderItem],
+    ) -> Result<Vec<TaxRate>, TaxCalculatorError> {
+        let rules = sqlx::query("SELECT * FROM tax_rules WHERE country = $1")
+            .bind(address.country.as_str())
+            .try_map(|row: PgRow| {
+                let region: String = row.try_get("region")?;
+                Ok(TaxRule {
+                    country: address.country.clone(),
+                    // An empty region is the whole country.
+                    region: (!region.is_empty()).then_some(region),
+                    category: category_from_row(&row)?,
+                    rate: row.try_get("rate")?,
+                })
+            })
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| TaxCalculatorError::TaxRulesUnavailableError(e.to_string()))?;
+
+        let product_ids: Vec<Uuid> = items.iter().map(|item| item.product_id.0).collect();
+        let product_categories =
+            sqlx::query("SELECT * FROM product_tax_categories WHERE product_id = ANY($1)")
+                .bind(product_ids)
+                .try_map(|row: PgRow| {
+                    Ok((
+                        row.try_get::<Uuid, _>("product_id")?,
+                        category_from_row(&row)?,
+                    ))
+                })
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| TaxCalculatorError::TaxRulesUnavailableError(e.to_string()))?
+                .into_iter()
+                .collect::<HashMap<_, _>>();
+
+        Ok(TaxRuleTable::new(rules, product_categories).rates(address, items))
+    }
+}
+
+fn category_from_row(row: &PgRow) -> Result<TaxCategory, sqlx::Error> {
+    row.try_get::<&str, _>("category")?
+        .parse()
+        .map_err(|error: String| sqlx::Error::ColumnDecode {
+            index: "category".to_string(),
+            source: error.into(),
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::test;
+    use domain::value_objects::{CountryCode, PostalCode, ProductId};
+
+    #[tokio::test]
+    async fn finds_the_rates_of_the_rules_and_categories_in_the_database() {
+        let pool = test::create_sqlx_connection_pool().await;
+        for (region, category, rate) in [("", "standard", 18.0), ("", "reduced", 5.0)] {
+            sqlx::query(
+                r#"
+                INSERT INTO tax_rules (country, region, category, rate) VALUES ('MT', $1, $2, $3)
+                ON CONFLICT (country, region, category) DO UPDATE SET rate = EXCLUDED.rate
+                "#,
+            )
+            .bind(region)
+            .bind(category)
+            .bind(rate)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let reduced_product_id = ProductId(Uuid::new_v4());
+        sqlx::query(
+            "INSERT INTO product_tax_categories (product_id, category) VALUES ($1, 'reduced')",
+        )
+        .bind(reduced_product_id.0)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let country = CountryCode::parse("MT").unwrap();
+        let address = Address {
+            street: "Triq il-Merkanti 1".to_string(),
+            city: "Valletta".to_string(),
+            zip_code: PostalCode::parse("VLT 1117", &country).unwrap(),
+            country,
+        };
+        let items = [
+            OrderItem {
+                price: 10.0,
+                quantity: 1,
+                product_id: ProductId(Uuid::new_v4()),
+            },
+            OrderItem {
+                price: 10.0,
+                quantity: 1,
+                product_id: reduced_product_id,
+            },
+        ];
+
+        let rates = PgTaxCalculator::new(pool)
+            .tax_rates(&address, &items)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            vec![(TaxCategory::Standard, 18.0), (TaxCategory::Reduced, 5.0)],
+            rates
+                .iter()
+                .map(|rate| (rate.category, rate.rate))
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use crate::value_objects::{Address, AddressId};
+
+pub const MAX_ADDRESSES_PER_CUSTOMER: usize = 20;
+
+#[derive(Debug, PartialEq)]
+pub enum AddressBookError {
+    AddressNotFoundError(AddressId),
+    DuplicateAddressError(AddressId),
+    AddressBookLimitExceededError,
+}
+
+impl std::fmt::Display for AddressBookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddressBookError::AddressNotFoundError(address_id) => {
+                write!(f, "Address {} is not in the address book", address_id.0)
+            }
+            AddressBookError::DuplicateAddressError(address_id) => {
+                write!(f, "Address {} is already in the address book", address_id.0)
+            }
+            AddressBookError::AddressBookLimitExceededError => write!(
+                f,
+                "A customer can't have more than {} addresses",
+                MAX_ADDRESSES_PER_CUSTOMER
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AddressBookError {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CustomerAddress {
+    pub id: AddressId,
+    pub address: Address,
+}
+
+/// Addresses of a customer. The default shipping and billing addresses, when
+/// set, are always in the book.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AddressBook {
+    addresses: Vec<CustomerAddress>,
+    default_shipping: Option<AddressId>,
+    default_billing: Option<AddressId>,
+}
+
+impl AddressBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuilds an address book read from storage.
+    pub fn restore(
+        addresses: Vec<CustomerAddress>,
+        default_shipping: Option<AddressId>,
+        default_billing: Option<AddressId>,
+    ) -> Result<Self, AddressBookError> {
+        let mut address_book = AddressBook::new();
+        for customer_address in addresses {
+            address_book.add(customer_address.id, customer_address.address)?;
+        }
+        address_book.default_shipping = None;
+        address_book.default_billing = None;
+        if let Some(address_id) = default_shipping {
+            address_book.set_default_shipping(&address_id)?;
+        }
+        if let Some(address_id) = default_billing {
+            address_book.set_default_billing(&address_id)?;
+        }
+        Ok(address_book)
+    }
+
+    /// Adds an address. The first one becomes the default for both shipping
+    /// and billing.
+    pub fn add(&mut self, id: AddressId, address: Address) -> Result<(), AddressBookError> {
+        if self.find(&id).is_some() {
+            return Err(AddressBookError::DuplicateAddressError(id));
+        }
+        if self.addresses.len() >= MAX_ADDRESSES_PER_CUSTOMER {
+            return Err(AddressBookError::AddressBookLimitExceededError);
+        }
+        if self.addresses.is_empty() {
+            self.default_shipping = Some(id.clone());
+            self.default_billing = Some(id.clone());
+        }
+        self.addresses.push(CustomerAddress { id, address });
+        Ok(())
+    }
+
+    /// Removes an address, which stops being a default if it was one.
+    pub fn remove(&mut self, id: &AddressId) -> Result<Address, AddressBookError> {
+        let position = self
+            .addresses
+            .iter()
+            .position(|customer_address| &customer_address.id == id)
+            .ok_or_else(|| AddressBookError::AddressNotFoundError(id.clone()))?;
+        if self.default_shipping.as_ref() == Some(id) {
+            self.default_shipping = None;
+        }
+        if self.default_billing.as_ref() == Some(id) {
+            self.default_billing = None;
+        }
+        Ok(self.addresses.remove(position).address)
+    }
+
+    pub fn set_default_shipping(&mut self, id: &AddressId) -> Result<(), AddressBookError> {
+        self.get(id)?;
+        self.default_shipping = Some(id.clone());
+        Ok(())
+    }
+
+    pub fn set_default_billing(&mut self, id: &AddressId) -> Result<(), AddressBookError> {
+        self.get(id)?;
+        self.default_billing = Some(id.clone());
+        Ok(())
+    }
+
+    pub fn find(&self, id: &AddressId) -> Option<&Address> {
+        self.addresses
+            .iter()
+            .find(|customer_address| &customer_address.id == id)
+            .map(|customer_address| &customer_address.address)
+    }
+
+    pub fn get(&self, id: &AddressId) -> Result<&Address, AddressBookError> {
+        self.find(id)
+            .ok_or_else(|| AddressBookError::AddressNotFoundError(id.clone()))
+    }
+
+    pub fn default_shipping(&self) -> Option<&AddressId> {
+        self.default_shipping.as_ref()
+    }
+
+    pub fn default_billing(&self) -> Option<&AddressId> {
+        self.default_billing.as_ref()
+    }
+
+    pub fn as_slice(&self) -> &[CustomerAddress] {
+        &self.addresses
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use crate::value_objects::{CountryCode, PostalCode};
+
+    use super::*;
+
+    #[test]
+    fn the_first_address_becomes_the_default_one() {
+        let mut address_book = AddressBook::new();
+        let first = AddressId(Uuid::new_v4());
+        let second = AddressId(Uuid::new_v4());
+
+        address_book.add(first.clone(), address("04401")).unwrap();
+        address_book.add(second.clone(), address("62701")).unwrap();
+
+        assert_eq!(Some(&first), address_book.default_shipping());
+        assert_eq!(Some(&first), address_book.default_billing());
+        assert_eq!(2, address_book.as_slice().len());
+    }
+
+    #[test]
+    fn sets_the_default_addresses() {
+        let mut address_book = AddressBook::new();
+        let first = AddressId(Uuid::new_v4());
+        let second = AddressId(Uuid::new_v4());
+        address_book.add(first.clone(), address("04401")).unwrap();
+        address_book.add(second.clone(), address("62701")).unwrap();
+
+        address_book.set_default_billing(&second).unwrap();
+
+        assert_eq!(Some(&first), address_book.default_shipping());
+        assert_eq!(Some(&second), address_book.default_billing());
+        let missing = AddressId(Uuid::new_v4());
+        assert_eq!(
+            Err(AddressBookError::AddressNotFoundError(missing.clone())),
+            address_book.set_default_shipping(&missing)
+        );
+    }
+
+    #[test]
+    fn removing_a_default_address_unsets_it() {
+        let mut address_book = AddressBook::new();
+        let address_id = AddressId(Uuid::new_v4());
+        address_book
+            .add(address_id.clone(), address("04401"))
+            .unwrap();
+
+        let removed = address_book.remove(&address_id);
+
+        assert_eq!(Ok(address("04401")), removed);
+        assert!(address_book.is_empty());
+        assert_eq!(None, address_book.default_shipping());
+        assert_eq!(None, address_book.default_billing());
+    }
+
+    #[test]
+    fn rejects_addresses_over_the_maximum() {
+        let mut address_book = AddressBook::new();
+        for _ in 0..MAX_ADDRESSES_PER_CUSTOMER {
+            address_book
+                .add(AddressId(Uuid::new_v4()), address("04401"))
+                .unwrap();
+        }
+
+        let result = address_book.add(AddressId(Uuid::new_v4()), address("04401"));
+
+        assert_eq!(Err(AddressBookError::AddressBookLimitExceededError), result);
+    }
+
+    #[test]
+    fn cannot_restore_a_default_address_missing_from_the_book() {
+        let address_id = AddressId(Uuid::new_v4());
+
+        let result = AddressBook::restore(vec![], Some(address_id.clone()), None);
+
+        assert_eq!(
+            Err(AddressBookError::AddressNotFoundError(address_id)),
+            result
+        );
+    }
+
+    fn address(zip_code: &str) -> Address {
+        let country = CountryCode::parse("US").unwrap();
+        Address {
+            street: "22 Elm Street".to_string(),
+            city: "Castle Rock".to_string(),
+            zip_code: PostalCode::parse(zip_code, &country).unwrap(),
+            country,
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    entities::order_lines::{OrderLines, OrderLinesError},
+    value_objects::{CartId, CustomerId, OrderItem, ProductId},
+};
+
+/// A cart expires this long after its last change.
+pub const CART_LIFETIME_DAYS: i64 = 30;
+
+#[derive(Debug, PartialEq)]
+pub enum CartError {
+    CartExpiredError,
+    CartCheckedOutError,
+    EmptyCartError,
+    AnonymousCartError,
+    CartOfAnotherCustomerError,
+    InvalidCartLinesError(OrderLinesError),
+}
+
+impl std::fmt::Display for CartError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CartError::CartExpiredError => write!(f, "Cart is expired"),
+            CartError::CartCheckedOutError => write!(f, "Cart is already checked out"),
+            CartError::EmptyCartError => write!(f, "Cart is empty"),
+            CartError::AnonymousCartError => write!(f, "Cart belongs to no customer"),
+            CartError::CartOfAnotherCustomerError => {
+                write!(f, "Cart belongs to another customer")
+            }
+            CartError::InvalidCartLinesError(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for CartError {}
+
+impl From<OrderLinesError> for CartError {
+    fn from(error: OrderLinesError) -> Self {
+        CartError::InvalidCartLinesError(error)
+    }
+}
+
+/// Items collected before placing an order, by a customer or by an anonymous
+/// visitor. Lines follow the same rules as the ones of an order.
+pub struct Cart {
+    id: CartId,
+    customer_id: Option<CustomerId>,
+    cart_lines: OrderLines,
+    expires_at: DateTime<Utc>,
+    checked_out_at: Option<DateTime<Utc>>,
+    version: i64,
+}
+
+impl Cart {
+    pub fn new(id: CartId, customer_id: Option<CustomerId>, now: DateTime<Utc>) -> Self {
+        Self {
+            id,
+            customer_id,
+            cart_lines: OrderLines::new(),
+            expires_at: now + Duration::days(CART_LIFETIME_DAYS),
+            checked_out_at: None,
+            version: 0,
+        }
+    }
+
+    /// Rebuilds a cart read from storage.
+    pub fn restore(
+        id: CartId,
+        customer_id: Option<CustomerId>,
+        cart_lines: OrderLines,
+        expires_at: DateTime<Utc>,
+        checked_out_at: Option<DateTime<Utc>>,
+        version: i64,
+    ) -> Self {
+        Self {
+            id,
+            customer_id,
+            cart_lines,
+            expires_at,
+            checked_out_at,
+            version,
+        }
+    }
+
+    pub fn id(&self) -> &CartId {
+        &self.id
+    }
+
+    /// `None` for carts of visitors not logged in.
+    pub fn customer_id(&self) -> Option<&CustomerId> {
+        self.customer_id.as_ref()
+    }
+
+    pub fn cart_lines(&self) -> &OrderLines {
+        &self.cart_lines
+    }
+
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.expires_at
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+
+    /// Set while an order is being placed with the items of the cart.
+    pub fn checked_out_at(&self) -> Option<DateTime<Utc>> {
+        self.checked_out_at
+    }
+
+    pub fn is_checked_out(&self) -> bool {
+        self.checked_out_at.is_some()
+    }
+
+    /// Version the cart was read at, checked when it is updated. 0 until saved.
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    /// Returns the cart at the version it has been stored with.
+    pub fn with_version(self, version: i64) -> Self {
+        Self { version, ..self }
+    }
+
+    /// Adds an item, or increases the quantity of the product if it is
+    /// already in the cart.
+    pub fn add(&mut self, item: OrderItem, now: DateTime<Utc>) -> Result<(), CartError> {
+        self.check_open(now)?;
+        self.cart_lines.add(item)?;
+        self.touch(now);
+        Ok(())
+    }
+
+    pub fn change_quantity(
+        &mut self,
+        product_id: &ProductId,
+        quantity: i32,
+        now: DateTime<Utc>,
+    ) -> Result<(), CartError> {
+        self.check_open(now)?;
+        self.cart_lines.change_quantity(product_id, quantity)?;
+        self.touch(now);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, product_id: &ProductId, now: DateTime<Utc>) -> Result<(), CartError> {
+        self.check_open(now)?;
+        self.cart_lines.remove(product_id)?;
+        self.touch(now);
+        Ok(())
+    }
+
+    /// Binds an anonymous cart to the customer who just logged in.
+    pub fn assign_to(
+        &mut self,
+        customer_id: CustomerId,
+        now: DateTime<Utc>,
+    ) -> Result<(), CartError> {
+        self.check_open(now)?;
+        match &self.customer_id {
+            Some(owner) if owner != &customer_id => Err(CartError::CartOfAnotherCustomerError),
+            _ => {
+                self.customer_id = Some(customer_id);
+                self.touch(now);
+                Ok(())
+            }
+        }
+    }
+
+    /// Moves the items of `other` into this cart, adding up the quantities of
+    /// products in both. Prices already in this cart are kept.
+    pub fn merge(&mut self, other: Cart, now: DateTime<Utc>) -> Result<(), CartError> {
+        self.check_open(now)?;
+        other.check_open(now)?;
+        let mut cart_lines = self.cart_lines.clone();
+        for item in other.cart_lines.iter() {
+            let price = cart_lines
+                .find(&item.product_id)
+                .map_or(item.price, |existing| existing.price);
+            cart_lines.add(OrderItem {
+                price,
+                ..item.clone()
+            })?;
+        }
+        self.cart_lines = cart_lines;
+        self.touch(now);
+        Ok(())
+    }
+
+    /// Marks the cart checked out `now`, so that it can't be changed nor
+    /// checked out again, and returns the lines to order.
+    pub fn checkout(&mut self, now: DateTime<Utc>) -> Result<(CustomerId, OrderLines), CartError> {
+        self.check_open(now)?;
+        let Some(customer_id) = &self.customer_id else {
+            return Err(CartError::AnonymousCartError);
+        };
+        if self.cart_lines.is_empty() {
+            return Err(CartError::EmptyCartError);
+        }
+        self.checked_out_at = Some(now);
+        Ok((customer_id.clone(), self.cart_lines.clone()))
+    }
+
+    /// Undoes a checkout whose order could not be placed.
+    pub fn reopen(&mut self) {
+        self.checked_out_at = None;
+    }
+
+    fn check_open(&self, now: DateTime<Utc>) -> Result<(), CartError> {
+        if self.is_expired(now) {
+            return Err(CartError::CartExpiredError);
+        }
+        if self.is_checked_out() {
+            return Err(CartError::CartCheckedOutError);
+        }
+        Ok(())
+    }
+
+    fn touch(&mut self, now: DateTime<Utc>) {
+        self.expires_at = now + Duration::days(CART_LIFETIME_DAYS);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, Utc};
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn changes_extend_the_expiry_of_a_cart() {
+        let created_at = Utc::now();
+        let mut cart = Cart::new(CartId(Uuid::new_v4()), None, created_at);
+        let later = created_at + Duration::days(1);
+
+        cart.add(item(9.99, 1), later).unwrap();
+
+        assert_eq!(
+            later + Duration::days(CART_LIFETIME_DAYS),
+            cart.expires_at()
+        );
+        assert!(cart.is_expired(later + Duration::days(CART_LIFETIME_DAYS)));
+    }
+
+    #[test]
+    fn cannot_change_an_expired_cart() {
+        let created_at = Utc::now();
+        let mut cart = Cart::new(CartId(Uuid::new_v4()), None, created_at);
+
+        let result = cart.add(
+            item(9.99, 1),
+            created_at + Duration::days(CART_LIFETIME_DAYS),
+        );
+
+        assert_eq!(Err(CartError::CartExpiredError), result);
+    }
+
+    #[test]
+    fn merges_the_items_of_another_cart() {
+        let now = Utc::now();
+        let customer_id = CustomerId(Uuid::new_v4());
+        let mut cart = Cart::new(CartId(Uuid::new_v4()), Some(customer_id), now);
+        let mut anonymous_cart = Cart::new(CartId(Uuid::new_v4()), None, now);
+        let shared = item(9.99, 1);
+        cart.add(shared.clone(), now).unwrap();
+        anonymous_cart
+            .add(
+                OrderItem {
+                    price: 5.0,
+                    quantity: 2,
+                    ..shared.clone()
+                },
+                now,
+            )
+            .unwrap();
+        anonymous_cart.add(item(1.5, 4), now).unwrap();
+
+        cart.merge(anonymous_cart, now).unwrap();
+
+        assert_eq!(2, cart.cart_lines().len());
+        let merged = cart.cart_lines().find(&shared.product_id).unwrap();
+        assert_eq!(3, merged.quantity);
+        assert_eq!(9.99, merged.price);
+    }
+
+    #[test]
+    fn only_carts_of_customers_with_items_can_be_checked_out() {
+        let now = Utc::now();
+        let mut cart = Cart::new(CartId(Uuid::new_v4()), None, now);
+        cart.add(item(9.99, 1), now).unwrap();
+        assert_eq!(
+            Some(CartError::AnonymousCartError),
+            cart.checkout(now).err()
+        );
+
+        let customer_id = CustomerId(Uuid::new_v4());
+        cart.assign_to(customer_id.clone(), now).unwrap();
+        assert_eq!(customer_id, cart.checkout(now).unwrap().0);
+
+        let mut empty_cart = Cart::new(CartId(Uuid::new_v4()), Some(customer_id), now);
+        assert_eq!(
+            Some(CartError::EmptyCartError),
+            empty_cart.checkout(now).err()
+        );
+    }
+
+    #[test]
+    fn a_checked_out_cart_is_closed_until_reopened() {
+        let now = Utc::now();
+        let mut cart = Cart::new(
+            CartId(Uuid::new_v4()),
+            Some(CustomerId(Uuid::new_v4())),
+            now,
+        );
+        cart.add(item(9.99, 1), now).unwrap();
+
+        cart.checkout(now).unwrap();
+
+        assert_eq!(Some(now), cart.checked_out_at());
+        assert_eq!(
+            Some(CartError::CartCheckedOutError),
+            cart.checkout(now).err()
+        );
+        assert_eq!(
+            Err(CartError::CartCheckedOutError),
+            cart.add(item(1.5, 1), now)
+        );
+        cart.reopen();
+        assert!(cart.checkout(now).is_ok());
+    }
+
+    fn item(price: f64, quantity: i32) -> OrderItem {
+        OrderItem {
+            price,
+            quantity,
+            product_id: ProductId(Uuid::new_v4()),
+        }
+    }
+}
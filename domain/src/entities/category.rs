@@ -0,0 +1,55 @@
+use crate::value_objects::CategoryId;
+
+#[derive(Debug, PartialEq)]
+pub enum CategoryError {
+    EmptyNameError,
+}
+
+impl std::fmt::Display for CategoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CategoryError::EmptyNameError => write!(f, "Category has no name"),
+        }
+    }
+}
+
+impl std::error::Error for CategoryError {}
+
+/// Node of the category tree products are browsed by.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Category {
+    id: CategoryId,
+    name: String,
+    parent_id: Option<CategoryId>,
+}
+
+impl Category {
+    /// A category under `parent_id`, or a root one without it.
+    pub fn new(
+        id: CategoryId,
+        name: &str,
+        parent_id: Option<CategoryId>,
+    ) -> Result<Self, CategoryError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(CategoryError::EmptyNameError);
+        }
+        Ok(Self {
+            id,
+            name: name.to_string(),
+            parent_id,
+        })
+    }
+
+    pub fn id(&self) -> &CategoryId {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn parent_id(&self) -> Option<&CategoryId> {
+        self.parent_id.as_ref()
+    }
+}
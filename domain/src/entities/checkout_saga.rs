@@ -0,0 +1,313 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::value_objects::{AuthorizationId, OrderId, ReservationId};
+
+/// A checkout waiting on a step for longer than this is cancelled.
+pub const CHECKOUT_STEP_TIMEOUT_MINUTES: i64 = 15;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CheckoutSagaState {
+    ReservingStock,
+    AuthorizingPayment,
+    /// Stock and payment are secured, the order still has to be confirmed.
+    Confirming,
+    Confirmed,
+    Cancelled,
+}
+
+const RESERVING_STOCK: &str = "reserving_stock";
+const AUTHORIZING_PAYMENT: &str = "authorizing_payment";
+const CONFIRMING: &str = "confirming";
+const CONFIRMED: &str = "confirmed";
+const CANCELLED: &str = "cancelled";
+
+impl std::fmt::Display for CheckoutSagaState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckoutSagaState::ReservingStock => write!(f, "{}", RESERVING_STOCK),
+            CheckoutSagaState::AuthorizingPayment => write!(f, "{}", AUTHORIZING_PAYMENT),
+            CheckoutSagaState::Confirming => write!(f, "{}", CONFIRMING),
+            CheckoutSagaState::Confirmed => write!(f, "{}", CONFIRMED),
+            CheckoutSagaState::Cancelled => write!(f, "{}", CANCELLED),
+        }
+    }
+}
+
+impl std::str::FromStr for CheckoutSagaState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            RESERVING_STOCK => Ok(CheckoutSagaState::ReservingStock),
+            AUTHORIZING_PAYMENT => Ok(CheckoutSagaState::AuthorizingPayment),
+            CONFIRMING => Ok(CheckoutSagaState::Confirming),
+            CONFIRMED => Ok(CheckoutSagaState::Confirmed),
+            CANCELLED => Ok(CheckoutSagaState::Cancelled),
+            _ => Err(format!("Unknown checkout saga state: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CheckoutSagaError {
+    UnexpectedStepError(CheckoutSagaState),
+}
+
+impl std::fmt::Display for CheckoutSagaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckoutSagaError::UnexpectedStepError(state) => {
+                write!(f, "Step not expected while the checkout is {}", state)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CheckoutSagaError {}
+
+/// Undoes a step already completed by a cancelled checkout.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Compensation {
+    ReleaseReservation(ReservationId),
+    VoidAuthorization(AuthorizationId),
+}
+
+/// Progress of the checkout of an order: stock is reserved, then payment
+/// authorized, then the order confirmed. If a step fails or times out, the
+/// completed ones are compensated and the order cancelled.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CheckoutSaga {
+    order_id: OrderId,
+    state: CheckoutSagaState,
+    reservation_id: Option<ReservationId>,
+    authorization_id: Option<AuthorizationId>,
+    failure_reason: Option<String>,
+    deadline: DateTime<Utc>,
+    version: i64,
+}
+
+impl CheckoutSaga {
+    pub fn start(order_id: OrderId, now: DateTime<Utc>) -> Self {
+        Self {
+            order_id,
+            state: CheckoutSagaState::ReservingStock,
+            reservation_id: None,
+            authorization_id: None,
+            failure_reason: None,
+            deadline: deadline(now),
+            version: 0,
+        }
+    }
+
+    /// Rebuilds a saga read from storage.
+    pub fn restore(
+        order_id: OrderId,
+        state: CheckoutSagaState,
+        reservation_id: Option<ReservationId>,
+        authorization_id: Option<AuthorizationId>,
+        failure_reason: Option<String>,
+        deadline: DateTime<Utc>,
+        version: i64,
+    ) -> Self {
+        Self {
+            order_id,
+            state,
+            reservation_id,
+            authorization_id,
+            failure_reason,
+            deadline,
+            version,
+        }
+    }
+
+    pub fn order_id(&self) -> &OrderId {
+        &self.order_id
+    }
+
+    pub fn state(&self) -> CheckoutSagaState {
+        self.state
+    }
+
+    pub fn reservation_id(&self) -> Option<&ReservationId> {
+        self.reservation_id.as_ref()
+    }
+
+    pub fn authorization_id(&self) -> Option<&AuthorizationId> {
+        self.authorization_id.as_ref()
+    }
+
+    /// Why the checkout was cancelled.
+    pub fn failure_reason(&self) -> Option<&str> {
+        self.failure_reason.as_deref()
+    }
+
+    /// When the current step times out.
+    pub fn deadline(&self) -> DateTime<Utc> {
+        self.deadline
+    }
+
+    /// Version the saga was read at, checked when it is updated. 0 until saved.
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    /// Returns the saga at the version it has been stored with.
+    pub fn with_version(self, version: i64) -> Self {
+        Self { version, ..self }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.state,
+            CheckoutSagaState::Confirmed | CheckoutSagaState::Cancelled
+        )
+    }
+
+    pub fn is_timed_out(&self, now: DateTime<Utc>) -> bool {
+        !self.is_finished() && self.deadline <= now
+    }
+
+    pub fn stock_reserved(
+        &mut self,
+        reservation_id: ReservationId,
+        now: DateTime<Utc>,
+    ) -> Result<(), CheckoutSagaError> {
+        self.expect(CheckoutSagaState::ReservingStock)?;
+        self.reservation_id = Some(reservation_id);
+        self.advance(CheckoutSagaState::AuthorizingPayment, now);
+        Ok(())
+    }
+
+    pub fn payment_authorized(
+        &mut self,
+        authorization_id: AuthorizationId,
+        now: DateTime<Utc>,
+    ) -> Result<(), CheckoutSagaError> {
+        self.expect(CheckoutSagaState::AuthorizingPayment)?;
+        self.authorization_id = Some(authorization_id);
+        self.advance(CheckoutSagaState::Confirming, now);
+        Ok(())
+    }
+
+    pub fn confirm(&mut self, now: DateTime<Utc>) -> Result<(), CheckoutSagaError> {
+        self.expect(CheckoutSagaState::Confirming)?;
+        self.advance(CheckoutSagaState::Confirmed, now);
+        Ok(())
+    }
+
+    /// Cancels the checkout, returning the compensations for the steps
+    /// completed so far, latest first.
+    pub fn cancel(
+        &mut self,
+        reason: &str,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<Compensation>, CheckoutSagaError> {
+        if self.is_finished() {
+            return Err(CheckoutSagaError::UnexpectedStepError(self.state));
+        }
+        let compensations = self
+            .authorization_id
+            .clone()
+            .map(Compensation::VoidAuthorization)
+            .into_iter()
+            .chain(
+                self.reservation_id
+                    .clone()
+                    .map(Compensation::ReleaseReservation),
+            )
+            .collect();
+        self.failure_reason = Some(reason.to_string());
+        self.advance(CheckoutSagaState::Cancelled, now);
+        Ok(compensations)
+    }
+
+    fn expect(&self, state: CheckoutSagaState) -> Result<(), CheckoutSagaError> {
+        if self.state != state {
+            return Err(CheckoutSagaError::UnexpectedStepError(self.state));
+        }
+        Ok(())
+    }
+
+    fn advance(&mut self, state: CheckoutSagaState, now: DateTime<Utc>) {
+        self.state = state;
+        self.deadline = deadline(now);
+    }
+}
+
+fn deadline(now: DateTime<Utc>) -> DateTime<Utc> {
+    now + Duration::minutes(CHECKOUT_STEP_TIMEOUT_MINUTES)
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, Utc};
+    use uuid::Uuid;
+
+    use crate::value_objects::{AuthorizationId, OrderId, ReservationId};
+
+    use super::{
+        CheckoutSaga, CheckoutSagaError, CheckoutSagaState, Compensation,
+        CHECKOUT_STEP_TIMEOUT_MINUTES,
+    };
+
+    #[test]
+    fn go_through_the_steps_of_a_checkout() {
+        let now = Utc::now();
+        let mut saga = CheckoutSaga::start(OrderId(Uuid::new_v4()), now);
+
+        saga.stock_reserved(ReservationId(Uuid::new_v4()), now)
+            .unwrap();
+        saga.payment_authorized(AuthorizationId("auth".to_string()), now)
+            .unwrap();
+        saga.confirm(now).unwrap();
+
+        assert_eq!(CheckoutSagaState::Confirmed, saga.state());
+        assert!(saga.is_finished());
+    }
+
+    #[test]
+    fn cannot_skip_a_step() {
+        let mut saga = CheckoutSaga::start(OrderId(Uuid::new_v4()), Utc::now());
+
+        let result = saga.payment_authorized(AuthorizationId("auth".to_string()), Utc::now());
+
+        assert_eq!(
+            Err(CheckoutSagaError::UnexpectedStepError(
+                CheckoutSagaState::ReservingStock
+            )),
+            result
+        );
+    }
+
+    #[test]
+    fn compensate_completed_steps_latest_first() {
+        let now = Utc::now();
+        let reservation_id = ReservationId(Uuid::new_v4());
+        let authorization_id = AuthorizationId("auth".to_string());
+        let mut saga = CheckoutSaga::start(OrderId(Uuid::new_v4()), now);
+        saga.stock_reserved(reservation_id.clone(), now).unwrap();
+        saga.payment_authorized(authorization_id.clone(), now)
+            .unwrap();
+
+        let compensations = saga.cancel("Order not confirmed", now).unwrap();
+
+        assert_eq!(
+            vec![
+                Compensation::VoidAuthorization(authorization_id),
+                Compensation::ReleaseReservation(reservation_id)
+            ],
+            compensations
+        );
+        assert_eq!(Some("Order not confirmed"), saga.failure_reason());
+        assert!(saga.cancel("again", now).is_err());
+    }
+
+    #[test]
+    fn time_out_a_step_after_its_deadline() {
+        let now = Utc::now();
+        let saga = CheckoutSaga::start(OrderId(Uuid::new_v4()), now);
+
+        assert!(!saga.is_timed_out(now));
+        assert!(saga.is_timed_out(now + Duration::minutes(CHECKOUT_STEP_TIMEOUT_MINUTES)));
+    }
+}
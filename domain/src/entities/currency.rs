@@ -0,0 +1,224 @@
+use chrono::NaiveDate;
+
+use crate::value_objects::{Currency, ProductId};
+
+#[derive(Debug, PartialEq)]
+pub enum CurrencyError {
+    InvalidRateError(String),
+    InvalidPriceError(String),
+    /// A line of an imported file, numbered from 1.
+    InvalidLineError(usize, String),
+}
+
+impl std::fmt::Display for CurrencyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CurrencyError::InvalidRateError(message) => write!(f, "Invalid rate: {}", message),
+            CurrencyError::InvalidPriceError(message) => write!(f, "Invalid price: {}", message),
+            CurrencyError::InvalidLineError(line, message) => {
+                write!(f, "Invalid line {}: {}", line, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CurrencyError {}
+
+/// Units of a currency worth one unit of the base currency, from a day on
+/// until the next rate of the currency.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExchangeRate {
+    currency: Currency,
+    effective_from: NaiveDate,
+    rate: f64,
+}
+
+impl ExchangeRate {
+    pub fn new(
+        currency: Currency,
+        effective_from: NaiveDate,
+        rate: f64,
+    ) -> Result<Self, CurrencyError> {
+        if currency == Currency::BASE {
+            return Err(CurrencyError::InvalidRateError(format!(
+                "{} is the base currency",
+                currency
+            )));
+        }
+        if !(rate > 0.0 && rate.is_finite()) {
+            return Err(CurrencyError::InvalidRateError(
+                "rate must be positive".to_string(),
+            ));
+        }
+        Ok(Self {
+            currency,
+            effective_from,
+            rate,
+        })
+    }
+
+    /// Reads rates from CSV lines of `currency,effective_from,rate`, with
+    /// dates as `YYYY-MM-DD`. A header line and blank lines are skipped.
+    pub fn parse_csv(csv: &str) -> Result<Vec<Self>, CurrencyError> {
+        csv.lines()
+            .enumerate()
+            .filter(|(index, line)| {
+                let header = *index == 0 && line.trim().starts_with("currency");
+                !line.trim().is_empty() && !header
+            })
+            .map(|(index, line)| {
+                let invalid = |message: String| CurrencyError::InvalidLineError(index + 1, message);
+                let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+                let [currency, effective_from, rate] = fields[..] else {
+                    return Err(invalid("expected currency,effective_from,rate".to_string()));
+                };
+                let currency =
+                    Currency::parse(currency).map_err(|e| invalid(format!("currency {}", e)))?;
+                let effective_from = effective_from
+                    .parse()
+                    .map_err(|_| invalid("effective_from must be YYYY-MM-DD".to_string()))?;
+                let rate = rate
+                    .parse()
+                    .map_err(|_| invalid("rate must be a number".to_string()))?;
+                Self::new(currency, effective_from, rate).map_err(|e| invalid(e.to_string()))
+            })
+            .collect()
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    pub fn effective_from(&self) -> NaiveDate {
+        self.effective_from
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+}
+
+/// Price of a product in one of the currencies of the price list.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProductPrice {
+    pub product_id: ProductId,
+    pub currency: Currency,
+    pub amount: f64,
+}
+
+impl ProductPrice {
+    pub fn new(
+        product_id: ProductId,
+        currency: Currency,
+        amount: f64,
+    ) -> Result<Self, CurrencyError> {
+        if !(amount >= 0.0 && amount.is_finite()) {
+            return Err(CurrencyError::InvalidPriceError(
+                "amount must not be negative".to_string(),
+            ));
+        }
+        if currency == Currency::BASE {
+            return Err(CurrencyError::InvalidPriceError(format!(
+                "the price in {} is the price of the variant",
+                currency
+            )));
+        }
+        Ok(Self {
+            product_id,
+            currency,
+            amount,
+        })
+    }
+}
+
+/// Currency of an order and the exchange rate in effect when it was created,
+/// kept so that its totals convert to the base currency as they did then.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrderCurrency {
+    pub currency: Currency,
+    pub exchange_rate: f64,
+}
+
+impl OrderCurrency {
+    pub fn new(rate: &ExchangeRate) -> Self {
+        Self {
+            currency: rate.currency(),
+            exchange_rate: rate.rate(),
+        }
+    }
+
+    pub fn to_base(&self, amount: f64) -> f64 {
+        amount / self.exchange_rate
+    }
+}
+
+/// Orders placed before currencies were in the base currency.
+impl Default for OrderCurrency {
+    fn default() -> Self {
+        Self {
+            currency: Currency::BASE,
+            exchange_rate: 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_exchange_rates_from_csv() {
+        let rates = ExchangeRate::parse_csv(
+            "currency,effective_from,rate\nUSD,2025-11-01,1.16\n\ngbp, 2025-11-01, 0.88\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![(Currency::Usd, 1.16), (Currency::Gbp, 0.88)],
+            rates
+                .iter()
+                .map(|rate| (rate.currency(), rate.rate()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2025, 11, 1).unwrap(),
+            rates[1].effective_from()
+        );
+        assert!(matches!(
+            ExchangeRate::parse_csv("USD,2025-11-01,1.16\nUSD,2025-11-02,0"),
+            Err(CurrencyError::InvalidLineError(2, _))
+        ));
+        assert!(matches!(
+            ExchangeRate::parse_csv("EUR,2025-11-01,1"),
+            Err(CurrencyError::InvalidLineError(1, _))
+        ));
+    }
+
+    #[test]
+    fn lists_prices_only_in_currencies_other_than_the_base_one() {
+        let product_id = ProductId(uuid::Uuid::new_v4());
+
+        assert!(ProductPrice::new(product_id.clone(), Currency::Usd, 12.0).is_ok());
+        assert!(matches!(
+            ProductPrice::new(product_id.clone(), Currency::Usd, -1.0),
+            Err(CurrencyError::InvalidPriceError(_))
+        ));
+        assert!(matches!(
+            ProductPrice::new(product_id, Currency::BASE, 10.0),
+            Err(CurrencyError::InvalidPriceError(_))
+        ));
+    }
+
+    #[test]
+    fn converts_order_amounts_to_the_base_currency() {
+        let rate = ExchangeRate::new(
+            Currency::Usd,
+            NaiveDate::from_ymd_opt(2025, 11, 1).unwrap(),
+            1.25,
+        )
+        .unwrap();
+
+        assert_eq!(80.0, OrderCurrency::new(&rate).to_base(100.0));
+        assert_eq!(100.0, OrderCurrency::default().to_base(100.0));
+    }
+}
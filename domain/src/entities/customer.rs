@@ -1,23 +1,132 @@
 use std::fmt;
 
-use crate::value_objects::{Address, CustomerId};
+use chrono::{DateTime, Utc};
+
+use crate::{
+    entities::address_book::{AddressBook, AddressBookError},
+    value_objects::{
+        Address, AddressId, Currency, CustomerId, Email, PersonName, PhoneNumber, ERASED,
+    },
+};
 
 pub struct Customer {
     pub id: CustomerId,
-    pub first_name: String,
-    pub last_name: String,
+    pub first_name: PersonName,
+    pub last_name: PersonName,
+    /// Unique among customers, regardless of case.
+    pub email: Email,
+    pub phone: Option<PhoneNumber>,
+    /// Registered address, used for orders when the address book is empty.
     pub address: Address,
+    pub address_book: AddressBook,
+    /// Currency of the customer's orders, unless an order asks for another.
+    pub currency: Currency,
+    /// Set when the customer is deactivated; the customer is kept for the
+    /// orders that refer to it.
+    pub deactivated_at: Option<DateTime<Utc>>,
+    /// Version the customer was read at, checked when it is updated. 0 until saved.
+    pub version: i64,
 }
 
 impl Customer {
-    pub fn new(id: CustomerId, first_name: String, last_name: String, address: Address) -> Self {
+    pub fn new(
+        id: CustomerId,
+        first_name: PersonName,
+        last_name: PersonName,
+        email: Email,
+        phone: Option<PhoneNumber>,
+        address: Address,
+    ) -> Self {
         Self {
             id,
             first_name,
             last_name,
+            email,
+            phone,
             address,
+            address_book: AddressBook::new(),
+            currency: Currency::default(),
+            deactivated_at: None,
+            version: 0,
         }
     }
+
+    pub fn update_profile(
+        &mut self,
+        first_name: PersonName,
+        last_name: PersonName,
+        email: Email,
+        phone: Option<PhoneNumber>,
+    ) {
+        self.first_name = first_name;
+        self.last_name = last_name;
+        self.email = email;
+        self.phone = phone;
+    }
+
+    pub fn change_address(&mut self, address: Address) {
+        self.address = address;
+    }
+
+    pub fn add_address(&mut self, id: AddressId, address: Address) -> Result<(), AddressBookError> {
+        self.address_book.add(id, address)
+    }
+
+    pub fn remove_address(&mut self, id: &AddressId) -> Result<Address, AddressBookError> {
+        self.address_book.remove(id)
+    }
+
+    pub fn set_default_shipping_address(&mut self, id: &AddressId) -> Result<(), AddressBookError> {
+        self.address_book.set_default_shipping(id)
+    }
+
+    pub fn set_default_billing_address(&mut self, id: &AddressId) -> Result<(), AddressBookError> {
+        self.address_book.set_default_billing(id)
+    }
+
+    /// Address to ship an order to: the chosen one, else the default shipping
+    /// address, else the registered one.
+    pub fn shipping_address(&self, id: Option<&AddressId>) -> Result<&Address, AddressBookError> {
+        self.address_or_default(id, self.address_book.default_shipping())
+    }
+
+    /// Address to bill an order to: the chosen one, else the default billing
+    /// address, else the registered one.
+    pub fn billing_address(&self, id: Option<&AddressId>) -> Result<&Address, AddressBookError> {
+        self.address_or_default(id, self.address_book.default_billing())
+    }
+
+    fn address_or_default(
+        &self,
+        id: Option<&AddressId>,
+        default: Option<&AddressId>,
+    ) -> Result<&Address, AddressBookError> {
+        match id.or(default) {
+            Some(id) => self.address_book.get(id),
+            None => Ok(&self.address),
+        }
+    }
+
+    pub fn deactivate(&mut self, at: DateTime<Utc>) {
+        self.deactivated_at.get_or_insert(at);
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.deactivated_at.is_none()
+    }
+
+    /// Replaces the personal data and deactivates the customer. The registered
+    /// address keeps its postal code and country, which alone don't identify anyone.
+    pub fn erase(&mut self, at: DateTime<Utc>) {
+        self.first_name = PersonName::erased();
+        self.last_name = PersonName::erased();
+        self.email = Email::erased(&self.id);
+        self.phone = None;
+        self.address.street = ERASED.to_string();
+        self.address.city = ERASED.to_string();
+        self.address_book = AddressBook::new();
+        self.deactivate(at);
+    }
 }
 
 impl fmt::Display for Customer {
@@ -33,9 +142,15 @@ impl fmt::Display for Customer {
 #[cfg(test)]
 mod test {
 
-    use crate::value_objects::{Address, CustomerId};
+    use crate::{
+        entities::address_book::{AddressBook, AddressBookError},
+        value_objects::{
+            Address, AddressId, CountryCode, Currency, CustomerId, Email, PersonName, PostalCode,
+        },
+    };
 
     use super::Customer;
+    use chrono::{Duration, Utc};
     use uuid::Uuid;
 
     #[test]
@@ -45,21 +160,103 @@ mod test {
         let customer = customer_fixture(id);
 
         assert_eq!(CustomerId(id), customer.id);
-        assert_eq!("John".to_string(), customer.first_name);
-        assert_eq!("Appleseed".to_string(), customer.last_name);
+        assert_eq!("John", customer.first_name.as_str());
+        assert_eq!("Appleseed", customer.last_name.as_str());
+        assert!(customer.is_active());
+    }
+
+    #[test]
+    fn deactivate_a_customer_once() {
+        let mut customer = customer_fixture(Uuid::new_v4());
+        let first = Utc::now();
+
+        customer.deactivate(first);
+        customer.deactivate(first + Duration::days(1));
+
+        assert!(!customer.is_active());
+        assert_eq!(Some(first), customer.deactivated_at);
+    }
+
+    #[test]
+    fn erase_the_personal_data_of_a_customer() {
+        let id = Uuid::new_v4();
+        let mut customer = customer_fixture(id);
+        customer
+            .add_address(AddressId(Uuid::new_v4()), address("04401"))
+            .unwrap();
+
+        customer.erase(Utc::now());
+
+        assert_eq!("erased", customer.first_name.as_str());
+        assert_eq!(format!("{}@erased.invalid", id), customer.email.as_str());
+        assert_eq!("erased", customer.address.street);
+        assert_eq!("12345", customer.address.zip_code.as_str());
+        assert!(customer.address_book.is_empty());
+        assert!(!customer.is_active());
+    }
+
+    #[test]
+    fn choose_the_addresses_of_an_order() {
+        let mut customer = customer_fixture(Uuid::new_v4());
+        assert_eq!(&customer.address, customer.shipping_address(None).unwrap());
+
+        let home = AddressId(Uuid::new_v4());
+        let office = AddressId(Uuid::new_v4());
+        customer
+            .add_address(home.clone(), address("04401"))
+            .unwrap();
+        customer
+            .add_address(office.clone(), address("62701"))
+            .unwrap();
+        customer.set_default_billing_address(&office).unwrap();
+
+        assert_eq!(
+            "04401",
+            customer.shipping_address(None).unwrap().zip_code.as_str()
+        );
+        assert_eq!(
+            "62701",
+            customer.billing_address(None).unwrap().zip_code.as_str()
+        );
+        assert_eq!(
+            "62701",
+            customer
+                .shipping_address(Some(&office))
+                .unwrap()
+                .zip_code
+                .as_str()
+        );
+        let missing = AddressId(Uuid::new_v4());
+        assert_eq!(
+            Err(AddressBookError::AddressNotFoundError(missing.clone())),
+            customer.billing_address(Some(&missing))
+        );
+    }
+
+    fn address(zip_code: &str) -> Address {
+        Address {
+            zip_code: PostalCode::parse(zip_code, &CountryCode::parse("US").unwrap()).unwrap(),
+            ..customer_fixture(Uuid::new_v4()).address
+        }
     }
 
     fn customer_fixture(id: Uuid) -> Customer {
         Customer {
             id: CustomerId(id),
-            first_name: "John".to_string(),
-            last_name: "Appleseed".to_string(),
+            first_name: PersonName::parse("John").unwrap(),
+            last_name: PersonName::parse("Appleseed").unwrap(),
+            email: Email::parse("mario.rossi@example.com").unwrap(),
+            phone: None,
             address: Address {
                 street: "22, Acacia Avenue".to_string(),
                 city: "Minneapolis".to_string(),
-                zip_code: "12345".to_string(),
-                state: "Usa".to_string(),
+                zip_code: PostalCode::parse("12345", &CountryCode::parse("US").unwrap()).unwrap(),
+                country: CountryCode::parse("US").unwrap(),
             },
+            address_book: AddressBook::new(),
+            currency: Currency::Eur,
+            deactivated_at: None,
+            version: 0,
         }
     }
 }
@@ -0,0 +1,349 @@
+use crate::value_objects::{OrderId, ProductId, ReservationId, WarehouseId};
+
+#[derive(Debug, PartialEq)]
+pub enum InventoryError {
+    InvalidQuantityError(i32),
+    InsufficientStockError,
+    ReservedStockExceededError,
+    ReservationClosedError(StockReservationState),
+}
+
+impl std::fmt::Display for InventoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InventoryError::InvalidQuantityError(quantity) => {
+                write!(f, "Invalid quantity: {}", quantity)
+            }
+            InventoryError::InsufficientStockError => write!(f, "Insufficient stock"),
+            InventoryError::ReservedStockExceededError => {
+                write!(f, "Quantity exceeds the reserved stock")
+            }
+            InventoryError::ReservationClosedError(state) => {
+                write!(f, "Reservation is already {}", state)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InventoryError {}
+
+/// Stock of a product in a warehouse. Reserved units are set aside for
+/// orders not yet confirmed: only the rest is available.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Inventory {
+    product_id: ProductId,
+    warehouse_id: WarehouseId,
+    on_hand: i32,
+    reserved: i32,
+    version: i64,
+}
+
+impl Inventory {
+    /// A product not stocked in the warehouse yet.
+    pub fn new(product_id: ProductId, warehouse_id: WarehouseId) -> Self {
+        Self {
+            product_id,
+            warehouse_id,
+            on_hand: 0,
+            reserved: 0,
+            version: 0,
+        }
+    }
+
+    /// Rebuilds an inventory read from storage.
+    pub fn restore(
+        product_id: ProductId,
+        warehouse_id: WarehouseId,
+        on_hand: i32,
+        reserved: i32,
+        version: i64,
+    ) -> Self {
+        Self {
+            product_id,
+            warehouse_id,
+            on_hand,
+            reserved,
+            version,
+        }
+    }
+
+    pub fn product_id(&self) -> &ProductId {
+        &self.product_id
+    }
+
+    pub fn warehouse_id(&self) -> &WarehouseId {
+        &self.warehouse_id
+    }
+
+    /// Units in the warehouse, reserved ones included.
+    pub fn on_hand(&self) -> i32 {
+        self.on_hand
+    }
+
+    pub fn reserved(&self) -> i32 {
+        self.reserved
+    }
+
+    pub fn available(&self) -> i32 {
+        self.on_hand - self.reserved
+    }
+
+    pub fn is_depleted(&self) -> bool {
+        self.available() == 0
+    }
+
+    /// Version the inventory was read at, checked when it is updated. 0 until saved.
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    /// Returns the inventory at the version it has been stored with.
+    pub fn with_version(self, version: i64) -> Self {
+        Self { version, ..self }
+    }
+
+    /// Adds received units, or removes lost or damaged ones when `quantity`
+    /// is negative. Reserved units can't be removed.
+    pub fn adjust(&mut self, quantity: i32) -> Result<(), InventoryError> {
+        if quantity == 0 {
+            return Err(InventoryError::InvalidQuantityError(quantity));
+        }
+        let on_hand = self
+            .on_hand
+            .checked_add(quantity)
+            .ok_or(InventoryError::InvalidQuantityError(quantity))?;
+        if on_hand < self.reserved {
+            return Err(InventoryError::InsufficientStockError);
+        }
+        self.on_hand = on_hand;
+        Ok(())
+    }
+
+    pub fn reserve(&mut self, quantity: i32) -> Result<(), InventoryError> {
+        check_positive(quantity)?;
+        if quantity > self.available() {
+            return Err(InventoryError::InsufficientStockError);
+        }
+        self.reserved += quantity;
+        Ok(())
+    }
+
+    pub fn release(&mut self, quantity: i32) -> Result<(), InventoryError> {
+        check_positive(quantity)?;
+        if quantity > self.reserved {
+            return Err(InventoryError::ReservedStockExceededError);
+        }
+        self.reserved -= quantity;
+        Ok(())
+    }
+
+    /// Ships reserved units: they leave the warehouse.
+    pub fn commit(&mut self, quantity: i32) -> Result<(), InventoryError> {
+        check_positive(quantity)?;
+        if quantity > self.reserved {
+            return Err(InventoryError::ReservedStockExceededError);
+        }
+        self.reserved -= quantity;
+        self.on_hand -= quantity;
+        Ok(())
+    }
+}
+
+fn check_positive(quantity: i32) -> Result<(), InventoryError> {
+    if quantity <= 0 {
+        return Err(InventoryError::InvalidQuantityError(quantity));
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StockReservationState {
+    Reserved,
+    Committed,
+    Released,
+}
+
+const RESERVED: &str = "reserved";
+const COMMITTED: &str = "committed";
+const RELEASED: &str = "released";
+
+impl std::fmt::Display for StockReservationState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StockReservationState::Reserved => write!(f, "{}", RESERVED),
+            StockReservationState::Committed => write!(f, "{}", COMMITTED),
+            StockReservationState::Released => write!(f, "{}", RELEASED),
+        }
+    }
+}
+
+impl std::str::FromStr for StockReservationState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            RESERVED => Ok(StockReservationState::Reserved),
+            COMMITTED => Ok(StockReservationState::Committed),
+            RELEASED => Ok(StockReservationState::Released),
+            _ => Err(format!("Unknown stock reservation state: {}", s)),
+        }
+    }
+}
+
+/// Units of a product set aside in a warehouse.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReservedStock {
+    pub product_id: ProductId,
+    pub warehouse_id: WarehouseId,
+    pub quantity: i32,
+}
+
+/// Stock set aside for an order, possibly taken from several warehouses.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StockReservation {
+    id: ReservationId,
+    order_id: OrderId,
+    lines: Vec<ReservedStock>,
+    state: StockReservationState,
+}
+
+impl StockReservation {
+    pub fn new(id: ReservationId, order_id: OrderId, lines: Vec<ReservedStock>) -> Self {
+        Self {
+            id,
+            order_id,
+            lines,
+            state: StockReservationState::Reserved,
+        }
+    }
+
+    /// Rebuilds a reservation read from storage.
+    pub fn restore(
+        id: ReservationId,
+        order_id: OrderId,
+        lines: Vec<ReservedStock>,
+        state: StockReservationState,
+    ) -> Self {
+        Self {
+            id,
+            order_id,
+            lines,
+            state,
+        }
+    }
+
+    pub fn id(&self) -> &ReservationId {
+        &self.id
+    }
+
+    pub fn order_id(&self) -> &OrderId {
+        &self.order_id
+    }
+
+    pub fn lines(&self) -> &[ReservedStock] {
+        &self.lines
+    }
+
+    pub fn state(&self) -> StockReservationState {
+        self.state
+    }
+
+    pub fn commit(&mut self) -> Result<(), InventoryError> {
+        self.close(StockReservationState::Committed)
+    }
+
+    pub fn release(&mut self) -> Result<(), InventoryError> {
+        self.close(StockReservationState::Released)
+    }
+
+    fn close(&mut self, state: StockReservationState) -> Result<(), InventoryError> {
+        if self.state != StockReservationState::Reserved {
+            return Err(InventoryError::ReservationClosedError(self.state));
+        }
+        self.state = state;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn reserves_only_available_units() {
+        let mut inventory = inventory(5);
+
+        inventory.reserve(3).unwrap();
+
+        assert_eq!(2, inventory.available());
+        assert_eq!(
+            Err(InventoryError::InsufficientStockError),
+            inventory.reserve(3)
+        );
+        inventory.reserve(2).unwrap();
+        assert!(inventory.is_depleted());
+    }
+
+    #[test]
+    fn commit_and_release_take_units_out_of_the_reserved_ones() {
+        let mut inventory = inventory(5);
+        inventory.reserve(4).unwrap();
+
+        inventory.commit(3).unwrap();
+        inventory.release(1).unwrap();
+
+        assert_eq!(2, inventory.on_hand());
+        assert_eq!(0, inventory.reserved());
+        assert_eq!(
+            Err(InventoryError::ReservedStockExceededError),
+            inventory.release(1)
+        );
+    }
+
+    #[test]
+    fn cannot_remove_reserved_units() {
+        let mut inventory = inventory(5);
+        inventory.reserve(4).unwrap();
+
+        assert_eq!(
+            Err(InventoryError::InsufficientStockError),
+            inventory.adjust(-2)
+        );
+        inventory.adjust(-1).unwrap();
+        assert_eq!(4, inventory.on_hand());
+        assert_eq!(
+            Err(InventoryError::InvalidQuantityError(0)),
+            inventory.adjust(0)
+        );
+    }
+
+    #[test]
+    fn a_reservation_is_committed_or_released_once() {
+        let mut reservation = StockReservation::new(
+            ReservationId(Uuid::new_v4()),
+            OrderId(Uuid::new_v4()),
+            vec![],
+        );
+
+        reservation.commit().unwrap();
+
+        assert_eq!(
+            Err(InventoryError::ReservationClosedError(
+                StockReservationState::Committed
+            )),
+            reservation.release()
+        );
+    }
+
+    fn inventory(on_hand: i32) -> Inventory {
+        Inventory::restore(
+            ProductId(Uuid::new_v4()),
+            WarehouseId(Uuid::new_v4()),
+            on_hand,
+            0,
+            1,
+        )
+    }
+}
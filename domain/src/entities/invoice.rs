@@ -0,0 +1,436 @@
+use chrono::{DateTime, Datelike, Utc};
+
+use crate::{
+    entities::order::Order,
+    value_objects::{CustomerId, InvoiceId, OrderId, ProductId},
+};
+
+#[derive(Debug, PartialEq)]
+pub enum InvoiceError {
+    NotAnInvoiceError,
+    InvoiceNotIssuedError,
+    InvalidCreditAmountError(f64),
+}
+
+impl std::fmt::Display for InvoiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvoiceError::NotAnInvoiceError => write!(f, "Only invoices can be credited"),
+            InvoiceError::InvoiceNotIssuedError => write!(f, "Invoice has not been issued"),
+            InvoiceError::InvalidCreditAmountError(amount) => {
+                write!(f, "Cannot credit {} on the invoice", amount)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvoiceError {}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InvoiceKind {
+    Invoice,
+    /// Cancels part of an invoice, for a refund.
+    CreditNote,
+}
+
+const INVOICE: &str = "invoice";
+const CREDIT_NOTE: &str = "credit_note";
+
+impl InvoiceKind {
+    fn prefix(&self) -> &'static str {
+        match self {
+            InvoiceKind::Invoice => "INV",
+            InvoiceKind::CreditNote => "CN",
+        }
+    }
+}
+
+impl std::fmt::Display for InvoiceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvoiceKind::Invoice => write!(f, "{}", INVOICE),
+            InvoiceKind::CreditNote => write!(f, "{}", CREDIT_NOTE),
+        }
+    }
+}
+
+impl std::str::FromStr for InvoiceKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            INVOICE => Ok(InvoiceKind::Invoice),
+            CREDIT_NOTE => Ok(InvoiceKind::CreditNote),
+            _ => Err(format!("Unknown invoice kind {}", s)),
+        }
+    }
+}
+
+/// Invoices and credit notes are numbered in two separate series, starting
+/// again from 1 each fiscal year, e.g. `INV-2025-000042`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InvoiceNumber {
+    pub kind: InvoiceKind,
+    pub fiscal_year: i32,
+    pub sequence: i64,
+}
+
+impl std::fmt::Display for InvoiceNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}-{}-{:06}",
+            self.kind.prefix(),
+            self.fiscal_year,
+            self.sequence
+        )
+    }
+}
+
+impl std::str::FromStr for InvoiceNumber {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid invoice number {}", s);
+        let mut parts = s.splitn(3, '-');
+        let kind = match parts.next() {
+            Some("INV") => InvoiceKind::Invoice,
+            Some("CN") => InvoiceKind::CreditNote,
+            _ => return Err(invalid()),
+        };
+        let fiscal_year = parts
+            .next()
+            .and_then(|year| year.parse().ok())
+            .ok_or_else(invalid)?;
+        let sequence = parts
+            .next()
+            .and_then(|sequence| sequence.parse().ok())
+            .ok_or_else(invalid)?;
+        Ok(Self {
+            kind,
+            fiscal_year,
+            sequence,
+        })
+    }
+}
+
+/// A line of an invoice. Credit notes have a single line, without product.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InvoiceLine {
+    pub product_id: Option<ProductId>,
+    pub quantity: i32,
+    pub unit_price: f64,
+    /// Before discounts.
+    pub amount: f64,
+    /// Percentage, if the line is taxed.
+    pub tax_rate: Option<f64>,
+    /// After discounts.
+    pub tax_amount: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct InvoiceTotals {
+    pub subtotal: f64,
+    pub discount_total: f64,
+    pub tax_total: f64,
+    pub total: f64,
+}
+
+/// Invoice of a paid order, or credit note of a refund, with the amounts of
+/// the order when it was issued. Numbered when issued.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Invoice {
+    id: InvoiceId,
+    order_id: OrderId,
+    customer_id: CustomerId,
+    kind: InvoiceKind,
+    number: Option<InvoiceNumber>,
+    billing_address: Option<String>,
+    credited_invoice: Option<InvoiceNumber>,
+    refund_key: Option<String>,
+    lines: Vec<InvoiceLine>,
+    totals: InvoiceTotals,
+    issued_at: DateTime<Utc>,
+}
+
+impl Invoice {
+    pub fn for_order(id: InvoiceId, order: &Order, issued_at: DateTime<Utc>) -> Self {
+        let pricing = order.pricing();
+        let lines = order
+            .order_items()
+            .iter()
+            .map(|item| {
+                let tax_line = pricing
+                    .tax_lines
+                    .iter()
+                    .find(|line| line.product_id == item.product_id);
+                InvoiceLine {
+                    product_id: Some(item.product_id.clone()),
+                    quantity: item.quantity,
+                    unit_price: item.price,
+                    amount: cents(item.price * item.quantity as f64),
+                    tax_rate: tax_line.map(|line| line.rate),
+                    tax_amount: tax_line.map_or(0.0, |line| cents(line.amount)),
+                }
+            })
+            .collect();
+        Self {
+            id,
+            order_id: order.id().clone(),
+            customer_id: order.customer_id().clone(),
+            kind: InvoiceKind::Invoice,
+            number: None,
+            billing_address: order
+                .addresses()
+                .map(|addresses| addresses.billing.to_string()),
+            credited_invoice: None,
+            refund_key: None,
+            lines,
+            totals: InvoiceTotals {
+                subtotal: cents(pricing.subtotal),
+                discount_total: cents(pricing.discounts.iter().map(|d| d.amount).sum()),
+                tax_total: cents(pricing.tax_total),
+                total: cents(pricing.total),
+            },
+            issued_at,
+        }
+    }
+
+    /// Credits `amount` of an issued invoice, for the refund identified by
+    /// `refund_key`. The tax credited is the invoice's share of it.
+    pub fn credit_note(
+        id: InvoiceId,
+        invoice: &Invoice,
+        refund_key: &str,
+        amount: f64,
+        issued_at: DateTime<Utc>,
+    ) -> Result<Self, InvoiceError> {
+        if invoice.kind != InvoiceKind::Invoice {
+            return Err(InvoiceError::NotAnInvoiceError);
+        }
+        let credited_invoice = invoice
+            .number
+            .clone()
+            .ok_or(InvoiceError::InvoiceNotIssuedError)?;
+        if amount <= 0.0 || amount > invoice.totals.total {
+            return Err(InvoiceError::InvalidCreditAmountError(amount));
+        }
+        let tax_amount = cents(invoice.totals.tax_total * amount / invoice.totals.total);
+        Ok(Self {
+            id,
+            order_id: invoice.order_id.clone(),
+            customer_id: invoice.customer_id.clone(),
+            kind: InvoiceKind::CreditNote,
+            number: None,
+            billing_address: invoice.billing_address.clone(),
+            credited_invoice: Some(credited_invoice),
+            refund_key: Some(refund_key.to_string()),
+            lines: vec![InvoiceLine {
+                product_id: None,
+                quantity: 1,
+                unit_price: amount,
+                amount,
+                tax_rate: None,
+                tax_amount,
+            }],
+            totals: InvoiceTotals {
+                subtotal: amount,
+                discount_total: 0.0,
+                tax_total: tax_amount,
+                total: amount,
+            },
+            issued_at,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn restore(
+        id: InvoiceId,
+        order_id: OrderId,
+        customer_id: CustomerId,
+        number: InvoiceNumber,
+        billing_address: Option<String>,
+        credited_invoice: Option<InvoiceNumber>,
+        refund_key: Option<String>,
+        lines: Vec<InvoiceLine>,
+        totals: InvoiceTotals,
+        issued_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id,
+            order_id,
+            customer_id,
+            kind: number.kind,
+            number: Some(number),
+            billing_address,
+            credited_invoice,
+            refund_key,
+            lines,
+            totals,
+            issued_at,
+        }
+    }
+
+    pub fn id(&self) -> &InvoiceId {
+        &self.id
+    }
+
+    pub fn order_id(&self) -> &OrderId {
+        &self.order_id
+    }
+
+    pub fn customer_id(&self) -> &CustomerId {
+        &self.customer_id
+    }
+
+    pub fn kind(&self) -> InvoiceKind {
+        self.kind
+    }
+
+    pub fn number(&self) -> Option<&InvoiceNumber> {
+        self.number.as_ref()
+    }
+
+    pub fn billing_address(&self) -> Option<&str> {
+        self.billing_address.as_deref()
+    }
+
+    /// The invoice a credit note cancels part of.
+    pub fn credited_invoice(&self) -> Option<&InvoiceNumber> {
+        self.credited_invoice.as_ref()
+    }
+
+    pub fn refund_key(&self) -> Option<&str> {
+        self.refund_key.as_deref()
+    }
+
+    pub fn lines(&self) -> &[InvoiceLine] {
+        &self.lines
+    }
+
+    pub fn totals(&self) -> &InvoiceTotals {
+        &self.totals
+    }
+
+    pub fn issued_at(&self) -> DateTime<Utc> {
+        self.issued_at
+    }
+
+    /// Calendar year of the issue date.
+    pub fn fiscal_year(&self) -> i32 {
+        self.issued_at.year()
+    }
+
+    /// Gives the invoice the number `sequence` of its kind in its fiscal year.
+    pub fn with_sequence(self, sequence: i64) -> Self {
+        let number = InvoiceNumber {
+            kind: self.kind,
+            fiscal_year: self.fiscal_year(),
+            sequence,
+        };
+        Self {
+            number: Some(number),
+            ..self
+        }
+    }
+}
+
+fn cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        entities::tax::{PricingMode, TaxCategory, TaxRate, Taxes},
+        value_objects::OrderItem,
+    };
+
+    #[test]
+    fn invoices_the_items_and_taxes_of_the_order() {
+        let product_id = ProductId(Uuid::new_v4());
+        let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()))
+            .with_taxes(Taxes::new(PricingMode::Exclusive));
+        order
+            .add(OrderItem {
+                price: 12.5,
+                quantity: 2,
+                product_id: product_id.clone(),
+            })
+            .unwrap();
+        order.set_tax_rates(vec![TaxRate {
+            product_id: product_id.clone(),
+            category: TaxCategory::Standard,
+            rate: 20.0,
+        }]);
+        let issued_at = Utc.with_ymd_and_hms(2025, 12, 31, 23, 0, 0).unwrap();
+
+        let invoice =
+            Invoice::for_order(InvoiceId(Uuid::new_v4()), &order, issued_at).with_sequence(7);
+
+        assert_eq!("INV-2025-000007", invoice.number().unwrap().to_string());
+        assert_eq!(Some(20.0), invoice.lines()[0].tax_rate);
+        assert_eq!(5.0, invoice.lines()[0].tax_amount);
+        assert_eq!(25.0, invoice.totals().subtotal);
+        assert_eq!(30.0, invoice.totals().total);
+        assert_eq!(
+            Ok(invoice.number().unwrap().clone()),
+            "INV-2025-000007".parse()
+        );
+    }
+
+    #[test]
+    fn credits_part_of_an_issued_invoice() {
+        let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()));
+        order
+            .add(OrderItem {
+                price: 40.0,
+                quantity: 1,
+                product_id: ProductId(Uuid::new_v4()),
+            })
+            .unwrap();
+        let issued_at = Utc.with_ymd_and_hms(2026, 1, 1, 9, 0, 0).unwrap();
+        let invoice = Invoice::for_order(InvoiceId(Uuid::new_v4()), &order, issued_at);
+
+        assert_eq!(
+            Err(InvoiceError::InvoiceNotIssuedError),
+            Invoice::credit_note(
+                InvoiceId(Uuid::new_v4()),
+                &invoice,
+                "refund",
+                10.0,
+                issued_at
+            )
+        );
+        let invoice = invoice.with_sequence(1);
+        assert_eq!(
+            Err(InvoiceError::InvalidCreditAmountError(50.0)),
+            Invoice::credit_note(
+                InvoiceId(Uuid::new_v4()),
+                &invoice,
+                "refund",
+                50.0,
+                issued_at
+            )
+        );
+        let credit_note = Invoice::credit_note(
+            InvoiceId(Uuid::new_v4()),
+            &invoice,
+            "refund",
+            10.0,
+            issued_at,
+        )
+        .unwrap()
+        .with_sequence(1);
+        assert_eq!("CN-2026-000001", credit_note.number().unwrap().to_string());
+        assert_eq!(
+            Some(invoice.number().unwrap()),
+            credit_note.credited_invoice()
+        );
+        assert_eq!(10.0, credit_note.totals().total);
+    }
+}
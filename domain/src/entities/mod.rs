@@ -1,4 +1,19 @@
+pub mod address_book;
+pub mod cart;
+pub mod category;
+pub mod checkout_saga;
+pub mod currency;
 pub mod customer;
+pub mod inventory;
+pub mod invoice;
 pub mod order;
+pub mod order_lines;
 pub mod outbox;
+pub mod payment;
 pub mod product;
+pub mod promotion;
+pub mod returns;
+pub mod review;
+pub mod shipment;
+pub mod shipping_method;
+pub mod tax;
@@ -1,9 +1,129 @@
-use crate::value_objects::{CustomerId, OrderId, OrderItem};
+use crate::{
+    entities::{
+        currency::OrderCurrency,
+        order_lines::{OrderLines, OrderLinesError},
+        promotion::{AppliedPromotion, Pricing, PromotionError},
+        shipping_method::OrderShipping,
+        tax::{TaxLine, TaxRate, Taxes},
+    },
+    value_objects::{Address, CustomerId, OrderId, OrderItem, ProductId},
+};
+
+/// Addresses of the customer when the order was placed, kept as they were
+/// even if the customer changes them later.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderAddresses {
+    pub shipping: Address,
+    pub billing: Address,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OrderStatus {
+    /// Not placed yet: items and promotions can still change.
+    Draft,
+    /// Placed, waiting for stock and payment. Its items and prices are locked.
+    Pending,
+    Confirmed,
+    Cancelled,
+}
+
+const DRAFT: &str = "draft";
+const PENDING: &str = "pending";
+const CONFIRMED: &str = "confirmed";
+const CANCELLED: &str = "cancelled";
+
+impl std::fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderStatus::Draft => write!(f, "{}", DRAFT),
+            OrderStatus::Pending => write!(f, "{}", PENDING),
+            OrderStatus::Confirmed => write!(f, "{}", CONFIRMED),
+            OrderStatus::Cancelled => write!(f, "{}", CANCELLED),
+        }
+    }
+}
+
+impl std::str::FromStr for OrderStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            DRAFT => Ok(OrderStatus::Draft),
+            PENDING => Ok(OrderStatus::Pending),
+            CONFIRMED => Ok(OrderStatus::Confirmed),
+            CANCELLED => Ok(OrderStatus::Cancelled),
+            _ => Err(format!("Unknown order status: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum OrderStatusError {
+    OrderNotDraftError(OrderStatus),
+    OrderNotPendingError(OrderStatus),
+}
+
+impl std::fmt::Display for OrderStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderStatusError::OrderNotDraftError(status) => {
+                write!(f, "Order is {}, not a draft", status)
+            }
+            OrderStatusError::OrderNotPendingError(status) => {
+                write!(f, "Order is {}, not pending", status)
+            }
+        }
+    }
+}
+
+impl std::error::Error for OrderStatusError {}
+
+/// Facts about an order, used to rebuild it when orders are stored as events.
+// `OrderCreated` is larger than the others, but there is one per order.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum OrderEvent {
+    OrderCreated {
+        order_id: OrderId,
+        customer_id: CustomerId,
+        addresses: Option<OrderAddresses>,
+        currency: OrderCurrency,
+        shipping: Option<OrderShipping>,
+    },
+    ProductAddedToOrder {
+        product_id: ProductId,
+        price: f64,
+        quantity: i32,
+    },
+    ProductRemovedFromOrder {
+        product_id: ProductId,
+    },
+    ProductQuantityChanged {
+        product_id: ProductId,
+        quantity: i32,
+    },
+    OrderStatusChanged {
+        status: OrderStatus,
+    },
+    PromotionApplied {
+        promotion: AppliedPromotion,
+    },
+    TaxesChanged {
+        taxes: Taxes,
+    },
+}
 
 pub struct Order {
-    pub id: OrderId,
-    pub customer_id: CustomerId,
-    pub order_items: Vec<OrderItem>,
+    id: OrderId,
+    customer_id: CustomerId,
+    order_lines: OrderLines,
+    addresses: Option<OrderAddresses>,
+    status: OrderStatus,
+    promotions: Vec<AppliedPromotion>,
+    taxes: Taxes,
+    currency: OrderCurrency,
+    shipping: Option<OrderShipping>,
+    version: i64,
 }
 
 impl Order {
@@ -11,27 +131,357 @@ impl Order {
         Self {
             id,
             customer_id,
-            order_items: vec![],
+            order_lines: OrderLines::new(),
+            addresses: None,
+            status: OrderStatus::Draft,
+            promotions: vec![],
+            taxes: Taxes::default(),
+            currency: OrderCurrency::default(),
+            shipping: None,
+            version: 0,
         }
     }
 
-    pub fn add(&mut self, order_item: OrderItem) {
-        self.order_items.push(order_item);
+    /// Rebuilds an order read from storage.
+    pub fn restore(
+        id: OrderId,
+        customer_id: CustomerId,
+        order_lines: OrderLines,
+        addresses: Option<OrderAddresses>,
+        version: i64,
+    ) -> Self {
+        Self {
+            id,
+            customer_id,
+            order_lines,
+            addresses,
+            status: OrderStatus::Draft,
+            promotions: vec![],
+            taxes: Taxes::default(),
+            currency: OrderCurrency::default(),
+            shipping: None,
+            version,
+        }
+    }
+
+    pub fn id(&self) -> &OrderId {
+        &self.id
+    }
+
+    pub fn customer_id(&self) -> &CustomerId {
+        &self.customer_id
+    }
+
+    pub fn order_lines(&self) -> &OrderLines {
+        &self.order_lines
     }
 
-    pub fn add_multiple(&mut self, order_items: Vec<OrderItem>) {
+    pub fn order_items(&self) -> &[OrderItem] {
+        self.order_lines.as_slice()
+    }
+
+    /// `None` for orders placed before addresses were recorded.
+    pub fn addresses(&self) -> Option<&OrderAddresses> {
+        self.addresses.as_ref()
+    }
+
+    pub fn with_addresses(self, addresses: OrderAddresses) -> Self {
+        Self {
+            addresses: Some(addresses),
+            ..self
+        }
+    }
+
+    pub fn status(&self) -> OrderStatus {
+        self.status
+    }
+
+    /// Returns the order with the status it has been stored with.
+    pub fn with_status(self, status: OrderStatus) -> Self {
+        Self { status, ..self }
+    }
+
+    /// Places a draft order: from then on its items and promotions can't
+    /// change, so that the stock reserved and the payment authorized for it
+    /// stay right.
+    pub fn place(&mut self) -> Result<(), OrderStatusError> {
+        if self.status != OrderStatus::Draft {
+            return Err(OrderStatusError::OrderNotDraftError(self.status));
+        }
+        self.status = OrderStatus::Pending;
+        Ok(())
+    }
+
+    pub fn confirm(&mut self) -> Result<(), OrderStatusError> {
+        self.change_status(OrderStatus::Confirmed)
+    }
+
+    pub fn cancel(&mut self) -> Result<(), OrderStatusError> {
+        self.change_status(OrderStatus::Cancelled)
+    }
+
+    pub fn promotions(&self) -> &[AppliedPromotion] {
+        &self.promotions
+    }
+
+    /// Returns the order with the promotions it has been stored with.
+    pub fn with_promotions(self, promotions: Vec<AppliedPromotion>) -> Self {
+        Self { promotions, ..self }
+    }
+
+    /// Promotions are applied while the order is a draft, each code once.
+    /// Use `Promotion::apply_to` to check the promotion's conditions first.
+    pub fn apply_promotion(&mut self, promotion: AppliedPromotion) -> Result<(), PromotionError> {
+        if self.status != OrderStatus::Draft {
+            return Err(PromotionError::OrderNotDraftError(self.status));
+        }
+        if self
+            .promotions
+            .iter()
+            .any(|applied| applied.code == promotion.code)
+        {
+            return Err(PromotionError::AlreadyAppliedError(promotion.code));
+        }
+        self.promotions.push(promotion);
+        Ok(())
+    }
+
+    pub fn taxes(&self) -> &Taxes {
+        &self.taxes
+    }
+
+    /// Returns the order with the taxes it has been stored with.
+    pub fn with_taxes(self, taxes: Taxes) -> Self {
+        Self { taxes, ..self }
+    }
+
+    /// Currency prices of the order are in, with the exchange rate recorded
+    /// when it was created.
+    pub fn currency(&self) -> &OrderCurrency {
+        &self.currency
+    }
+
+    /// Returns the order in `currency`, or with the currency it has been
+    /// stored with.
+    pub fn with_currency(self, currency: OrderCurrency) -> Self {
+        Self { currency, ..self }
+    }
+
+    /// Shipping method chosen when the order was placed, `None` for orders
+    /// placed without one.
+    pub fn shipping(&self) -> Option<&OrderShipping> {
+        self.shipping.as_ref()
+    }
+
+    /// Returns the order shipped with `shipping`, or with the shipping it
+    /// has been stored with.
+    pub fn with_shipping(self, shipping: OrderShipping) -> Self {
+        Self {
+            shipping: Some(shipping),
+            ..self
+        }
+    }
+
+    /// Replaces the tax rates, found by a `TaxCalculator` for the current items.
+    pub fn set_tax_rates(&mut self, rates: Vec<TaxRate>) {
+        self.taxes.rates = rates;
+    }
+
+    fn change_status(&mut self, status: OrderStatus) -> Result<(), OrderStatusError> {
+        if self.status != OrderStatus::Pending {
+            return Err(OrderStatusError::OrderNotPendingError(self.status));
+        }
+        self.status = status;
+        Ok(())
+    }
+
+    /// Version the order was read at, checked when it is updated. 0 until saved.
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    /// Returns the order at the version it has been stored with.
+    pub fn with_version(self, version: i64) -> Self {
+        Self { version, ..self }
+    }
+
+    /// Adds an item, or increases the quantity of the product if it is
+    /// already in the order.
+    /// Items change only while the order is a draft.
+    pub fn add(&mut self, order_item: OrderItem) -> Result<(), OrderLinesError> {
+        self.check_draft()?;
+        self.order_lines.add(order_item)
+    }
+
+    pub fn add_multiple(&mut self, order_items: Vec<OrderItem>) -> Result<(), OrderLinesError> {
         for order_item in order_items {
-            self.add(order_item)
+            self.add(order_item)?;
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, product_id: &ProductId) -> Result<OrderItem, OrderLinesError> {
+        self.check_draft()?;
+        self.order_lines.remove(product_id)
+    }
+
+    pub fn change_quantity(
+        &mut self,
+        product_id: &ProductId,
+        quantity: i32,
+    ) -> Result<(), OrderLinesError> {
+        self.check_draft()?;
+        self.order_lines.change_quantity(product_id, quantity)
+    }
+
+    fn check_draft(&self) -> Result<(), OrderLinesError> {
+        if self.status != OrderStatus::Draft {
+            return Err(OrderLinesError::OrderNotDraftError(self.status));
         }
+        Ok(())
     }
 
-    pub fn total_price(self) -> f64 {
-        let total: f64 = self
-            .order_items
+    /// Rebuilds an order from its events. Returns `None` if the first event
+    /// is not `OrderCreated`.
+    pub fn rehydrate(
+        events: impl IntoIterator<Item = OrderEvent>,
+    ) -> Result<Option<Self>, OrderLinesError> {
+        let mut events = events.into_iter();
+        let Some(OrderEvent::OrderCreated {
+            order_id,
+            customer_id,
+            addresses,
+            currency,
+            shipping,
+        }) = events.next()
+        else {
+            return Ok(None);
+        };
+        let mut order = Order::create(order_id, customer_id);
+        order.addresses = addresses;
+        order.currency = currency;
+        order.shipping = shipping;
+        for event in events {
+            order.apply(event)?;
+        }
+        Ok(Some(order))
+    }
+
+    pub fn apply(&mut self, event: OrderEvent) -> Result<(), OrderLinesError> {
+        match event {
+            OrderEvent::OrderCreated { .. } => Ok(()),
+            OrderEvent::ProductAddedToOrder {
+                product_id,
+                price,
+                quantity,
+            } => self.order_lines.add(OrderItem {
+                price,
+                quantity,
+                product_id,
+            }),
+            OrderEvent::ProductRemovedFromOrder { product_id } => {
+                self.order_lines.remove(&product_id).map(|_| ())
+            }
+            OrderEvent::ProductQuantityChanged {
+                product_id,
+                quantity,
+            } => self.order_lines.change_quantity(&product_id, quantity),
+            OrderEvent::OrderStatusChanged { status } => {
+                self.status = status;
+                Ok(())
+            }
+            OrderEvent::PromotionApplied { promotion } => {
+                self.promotions.push(promotion);
+                Ok(())
+            }
+            OrderEvent::TaxesChanged { taxes } => {
+                self.taxes = taxes;
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the event recording the current status, if it is not `previous`.
+    pub fn status_change_since(&self, previous: OrderStatus) -> Option<OrderEvent> {
+        (self.status != previous).then_some(OrderEvent::OrderStatusChanged {
+            status: self.status,
+        })
+    }
+
+    /// Returns the event recording the current taxes, if they are not `previous`.
+    pub fn taxes_change_since(&self, previous: &Taxes) -> Option<OrderEvent> {
+        (self.taxes != *previous).then(|| OrderEvent::TaxesChanged {
+            taxes: self.taxes.clone(),
+        })
+    }
+
+    /// Returns the events recording the promotions not in `previous`.
+    pub fn promotions_since(&self, previous: &[AppliedPromotion]) -> Vec<OrderEvent> {
+        self.promotions
             .iter()
-            .map(|x| x.price * x.quantity as f64)
-            .sum();
-        (total * 100.0).round() / 100.0
+            .filter(|promotion| !previous.contains(promotion))
+            .map(|promotion| OrderEvent::PromotionApplied {
+                promotion: promotion.clone(),
+            })
+            .collect()
+    }
+
+    /// Returns the events that turn `previous_items` into the current items,
+    /// so that only what changed needs to be stored.
+    pub fn changes_since(&self, previous_items: &[OrderItem]) -> Vec<OrderEvent> {
+        let mut events = vec![];
+        for previous in previous_items {
+            match self.order_lines.find(&previous.product_id) {
+                Some(current) if current.price == previous.price => {
+                    if current.quantity != previous.quantity {
+                        events.push(OrderEvent::ProductQuantityChanged {
+                            product_id: current.product_id.clone(),
+                            quantity: current.quantity,
+                        });
+                    }
+                }
+                _ => events.push(OrderEvent::ProductRemovedFromOrder {
+                    product_id: previous.product_id.clone(),
+                }),
+            }
+        }
+        for current in &self.order_lines {
+            let unchanged_price = previous_items.iter().any(|previous| {
+                previous.product_id == current.product_id && previous.price == current.price
+            });
+            if !unchanged_price {
+                events.push(OrderEvent::ProductAddedToOrder {
+                    product_id: current.product_id.clone(),
+                    price: current.price,
+                    quantity: current.quantity,
+                });
+            }
+        }
+        events
+    }
+
+    pub fn pricing(&self) -> Pricing {
+        Pricing::new(
+            &self.order_lines,
+            &self.promotions,
+            &self.taxes,
+            self.shipping.as_ref(),
+        )
+    }
+
+    pub fn tax_lines(&self) -> Vec<TaxLine> {
+        self.pricing().tax_lines
+    }
+
+    /// Price to pay: the items less the discounts of the promotions, plus
+    /// taxes when prices don't include them, plus shipping.
+    pub fn total_price(&self) -> f64 {
+        self.pricing().total
+    }
+
+    /// Price to pay converted to the base currency, at the order's rate.
+    pub fn base_total_price(&self) -> f64 {
+        self.currency.to_base(self.total_price())
     }
 }
 
@@ -39,9 +489,12 @@ impl Order {
 mod test {
     use uuid::Uuid;
 
-    use crate::value_objects::{CustomerId, OrderId, OrderItem, ProductId};
+    use crate::{
+        entities::{currency::OrderCurrency, order_lines::OrderLinesError},
+        value_objects::{Currency, CustomerId, OrderId, OrderItem, ProductId},
+    };
 
-    use super::Order;
+    use super::{Order, OrderEvent, OrderStatus, OrderStatusError};
 
     #[test]
     fn create_an_order_for_a_customer() {
@@ -50,51 +503,258 @@ mod test {
 
         let order = Order::create(OrderId(order_id), CustomerId(customer_id));
 
-        assert_eq!(OrderId(order_id), order.id);
-        assert_eq!(CustomerId(customer_id), order.customer_id);
+        assert_eq!(&OrderId(order_id), order.id());
+        assert_eq!(&CustomerId(customer_id), order.customer_id());
+        assert_eq!(0, order.version());
     }
 
     #[test]
     fn add_items_to_order() {
         let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()));
 
-        order.add_multiple(vec![
-            OrderItem {
-                price: 9.99,
-                quantity: 1,
-                product_id: ProductId(Uuid::new_v4()),
-            },
-            OrderItem {
-                price: 5.55,
-                quantity: 2,
-                product_id: ProductId(Uuid::new_v4()),
-            },
-            OrderItem {
-                price: 7.77,
-                quantity: 3,
-                product_id: ProductId(Uuid::new_v4()),
-            },
-        ]);
-        assert_eq!(3, order.order_items.len());
+        order
+            .add_multiple(vec![
+                OrderItem {
+                    price: 9.99,
+                    quantity: 1,
+                    product_id: ProductId(Uuid::new_v4()),
+                },
+                OrderItem {
+                    price: 5.55,
+                    quantity: 2,
+                    product_id: ProductId(Uuid::new_v4()),
+                },
+                OrderItem {
+                    price: 7.77,
+                    quantity: 3,
+                    product_id: ProductId(Uuid::new_v4()),
+                },
+            ])
+            .unwrap();
+        assert_eq!(3, order.order_items().len());
+    }
+
+    #[test]
+    fn merge_quantities_of_the_same_product() {
+        let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()));
+        let product_id = ProductId(Uuid::new_v4());
+
+        order.add(order_item(&product_id, 2)).unwrap();
+        order.add(order_item(&product_id, 3)).unwrap();
+
+        assert_eq!(1, order.order_items().len());
+        assert_eq!(5, order.order_items()[0].quantity);
+    }
+
+    #[test]
+    fn cannot_add_an_invalid_item() {
+        let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()));
+
+        let result = order.add(order_item(&ProductId(Uuid::new_v4()), 0));
+
+        assert_eq!(Err(OrderLinesError::InvalidQuantityError(0)), result);
+        assert!(order.order_lines().is_empty());
+    }
+
+    #[test]
+    fn remove_an_item_from_order() {
+        let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()));
+        let product_id = ProductId(Uuid::new_v4());
+        order.add(order_item(&product_id, 1)).unwrap();
+
+        let removed = order.remove(&product_id);
+
+        assert_eq!(Ok(order_item(&product_id, 1)), removed);
+        assert!(order.order_items().is_empty());
+        assert_eq!(
+            Err(OrderLinesError::ProductNotFoundError(product_id.clone())),
+            order.remove(&product_id)
+        );
+    }
+
+    #[test]
+    fn change_quantity_of_an_item() {
+        let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()));
+        let product_id = ProductId(Uuid::new_v4());
+        order.add(order_item(&product_id, 1)).unwrap();
+
+        order.change_quantity(&product_id, 4).unwrap();
+
+        assert_eq!(4, order.order_items()[0].quantity);
+        assert_eq!(
+            Err(OrderLinesError::InvalidQuantityError(0)),
+            order.change_quantity(&product_id, 0)
+        );
+    }
+
+    #[test]
+    fn list_changes_since_previous_items() {
+        let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()));
+        let kept = ProductId(Uuid::new_v4());
+        let changed = ProductId(Uuid::new_v4());
+        let removed = ProductId(Uuid::new_v4());
+        let added = ProductId(Uuid::new_v4());
+        order
+            .add_multiple(vec![
+                order_item(&kept, 1),
+                order_item(&changed, 1),
+                order_item(&removed, 1),
+            ])
+            .unwrap();
+        let previous_items = order.order_items().to_vec();
+
+        order.change_quantity(&changed, 3).unwrap();
+        order.remove(&removed).unwrap();
+        order.add(order_item(&added, 2)).unwrap();
+
+        assert_eq!(
+            vec![
+                OrderEvent::ProductQuantityChanged {
+                    product_id: changed,
+                    quantity: 3
+                },
+                OrderEvent::ProductRemovedFromOrder {
+                    product_id: removed
+                },
+                OrderEvent::ProductAddedToOrder {
+                    product_id: added,
+                    price: 9.99,
+                    quantity: 2
+                },
+            ],
+            order.changes_since(&previous_items)
+        );
     }
 
     #[test]
     fn calculate_total_price_of_an_order() {
         let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()));
 
-        order.add_multiple(vec![
-            OrderItem {
+        order
+            .add_multiple(vec![
+                OrderItem {
+                    price: 9.99,
+                    quantity: 10,
+                    product_id: ProductId(Uuid::new_v4()),
+                },
+                OrderItem {
+                    price: 5.55,
+                    quantity: 2,
+                    product_id: ProductId(Uuid::new_v4()),
+                },
+            ])
+            .unwrap();
+
+        assert_eq!(111.0, order.total_price());
+    }
+
+    #[test]
+    fn rehydrate_an_order_from_its_events() {
+        let order_id = OrderId(Uuid::new_v4());
+        let customer_id = CustomerId(Uuid::new_v4());
+
+        let order = Order::rehydrate(vec![
+            OrderEvent::OrderCreated {
+                order_id: order_id.clone(),
+                customer_id: customer_id.clone(),
+                addresses: None,
+                currency: OrderCurrency {
+                    currency: Currency::Usd,
+                    exchange_rate: 1.11,
+                },
+                shipping: None,
+            },
+            OrderEvent::ProductAddedToOrder {
+                product_id: ProductId(Uuid::new_v4()),
                 price: 9.99,
                 quantity: 10,
-                product_id: ProductId(Uuid::new_v4()),
             },
-            OrderItem {
+            OrderEvent::ProductAddedToOrder {
+                product_id: ProductId(Uuid::new_v4()),
                 price: 5.55,
                 quantity: 2,
-                product_id: ProductId(Uuid::new_v4()),
             },
-        ]);
+        ])
+        .unwrap()
+        .unwrap();
 
+        assert_eq!(order_id, order.id);
+        assert_eq!(customer_id, order.customer_id);
         assert_eq!(111.0, order.total_price());
+        assert_eq!(Currency::Usd, order.currency().currency);
+        assert_eq!(100.0, (order.base_total_price() * 100.0).round() / 100.0);
+    }
+
+    #[test]
+    fn confirm_or_cancel_only_a_pending_order() {
+        let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()));
+        assert_eq!(
+            Err(OrderStatusError::OrderNotPendingError(OrderStatus::Draft)),
+            order.confirm()
+        );
+        order.place().unwrap();
+
+        order.confirm().unwrap();
+
+        assert_eq!(OrderStatus::Confirmed, order.status());
+        assert_eq!(
+            Some(OrderEvent::OrderStatusChanged {
+                status: OrderStatus::Confirmed
+            }),
+            order.status_change_since(OrderStatus::Pending)
+        );
+        assert_eq!(
+            Err(OrderStatusError::OrderNotPendingError(
+                OrderStatus::Confirmed
+            )),
+            order.cancel()
+        );
+    }
+
+    #[test]
+    fn changes_items_only_while_a_draft() {
+        let product_id = ProductId(Uuid::new_v4());
+        let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()));
+        order.add(order_item(&product_id, 1)).unwrap();
+
+        order.place().unwrap();
+
+        assert_eq!(OrderStatus::Pending, order.status());
+        assert_eq!(
+            Err(OrderLinesError::OrderNotDraftError(OrderStatus::Pending)),
+            order.add(order_item(&product_id, 1))
+        );
+        assert_eq!(
+            Err(OrderLinesError::OrderNotDraftError(OrderStatus::Pending)),
+            order.change_quantity(&product_id, 2)
+        );
+        assert_eq!(
+            Err(OrderLinesError::OrderNotDraftError(OrderStatus::Pending)),
+            order.remove(&product_id)
+        );
+        assert_eq!(
+            Err(OrderStatusError::OrderNotDraftError(OrderStatus::Pending)),
+            order.place()
+        );
+        assert_eq!(1, order.order_items()[0].quantity);
+    }
+
+    #[test]
+    fn cannot_rehydrate_an_order_without_creation_event() {
+        let order = Order::rehydrate(vec![OrderEvent::ProductAddedToOrder {
+            product_id: ProductId(Uuid::new_v4()),
+            price: 9.99,
+            quantity: 1,
+        }]);
+
+        assert_eq!(None, order.unwrap().map(|order| order.id));
+    }
+
+    fn order_item(product_id: &ProductId, quantity: i32) -> OrderItem {
+        OrderItem {
+            price: 9.99,
+            quantity,
+            product_id: product_id.clone(),
+        }
     }
 }
@@ -0,0 +1,341 @@
+use crate::{
+    entities::order::OrderStatus,
+    value_objects::{OrderItem, ProductId},
+};
+
+pub const MAX_QUANTITY_PER_LINE: i32 = 1000;
+pub const MAX_LINES_PER_ORDER: usize = 100;
+
+#[derive(Debug, PartialEq)]
+pub enum OrderLinesError {
+    InvalidQuantityError(i32),
+    InvalidPriceError(f64),
+    LineQuantityLimitExceededError(ProductId),
+    OrderLinesLimitExceededError,
+    DuplicateProductError(ProductId),
+    /// The product is already in a line at another price.
+    PriceMismatchError(ProductId),
+    ProductNotFoundError(ProductId),
+    /// Items of an order change only while it is a draft.
+    OrderNotDraftError(OrderStatus),
+}
+
+impl std::fmt::Display for OrderLinesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OrderLinesError::InvalidQuantityError(quantity) => {
+                write!(f, "Invalid quantity error: {}", quantity)
+            }
+            OrderLinesError::InvalidPriceError(price) => {
+                write!(f, "Invalid price error: {}", price)
+            }
+            OrderLinesError::LineQuantityLimitExceededError(product_id) => write!(
+                f,
+                "Quantity of product {} exceeds {}",
+                product_id.0, MAX_QUANTITY_PER_LINE
+            ),
+            OrderLinesError::OrderLinesLimitExceededError => {
+                write!(
+                    f,
+                    "An order can't have more than {} lines",
+                    MAX_LINES_PER_ORDER
+                )
+            }
+            OrderLinesError::DuplicateProductError(product_id) => {
+                write!(f, "Product {} is in more than one line", product_id.0)
+            }
+            OrderLinesError::ProductNotFoundError(product_id) => {
+                write!(f, "Product {} is not in the order", product_id.0)
+            }
+            OrderLinesError::OrderNotDraftError(status) => {
+                write!(f, "Order is {}, its items can't change", status)
+            }
+            OrderLinesError::PriceMismatchError(product_id) => {
+                write!(
+                    f,
+                    "Product {} is already in a line at another price",
+                    product_id.0
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for OrderLinesError {}
+
+/// Items of an order: one line per product, each with a positive quantity and
+/// a non-negative price.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OrderLines(Vec<OrderItem>);
+
+impl OrderLines {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a line, or increases the quantity of the product if it already
+    /// has one at the same price.
+    pub fn add(&mut self, order_item: OrderItem) -> Result<(), OrderLinesError> {
+        validate_quantity(order_item.quantity)?;
+        validate_price(order_item.price)?;
+        match self.find_mut(&order_item.product_id) {
+            Some(existing) => {
+                if existing.price != order_item.price {
+                    return Err(OrderLinesError::PriceMismatchError(order_item.product_id));
+                }
+                let quantity = existing.quantity.saturating_add(order_item.quantity);
+                if quantity > MAX_QUANTITY_PER_LINE {
+                    return Err(OrderLinesError::LineQuantityLimitExceededError(
+                        order_item.product_id,
+                    ));
+                }
+                existing.quantity = quantity;
+            }
+            None => {
+                if order_item.quantity > MAX_QUANTITY_PER_LINE {
+                    return Err(OrderLinesError::LineQuantityLimitExceededError(
+                        order_item.product_id,
+                    ));
+                }
+                if self.0.len() >= MAX_LINES_PER_ORDER {
+                    return Err(OrderLinesError::OrderLinesLimitExceededError);
+                }
+                self.0.push(order_item);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, product_id: &ProductId) -> Result<OrderItem, OrderLinesError> {
+        let position = self
+            .0
+            .iter()
+            .position(|item| &item.product_id == product_id)
+            .ok_or_else(|| OrderLinesError::ProductNotFoundError(product_id.clone()))?;
+        Ok(self.0.remove(position))
+    }
+
+    pub fn change_quantity(
+        &mut self,
+        product_id: &ProductId,
+        quantity: i32,
+    ) -> Result<(), OrderLinesError> {
+        validate_quantity(quantity)?;
+        if quantity > MAX_QUANTITY_PER_LINE {
+            return Err(OrderLinesError::LineQuantityLimitExceededError(
+                product_id.clone(),
+            ));
+        }
+        let existing = self
+            .find_mut(product_id)
+            .ok_or_else(|| OrderLinesError::ProductNotFoundError(product_id.clone()))?;
+        existing.quantity = quantity;
+        Ok(())
+    }
+
+    pub fn find(&self, product_id: &ProductId) -> Option<&OrderItem> {
+        self.0.iter().find(|item| &item.product_id == product_id)
+    }
+
+    pub fn as_slice(&self) -> &[OrderItem] {
+        &self.0
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, OrderItem> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn total_price(&self) -> f64 {
+        let total: f64 = self.0.iter().map(|x| x.price * x.quantity as f64).sum();
+        (total * 100.0).round() / 100.0
+    }
+
+    fn find_mut(&mut self, product_id: &ProductId) -> Option<&mut OrderItem> {
+        self.0
+            .iter_mut()
+            .find(|item| &item.product_id == product_id)
+    }
+}
+
+/// Checks stored lines, which must already be one per product.
+impl TryFrom<Vec<OrderItem>> for OrderLines {
+    type Error = OrderLinesError;
+
+    fn try_from(order_items: Vec<OrderItem>) -> Result<Self, Self::Error> {
+        let mut order_lines = OrderLines::new();
+        for order_item in order_items {
+            if order_lines.find(&order_item.product_id).is_some() {
+                return Err(OrderLinesError::DuplicateProductError(
+                    order_item.product_id,
+                ));
+            }
+            order_lines.add(order_item)?;
+        }
+        Ok(order_lines)
+    }
+}
+
+impl<'a> IntoIterator for &'a OrderLines {
+    type Item = &'a OrderItem;
+    type IntoIter = std::slice::Iter<'a, OrderItem>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+fn validate_quantity(quantity: i32) -> Result<(), OrderLinesError> {
+    if quantity <= 0 {
+        return Err(OrderLinesError::InvalidQuantityError(quantity));
+    }
+    Ok(())
+}
+
+fn validate_price(price: f64) -> Result<(), OrderLinesError> {
+    if !price.is_finite() || price < 0.0 {
+        return Err(OrderLinesError::InvalidPriceError(price));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn merges_lines_of_the_same_product() {
+        let mut order_lines = OrderLines::new();
+        let product_id = ProductId(Uuid::new_v4());
+
+        order_lines.add(order_item(&product_id, 2)).unwrap();
+        order_lines.add(order_item(&product_id, 3)).unwrap();
+
+        assert_eq!(1, order_lines.len());
+        assert_eq!(5, order_lines.find(&product_id).unwrap().quantity);
+    }
+
+    #[test]
+    fn does_not_merge_lines_of_the_same_product_at_another_price() {
+        let mut order_lines = OrderLines::new();
+        let product_id = ProductId(Uuid::new_v4());
+        order_lines.add(order_item(&product_id, 2)).unwrap();
+
+        let result = order_lines.add(OrderItem {
+            price: 12.5,
+            ..order_item(&product_id, 1)
+        });
+
+        assert_eq!(
+            Err(OrderLinesError::PriceMismatchError(product_id.clone())),
+            result
+        );
+        assert_eq!(2, order_lines.find(&product_id).unwrap().quantity);
+    }
+
+    #[test]
+    fn rejects_non_positive_quantities() {
+        let mut order_lines = OrderLines::new();
+        let product_id = ProductId(Uuid::new_v4());
+
+        assert_eq!(
+            Err(OrderLinesError::InvalidQuantityError(0)),
+            order_lines.add(order_item(&product_id, 0))
+        );
+        order_lines.add(order_item(&product_id, 1)).unwrap();
+        assert_eq!(
+            Err(OrderLinesError::InvalidQuantityError(-1)),
+            order_lines.change_quantity(&product_id, -1)
+        );
+    }
+
+    #[test]
+    fn rejects_negative_prices() {
+        let mut order_lines = OrderLines::new();
+
+        let result = order_lines.add(OrderItem {
+            price: -0.01,
+            ..order_item(&ProductId(Uuid::new_v4()), 1)
+        });
+
+        assert_eq!(Err(OrderLinesError::InvalidPriceError(-0.01)), result);
+        assert!(order_lines.is_empty());
+    }
+
+    #[test]
+    fn rejects_quantities_over_the_line_maximum() {
+        let mut order_lines = OrderLines::new();
+        let product_id = ProductId(Uuid::new_v4());
+        order_lines
+            .add(order_item(&product_id, MAX_QUANTITY_PER_LINE))
+            .unwrap();
+
+        let result = order_lines.add(order_item(&product_id, 1));
+
+        assert_eq!(
+            Err(OrderLinesError::LineQuantityLimitExceededError(
+                product_id.clone()
+            )),
+            result
+        );
+        assert_eq!(
+            MAX_QUANTITY_PER_LINE,
+            order_lines.find(&product_id).unwrap().quantity
+        );
+    }
+
+    #[test]
+    fn rejects_lines_over_the_order_maximum() {
+        let mut order_lines = OrderLines::new();
+        for _ in 0..MAX_LINES_PER_ORDER {
+            order_lines
+                .add(order_item(&ProductId(Uuid::new_v4()), 1))
+                .unwrap();
+        }
+
+        let result = order_lines.add(order_item(&ProductId(Uuid::new_v4()), 1));
+
+        assert_eq!(Err(OrderLinesError::OrderLinesLimitExceededError), result);
+    }
+
+    #[test]
+    fn rejects_duplicate_products_in_stored_lines() {
+        let product_id = ProductId(Uuid::new_v4());
+
+        let result =
+            OrderLines::try_from(vec![order_item(&product_id, 1), order_item(&product_id, 1)]);
+
+        assert_eq!(
+            Err(OrderLinesError::DuplicateProductError(product_id)),
+            result
+        );
+    }
+
+    #[test]
+    fn cannot_remove_a_missing_product() {
+        let mut order_lines = OrderLines::new();
+        let product_id = ProductId(Uuid::new_v4());
+
+        assert_eq!(
+            Err(OrderLinesError::ProductNotFoundError(product_id.clone())),
+            order_lines.remove(&product_id)
+        );
+    }
+
+    fn order_item(product_id: &ProductId, quantity: i32) -> OrderItem {
+        OrderItem {
+            price: 9.99,
+            quantity,
+            product_id: product_id.clone(),
+        }
+    }
+}
@@ -1,9 +1,23 @@
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::entities::{customer::Customer, order::Order};
-use crate::value_objects::{OrderId, ProductId};
+use crate::entities::{
+    currency::OrderCurrency,
+    customer::Customer,
+    inventory::Inventory,
+    order::{Order, OrderAddresses},
+    promotion::{AppliedPromotion, Discount, Pricing, PromotionError},
+    returns::{Return, ReturnState},
+    review::Review,
+    shipment::Shipment,
+    shipping_method::{OrderShipping, ShippingRate},
+    tax::{TaxRate, Taxes},
+};
+use crate::value_objects::{
+    Address, AuthorizationId, CountryCode, CouponCode, Currency, CustomerId, InvalidValueError,
+    OrderId, OrderItem, PostalCode, ProductId, ReservationId, ShippingMethodId, ERASED,
+};
 
 #[derive(Debug)]
 pub enum OutboxMessageError {
@@ -27,11 +41,57 @@ pub enum OutboxMessageType {
     OrderCreated,
     CustomerCreated,
     ProductAddedToOrder,
+    ProductRemovedFromOrder,
+    ProductQuantityChanged,
+    CustomerUpdated,
+    CustomerAddressChanged,
+    CustomerDeactivated,
+    CustomerAddressBookChanged,
+    CustomerErased,
+    OrderPlaced,
+    StockReserved,
+    PaymentAuthorized,
+    OrderConfirmed,
+    OrderCancelled,
+    StockDepleted,
+    PaymentFailed,
+    ReturnRequested,
+    ReturnApproved,
+    ReturnRejected,
+    ReturnReceived,
+    ReturnRefunded,
+    CouponApplied,
+    OrderShipped,
+    ShipmentDelivered,
+    ReviewSubmitted,
 }
 
 const ORDER_CREATED: &str = "order_created";
 const CUSTOMER_CREATED: &str = "customer_created";
 const PRODUCT_ADDED_TO_ORDER: &str = "product_added_to_order";
+const PRODUCT_REMOVED_FROM_ORDER: &str = "product_removed_from_order";
+const PRODUCT_QUANTITY_CHANGED: &str = "product_quantity_changed";
+const CUSTOMER_UPDATED: &str = "customer_updated";
+const CUSTOMER_ADDRESS_CHANGED: &str = "customer_address_changed";
+const CUSTOMER_DEACTIVATED: &str = "customer_deactivated";
+const CUSTOMER_ADDRESS_BOOK_CHANGED: &str = "customer_address_book_changed";
+const CUSTOMER_ERASED: &str = "customer_erased";
+const ORDER_PLACED: &str = "order_placed";
+const STOCK_RESERVED: &str = "stock_reserved";
+const PAYMENT_AUTHORIZED: &str = "payment_authorized";
+const ORDER_CONFIRMED: &str = "order_confirmed";
+const ORDER_CANCELLED: &str = "order_cancelled";
+const STOCK_DEPLETED: &str = "stock_depleted";
+const PAYMENT_FAILED: &str = "payment_failed";
+const RETURN_REQUESTED: &str = "return_requested";
+const RETURN_APPROVED: &str = "return_approved";
+const RETURN_REJECTED: &str = "return_rejected";
+const RETURN_RECEIVED: &str = "return_received";
+const RETURN_REFUNDED: &str = "return_refunded";
+const COUPON_APPLIED: &str = "coupon_applied";
+const ORDER_SHIPPED: &str = "order_shipped";
+const SHIPMENT_DELIVERED: &str = "shipment_delivered";
+const REVIEW_SUBMITTED: &str = "review_submitted";
 
 impl std::fmt::Display for OutboxMessageType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -39,6 +99,33 @@ impl std::fmt::Display for OutboxMessageType {
             OutboxMessageType::OrderCreated => write!(f, "{}", ORDER_CREATED),
             OutboxMessageType::CustomerCreated => write!(f, "{}", CUSTOMER_CREATED),
             OutboxMessageType::ProductAddedToOrder => write!(f, "{}", PRODUCT_ADDED_TO_ORDER),
+            OutboxMessageType::ProductRemovedFromOrder => {
+                write!(f, "{}", PRODUCT_REMOVED_FROM_ORDER)
+            }
+            OutboxMessageType::ProductQuantityChanged => write!(f, "{}", PRODUCT_QUANTITY_CHANGED),
+            OutboxMessageType::CustomerUpdated => write!(f, "{}", CUSTOMER_UPDATED),
+            OutboxMessageType::CustomerAddressChanged => write!(f, "{}", CUSTOMER_ADDRESS_CHANGED),
+            OutboxMessageType::CustomerDeactivated => write!(f, "{}", CUSTOMER_DEACTIVATED),
+            OutboxMessageType::CustomerAddressBookChanged => {
+                write!(f, "{}", CUSTOMER_ADDRESS_BOOK_CHANGED)
+            }
+            OutboxMessageType::CustomerErased => write!(f, "{}", CUSTOMER_ERASED),
+            OutboxMessageType::OrderPlaced => write!(f, "{}", ORDER_PLACED),
+            OutboxMessageType::StockReserved => write!(f, "{}", STOCK_RESERVED),
+            OutboxMessageType::PaymentAuthorized => write!(f, "{}", PAYMENT_AUTHORIZED),
+            OutboxMessageType::OrderConfirmed => write!(f, "{}", ORDER_CONFIRMED),
+            OutboxMessageType::OrderCancelled => write!(f, "{}", ORDER_CANCELLED),
+            OutboxMessageType::StockDepleted => write!(f, "{}", STOCK_DEPLETED),
+            OutboxMessageType::PaymentFailed => write!(f, "{}", PAYMENT_FAILED),
+            OutboxMessageType::ReturnRequested => write!(f, "{}", RETURN_REQUESTED),
+            OutboxMessageType::ReturnApproved => write!(f, "{}", RETURN_APPROVED),
+            OutboxMessageType::ReturnRejected => write!(f, "{}", RETURN_REJECTED),
+            OutboxMessageType::ReturnReceived => write!(f, "{}", RETURN_RECEIVED),
+            OutboxMessageType::ReturnRefunded => write!(f, "{}", RETURN_REFUNDED),
+            OutboxMessageType::CouponApplied => write!(f, "{}", COUPON_APPLIED),
+            OutboxMessageType::OrderShipped => write!(f, "{}", ORDER_SHIPPED),
+            OutboxMessageType::ShipmentDelivered => write!(f, "{}", SHIPMENT_DELIVERED),
+            OutboxMessageType::ReviewSubmitted => write!(f, "{}", REVIEW_SUBMITTED),
         }
     }
 }
@@ -51,6 +138,29 @@ impl std::str::FromStr for OutboxMessageType {
             ORDER_CREATED => Ok(OutboxMessageType::OrderCreated),
             CUSTOMER_CREATED => Ok(OutboxMessageType::CustomerCreated),
             PRODUCT_ADDED_TO_ORDER => Ok(OutboxMessageType::ProductAddedToOrder),
+            PRODUCT_REMOVED_FROM_ORDER => Ok(OutboxMessageType::ProductRemovedFromOrder),
+            PRODUCT_QUANTITY_CHANGED => Ok(OutboxMessageType::ProductQuantityChanged),
+            CUSTOMER_UPDATED => Ok(OutboxMessageType::CustomerUpdated),
+            CUSTOMER_ADDRESS_CHANGED => Ok(OutboxMessageType::CustomerAddressChanged),
+            CUSTOMER_DEACTIVATED => Ok(OutboxMessageType::CustomerDeactivated),
+            CUSTOMER_ADDRESS_BOOK_CHANGED => Ok(OutboxMessageType::CustomerAddressBookChanged),
+            CUSTOMER_ERASED => Ok(OutboxMessageType::CustomerErased),
+            ORDER_PLACED => Ok(OutboxMessageType::OrderPlaced),
+            STOCK_RESERVED => Ok(OutboxMessageType::StockReserved),
+            PAYMENT_AUTHORIZED => Ok(OutboxMessageType::PaymentAuthorized),
+            ORDER_CONFIRMED => Ok(OutboxMessageType::OrderConfirmed),
+            ORDER_CANCELLED => Ok(OutboxMessageType::OrderCancelled),
+            STOCK_DEPLETED => Ok(OutboxMessageType::StockDepleted),
+            PAYMENT_FAILED => Ok(OutboxMessageType::PaymentFailed),
+            RETURN_REQUESTED => Ok(OutboxMessageType::ReturnRequested),
+            RETURN_APPROVED => Ok(OutboxMessageType::ReturnApproved),
+            RETURN_REJECTED => Ok(OutboxMessageType::ReturnRejected),
+            RETURN_RECEIVED => Ok(OutboxMessageType::ReturnReceived),
+            RETURN_REFUNDED => Ok(OutboxMessageType::ReturnRefunded),
+            COUPON_APPLIED => Ok(OutboxMessageType::CouponApplied),
+            ORDER_SHIPPED => Ok(OutboxMessageType::OrderShipped),
+            SHIPMENT_DELIVERED => Ok(OutboxMessageType::ShipmentDelivered),
+            REVIEW_SUBMITTED => Ok(OutboxMessageType::ReviewSubmitted),
             _ => Err(format!("Unknown outbox message type: {}", s)),
         }
     }
@@ -95,6 +205,69 @@ impl OutboxMessage {
         })
     }
 
+    pub fn customer_updated_event(
+        customer: &Customer,
+    ) -> Result<OutboxMessage, OutboxMessageError> {
+        let event_payload = customer_updated_event_payload(customer)?;
+        Ok(OutboxMessage {
+            id: Uuid::new_v4(),
+            event_type: OutboxMessageType::CustomerUpdated,
+            event_payload,
+            created_at: Utc::now(),
+            processed_at: None,
+        })
+    }
+
+    pub fn customer_address_changed_event(
+        customer: &Customer,
+    ) -> Result<OutboxMessage, OutboxMessageError> {
+        let event_payload = customer_address_changed_event_payload(customer)?;
+        Ok(OutboxMessage {
+            id: Uuid::new_v4(),
+            event_type: OutboxMessageType::CustomerAddressChanged,
+            event_payload,
+            created_at: Utc::now(),
+            processed_at: None,
+        })
+    }
+
+    pub fn customer_deactivated_event(
+        customer: &Customer,
+    ) -> Result<OutboxMessage, OutboxMessageError> {
+        let event_payload = customer_deactivated_event_payload(customer)?;
+        Ok(OutboxMessage {
+            id: Uuid::new_v4(),
+            event_type: OutboxMessageType::CustomerDeactivated,
+            event_payload,
+            created_at: Utc::now(),
+            processed_at: None,
+        })
+    }
+
+    pub fn customer_address_book_changed_event(
+        customer: &Customer,
+    ) -> Result<OutboxMessage, OutboxMessageError> {
+        let event_payload = customer_address_book_changed_event_payload(customer)?;
+        Ok(OutboxMessage {
+            id: Uuid::new_v4(),
+            event_type: OutboxMessageType::CustomerAddressBookChanged,
+            event_payload,
+            created_at: Utc::now(),
+            processed_at: None,
+        })
+    }
+
+    pub fn customer_erased_event(customer: &Customer) -> Result<OutboxMessage, OutboxMessageError> {
+        let event_payload = customer_erased_event_payload(customer)?;
+        Ok(OutboxMessage {
+            id: Uuid::new_v4(),
+            event_type: OutboxMessageType::CustomerErased,
+            event_payload,
+            created_at: Utc::now(),
+            processed_at: None,
+        })
+    }
+
     pub fn order_created_event(order: &Order) -> Result<OutboxMessage, OutboxMessageError> {
         let event_payload = order_created_event_payload(order)?;
         Ok(OutboxMessage {
@@ -111,12 +284,14 @@ impl OutboxMessage {
         product_id: &ProductId,
         price: f64,
         quantity: i32,
+        currency: &OrderCurrency,
     ) -> Result<OutboxMessage, OutboxMessageError> {
         let event_payload = product_added_to_order_event_payload(
             order_id.0.to_string(),
             product_id.0.to_string(),
             price,
             quantity,
+            currency,
         )?;
         Ok(OutboxMessage {
             id: Uuid::new_v4(),
@@ -127,6 +302,225 @@ impl OutboxMessage {
         })
     }
 
+    /// Published when the line of `removed` is taken out of an order.
+    pub fn product_removed_from_order_event(
+        order_id: &OrderId,
+        removed: &OrderItem,
+        currency: &OrderCurrency,
+    ) -> Result<OutboxMessage, OutboxMessageError> {
+        let event = ProductRemovedFromOrderEvent {
+            order_id: order_id.0.to_string(),
+            product_id: removed.product_id.0.to_string(),
+            quantity: removed.quantity,
+            price: removed.price,
+            currency: Some(CurrencyPayload::from(currency)),
+        };
+        Self::with_payload(OutboxMessageType::ProductRemovedFromOrder, &event)
+    }
+
+    /// Published when the quantity of the line of `previous` changes to `quantity`.
+    pub fn product_quantity_changed_event(
+        order_id: &OrderId,
+        previous: &OrderItem,
+        quantity: i32,
+        currency: &OrderCurrency,
+    ) -> Result<OutboxMessage, OutboxMessageError> {
+        let event = ProductQuantityChangedEvent {
+            order_id: order_id.0.to_string(),
+            product_id: previous.product_id.0.to_string(),
+            previous_quantity: previous.quantity,
+            quantity,
+            price: previous.price,
+            currency: Some(CurrencyPayload::from(currency)),
+        };
+        Self::with_payload(OutboxMessageType::ProductQuantityChanged, &event)
+    }
+
+    /// Published when an order holding items is placed, starting its checkout.
+    pub fn order_placed_event(order: &Order) -> Result<OutboxMessage, OutboxMessageError> {
+        let event = OrderPlacedEvent {
+            order_id: order.id().0.to_string(),
+            customer_id: order.customer_id().0.to_string(),
+            total_price: order.total_price(),
+            pricing: Some(PricingPayload::from(&order.pricing())),
+        };
+        Self::with_payload(OutboxMessageType::OrderPlaced, &event)
+    }
+
+    /// Published when a coupon is applied to an order, with its new price.
+    pub fn coupon_applied_event(
+        order: &Order,
+        promotion: &AppliedPromotion,
+    ) -> Result<OutboxMessage, OutboxMessageError> {
+        let event = CouponAppliedEvent {
+            order_id: order.id().0.to_string(),
+            customer_id: order.customer_id().0.to_string(),
+            promotion: PromotionPayload::from(promotion),
+            pricing: PricingPayload::from(&order.pricing()),
+        };
+        Self::with_payload(OutboxMessageType::CouponApplied, &event)
+    }
+
+    pub fn stock_reserved_event(
+        order_id: &OrderId,
+        reservation_id: &ReservationId,
+    ) -> Result<OutboxMessage, OutboxMessageError> {
+        let event = StockReservedEvent {
+            order_id: order_id.0.to_string(),
+            reservation_id: reservation_id.0.to_string(),
+        };
+        Self::with_payload(OutboxMessageType::StockReserved, &event)
+    }
+
+    pub fn payment_authorized_event(
+        order_id: &OrderId,
+        authorization_id: &AuthorizationId,
+        amount: f64,
+    ) -> Result<OutboxMessage, OutboxMessageError> {
+        let event = PaymentAuthorizedEvent {
+            order_id: order_id.0.to_string(),
+            authorization_id: authorization_id.0.clone(),
+            amount,
+        };
+        Self::with_payload(OutboxMessageType::PaymentAuthorized, &event)
+    }
+
+    pub fn order_confirmed_event(order_id: &OrderId) -> Result<OutboxMessage, OutboxMessageError> {
+        let event = OrderConfirmedEvent {
+            order_id: order_id.0.to_string(),
+        };
+        Self::with_payload(OutboxMessageType::OrderConfirmed, &event)
+    }
+
+    pub fn order_cancelled_event(
+        order_id: &OrderId,
+        reason: &str,
+    ) -> Result<OutboxMessage, OutboxMessageError> {
+        let event = OrderCancelledEvent {
+            order_id: order_id.0.to_string(),
+            reason: reason.to_string(),
+        };
+        Self::with_payload(OutboxMessageType::OrderCancelled, &event)
+    }
+
+    /// Published when no unit of the product is left available in the warehouse.
+    pub fn stock_depleted_event(
+        inventory: &Inventory,
+    ) -> Result<OutboxMessage, OutboxMessageError> {
+        let event = StockDepletedEvent {
+            product_id: inventory.product_id().0.to_string(),
+            warehouse_id: inventory.warehouse_id().0.to_string(),
+        };
+        Self::with_payload(OutboxMessageType::StockDepleted, &event)
+    }
+
+    /// Published when the payment provider declines the payment of an order.
+    pub fn payment_failed_event(
+        order_id: &OrderId,
+        amount: f64,
+        reason: &str,
+    ) -> Result<OutboxMessage, OutboxMessageError> {
+        let event = PaymentFailedEvent {
+            order_id: order_id.0.to_string(),
+            amount,
+            reason: reason.to_string(),
+        };
+        Self::with_payload(OutboxMessageType::PaymentFailed, &event)
+    }
+
+    /// Published each time a return changes state, with the event type of
+    /// the new state, so the warehouse and finance know what to expect.
+    pub fn return_event(order_return: &Return) -> Result<OutboxMessage, OutboxMessageError> {
+        let event_type = match order_return.state() {
+            ReturnState::Requested => OutboxMessageType::ReturnRequested,
+            ReturnState::Approved => OutboxMessageType::ReturnApproved,
+            ReturnState::Rejected => OutboxMessageType::ReturnRejected,
+            ReturnState::Received => OutboxMessageType::ReturnReceived,
+            ReturnState::Refunded => OutboxMessageType::ReturnRefunded,
+        };
+        let event = ReturnEvent {
+            return_id: order_return.id().0.to_string(),
+            order_id: order_return.order_id().0.to_string(),
+            state: order_return.state().to_string(),
+            items: order_return
+                .items()
+                .iter()
+                .map(|item| ReturnItemPayload {
+                    product_id: item.product_id.0.to_string(),
+                    price: item.price,
+                    quantity: item.quantity,
+                })
+                .collect(),
+            refund_amount: order_return.refund_amount(),
+            rejection_reason: order_return.rejection_reason().map(str::to_string),
+        };
+        Self::with_payload(event_type, &event)
+    }
+
+    /// Published when a shipment is handed to the carrier. `fully_shipped`
+    /// tells whether no units of the order are left to ship.
+    pub fn order_shipped_event(
+        shipment: &Shipment,
+        fully_shipped: bool,
+    ) -> Result<OutboxMessage, OutboxMessageError> {
+        let event = OrderShippedEvent {
+            shipment_id: shipment.id().0.to_string(),
+            order_id: shipment.order_id().0.to_string(),
+            carrier: shipment.carrier().to_string(),
+            tracking_number: shipment.tracking_number().unwrap_or_default().to_string(),
+            items: shipment
+                .items()
+                .iter()
+                .map(|item| ShipmentItemPayload {
+                    product_id: item.product_id.0.to_string(),
+                    quantity: item.quantity,
+                })
+                .collect(),
+            weight: shipment.weight(),
+            fully_shipped,
+        };
+        Self::with_payload(OutboxMessageType::OrderShipped, &event)
+    }
+
+    pub fn shipment_delivered_event(
+        shipment: &Shipment,
+    ) -> Result<OutboxMessage, OutboxMessageError> {
+        let event = ShipmentDeliveredEvent {
+            shipment_id: shipment.id().0.to_string(),
+            order_id: shipment.order_id().0.to_string(),
+            carrier: shipment.carrier().to_string(),
+            tracking_number: shipment.tracking_number().unwrap_or_default().to_string(),
+        };
+        Self::with_payload(OutboxMessageType::ShipmentDelivered, &event)
+    }
+
+    pub fn review_submitted_event(review: &Review) -> Result<OutboxMessage, OutboxMessageError> {
+        let event = ReviewSubmittedEvent {
+            review_id: review.id().0.to_string(),
+            product_id: review.product_id().0.to_string(),
+            customer_id: review.customer_id().0.to_string(),
+            rating: review.rating(),
+            text: review.text().to_string(),
+            verified_purchase: review.verified_purchase(),
+        };
+        Self::with_payload(OutboxMessageType::ReviewSubmitted, &event)
+    }
+
+    fn with_payload(
+        event_type: OutboxMessageType,
+        event: &impl Serialize,
+    ) -> Result<OutboxMessage, OutboxMessageError> {
+        let event_payload = serde_json::to_string(event)
+            .map_err(|e| OutboxMessageError::PayloadSerializationError(e.to_string()))?;
+        Ok(OutboxMessage {
+            id: Uuid::new_v4(),
+            event_type,
+            event_payload,
+            created_at: Utc::now(),
+            processed_at: None,
+        })
+    }
+
     pub fn id(&self) -> Uuid {
         self.id
     }
@@ -148,46 +542,385 @@ impl OutboxMessage {
     pub fn set_processed_at(&mut self, processed_at: DateTime<Utc>) {
         self.processed_at = Some(processed_at);
     }
+
+    /// The same message, with the personal data in its payload replaced by
+    /// [`ERASED`]. Like for erased customers, addresses keep postal code and country.
+    pub fn anonymized(&self) -> Result<OutboxMessage, OutboxMessageError> {
+        let payload = &self.event_payload;
+        let event_payload = match self.event_type {
+            OutboxMessageType::CustomerCreated => {
+                anonymize(payload, |event: &mut CustomerCreatedEvent| {
+                    event.first_name = ERASED.to_string();
+                    event.last_name = ERASED.to_string();
+                    event.email = ERASED.to_string();
+                })?
+            }
+            OutboxMessageType::CustomerUpdated => {
+                anonymize(payload, |event: &mut CustomerUpdatedEvent| {
+                    event.first_name = ERASED.to_string();
+                    event.last_name = ERASED.to_string();
+                    event.email = ERASED.to_string();
+                    event.phone = None;
+                })?
+            }
+            OutboxMessageType::CustomerAddressChanged => {
+                anonymize(payload, |event: &mut CustomerAddressChangedEvent| {
+                    event.street = ERASED.to_string();
+                    event.city = ERASED.to_string();
+                })?
+            }
+            OutboxMessageType::CustomerAddressBookChanged => {
+                anonymize(payload, |event: &mut CustomerAddressBookChangedEvent| {
+                    for customer_address in event.addresses.iter_mut() {
+                        customer_address.address.erase();
+                    }
+                })?
+            }
+            OutboxMessageType::OrderCreated => {
+                anonymize(payload, |event: &mut OrderCreatedEvent| {
+                    for address in [&mut event.shipping_address, &mut event.billing_address]
+                        .into_iter()
+                        .flatten()
+                    {
+                        address.erase();
+                    }
+                })?
+            }
+            OutboxMessageType::ReviewSubmitted => {
+                anonymize(payload, |event: &mut ReviewSubmittedEvent| {
+                    event.text = ERASED.to_string();
+                })?
+            }
+            OutboxMessageType::CustomerDeactivated
+            | OutboxMessageType::CustomerErased
+            | OutboxMessageType::ProductAddedToOrder
+            | OutboxMessageType::ProductRemovedFromOrder
+            | OutboxMessageType::ProductQuantityChanged
+            | OutboxMessageType::OrderPlaced
+            | OutboxMessageType::StockReserved
+            | OutboxMessageType::PaymentAuthorized
+            | OutboxMessageType::OrderConfirmed
+            | OutboxMessageType::OrderCancelled
+            | OutboxMessageType::StockDepleted
+            | OutboxMessageType::PaymentFailed
+            | OutboxMessageType::ReturnRequested
+            | OutboxMessageType::ReturnApproved
+            | OutboxMessageType::ReturnRejected
+            | OutboxMessageType::ReturnReceived
+            | OutboxMessageType::ReturnRefunded
+            | OutboxMessageType::CouponApplied
+            | OutboxMessageType::OrderShipped
+            | OutboxMessageType::ShipmentDelivered => payload.clone(),
+        };
+        Ok(OutboxMessage {
+            event_payload,
+            ..self.clone()
+        })
+    }
 }
 
-#[derive(Serialize)]
-struct CustomerCreatedEvent {
-    id: String,
-    first_name: String,
-    last_name: String,
+fn anonymize<T: Serialize + DeserializeOwned>(
+    payload: &str,
+    erase: impl FnOnce(&mut T),
+) -> Result<String, OutboxMessageError> {
+    let mut event: T = serde_json::from_str(payload)
+        .map_err(|e| OutboxMessageError::PayloadSerializationError(e.to_string()))?;
+    erase(&mut event);
+    serde_json::to_string(&event)
+        .map_err(|e| OutboxMessageError::PayloadSerializationError(e.to_string()))
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct CustomerCreatedEvent {
+    pub id: String,
+    pub first_name: String,
+    pub last_name: String,
+    /// Empty in events published before customers had an email.
+    #[serde(default)]
+    pub email: String,
 }
 
 fn customer_created_event_payload(customer: &Customer) -> Result<String, OutboxMessageError> {
     let event = CustomerCreatedEvent {
         id: customer.id.0.to_string(),
-        first_name: customer.first_name.clone(),
-        last_name: customer.last_name.clone(),
+        first_name: customer.first_name.to_string(),
+        last_name: customer.last_name.to_string(),
+        email: customer.email.to_string(),
+    };
+    serde_json::to_string(&event)
+        .map_err(|e| OutboxMessageError::PayloadSerializationError(e.to_string()))
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct CustomerUpdatedEvent {
+    pub id: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub phone: Option<String>,
+}
+
+fn customer_updated_event_payload(customer: &Customer) -> Result<String, OutboxMessageError> {
+    let event = CustomerUpdatedEvent {
+        id: customer.id.0.to_string(),
+        first_name: customer.first_name.to_string(),
+        last_name: customer.last_name.to_string(),
+        email: customer.email.to_string(),
+        phone: customer.phone.as_ref().map(|phone| phone.to_string()),
+    };
+    serde_json::to_string(&event)
+        .map_err(|e| OutboxMessageError::PayloadSerializationError(e.to_string()))
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct CustomerAddressChangedEvent {
+    pub id: String,
+    pub street: String,
+    pub city: String,
+    pub zip_code: String,
+    pub country: String,
+}
+
+fn customer_address_changed_event_payload(
+    customer: &Customer,
+) -> Result<String, OutboxMessageError> {
+    let event = CustomerAddressChangedEvent {
+        id: customer.id.0.to_string(),
+        street: customer.address.street.clone(),
+        city: customer.address.city.clone(),
+        zip_code: customer.address.zip_code.to_string(),
+        country: customer.address.country.to_string(),
+    };
+    serde_json::to_string(&event)
+        .map_err(|e| OutboxMessageError::PayloadSerializationError(e.to_string()))
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct CustomerDeactivatedEvent {
+    pub id: String,
+}
+
+fn customer_deactivated_event_payload(customer: &Customer) -> Result<String, OutboxMessageError> {
+    let event = CustomerDeactivatedEvent {
+        id: customer.id.0.to_string(),
+    };
+    serde_json::to_string(&event)
+        .map_err(|e| OutboxMessageError::PayloadSerializationError(e.to_string()))
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct CustomerErasedEvent {
+    pub id: String,
+}
+
+fn customer_erased_event_payload(customer: &Customer) -> Result<String, OutboxMessageError> {
+    let event = CustomerErasedEvent {
+        id: customer.id.0.to_string(),
+    };
+    serde_json::to_string(&event)
+        .map_err(|e| OutboxMessageError::PayloadSerializationError(e.to_string()))
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct CustomerAddressBookChangedEvent {
+    pub id: String,
+    pub addresses: Vec<CustomerAddressPayload>,
+    pub default_shipping_address_id: Option<String>,
+    pub default_billing_address_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct CustomerAddressPayload {
+    pub id: String,
+    #[serde(flatten)]
+    pub address: AddressPayload,
+}
+
+fn customer_address_book_changed_event_payload(
+    customer: &Customer,
+) -> Result<String, OutboxMessageError> {
+    let address_book = &customer.address_book;
+    let event = CustomerAddressBookChangedEvent {
+        id: customer.id.0.to_string(),
+        addresses: address_book
+            .as_slice()
+            .iter()
+            .map(|customer_address| CustomerAddressPayload {
+                id: customer_address.id.0.to_string(),
+                address: AddressPayload::from(&customer_address.address),
+            })
+            .collect(),
+        default_shipping_address_id: address_book.default_shipping().map(|id| id.0.to_string()),
+        default_billing_address_id: address_book.default_billing().map(|id| id.0.to_string()),
     };
     serde_json::to_string(&event)
         .map_err(|e| OutboxMessageError::PayloadSerializationError(e.to_string()))
 }
 
-#[derive(Serialize)]
-struct OrderCreatedEvent {
-    id: String,
-    customer_id: String,
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct AddressPayload {
+    pub street: String,
+    pub city: String,
+    pub zip_code: String,
+    pub country: String,
+}
+
+impl AddressPayload {
+    fn erase(&mut self) {
+        self.street = ERASED.to_string();
+        self.city = ERASED.to_string();
+    }
+}
+
+impl From<&Address> for AddressPayload {
+    fn from(address: &Address) -> Self {
+        Self {
+            street: address.street.clone(),
+            city: address.city.clone(),
+            zip_code: address.zip_code.to_string(),
+            country: address.country.to_string(),
+        }
+    }
+}
+
+impl TryFrom<&AddressPayload> for Address {
+    type Error = InvalidValueError;
+
+    fn try_from(payload: &AddressPayload) -> Result<Self, Self::Error> {
+        let country = CountryCode::parse(&payload.country)?;
+        Ok(Address {
+            street: payload.street.clone(),
+            city: payload.city.clone(),
+            zip_code: PostalCode::parse(&payload.zip_code, &country)?,
+            country,
+        })
+    }
+}
+
+/// Currency of an order and its exchange rate to the base currency.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct CurrencyPayload {
+    pub currency: String,
+    pub exchange_rate: f64,
+}
+
+impl From<&OrderCurrency> for CurrencyPayload {
+    fn from(currency: &OrderCurrency) -> Self {
+        Self {
+            currency: currency.currency.to_string(),
+            exchange_rate: currency.exchange_rate,
+        }
+    }
+}
+
+impl TryFrom<&CurrencyPayload> for OrderCurrency {
+    type Error = InvalidValueError;
+
+    fn try_from(payload: &CurrencyPayload) -> Result<Self, Self::Error> {
+        Ok(OrderCurrency {
+            currency: Currency::parse(&payload.currency)?,
+            exchange_rate: payload.exchange_rate,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct OrderCreatedEvent {
+    pub id: String,
+    pub customer_id: String,
+    /// Missing in events of orders placed before addresses were recorded.
+    #[serde(default)]
+    pub shipping_address: Option<AddressPayload>,
+    #[serde(default)]
+    pub billing_address: Option<AddressPayload>,
+    /// Missing in events of orders created before taxes.
+    #[serde(default)]
+    pub tax: Option<TaxPayload>,
+    /// Missing in events of orders created before currencies, which are in
+    /// the base currency.
+    #[serde(default)]
+    pub currency: Option<CurrencyPayload>,
+    /// Missing in events of orders placed without a shipping method.
+    #[serde(default)]
+    pub shipping: Option<ShippingPayload>,
+}
+
+impl OrderCreatedEvent {
+    pub fn new(
+        id: &OrderId,
+        customer_id: &CustomerId,
+        addresses: Option<&OrderAddresses>,
+        currency: &OrderCurrency,
+        shipping: Option<&OrderShipping>,
+    ) -> Self {
+        Self {
+            id: id.0.to_string(),
+            customer_id: customer_id.0.to_string(),
+            shipping_address: addresses.map(|addresses| AddressPayload::from(&addresses.shipping)),
+            billing_address: addresses.map(|addresses| AddressPayload::from(&addresses.billing)),
+            tax: None,
+            currency: Some(CurrencyPayload::from(currency)),
+            shipping: shipping.map(ShippingPayload::from),
+        }
+    }
+
+    pub fn shipping(&self) -> Result<Option<OrderShipping>, String> {
+        self.shipping
+            .as_ref()
+            .map(OrderShipping::try_from)
+            .transpose()
+    }
+
+    pub fn currency(&self) -> Result<OrderCurrency, InvalidValueError> {
+        self.currency
+            .as_ref()
+            .map_or(Ok(OrderCurrency::default()), OrderCurrency::try_from)
+    }
+
+    pub fn addresses(&self) -> Result<Option<OrderAddresses>, InvalidValueError> {
+        match (&self.shipping_address, &self.billing_address) {
+            (Some(shipping), Some(billing)) => Ok(Some(OrderAddresses {
+                shipping: shipping.try_into()?,
+                billing: billing.try_into()?,
+            })),
+            _ => Ok(None),
+        }
+    }
 }
 
 fn order_created_event_payload(order: &Order) -> Result<String, OutboxMessageError> {
     let event = OrderCreatedEvent {
-        id: order.id.0.to_string(),
-        customer_id: order.customer_id.0.to_string(),
+        tax: Some(TaxPayload::from(&order.pricing())),
+        ..OrderCreatedEvent::new(
+            order.id(),
+            order.customer_id(),
+            order.addresses(),
+            order.currency(),
+            order.shipping(),
+        )
     };
     serde_json::to_string(&event)
         .map_err(|e| OutboxMessageError::PayloadSerializationError(e.to_string()))
 }
 
-#[derive(Serialize)]
-struct ProductAddedToOrderEvent {
-    order_id: String,
-    product_id: String,
-    quantity: i32,
-    price: f64,
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ProductAddedToOrderEvent {
+    pub order_id: String,
+    pub product_id: String,
+    pub quantity: i32,
+    pub price: f64,
+    /// Missing in events of orders in the base currency created before
+    /// currencies, and in stored order events, whose currency is the one of
+    /// `OrderCreatedEvent`.
+    #[serde(default)]
+    pub currency: Option<CurrencyPayload>,
+}
+
+impl ProductAddedToOrderEvent {
+    /// Price of the added quantity in the base currency.
+    pub fn base_amount(&self) -> Result<f64, InvalidValueError> {
+        base_amount(self.currency.as_ref(), self.price * self.quantity as f64)
+    }
 }
 
 fn product_added_to_order_event_payload(
@@ -195,13 +928,412 @@ fn product_added_to_order_event_payload(
     product_id: String,
     price: f64,
     quantity: i32,
+    currency: &OrderCurrency,
 ) -> Result<String, OutboxMessageError> {
     let event = ProductAddedToOrderEvent {
         order_id,
         product_id,
         price,
         quantity,
+        currency: Some(CurrencyPayload::from(currency)),
     };
     serde_json::to_string(&event)
         .map_err(|e| OutboxMessageError::PayloadSerializationError(e.to_string()))
 }
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ProductRemovedFromOrderEvent {
+    pub order_id: String,
+    pub product_id: String,
+    /// Quantity the line held when it was removed.
+    pub quantity: i32,
+    pub price: f64,
+    pub currency: Option<CurrencyPayload>,
+}
+
+impl ProductRemovedFromOrderEvent {
+    /// Price of the removed quantity in the base currency.
+    pub fn base_amount(&self) -> Result<f64, InvalidValueError> {
+        base_amount(self.currency.as_ref(), self.price * self.quantity as f64)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ProductQuantityChangedEvent {
+    pub order_id: String,
+    pub product_id: String,
+    pub previous_quantity: i32,
+    pub quantity: i32,
+    pub price: f64,
+    pub currency: Option<CurrencyPayload>,
+}
+
+impl ProductQuantityChangedEvent {
+    /// Units added to the line, negative if some were taken out.
+    pub fn quantity_change(&self) -> i32 {
+        self.quantity - self.previous_quantity
+    }
+
+    /// Price of the added units in the base currency, negative if some were
+    /// taken out.
+    pub fn base_amount(&self) -> Result<f64, InvalidValueError> {
+        base_amount(
+            self.currency.as_ref(),
+            self.price * self.quantity_change() as f64,
+        )
+    }
+}
+
+fn base_amount(currency: Option<&CurrencyPayload>, amount: f64) -> Result<f64, InvalidValueError> {
+    let currency = currency.map_or(Ok(OrderCurrency::default()), OrderCurrency::try_from)?;
+    Ok(currency.to_base(amount))
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct OrderPlacedEvent {
+    pub order_id: String,
+    pub customer_id: String,
+    /// Price to pay, after discounts.
+    pub total_price: f64,
+    /// Missing in events of orders placed before promotions.
+    #[serde(default)]
+    pub pricing: Option<PricingPayload>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct PricingPayload {
+    pub subtotal: f64,
+    pub discounts: Vec<DiscountPayload>,
+    #[serde(default)]
+    pub tax_total: f64,
+    /// Missing in events of orders placed before shipping was priced with them.
+    #[serde(default)]
+    pub shipping: f64,
+    pub total: f64,
+    pub free_shipping: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct DiscountPayload {
+    pub code: String,
+    pub amount: f64,
+}
+
+impl From<&Pricing> for PricingPayload {
+    fn from(pricing: &Pricing) -> Self {
+        Self {
+            subtotal: pricing.subtotal,
+            discounts: pricing
+                .discounts
+                .iter()
+                .map(|discount| DiscountPayload {
+                    code: discount.code.to_string(),
+                    amount: discount.amount,
+                })
+                .collect(),
+            tax_total: pricing.tax_total,
+            shipping: pricing.shipping,
+            total: pricing.total,
+            free_shipping: pricing.free_shipping,
+        }
+    }
+}
+
+/// Taxes of an order, computed on its items after discounts.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct TaxPayload {
+    pub pricing_mode: String,
+    pub lines: Vec<TaxLinePayload>,
+    pub total: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct TaxLinePayload {
+    pub product_id: String,
+    pub category: String,
+    pub rate: f64,
+    pub net_amount: f64,
+    pub amount: f64,
+}
+
+impl From<&Pricing> for TaxPayload {
+    fn from(pricing: &Pricing) -> Self {
+        Self {
+            pricing_mode: pricing.pricing_mode.to_string(),
+            lines: pricing
+                .tax_lines
+                .iter()
+                .map(|line| TaxLinePayload {
+                    product_id: line.product_id.0.to_string(),
+                    category: line.category.to_string(),
+                    rate: line.rate,
+                    net_amount: line.net_amount,
+                    amount: line.amount,
+                })
+                .collect(),
+            total: pricing.tax_total,
+        }
+    }
+}
+
+/// Pricing mode and tax rates of an order, as stored with it.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct TaxesPayload {
+    pub pricing_mode: String,
+    pub rates: Vec<TaxRatePayload>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct TaxRatePayload {
+    pub product_id: String,
+    pub category: String,
+    pub rate: f64,
+}
+
+impl From<&Taxes> for TaxesPayload {
+    fn from(taxes: &Taxes) -> Self {
+        Self {
+            pricing_mode: taxes.pricing_mode.to_string(),
+            rates: taxes
+                .rates
+                .iter()
+                .map(|rate| TaxRatePayload {
+                    product_id: rate.product_id.0.to_string(),
+                    category: rate.category.to_string(),
+                    rate: rate.rate,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<&TaxesPayload> for Taxes {
+    type Error = String;
+
+    fn try_from(payload: &TaxesPayload) -> Result<Self, Self::Error> {
+        Ok(Taxes {
+            pricing_mode: payload.pricing_mode.parse()?,
+            rates: payload
+                .rates
+                .iter()
+                .map(|rate| {
+                    Ok(TaxRate {
+                        product_id: ProductId(
+                            Uuid::try_parse(&rate.product_id).map_err(|e| e.to_string())?,
+                        ),
+                        category: rate.category.parse()?,
+                        rate: rate.rate,
+                    })
+                })
+                .collect::<Result<_, String>>()?,
+        })
+    }
+}
+
+/// Shipping method of an order, with the fields of its kind of rate.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct ShippingPayload {
+    pub shipping_method_id: String,
+    pub carrier: String,
+    pub kind: String,
+    pub amount: f64,
+    pub per_kg: Option<f64>,
+    pub threshold: Option<f64>,
+    pub weight: f64,
+}
+
+impl From<&OrderShipping> for ShippingPayload {
+    fn from(shipping: &OrderShipping) -> Self {
+        let (amount, per_kg, threshold) = shipping.rate.amounts();
+        Self {
+            shipping_method_id: shipping.shipping_method_id.0.to_string(),
+            carrier: shipping.carrier.clone(),
+            kind: shipping.rate.kind().to_string(),
+            amount,
+            per_kg,
+            threshold,
+            weight: shipping.weight,
+        }
+    }
+}
+
+impl TryFrom<&ShippingPayload> for OrderShipping {
+    type Error = String;
+
+    fn try_from(payload: &ShippingPayload) -> Result<Self, Self::Error> {
+        Ok(OrderShipping {
+            shipping_method_id: ShippingMethodId(
+                Uuid::try_parse(&payload.shipping_method_id).map_err(|e| e.to_string())?,
+            ),
+            carrier: payload.carrier.clone(),
+            rate: ShippingRate::parse(
+                &payload.kind,
+                Some(payload.amount),
+                payload.per_kg,
+                payload.threshold,
+            )
+            .map_err(|e| e.to_string())?,
+            weight: payload.weight,
+        })
+    }
+}
+
+/// A promotion applied to an order, with the fields of its kind of discount.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct PromotionPayload {
+    pub code: String,
+    pub kind: String,
+    pub value: Option<f64>,
+    pub product_id: Option<String>,
+    pub buy: Option<i32>,
+    pub get: Option<i32>,
+    pub min_spend: Option<f64>,
+}
+
+impl From<&AppliedPromotion> for PromotionPayload {
+    fn from(promotion: &AppliedPromotion) -> Self {
+        let (value, product_id, buy, get) = match &promotion.discount {
+            Discount::Percentage(value) | Discount::Fixed(value) => {
+                (Some(*value), None, None, None)
+            }
+            Discount::BuyXGetY {
+                product_id,
+                buy,
+                get,
+            } => (None, Some(product_id.0.to_string()), Some(*buy), Some(*get)),
+            Discount::FreeShipping => (None, None, None, None),
+        };
+        Self {
+            code: promotion.code.to_string(),
+            kind: promotion.discount.kind().to_string(),
+            value,
+            product_id,
+            buy,
+            get,
+            min_spend: promotion.min_spend,
+        }
+    }
+}
+
+impl TryFrom<&PromotionPayload> for AppliedPromotion {
+    type Error = PromotionError;
+
+    fn try_from(payload: &PromotionPayload) -> Result<Self, Self::Error> {
+        let product_id = payload
+            .product_id
+            .as_deref()
+            .map(Uuid::try_parse)
+            .transpose()
+            .map_err(|e| PromotionError::InvalidDiscountError(e.to_string()))?
+            .map(ProductId);
+        Ok(AppliedPromotion {
+            code: CouponCode::parse(&payload.code).map_err(PromotionError::InvalidCodeError)?,
+            discount: Discount::parse(
+                &payload.kind,
+                payload.value,
+                product_id,
+                payload.buy,
+                payload.get,
+            )?,
+            min_spend: payload.min_spend,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct CouponAppliedEvent {
+    pub order_id: String,
+    pub customer_id: String,
+    pub promotion: PromotionPayload,
+    pub pricing: PricingPayload,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct StockReservedEvent {
+    pub order_id: String,
+    pub reservation_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct PaymentAuthorizedEvent {
+    pub order_id: String,
+    pub authorization_id: String,
+    pub amount: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct OrderConfirmedEvent {
+    pub order_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct OrderCancelledEvent {
+    pub order_id: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct StockDepletedEvent {
+    pub product_id: String,
+    pub warehouse_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct PaymentFailedEvent {
+    pub order_id: String,
+    pub amount: f64,
+    pub reason: String,
+}
+
+/// Payload of every `return_*` event.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ReturnEvent {
+    pub return_id: String,
+    pub order_id: String,
+    pub state: String,
+    pub items: Vec<ReturnItemPayload>,
+    pub refund_amount: f64,
+    pub rejection_reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ReturnItemPayload {
+    pub product_id: String,
+    pub price: f64,
+    pub quantity: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct OrderShippedEvent {
+    pub shipment_id: String,
+    pub order_id: String,
+    pub carrier: String,
+    pub tracking_number: String,
+    pub items: Vec<ShipmentItemPayload>,
+    pub weight: f64,
+    pub fully_shipped: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ShipmentItemPayload {
+    pub product_id: String,
+    pub quantity: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ShipmentDeliveredEvent {
+    pub shipment_id: String,
+    pub order_id: String,
+    pub carrier: String,
+    pub tracking_number: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ReviewSubmittedEvent {
+    pub review_id: String,
+    pub product_id: String,
+    pub customer_id: String,
+    pub rating: i32,
+    pub text: String,
+    pub verified_purchase: bool,
+}
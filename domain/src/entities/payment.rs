@@ -0,0 +1,350 @@
+use crate::{
+    entities::order::{Order, OrderStatus},
+    value_objects::{AuthorizationId, OrderId, PaymentId},
+};
+
+#[derive(Debug, PartialEq)]
+pub enum PaymentError {
+    InvalidAmountError(f64),
+    OrderNotPendingError(OrderStatus),
+    OrderNotConfirmedError(OrderStatus),
+    OrderConfirmedError,
+    InvalidStateError(PaymentState),
+}
+
+impl std::fmt::Display for PaymentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaymentError::InvalidAmountError(amount) => write!(f, "Invalid amount: {}", amount),
+            PaymentError::OrderNotPendingError(status) => {
+                write!(f, "Order is {}, not pending", status)
+            }
+            PaymentError::OrderNotConfirmedError(status) => {
+                write!(f, "Order is {}, not confirmed", status)
+            }
+            PaymentError::OrderConfirmedError => write!(f, "Order is already confirmed"),
+            PaymentError::InvalidStateError(state) => write!(f, "Payment is {}", state),
+        }
+    }
+}
+
+impl std::error::Error for PaymentError {}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PaymentState {
+    /// Recorded, waiting for the payment provider.
+    Pending,
+    Authorized,
+    Captured,
+    Voided,
+    /// Captured, then given back in full.
+    Refunded,
+    /// Declined by the payment provider.
+    Failed,
+}
+
+const PENDING: &str = "pending";
+const AUTHORIZED: &str = "authorized";
+const CAPTURED: &str = "captured";
+const VOIDED: &str = "voided";
+const REFUNDED: &str = "refunded";
+const FAILED: &str = "failed";
+
+impl std::fmt::Display for PaymentState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaymentState::Pending => write!(f, "{}", PENDING),
+            PaymentState::Authorized => write!(f, "{}", AUTHORIZED),
+            PaymentState::Captured => write!(f, "{}", CAPTURED),
+            PaymentState::Voided => write!(f, "{}", VOIDED),
+            PaymentState::Refunded => write!(f, "{}", REFUNDED),
+            PaymentState::Failed => write!(f, "{}", FAILED),
+        }
+    }
+}
+
+impl std::str::FromStr for PaymentState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            PENDING => Ok(PaymentState::Pending),
+            AUTHORIZED => Ok(PaymentState::Authorized),
+            CAPTURED => Ok(PaymentState::Captured),
+            VOIDED => Ok(PaymentState::Voided),
+            REFUNDED => Ok(PaymentState::Refunded),
+            FAILED => Ok(PaymentState::Failed),
+            _ => Err(format!("Unknown payment state: {}", s)),
+        }
+    }
+}
+
+/// Part of a captured payment given back, made once per idempotency key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Refund {
+    pub idempotency_key: String,
+    pub amount: f64,
+}
+
+/// Payment of an order. It is authorized while the order is pending,
+/// captured once the order is confirmed and voided if the order is not.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Payment {
+    id: PaymentId,
+    order_id: OrderId,
+    amount: f64,
+    state: PaymentState,
+    authorization_id: Option<AuthorizationId>,
+    captured_amount: f64,
+    refunds: Vec<Refund>,
+    failure_reason: Option<String>,
+    version: i64,
+}
+
+impl Payment {
+    pub fn new(id: PaymentId, order: &Order, amount: f64) -> Result<Self, PaymentError> {
+        if order.status() != OrderStatus::Pending {
+            return Err(PaymentError::OrderNotPendingError(order.status()));
+        }
+        check_positive(amount)?;
+        Ok(Self {
+            id,
+            order_id: order.id().clone(),
+            amount,
+            state: PaymentState::Pending,
+            authorization_id: None,
+            captured_amount: 0.0,
+            refunds: vec![],
+            failure_reason: None,
+            version: 0,
+        })
+    }
+
+    /// Rebuilds a payment read from storage.
+    #[allow(clippy::too_many_arguments)]
+    pub fn restore(
+        id: PaymentId,
+        order_id: OrderId,
+        amount: f64,
+        state: PaymentState,
+        authorization_id: Option<AuthorizationId>,
+        captured_amount: f64,
+        refunds: Vec<Refund>,
+        failure_reason: Option<String>,
+        version: i64,
+    ) -> Self {
+        Self {
+            id,
+            order_id,
+            amount,
+            state,
+            authorization_id,
+            captured_amount,
+            refunds,
+            failure_reason,
+            version,
+        }
+    }
+
+    pub fn id(&self) -> &PaymentId {
+        &self.id
+    }
+
+    pub fn order_id(&self) -> &OrderId {
+        &self.order_id
+    }
+
+    /// Amount authorized.
+    pub fn amount(&self) -> f64 {
+        self.amount
+    }
+
+    pub fn state(&self) -> PaymentState {
+        self.state
+    }
+
+    /// `None` until the payment provider authorizes the payment.
+    pub fn authorization_id(&self) -> Option<&AuthorizationId> {
+        self.authorization_id.as_ref()
+    }
+
+    pub fn captured_amount(&self) -> f64 {
+        self.captured_amount
+    }
+
+    pub fn refunds(&self) -> &[Refund] {
+        &self.refunds
+    }
+
+    pub fn refunded_amount(&self) -> f64 {
+        self.refunds.iter().map(|refund| refund.amount).sum()
+    }
+
+    pub fn failure_reason(&self) -> Option<&str> {
+        self.failure_reason.as_deref()
+    }
+
+    /// Version the payment was read at, checked when it is updated. 0 until saved.
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    /// Returns the payment at the version it has been stored with.
+    pub fn with_version(self, version: i64) -> Self {
+        Self { version, ..self }
+    }
+
+    pub fn has_refund(&self, idempotency_key: &str) -> bool {
+        self.refunds
+            .iter()
+            .any(|refund| refund.idempotency_key == idempotency_key)
+    }
+
+    pub fn authorized(&mut self, authorization_id: AuthorizationId) -> Result<(), PaymentError> {
+        self.check_state(PaymentState::Pending)?;
+        self.authorization_id = Some(authorization_id);
+        self.state = PaymentState::Authorized;
+        Ok(())
+    }
+
+    pub fn failed(&mut self, reason: &str) -> Result<(), PaymentError> {
+        self.check_state(PaymentState::Pending)?;
+        self.failure_reason = Some(reason.to_string());
+        self.state = PaymentState::Failed;
+        Ok(())
+    }
+
+    /// Charges `amount`, at most the authorized one, for the confirmed order.
+    pub fn capture(&mut self, order_status: OrderStatus, amount: f64) -> Result<(), PaymentError> {
+        self.check_state(PaymentState::Authorized)?;
+        if order_status != OrderStatus::Confirmed {
+            return Err(PaymentError::OrderNotConfirmedError(order_status));
+        }
+        check_positive(amount)?;
+        if amount > self.amount {
+            return Err(PaymentError::InvalidAmountError(amount));
+        }
+        self.captured_amount = amount;
+        self.state = PaymentState::Captured;
+        Ok(())
+    }
+
+    pub fn void(&mut self, order_status: OrderStatus) -> Result<(), PaymentError> {
+        self.check_state(PaymentState::Authorized)?;
+        if order_status == OrderStatus::Confirmed {
+            return Err(PaymentError::OrderConfirmedError);
+        }
+        self.state = PaymentState::Voided;
+        Ok(())
+    }
+
+    /// Gives back `amount` of the captured one. The payment is refunded once
+    /// nothing captured is left.
+    pub fn refund(&mut self, idempotency_key: &str, amount: f64) -> Result<(), PaymentError> {
+        self.check_state(PaymentState::Captured)?;
+        check_positive(amount)?;
+        if amount > self.captured_amount - self.refunded_amount() {
+            return Err(PaymentError::InvalidAmountError(amount));
+        }
+        self.refunds.push(Refund {
+            idempotency_key: idempotency_key.to_string(),
+            amount,
+        });
+        if self.refunded_amount() >= self.captured_amount {
+            self.state = PaymentState::Refunded;
+        }
+        Ok(())
+    }
+
+    fn check_state(&self, state: PaymentState) -> Result<(), PaymentError> {
+        if self.state != state {
+            return Err(PaymentError::InvalidStateError(self.state));
+        }
+        Ok(())
+    }
+}
+
+fn check_positive(amount: f64) -> Result<(), PaymentError> {
+    if amount.is_nan() || amount <= 0.0 {
+        return Err(PaymentError::InvalidAmountError(amount));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::value_objects::CustomerId;
+
+    #[test]
+    fn pays_only_pending_orders() {
+        let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()));
+        order.place().unwrap();
+        order.cancel().unwrap();
+
+        assert_eq!(
+            Err(PaymentError::OrderNotPendingError(OrderStatus::Cancelled)),
+            Payment::new(PaymentId(Uuid::new_v4()), &order, 10.0)
+        );
+    }
+
+    #[test]
+    fn captures_an_authorized_payment_once_the_order_is_confirmed() {
+        let mut payment = authorized_payment();
+
+        assert_eq!(
+            Err(PaymentError::OrderNotConfirmedError(OrderStatus::Pending)),
+            payment.capture(OrderStatus::Pending, 10.0)
+        );
+        payment.capture(OrderStatus::Confirmed, 8.0).unwrap();
+
+        assert_eq!(PaymentState::Captured, payment.state());
+        assert_eq!(8.0, payment.captured_amount());
+        assert_eq!(
+            Err(PaymentError::InvalidStateError(PaymentState::Captured)),
+            payment.void(OrderStatus::Cancelled)
+        );
+    }
+
+    #[test]
+    fn is_refunded_once_the_captured_amount_is_given_back() {
+        let mut payment = authorized_payment();
+        payment.capture(OrderStatus::Confirmed, 10.0).unwrap();
+
+        payment.refund("first", 4.0).unwrap();
+        assert_eq!(
+            Err(PaymentError::InvalidAmountError(7.0)),
+            payment.refund("second", 7.0)
+        );
+        payment.refund("second", 6.0).unwrap();
+
+        assert_eq!(PaymentState::Refunded, payment.state());
+        assert_eq!(10.0, payment.refunded_amount());
+        assert!(payment.has_refund("first"));
+    }
+
+    #[test]
+    fn voids_an_authorization_unless_the_order_is_confirmed() {
+        let mut payment = authorized_payment();
+
+        assert_eq!(
+            Err(PaymentError::OrderConfirmedError),
+            payment.void(OrderStatus::Confirmed)
+        );
+        payment.void(OrderStatus::Cancelled).unwrap();
+
+        assert_eq!(PaymentState::Voided, payment.state());
+    }
+
+    fn authorized_payment() -> Payment {
+        let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()));
+        order.place().unwrap();
+        let mut payment = Payment::new(PaymentId(Uuid::new_v4()), &order, 10.0).unwrap();
+        payment
+            .authorized(AuthorizationId("auth".to_string()))
+            .unwrap();
+        payment
+    }
+}
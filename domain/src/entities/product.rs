@@ -1,8 +1,330 @@
-use uuid::Uuid;
+use crate::value_objects::{CatalogProductId, CategoryId, InvalidValueError, ProductId, Sku};
 
-pub struct Product {
-    pub id: Uuid,
+#[derive(Debug, PartialEq)]
+pub enum ProductError {
+    EmptyNameError,
+    InvalidSkuError(InvalidValueError),
+    InvalidPriceError(f64),
+    InvalidAttributeError(String),
+    VariantAlreadyExistsError,
+    SkuAlreadyExistsError(String),
+    VariantNotFoundError,
+}
+
+impl std::fmt::Display for ProductError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProductError::EmptyNameError => write!(f, "Product has no name"),
+            ProductError::InvalidSkuError(error) => write!(f, "Invalid SKU: {}", error),
+            ProductError::InvalidPriceError(price) => {
+                write!(f, "Invalid price {}: must not be negative", price)
+            }
+            ProductError::InvalidAttributeError(message) => {
+                write!(f, "Invalid attribute: {}", message)
+            }
+            ProductError::VariantAlreadyExistsError => {
+                write!(f, "Product has a variant with the same id")
+            }
+            ProductError::SkuAlreadyExistsError(sku) => {
+                write!(f, "Product has a variant with SKU {}", sku)
+            }
+            ProductError::VariantNotFoundError => write!(f, "Variant not found"),
+        }
+    }
+}
+
+impl std::error::Error for ProductError {}
+
+const TEXT: &str = "text";
+const NUMBER: &str = "number";
+const BOOLEAN: &str = "boolean";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttributeValue {
+    Text(String),
+    Number(f64),
+    Boolean(bool),
+}
+
+impl AttributeValue {
+    /// Reads a value of `kind` as sent by clients or stored.
+    pub fn parse(kind: &str, value: &str) -> Result<Self, ProductError> {
+        let invalid = |message: &str| ProductError::InvalidAttributeError(message.to_string());
+        let value = value.trim();
+        match kind {
+            TEXT if value.is_empty() => Err(invalid("text must not be empty")),
+            TEXT => Ok(AttributeValue::Text(value.to_string())),
+            NUMBER => value
+                .parse()
+                .ok()
+                .filter(|number: &f64| number.is_finite())
+                .map(AttributeValue::Number)
+                .ok_or_else(|| invalid("value must be a number")),
+            BOOLEAN => value
+                .parse()
+                .map(AttributeValue::Boolean)
+                .map_err(|_| invalid("value must be true or false")),
+            _ => Err(invalid(&format!("unknown kind {}", kind))),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AttributeValue::Text(_) => TEXT,
+            AttributeValue::Number(_) => NUMBER,
+            AttributeValue::Boolean(_) => BOOLEAN,
+        }
+    }
+}
+
+impl std::fmt::Display for AttributeValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttributeValue::Text(text) => write!(f, "{}", text),
+            AttributeValue::Number(number) => write!(f, "{}", number),
+            AttributeValue::Boolean(boolean) => write!(f, "{}", boolean),
+        }
+    }
+}
+
+/// A named property, like the material of a product or the size of a variant.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Attribute {
     pub name: String,
+    pub value: AttributeValue,
+}
+
+impl Attribute {
+    /// Names are trimmed and lower case.
+    pub fn new(name: &str, value: AttributeValue) -> Result<Self, ProductError> {
+        let name = name.trim().to_lowercase();
+        if name.is_empty() {
+            return Err(ProductError::InvalidAttributeError(
+                "name must not be empty".to_string(),
+            ));
+        }
+        Ok(Self { name, value })
+    }
+}
+
+/// A sellable version of a product with its own SKU and price, in the base
+/// currency. Its id is the product id of order items, stock and price lists.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Variant {
+    pub id: ProductId,
+    pub sku: Sku,
+    pub price: f64,
+    pub attributes: Vec<Attribute>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Product {
+    id: CatalogProductId,
+    name: String,
+    category_id: Option<CategoryId>,
+    attributes: Vec<Attribute>,
+    variants: Vec<Variant>,
+    version: i64,
 }
 
-impl Product {}
+impl Product {
+    /// A product without variants, in `category_id` if given.
+    pub fn new(
+        id: CatalogProductId,
+        name: &str,
+        category_id: Option<CategoryId>,
+    ) -> Result<Self, ProductError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ProductError::EmptyNameError);
+        }
+        Ok(Self {
+            id,
+            name: name.to_string(),
+            category_id,
+            attributes: vec![],
+            variants: vec![],
+            version: 0,
+        })
+    }
+
+    /// Rebuilds a product read from storage.
+    pub fn restore(
+        id: CatalogProductId,
+        name: String,
+        category_id: Option<CategoryId>,
+        attributes: Vec<Attribute>,
+        variants: Vec<Variant>,
+        version: i64,
+    ) -> Self {
+        Self {
+            id,
+            name,
+            category_id,
+            attributes,
+            variants,
+            version,
+        }
+    }
+
+    pub fn id(&self) -> &CatalogProductId {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn category_id(&self) -> Option<&CategoryId> {
+        self.category_id.as_ref()
+    }
+
+    /// Attributes shared by all the variants.
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+
+    /// Variants in the order they were added.
+    pub fn variants(&self) -> &[Variant] {
+        &self.variants
+    }
+
+    /// Version the product was read at, checked when it is updated. 0 until saved.
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    /// Returns the product at the version it has been stored with.
+    pub fn with_version(self, version: i64) -> Self {
+        Self { version, ..self }
+    }
+
+    pub fn add_variant(
+        &mut self,
+        id: ProductId,
+        sku: &str,
+        price: f64,
+    ) -> Result<(), ProductError> {
+        let sku = Sku::parse(sku).map_err(ProductError::InvalidSkuError)?;
+        if price < 0.0 || !price.is_finite() {
+            return Err(ProductError::InvalidPriceError(price));
+        }
+        if self.variants.iter().any(|variant| variant.id == id) {
+            return Err(ProductError::VariantAlreadyExistsError);
+        }
+        if self.variants.iter().any(|variant| variant.sku == sku) {
+            return Err(ProductError::SkuAlreadyExistsError(sku.to_string()));
+        }
+        self.variants.push(Variant {
+            id,
+            sku,
+            price,
+            attributes: vec![],
+        });
+        Ok(())
+    }
+
+    /// Sets an attribute of the variant, or of the product without one,
+    /// replacing the one with the same name.
+    pub fn set_attribute(
+        &mut self,
+        variant_id: Option<&ProductId>,
+        attribute: Attribute,
+    ) -> Result<(), ProductError> {
+        let attributes = match variant_id {
+            Some(variant_id) => {
+                &mut self
+                    .variants
+                    .iter_mut()
+                    .find(|variant| &variant.id == variant_id)
+                    .ok_or(ProductError::VariantNotFoundError)?
+                    .attributes
+            }
+            None => &mut self.attributes,
+        };
+        match attributes
+            .iter_mut()
+            .find(|existing| existing.name == attribute.name)
+        {
+            Some(existing) => *existing = attribute,
+            None => attributes.push(attribute),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn adds_variants_with_distinct_ids_and_skus() {
+        let mut product = product();
+        let variant_id = ProductId(Uuid::new_v4());
+        product
+            .add_variant(variant_id.clone(), "ts-red-m", 19.9)
+            .unwrap();
+
+        assert_eq!(
+            Err(ProductError::VariantAlreadyExistsError),
+            product.add_variant(variant_id, "TS-RED-L", 19.9)
+        );
+        assert_eq!(
+            Err(ProductError::SkuAlreadyExistsError("TS-RED-M".to_string())),
+            product.add_variant(ProductId(Uuid::new_v4()), "TS-RED-M", 19.9)
+        );
+        assert_eq!(
+            Err(ProductError::InvalidPriceError(-1.0)),
+            product.add_variant(ProductId(Uuid::new_v4()), "TS-RED-L", -1.0)
+        );
+        assert_eq!("TS-RED-M", product.variants()[0].sku.as_str());
+    }
+
+    #[test]
+    fn sets_typed_attributes_of_the_product_and_its_variants() {
+        let mut product = product();
+        let variant_id = ProductId(Uuid::new_v4());
+        product
+            .add_variant(variant_id.clone(), "TS-RED-M", 19.9)
+            .unwrap();
+        let attribute = |name, kind, value| {
+            Attribute::new(name, AttributeValue::parse(kind, value).unwrap()).unwrap()
+        };
+
+        product
+            .set_attribute(None, attribute("Organic", "boolean", "true"))
+            .unwrap();
+        product
+            .set_attribute(Some(&variant_id), attribute("size", "text", "S"))
+            .unwrap();
+        product
+            .set_attribute(Some(&variant_id), attribute("size", "text", "M"))
+            .unwrap();
+
+        assert_eq!(
+            vec![attribute("organic", "boolean", "true")],
+            product.attributes()
+        );
+        assert_eq!(
+            vec![attribute("size", "text", "M")],
+            product.variants()[0].attributes
+        );
+        assert_eq!(
+            Err(ProductError::VariantNotFoundError),
+            product.set_attribute(
+                Some(&ProductId(Uuid::new_v4())),
+                attribute("size", "text", "L")
+            )
+        );
+        assert!(matches!(
+            AttributeValue::parse("number", "heavy"),
+            Err(ProductError::InvalidAttributeError(_))
+        ));
+    }
+
+    fn product() -> Product {
+        Product::new(CatalogProductId(Uuid::new_v4()), "T-shirt", None).unwrap()
+    }
+}
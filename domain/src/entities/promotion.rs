@@ -0,0 +1,611 @@
+use chrono::{DateTime, Utc};
+
+use crate::{
+    entities::{
+        order::{Order, OrderStatus},
+        order_lines::OrderLines,
+        shipping_method::OrderShipping,
+        tax::{PricingMode, TaxLine, Taxes},
+    },
+    value_objects::{CouponCode, InvalidValueError, ProductId, PromotionId},
+};
+
+#[derive(Debug, PartialEq)]
+pub enum PromotionError {
+    InvalidCodeError(InvalidValueError),
+    InvalidDiscountError(String),
+    InvalidConditionsError(String),
+    NotStartedError,
+    ExpiredError,
+    MinSpendNotReachedError(f64),
+    UsageLimitReachedError,
+    ProductNotInOrderError(ProductId),
+    OrderNotDraftError(OrderStatus),
+    AlreadyAppliedError(CouponCode),
+}
+
+impl std::fmt::Display for PromotionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PromotionError::InvalidCodeError(error) => write!(f, "Coupon code {}", error),
+            PromotionError::InvalidDiscountError(message) => {
+                write!(f, "Invalid discount: {}", message)
+            }
+            PromotionError::InvalidConditionsError(message) => {
+                write!(f, "Invalid conditions: {}", message)
+            }
+            PromotionError::NotStartedError => write!(f, "Promotion has not started yet"),
+            PromotionError::ExpiredError => write!(f, "Promotion has expired"),
+            PromotionError::MinSpendNotReachedError(min_spend) => {
+                write!(f, "Order must be at least {}", min_spend)
+            }
+            PromotionError::UsageLimitReachedError => {
+                write!(f, "Promotion already used as many times as allowed")
+            }
+            PromotionError::ProductNotInOrderError(product_id) => {
+                write!(f, "Product {} is not in the order", product_id.0)
+            }
+            PromotionError::OrderNotDraftError(status) => {
+                write!(f, "Order is {}, not a draft", status)
+            }
+            PromotionError::AlreadyAppliedError(code) => {
+                write!(f, "Coupon {} already applied to the order", code)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PromotionError {}
+
+const PERCENTAGE: &str = "percentage";
+const FIXED: &str = "fixed";
+const BUY_X_GET_Y: &str = "buy_x_get_y";
+const FREE_SHIPPING: &str = "free_shipping";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Discount {
+    /// Percentage of the subtotal, up to 100.
+    Percentage(f64),
+    /// Amount off the subtotal.
+    Fixed(f64),
+    /// For every `buy` units of the product, `get` more are free.
+    BuyXGetY {
+        product_id: ProductId,
+        buy: i32,
+        get: i32,
+    },
+    /// Takes nothing off the items; shipping is not charged.
+    FreeShipping,
+}
+
+impl Discount {
+    /// Builds a discount of `kind` from the fields it needs, as sent by
+    /// clients or stored. Fields the kind does not need are ignored.
+    pub fn parse(
+        kind: &str,
+        value: Option<f64>,
+        product_id: Option<ProductId>,
+        buy: Option<i32>,
+        get: Option<i32>,
+    ) -> Result<Self, PromotionError> {
+        let invalid = |message: &str| PromotionError::InvalidDiscountError(message.to_string());
+        match kind {
+            PERCENTAGE => match value {
+                Some(value) if value > 0.0 && value <= 100.0 => Ok(Discount::Percentage(value)),
+                _ => Err(invalid("percentage must be between 0 and 100")),
+            },
+            FIXED => match value {
+                Some(value) if value > 0.0 && value.is_finite() => Ok(Discount::Fixed(value)),
+                _ => Err(invalid("amount must be positive")),
+            },
+            BUY_X_GET_Y => match (product_id, buy, get) {
+                (Some(product_id), Some(buy), Some(get)) if buy > 0 && get > 0 => {
+                    Ok(Discount::BuyXGetY {
+                        product_id,
+                        buy,
+                        get,
+                    })
+                }
+                _ => Err(invalid(
+                    "a product and positive quantities to buy and get are required",
+                )),
+            },
+            FREE_SHIPPING => Ok(Discount::FreeShipping),
+            _ => Err(invalid(&format!("unknown kind {}", kind))),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Discount::Percentage(_) => PERCENTAGE,
+            Discount::Fixed(_) => FIXED,
+            Discount::BuyXGetY { .. } => BUY_X_GET_Y,
+            Discount::FreeShipping => FREE_SHIPPING,
+        }
+    }
+
+    /// The product a buy-X-get-Y discount is for.
+    pub fn product_id(&self) -> Option<&ProductId> {
+        match self {
+            Discount::BuyXGetY { product_id, .. } => Some(product_id),
+            _ => None,
+        }
+    }
+
+    /// Amount taken off the items, rounded to cents.
+    fn amount(&self, order_lines: &OrderLines) -> f64 {
+        let amount = match self {
+            Discount::Percentage(percentage) => order_lines.total_price() * percentage / 100.0,
+            Discount::Fixed(amount) => *amount,
+            Discount::BuyXGetY {
+                product_id,
+                buy,
+                get,
+            } => order_lines.find(product_id).map_or(0.0, |item| {
+                let free_units = item.quantity / (buy + get) * get;
+                item.price * free_units as f64
+            }),
+            Discount::FreeShipping => 0.0,
+        };
+        round_to_cents(amount)
+    }
+}
+
+/// A promotion as applied to an order, kept there as it was even if the
+/// promotion changes later.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AppliedPromotion {
+    pub code: CouponCode,
+    pub discount: Discount,
+    /// Subtotal the order must keep for the discount to be given.
+    pub min_spend: Option<f64>,
+}
+
+impl AppliedPromotion {
+    fn holds(&self, subtotal: f64) -> bool {
+        self.min_spend.is_none_or(|min_spend| subtotal >= min_spend)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AppliedDiscount {
+    pub code: CouponCode,
+    pub amount: f64,
+}
+
+/// Price of an order: the sum of its items, less the discounts of its
+/// promotions, plus taxes unless the prices include them, plus shipping.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pricing {
+    pub subtotal: f64,
+    pub discounts: Vec<AppliedDiscount>,
+    pub pricing_mode: PricingMode,
+    pub tax_lines: Vec<TaxLine>,
+    pub tax_total: f64,
+    /// Cost of the shipping method of the order, 0 if it has none or a
+    /// promotion makes shipping free.
+    pub shipping: f64,
+    pub total: f64,
+    pub free_shipping: bool,
+}
+
+impl Pricing {
+    /// Discounts are computed on the current items, in the order the
+    /// promotions were applied, and never take the total below zero. A
+    /// promotion whose minimum spend the items no longer reach gives nothing.
+    /// Taxes are computed on the discounted items, and so is shipping, which
+    /// is not taxed.
+    pub fn new(
+        order_lines: &OrderLines,
+        promotions: &[AppliedPromotion],
+        taxes: &Taxes,
+        shipping: Option<&OrderShipping>,
+    ) -> Self {
+        let subtotal = order_lines.total_price();
+        let mut total = subtotal;
+        let discounts = promotions
+            .iter()
+            .map(|promotion| {
+                let amount = if promotion.holds(subtotal) {
+                    promotion.discount.amount(order_lines).min(total)
+                } else {
+                    0.0
+                };
+                total = round_to_cents(total - amount);
+                AppliedDiscount {
+                    code: promotion.code.clone(),
+                    amount,
+                }
+            })
+            .collect();
+        let free_shipping = promotions.iter().any(|promotion| {
+            promotion.discount == Discount::FreeShipping && promotion.holds(subtotal)
+        });
+        let shipping = match shipping {
+            Some(shipping) if !free_shipping => shipping.cost(total),
+            _ => 0.0,
+        };
+        let tax_lines = taxes.lines(order_lines, total);
+        let tax_total = round_to_cents(tax_lines.iter().map(|line| line.amount).sum());
+        if taxes.pricing_mode == PricingMode::Exclusive {
+            total = round_to_cents(total + tax_total);
+        }
+        total = round_to_cents(total + shipping);
+        Self {
+            subtotal,
+            discounts,
+            pricing_mode: taxes.pricing_mode,
+            tax_lines,
+            tax_total,
+            shipping,
+            total,
+            free_shipping,
+        }
+    }
+
+    pub fn discount_total(&self) -> f64 {
+        round_to_cents(self.discounts.iter().map(|discount| discount.amount).sum())
+    }
+
+    /// What was paid for `items`, some of the `order_lines` priced: their
+    /// share of the discounts is taken off and, when prices don't include
+    /// taxes, their share of the tax lines added.
+    pub fn paid_for(&self, order_lines: &OrderLines, items: &OrderLines) -> f64 {
+        let share = if self.subtotal > 0.0 {
+            (self.subtotal - self.discount_total()) / self.subtotal
+        } else {
+            0.0
+        };
+        let paid = items
+            .iter()
+            .map(|item| {
+                let price = item.price * item.quantity as f64 * share;
+                let tax = match (self.pricing_mode, order_lines.find(&item.product_id)) {
+                    (PricingMode::Exclusive, Some(ordered)) => self
+                        .tax_lines
+                        .iter()
+                        .find(|line| line.product_id == item.product_id)
+                        .map_or(0.0, |line| {
+                            line.amount * item.quantity as f64 / ordered.quantity as f64
+                        }),
+                    _ => 0.0,
+                };
+                price + tax
+            })
+            .sum();
+        round_to_cents(paid)
+    }
+}
+
+/// When and how often a promotion can be used. Unset conditions don't apply.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PromotionConditions {
+    /// Subtotal the order must reach.
+    pub min_spend: Option<f64>,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+    /// Orders of a customer the promotion can be applied to.
+    pub max_uses_per_customer: Option<i64>,
+}
+
+/// Discount customers get by entering its coupon code on an order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Promotion {
+    id: PromotionId,
+    code: CouponCode,
+    discount: Discount,
+    conditions: PromotionConditions,
+}
+
+impl Promotion {
+    pub fn new(
+        id: PromotionId,
+        code: CouponCode,
+        discount: Discount,
+        conditions: PromotionConditions,
+    ) -> Result<Self, PromotionError> {
+        let invalid = |message: &str| PromotionError::InvalidConditionsError(message.to_string());
+        if conditions
+            .min_spend
+            .is_some_and(|min_spend| min_spend.is_nan() || min_spend < 0.0)
+        {
+            return Err(invalid("minimum spend must not be negative"));
+        }
+        if let (Some(valid_from), Some(valid_until)) =
+            (conditions.valid_from, conditions.valid_until)
+        {
+            if valid_from >= valid_until {
+                return Err(invalid("validity must end after it starts"));
+            }
+        }
+        if conditions
+            .max_uses_per_customer
+            .is_some_and(|uses| uses < 1)
+        {
+            return Err(invalid("uses per customer must be at least 1"));
+        }
+        Ok(Self {
+            id,
+            code,
+            discount,
+            conditions,
+        })
+    }
+
+    pub fn id(&self) -> &PromotionId {
+        &self.id
+    }
+
+    pub fn code(&self) -> &CouponCode {
+        &self.code
+    }
+
+    pub fn discount(&self) -> &Discount {
+        &self.discount
+    }
+
+    pub fn conditions(&self) -> &PromotionConditions {
+        &self.conditions
+    }
+
+    /// Applies the promotion to the order if its conditions are met.
+    /// `uses` is the number of orders of the customer it was applied to.
+    pub fn apply_to(
+        &self,
+        order: &mut Order,
+        now: DateTime<Utc>,
+        uses: i64,
+    ) -> Result<(), PromotionError> {
+        if self.conditions.valid_from.is_some_and(|from| now < from) {
+            return Err(PromotionError::NotStartedError);
+        }
+        if self
+            .conditions
+            .valid_until
+            .is_some_and(|until| now >= until)
+        {
+            return Err(PromotionError::ExpiredError);
+        }
+        if self
+            .conditions
+            .max_uses_per_customer
+            .is_some_and(|max_uses| uses >= max_uses)
+        {
+            return Err(PromotionError::UsageLimitReachedError);
+        }
+        if let Some(min_spend) = self.conditions.min_spend {
+            if order.order_lines().total_price() < min_spend {
+                return Err(PromotionError::MinSpendNotReachedError(min_spend));
+            }
+        }
+        if let Discount::BuyXGetY { product_id, .. } = &self.discount {
+            if order.order_lines().find(product_id).is_none() {
+                return Err(PromotionError::ProductNotInOrderError(product_id.clone()));
+            }
+        }
+        order.apply_promotion(AppliedPromotion {
+            code: self.code.clone(),
+            discount: self.discount.clone(),
+            min_spend: self.conditions.min_spend,
+        })
+    }
+}
+
+fn round_to_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Duration;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        entities::shipping_method::{ShippingMethod, ShippingRate},
+        value_objects::{CustomerId, OrderId, OrderItem, ShippingMethodId},
+    };
+
+    #[test]
+    fn prices_an_order_with_its_discounts() {
+        let mut order = order();
+        order
+            .apply_promotion(applied("TEN", Discount::Percentage(10.0)))
+            .unwrap();
+        order
+            .apply_promotion(applied(
+                "THREE-FOR-TWO",
+                Discount::BuyXGetY {
+                    product_id: product_id(1),
+                    buy: 2,
+                    get: 1,
+                },
+            ))
+            .unwrap();
+        order
+            .apply_promotion(applied("SHIP", Discount::FreeShipping))
+            .unwrap();
+
+        let pricing = order.pricing();
+
+        assert_eq!(35.5, pricing.subtotal);
+        assert_eq!(
+            vec![3.55, 10.0, 0.0],
+            pricing
+                .discounts
+                .iter()
+                .map(|discount| discount.amount)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(21.95, pricing.total);
+        assert_eq!(13.55, pricing.discount_total());
+        assert!(pricing.free_shipping);
+        assert_eq!(21.95, order.total_price());
+    }
+
+    #[test]
+    fn never_discounts_below_zero() {
+        let mut order = order();
+
+        order
+            .apply_promotion(applied("FIFTY", Discount::Fixed(50.0)))
+            .unwrap();
+
+        assert_eq!(0.0, order.pricing().total);
+        assert_eq!(35.5, order.pricing().discount_total());
+        assert_eq!(
+            Err(PromotionError::AlreadyAppliedError(code("FIFTY"))),
+            order.apply_promotion(applied("FIFTY", Discount::Fixed(50.0)))
+        );
+    }
+
+    #[test]
+    fn adds_shipping_to_the_total_unless_a_promotion_makes_it_free() {
+        let shipping = OrderShipping::new(
+            &ShippingMethod::new(
+                ShippingMethodId(Uuid::new_v4()),
+                "Standard",
+                "UPS",
+                ShippingRate::FreeOver {
+                    amount: 4.9,
+                    threshold: 35.0,
+                },
+            )
+            .unwrap(),
+            None,
+        )
+        .unwrap();
+        let mut order = order().with_shipping(shipping);
+        order
+            .apply_promotion(applied("FIVE", Discount::Fixed(5.0)))
+            .unwrap();
+
+        assert_eq!(4.9, order.pricing().shipping);
+        assert_eq!(35.4, order.total_price());
+
+        order
+            .apply_promotion(applied("SHIP", Discount::FreeShipping))
+            .unwrap();
+
+        assert_eq!(0.0, order.pricing().shipping);
+        assert_eq!(30.5, order.total_price());
+    }
+
+    #[test]
+    fn applies_a_promotion_only_if_its_conditions_are_met() {
+        let now = Utc::now();
+        let promotion = Promotion::new(
+            PromotionId(Uuid::new_v4()),
+            code("WELCOME"),
+            Discount::Fixed(5.0),
+            PromotionConditions {
+                min_spend: Some(50.0),
+                valid_from: Some(now - Duration::days(1)),
+                valid_until: Some(now + Duration::days(1)),
+                max_uses_per_customer: Some(1),
+            },
+        )
+        .unwrap();
+        let mut order = order();
+
+        assert_eq!(
+            Err(PromotionError::ExpiredError),
+            promotion.apply_to(&mut order, now + Duration::days(1), 0)
+        );
+        assert_eq!(
+            Err(PromotionError::UsageLimitReachedError),
+            promotion.apply_to(&mut order, now, 1)
+        );
+        assert_eq!(
+            Err(PromotionError::MinSpendNotReachedError(50.0)),
+            promotion.apply_to(&mut order, now, 0)
+        );
+        order.add(order_item(1, 2)).unwrap();
+        promotion.apply_to(&mut order, now, 0).unwrap();
+
+        assert_eq!(55.5 - 5.0, order.total_price());
+    }
+
+    #[test]
+    fn gives_no_discount_once_the_order_is_below_the_minimum_spend() {
+        let mut order = order();
+        order
+            .apply_promotion(AppliedPromotion {
+                min_spend: Some(30.0),
+                ..applied("TEN", Discount::Fixed(10.0))
+            })
+            .unwrap();
+        order
+            .apply_promotion(AppliedPromotion {
+                min_spend: Some(30.0),
+                ..applied("SHIP", Discount::FreeShipping)
+            })
+            .unwrap();
+        assert_eq!(25.5, order.total_price());
+
+        order.change_quantity(&product_id(1), 2).unwrap();
+
+        let pricing = order.pricing();
+        assert_eq!(0.0, pricing.discount_total());
+        assert!(!pricing.free_shipping);
+        assert_eq!(25.5, pricing.total);
+        order.change_quantity(&product_id(1), 3).unwrap();
+        assert_eq!(25.5, order.total_price());
+    }
+
+    #[test]
+    fn parses_discounts_of_each_kind() {
+        assert_eq!(
+            Ok(Discount::Percentage(15.0)),
+            Discount::parse("percentage", Some(15.0), None, None, None)
+        );
+        assert!(Discount::parse("percentage", Some(150.0), None, None, None).is_err());
+        assert!(Discount::parse("fixed", None, None, None, None).is_err());
+        assert!(Discount::parse("buy_x_get_y", None, Some(product_id(1)), Some(2), None).is_err());
+        assert_eq!(
+            Ok(Discount::FreeShipping),
+            Discount::parse("free_shipping", Some(1.0), None, None, None)
+        );
+        assert!(Discount::parse("cashback", None, None, None, None).is_err());
+    }
+
+    /// Three units of product 1 at 10.0 and one of product 2 at 5.5.
+    fn order() -> Order {
+        let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()));
+        order
+            .add_multiple(vec![
+                order_item(1, 3),
+                OrderItem {
+                    price: 5.5,
+                    quantity: 1,
+                    product_id: product_id(2),
+                },
+            ])
+            .unwrap();
+        order
+    }
+
+    fn order_item(product: u128, quantity: i32) -> OrderItem {
+        OrderItem {
+            price: 10.0,
+            quantity,
+            product_id: product_id(product),
+        }
+    }
+
+    fn applied(code_value: &str, discount: Discount) -> AppliedPromotion {
+        AppliedPromotion {
+            code: code(code_value),
+            discount,
+            min_spend: None,
+        }
+    }
+
+    fn code(value: &str) -> CouponCode {
+        CouponCode::parse(value).unwrap()
+    }
+
+    fn product_id(n: u128) -> ProductId {
+        ProductId(Uuid::from_u128(n))
+    }
+}
@@ -0,0 +1,419 @@
+use crate::{
+    entities::{
+        order::{Order, OrderStatus},
+        order_lines::{OrderLines, OrderLinesError},
+        shipment::{Shipment, ShipmentState},
+    },
+    value_objects::{OrderId, OrderItem, ProductId, ReturnId},
+};
+
+#[derive(Debug, PartialEq)]
+pub enum ReturnError {
+    OrderNotConfirmedError(OrderStatus),
+    OrderNotDeliveredError,
+    ReturnOfAnotherOrderError,
+    ProductNotInOrderError(ProductId),
+    ReturnedQuantityExceededError(ProductId),
+    EmptyReturnError,
+    InvalidStateError(ReturnState),
+    InvalidReturnLinesError(OrderLinesError),
+}
+
+impl std::fmt::Display for ReturnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReturnError::OrderNotConfirmedError(status) => {
+                write!(f, "Order is {}, not confirmed", status)
+            }
+            ReturnError::OrderNotDeliveredError => write!(f, "Order is not delivered yet"),
+            ReturnError::ReturnOfAnotherOrderError => {
+                write!(f, "Return belongs to another order")
+            }
+            ReturnError::ProductNotInOrderError(product_id) => {
+                write!(f, "Product {} is not in the order", product_id.0)
+            }
+            ReturnError::ReturnedQuantityExceededError(product_id) => write!(
+                f,
+                "More units of product {} returned than ordered",
+                product_id.0
+            ),
+            ReturnError::EmptyReturnError => write!(f, "Return has no items"),
+            ReturnError::InvalidStateError(state) => write!(f, "Return is {}", state),
+            ReturnError::InvalidReturnLinesError(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for ReturnError {}
+
+impl From<OrderLinesError> for ReturnError {
+    fn from(error: OrderLinesError) -> Self {
+        ReturnError::InvalidReturnLinesError(error)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReturnState {
+    /// Asked by the customer, items can still be added.
+    Requested,
+    /// Accepted by the staff, waiting for the items.
+    Approved,
+    Rejected,
+    /// Items back in the warehouse, waiting for the refund.
+    Received,
+    Refunded,
+}
+
+const REQUESTED: &str = "requested";
+const APPROVED: &str = "approved";
+const REJECTED: &str = "rejected";
+const RECEIVED: &str = "received";
+const REFUNDED: &str = "refunded";
+
+impl std::fmt::Display for ReturnState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReturnState::Requested => write!(f, "{}", REQUESTED),
+            ReturnState::Approved => write!(f, "{}", APPROVED),
+            ReturnState::Rejected => write!(f, "{}", REJECTED),
+            ReturnState::Received => write!(f, "{}", RECEIVED),
+            ReturnState::Refunded => write!(f, "{}", REFUNDED),
+        }
+    }
+}
+
+impl std::str::FromStr for ReturnState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            REQUESTED => Ok(ReturnState::Requested),
+            APPROVED => Ok(ReturnState::Approved),
+            REJECTED => Ok(ReturnState::Rejected),
+            RECEIVED => Ok(ReturnState::Received),
+            REFUNDED => Ok(ReturnState::Refunded),
+            _ => Err(format!("Unknown return state: {}", s)),
+        }
+    }
+}
+
+/// Items of a confirmed order sent back by the customer, priced as in the
+/// order and refunded at what was paid for them. Several returns can be made
+/// for an order, as long as no more units of a product are returned than
+/// ordered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Return {
+    id: ReturnId,
+    order_id: OrderId,
+    items: OrderLines,
+    reason: String,
+    state: ReturnState,
+    rejection_reason: Option<String>,
+    refund_amount: f64,
+    version: i64,
+}
+
+impl Return {
+    /// Starts a return of a confirmed order, once one of its `shipments` has
+    /// been delivered.
+    pub fn request(
+        id: ReturnId,
+        order: &Order,
+        shipments: &[Shipment],
+        reason: &str,
+    ) -> Result<Self, ReturnError> {
+        if order.status() != OrderStatus::Confirmed {
+            return Err(ReturnError::OrderNotConfirmedError(order.status()));
+        }
+        if !shipments.iter().any(|shipment| {
+            shipment.order_id() == order.id() && shipment.state() == ShipmentState::Delivered
+        }) {
+            return Err(ReturnError::OrderNotDeliveredError);
+        }
+        Ok(Self {
+            id,
+            order_id: order.id().clone(),
+            items: OrderLines::new(),
+            reason: reason.trim().to_string(),
+            state: ReturnState::Requested,
+            rejection_reason: None,
+            refund_amount: 0.0,
+            version: 0,
+        })
+    }
+
+    /// Rebuilds a return read from storage.
+    #[allow(clippy::too_many_arguments)]
+    pub fn restore(
+        id: ReturnId,
+        order_id: OrderId,
+        items: OrderLines,
+        reason: String,
+        state: ReturnState,
+        rejection_reason: Option<String>,
+        refund_amount: f64,
+        version: i64,
+    ) -> Self {
+        Self {
+            id,
+            order_id,
+            items,
+            reason,
+            state,
+            rejection_reason,
+            refund_amount,
+            version,
+        }
+    }
+
+    pub fn id(&self) -> &ReturnId {
+        &self.id
+    }
+
+    pub fn order_id(&self) -> &OrderId {
+        &self.order_id
+    }
+
+    pub fn items(&self) -> &OrderLines {
+        &self.items
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    pub fn state(&self) -> ReturnState {
+        self.state
+    }
+
+    pub fn rejection_reason(&self) -> Option<&str> {
+        self.rejection_reason.as_deref()
+    }
+
+    /// Amount given back to the customer: what was paid for the items,
+    /// after the discounts of the order and with taxes added to its prices.
+    pub fn refund_amount(&self) -> f64 {
+        self.refund_amount
+    }
+
+    /// Version the return was read at, checked when it is updated. 0 until saved.
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    /// Returns the return at the version it has been stored with.
+    pub fn with_version(self, version: i64) -> Self {
+        Self { version, ..self }
+    }
+
+    /// Adds units of a product of the order, or more units of a product
+    /// already in the return. `other_returns` are the other returns of the
+    /// order: units they take back, unless rejected, can't be returned again.
+    pub fn add(
+        &mut self,
+        order: &Order,
+        other_returns: &[Return],
+        product_id: ProductId,
+        quantity: i32,
+    ) -> Result<(), ReturnError> {
+        self.check_state(ReturnState::Requested)?;
+        if order.id() != &self.order_id {
+            return Err(ReturnError::ReturnOfAnotherOrderError);
+        }
+        let ordered = order
+            .order_lines()
+            .find(&product_id)
+            .ok_or_else(|| ReturnError::ProductNotInOrderError(product_id.clone()))?;
+
+        let returned: i32 = other_returns
+            .iter()
+            .filter(|other| other.id != self.id && other.state != ReturnState::Rejected)
+            .chain(std::iter::once(&*self))
+            .filter_map(|other| other.items.find(&product_id))
+            .map(|item| item.quantity)
+            .sum();
+        if returned.saturating_add(quantity) > ordered.quantity {
+            return Err(ReturnError::ReturnedQuantityExceededError(product_id));
+        }
+
+        self.items.add(OrderItem {
+            price: ordered.price,
+            quantity,
+            product_id,
+        })?;
+        self.refund_amount = order.pricing().paid_for(order.order_lines(), &self.items);
+        Ok(())
+    }
+
+    pub fn approve(&mut self) -> Result<(), ReturnError> {
+        self.check_state(ReturnState::Requested)?;
+        if self.items.is_empty() {
+            return Err(ReturnError::EmptyReturnError);
+        }
+        self.state = ReturnState::Approved;
+        Ok(())
+    }
+
+    pub fn reject(&mut self, reason: &str) -> Result<(), ReturnError> {
+        self.check_state(ReturnState::Requested)?;
+        self.rejection_reason = Some(reason.trim().to_string());
+        self.state = ReturnState::Rejected;
+        Ok(())
+    }
+
+    pub fn receive(&mut self) -> Result<(), ReturnError> {
+        self.check_state(ReturnState::Approved)?;
+        self.state = ReturnState::Received;
+        Ok(())
+    }
+
+    pub fn refunded(&mut self) -> Result<(), ReturnError> {
+        self.check_state(ReturnState::Received)?;
+        self.state = ReturnState::Refunded;
+        Ok(())
+    }
+
+    fn check_state(&self, state: ReturnState) -> Result<(), ReturnError> {
+        if self.state != state {
+            return Err(ReturnError::InvalidStateError(self.state));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        entities::{
+            promotion::{AppliedPromotion, Discount},
+            tax::{PricingMode, TaxCategory, TaxRate, Taxes},
+        },
+        fixtures::{order, order_with_id, product_id, shipment},
+        value_objects::CouponCode,
+    };
+
+    #[test]
+    fn returns_only_items_of_confirmed_orders() {
+        let order = order(OrderStatus::Pending);
+
+        assert_eq!(
+            Err(ReturnError::OrderNotConfirmedError(OrderStatus::Pending)),
+            Return::request(ReturnId(Uuid::new_v4()), &order, &[], "Too small")
+        );
+    }
+
+    #[test]
+    fn returns_only_items_of_delivered_orders() {
+        let other_order = order_with_id(OrderId(Uuid::new_v4()), OrderStatus::Confirmed);
+        let order = order(OrderStatus::Confirmed);
+
+        assert_eq!(
+            Err(ReturnError::OrderNotDeliveredError),
+            Return::request(ReturnId(Uuid::new_v4()), &order, &[], "")
+        );
+        assert_eq!(
+            Err(ReturnError::OrderNotDeliveredError),
+            Return::request(
+                ReturnId(Uuid::new_v4()),
+                &order,
+                &delivered(&other_order),
+                ""
+            )
+        );
+    }
+
+    #[test]
+    fn refunds_returned_items_at_the_order_price() {
+        let order = order(OrderStatus::Confirmed);
+        let mut order_return =
+            Return::request(ReturnId(Uuid::new_v4()), &order, &delivered(&order), "").unwrap();
+
+        order_return.add(&order, &[], product_id(1), 2).unwrap();
+        order_return.add(&order, &[], product_id(2), 1).unwrap();
+
+        assert_eq!(2.0 * 10.0 + 5.5, order_return.refund_amount());
+        assert_eq!(
+            Err(ReturnError::ProductNotInOrderError(product_id(3))),
+            order_return.add(&order, &[], product_id(3), 1)
+        );
+    }
+
+    #[test]
+    fn refunds_returned_items_less_their_share_of_the_discounts() {
+        let order = order(OrderStatus::Confirmed).with_promotions(vec![AppliedPromotion {
+            code: CouponCode::parse("SAVE20").unwrap(),
+            discount: Discount::Fixed(7.1),
+            min_spend: None,
+        }]);
+        let mut order_return =
+            Return::request(ReturnId(Uuid::new_v4()), &order, &delivered(&order), "").unwrap();
+
+        order_return.add(&order, &[], product_id(1), 2).unwrap();
+
+        assert_eq!(28.4, order.total_price());
+        assert_eq!(2.0 * 10.0 * 0.8, order_return.refund_amount());
+    }
+
+    #[test]
+    fn refunds_returned_items_with_the_taxes_added_to_their_prices() {
+        let order = order(OrderStatus::Confirmed).with_taxes(Taxes {
+            pricing_mode: PricingMode::Exclusive,
+            rates: vec![TaxRate {
+                product_id: product_id(1),
+                category: TaxCategory::Standard,
+                rate: 22.0,
+            }],
+        });
+        let mut order_return =
+            Return::request(ReturnId(Uuid::new_v4()), &order, &delivered(&order), "").unwrap();
+
+        order_return.add(&order, &[], product_id(1), 2).unwrap();
+        order_return.add(&order, &[], product_id(2), 1).unwrap();
+
+        assert_eq!(2.0 * 10.0 * 1.22 + 5.5, order_return.refund_amount());
+    }
+
+    #[test]
+    fn cannot_return_more_units_than_ordered() {
+        let order = order(OrderStatus::Confirmed);
+        let mut first =
+            Return::request(ReturnId(Uuid::new_v4()), &order, &delivered(&order), "").unwrap();
+        first.add(&order, &[], product_id(1), 2).unwrap();
+        let mut second =
+            Return::request(ReturnId(Uuid::new_v4()), &order, &delivered(&order), "").unwrap();
+
+        assert_eq!(
+            Err(ReturnError::ReturnedQuantityExceededError(product_id(1))),
+            second.add(&order, &[first.clone()], product_id(1), 2)
+        );
+        first.reject("Worn").unwrap();
+        second.add(&order, &[first], product_id(1), 3).unwrap();
+    }
+
+    #[test]
+    fn is_refunded_after_being_approved_and_received() {
+        let order = order(OrderStatus::Confirmed);
+        let mut order_return =
+            Return::request(ReturnId(Uuid::new_v4()), &order, &delivered(&order), "").unwrap();
+        assert_eq!(Err(ReturnError::EmptyReturnError), order_return.approve());
+        order_return.add(&order, &[], product_id(1), 1).unwrap();
+
+        order_return.approve().unwrap();
+        assert_eq!(
+            Err(ReturnError::InvalidStateError(ReturnState::Approved)),
+            order_return.refunded()
+        );
+        order_return.receive().unwrap();
+        order_return.refunded().unwrap();
+
+        assert_eq!(ReturnState::Refunded, order_return.state());
+    }
+
+    /// A shipment of the order handed to the customer.
+    fn delivered(order: &Order) -> Vec<Shipment> {
+        vec![shipment(order.id().clone(), ShipmentState::Delivered)]
+    }
+}
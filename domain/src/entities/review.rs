@@ -0,0 +1,307 @@
+use chrono::{DateTime, Utc};
+
+use crate::{
+    entities::{
+        order::{Order, OrderStatus},
+        product::Product,
+    },
+    value_objects::{CatalogProductId, CustomerId, ReviewId},
+};
+
+/// Longest review text, in characters.
+pub const MAX_TEXT_LENGTH: usize = 2000;
+
+#[derive(Debug, PartialEq)]
+pub enum ReviewError {
+    InvalidRatingError(i32),
+    EmptyTextError,
+    TextTooLongError,
+    AlreadyModeratedError(ReviewStatus),
+}
+
+impl std::fmt::Display for ReviewError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReviewError::InvalidRatingError(rating) => {
+                write!(f, "Rating {} is not between 1 and 5", rating)
+            }
+            ReviewError::EmptyTextError => write!(f, "Review has no text"),
+            ReviewError::TextTooLongError => {
+                write!(f, "Review is over {} characters", MAX_TEXT_LENGTH)
+            }
+            ReviewError::AlreadyModeratedError(status) => {
+                write!(f, "Review is {}, not pending", status)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReviewError {}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReviewStatus {
+    /// Submitted, not shown until approved.
+    Pending,
+    Approved,
+    Rejected,
+}
+
+const PENDING: &str = "pending";
+const APPROVED: &str = "approved";
+const REJECTED: &str = "rejected";
+
+impl std::fmt::Display for ReviewStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReviewStatus::Pending => write!(f, "{}", PENDING),
+            ReviewStatus::Approved => write!(f, "{}", APPROVED),
+            ReviewStatus::Rejected => write!(f, "{}", REJECTED),
+        }
+    }
+}
+
+impl std::str::FromStr for ReviewStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            PENDING => Ok(ReviewStatus::Pending),
+            APPROVED => Ok(ReviewStatus::Approved),
+            REJECTED => Ok(ReviewStatus::Rejected),
+            _ => Err(format!("Unknown review status: {}", s)),
+        }
+    }
+}
+
+/// Average of the approved reviews of a product, `None` without any.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Rating {
+    pub average: Option<f64>,
+    pub count: i64,
+}
+
+/// A customer's rating of a catalog product, from 1 to 5 stars, shown once
+/// approved by a moderator.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Review {
+    id: ReviewId,
+    product_id: CatalogProductId,
+    customer_id: CustomerId,
+    rating: i32,
+    text: String,
+    verified_purchase: bool,
+    status: ReviewStatus,
+    created_at: DateTime<Utc>,
+    version: i64,
+}
+
+impl Review {
+    /// A pending review, a verified purchase if one of the customer's
+    /// `orders` is confirmed with a variant of the product in it.
+    pub fn submit(
+        id: ReviewId,
+        product: &Product,
+        customer_id: CustomerId,
+        rating: i32,
+        text: &str,
+        orders: &[Order],
+        now: DateTime<Utc>,
+    ) -> Result<Self, ReviewError> {
+        if !(1..=5).contains(&rating) {
+            return Err(ReviewError::InvalidRatingError(rating));
+        }
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(ReviewError::EmptyTextError);
+        }
+        if text.chars().count() > MAX_TEXT_LENGTH {
+            return Err(ReviewError::TextTooLongError);
+        }
+        let verified_purchase = orders.iter().any(|order| {
+            order.customer_id() == &customer_id
+                && order.status() == OrderStatus::Confirmed
+                && order.order_items().iter().any(|item| {
+                    product
+                        .variants()
+                        .iter()
+                        .any(|variant| variant.id == item.product_id)
+                })
+        });
+        Ok(Self {
+            id,
+            product_id: product.id().clone(),
+            customer_id,
+            rating,
+            text: text.to_string(),
+            verified_purchase,
+            status: ReviewStatus::Pending,
+            created_at: now,
+            version: 0,
+        })
+    }
+
+    /// Rebuilds a review read from storage.
+    #[allow(clippy::too_many_arguments)]
+    pub fn restore(
+        id: ReviewId,
+        product_id: CatalogProductId,
+        customer_id: CustomerId,
+        rating: i32,
+        text: String,
+        verified_purchase: bool,
+        status: ReviewStatus,
+        created_at: DateTime<Utc>,
+        version: i64,
+    ) -> Self {
+        Self {
+            id,
+            product_id,
+            customer_id,
+            rating,
+            text,
+            verified_purchase,
+            status,
+            created_at,
+            version,
+        }
+    }
+
+    pub fn id(&self) -> &ReviewId {
+        &self.id
+    }
+
+    pub fn product_id(&self) -> &CatalogProductId {
+        &self.product_id
+    }
+
+    pub fn customer_id(&self) -> &CustomerId {
+        &self.customer_id
+    }
+
+    pub fn rating(&self) -> i32 {
+        self.rating
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn verified_purchase(&self) -> bool {
+        self.verified_purchase
+    }
+
+    pub fn status(&self) -> ReviewStatus {
+        self.status
+    }
+
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    /// Version the review was read at, checked when it is updated. 0 until saved.
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    /// Returns the review at the version it has been stored with.
+    pub fn with_version(self, version: i64) -> Self {
+        Self { version, ..self }
+    }
+
+    pub fn approve(&mut self) -> Result<(), ReviewError> {
+        self.moderate(ReviewStatus::Approved)
+    }
+
+    pub fn reject(&mut self) -> Result<(), ReviewError> {
+        self.moderate(ReviewStatus::Rejected)
+    }
+
+    fn moderate(&mut self, status: ReviewStatus) -> Result<(), ReviewError> {
+        if self.status != ReviewStatus::Pending {
+            return Err(ReviewError::AlreadyModeratedError(self.status));
+        }
+        self.status = status;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::value_objects::{OrderId, OrderItem, ProductId};
+
+    #[test]
+    fn is_a_verified_purchase_with_a_confirmed_order_of_a_variant() {
+        let product = product();
+        let customer_id = CustomerId(Uuid::new_v4());
+        let submit = |orders: &[Order]| {
+            Review::submit(
+                ReviewId(Uuid::new_v4()),
+                &product,
+                customer_id.clone(),
+                4,
+                " Fits well ",
+                orders,
+                Utc::now(),
+            )
+            .unwrap()
+        };
+
+        let review = submit(&[order(&customer_id, &product, OrderStatus::Confirmed)]);
+        assert!(review.verified_purchase());
+        assert_eq!("Fits well", review.text());
+        assert_eq!(ReviewStatus::Pending, review.status());
+
+        assert!(
+            !submit(&[order(&customer_id, &product, OrderStatus::Cancelled)]).verified_purchase()
+        );
+        assert!(!submit(&[]).verified_purchase());
+    }
+
+    #[test]
+    fn rates_from_one_to_five_and_is_moderated_once() {
+        let product = product();
+        let submit = |rating| {
+            Review::submit(
+                ReviewId(Uuid::new_v4()),
+                &product,
+                CustomerId(Uuid::new_v4()),
+                rating,
+                "Too small",
+                &[],
+                Utc::now(),
+            )
+        };
+
+        assert_eq!(Err(ReviewError::InvalidRatingError(0)), submit(0));
+        assert_eq!(Err(ReviewError::InvalidRatingError(6)), submit(6));
+        let mut review = submit(1).unwrap();
+        review.reject().unwrap();
+        assert_eq!(
+            Err(ReviewError::AlreadyModeratedError(ReviewStatus::Rejected)),
+            review.approve()
+        );
+    }
+
+    fn product() -> Product {
+        let mut product = Product::new(CatalogProductId(Uuid::new_v4()), "T-shirt", None).unwrap();
+        product
+            .add_variant(ProductId(Uuid::new_v4()), "TS-RED-M", 19.9)
+            .unwrap();
+        product
+    }
+
+    fn order(customer_id: &CustomerId, product: &Product, status: OrderStatus) -> Order {
+        let mut order = Order::create(OrderId(Uuid::new_v4()), customer_id.clone());
+        order
+            .add(OrderItem {
+                price: 19.9,
+                quantity: 1,
+                product_id: product.variants()[0].id.clone(),
+            })
+            .unwrap();
+        order.with_status(status)
+    }
+}
@@ -0,0 +1,377 @@
+use crate::{
+    entities::{
+        order::{Order, OrderStatus},
+        order_lines::{OrderLines, OrderLinesError},
+        shipping_method::ShippingMethod,
+    },
+    value_objects::{OrderId, OrderItem, ProductId, ShipmentId, ShippingMethodId},
+};
+
+#[derive(Debug, PartialEq)]
+pub enum ShipmentError {
+    OrderNotConfirmedError(OrderStatus),
+    ShipmentOfAnotherOrderError,
+    ProductNotInOrderError(ProductId),
+    ShippedQuantityExceededError(ProductId),
+    InvalidWeightError(f64),
+    EmptyShipmentError,
+    MissingTrackingNumberError,
+    InvalidStateError(ShipmentState),
+    InvalidShipmentLinesError(OrderLinesError),
+}
+
+impl std::fmt::Display for ShipmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShipmentError::OrderNotConfirmedError(status) => {
+                write!(f, "Order is {}, not confirmed", status)
+            }
+            ShipmentError::ShipmentOfAnotherOrderError => {
+                write!(f, "Shipment belongs to another order")
+            }
+            ShipmentError::ProductNotInOrderError(product_id) => {
+                write!(f, "Product {} is not in the order", product_id.0)
+            }
+            ShipmentError::ShippedQuantityExceededError(product_id) => write!(
+                f,
+                "More units of product {} shipped than ordered",
+                product_id.0
+            ),
+            ShipmentError::InvalidWeightError(weight) => {
+                write!(f, "Weight must be positive, not {}", weight)
+            }
+            ShipmentError::EmptyShipmentError => write!(f, "Shipment has no items"),
+            ShipmentError::MissingTrackingNumberError => {
+                write!(f, "Shipment has no tracking number")
+            }
+            ShipmentError::InvalidStateError(state) => write!(f, "Shipment is {}", state),
+            ShipmentError::InvalidShipmentLinesError(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for ShipmentError {}
+
+impl From<OrderLinesError> for ShipmentError {
+    fn from(error: OrderLinesError) -> Self {
+        ShipmentError::InvalidShipmentLinesError(error)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShipmentState {
+    /// Being packed, items can still be added.
+    Preparing,
+    /// Handed to the carrier, with a tracking number.
+    Shipped,
+    Delivered,
+}
+
+const PREPARING: &str = "preparing";
+const SHIPPED: &str = "shipped";
+const DELIVERED: &str = "delivered";
+
+impl std::fmt::Display for ShipmentState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShipmentState::Preparing => write!(f, "{}", PREPARING),
+            ShipmentState::Shipped => write!(f, "{}", SHIPPED),
+            ShipmentState::Delivered => write!(f, "{}", DELIVERED),
+        }
+    }
+}
+
+impl std::str::FromStr for ShipmentState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            PREPARING => Ok(ShipmentState::Preparing),
+            SHIPPED => Ok(ShipmentState::Shipped),
+            DELIVERED => Ok(ShipmentState::Delivered),
+            _ => Err(format!("Unknown shipment state: {}", s)),
+        }
+    }
+}
+
+/// Items of a confirmed order sent together with a shipping method. An order
+/// can be shipped in several parcels, as long as no more units of a product
+/// are shipped than ordered. Shipping is charged once, in the pricing of the
+/// order, not per parcel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shipment {
+    id: ShipmentId,
+    order_id: OrderId,
+    shipping_method_id: ShippingMethodId,
+    carrier: String,
+    items: OrderLines,
+    weight: f64,
+    tracking_number: Option<String>,
+    state: ShipmentState,
+    version: i64,
+}
+
+impl Shipment {
+    /// Starts a parcel of `weight` kilograms, taken by the carrier of the method.
+    pub fn prepare(
+        id: ShipmentId,
+        order: &Order,
+        shipping_method: &ShippingMethod,
+        weight: f64,
+    ) -> Result<Self, ShipmentError> {
+        if order.status() != OrderStatus::Confirmed {
+            return Err(ShipmentError::OrderNotConfirmedError(order.status()));
+        }
+        if weight <= 0.0 || !weight.is_finite() {
+            return Err(ShipmentError::InvalidWeightError(weight));
+        }
+        Ok(Self {
+            id,
+            order_id: order.id().clone(),
+            shipping_method_id: shipping_method.id().clone(),
+            carrier: shipping_method.carrier().to_string(),
+            items: OrderLines::new(),
+            weight,
+            tracking_number: None,
+            state: ShipmentState::Preparing,
+            version: 0,
+        })
+    }
+
+    /// Rebuilds a shipment read from storage.
+    #[allow(clippy::too_many_arguments)]
+    pub fn restore(
+        id: ShipmentId,
+        order_id: OrderId,
+        shipping_method_id: ShippingMethodId,
+        carrier: String,
+        items: OrderLines,
+        weight: f64,
+        tracking_number: Option<String>,
+        state: ShipmentState,
+        version: i64,
+    ) -> Self {
+        Self {
+            id,
+            order_id,
+            shipping_method_id,
+            carrier,
+            items,
+            weight,
+            tracking_number,
+            state,
+            version,
+        }
+    }
+
+    pub fn id(&self) -> &ShipmentId {
+        &self.id
+    }
+
+    pub fn order_id(&self) -> &OrderId {
+        &self.order_id
+    }
+
+    pub fn shipping_method_id(&self) -> &ShippingMethodId {
+        &self.shipping_method_id
+    }
+
+    pub fn carrier(&self) -> &str {
+        &self.carrier
+    }
+
+    pub fn items(&self) -> &OrderLines {
+        &self.items
+    }
+
+    /// Weight of the parcel, in kilograms.
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    pub fn tracking_number(&self) -> Option<&str> {
+        self.tracking_number.as_deref()
+    }
+
+    pub fn state(&self) -> ShipmentState {
+        self.state
+    }
+
+    /// Version the shipment was read at, checked when it is updated. 0 until saved.
+    pub fn version(&self) -> i64 {
+        self.version
+    }
+
+    /// Returns the shipment at the version it has been stored with.
+    pub fn with_version(self, version: i64) -> Self {
+        Self { version, ..self }
+    }
+
+    /// Adds units of a product of the order, or more units of a product
+    /// already in the shipment. Units in `other_shipments`, the other
+    /// shipments of the order, can't be shipped again.
+    pub fn add(
+        &mut self,
+        order: &Order,
+        other_shipments: &[Shipment],
+        product_id: ProductId,
+        quantity: i32,
+    ) -> Result<(), ShipmentError> {
+        self.check_state(ShipmentState::Preparing)?;
+        if order.id() != &self.order_id {
+            return Err(ShipmentError::ShipmentOfAnotherOrderError);
+        }
+        let ordered = order
+            .order_lines()
+            .find(&product_id)
+            .ok_or_else(|| ShipmentError::ProductNotInOrderError(product_id.clone()))?;
+
+        let shipped = units_in(
+            other_shipments
+                .iter()
+                .filter(|other| other.id != self.id)
+                .chain(std::iter::once(&*self)),
+            &product_id,
+        );
+        if shipped.saturating_add(quantity) > ordered.quantity {
+            return Err(ShipmentError::ShippedQuantityExceededError(product_id));
+        }
+
+        self.items.add(OrderItem {
+            price: ordered.price,
+            quantity,
+            product_id,
+        })?;
+        Ok(())
+    }
+
+    /// Hands the parcel to the carrier.
+    pub fn ship(&mut self, tracking_number: &str) -> Result<(), ShipmentError> {
+        self.check_state(ShipmentState::Preparing)?;
+        if self.items.is_empty() {
+            return Err(ShipmentError::EmptyShipmentError);
+        }
+        let tracking_number = tracking_number.trim();
+        if tracking_number.is_empty() {
+            return Err(ShipmentError::MissingTrackingNumberError);
+        }
+        self.tracking_number = Some(tracking_number.to_string());
+        self.state = ShipmentState::Shipped;
+        Ok(())
+    }
+
+    pub fn deliver(&mut self) -> Result<(), ShipmentError> {
+        self.check_state(ShipmentState::Shipped)?;
+        self.state = ShipmentState::Delivered;
+        Ok(())
+    }
+
+    fn check_state(&self, state: ShipmentState) -> Result<(), ShipmentError> {
+        if self.state != state {
+            return Err(ShipmentError::InvalidStateError(self.state));
+        }
+        Ok(())
+    }
+}
+
+/// Whether every unit of the order is in a shipment handed to the carrier.
+pub fn is_fully_shipped(order: &Order, shipments: &[Shipment]) -> bool {
+    let handed_over: Vec<&Shipment> = shipments
+        .iter()
+        .filter(|shipment| shipment.state != ShipmentState::Preparing)
+        .collect();
+    order
+        .order_lines()
+        .iter()
+        .all(|item| units_in(handed_over.iter().copied(), &item.product_id) >= item.quantity)
+}
+
+fn units_in<'a>(shipments: impl Iterator<Item = &'a Shipment>, product_id: &ProductId) -> i32 {
+    shipments
+        .filter_map(|shipment| shipment.items.find(product_id))
+        .map(|item| item.quantity)
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{
+        entities::shipping_method::ShippingRate,
+        fixtures::{order, product_id},
+    };
+
+    #[test]
+    fn ships_only_items_of_confirmed_orders() {
+        let order = order(OrderStatus::Pending);
+
+        assert_eq!(
+            Err(ShipmentError::OrderNotConfirmedError(OrderStatus::Pending)),
+            Shipment::prepare(ShipmentId(Uuid::new_v4()), &order, &method(), 1.0)
+        );
+    }
+
+    #[test]
+    fn cannot_ship_more_units_than_ordered() {
+        let order = order(OrderStatus::Confirmed);
+        let mut first =
+            Shipment::prepare(ShipmentId(Uuid::new_v4()), &order, &method(), 1.0).unwrap();
+        first.add(&order, &[], product_id(1), 2).unwrap();
+        let mut second =
+            Shipment::prepare(ShipmentId(Uuid::new_v4()), &order, &method(), 1.0).unwrap();
+
+        assert_eq!(
+            Err(ShipmentError::ShippedQuantityExceededError(product_id(1))),
+            second.add(&order, &[first.clone()], product_id(1), 2)
+        );
+        second
+            .add(&order, &[first.clone()], product_id(1), 1)
+            .unwrap();
+        second
+            .add(&order, &[first.clone()], product_id(2), 1)
+            .unwrap();
+
+        first.ship("TRACK-1").unwrap();
+        assert!(!is_fully_shipped(&order, &[first.clone(), second.clone()]));
+        second.ship("TRACK-2").unwrap();
+        assert!(is_fully_shipped(&order, &[first, second]));
+    }
+
+    #[test]
+    fn is_delivered_after_being_shipped_with_a_tracking_number() {
+        let order = order(OrderStatus::Confirmed);
+        let mut shipment =
+            Shipment::prepare(ShipmentId(Uuid::new_v4()), &order, &method(), 2.0).unwrap();
+        assert_eq!(Err(ShipmentError::EmptyShipmentError), shipment.ship("T"));
+        shipment.add(&order, &[], product_id(1), 1).unwrap();
+        assert_eq!(
+            Err(ShipmentError::MissingTrackingNumberError),
+            shipment.ship(" ")
+        );
+        assert_eq!(
+            Err(ShipmentError::InvalidStateError(ShipmentState::Preparing)),
+            shipment.deliver()
+        );
+
+        shipment.ship("TRACK-1").unwrap();
+        shipment.deliver().unwrap();
+
+        assert_eq!(ShipmentState::Delivered, shipment.state());
+        assert_eq!(Some("TRACK-1"), shipment.tracking_number());
+    }
+
+    fn method() -> ShippingMethod {
+        ShippingMethod::new(
+            ShippingMethodId(Uuid::new_v4()),
+            "Standard",
+            "UPS",
+            ShippingRate::WeightBased {
+                base: 4.0,
+                per_kg: 1.0,
+            },
+        )
+        .unwrap()
+    }
+}
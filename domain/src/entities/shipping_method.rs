@@ -0,0 +1,250 @@
+use crate::value_objects::ShippingMethodId;
+
+#[derive(Debug, PartialEq)]
+pub enum ShippingMethodError {
+    EmptyNameError,
+    EmptyCarrierError,
+    InvalidRateError(String),
+    InvalidWeightError(f64),
+}
+
+impl std::fmt::Display for ShippingMethodError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShippingMethodError::EmptyNameError => write!(f, "Shipping method has no name"),
+            ShippingMethodError::EmptyCarrierError => {
+                write!(f, "Shipping method has no carrier")
+            }
+            ShippingMethodError::InvalidRateError(message) => {
+                write!(f, "Invalid rate: {}", message)
+            }
+            ShippingMethodError::InvalidWeightError(weight) => {
+                write!(f, "Invalid weight: {}", weight)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShippingMethodError {}
+
+const FLAT: &str = "flat";
+const WEIGHT_BASED: &str = "weight_based";
+const FREE_OVER: &str = "free_over";
+
+/// What a shipment costs with a shipping method.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShippingRate {
+    Flat(f64),
+    /// A base amount plus an amount per kilogram of the parcel.
+    WeightBased {
+        base: f64,
+        per_kg: f64,
+    },
+    /// A flat amount, not charged on orders worth at least `threshold`.
+    FreeOver {
+        amount: f64,
+        threshold: f64,
+    },
+}
+
+impl ShippingRate {
+    /// Builds a rate of `kind` from the fields it needs, as sent by clients
+    /// or stored. Fields the kind does not need are ignored.
+    pub fn parse(
+        kind: &str,
+        amount: Option<f64>,
+        per_kg: Option<f64>,
+        threshold: Option<f64>,
+    ) -> Result<Self, ShippingMethodError> {
+        let invalid = |message: &str| ShippingMethodError::InvalidRateError(message.to_string());
+        let valid = |value: Option<f64>| value.filter(|value| *value >= 0.0 && value.is_finite());
+        match kind {
+            FLAT => valid(amount)
+                .map(ShippingRate::Flat)
+                .ok_or_else(|| invalid("amount must not be negative")),
+            WEIGHT_BASED => match (valid(amount), valid(per_kg)) {
+                (Some(base), Some(per_kg)) => Ok(ShippingRate::WeightBased { base, per_kg }),
+                _ => Err(invalid("amount and per_kg must not be negative")),
+            },
+            FREE_OVER => match (valid(amount), valid(threshold)) {
+                (Some(amount), Some(threshold)) => Ok(ShippingRate::FreeOver { amount, threshold }),
+                _ => Err(invalid("amount and threshold must not be negative")),
+            },
+            _ => Err(invalid(&format!("unknown kind {}", kind))),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ShippingRate::Flat(_) => FLAT,
+            ShippingRate::WeightBased { .. } => WEIGHT_BASED,
+            ShippingRate::FreeOver { .. } => FREE_OVER,
+        }
+    }
+
+    /// The amount, amount per kilogram and threshold of the rate, as taken
+    /// by `parse`.
+    pub fn amounts(&self) -> (f64, Option<f64>, Option<f64>) {
+        match self {
+            ShippingRate::Flat(amount) => (*amount, None, None),
+            ShippingRate::WeightBased { base, per_kg } => (*base, Some(*per_kg), None),
+            ShippingRate::FreeOver { amount, threshold } => (*amount, None, Some(*threshold)),
+        }
+    }
+
+    /// Cost of a parcel of `weight` kilograms of an order worth `order_value`,
+    /// rounded to cents.
+    pub fn cost(&self, order_value: f64, weight: f64) -> f64 {
+        let cost = match self {
+            ShippingRate::Flat(amount) => *amount,
+            ShippingRate::WeightBased { base, per_kg } => base + per_kg * weight,
+            ShippingRate::FreeOver { amount, threshold } => {
+                if order_value >= *threshold {
+                    0.0
+                } else {
+                    *amount
+                }
+            }
+        };
+        (cost * 100.0).round() / 100.0
+    }
+}
+
+/// A way of shipping orders, with the carrier taking the parcels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShippingMethod {
+    id: ShippingMethodId,
+    name: String,
+    carrier: String,
+    rate: ShippingRate,
+}
+
+impl ShippingMethod {
+    pub fn new(
+        id: ShippingMethodId,
+        name: &str,
+        carrier: &str,
+        rate: ShippingRate,
+    ) -> Result<Self, ShippingMethodError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ShippingMethodError::EmptyNameError);
+        }
+        let carrier = carrier.trim();
+        if carrier.is_empty() {
+            return Err(ShippingMethodError::EmptyCarrierError);
+        }
+        Ok(Self {
+            id,
+            name: name.to_string(),
+            carrier: carrier.to_string(),
+            rate,
+        })
+    }
+
+    pub fn id(&self) -> &ShippingMethodId {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn carrier(&self) -> &str {
+        &self.carrier
+    }
+
+    pub fn rate(&self) -> &ShippingRate {
+        &self.rate
+    }
+}
+
+/// The shipping method chosen for an order, with its rate as it was then,
+/// kept even if the method changes later. The order is charged for shipping
+/// once, however many parcels it ships in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrderShipping {
+    pub shipping_method_id: ShippingMethodId,
+    pub carrier: String,
+    pub rate: ShippingRate,
+    /// Kilograms the order weighs, charged by weight-based rates.
+    pub weight: f64,
+}
+
+impl OrderShipping {
+    /// Weight-based rates need the `weight` of the order, other rates ignore it.
+    pub fn new(
+        shipping_method: &ShippingMethod,
+        weight: Option<f64>,
+    ) -> Result<Self, ShippingMethodError> {
+        let weight = match (shipping_method.rate(), weight) {
+            (_, Some(weight)) if weight < 0.0 || !weight.is_finite() => {
+                return Err(ShippingMethodError::InvalidWeightError(weight))
+            }
+            (ShippingRate::WeightBased { .. }, None) => {
+                return Err(ShippingMethodError::InvalidRateError(
+                    "weight of the order is required".to_string(),
+                ))
+            }
+            (_, weight) => weight.unwrap_or_default(),
+        };
+        Ok(Self {
+            shipping_method_id: shipping_method.id().clone(),
+            carrier: shipping_method.carrier().to_string(),
+            rate: shipping_method.rate().clone(),
+            weight,
+        })
+    }
+
+    /// Cost of shipping an order worth `order_value`.
+    pub fn cost(&self, order_value: f64) -> f64 {
+        self.rate.cost(order_value, self.weight)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn charges_shipments_by_the_kind_of_rate() {
+        let flat = ShippingRate::parse("flat", Some(4.9), None, None).unwrap();
+        let weight_based = ShippingRate::parse("weight_based", Some(3.0), Some(1.5), None).unwrap();
+        let free_over = ShippingRate::parse("free_over", Some(5.0), None, Some(50.0)).unwrap();
+
+        assert_eq!(4.9, flat.cost(100.0, 2.0));
+        assert_eq!(6.75, weight_based.cost(100.0, 2.5));
+        assert_eq!(5.0, free_over.cost(49.99, 2.0));
+        assert_eq!(0.0, free_over.cost(50.0, 2.0));
+        assert!(ShippingRate::parse("weight_based", Some(3.0), None, None).is_err());
+        assert!(ShippingRate::parse("flat", Some(-1.0), None, None).is_err());
+    }
+
+    #[test]
+    fn charges_an_order_for_shipping_by_its_weight_only_with_weight_based_rates() {
+        let method = |rate| {
+            ShippingMethod::new(ShippingMethodId(Uuid::new_v4()), "Standard", "UPS", rate).unwrap()
+        };
+        let weight_based = method(ShippingRate::WeightBased {
+            base: 3.0,
+            per_kg: 1.5,
+        });
+
+        assert_eq!(
+            6.75,
+            OrderShipping::new(&weight_based, Some(2.5))
+                .unwrap()
+                .cost(10.0)
+        );
+        assert!(OrderShipping::new(&weight_based, None).is_err());
+        assert!(OrderShipping::new(&weight_based, Some(-1.0)).is_err());
+        assert_eq!(
+            4.9,
+            OrderShipping::new(&method(ShippingRate::Flat(4.9)), None)
+                .unwrap()
+                .cost(10.0)
+        );
+    }
+}
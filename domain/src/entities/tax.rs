@@ -0,0 +1,213 @@
+use crate::{
+    entities::order_lines::OrderLines,
+    value_objects::{OrderItem, ProductId},
+};
+
+/// Products are taxed at the rate of their category in the customer's country.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TaxCategory {
+    #[default]
+    Standard,
+    Reduced,
+    Exempt,
+}
+
+const STANDARD: &str = "standard";
+const REDUCED: &str = "reduced";
+const EXEMPT: &str = "exempt";
+
+impl std::fmt::Display for TaxCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaxCategory::Standard => write!(f, "{}", STANDARD),
+            TaxCategory::Reduced => write!(f, "{}", REDUCED),
+            TaxCategory::Exempt => write!(f, "{}", EXEMPT),
+        }
+    }
+}
+
+impl std::str::FromStr for TaxCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            STANDARD => Ok(TaxCategory::Standard),
+            REDUCED => Ok(TaxCategory::Reduced),
+            EXEMPT => Ok(TaxCategory::Exempt),
+            _ => Err(format!("Unknown tax category: {}", s)),
+        }
+    }
+}
+
+/// Whether the prices of the items already include taxes.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PricingMode {
+    /// Taxes are added on top of the prices.
+    #[default]
+    Exclusive,
+    /// Taxes are part of the prices, the total doesn't change.
+    Inclusive,
+}
+
+const EXCLUSIVE: &str = "exclusive";
+const INCLUSIVE: &str = "inclusive";
+
+impl std::fmt::Display for PricingMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PricingMode::Exclusive => write!(f, "{}", EXCLUSIVE),
+            PricingMode::Inclusive => write!(f, "{}", INCLUSIVE),
+        }
+    }
+}
+
+impl std::str::FromStr for PricingMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            EXCLUSIVE => Ok(PricingMode::Exclusive),
+            INCLUSIVE => Ok(PricingMode::Inclusive),
+            _ => Err(format!("Unknown pricing mode: {}", s)),
+        }
+    }
+}
+
+/// Rate, as a percentage, a product of the order is taxed at.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaxRate {
+    pub product_id: ProductId,
+    pub category: TaxCategory,
+    pub rate: f64,
+}
+
+/// How an order is taxed. Rates are looked up by a `TaxCalculator` when
+/// items or addresses change; amounts are computed from the current items.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Taxes {
+    pub pricing_mode: PricingMode,
+    pub rates: Vec<TaxRate>,
+}
+
+/// Tax on an item of the order, after the order's discounts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaxLine {
+    pub product_id: ProductId,
+    pub category: TaxCategory,
+    pub rate: f64,
+    /// Price of the item, without the tax.
+    pub net_amount: f64,
+    pub amount: f64,
+}
+
+impl Taxes {
+    pub fn new(pricing_mode: PricingMode) -> Self {
+        Self {
+            pricing_mode,
+            rates: vec![],
+        }
+    }
+
+    /// Tax lines of the items with a rate. Discounts are shared among the
+    /// items in proportion to their price, `discounted_total` being what is
+    /// left of the subtotal.
+    pub fn lines(&self, order_lines: &OrderLines, discounted_total: f64) -> Vec<TaxLine> {
+        let subtotal = order_lines.total_price();
+        let share = if subtotal > 0.0 {
+            discounted_total / subtotal
+        } else {
+            0.0
+        };
+        order_lines
+            .iter()
+            .filter_map(|item| {
+                let tax_rate = self.rate_of(item)?;
+                let price = item.price * item.quantity as f64 * share;
+                let (net_amount, amount) = match self.pricing_mode {
+                    PricingMode::Exclusive => (price, price * tax_rate.rate / 100.0),
+                    PricingMode::Inclusive => {
+                        let net_amount = price / (1.0 + tax_rate.rate / 100.0);
+                        (net_amount, price - net_amount)
+                    }
+                };
+                Some(TaxLine {
+                    product_id: item.product_id.clone(),
+                    category: tax_rate.category,
+                    rate: tax_rate.rate,
+                    net_amount: round_to_cents(net_amount),
+                    amount: round_to_cents(amount),
+                })
+            })
+            .collect()
+    }
+
+    fn rate_of(&self, item: &OrderItem) -> Option<&TaxRate> {
+        self.rates
+            .iter()
+            .find(|rate| rate.product_id == item.product_id)
+    }
+}
+
+fn round_to_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn taxes_items_on_top_of_or_within_their_discounted_prices() {
+        let order_lines = OrderLines::try_from(vec![
+            OrderItem {
+                price: 10.0,
+                quantity: 2,
+                product_id: product_id(1),
+            },
+            OrderItem {
+                price: 20.0,
+                quantity: 1,
+                product_id: product_id(2),
+            },
+            OrderItem {
+                price: 5.0,
+                quantity: 1,
+                product_id: product_id(3),
+            },
+        ])
+        .unwrap();
+        let mut taxes = Taxes {
+            pricing_mode: PricingMode::Exclusive,
+            rates: vec![
+                rate(1, TaxCategory::Standard, 22.0),
+                rate(2, TaxCategory::Reduced, 10.0),
+            ],
+        };
+
+        let lines = taxes.lines(&order_lines, 36.0);
+
+        assert_eq!(2, lines.len());
+        assert_eq!((16.0, 3.52), (lines[0].net_amount, lines[0].amount));
+        assert_eq!((16.0, 1.6), (lines[1].net_amount, lines[1].amount));
+
+        taxes.pricing_mode = PricingMode::Inclusive;
+        let lines = taxes.lines(&order_lines, 45.0);
+
+        assert_eq!((16.39, 3.61), (lines[0].net_amount, lines[0].amount));
+        assert_eq!((18.18, 1.82), (lines[1].net_amount, lines[1].amount));
+    }
+
+    fn rate(n: u128, category: TaxCategory, rate: f64) -> TaxRate {
+        TaxRate {
+            product_id: product_id(n),
+            category,
+            rate,
+        }
+    }
+
+    fn product_id(n: u128) -> ProductId {
+        ProductId(Uuid::from_u128(n))
+    }
+}
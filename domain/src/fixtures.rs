@@ -0,0 +1,149 @@
+//! Orders and repositories shared by the tests of what follows an order:
+//! its payment, shipments and returns.
+
+use std::sync::{Arc, Mutex};
+
+use uuid::Uuid;
+
+use crate::{
+    entities::{
+        order::{Order, OrderStatus},
+        order_lines::OrderLines,
+        payment::Payment,
+        returns::Return,
+        shipment::{Shipment, ShipmentState},
+    },
+    repositories::order_repository::MockMyOrderRepository,
+    value_objects::{CustomerId, OrderId, OrderItem, ProductId, ShipmentId, ShippingMethodId},
+};
+
+pub const ORDER_ID: &str = "3f1c2b4a-5d6e-4f70-8192-a3b4c5d6e7f8";
+
+pub fn order_id() -> OrderId {
+    OrderId(Uuid::try_parse(ORDER_ID).unwrap())
+}
+
+pub fn product_id(n: u128) -> ProductId {
+    ProductId(Uuid::from_u128(n))
+}
+
+/// Three units of product 1 at 10.0 and one of product 2 at 5.5.
+pub fn order(status: OrderStatus) -> Order {
+    order_with_id(order_id(), status)
+}
+
+pub fn order_with_id(id: OrderId, status: OrderStatus) -> Order {
+    let mut order = Order::create(id, CustomerId(Uuid::new_v4()));
+    order
+        .add_multiple(vec![
+            OrderItem {
+                price: 10.0,
+                quantity: 3,
+                product_id: product_id(1),
+            },
+            OrderItem {
+                price: 5.5,
+                quantity: 1,
+                product_id: product_id(2),
+            },
+        ])
+        .unwrap();
+    order.with_status(status)
+}
+
+/// Finds the order of any id, in the status.
+pub fn order_repository(status: OrderStatus) -> MockMyOrderRepository {
+    let mut order_repository = MockMyOrderRepository::new();
+    order_repository
+        .expect_find_by_id()
+        .returning(move |id| Ok(Some(order_with_id(id, status))));
+    order_repository
+}
+
+/// A shipment of the order in the state, once handed to the carrier.
+pub fn shipment(order_id: OrderId, state: ShipmentState) -> Shipment {
+    Shipment::restore(
+        ShipmentId(Uuid::new_v4()),
+        order_id,
+        ShippingMethodId(Uuid::new_v4()),
+        "DHL".to_string(),
+        OrderLines::new(),
+        1.0,
+        Some("JD0001".to_string()),
+        state,
+        3,
+    )
+}
+
+/// Entities stored with a version, checked and bumped on every update.
+pub trait Versioned: Clone {
+    fn version(&self) -> i64;
+    fn with_version(self, version: i64) -> Self;
+}
+
+impl Versioned for Payment {
+    fn version(&self) -> i64 {
+        Payment::version(self)
+    }
+
+    fn with_version(self, version: i64) -> Self {
+        Payment::with_version(self, version)
+    }
+}
+
+impl Versioned for Return {
+    fn version(&self) -> i64 {
+        Return::version(self)
+    }
+
+    fn with_version(self, version: i64) -> Self {
+        Return::with_version(self, version)
+    }
+}
+
+impl Versioned for Shipment {
+    fn version(&self) -> i64 {
+        Shipment::version(self)
+    }
+
+    fn with_version(self, version: i64) -> Self {
+        Shipment::with_version(self, version)
+    }
+}
+
+/// The last entity stored by a mocked repository, shared with the test.
+pub struct Stored<T>(Arc<Mutex<Option<T>>>);
+
+impl<T> Clone for Stored<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Default for Stored<T> {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(None)))
+    }
+}
+
+impl<T: Versioned> Stored<T> {
+    pub fn get(&self) -> Option<T> {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// Stores a new entity at version 1.
+    pub fn save(&self, entity: T) -> T {
+        self.keep(entity.with_version(1))
+    }
+
+    /// Stores the entity at its next version.
+    pub fn update(&self, entity: T) -> T {
+        let version = entity.version() + 1;
+        self.keep(entity.with_version(version))
+    }
+
+    fn keep(&self, entity: T) -> T {
+        *self.0.lock().unwrap() = Some(entity.clone());
+        entity
+    }
+}
@@ -0,0 +1,222 @@
+use async_trait::async_trait;
+use mockall::automock;
+
+use crate::value_objects::{OrderId, OrderItem, ProductId, ReservationId};
+
+#[derive(Debug, PartialEq)]
+pub enum InventoryGatewayError {
+    InsufficientStockError(ProductId),
+    ReservationNotFoundError(ReservationId),
+    InventoryUnavailableError(String),
+}
+
+impl std::fmt::Display for InventoryGatewayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InventoryGatewayError::InsufficientStockError(product_id) => {
+                write!(f, "Insufficient stock of product {}", product_id.0)
+            }
+            InventoryGatewayError::ReservationNotFoundError(reservation_id) => {
+                write!(f, "Reservation {} not found", reservation_id.0)
+            }
+            InventoryGatewayError::InventoryUnavailableError(message) => {
+                write!(f, "Inventory unavailable: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InventoryGatewayError {}
+
+/// Stock handling needed by the checkout. Every operation may be retried, so
+/// implementations must be idempotent.
+#[automock]
+#[async_trait]
+pub trait InventoryGateway {
+    /// Sets aside stock for the items of the order, all of them or none.
+    /// Reserving again for the same order returns the same reservation.
+    async fn reserve(
+        &mut self,
+        order_id: &OrderId,
+        items: &[OrderItem],
+    ) -> Result<ReservationId, InventoryGatewayError>;
+
+    /// Makes the reservation final: its stock leaves the warehouse.
+    async fn commit(&mut self, reservation_id: &ReservationId)
+        -> Result<(), InventoryGatewayError>;
+
+    /// Gives the reserved stock back. Committed reservations can't be released.
+    async fn release(
+        &mut self,
+        reservation_id: &ReservationId,
+    ) -> Result<(), InventoryGatewayError>;
+}
+
+#[cfg(test)]
+pub use in_memory::{InMemoryInventoryGateway, InMemoryReservationState};
+
+#[cfg(test)]
+mod in_memory {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum InMemoryReservationState {
+        Reserved,
+        Committed,
+        Released,
+    }
+
+    struct InMemoryReservation {
+        id: ReservationId,
+        order_id: OrderId,
+        items: Vec<(Uuid, i32)>,
+        state: InMemoryReservationState,
+    }
+
+    #[derive(Default)]
+    struct InMemoryStock {
+        available: HashMap<Uuid, i32>,
+        reservations: Vec<InMemoryReservation>,
+    }
+
+    /// Inventory kept in memory, for tests. Clones share the same stock.
+    #[derive(Clone, Default)]
+    pub struct InMemoryInventoryGateway {
+        stock: Arc<Mutex<InMemoryStock>>,
+    }
+
+    impl InMemoryInventoryGateway {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn add_stock(&self, product_id: &ProductId, quantity: i32) {
+            *self
+                .stock
+                .lock()
+                .unwrap()
+                .available
+                .entry(product_id.0)
+                .or_default() += quantity;
+        }
+
+        /// Stock neither reserved nor committed.
+        pub fn available(&self, product_id: &ProductId) -> i32 {
+            self.stock
+                .lock()
+                .unwrap()
+                .available
+                .get(&product_id.0)
+                .copied()
+                .unwrap_or_default()
+        }
+
+        pub fn reservation_state(&self, order_id: &OrderId) -> Option<InMemoryReservationState> {
+            self.stock
+                .lock()
+                .unwrap()
+                .reservations
+                .iter()
+                .find(|reservation| &reservation.order_id == order_id)
+                .map(|reservation| reservation.state)
+        }
+    }
+
+    #[async_trait]
+    impl InventoryGateway for InMemoryInventoryGateway {
+        async fn reserve(
+            &mut self,
+            order_id: &OrderId,
+            items: &[OrderItem],
+        ) -> Result<ReservationId, InventoryGatewayError> {
+            let mut stock = self.stock.lock().unwrap();
+            if let Some(reservation) = stock
+                .reservations
+                .iter()
+                .find(|reservation| &reservation.order_id == order_id)
+            {
+                return Ok(reservation.id.clone());
+            }
+
+            for item in items {
+                let available = stock.available.get(&item.product_id.0).copied();
+                if available.unwrap_or_default() < item.quantity {
+                    return Err(InventoryGatewayError::InsufficientStockError(
+                        item.product_id.clone(),
+                    ));
+                }
+            }
+            for item in items {
+                *stock.available.entry(item.product_id.0).or_default() -= item.quantity;
+            }
+            let id = ReservationId(Uuid::new_v4());
+            stock.reservations.push(InMemoryReservation {
+                id: id.clone(),
+                order_id: order_id.clone(),
+                items: items
+                    .iter()
+                    .map(|item| (item.product_id.0, item.quantity))
+                    .collect(),
+                state: InMemoryReservationState::Reserved,
+            });
+            Ok(id)
+        }
+
+        async fn commit(
+            &mut self,
+            reservation_id: &ReservationId,
+        ) -> Result<(), InventoryGatewayError> {
+            let mut stock = self.stock.lock().unwrap();
+            let reservation = stock
+                .reservations
+                .iter_mut()
+                .find(|reservation| &reservation.id == reservation_id)
+                .ok_or_else(|| {
+                    InventoryGatewayError::ReservationNotFoundError(reservation_id.clone())
+                })?;
+            if reservation.state == InMemoryReservationState::Released {
+                return Err(InventoryGatewayError::ReservationNotFoundError(
+                    reservation_id.clone(),
+                ));
+            }
+            reservation.state = InMemoryReservationState::Committed;
+            Ok(())
+        }
+
+        async fn release(
+            &mut self,
+            reservation_id: &ReservationId,
+        ) -> Result<(), InventoryGatewayError> {
+            let mut stock = self.stock.lock().unwrap();
+            let reservation = stock
+                .reservations
+                .iter_mut()
+                .find(|reservation| &reservation.id == reservation_id)
+                .ok_or_else(|| {
+                    InventoryGatewayError::ReservationNotFoundError(reservation_id.clone())
+                })?;
+            match reservation.state {
+                InMemoryReservationState::Released => return Ok(()),
+                InMemoryReservationState::Committed => {
+                    return Err(InventoryGatewayError::ReservationNotFoundError(
+                        reservation_id.clone(),
+                    ))
+                }
+                InMemoryReservationState::Reserved => {}
+            }
+            reservation.state = InMemoryReservationState::Released;
+            let items = reservation.items.clone();
+            for (product_id, quantity) in items {
+                *stock.available.entry(product_id).or_default() += quantity;
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod inventory_gateway;
+pub mod payment_gateway;
+pub mod product_search;
+pub mod tax_calculator;
@@ -0,0 +1,243 @@
+use async_trait::async_trait;
+use mockall::automock;
+
+use crate::value_objects::{AuthorizationId, OrderId};
+
+#[derive(Debug, PartialEq)]
+pub enum PaymentGatewayError {
+    PaymentDeclinedError(String),
+    AuthorizationNotFoundError(AuthorizationId),
+    PaymentRejectedError(String),
+    PaymentProviderUnavailableError(String),
+}
+
+impl std::fmt::Display for PaymentGatewayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaymentGatewayError::PaymentDeclinedError(reason) => {
+                write!(f, "Payment declined: {}", reason)
+            }
+            PaymentGatewayError::AuthorizationNotFoundError(authorization_id) => {
+                write!(f, "Authorization {} not found", authorization_id.0)
+            }
+            PaymentGatewayError::PaymentRejectedError(reason) => {
+                write!(f, "Payment operation rejected: {}", reason)
+            }
+            PaymentGatewayError::PaymentProviderUnavailableError(message) => {
+                write!(f, "Payment provider unavailable: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PaymentGatewayError {}
+
+/// Payment provider used by the checkout. Every operation may be retried, so
+/// implementations must be idempotent.
+#[automock]
+#[async_trait]
+pub trait PaymentGateway {
+    /// Holds `amount` on the customer's payment method. Authorizing again
+    /// for the same order returns the same authorization.
+    async fn authorize(
+        &mut self,
+        order_id: &OrderId,
+        amount: f64,
+    ) -> Result<AuthorizationId, PaymentGatewayError>;
+
+    /// Charges `amount` out of the held one.
+    async fn capture(
+        &mut self,
+        authorization_id: &AuthorizationId,
+        amount: f64,
+    ) -> Result<(), PaymentGatewayError>;
+
+    /// Cancels an authorization not captured yet, releasing the held amount.
+    async fn void(&mut self, authorization_id: &AuthorizationId)
+        -> Result<(), PaymentGatewayError>;
+
+    /// Gives back `amount` of the captured one. Refunds with the same
+    /// `idempotency_key` are made once.
+    async fn refund(
+        &mut self,
+        authorization_id: &AuthorizationId,
+        amount: f64,
+        idempotency_key: &str,
+    ) -> Result<(), PaymentGatewayError>;
+}
+
+#[cfg(test)]
+pub use in_memory::InMemoryPaymentGateway;
+
+#[cfg(test)]
+mod in_memory {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    struct InMemoryAuthorization {
+        id: AuthorizationId,
+        order_id: OrderId,
+        amount: f64,
+        captured: f64,
+        refunds: Vec<(String, f64)>,
+        voided: bool,
+    }
+
+    /// Payment provider kept in memory, for tests. It declines amounts above
+    /// its limit, if any, and derives authorization ids from order ids, so runs
+    /// are repeatable. Clones share the same authorizations.
+    #[derive(Clone, Default)]
+    pub struct InMemoryPaymentGateway {
+        limit: Option<f64>,
+        authorizations: Arc<Mutex<Vec<InMemoryAuthorization>>>,
+    }
+
+    impl InMemoryPaymentGateway {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn with_limit(limit: f64) -> Self {
+            Self {
+                limit: Some(limit),
+                ..Self::default()
+            }
+        }
+
+        /// `None` if no payment was authorized for the order.
+        pub fn is_voided(&self, order_id: &OrderId) -> Option<bool> {
+            self.find(order_id, |authorization| authorization.voided)
+        }
+
+        pub fn captured(&self, order_id: &OrderId) -> Option<f64> {
+            self.find(order_id, |authorization| authorization.captured)
+        }
+
+        pub fn refunded(&self, order_id: &OrderId) -> Option<f64> {
+            self.find(order_id, |authorization| {
+                authorization.refunds.iter().map(|(_, amount)| amount).sum()
+            })
+        }
+
+        fn find<T>(
+            &self,
+            order_id: &OrderId,
+            f: impl Fn(&InMemoryAuthorization) -> T,
+        ) -> Option<T> {
+            self.authorizations
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|authorization| &authorization.order_id == order_id)
+                .map(f)
+        }
+
+        fn update(
+            &self,
+            authorization_id: &AuthorizationId,
+            f: impl FnOnce(&mut InMemoryAuthorization) -> Result<(), PaymentGatewayError>,
+        ) -> Result<(), PaymentGatewayError> {
+            let mut authorizations = self.authorizations.lock().unwrap();
+            let authorization = authorizations
+                .iter_mut()
+                .find(|authorization| &authorization.id == authorization_id)
+                .ok_or_else(|| {
+                    PaymentGatewayError::AuthorizationNotFoundError(authorization_id.clone())
+                })?;
+            f(authorization)
+        }
+    }
+
+    #[async_trait]
+    impl PaymentGateway for InMemoryPaymentGateway {
+        async fn authorize(
+            &mut self,
+            order_id: &OrderId,
+            amount: f64,
+        ) -> Result<AuthorizationId, PaymentGatewayError> {
+            let mut authorizations = self.authorizations.lock().unwrap();
+            if let Some(authorization) = authorizations
+                .iter()
+                .find(|authorization| &authorization.order_id == order_id)
+            {
+                return Ok(authorization.id.clone());
+            }
+            if self.limit.is_some_and(|limit| amount > limit) {
+                return Err(PaymentGatewayError::PaymentDeclinedError(format!(
+                    "{} is over the limit",
+                    amount
+                )));
+            }
+
+            let id = AuthorizationId(format!("auth_{}", order_id.0.simple()));
+            authorizations.push(InMemoryAuthorization {
+                id: id.clone(),
+                order_id: order_id.clone(),
+                amount,
+                captured: 0.0,
+                refunds: vec![],
+                voided: false,
+            });
+            Ok(id)
+        }
+
+        async fn capture(
+            &mut self,
+            authorization_id: &AuthorizationId,
+            amount: f64,
+        ) -> Result<(), PaymentGatewayError> {
+            self.update(authorization_id, |authorization| {
+                if authorization.voided || amount > authorization.amount {
+                    return Err(PaymentGatewayError::PaymentRejectedError(
+                        "Capture not allowed".to_string(),
+                    ));
+                }
+                authorization.captured = amount;
+                Ok(())
+            })
+        }
+
+        async fn void(
+            &mut self,
+            authorization_id: &AuthorizationId,
+        ) -> Result<(), PaymentGatewayError> {
+            self.update(authorization_id, |authorization| {
+                if authorization.captured > 0.0 {
+                    return Err(PaymentGatewayError::PaymentRejectedError(
+                        "Authorization already captured".to_string(),
+                    ));
+                }
+                authorization.voided = true;
+                Ok(())
+            })
+        }
+
+        async fn refund(
+            &mut self,
+            authorization_id: &AuthorizationId,
+            amount: f64,
+            idempotency_key: &str,
+        ) -> Result<(), PaymentGatewayError> {
+            self.update(authorization_id, |authorization| {
+                if authorization
+                    .refunds
+                    .iter()
+                    .any(|(key, _)| key == idempotency_key)
+                {
+                    return Ok(());
+                }
+                let refunded: f64 = authorization.refunds.iter().map(|(_, amount)| amount).sum();
+                if refunded + amount > authorization.captured {
+                    return Err(PaymentGatewayError::PaymentRejectedError(
+                        "Refund exceeds the captured amount".to_string(),
+                    ));
+                }
+                authorization
+                    .refunds
+                    .push((idempotency_key.to_string(), amount));
+                Ok(())
+            })
+        }
+    }
+}
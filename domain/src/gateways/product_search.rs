@@ -0,0 +1,106 @@
+use async_trait::async_trait;
+use mockall::automock;
+
+use crate::value_objects::{CatalogProductId, CategoryId, InvalidValueError};
+
+#[derive(Debug, PartialEq)]
+pub enum ProductSearchError {
+    SearchUnavailableError(String),
+}
+
+impl std::fmt::Display for ProductSearchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProductSearchError::SearchUnavailableError(message) => {
+                write!(f, "Search unavailable: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProductSearchError {}
+
+/// Lower bounds of the price buckets counted by searches; the last bucket
+/// has no upper bound.
+pub const PRICE_BUCKETS: [f64; 4] = [0.0, 25.0, 50.0, 100.0];
+
+/// Hits returned by a search at most.
+pub const MAX_HITS: i64 = 50;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SearchSort {
+    /// Best matches first, then by name.
+    #[default]
+    Relevance,
+    PriceAsc,
+    PriceDesc,
+    Name,
+}
+
+impl SearchSort {
+    pub fn parse(value: &str) -> Result<Self, InvalidValueError> {
+        match value.trim() {
+            "" | "relevance" => Ok(SearchSort::Relevance),
+            "price_asc" => Ok(SearchSort::PriceAsc),
+            "price_desc" => Ok(SearchSort::PriceDesc),
+            "name" => Ok(SearchSort::Name),
+            _ => Err(InvalidValueError::InvalidFormat(
+                "one of relevance, price_asc, price_desc or name".to_string(),
+            )),
+        }
+    }
+}
+
+/// Products matching `text` in their name or text attributes, or all of them
+/// with an empty text. Prices are those of the variants, in the base currency.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchQuery {
+    pub text: String,
+    /// Products of the category and of the categories under it.
+    pub category_id: Option<CategoryId>,
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+    pub sort: SearchSort,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchHit {
+    pub product_id: CatalogProductId,
+    pub name: String,
+    pub category_id: Option<CategoryId>,
+    /// Lowest price of the variants in the price range, `None` for products
+    /// without variants.
+    pub price: Option<f64>,
+    pub rank: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CategoryFacet {
+    pub category_id: CategoryId,
+    pub name: String,
+    pub count: i64,
+}
+
+/// Products priced from `from` up to `to`, excluded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PriceFacet {
+    pub from: f64,
+    pub to: Option<f64>,
+    pub count: i64,
+}
+
+/// The first `MAX_HITS` hits, with the counts of all the matching products
+/// per category and per price bucket.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SearchResult {
+    pub hits: Vec<SearchHit>,
+    pub category_facets: Vec<CategoryFacet>,
+    pub price_facets: Vec<PriceFacet>,
+}
+
+/// Searches the catalog, in the database or in an external engine.
+#[automock]
+#[async_trait]
+pub trait ProductSearch {
+    async fn search(&self, query: &SearchQuery) -> Result<SearchResult, ProductSearchError>;
+}
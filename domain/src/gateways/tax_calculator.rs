@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use mockall::automock;
+use uuid::Uuid;
+
+use crate::{
+    entities::tax::{TaxCategory, TaxRate},
+    value_objects::{Address, CountryCode, OrderItem},
+};
+
+#[derive(Debug, PartialEq)]
+pub enum TaxCalculatorError {
+    TaxRulesUnavailableError(String),
+}
+
+impl std::fmt::Display for TaxCalculatorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaxCalculatorError::TaxRulesUnavailableError(message) => {
+                write!(f, "Tax rules unavailable: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TaxCalculatorError {}
+
+/// Finds the rates the items of an order are taxed at.
+#[automock]
+#[async_trait]
+pub trait TaxCalculator {
+    /// One rate per item, for items shipped to `address`.
+    async fn tax_rates(
+        &self,
+        address: &Address,
+        items: &[OrderItem],
+    ) -> Result<Vec<TaxRate>, TaxCalculatorError>;
+}
+
+/// Rate of a tax category in a country, or in a region of it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaxRule {
+    pub country: CountryCode,
+    /// Regions are told apart by the start of their postal codes, e.g. `35`
+    /// and `38` for the Canary Islands in Spain. `None` for the whole country.
+    pub region: Option<String>,
+    pub category: TaxCategory,
+    pub rate: f64,
+}
+
+/// Looks rates up in a table of rules. Products without a category are
+/// `standard`; items without a matching rule are not taxed.
+#[derive(Default)]
+pub struct TaxRuleTable {
+    rules: Vec<TaxRule>,
+    product_categories: HashMap<Uuid, TaxCategory>,
+}
+
+impl TaxRuleTable {
+    pub fn new(rules: Vec<TaxRule>, product_categories: HashMap<Uuid, TaxCategory>) -> Self {
+        Self {
+            rules,
+            product_categories,
+        }
+    }
+
+    pub fn rates(&self, address: &Address, items: &[OrderItem]) -> Vec<TaxRate> {
+        items
+            .iter()
+            .map(|item| {
+                let category = self
+                    .product_categories
+                    .get(&item.product_id.0)
+                    .copied()
+                    .unwrap_or_default();
+                TaxRate {
+                    product_id: item.product_id.clone(),
+                    category,
+                    rate: self
+                        .rule_for(address, category)
+                        .map_or(0.0, |rule| rule.rate),
+                }
+            })
+            .collect()
+    }
+
+    /// The rule of the most specific region the address is in.
+    fn rule_for(&self, address: &Address, category: TaxCategory) -> Option<&TaxRule> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.country == address.country && rule.category == category)
+            .filter(|rule| {
+                rule.region
+                    .as_ref()
+                    .is_none_or(|region| address.zip_code.as_str().starts_with(region.as_str()))
+            })
+            .max_by_key(|rule| rule.region.as_ref().map_or(0, String::len))
+    }
+}
+
+#[async_trait]
+impl TaxCalculator for TaxRuleTable {
+    async fn tax_rates(
+        &self,
+        address: &Address,
+        items: &[OrderItem],
+    ) -> Result<Vec<TaxRate>, TaxCalculatorError> {
+        Ok(self.rates(address, items))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::value_objects::{PostalCode, ProductId};
+
+    #[tokio::test]
+    async fn finds_the_rate_of_the_category_in_the_region_of_the_address() {
+        let table = TaxRuleTable::new(
+            vec![
+                rule(None, TaxCategory::Standard, 21.0),
+                rule(None, TaxCategory::Reduced, 10.0),
+                rule(Some("35"), TaxCategory::Standard, 7.0),
+            ],
+            HashMap::from([(product_id(2).0, TaxCategory::Reduced)]),
+        );
+        let items = [item(1), item(2)];
+
+        let madrid = table.tax_rates(&address("28001"), &items).await.unwrap();
+        let canary_islands = table.tax_rates(&address("35001"), &items).await.unwrap();
+
+        assert_eq!(
+            vec![(TaxCategory::Standard, 21.0), (TaxCategory::Reduced, 10.0)],
+            madrid
+                .iter()
+                .map(|rate| (rate.category, rate.rate))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![(TaxCategory::Standard, 7.0), (TaxCategory::Reduced, 10.0)],
+            canary_islands
+                .iter()
+                .map(|rate| (rate.category, rate.rate))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    fn rule(region: Option<&str>, category: TaxCategory, rate: f64) -> TaxRule {
+        TaxRule {
+            country: CountryCode::parse("ES").unwrap(),
+            region: region.map(str::to_string),
+            category,
+            rate,
+        }
+    }
+
+    fn address(zip_code: &str) -> Address {
+        let country = CountryCode::parse("ES").unwrap();
+        Address {
+            street: "Calle Mayor 1".to_string(),
+            city: "Madrid".to_string(),
+            zip_code: PostalCode::parse(zip_code, &country).unwrap(),
+            country,
+        }
+    }
+
+    fn item(n: u128) -> OrderItem {
+        OrderItem {
+            price: 10.0,
+            quantity: 1,
+            product_id: product_id(n),
+        }
+    }
+
+    fn product_id(n: u128) -> ProductId {
+        ProductId(Uuid::from_u128(n))
+    }
+}
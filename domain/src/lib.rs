@@ -1,4 +1,7 @@
 pub mod entities;
+#[cfg(test)]
+mod fixtures;
+pub mod gateways;
 pub mod publishers;
 pub mod repositories;
 pub mod services;
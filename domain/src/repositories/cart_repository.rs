@@ -0,0 +1,52 @@
+use async_trait::async_trait;
+use mockall::automock;
+
+use crate::{
+    entities::cart::Cart,
+    value_objects::{CartId, CustomerId},
+};
+
+#[derive(Debug)]
+pub enum CartRepositoryError {
+    CartNotReadError(String),
+    CartNotSavedError,
+    CartNotDeletedError,
+    ConcurrencyConflict,
+}
+
+impl std::fmt::Display for CartRepositoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CartRepositoryError::CartNotReadError(message) => {
+                write!(f, "Cart not read error: {}", message)
+            }
+            CartRepositoryError::CartNotSavedError => write!(f, "Cart not saved error"),
+            CartRepositoryError::CartNotDeletedError => write!(f, "Cart not deleted error"),
+            CartRepositoryError::ConcurrencyConflict => {
+                write!(f, "Cart was modified concurrently")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CartRepositoryError {}
+
+#[automock]
+#[async_trait]
+pub trait CartRepository {
+    async fn find_by_id(&self, id: CartId) -> Result<Option<Cart>, CartRepositoryError>;
+
+    /// A customer has at most one cart, expired ones included.
+    async fn find_by_customer_id(
+        &self,
+        customer_id: CustomerId,
+    ) -> Result<Option<Cart>, CartRepositoryError>;
+
+    async fn save(&self, cart: Cart) -> Result<Cart, CartRepositoryError>;
+
+    /// Fails with `ConcurrencyConflict` if the stored cart is no longer at
+    /// `cart.version`. Returns the cart with its new version.
+    async fn update(&self, cart: Cart) -> Result<Cart, CartRepositoryError>;
+
+    async fn delete(&self, id: CartId) -> Result<(), CartRepositoryError>;
+}
@@ -0,0 +1,92 @@
+use async_trait::async_trait;
+use mockall::automock;
+
+use crate::{
+    entities::{
+        category::Category,
+        product::{Product, Variant},
+    },
+    value_objects::{CatalogProductId, CategoryId, ProductId},
+};
+
+#[derive(Debug)]
+pub enum CatalogRepositoryError {
+    CategoryNotReadError(String),
+    CategoryNotSavedError,
+    CategoryAlreadyExistsError,
+    ProductNotReadError(String),
+    ProductNotSavedError,
+    ProductAlreadyExistsError,
+    /// A variant of another product has the same id.
+    VariantAlreadyExistsError,
+    /// A variant of another product has the same SKU.
+    SkuAlreadyExistsError,
+    ConcurrencyConflict,
+}
+
+impl std::fmt::Display for CatalogRepositoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CatalogRepositoryError::CategoryNotReadError(message) => {
+                write!(f, "Category not read error: {}", message)
+            }
+            CatalogRepositoryError::CategoryNotSavedError => write!(f, "Category not saved error"),
+            CatalogRepositoryError::CategoryAlreadyExistsError => {
+                write!(f, "A category with the same id exists")
+            }
+            CatalogRepositoryError::ProductNotReadError(message) => {
+                write!(f, "Product not read error: {}", message)
+            }
+            CatalogRepositoryError::ProductNotSavedError => write!(f, "Product not saved error"),
+            CatalogRepositoryError::ProductAlreadyExistsError => {
+                write!(f, "A product with the same id exists")
+            }
+            CatalogRepositoryError::VariantAlreadyExistsError => {
+                write!(f, "A variant with the same id exists")
+            }
+            CatalogRepositoryError::SkuAlreadyExistsError => {
+                write!(f, "A variant with the same SKU exists")
+            }
+            CatalogRepositoryError::ConcurrencyConflict => {
+                write!(f, "Product was modified concurrently")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CatalogRepositoryError {}
+
+#[automock]
+#[async_trait]
+pub trait CatalogRepository {
+    async fn find_category(
+        &self,
+        id: CategoryId,
+    ) -> Result<Option<Category>, CatalogRepositoryError>;
+
+    /// Fails with `CategoryAlreadyExistsError` if the id is taken.
+    async fn save_category(&self, category: Category) -> Result<Category, CatalogRepositoryError>;
+
+    async fn find_product(
+        &self,
+        id: CatalogProductId,
+    ) -> Result<Option<Product>, CatalogRepositoryError>;
+
+    /// The variant with the id, whatever its product.
+    async fn find_variant(&self, id: ProductId) -> Result<Option<Variant>, CatalogRepositoryError>;
+
+    /// Products of the category and of the categories under it, ordered by
+    /// name.
+    async fn find_products_in_category(
+        &self,
+        category_id: CategoryId,
+    ) -> Result<Vec<Product>, CatalogRepositoryError>;
+
+    /// Fails with `ProductAlreadyExistsError` if the id is taken. The prices
+    /// of the variants are their prices in the base currency.
+    async fn save_product(&self, product: Product) -> Result<Product, CatalogRepositoryError>;
+
+    /// Fails with `ConcurrencyConflict` if the stored product is no longer at
+    /// `product.version`. Returns the product with its new version.
+    async fn update_product(&self, product: Product) -> Result<Product, CatalogRepositoryError>;
+}
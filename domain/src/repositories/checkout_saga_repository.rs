@@ -0,0 +1,179 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mockall::mock;
+
+use crate::{
+    entities::checkout_saga::CheckoutSaga,
+    repositories::transactional_repository::{
+        TransactionalRepository, TransactionalRepositoryError,
+    },
+    value_objects::OrderId,
+};
+
+#[derive(Debug)]
+pub enum CheckoutSagaRepositoryError {
+    SagaNotReadError(String),
+    SagaNotSavedError,
+    ConcurrencyConflict,
+}
+
+impl std::fmt::Display for CheckoutSagaRepositoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckoutSagaRepositoryError::SagaNotReadError(message) => {
+                write!(f, "Checkout saga not read error: {}", message)
+            }
+            CheckoutSagaRepositoryError::SagaNotSavedError => {
+                write!(f, "Checkout saga not saved error")
+            }
+            CheckoutSagaRepositoryError::ConcurrencyConflict => {
+                write!(f, "Checkout saga was modified concurrently")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CheckoutSagaRepositoryError {}
+
+#[async_trait]
+pub trait CheckoutSagaRepository: TransactionalRepository {
+    async fn find_by_order_id(
+        &self,
+        order_id: OrderId,
+    ) -> Result<Option<CheckoutSaga>, CheckoutSagaRepositoryError>;
+
+    /// Unfinished sagas whose deadline is not after `now`, earliest first.
+    async fn find_timed_out(
+        &self,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<CheckoutSaga>, CheckoutSagaRepositoryError>;
+
+    /// Fails with `ConcurrencyConflict` if the order already has a saga.
+    async fn save(&self, saga: CheckoutSaga) -> Result<CheckoutSaga, CheckoutSagaRepositoryError>;
+
+    /// Fails with `ConcurrencyConflict` if the stored saga is no longer at
+    /// `saga.version`. Returns the saga with its new version.
+    async fn update(&self, saga: CheckoutSaga)
+        -> Result<CheckoutSaga, CheckoutSagaRepositoryError>;
+}
+
+mock! {
+    pub MyCheckoutSagaRepository {}
+
+    #[async_trait]
+    impl CheckoutSagaRepository for MyCheckoutSagaRepository {
+        async fn find_by_order_id(&self, order_id: OrderId) -> Result<Option<CheckoutSaga>, CheckoutSagaRepositoryError>;
+        async fn find_timed_out(&self, now: DateTime<Utc>) -> Result<Vec<CheckoutSaga>, CheckoutSagaRepositoryError>;
+        async fn save(&self, saga: CheckoutSaga) -> Result<CheckoutSaga, CheckoutSagaRepositoryError>;
+        async fn update(&self, saga: CheckoutSaga) -> Result<CheckoutSaga, CheckoutSagaRepositoryError>;
+    }
+
+    #[async_trait]
+    impl TransactionalRepository for MyCheckoutSagaRepository {
+        async fn begin_transaction(&mut self) -> Result<(), TransactionalRepositoryError>;
+        async fn commit_transaction(&mut self) -> Result<(), TransactionalRepositoryError>;
+        async fn rollback_transaction(&mut self) -> Result<(), TransactionalRepositoryError>;
+    }
+}
+
+#[cfg(test)]
+pub use in_memory::InMemoryCheckoutSagaRepository;
+
+#[cfg(test)]
+mod in_memory {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// Sagas kept in memory, for tests. Clones share the same sagas.
+    #[derive(Clone, Default)]
+    pub struct InMemoryCheckoutSagaRepository {
+        sagas: Arc<Mutex<Vec<CheckoutSaga>>>,
+    }
+
+    impl InMemoryCheckoutSagaRepository {
+        pub fn new() -> Self {
+            Self::default()
+        }
+    }
+
+    #[async_trait]
+    impl TransactionalRepository for InMemoryCheckoutSagaRepository {
+        async fn begin_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+            Ok(())
+        }
+        async fn commit_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+            Ok(())
+        }
+        async fn rollback_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl CheckoutSagaRepository for InMemoryCheckoutSagaRepository {
+        async fn find_by_order_id(
+            &self,
+            order_id: OrderId,
+        ) -> Result<Option<CheckoutSaga>, CheckoutSagaRepositoryError> {
+            Ok(self
+                .sagas
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|saga| saga.order_id() == &order_id)
+                .cloned())
+        }
+
+        async fn find_timed_out(
+            &self,
+            now: DateTime<Utc>,
+        ) -> Result<Vec<CheckoutSaga>, CheckoutSagaRepositoryError> {
+            let mut sagas: Vec<CheckoutSaga> = self
+                .sagas
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|saga| saga.is_timed_out(now))
+                .cloned()
+                .collect();
+            sagas.sort_by_key(|saga| saga.deadline());
+            Ok(sagas)
+        }
+
+        async fn save(
+            &self,
+            saga: CheckoutSaga,
+        ) -> Result<CheckoutSaga, CheckoutSagaRepositoryError> {
+            let mut sagas = self.sagas.lock().unwrap();
+            if sagas
+                .iter()
+                .any(|stored| stored.order_id() == saga.order_id())
+            {
+                return Err(CheckoutSagaRepositoryError::ConcurrencyConflict);
+            }
+            let saga = saga.with_version(1);
+            sagas.push(saga.clone());
+            Ok(saga)
+        }
+
+        async fn update(
+            &self,
+            saga: CheckoutSaga,
+        ) -> Result<CheckoutSaga, CheckoutSagaRepositoryError> {
+            let mut sagas = self.sagas.lock().unwrap();
+            let Some(stored) = sagas
+                .iter_mut()
+                .find(|stored| stored.order_id() == saga.order_id())
+            else {
+                return Err(CheckoutSagaRepositoryError::SagaNotSavedError);
+            };
+            if stored.version() != saga.version() {
+                return Err(CheckoutSagaRepositoryError::ConcurrencyConflict);
+            }
+            let version = saga.version() + 1;
+            *stored = saga.with_version(version);
+            Ok(stored.clone())
+        }
+    }
+}
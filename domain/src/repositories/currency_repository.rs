@@ -0,0 +1,138 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use mockall::automock;
+
+use crate::{
+    entities::currency::{ExchangeRate, ProductPrice},
+    value_objects::{Currency, ProductId},
+};
+
+#[derive(Debug)]
+pub enum CurrencyRepositoryError {
+    ExchangeRateNotReadError(String),
+    ExchangeRatesNotSavedError,
+    PriceNotReadError(String),
+    PriceNotSavedError,
+}
+
+impl std::fmt::Display for CurrencyRepositoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CurrencyRepositoryError::ExchangeRateNotReadError(message) => {
+                write!(f, "Exchange rate not read error: {}", message)
+            }
+            CurrencyRepositoryError::ExchangeRatesNotSavedError => {
+                write!(f, "Exchange rates not saved error")
+            }
+            CurrencyRepositoryError::PriceNotReadError(message) => {
+                write!(f, "Price not read error: {}", message)
+            }
+            CurrencyRepositoryError::PriceNotSavedError => write!(f, "Price not saved error"),
+        }
+    }
+}
+
+impl std::error::Error for CurrencyRepositoryError {}
+
+/// Exchange rates to the base currency and the price lists of products.
+#[automock]
+#[async_trait]
+pub trait CurrencyRepository {
+    /// Saves all the rates or none, replacing those of the same currency and day.
+    async fn save_rates(&self, rates: Vec<ExchangeRate>) -> Result<(), CurrencyRepositoryError>;
+
+    /// The latest rate of the currency effective on `day`.
+    async fn find_rate(
+        &self,
+        currency: Currency,
+        day: NaiveDate,
+    ) -> Result<Option<ExchangeRate>, CurrencyRepositoryError>;
+
+    /// Replaces the price of the product in the same currency.
+    async fn save_price(&self, price: ProductPrice) -> Result<(), CurrencyRepositoryError>;
+
+    /// The prices of the product, one per currency.
+    async fn find_prices(
+        &self,
+        product_id: ProductId,
+    ) -> Result<Vec<ProductPrice>, CurrencyRepositoryError>;
+}
+
+#[cfg(test)]
+pub use in_memory::InMemoryCurrencyRepository;
+
+#[cfg(test)]
+mod in_memory {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// Rates and prices kept in memory, for tests. Clones share the same data.
+    #[derive(Clone, Default)]
+    pub struct InMemoryCurrencyRepository {
+        rates: Arc<Mutex<Vec<ExchangeRate>>>,
+        prices: Arc<Mutex<Vec<ProductPrice>>>,
+    }
+
+    impl InMemoryCurrencyRepository {
+        pub fn new() -> Self {
+            Self::default()
+        }
+    }
+
+    #[async_trait]
+    impl CurrencyRepository for InMemoryCurrencyRepository {
+        async fn save_rates(
+            &self,
+            rates: Vec<ExchangeRate>,
+        ) -> Result<(), CurrencyRepositoryError> {
+            let mut saved = self.rates.lock().unwrap();
+            for rate in rates {
+                saved.retain(|saved| {
+                    saved.currency() != rate.currency()
+                        || saved.effective_from() != rate.effective_from()
+                });
+                saved.push(rate);
+            }
+            Ok(())
+        }
+
+        async fn find_rate(
+            &self,
+            currency: Currency,
+            day: NaiveDate,
+        ) -> Result<Option<ExchangeRate>, CurrencyRepositoryError> {
+            Ok(self
+                .rates
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|rate| rate.currency() == currency && rate.effective_from() <= day)
+                .max_by_key(|rate| rate.effective_from())
+                .cloned())
+        }
+
+        async fn save_price(&self, price: ProductPrice) -> Result<(), CurrencyRepositoryError> {
+            let mut prices = self.prices.lock().unwrap();
+            prices.retain(|saved| {
+                saved.product_id != price.product_id || saved.currency != price.currency
+            });
+            prices.push(price);
+            Ok(())
+        }
+
+        async fn find_prices(
+            &self,
+            product_id: ProductId,
+        ) -> Result<Vec<ProductPrice>, CurrencyRepositoryError> {
+            Ok(self
+                .prices
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|price| price.product_id == product_id)
+                .cloned()
+                .collect())
+        }
+    }
+}
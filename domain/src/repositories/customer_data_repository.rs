@@ -0,0 +1,48 @@
+use async_trait::async_trait;
+use mockall::automock;
+
+use crate::entities::{customer::Customer, outbox::OutboxMessage};
+
+#[derive(Debug)]
+pub enum CustomerDataRepositoryError {
+    CustomerNotFoundError,
+    ConcurrencyConflict,
+    CustomerNotErasedError(String),
+}
+
+impl std::fmt::Display for CustomerDataRepositoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CustomerDataRepositoryError::CustomerNotFoundError => {
+                write!(f, "Customer not found error")
+            }
+            CustomerDataRepositoryError::ConcurrencyConflict => {
+                write!(f, "Customer was modified concurrently")
+            }
+            CustomerDataRepositoryError::CustomerNotErasedError(message) => {
+                write!(f, "Customer not erased error {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CustomerDataRepositoryError {}
+
+/// Personal data of a customer spread across customers, orders and outbox
+/// messages, written all or nothing.
+#[automock]
+#[async_trait]
+pub trait CustomerDataRepository {
+    /// In one transaction, overwrites the stored personal data with the one
+    /// of the erased `customer` and removes its address book, erases the
+    /// addresses of its orders, replaces the payloads of `anonymized_events`
+    /// and saves `erased_event`. Fails with `ConcurrencyConflict` if the stored
+    /// customer is no longer at `customer.version`. Returns the customer with
+    /// its new version.
+    async fn erase(
+        &self,
+        customer: Customer,
+        anonymized_events: Vec<OutboxMessage>,
+        erased_event: OutboxMessage,
+    ) -> Result<Customer, CustomerDataRepositoryError>;
+}
@@ -6,13 +6,16 @@ use crate::{
     repositories::transactional_repository::{
         TransactionalRepository, TransactionalRepositoryError,
     },
-    value_objects::CustomerId,
+    value_objects::{CustomerId, Email},
 };
 
 #[derive(Debug)]
 pub enum CustomerRepositoryError {
     CustomerNotFoundError,
+    CustomerNotReadError(String),
     CustomerNotSavedError,
+    CustomerAlreadyExists,
+    ConcurrencyConflict,
     ConnectionNotCreatedError,
 }
 
@@ -23,7 +26,16 @@ impl std::fmt::Display for CustomerRepositoryError {
                 write!(f, "Connection not created error")
             }
             CustomerRepositoryError::CustomerNotFoundError => write!(f, "Customer not found error"),
+            CustomerRepositoryError::CustomerNotReadError(error) => {
+                write!(f, "Customer not read error: {}", error)
+            }
             CustomerRepositoryError::CustomerNotSavedError => write!(f, "Customer not saved error"),
+            CustomerRepositoryError::CustomerAlreadyExists => {
+                write!(f, "Customer already exists")
+            }
+            CustomerRepositoryError::ConcurrencyConflict => {
+                write!(f, "Customer was modified concurrently")
+            }
         }
     }
 }
@@ -35,7 +47,22 @@ pub trait CustomerRepository: TransactionalRepository {
     async fn find_by_id(&self, id: CustomerId)
         -> Result<Option<Customer>, CustomerRepositoryError>;
 
+    /// Emails are compared regardless of case.
+    async fn find_by_email(
+        &self,
+        email: &Email,
+    ) -> Result<Option<Customer>, CustomerRepositoryError>;
+
+    /// Fails with `CustomerAlreadyExists` if another customer has the same email.
     async fn save(&self, customer: Customer) -> Result<Customer, CustomerRepositoryError>;
+
+    /// Fails with `ConcurrencyConflict` if the stored customer is no longer at
+    /// `customer.version`. Returns the customer with its new version.
+    async fn update(&self, customer: Customer) -> Result<Customer, CustomerRepositoryError>;
+
+    /// Soft-deletes the customer: the row is kept and marked with
+    /// `customer.deactivated_at`. Fails like `update` on a stale version.
+    async fn delete(&self, customer: Customer) -> Result<Customer, CustomerRepositoryError>;
 }
 
 mock! {
@@ -44,7 +71,10 @@ mock! {
     #[async_trait]
     impl CustomerRepository for MyCustomerRepository {
         async fn find_by_id(&self, id: CustomerId) -> Result<Option<Customer>, CustomerRepositoryError>;
+        async fn find_by_email(&self, email: &Email) -> Result<Option<Customer>, CustomerRepositoryError>;
         async fn save(&self, customer: Customer) -> Result<Customer, CustomerRepositoryError>;
+        async fn update(&self, customer: Customer) -> Result<Customer, CustomerRepositoryError>;
+        async fn delete(&self, customer: Customer) -> Result<Customer, CustomerRepositoryError>;
     }
 
     #[async_trait]
@@ -0,0 +1,718 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    entities::{
+        currency::OrderCurrency,
+        order::{Order, OrderAddresses, OrderEvent, OrderStatus},
+        order_lines::{OrderLines, OrderLinesError},
+        outbox::{
+            AddressPayload, CurrencyPayload, OrderCreatedEvent, OutboxMessageType,
+            ProductAddedToOrderEvent, PromotionPayload, ShippingPayload, TaxesPayload,
+        },
+        promotion::AppliedPromotion,
+        shipping_method::OrderShipping,
+        tax::Taxes,
+    },
+    repositories::{
+        event_store::{EventStore, EventStoreError, NewEvent, Snapshot, StoredEvent},
+        order_repository::{OrderRepository, OrderRepositoryError},
+        transactional_repository::{TransactionalRepository, TransactionalRepositoryError},
+    },
+    value_objects::{CustomerId, InvalidValueError, OrderId, OrderItem, ProductId},
+};
+
+/// A snapshot is saved every time the stream grows past a multiple of this.
+const SNAPSHOT_FREQUENCY: i64 = 20;
+
+// Order creation and added products are stored like the matching outbox
+// messages; these changes are stored with payloads of their own.
+const PRODUCT_REMOVED_FROM_ORDER: &str = "product_removed_from_order";
+const PRODUCT_QUANTITY_CHANGED: &str = "product_quantity_changed";
+const ORDER_STATUS_CHANGED: &str = "order_status_changed";
+const PROMOTION_APPLIED: &str = "promotion_applied";
+const TAXES_CHANGED: &str = "taxes_changed";
+
+#[derive(Serialize, Deserialize)]
+struct ProductRemovedFromOrderPayload {
+    product_id: Uuid,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProductQuantityChangedPayload {
+    product_id: Uuid,
+    quantity: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct OrderStatusChangedPayload {
+    status: String,
+}
+
+/// Stores orders as a stream of `OrderEvent`s, one stream per order.
+pub struct EventSourcedOrderRepository {
+    event_store: Box<dyn EventStore + Send + Sync>,
+}
+
+impl EventSourcedOrderRepository {
+    pub fn new(event_store: Box<dyn EventStore + Send + Sync>) -> Self {
+        Self { event_store }
+    }
+
+    async fn load(&self, id: &OrderId) -> Result<Option<Order>, OrderRepositoryError> {
+        let snapshot = self
+            .event_store
+            .load_snapshot(id.0)
+            .await
+            .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))?;
+        let (order, version) = match snapshot {
+            Some(snapshot) => (Some(from_snapshot(&snapshot)?), snapshot.version),
+            None => (None, 0),
+        };
+
+        let stored_events = self
+            .event_store
+            .load(id.0, version)
+            .await
+            .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))?;
+        let version = stored_events.last().map_or(version, |e| e.version);
+        let events = stored_events
+            .iter()
+            .map(from_stored_event)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let not_read = |e: OrderLinesError| OrderRepositoryError::OrderNotReadError(e.to_string());
+        let order = match order {
+            Some(mut order) => {
+                for event in events {
+                    order.apply(event).map_err(not_read)?;
+                }
+                Some(order)
+            }
+            None => Order::rehydrate(events).map_err(not_read)?,
+        };
+        Ok(order.map(|order| order.with_version(version)))
+    }
+
+    /// Appends `events` at the order's version and returns the new one.
+    async fn append(&self, order: &Order, events: Vec<OrderEvent>) -> Result<i64, EventStoreError> {
+        let new_events = events
+            .iter()
+            .map(|event| to_new_event(order.id(), event))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| EventStoreError::EventsNotSavedError(e.to_string()))?;
+        let version = self
+            .event_store
+            .append(order.id().0, order.version(), new_events)
+            .await?;
+
+        if version / SNAPSHOT_FREQUENCY > order.version() / SNAPSHOT_FREQUENCY {
+            let snapshot = to_snapshot(order, version)
+                .map_err(|e| EventStoreError::SnapshotNotSavedError(e.to_string()))?;
+            self.event_store.save_snapshot(snapshot).await?;
+        }
+        Ok(version)
+    }
+}
+
+#[async_trait]
+impl TransactionalRepository for EventSourcedOrderRepository {
+    // Every append is atomic on its own.
+    async fn begin_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        Ok(())
+    }
+    async fn commit_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        Ok(())
+    }
+    async fn rollback_transaction(&mut self) -> Result<(), TransactionalRepositoryError> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OrderRepository for EventSourcedOrderRepository {
+    async fn find_by_id(&self, id: OrderId) -> Result<Option<Order>, OrderRepositoryError> {
+        self.load(&id).await
+    }
+
+    /// Streams are not indexed by customer, so orders can't be looked up this way.
+    async fn find_by_customer_id(&self, _: CustomerId) -> Result<Vec<Order>, OrderRepositoryError> {
+        Err(OrderRepositoryError::OrderNotReadError(
+            "orders are not indexed by customer in the event store".to_string(),
+        ))
+    }
+
+    async fn save(&self, order: Order) -> Result<Order, OrderRepositoryError> {
+        let mut events = vec![OrderEvent::OrderCreated {
+            order_id: order.id().clone(),
+            customer_id: order.customer_id().clone(),
+            addresses: order.addresses().cloned(),
+            currency: *order.currency(),
+            shipping: order.shipping().cloned(),
+        }];
+        events.extend(order.changes_since(&[]));
+        events.extend(order.status_change_since(OrderStatus::Draft));
+        events.extend(order.promotions_since(&[]));
+        events.extend(order.taxes_change_since(&Taxes::default()));
+
+        let order = order.with_version(0);
+        let version = self
+            .append(&order, events)
+            .await
+            .map_err(|_| OrderRepositoryError::OrderNotSavedError)?;
+        Ok(order.with_version(version))
+    }
+
+    async fn update(&self, order: Order) -> Result<Order, OrderRepositoryError> {
+        let Some(stored_order) = self.load(order.id()).await? else {
+            return Err(OrderRepositoryError::OrderNotFoundError);
+        };
+        if stored_order.version() != order.version() {
+            return Err(OrderRepositoryError::ConcurrencyConflict);
+        }
+
+        let mut events = order.changes_since(stored_order.order_items());
+        events.extend(order.status_change_since(stored_order.status()));
+        events.extend(order.promotions_since(stored_order.promotions()));
+        events.extend(order.taxes_change_since(stored_order.taxes()));
+        if events.is_empty() {
+            return Ok(order);
+        }
+        let version = self.append(&order, events).await.map_err(|e| match e {
+            EventStoreError::VersionConflictError { .. } => {
+                OrderRepositoryError::ConcurrencyConflict
+            }
+            _ => OrderRepositoryError::OrderNotSavedError,
+        })?;
+        Ok(order.with_version(version))
+    }
+}
+
+fn to_new_event(order_id: &OrderId, event: &OrderEvent) -> Result<NewEvent, OrderRepositoryError> {
+    let (event_type, event_payload) = match event {
+        OrderEvent::OrderCreated {
+            customer_id,
+            addresses,
+            currency,
+            shipping,
+            ..
+        } => (
+            OutboxMessageType::OrderCreated.to_string(),
+            serde_json::to_string(&OrderCreatedEvent::new(
+                order_id,
+                customer_id,
+                addresses.as_ref(),
+                currency,
+                shipping.as_ref(),
+            )),
+        ),
+        OrderEvent::ProductAddedToOrder {
+            product_id,
+            price,
+            quantity,
+        } => (
+            OutboxMessageType::ProductAddedToOrder.to_string(),
+            serde_json::to_string(&ProductAddedToOrderEvent {
+                order_id: order_id.0.to_string(),
+                product_id: product_id.0.to_string(),
+                quantity: *quantity,
+                price: *price,
+                currency: None,
+            }),
+        ),
+        OrderEvent::ProductRemovedFromOrder { product_id } => (
+            PRODUCT_REMOVED_FROM_ORDER.to_string(),
+            serde_json::to_string(&ProductRemovedFromOrderPayload {
+                product_id: product_id.0,
+            }),
+        ),
+        OrderEvent::ProductQuantityChanged {
+            product_id,
+            quantity,
+        } => (
+            PRODUCT_QUANTITY_CHANGED.to_string(),
+            serde_json::to_string(&ProductQuantityChangedPayload {
+                product_id: product_id.0,
+                quantity: *quantity,
+            }),
+        ),
+        OrderEvent::OrderStatusChanged { status } => (
+            ORDER_STATUS_CHANGED.to_string(),
+            serde_json::to_string(&OrderStatusChangedPayload {
+                status: status.to_string(),
+            }),
+        ),
+        OrderEvent::PromotionApplied { promotion } => (
+            PROMOTION_APPLIED.to_string(),
+            serde_json::to_string(&PromotionPayload::from(promotion)),
+        ),
+        OrderEvent::TaxesChanged { taxes } => (
+            TAXES_CHANGED.to_string(),
+            serde_json::to_string(&TaxesPayload::from(taxes)),
+        ),
+    };
+    Ok(NewEvent {
+        event_type,
+        event_payload: event_payload.map_err(|_| OrderRepositoryError::OrderNotSavedError)?,
+    })
+}
+
+fn from_stored_event(event: &StoredEvent) -> Result<OrderEvent, OrderRepositoryError> {
+    let not_read = |e: String| OrderRepositoryError::OrderNotReadError(e);
+    match event.event_type.as_str() {
+        PRODUCT_REMOVED_FROM_ORDER => {
+            let payload: ProductRemovedFromOrderPayload =
+                serde_json::from_str(&event.event_payload).map_err(|e| not_read(e.to_string()))?;
+            return Ok(OrderEvent::ProductRemovedFromOrder {
+                product_id: ProductId(payload.product_id),
+            });
+        }
+        PRODUCT_QUANTITY_CHANGED => {
+            let payload: ProductQuantityChangedPayload =
+                serde_json::from_str(&event.event_payload).map_err(|e| not_read(e.to_string()))?;
+            return Ok(OrderEvent::ProductQuantityChanged {
+                product_id: ProductId(payload.product_id),
+                quantity: payload.quantity,
+            });
+        }
+        ORDER_STATUS_CHANGED => {
+            let payload: OrderStatusChangedPayload =
+                serde_json::from_str(&event.event_payload).map_err(|e| not_read(e.to_string()))?;
+            return Ok(OrderEvent::OrderStatusChanged {
+                status: payload.status.parse().map_err(not_read)?,
+            });
+        }
+        PROMOTION_APPLIED => {
+            let payload: PromotionPayload =
+                serde_json::from_str(&event.event_payload).map_err(|e| not_read(e.to_string()))?;
+            return Ok(OrderEvent::PromotionApplied {
+                promotion: AppliedPromotion::try_from(&payload)
+                    .map_err(|e| not_read(e.to_string()))?,
+            });
+        }
+        TAXES_CHANGED => {
+            let payload: TaxesPayload =
+                serde_json::from_str(&event.event_payload).map_err(|e| not_read(e.to_string()))?;
+            return Ok(OrderEvent::TaxesChanged {
+                taxes: Taxes::try_from(&payload).map_err(not_read)?,
+            });
+        }
+        _ => {}
+    }
+
+    let event_type: OutboxMessageType = event.event_type.parse().map_err(not_read)?;
+    match event_type {
+        OutboxMessageType::OrderCreated => {
+            let payload: OrderCreatedEvent =
+                serde_json::from_str(&event.event_payload).map_err(|e| not_read(e.to_string()))?;
+            Ok(OrderEvent::OrderCreated {
+                order_id: OrderId(parse_uuid(&payload.id)?),
+                customer_id: CustomerId(parse_uuid(&payload.customer_id)?),
+                addresses: payload.addresses().map_err(|e| not_read(e.to_string()))?,
+                currency: payload.currency().map_err(|e| not_read(e.to_string()))?,
+                shipping: payload.shipping().map_err(not_read)?,
+            })
+        }
+        OutboxMessageType::ProductAddedToOrder => {
+            let payload: ProductAddedToOrderEvent =
+                serde_json::from_str(&event.event_payload).map_err(|e| not_read(e.to_string()))?;
+            Ok(OrderEvent::ProductAddedToOrder {
+                product_id: ProductId(parse_uuid(&payload.product_id)?),
+                price: payload.price,
+                quantity: payload.quantity,
+            })
+        }
+        other => Err(not_read(format!("Unexpected order event: {}", other))),
+    }
+}
+
+fn parse_uuid(id: &str) -> Result<Uuid, OrderRepositoryError> {
+    Uuid::try_parse(id).map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))
+}
+
+#[derive(Serialize, Deserialize)]
+struct OrderSnapshot {
+    id: Uuid,
+    customer_id: Uuid,
+    order_items: Vec<OrderItemSnapshot>,
+    #[serde(default)]
+    addresses: Option<OrderAddressesSnapshot>,
+    /// Missing in snapshots taken before orders had a status.
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    promotions: Vec<PromotionPayload>,
+    #[serde(default)]
+    taxes: Option<TaxesPayload>,
+    #[serde(default)]
+    currency: Option<CurrencyPayload>,
+    #[serde(default)]
+    shipping: Option<ShippingPayload>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct OrderAddressesSnapshot {
+    shipping: AddressPayload,
+    billing: AddressPayload,
+}
+
+#[derive(Serialize, Deserialize)]
+struct OrderItemSnapshot {
+    product_id: Uuid,
+    price: f64,
+    quantity: i32,
+}
+
+fn to_snapshot(order: &Order, version: i64) -> Result<Snapshot, OrderRepositoryError> {
+    let snapshot_payload = serde_json::to_string(&OrderSnapshot {
+        id: order.id().0,
+        customer_id: order.customer_id().0,
+        order_items: order
+            .order_items()
+            .iter()
+            .map(|item| OrderItemSnapshot {
+                product_id: item.product_id.0,
+                price: item.price,
+                quantity: item.quantity,
+            })
+            .collect(),
+        addresses: order.addresses().map(|addresses| OrderAddressesSnapshot {
+            shipping: AddressPayload::from(&addresses.shipping),
+            billing: AddressPayload::from(&addresses.billing),
+        }),
+        status: Some(order.status().to_string()),
+        promotions: order
+            .promotions()
+            .iter()
+            .map(PromotionPayload::from)
+            .collect(),
+        taxes: Some(TaxesPayload::from(order.taxes())),
+        currency: Some(CurrencyPayload::from(order.currency())),
+        shipping: order.shipping().map(ShippingPayload::from),
+    })
+    .map_err(|_| OrderRepositoryError::OrderNotSavedError)?;
+    Ok(Snapshot {
+        stream_id: order.id().0,
+        version,
+        snapshot_payload,
+        created_at: Utc::now(),
+    })
+}
+
+fn from_snapshot(snapshot: &Snapshot) -> Result<Order, OrderRepositoryError> {
+    let order_snapshot: OrderSnapshot = serde_json::from_str(&snapshot.snapshot_payload)
+        .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))?;
+    let order_lines = OrderLines::try_from(
+        order_snapshot
+            .order_items
+            .into_iter()
+            .map(|item| OrderItem {
+                price: item.price,
+                quantity: item.quantity,
+                product_id: ProductId(item.product_id),
+            })
+            .collect::<Vec<_>>(),
+    )
+    .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))?;
+    let addresses = order_snapshot
+        .addresses
+        .map(|addresses| {
+            Ok::<_, InvalidValueError>(OrderAddresses {
+                shipping: (&addresses.shipping).try_into()?,
+                billing: (&addresses.billing).try_into()?,
+            })
+        })
+        .transpose()
+        .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))?;
+    let status = match order_snapshot.status {
+        Some(status) => status
+            .parse()
+            .map_err(OrderRepositoryError::OrderNotReadError)?,
+        None => OrderStatus::Draft,
+    };
+    let promotions = order_snapshot
+        .promotions
+        .iter()
+        .map(AppliedPromotion::try_from)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))?;
+    let taxes = order_snapshot
+        .taxes
+        .as_ref()
+        .map(Taxes::try_from)
+        .transpose()
+        .map_err(OrderRepositoryError::OrderNotReadError)?
+        .unwrap_or_default();
+    let currency = order_snapshot
+        .currency
+        .as_ref()
+        .map(OrderCurrency::try_from)
+        .transpose()
+        .map_err(|e| OrderRepositoryError::OrderNotReadError(e.to_string()))?
+        .unwrap_or_default();
+    let shipping = order_snapshot
+        .shipping
+        .as_ref()
+        .map(OrderShipping::try_from)
+        .transpose()
+        .map_err(OrderRepositoryError::OrderNotReadError)?;
+    let order = Order::restore(
+        OrderId(order_snapshot.id),
+        CustomerId(order_snapshot.customer_id),
+        order_lines,
+        addresses,
+        snapshot.version,
+    )
+    .with_status(status)
+    .with_promotions(promotions)
+    .with_taxes(taxes)
+    .with_currency(currency);
+    Ok(match shipping {
+        Some(shipping) => order.with_shipping(shipping),
+        None => order,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use mockall::predicate::eq;
+
+    use crate::{
+        repositories::event_store::{InMemoryEventStore, MockEventStore},
+        value_objects::{Address, CountryCode, PostalCode},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn saves_and_finds_an_order() {
+        let repository = EventSourcedOrderRepository::new(Box::new(InMemoryEventStore::new()));
+        let order_id = OrderId(Uuid::new_v4());
+        let customer_id = CustomerId(Uuid::new_v4());
+        let country = CountryCode::parse("IT").unwrap();
+        let addresses = OrderAddresses {
+            shipping: Address {
+                street: "Via Dante 1".to_string(),
+                city: "Milano".to_string(),
+                zip_code: PostalCode::parse("20121", &country).unwrap(),
+                country: country.clone(),
+            },
+            billing: Address {
+                street: "Via Roma 2".to_string(),
+                city: "Torino".to_string(),
+                zip_code: PostalCode::parse("10121", &country).unwrap(),
+                country,
+            },
+        };
+        repository
+            .save(
+                Order::create(order_id.clone(), customer_id.clone())
+                    .with_addresses(addresses.clone()),
+            )
+            .await
+            .unwrap();
+
+        let order = repository
+            .find_by_id(order_id.clone())
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(&order_id, order.id());
+        assert_eq!(&customer_id, order.customer_id());
+        assert_eq!(Some(&addresses), order.addresses());
+    }
+
+    #[tokio::test]
+    async fn appends_added_products() {
+        let repository = EventSourcedOrderRepository::new(Box::new(InMemoryEventStore::new()));
+        let order_id = OrderId(Uuid::new_v4());
+        repository
+            .save(Order::create(order_id.clone(), CustomerId(Uuid::new_v4())))
+            .await
+            .unwrap();
+
+        for _ in 0..2 {
+            let mut order = repository
+                .find_by_id(order_id.clone())
+                .await
+                .unwrap()
+                .unwrap();
+            order.add(order_item()).unwrap();
+            repository.update(order).await.unwrap();
+        }
+
+        let order = repository.find_by_id(order_id).await.unwrap().unwrap();
+        assert_eq!(2, order.order_items().len());
+        assert_eq!(3, order.version());
+    }
+
+    #[tokio::test]
+    async fn stores_removed_products_and_changed_quantities() {
+        let repository = EventSourcedOrderRepository::new(Box::new(InMemoryEventStore::new()));
+        let order_id = OrderId(Uuid::new_v4());
+        let mut order = Order::create(order_id.clone(), CustomerId(Uuid::new_v4()));
+        let kept = order_item();
+        let removed = order_item();
+        order
+            .add_multiple(vec![kept.clone(), removed.clone()])
+            .unwrap();
+        let mut order = repository.save(order).await.unwrap();
+
+        order.remove(&removed.product_id).unwrap();
+        order.change_quantity(&kept.product_id, 5).unwrap();
+        repository.update(order).await.unwrap();
+
+        let order = repository.find_by_id(order_id).await.unwrap().unwrap();
+        assert_eq!(
+            vec![OrderItem {
+                quantity: 5,
+                ..kept
+            }],
+            order.order_items()
+        );
+        assert_eq!(5, order.version());
+    }
+
+    #[tokio::test]
+    async fn rejects_concurrent_updates() {
+        let repository = EventSourcedOrderRepository::new(Box::new(InMemoryEventStore::new()));
+        let order_id = OrderId(Uuid::new_v4());
+        repository
+            .save(Order::create(order_id.clone(), CustomerId(Uuid::new_v4())))
+            .await
+            .unwrap();
+        let mut first = repository
+            .find_by_id(order_id.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        let mut second = repository
+            .find_by_id(order_id.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        first.add(order_item()).unwrap();
+        second.add(order_item()).unwrap();
+
+        let (first, second) = tokio::join!(repository.update(first), repository.update(second));
+
+        assert_eq!(
+            1,
+            [&first, &second]
+                .iter()
+                .filter(|result| result.is_ok())
+                .count()
+        );
+        assert!([first, second]
+            .into_iter()
+            .any(|result| matches!(result, Err(OrderRepositoryError::ConcurrencyConflict))));
+        let order = repository.find_by_id(order_id).await.unwrap().unwrap();
+        assert_eq!(1, order.order_items().len());
+    }
+
+    #[tokio::test]
+    async fn does_not_find_a_missing_order() {
+        let repository = EventSourcedOrderRepository::new(Box::new(InMemoryEventStore::new()));
+
+        let order = repository
+            .find_by_id(OrderId(Uuid::new_v4()))
+            .await
+            .unwrap();
+
+        assert!(order.is_none());
+    }
+
+    #[tokio::test]
+    async fn cannot_save_an_order_twice() {
+        let repository = EventSourcedOrderRepository::new(Box::new(InMemoryEventStore::new()));
+        let order_id = OrderId(Uuid::new_v4());
+        let customer_id = CustomerId(Uuid::new_v4());
+        repository
+            .save(Order::create(order_id.clone(), customer_id.clone()))
+            .await
+            .unwrap();
+
+        let result = repository.save(Order::create(order_id, customer_id)).await;
+
+        assert!(matches!(
+            result,
+            Err(OrderRepositoryError::OrderNotSavedError)
+        ));
+    }
+
+    #[tokio::test]
+    async fn takes_a_snapshot_every_few_events() {
+        let event_store = InMemoryEventStore::new();
+        let repository = EventSourcedOrderRepository::new(Box::new(event_store.clone()));
+        let order_id = OrderId(Uuid::new_v4());
+        let mut order = Order::create(order_id.clone(), CustomerId(Uuid::new_v4()));
+        order
+            .add_multiple((0..SNAPSHOT_FREQUENCY).map(|_| order_item()).collect())
+            .unwrap();
+
+        repository.save(order).await.unwrap();
+
+        let snapshot = event_store
+            .load_snapshot(order_id.0)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(SNAPSHOT_FREQUENCY + 1, snapshot.version);
+    }
+
+    #[tokio::test]
+    async fn rehydrates_from_snapshot_and_later_events() {
+        let order_id = Uuid::new_v4();
+        let mut snapshot_order = Order::create(OrderId(order_id), CustomerId(Uuid::new_v4()));
+        snapshot_order.add(order_item()).unwrap();
+        let snapshot = to_snapshot(&snapshot_order, 2).unwrap();
+        let later_event = to_new_event(
+            &OrderId(order_id),
+            &OrderEvent::ProductAddedToOrder {
+                product_id: ProductId(Uuid::new_v4()),
+                price: 9.99,
+                quantity: 1,
+            },
+        )
+        .unwrap();
+
+        let mut event_store = MockEventStore::new();
+        event_store
+            .expect_load_snapshot()
+            .with(eq(order_id))
+            .return_once(|_| Ok(Some(snapshot)));
+        event_store
+            .expect_load()
+            .with(eq(order_id), eq(2))
+            .return_once(move |stream_id, _| {
+                Ok(vec![StoredEvent {
+                    stream_id,
+                    version: 3,
+                    event_type: later_event.event_type,
+                    event_payload: later_event.event_payload,
+                    created_at: Utc::now(),
+                }])
+            });
+        let repository = EventSourcedOrderRepository::new(Box::new(event_store));
+
+        let order = repository
+            .find_by_id(OrderId(order_id))
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(2, order.order_items().len());
+        assert_eq!(3, order.version());
+    }
+
+    fn order_item() -> OrderItem {
+        OrderItem {
+            price: 9.99,
+            quantity: 1,
+            product_id: ProductId(Uuid::new_v4()),
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mockall::automock;
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub enum EventStoreError {
+    VersionConflictError { expected: i64, actual: i64 },
+    EventsNotSavedError(String),
+    EventsNotReadError(String),
+    SnapshotNotSavedError(String),
+    SnapshotNotReadError(String),
+}
+
+impl std::fmt::Display for EventStoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventStoreError::VersionConflictError { expected, actual } => write!(
+                f,
+                "Version conflict error: expected version {}, actual version {}",
+                expected, actual
+            ),
+            EventStoreError::EventsNotSavedError(message) => {
+                write!(f, "Events not saved error: {}", message)
+            }
+            EventStoreError::EventsNotReadError(message) => {
+                write!(f, "Events not read error: {}", message)
+            }
+            EventStoreError::SnapshotNotSavedError(message) => {
+                write!(f, "Snapshot not saved error: {}", message)
+            }
+            EventStoreError::SnapshotNotReadError(message) => {
+                write!(f, "Snapshot not read error: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EventStoreError {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NewEvent {
+    pub event_type: String,
+    pub event_payload: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoredEvent {
+    pub stream_id: Uuid,
+    pub version: i64,
+    pub event_type: String,
+    pub event_payload: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// State of a stream at `version`, so that older events don't need to be read.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub stream_id: Uuid,
+    pub version: i64,
+    pub snapshot_payload: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[automock]
+#[async_trait]
+pub trait EventStore {
+    /// Appends `events` to the stream if its version is still `expected_version`
+    /// (0 for a new stream). Returns the version after the last appended event.
+    async fn append(
+        &self,
+        stream_id: Uuid,
+        expected_version: i64,
+        events: Vec<NewEvent>,
+    ) -> Result<i64, EventStoreError>;
+
+    /// Returns the events of the stream after `after_version`, in order.
+    async fn load(
+        &self,
+        stream_id: Uuid,
+        after_version: i64,
+    ) -> Result<Vec<StoredEvent>, EventStoreError>;
+
+    async fn save_snapshot(&self, snapshot: Snapshot) -> Result<(), EventStoreError>;
+
+    async fn load_snapshot(&self, stream_id: Uuid) -> Result<Option<Snapshot>, EventStoreError>;
+}
+
+#[cfg(test)]
+pub use in_memory::InMemoryEventStore;
+
+#[cfg(test)]
+mod in_memory {
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+
+    use super::*;
+
+    /// Event store kept in memory, for tests. Clones share the same events.
+    #[derive(Clone, Default)]
+    pub struct InMemoryEventStore {
+        events: Arc<Mutex<Vec<StoredEvent>>>,
+        snapshots: Arc<Mutex<HashMap<Uuid, Snapshot>>>,
+    }
+
+    impl InMemoryEventStore {
+        pub fn new() -> Self {
+            Self::default()
+        }
+    }
+
+    #[async_trait]
+    impl EventStore for InMemoryEventStore {
+        async fn append(
+            &self,
+            stream_id: Uuid,
+            expected_version: i64,
+            events: Vec<NewEvent>,
+        ) -> Result<i64, EventStoreError> {
+            let mut stored_events = self.events.lock().unwrap();
+            let actual = stored_events
+                .iter()
+                .filter(|e| e.stream_id == stream_id)
+                .count() as i64;
+            if actual != expected_version {
+                return Err(EventStoreError::VersionConflictError {
+                    expected: expected_version,
+                    actual,
+                });
+            }
+
+            let mut version = actual;
+            for event in events {
+                version += 1;
+                stored_events.push(StoredEvent {
+                    stream_id,
+                    version,
+                    event_type: event.event_type,
+                    event_payload: event.event_payload,
+                    created_at: Utc::now(),
+                });
+            }
+            Ok(version)
+        }
+
+        async fn load(
+            &self,
+            stream_id: Uuid,
+            after_version: i64,
+        ) -> Result<Vec<StoredEvent>, EventStoreError> {
+            Ok(self
+                .events
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|e| e.stream_id == stream_id && e.version > after_version)
+                .cloned()
+                .collect())
+        }
+
+        async fn save_snapshot(&self, snapshot: Snapshot) -> Result<(), EventStoreError> {
+            self.snapshots
+                .lock()
+                .unwrap()
+                .insert(snapshot.stream_id, snapshot);
+            Ok(())
+        }
+
+        async fn load_snapshot(
+            &self,
+            stream_id: Uuid,
+        ) -> Result<Option<Snapshot>, EventStoreError> {
+            Ok(self.snapshots.lock().unwrap().get(&stream_id).cloned())
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use mockall::mock;
+
+use crate::{
+    entities::inventory::{Inventory, StockReservation},
+    repositories::transactional_repository::{
+        TransactionalRepository, TransactionalRepositoryError,
+    },
+    value_objects::{OrderId, ProductId, ReservationId},
+};
+
+#[derive(Debug)]
+pub enum InventoryRepositoryError {
+    InventoryNotReadError(String),
+    InventoryNotSavedError,
+    ConcurrencyConflict,
+}
+
+impl std::fmt::Display for InventoryRepositoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InventoryRepositoryError::InventoryNotReadError(message) => {
+                write!(f, "Inventory not read error: {}", message)
+            }
+            InventoryRepositoryError::InventoryNotSavedError => {
+                write!(f, "Inventory not saved error")
+            }
+            InventoryRepositoryError::ConcurrencyConflict => {
+                write!(f, "Inventory was modified concurrently")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InventoryRepositoryError {}
+
+#[async_trait]
+pub trait InventoryRepository: TransactionalRepository {
+    /// Stock of the product in each warehouse, ordered by warehouse.
+    async fn find_by_product_id(
+        &self,
+        product_id: ProductId,
+    ) -> Result<Vec<Inventory>, InventoryRepositoryError>;
+
+    /// Stores the inventories, all or none, inserting the ones at version 0.
+    /// Fails with `ConcurrencyConflict` if any stored inventory is no longer
+    /// at its version. Returns the inventories with their new versions.
+    async fn save(
+        &self,
+        inventories: Vec<Inventory>,
+    ) -> Result<Vec<Inventory>, InventoryRepositoryError>;
+
+    async fn find_reservation(
+        &self,
+        id: ReservationId,
+    ) -> Result<Option<StockReservation>, InventoryRepositoryError>;
+
+    async fn find_reservation_by_order_id(
+        &self,
+        order_id: OrderId,
+    ) -> Result<Option<StockReservation>, InventoryRepositoryError>;
+
+    /// Stores the reservation together with the inventories it changed, as
+    /// `save` does. Fails with `ConcurrencyConflict` if another reservation
+    /// was stored for the same order.
+    async fn save_reservation(
+        &self,
+        reservation: StockReservation,
+        inventories: Vec<Inventory>,
+    ) -> Result<Vec<Inventory>, InventoryRepositoryError>;
+}
+
+mock! {
+    pub MyInventoryRepository {}
+
+    #[async_trait]
+    impl InventoryRepository for MyInventoryRepository {
+        async fn find_by_product_id(&self, product_id: ProductId) -> Result<Vec<Inventory>, InventoryRepositoryError>;
+        async fn save(&self, inventories: Vec<Inventory>) -> Result<Vec<Inventory>, InventoryRepositoryError>;
+        async fn find_reservation(&self, id: ReservationId) -> Result<Option<StockReservation>, InventoryRepositoryError>;
+        async fn find_reservation_by_order_id(&self, order_id: OrderId) -> Result<Option<StockReservation>, InventoryRepositoryError>;
+        async fn save_reservation(&self, reservation: StockReservation, inventories: Vec<Inventory>) -> Result<Vec<Inventory>, InventoryRepositoryError>;
+    }
+
+    #[async_trait]
+    impl TransactionalRepository for MyInventoryRepository {
+        async fn begin_transaction(&mut self) -> Result<(), TransactionalRepositoryError>;
+        async fn commit_transaction(&mut self) -> Result<(), TransactionalRepositoryError>;
+        async fn rollback_transaction(&mut self) -> Result<(), TransactionalRepositoryError>;
+    }
+}
@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use mockall::automock;
+
+use crate::{entities::invoice::Invoice, value_objects::OrderId};
+
+#[derive(Debug)]
+pub enum InvoiceRepositoryError {
+    InvoiceNotReadError(String),
+    InvoiceNotSavedError,
+    InvoiceAlreadyIssuedError,
+}
+
+impl std::fmt::Display for InvoiceRepositoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvoiceRepositoryError::InvoiceNotReadError(message) => {
+                write!(f, "Invoice not read error: {}", message)
+            }
+            InvoiceRepositoryError::InvoiceNotSavedError => write!(f, "Invoice not saved error"),
+            InvoiceRepositoryError::InvoiceAlreadyIssuedError => {
+                write!(f, "Invoice already issued")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvoiceRepositoryError {}
+
+#[automock]
+#[async_trait]
+pub trait InvoiceRepository {
+    /// Saves the invoice with the next number of its kind in its fiscal year,
+    /// leaving no gaps between numbers. Fails with `InvoiceAlreadyIssuedError`
+    /// for a second invoice of an order, or a second credit note of a refund.
+    async fn issue(&self, invoice: Invoice) -> Result<Invoice, InvoiceRepositoryError>;
+
+    /// The invoice of the order and its credit notes, in the order they were
+    /// issued.
+    async fn find_by_order_id(
+        &self,
+        order_id: OrderId,
+    ) -> Result<Vec<Invoice>, InvoiceRepositoryError>;
+}
+
+#[cfg(test)]
+pub use in_memory::InMemoryInvoiceRepository;
+
+#[cfg(test)]
+mod in_memory {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// Invoices kept in memory, for tests. Clones share the same invoices.
+    #[derive(Clone, Default)]
+    pub struct InMemoryInvoiceRepository {
+        invoices: Arc<Mutex<Vec<Invoice>>>,
+    }
+
+    impl InMemoryInvoiceRepository {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Every issued invoice, in the order they were issued.
+        pub fn invoices(&self) -> Vec<Invoice> {
+            self.invoices.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl InvoiceRepository for InMemoryInvoiceRepository {
+        async fn issue(&self, invoice: Invoice) -> Result<Invoice, InvoiceRepositoryError> {
+            let mut invoices = self.invoices.lock().unwrap();
+            if invoices.iter().any(|issued| {
+                issued.order_id() == invoice.order_id()
+                    && issued.kind() == invoice.kind()
+                    && issued.refund_key() == invoice.refund_key()
+            }) {
+                return Err(InvoiceRepositoryError::InvoiceAlreadyIssuedError);
+            }
+            let sequence = invoices
+                .iter()
+                .filter(|issued| {
+                    issued.kind() == invoice.kind() && issued.fiscal_year() == invoice.fiscal_year()
+                })
+                .count() as i64
+                + 1;
+            let invoice = invoice.with_sequence(sequence);
+            invoices.push(invoice.clone());
+            Ok(invoice)
+        }
+
+        async fn find_by_order_id(
+            &self,
+            order_id: OrderId,
+        ) -> Result<Vec<Invoice>, InvoiceRepositoryError> {
+            Ok(self
+                .invoices()
+                .into_iter()
+                .filter(|invoice| invoice.order_id() == &order_id)
+                .collect())
+        }
+    }
+}
@@ -1,4 +1,19 @@
+pub mod cart_repository;
+pub mod catalog_repository;
+pub mod checkout_saga_repository;
+pub mod currency_repository;
+pub mod customer_data_repository;
 pub mod customer_repository;
+pub mod event_sourced_order_repository;
+pub mod event_store;
+pub mod inventory_repository;
+pub mod invoice_repository;
 pub mod order_repository;
 pub mod outbox_repository;
+pub mod payment_repository;
+pub mod promotion_repository;
+pub mod return_repository;
+pub mod review_repository;
+pub mod shipment_repository;
+pub mod shipping_method_repository;
 pub mod transactional_repository;
@@ -6,7 +6,7 @@ use crate::{
     repositories::transactional_repository::{
         TransactionalRepository, TransactionalRepositoryError,
     },
-    value_objects::OrderId,
+    value_objects::{CustomerId, OrderId},
 };
 
 #[derive(Debug)]
@@ -14,6 +14,8 @@ pub enum OrderRepositoryError {
     OrderNotFoundError,
     OrderNotSavedError,
     OrderItemsNotReadError,
+    OrderNotReadError(String),
+    ConcurrencyConflict,
     ConnectionError,
 }
 
@@ -28,6 +30,12 @@ impl std::fmt::Display for OrderRepositoryError {
             OrderRepositoryError::OrderItemsNotReadError => {
                 write!(f, "Order items not read error")
             }
+            OrderRepositoryError::OrderNotReadError(message) => {
+                write!(f, "Order not read error: {}", message)
+            }
+            OrderRepositoryError::ConcurrencyConflict => {
+                write!(f, "Order was modified concurrently")
+            }
         }
     }
 }
@@ -38,8 +46,16 @@ impl std::error::Error for OrderRepositoryError {}
 pub trait OrderRepository: TransactionalRepository {
     async fn find_by_id(&self, id: OrderId) -> Result<Option<Order>, OrderRepositoryError>;
 
+    /// Orders placed by the customer, ordered by id.
+    async fn find_by_customer_id(
+        &self,
+        customer_id: CustomerId,
+    ) -> Result<Vec<Order>, OrderRepositoryError>;
+
     async fn save(&self, order: Order) -> Result<Order, OrderRepositoryError>;
 
+    /// Fails with `ConcurrencyConflict` if the stored order is no longer at
+    /// `order.version`. Returns the order with its new version.
     async fn update(&self, order: Order) -> Result<Order, OrderRepositoryError>;
 }
 
@@ -49,6 +65,7 @@ mock! {
     #[async_trait]
     impl OrderRepository for MyOrderRepository {
         async fn find_by_id(&self, id: OrderId) -> Result<Option<Order>, OrderRepositoryError>;
+        async fn find_by_customer_id(&self, customer_id: CustomerId) -> Result<Vec<Order>, OrderRepositoryError>;
         async fn save(&self, order: Order) -> Result<Order, OrderRepositoryError>;
         async fn update(&self, order: Order) -> Result<Order, OrderRepositoryError>;
     }
@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use mockall::automock;
 
-use crate::entities::outbox::OutboxMessage;
+use crate::{entities::outbox::OutboxMessage, value_objects::CustomerId};
 
 #[derive(Debug)]
 pub enum OutboxMessageRepositoryError {
@@ -24,6 +24,14 @@ impl std::fmt::Display for OutboxMessageRepositoryError {
 
 impl std::error::Error for OutboxMessageRepositoryError {}
 
+/// Where a message is in the outbox: messages are ordered by the transaction
+/// that stored them, then by the order they were stored in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OutboxPosition {
+    pub transaction_id: i64,
+    pub position: i64,
+}
+
 #[automock]
 #[async_trait]
 pub trait OutboxMessageRepository {
@@ -36,9 +44,150 @@ pub trait OutboxMessageRepository {
         &self,
     ) -> Result<Option<Vec<OutboxMessage>>, OutboxMessageRepositoryError>;
 
+    /// Returns up to `limit` messages with their position, in position order,
+    /// starting after `after` or from the first one. Messages of transactions
+    /// that may still be running are left out, so that no message can later
+    /// appear before the last one returned.
+    async fn find_after(
+        &self,
+        after: Option<OutboxPosition>,
+        limit: i64,
+    ) -> Result<Vec<(OutboxPosition, OutboxMessage)>, OutboxMessageRepositoryError>;
+
+    /// Messages whose payload identifies the customer, either as the event
+    /// subject or as `customer_id`, or one of its orders as `order_id`,
+    /// ordered by creation.
+    async fn find_by_customer_id(
+        &self,
+        customer_id: &CustomerId,
+    ) -> Result<Vec<OutboxMessage>, OutboxMessageRepositoryError>;
+
     async fn set_processed(
         &self,
         message_id: uuid::Uuid,
         processed_at: chrono::DateTime<chrono::Utc>,
     ) -> Result<(), OutboxMessageRepositoryError>;
 }
+
+#[cfg(test)]
+pub use in_memory::InMemoryOutboxMessageRepository;
+
+#[cfg(test)]
+mod in_memory {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::entities::outbox::OutboxMessageType;
+
+    /// Outbox kept in memory, for tests. Clones share the same messages.
+    #[derive(Clone, Default)]
+    pub struct InMemoryOutboxMessageRepository {
+        messages: Arc<Mutex<Vec<OutboxMessage>>>,
+    }
+
+    impl InMemoryOutboxMessageRepository {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Every saved message, in the order they were saved.
+        pub fn messages(&self) -> Vec<OutboxMessage> {
+            self.messages.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl OutboxMessageRepository for InMemoryOutboxMessageRepository {
+        async fn save(
+            &self,
+            message: OutboxMessage,
+        ) -> Result<OutboxMessage, OutboxMessageRepositoryError> {
+            self.messages.lock().unwrap().push(message.clone());
+            Ok(message)
+        }
+
+        async fn find_unprocessed(
+            &self,
+        ) -> Result<Option<Vec<OutboxMessage>>, OutboxMessageRepositoryError> {
+            let messages: Vec<OutboxMessage> = self
+                .messages()
+                .into_iter()
+                .filter(|message| message.processed_at().is_none())
+                .collect();
+            Ok((!messages.is_empty()).then_some(messages))
+        }
+
+        async fn find_after(
+            &self,
+            after: Option<OutboxPosition>,
+            limit: i64,
+        ) -> Result<Vec<(OutboxPosition, OutboxMessage)>, OutboxMessageRepositoryError> {
+            Ok(self
+                .messages()
+                .into_iter()
+                .enumerate()
+                .map(|(index, message)| {
+                    let position = OutboxPosition {
+                        transaction_id: 0,
+                        position: index as i64 + 1,
+                    };
+                    (position, message)
+                })
+                .filter(|(position, _)| after.is_none_or(|after| *position > after))
+                .take(limit.max(0) as usize)
+                .collect())
+        }
+
+        async fn find_by_customer_id(
+            &self,
+            customer_id: &CustomerId,
+        ) -> Result<Vec<OutboxMessage>, OutboxMessageRepositoryError> {
+            let customer_id = customer_id.0.to_string();
+            let payloads: Vec<(OutboxMessage, serde_json::Value)> = self
+                .messages()
+                .into_iter()
+                .map(|message| {
+                    let payload =
+                        serde_json::from_str(&message.event_payload()).unwrap_or_default();
+                    (message, payload)
+                })
+                .collect();
+            let order_ids: Vec<&str> = payloads
+                .iter()
+                .filter(|(message, payload)| {
+                    message.event_type() == OutboxMessageType::OrderCreated
+                        && payload["customer_id"].as_str() == Some(customer_id.as_str())
+                })
+                .filter_map(|(_, payload)| payload["id"].as_str())
+                .collect();
+            Ok(payloads
+                .iter()
+                .filter(|(_, payload)| {
+                    ["id", "customer_id"]
+                        .iter()
+                        .any(|key| payload[key].as_str() == Some(customer_id.as_str()))
+                        || payload["order_id"]
+                            .as_str()
+                            .is_some_and(|order_id| order_ids.contains(&order_id))
+                })
+                .map(|(message, _)| message.clone())
+                .collect())
+        }
+
+        async fn set_processed(
+            &self,
+            message_id: uuid::Uuid,
+            processed_at: chrono::DateTime<chrono::Utc>,
+        ) -> Result<(), OutboxMessageRepositoryError> {
+            let mut messages = self.messages.lock().unwrap();
+            let stored = messages
+                .iter_mut()
+                .find(|stored| stored.id() == message_id)
+                .ok_or_else(|| {
+                    OutboxMessageRepositoryError::OutboxMessageNotSavedError(message_id.to_string())
+                })?;
+            stored.set_processed_at(processed_at);
+            Ok(())
+        }
+    }
+}
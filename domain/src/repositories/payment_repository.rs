@@ -0,0 +1,72 @@
+use async_trait::async_trait;
+use mockall::mock;
+
+use crate::{
+    entities::payment::Payment,
+    repositories::transactional_repository::{
+        TransactionalRepository, TransactionalRepositoryError,
+    },
+    value_objects::{AuthorizationId, OrderId},
+};
+
+#[derive(Debug)]
+pub enum PaymentRepositoryError {
+    PaymentNotReadError(String),
+    PaymentNotSavedError,
+    ConcurrencyConflict,
+}
+
+impl std::fmt::Display for PaymentRepositoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaymentRepositoryError::PaymentNotReadError(message) => {
+                write!(f, "Payment not read error: {}", message)
+            }
+            PaymentRepositoryError::PaymentNotSavedError => write!(f, "Payment not saved error"),
+            PaymentRepositoryError::ConcurrencyConflict => {
+                write!(f, "Payment was modified concurrently")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PaymentRepositoryError {}
+
+#[async_trait]
+pub trait PaymentRepository: TransactionalRepository {
+    async fn find_by_order_id(
+        &self,
+        order_id: OrderId,
+    ) -> Result<Option<Payment>, PaymentRepositoryError>;
+
+    async fn find_by_authorization_id(
+        &self,
+        authorization_id: AuthorizationId,
+    ) -> Result<Option<Payment>, PaymentRepositoryError>;
+
+    /// Fails with `ConcurrencyConflict` if the order already has a payment.
+    async fn save(&self, payment: Payment) -> Result<Payment, PaymentRepositoryError>;
+
+    /// Fails with `ConcurrencyConflict` if the stored payment is no longer at
+    /// `payment.version`. Returns the payment with its new version.
+    async fn update(&self, payment: Payment) -> Result<Payment, PaymentRepositoryError>;
+}
+
+mock! {
+    pub MyPaymentRepository {}
+
+    #[async_trait]
+    impl PaymentRepository for MyPaymentRepository {
+        async fn find_by_order_id(&self, order_id: OrderId) -> Result<Option<Payment>, PaymentRepositoryError>;
+        async fn find_by_authorization_id(&self, authorization_id: AuthorizationId) -> Result<Option<Payment>, PaymentRepositoryError>;
+        async fn save(&self, payment: Payment) -> Result<Payment, PaymentRepositoryError>;
+        async fn update(&self, payment: Payment) -> Result<Payment, PaymentRepositoryError>;
+    }
+
+    #[async_trait]
+    impl TransactionalRepository for MyPaymentRepository {
+        async fn begin_transaction(&mut self) -> Result<(), TransactionalRepositoryError>;
+        async fn commit_transaction(&mut self) -> Result<(), TransactionalRepositoryError>;
+        async fn rollback_transaction(&mut self) -> Result<(), TransactionalRepositoryError>;
+    }
+}
@@ -0,0 +1,72 @@
+use async_trait::async_trait;
+use mockall::automock;
+
+use crate::{
+    entities::promotion::Promotion,
+    value_objects::{CouponCode, CustomerId, OrderId, PromotionId},
+};
+
+#[derive(Debug)]
+pub enum PromotionRepositoryError {
+    PromotionNotReadError(String),
+    PromotionNotSavedError,
+    PromotionAlreadyExistsError,
+    UsageLimitReachedError,
+}
+
+impl std::fmt::Display for PromotionRepositoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PromotionRepositoryError::PromotionNotReadError(message) => {
+                write!(f, "Promotion not read error: {}", message)
+            }
+            PromotionRepositoryError::PromotionNotSavedError => {
+                write!(f, "Promotion not saved error")
+            }
+            PromotionRepositoryError::PromotionAlreadyExistsError => {
+                write!(f, "A promotion with the same id or code exists")
+            }
+            PromotionRepositoryError::UsageLimitReachedError => {
+                write!(f, "Promotion already used as many times as allowed")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PromotionRepositoryError {}
+
+#[automock]
+#[async_trait]
+pub trait PromotionRepository {
+    async fn find_by_code(
+        &self,
+        code: CouponCode,
+    ) -> Result<Option<Promotion>, PromotionRepositoryError>;
+
+    /// Fails with `PromotionAlreadyExistsError` if the id or the code is taken.
+    async fn save(&self, promotion: Promotion) -> Result<Promotion, PromotionRepositoryError>;
+
+    /// Number of orders of the customer the promotion was applied to.
+    async fn count_redemptions(
+        &self,
+        promotion_id: PromotionId,
+        customer_id: CustomerId,
+    ) -> Result<i64, PromotionRepositoryError>;
+
+    /// Records that the promotion was applied to an order of the customer.
+    /// Fails with `UsageLimitReachedError` if the customer already used it
+    /// as many times as its conditions allow, even if applied concurrently.
+    async fn redeem(
+        &self,
+        promotion: &Promotion,
+        customer_id: CustomerId,
+        order_id: OrderId,
+    ) -> Result<(), PromotionRepositoryError>;
+
+    /// Undoes `redeem`, when the promotion could not be applied after all.
+    async fn release(
+        &self,
+        promotion_id: PromotionId,
+        order_id: OrderId,
+    ) -> Result<(), PromotionRepositoryError>;
+}
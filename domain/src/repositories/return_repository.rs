@@ -0,0 +1,86 @@
+use async_trait::async_trait;
+use mockall::mock;
+
+use crate::{
+    entities::returns::Return,
+    repositories::transactional_repository::{
+        TransactionalRepository, TransactionalRepositoryError,
+    },
+    value_objects::{OrderId, ReturnId},
+};
+
+#[derive(Debug)]
+pub enum ReturnRepositoryError {
+    ReturnNotReadError(String),
+    ReturnNotSavedError,
+    ConcurrencyConflict,
+}
+
+impl std::fmt::Display for ReturnRepositoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReturnRepositoryError::ReturnNotReadError(message) => {
+                write!(f, "Return not read error: {}", message)
+            }
+            ReturnRepositoryError::ReturnNotSavedError => write!(f, "Return not saved error"),
+            ReturnRepositoryError::ConcurrencyConflict => {
+                write!(f, "Return was modified concurrently")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReturnRepositoryError {}
+
+#[async_trait]
+pub trait ReturnRepository: TransactionalRepository {
+    async fn find_by_id(&self, id: ReturnId) -> Result<Option<Return>, ReturnRepositoryError>;
+
+    /// Returns of the order, ordered by id.
+    async fn find_by_order_id(
+        &self,
+        order_id: OrderId,
+    ) -> Result<Vec<Return>, ReturnRepositoryError>;
+
+    /// Fails with `ConcurrencyConflict` if a return with the same id exists.
+    /// Also moves the order to its next version, failing with
+    /// `ConcurrencyConflict` if it is no longer at `order_version`: the items
+    /// were checked against the other returns of the order at that version.
+    async fn save(
+        &self,
+        order_return: Return,
+        order_version: i64,
+    ) -> Result<Return, ReturnRepositoryError>;
+
+    /// Fails with `ConcurrencyConflict` if the stored return is no longer at
+    /// `order_return.version`. Returns the return with its new version.
+    async fn update(&self, order_return: Return) -> Result<Return, ReturnRepositoryError>;
+
+    /// Like `update`, for a return whose items changed: also moves the order
+    /// to its next version, like `save`.
+    async fn update_items(
+        &self,
+        order_return: Return,
+        order_version: i64,
+    ) -> Result<Return, ReturnRepositoryError>;
+}
+
+mock! {
+    pub MyReturnRepository {}
+
+    #[async_trait]
+    impl ReturnRepository for MyReturnRepository {
+        async fn find_by_id(&self, id: ReturnId) -> Result<Option<Return>, ReturnRepositoryError>;
+        async fn find_by_order_id(&self, order_id: OrderId) -> Result<Vec<Return>, ReturnRepositoryError>;
+        async fn save(&self, order_return: Return, order_version: i64) -> Result<Return, ReturnRepositoryError>;
+        async fn update(&self, order_return: Return) -> Result<Return, ReturnRepositoryError>;
+        async fn update_items(&self, order_return: Return, order_version: i64) -> Result<Return, ReturnRepositoryError>;
+    }
+
+    #[async_trait]
+    impl TransactionalRepository for MyReturnRepository {
+        async fn begin_transaction(&mut self) -> Result<(), TransactionalRepositoryError>;
+        async fn commit_transaction(&mut self) -> Result<(), TransactionalRepositoryError>;
+        async fn rollback_transaction(&mut self) -> Result<(), TransactionalRepositoryError>;
+    }
+}
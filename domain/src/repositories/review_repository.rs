@@ -0,0 +1,86 @@
+use async_trait::async_trait;
+use mockall::mock;
+
+use crate::{
+    entities::review::{Rating, Review, ReviewStatus},
+    repositories::transactional_repository::{
+        TransactionalRepository, TransactionalRepositoryError,
+    },
+    value_objects::{CatalogProductId, ReviewId},
+};
+
+#[derive(Debug)]
+pub enum ReviewRepositoryError {
+    ReviewNotReadError(String),
+    ReviewNotSavedError,
+    ReviewAlreadyExistsError,
+    /// The customer has already reviewed the product.
+    ProductAlreadyReviewedError,
+    ConcurrencyConflict,
+}
+
+impl std::fmt::Display for ReviewRepositoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReviewRepositoryError::ReviewNotReadError(message) => {
+                write!(f, "Review not read error: {}", message)
+            }
+            ReviewRepositoryError::ReviewNotSavedError => write!(f, "Review not saved error"),
+            ReviewRepositoryError::ReviewAlreadyExistsError => {
+                write!(f, "A review with the same id exists")
+            }
+            ReviewRepositoryError::ProductAlreadyReviewedError => {
+                write!(f, "The customer has already reviewed the product")
+            }
+            ReviewRepositoryError::ConcurrencyConflict => {
+                write!(f, "Review was modified concurrently")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReviewRepositoryError {}
+
+#[async_trait]
+pub trait ReviewRepository: TransactionalRepository {
+    async fn find_by_id(&self, id: ReviewId) -> Result<Option<Review>, ReviewRepositoryError>;
+
+    /// Reviews of the product in `status`, newest first.
+    async fn find_by_product_id(
+        &self,
+        product_id: CatalogProductId,
+        status: ReviewStatus,
+    ) -> Result<Vec<Review>, ReviewRepositoryError>;
+
+    /// Average and count of the approved reviews of the product.
+    async fn rating_of(
+        &self,
+        product_id: CatalogProductId,
+    ) -> Result<Rating, ReviewRepositoryError>;
+
+    async fn save(&self, review: Review) -> Result<Review, ReviewRepositoryError>;
+
+    /// Fails with `ConcurrencyConflict` if the stored review is no longer at
+    /// `review.version`. Returns the review with its new version.
+    async fn update(&self, review: Review) -> Result<Review, ReviewRepositoryError>;
+}
+
+mock! {
+    pub MyReviewRepository {}
+
+    #[async_trait]
+    impl ReviewRepository for MyReviewRepository {
+        async fn find_by_id(&self, id: ReviewId) -> Result<Option<Review>, ReviewRepositoryError>;
+        async fn find_by_product_id(&self, product_id: CatalogProductId, status: ReviewStatus) -> Result<Vec<Review>, ReviewRepositoryError>;
+        async fn rating_of(&self, product_id: CatalogProductId) -> Result<Rating, ReviewRepositoryError>;
+        async fn save(&self, review: Review) -> Result<Review, ReviewRepositoryError>;
+        async fn update(&self, review: Review) -> Result<Review, ReviewRepositoryError>;
+    }
+
+    #[async_trait]
+    impl TransactionalRepository for MyReviewRepository {
+        async fn begin_transaction(&mut self) -> Result<(), TransactionalRepositoryError>;
+        async fn commit_transaction(&mut self) -> Result<(), TransactionalRepositoryError>;
+        async fn rollback_transaction(&mut self) -> Result<(), TransactionalRepositoryError>;
+    }
+}
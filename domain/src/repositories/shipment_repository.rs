@@ -0,0 +1,87 @@
+use async_trait::async_trait;
+use mockall::mock;
+
+use crate::{
+    entities::shipment::Shipment,
+    repositories::transactional_repository::{
+        TransactionalRepository, TransactionalRepositoryError,
+    },
+    value_objects::{OrderId, ShipmentId},
+};
+
+#[derive(Debug)]
+pub enum ShipmentRepositoryError {
+    ShipmentNotReadError(String),
+    ShipmentNotSavedError,
+    ConcurrencyConflict,
+}
+
+impl std::fmt::Display for ShipmentRepositoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShipmentRepositoryError::ShipmentNotReadError(message) => {
+                write!(f, "Shipment not read error: {}", message)
+            }
+            ShipmentRepositoryError::ShipmentNotSavedError => write!(f, "Shipment not saved error"),
+            ShipmentRepositoryError::ConcurrencyConflict => {
+                write!(f, "Shipment was modified concurrently")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShipmentRepositoryError {}
+
+#[async_trait]
+pub trait ShipmentRepository: TransactionalRepository {
+    async fn find_by_id(&self, id: ShipmentId)
+        -> Result<Option<Shipment>, ShipmentRepositoryError>;
+
+    /// Shipments of the order, ordered by id.
+    async fn find_by_order_id(
+        &self,
+        order_id: OrderId,
+    ) -> Result<Vec<Shipment>, ShipmentRepositoryError>;
+
+    /// Fails with `ConcurrencyConflict` if a shipment with the same id exists.
+    /// Also moves the order to its next version, failing with
+    /// `ConcurrencyConflict` if it is no longer at `order_version`: the items
+    /// were checked against the other shipments of the order at that version.
+    async fn save(
+        &self,
+        shipment: Shipment,
+        order_version: i64,
+    ) -> Result<Shipment, ShipmentRepositoryError>;
+
+    /// Fails with `ConcurrencyConflict` if the stored shipment is no longer at
+    /// `shipment.version`. Returns the shipment with its new version.
+    async fn update(&self, shipment: Shipment) -> Result<Shipment, ShipmentRepositoryError>;
+
+    /// Like `update`, for a shipment whose items changed: also moves the order
+    /// to its next version, like `save`.
+    async fn update_items(
+        &self,
+        shipment: Shipment,
+        order_version: i64,
+    ) -> Result<Shipment, ShipmentRepositoryError>;
+}
+
+mock! {
+    pub MyShipmentRepository {}
+
+    #[async_trait]
+    impl ShipmentRepository for MyShipmentRepository {
+        async fn find_by_id(&self, id: ShipmentId) -> Result<Option<Shipment>, ShipmentRepositoryError>;
+        async fn find_by_order_id(&self, order_id: OrderId) -> Result<Vec<Shipment>, ShipmentRepositoryError>;
+        async fn save(&self, shipment: Shipment, order_version: i64) -> Result<Shipment, ShipmentRepositoryError>;
+        async fn update(&self, shipment: Shipment) -> Result<Shipment, ShipmentRepositoryError>;
+        async fn update_items(&self, shipment: Shipment, order_version: i64) -> Result<Shipment, ShipmentRepositoryError>;
+    }
+
+    #[async_trait]
+    impl TransactionalRepository for MyShipmentRepository {
+        async fn begin_transaction(&mut self) -> Result<(), TransactionalRepositoryError>;
+        async fn commit_transaction(&mut self) -> Result<(), TransactionalRepositoryError>;
+        async fn rollback_transaction(&mut self) -> Result<(), TransactionalRepositoryError>;
+    }
+}
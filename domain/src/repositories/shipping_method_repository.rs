@@ -0,0 +1,47 @@
+use async_trait::async_trait;
+use mockall::automock;
+
+use crate::{entities::shipping_method::ShippingMethod, value_objects::ShippingMethodId};
+
+#[derive(Debug)]
+pub enum ShippingMethodRepositoryError {
+    ShippingMethodNotReadError(String),
+    ShippingMethodNotSavedError,
+    ShippingMethodAlreadyExistsError,
+}
+
+impl std::fmt::Display for ShippingMethodRepositoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShippingMethodRepositoryError::ShippingMethodNotReadError(message) => {
+                write!(f, "Shipping method not read error: {}", message)
+            }
+            ShippingMethodRepositoryError::ShippingMethodNotSavedError => {
+                write!(f, "Shipping method not saved error")
+            }
+            ShippingMethodRepositoryError::ShippingMethodAlreadyExistsError => {
+                write!(f, "A shipping method with the same id exists")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShippingMethodRepositoryError {}
+
+#[automock]
+#[async_trait]
+pub trait ShippingMethodRepository {
+    async fn find_by_id(
+        &self,
+        id: ShippingMethodId,
+    ) -> Result<Option<ShippingMethod>, ShippingMethodRepositoryError>;
+
+    /// All the shipping methods, ordered by name.
+    async fn find_all(&self) -> Result<Vec<ShippingMethod>, ShippingMethodRepositoryError>;
+
+    /// Fails with `ShippingMethodAlreadyExistsError` if the id is taken.
+    async fn save(
+        &self,
+        shipping_method: ShippingMethod,
+    ) -> Result<ShippingMethod, ShippingMethodRepositoryError>;
+}
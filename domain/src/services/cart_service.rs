@@ -0,0 +1,685 @@
+use chrono::Utc;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    entities::{
+        cart::{Cart, CartError},
+        order::Order,
+    },
+    repositories::cart_repository::{CartRepository, CartRepositoryError},
+    services::order_service::{CreateOrderRequestObject, OrderService, OrderServiceError},
+    value_objects::{CartId, CustomerId, OrderItem, ProductId},
+};
+
+#[derive(Debug)]
+pub enum CartServiceError {
+    CartNotFoundError,
+    CartNotReadError,
+    CartNotSavedError,
+    ConcurrencyConflictError,
+    InvalidCartError(CartError),
+    OrderError(OrderServiceError),
+    GenericError(String),
+}
+
+impl std::fmt::Display for CartServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CartServiceError::CartNotFoundError => write!(f, "Cart not found error"),
+            CartServiceError::CartNotReadError => write!(f, "Cart not read error"),
+            CartServiceError::CartNotSavedError => write!(f, "Cart not saved error"),
+            CartServiceError::ConcurrencyConflictError => {
+                write!(f, "Cart was modified concurrently")
+            }
+            CartServiceError::InvalidCartError(error) => write!(f, "Invalid cart: {}", error),
+            CartServiceError::OrderError(error) => write!(f, "Order not placed: {}", error),
+            CartServiceError::GenericError(error) => write!(f, "Generic error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for CartServiceError {}
+
+pub struct AddCartItemRequestObject {
+    pub cart_id: String,
+    pub product_id: String,
+    pub price: f64,
+    pub quantity: i32,
+    /// When set, the item is added only if the cart is still at this version.
+    pub expected_version: Option<i64>,
+}
+
+pub struct ChangeCartItemQuantityRequestObject {
+    pub cart_id: String,
+    pub product_id: String,
+    pub quantity: i32,
+    /// When set, the quantity is changed only if the cart is still at this version.
+    pub expected_version: Option<i64>,
+}
+
+pub struct CheckoutRequestObject {
+    pub cart_id: String,
+    pub order_id: String,
+    /// Address book entries to use instead of the customer's defaults.
+    pub shipping_address_id: Option<String>,
+    pub billing_address_id: Option<String>,
+    pub pricing_mode: Option<String>,
+    pub currency: Option<String>,
+    pub shipping_method_id: Option<String>,
+    pub shipping_weight: Option<f64>,
+    /// When set, the cart is checked out only if it is still at this version.
+    pub expected_version: Option<i64>,
+}
+
+pub struct CartService {
+    cart_repository: Box<dyn CartRepository>,
+    order_service: OrderService,
+}
+
+impl CartService {
+    pub fn new(cart_repository: Box<dyn CartRepository>, order_service: OrderService) -> Self {
+        Self {
+            cart_repository,
+            order_service,
+        }
+    }
+
+    /// Returns the cart of the customer if there is one, otherwise starts a
+    /// new cart, anonymous when no customer is given.
+    pub async fn create_cart(
+        &mut self,
+        customer_id: Option<&str>,
+    ) -> Result<Cart, CartServiceError> {
+        let customer_id = customer_id.map(parse_customer_id).transpose()?;
+        let now = Utc::now();
+
+        info!("Creating cart");
+
+        if let Some(customer_id) = &customer_id {
+            if let Some(cart) = self.find_customer_cart(customer_id).await? {
+                return Ok(cart);
+            }
+        }
+
+        let cart = Cart::new(CartId(Uuid::new_v4()), customer_id, now);
+        self.cart_repository.save(cart).await.map_err(|e| {
+            error!("Error saving cart: {}", e);
+            CartServiceError::CartNotSavedError
+        })
+    }
+
+    /// Expired carts are not found.
+    pub async fn find_cart(&self, cart_id: &str) -> Result<Cart, CartServiceError> {
+        self.find_active_cart(cart_id, None).await
+    }
+
+    pub async fn add_item(
+        &mut self,
+        request: AddCartItemRequestObject,
+    ) -> Result<Cart, CartServiceError> {
+        let product_id = parse_product_id(&request.product_id)?;
+
+        info!("Adding item to cart");
+
+        let mut cart = self
+            .find_active_cart(&request.cart_id, request.expected_version)
+            .await?;
+        cart.add(
+            OrderItem {
+                price: request.price,
+                quantity: request.quantity,
+                product_id,
+            },
+            Utc::now(),
+        )
+        .map_err(CartServiceError::InvalidCartError)?;
+        self.update(cart).await
+    }
+
+    pub async fn change_item_quantity(
+        &mut self,
+        request: ChangeCartItemQuantityRequestObject,
+    ) -> Result<Cart, CartServiceError> {
+        let product_id = parse_product_id(&request.product_id)?;
+
+        info!("Changing quantity of cart item");
+
+        let mut cart = self
+            .find_active_cart(&request.cart_id, request.expected_version)
+            .await?;
+        cart.change_quantity(&product_id, request.quantity, Utc::now())
+            .map_err(CartServiceError::InvalidCartError)?;
+        self.update(cart).await
+    }
+
+    pub async fn remove_item(
+        &mut self,
+        cart_id: &str,
+        product_id: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Cart, CartServiceError> {
+        let product_id = parse_product_id(product_id)?;
+
+        info!("Removing item from cart");
+
+        let mut cart = self.find_active_cart(cart_id, expected_version).await?;
+        cart.remove(&product_id, Utc::now())
+            .map_err(CartServiceError::InvalidCartError)?;
+        self.update(cart).await
+    }
+
+    /// Called when a visitor logs in: the anonymous cart becomes the cart of
+    /// the customer, or is merged into the one the customer already has.
+    pub async fn merge_into_customer_cart(
+        &mut self,
+        cart_id: &str,
+        customer_id: &str,
+    ) -> Result<Cart, CartServiceError> {
+        let customer_id = parse_customer_id(customer_id)?;
+        let now = Utc::now();
+
+        info!("Merging cart into the customer cart");
+
+        let mut cart = self.find_active_cart(cart_id, None).await?;
+        match cart.customer_id() {
+            Some(owner) if owner == &customer_id => return Ok(cart),
+            Some(_) => {
+                return Err(CartServiceError::InvalidCartError(
+                    CartError::CartOfAnotherCustomerError,
+                ))
+            }
+            None => {}
+        }
+
+        let Some(mut customer_cart) = self.find_customer_cart(&customer_id).await? else {
+            cart.assign_to(customer_id, now)
+                .map_err(CartServiceError::InvalidCartError)?;
+            return self.update(cart).await;
+        };
+        let merged_cart_id = cart.id().clone();
+        customer_cart
+            .merge(cart, now)
+            .map_err(CartServiceError::InvalidCartError)?;
+        let customer_cart = self.update(customer_cart).await?;
+        self.delete(merged_cart_id).await?;
+        Ok(customer_cart)
+    }
+
+    /// Marks the cart checked out, so that a concurrent checkout of the same
+    /// cart fails, places an order with its items, then discards the cart.
+    /// The cart is reopened if the order can't be placed.
+    pub async fn checkout(
+        &mut self,
+        request: CheckoutRequestObject,
+    ) -> Result<Order, CartServiceError> {
+        info!("Checking out cart");
+
+        let mut cart = self
+            .find_active_cart(&request.cart_id, request.expected_version)
+            .await?;
+        let (customer_id, cart_lines) = cart
+            .checkout(Utc::now())
+            .map_err(CartServiceError::InvalidCartError)?;
+        let mut cart = self.update(cart).await?;
+
+        let placed = self
+            .order_service
+            .place_order(
+                CreateOrderRequestObject {
+                    order_id: request.order_id,
+                    customer_id: customer_id.0.to_string(),
+                    shipping_address_id: request.shipping_address_id,
+                    billing_address_id: request.billing_address_id,
+                    pricing_mode: request.pricing_mode,
+                    currency: request.currency,
+                    shipping_method_id: request.shipping_method_id,
+                    shipping_weight: request.shipping_weight,
+                },
+                cart_lines,
+            )
+            .await;
+        let order = match placed {
+            Ok(order) => order,
+            Err(e) => {
+                cart.reopen();
+                if let Err(e) = self.update(cart).await {
+                    error!("Error reopening checked out cart: {}", e);
+                }
+                return Err(CartServiceError::OrderError(e));
+            }
+        };
+
+        // The order is placed anyway; a leftover cart only shows its items again.
+        if let Err(e) = self.cart_repository.delete(cart.id().clone()).await {
+            error!("Error deleting checked out cart: {}", e);
+        }
+        Ok(order)
+    }
+
+    async fn find_active_cart(
+        &self,
+        cart_id: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Cart, CartServiceError> {
+        let cart_id = Uuid::try_parse(cart_id)
+            .map_err(|err| CartServiceError::GenericError(err.to_string()))?;
+        let cart = match self.cart_repository.find_by_id(CartId(cart_id)).await {
+            Ok(Some(cart)) if !cart.is_expired(Utc::now()) && !cart.is_checked_out() => cart,
+            Ok(_) => {
+                error!("Cart not found");
+                return Err(CartServiceError::CartNotFoundError);
+            }
+            Err(e) => {
+                error!("Error reading cart: {}", e);
+                return Err(CartServiceError::CartNotReadError);
+            }
+        };
+        if expected_version.is_some_and(|version| version != cart.version()) {
+            error!("Cart version does not match the expected one");
+            return Err(CartServiceError::ConcurrencyConflictError);
+        }
+        Ok(cart)
+    }
+
+    /// Discards the cart of the customer if expired, so that a new one can
+    /// take its place.
+    async fn find_customer_cart(
+        &self,
+        customer_id: &CustomerId,
+    ) -> Result<Option<Cart>, CartServiceError> {
+        let cart = self
+            .cart_repository
+            .find_by_customer_id(customer_id.clone())
+            .await
+            .map_err(|e| {
+                error!("Error reading cart: {}", e);
+                CartServiceError::CartNotReadError
+            })?;
+        match cart {
+            Some(cart) if cart.is_expired(Utc::now()) || cart.is_checked_out() => {
+                self.delete(cart.id().clone()).await?;
+                Ok(None)
+            }
+            cart => Ok(cart),
+        }
+    }
+
+    async fn update(&self, cart: Cart) -> Result<Cart, CartServiceError> {
+        self.cart_repository.update(cart).await.map_err(|e| {
+            error!("Error saving cart: {}", e);
+            match e {
+                CartRepositoryError::ConcurrencyConflict => {
+                    CartServiceError::ConcurrencyConflictError
+                }
+                _ => CartServiceError::CartNotSavedError,
+            }
+        })
+    }
+
+    async fn delete(&self, cart_id: CartId) -> Result<(), CartServiceError> {
+        self.cart_repository.delete(cart_id).await.map_err(|e| {
+            error!("Error deleting cart: {}", e);
+            CartServiceError::CartNotSavedError
+        })
+    }
+}
+
+fn parse_customer_id(customer_id: &str) -> Result<CustomerId, CartServiceError> {
+    Uuid::try_parse(customer_id)
+        .map(CustomerId)
+        .map_err(|err| CartServiceError::GenericError(err.to_string()))
+}
+
+fn parse_product_id(product_id: &str) -> Result<ProductId, CartServiceError> {
+    Uuid::try_parse(product_id)
+        .map(ProductId)
+        .map_err(|err| CartServiceError::GenericError(err.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, Utc};
+    use uuid::Uuid;
+
+    use crate::{
+        entities::{
+            address_book::AddressBook,
+            cart::{Cart, CartError, CART_LIFETIME_DAYS},
+            customer::Customer,
+            outbox::OutboxMessageType,
+        },
+        gateways::tax_calculator::TaxRuleTable,
+        repositories::{
+            cart_repository::{CartRepositoryError, MockCartRepository},
+            catalog_repository::MockCatalogRepository,
+            currency_repository::InMemoryCurrencyRepository,
+            customer_repository::MockMyCustomerRepository,
+            order_repository::MockMyOrderRepository,
+            outbox_repository::MockOutboxMessageRepository,
+            shipping_method_repository::MockShippingMethodRepository,
+        },
+        services::{currency_service::CurrencyService, order_service::OrderService},
+        value_objects::{
+            Address, CartId, CountryCode, Currency, CustomerId, Email, OrderItem, PersonName,
+            PostalCode, ProductId,
+        },
+    };
+
+    use super::{AddCartItemRequestObject, CartService, CartServiceError, CheckoutRequestObject};
+
+    const CART_ID: &str = "6f1f6b52-8e05-11ee-af1c-9bfe41ffe61f";
+    const CUSTOMER_ID: &str = "2585491a-8e05-11ee-af1c-9bfe41ffe61f";
+    const PRODUCT_ID: &str = "8c7ab3d2-8e05-11ee-af1c-9bfe41ffe61f";
+
+    #[tokio::test]
+    async fn adds_an_item_to_a_cart() {
+        let mut cart_repository = MockCartRepository::new();
+        cart_repository
+            .expect_find_by_id()
+            .returning(|_| Ok(Some(cart(None))));
+        cart_repository
+            .expect_update()
+            .withf(|cart| cart.cart_lines().len() == 1)
+            .times(1)
+            .returning(|cart| Ok(cart.with_version(2)));
+        let mut service = CartService::new(Box::new(cart_repository), order_service());
+
+        let cart = service
+            .add_item(AddCartItemRequestObject {
+                cart_id: CART_ID.to_string(),
+                product_id: PRODUCT_ID.to_string(),
+                price: 9.99,
+                quantity: 2,
+                expected_version: Some(1),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(2, cart.version());
+    }
+
+    #[tokio::test]
+    async fn expired_carts_are_not_found() {
+        let mut cart_repository = MockCartRepository::new();
+        cart_repository.expect_find_by_id().returning(|_| {
+            let created_at = Utc::now() - Duration::days(CART_LIFETIME_DAYS + 1);
+            Ok(Some(Cart::new(
+                CartId(Uuid::try_parse(CART_ID).unwrap()),
+                None,
+                created_at,
+            )))
+        });
+        let service = CartService::new(Box::new(cart_repository), order_service());
+
+        let result = service.find_cart(CART_ID).await;
+
+        assert!(matches!(result, Err(CartServiceError::CartNotFoundError)));
+    }
+
+    #[tokio::test]
+    async fn merges_an_anonymous_cart_into_the_customer_cart() {
+        let mut cart_repository = MockCartRepository::new();
+        cart_repository.expect_find_by_id().returning(|_| {
+            let mut cart = Cart::new(CartId(Uuid::new_v4()), None, Utc::now());
+            cart.add(item(), Utc::now()).unwrap();
+            Ok(Some(cart))
+        });
+        cart_repository
+            .expect_find_by_customer_id()
+            .returning(|customer_id| Ok(Some(cart(Some(customer_id)))));
+        cart_repository
+            .expect_update()
+            .withf(|cart| cart.id().0 == Uuid::try_parse(CART_ID).unwrap())
+            .times(1)
+            .returning(Ok);
+        cart_repository
+            .expect_delete()
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut service = CartService::new(Box::new(cart_repository), order_service());
+
+        let cart = service
+            .merge_into_customer_cart(&Uuid::new_v4().to_string(), CUSTOMER_ID)
+            .await
+            .unwrap();
+
+        assert_eq!(1, cart.cart_lines().len());
+    }
+
+    #[tokio::test]
+    async fn checks_out_a_cart_into_an_order() {
+        let mut cart_repository = MockCartRepository::new();
+        cart_repository.expect_find_by_id().returning(|_| {
+            let mut cart = cart(Some(CustomerId(Uuid::try_parse(CUSTOMER_ID).unwrap())));
+            cart.add(item(), Utc::now()).unwrap();
+            Ok(Some(cart))
+        });
+        cart_repository
+            .expect_update()
+            .withf(|cart| cart.is_checked_out())
+            .times(1)
+            .returning(|cart| Ok(cart.with_version(2)));
+        cart_repository
+            .expect_delete()
+            .times(1)
+            .returning(|_| Ok(()));
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_id()
+            .returning(|_| Ok(Some(customer())));
+        let mut order_repository = MockMyOrderRepository::new();
+        order_repository
+            .expect_begin_transaction()
+            .returning(|| Ok(()));
+        order_repository
+            .expect_commit_transaction()
+            .returning(|| Ok(()));
+        order_repository
+            .expect_save()
+            .withf(|order| order.order_items().len() == 1)
+            .returning(|order| Ok(order.with_version(1)));
+        let mut outbox_message_repository = MockOutboxMessageRepository::new();
+        outbox_message_repository
+            .expect_save()
+            .withf(|message| message.event_type() == OutboxMessageType::OrderCreated)
+            .times(1)
+            .returning(Ok);
+        outbox_message_repository
+            .expect_save()
+            .withf(|message| message.event_type() == OutboxMessageType::ProductAddedToOrder)
+            .times(1)
+            .returning(Ok);
+        outbox_message_repository
+            .expect_save()
+            .withf(|message| message.event_type() == OutboxMessageType::OrderPlaced)
+            .times(1)
+            .returning(Ok);
+        let mut service = CartService::new(
+            Box::new(cart_repository),
+            OrderService::new(
+                Box::new(customer_repository),
+                Box::new(order_repository),
+                Box::new(outbox_message_repository),
+                Box::new(MockCatalogRepository::new()),
+                Box::new(MockShippingMethodRepository::new()),
+                Box::new(TaxRuleTable::default()),
+                CurrencyService::new(Box::new(InMemoryCurrencyRepository::new())),
+            ),
+        );
+
+        let order = service
+            .checkout(CheckoutRequestObject {
+                cart_id: CART_ID.to_string(),
+                order_id: Uuid::new_v4().to_string(),
+                shipping_address_id: None,
+                billing_address_id: None,
+                pricing_mode: None,
+                currency: None,
+                shipping_method_id: None,
+                shipping_weight: None,
+                expected_version: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(19.98, order.order_lines().total_price());
+    }
+
+    #[tokio::test]
+    async fn cannot_check_out_an_anonymous_cart() {
+        let mut cart_repository = MockCartRepository::new();
+        cart_repository.expect_find_by_id().returning(|_| {
+            let mut cart = cart(None);
+            cart.add(item(), Utc::now()).unwrap();
+            Ok(Some(cart))
+        });
+        let mut service = CartService::new(Box::new(cart_repository), order_service());
+
+        let result = service
+            .checkout(CheckoutRequestObject {
+                cart_id: CART_ID.to_string(),
+                order_id: Uuid::new_v4().to_string(),
+                shipping_address_id: None,
+                billing_address_id: None,
+                pricing_mode: None,
+                currency: None,
+                shipping_method_id: None,
+                shipping_weight: None,
+                expected_version: None,
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(CartServiceError::InvalidCartError(
+                CartError::AnonymousCartError
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn places_no_order_if_the_cart_is_checked_out_concurrently() {
+        let mut cart_repository = MockCartRepository::new();
+        cart_repository.expect_find_by_id().returning(|_| {
+            let mut cart = cart(Some(CustomerId(Uuid::try_parse(CUSTOMER_ID).unwrap())));
+            cart.add(item(), Utc::now()).unwrap();
+            Ok(Some(cart))
+        });
+        cart_repository
+            .expect_update()
+            .times(1)
+            .returning(|_| Err(CartRepositoryError::ConcurrencyConflict));
+        let mut service = CartService::new(Box::new(cart_repository), order_service());
+
+        let result = service.checkout(checkout_request()).await;
+
+        assert!(matches!(
+            result,
+            Err(CartServiceError::ConcurrencyConflictError)
+        ));
+    }
+
+    #[tokio::test]
+    async fn reopens_the_cart_if_the_order_is_not_placed() {
+        let mut cart_repository = MockCartRepository::new();
+        cart_repository.expect_find_by_id().returning(|_| {
+            let mut cart = cart(Some(CustomerId(Uuid::try_parse(CUSTOMER_ID).unwrap())));
+            cart.add(item(), Utc::now()).unwrap();
+            Ok(Some(cart))
+        });
+        cart_repository
+            .expect_update()
+            .withf(|cart| cart.is_checked_out())
+            .times(1)
+            .returning(|cart| Ok(cart.with_version(2)));
+        cart_repository
+            .expect_update()
+            .withf(|cart| !cart.is_checked_out() && cart.version() == 2)
+            .times(1)
+            .returning(|cart| Ok(cart.with_version(3)));
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_id()
+            .returning(|_| Ok(None));
+        let mut service = CartService::new(
+            Box::new(cart_repository),
+            OrderService::new(
+                Box::new(customer_repository),
+                Box::new(MockMyOrderRepository::new()),
+                Box::new(MockOutboxMessageRepository::new()),
+                Box::new(MockCatalogRepository::new()),
+                Box::new(MockShippingMethodRepository::new()),
+                Box::new(TaxRuleTable::default()),
+                CurrencyService::new(Box::new(InMemoryCurrencyRepository::new())),
+            ),
+        );
+
+        let result = service.checkout(checkout_request()).await;
+
+        assert!(matches!(result, Err(CartServiceError::OrderError(_))));
+    }
+
+    fn checkout_request() -> CheckoutRequestObject {
+        CheckoutRequestObject {
+            cart_id: CART_ID.to_string(),
+            order_id: Uuid::new_v4().to_string(),
+            shipping_address_id: None,
+            billing_address_id: None,
+            pricing_mode: None,
+            currency: None,
+            shipping_method_id: None,
+            shipping_weight: None,
+            expected_version: None,
+        }
+    }
+
+    fn order_service() -> OrderService {
+        OrderService::new(
+            Box::new(MockMyCustomerRepository::new()),
+            Box::new(MockMyOrderRepository::new()),
+            Box::new(MockOutboxMessageRepository::new()),
+            Box::new(MockCatalogRepository::new()),
+            Box::new(MockShippingMethodRepository::new()),
+            Box::new(TaxRuleTable::default()),
+            CurrencyService::new(Box::new(InMemoryCurrencyRepository::new())),
+        )
+    }
+
+    fn cart(customer_id: Option<CustomerId>) -> Cart {
+        Cart::new(
+            CartId(Uuid::try_parse(CART_ID).unwrap()),
+            customer_id,
+            Utc::now(),
+        )
+        .with_version(1)
+    }
+
+    fn item() -> OrderItem {
+        OrderItem {
+            price: 9.99,
+            quantity: 2,
+            product_id: ProductId(Uuid::try_parse(PRODUCT_ID).unwrap()),
+        }
+    }
+
+    fn customer() -> Customer {
+        Customer {
+            id: CustomerId(Uuid::try_parse(CUSTOMER_ID).unwrap()),
+            first_name: PersonName::parse("Mario").unwrap(),
+            last_name: PersonName::parse("Rossi").unwrap(),
+            email: Email::parse("mario.rossi@example.com").unwrap(),
+            phone: None,
+            address: Address {
+                street: "Via Roma 1".to_string(),
+                city: "Milano".to_string(),
+                zip_code: PostalCode::parse("20121", &CountryCode::parse("IT").unwrap()).unwrap(),
+                country: CountryCode::parse("IT").unwrap(),
+            },
+            address_book: AddressBook::new(),
+            currency: Currency::Eur,
+            deactivated_at: None,
+            version: 1,
+        }
+    }
+}
@@ -0,0 +1,376 @@
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    entities::{
+        category::{Category, CategoryError},
+        product::{Attribute, AttributeValue, Product, ProductError},
+    },
+    repositories::catalog_repository::{CatalogRepository, CatalogRepositoryError},
+    value_objects::{CatalogProductId, CategoryId, ProductId},
+};
+
+#[derive(Debug)]
+pub enum CatalogServiceError {
+    CategoryNotFoundError,
+    CategoryNotReadError,
+    CategoryNotSavedError,
+    CategoryAlreadyExistsError,
+    ProductNotFoundError,
+    ProductNotReadError,
+    ProductNotSavedError,
+    ProductAlreadyExistsError,
+    VariantAlreadyExistsError,
+    SkuAlreadyExistsError,
+    ConcurrencyConflictError,
+    InvalidCategoryError(CategoryError),
+    InvalidProductError(ProductError),
+    GenericError(String),
+}
+
+impl std::fmt::Display for CatalogServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CatalogServiceError::CategoryNotFoundError => write!(f, "Category not found error"),
+            CatalogServiceError::CategoryNotReadError => write!(f, "Category not read error"),
+            CatalogServiceError::CategoryNotSavedError => write!(f, "Category not saved error"),
+            CatalogServiceError::CategoryAlreadyExistsError => {
+                write!(f, "A category with the same id exists")
+            }
+            CatalogServiceError::ProductNotFoundError => write!(f, "Product not found error"),
+            CatalogServiceError::ProductNotReadError => write!(f, "Product not read error"),
+            CatalogServiceError::ProductNotSavedError => write!(f, "Product not saved error"),
+            CatalogServiceError::ProductAlreadyExistsError => {
+                write!(f, "A product with the same id exists")
+            }
+            CatalogServiceError::VariantAlreadyExistsError => {
+                write!(f, "A variant with the same id exists")
+            }
+            CatalogServiceError::SkuAlreadyExistsError => {
+                write!(f, "A variant with the same SKU exists")
+            }
+            CatalogServiceError::ConcurrencyConflictError => {
+                write!(f, "Product was modified concurrently")
+            }
+            CatalogServiceError::InvalidCategoryError(error) => {
+                write!(f, "Invalid category: {}", error)
+            }
+            CatalogServiceError::InvalidProductError(error) => {
+                write!(f, "Invalid product: {}", error)
+            }
+            CatalogServiceError::GenericError(error) => write!(f, "Generic error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for CatalogServiceError {}
+
+pub struct CreateCategoryRequestObject {
+    pub category_id: String,
+    pub name: String,
+    pub parent_id: Option<String>,
+}
+
+pub struct CreateProductRequestObject {
+    pub product_id: String,
+    pub name: String,
+    pub category_id: Option<String>,
+}
+
+pub struct AddVariantRequestObject {
+    pub product_id: String,
+    pub variant_id: String,
+    pub sku: String,
+    pub price: f64,
+    /// When set, the variant is added only if the product is still at this version.
+    pub expected_version: Option<i64>,
+}
+
+pub struct SetAttributeRequestObject {
+    pub product_id: String,
+    /// The attribute is of the product when not set.
+    pub variant_id: Option<String>,
+    pub name: String,
+    /// One of `text`, `number` or `boolean`.
+    pub kind: String,
+    pub value: String,
+    /// When set, the attribute is set only if the product is still at this version.
+    pub expected_version: Option<i64>,
+}
+
+/// Keeps the catalog: the category tree and the products in it, sold in
+/// variants.
+pub struct CatalogService {
+    catalog_repository: Box<dyn CatalogRepository>,
+}
+
+impl CatalogService {
+    pub fn new(catalog_repository: Box<dyn CatalogRepository>) -> Self {
+        Self { catalog_repository }
+    }
+
+    /// A parent must exist before its children, so the tree has no cycles.
+    pub async fn create_category(
+        &self,
+        request: CreateCategoryRequestObject,
+    ) -> Result<Category, CatalogServiceError> {
+        let category_id = parse_category_id(&request.category_id)?;
+        let parent_id = match request.parent_id.as_deref() {
+            Some(parent_id) => Some(self.find_category(parent_id).await?.id().clone()),
+            None => None,
+        };
+        let category = Category::new(category_id, &request.name, parent_id)
+            .map_err(CatalogServiceError::InvalidCategoryError)?;
+
+        info!("Creating category");
+
+        self.catalog_repository
+            .save_category(category)
+            .await
+            .map_err(|e| {
+                error!("Error saving category: {}", e);
+                match e {
+                    CatalogRepositoryError::CategoryAlreadyExistsError => {
+                        CatalogServiceError::CategoryAlreadyExistsError
+                    }
+                    _ => CatalogServiceError::CategoryNotSavedError,
+                }
+            })
+    }
+
+    pub async fn create_product(
+        &self,
+        request: CreateProductRequestObject,
+    ) -> Result<Product, CatalogServiceError> {
+        let product_id = parse_product_id(&request.product_id)?;
+        let category_id = match request.category_id.as_deref() {
+            Some(category_id) => Some(self.find_category(category_id).await?.id().clone()),
+            None => None,
+        };
+        let product = Product::new(product_id, &request.name, category_id)
+            .map_err(CatalogServiceError::InvalidProductError)?;
+
+        info!("Creating product");
+
+        self.catalog_repository
+            .save_product(product)
+            .await
+            .map_err(save_error)
+    }
+
+    pub async fn find_product(&self, product_id: &str) -> Result<Product, CatalogServiceError> {
+        self.get_product(product_id, None).await
+    }
+
+    /// Products of the category and of the categories under it.
+    pub async fn find_products_in_category(
+        &self,
+        category_id: &str,
+    ) -> Result<Vec<Product>, CatalogServiceError> {
+        let category = self.find_category(category_id).await?;
+        self.catalog_repository
+            .find_products_in_category(category.id().clone())
+            .await
+            .map_err(|e| {
+                error!("Error reading products: {}", e);
+                CatalogServiceError::ProductNotReadError
+            })
+    }
+
+    pub async fn add_variant(
+        &self,
+        request: AddVariantRequestObject,
+    ) -> Result<Product, CatalogServiceError> {
+        let variant_id = parse_variant_id(&request.variant_id)?;
+
+        info!("Adding variant to product");
+
+        let mut product = self
+            .get_product(&request.product_id, request.expected_version)
+            .await?;
+        product
+            .add_variant(variant_id, &request.sku, request.price)
+            .map_err(CatalogServiceError::InvalidProductError)?;
+        self.catalog_repository
+            .update_product(product)
+            .await
+            .map_err(save_error)
+    }
+
+    pub async fn set_attribute(
+        &self,
+        request: SetAttributeRequestObject,
+    ) -> Result<Product, CatalogServiceError> {
+        let variant_id = request
+            .variant_id
+            .as_deref()
+            .map(parse_variant_id)
+            .transpose()?;
+        let attribute = AttributeValue::parse(&request.kind, &request.value)
+            .and_then(|value| Attribute::new(&request.name, value))
+            .map_err(CatalogServiceError::InvalidProductError)?;
+
+        info!("Setting attribute of product");
+
+        let mut product = self
+            .get_product(&request.product_id, request.expected_version)
+            .await?;
+        product
+            .set_attribute(variant_id.as_ref(), attribute)
+            .map_err(CatalogServiceError::InvalidProductError)?;
+        self.catalog_repository
+            .update_product(product)
+            .await
+            .map_err(save_error)
+    }
+
+    async fn find_category(&self, category_id: &str) -> Result<Category, CatalogServiceError> {
+        let category_id = parse_category_id(category_id)?;
+        match self.catalog_repository.find_category(category_id).await {
+            Ok(Some(category)) => Ok(category),
+            Ok(None) => {
+                error!("Category not found");
+                Err(CatalogServiceError::CategoryNotFoundError)
+            }
+            Err(e) => {
+                error!("Error reading category: {}", e);
+                Err(CatalogServiceError::CategoryNotReadError)
+            }
+        }
+    }
+
+    async fn get_product(
+        &self,
+        product_id: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Product, CatalogServiceError> {
+        let product_id = parse_product_id(product_id)?;
+        let product = match self.catalog_repository.find_product(product_id).await {
+            Ok(Some(product)) => product,
+            Ok(None) => {
+                error!("Product not found");
+                return Err(CatalogServiceError::ProductNotFoundError);
+            }
+            Err(e) => {
+                error!("Error reading product: {}", e);
+                return Err(CatalogServiceError::ProductNotReadError);
+            }
+        };
+        if expected_version.is_some_and(|version| version != product.version()) {
+            error!("Product version does not match the expected one");
+            return Err(CatalogServiceError::ConcurrencyConflictError);
+        }
+        Ok(product)
+    }
+}
+
+fn save_error(error: CatalogRepositoryError) -> CatalogServiceError {
+    error!("Error saving product: {}", error);
+    match error {
+        CatalogRepositoryError::ProductAlreadyExistsError => {
+            CatalogServiceError::ProductAlreadyExistsError
+        }
+        CatalogRepositoryError::VariantAlreadyExistsError => {
+            CatalogServiceError::VariantAlreadyExistsError
+        }
+        CatalogRepositoryError::SkuAlreadyExistsError => CatalogServiceError::SkuAlreadyExistsError,
+        CatalogRepositoryError::ConcurrencyConflict => {
+            CatalogServiceError::ConcurrencyConflictError
+        }
+        _ => CatalogServiceError::ProductNotSavedError,
+    }
+}
+
+fn parse_category_id(category_id: &str) -> Result<CategoryId, CatalogServiceError> {
+    Uuid::try_parse(category_id)
+        .map(CategoryId)
+        .map_err(|err| CatalogServiceError::GenericError(err.to_string()))
+}
+
+fn parse_product_id(product_id: &str) -> Result<CatalogProductId, CatalogServiceError> {
+    Uuid::try_parse(product_id)
+        .map(CatalogProductId)
+        .map_err(|err| CatalogServiceError::GenericError(err.to_string()))
+}
+
+fn parse_variant_id(variant_id: &str) -> Result<ProductId, CatalogServiceError> {
+    Uuid::try_parse(variant_id)
+        .map(ProductId)
+        .map_err(|err| CatalogServiceError::GenericError(err.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use crate::{
+        entities::product::Product,
+        repositories::catalog_repository::MockCatalogRepository,
+        value_objects::{CatalogProductId, CategoryId},
+    };
+
+    use super::{
+        AddVariantRequestObject, CatalogService, CatalogServiceError, CreateCategoryRequestObject,
+    };
+
+    #[tokio::test]
+    async fn creates_a_category_under_an_existing_parent_only() {
+        let mut catalog_repository = MockCatalogRepository::new();
+        catalog_repository
+            .expect_find_category()
+            .returning(|_| Ok(None));
+        catalog_repository.expect_save_category().never();
+        let service = CatalogService::new(Box::new(catalog_repository));
+
+        let result = service
+            .create_category(CreateCategoryRequestObject {
+                category_id: Uuid::new_v4().to_string(),
+                name: "T-shirts".to_string(),
+                parent_id: Some(Uuid::new_v4().to_string()),
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(CatalogServiceError::CategoryNotFoundError)
+        ));
+    }
+
+    #[tokio::test]
+    async fn adds_a_variant_to_the_product_at_the_expected_version() {
+        let product_id = Uuid::new_v4();
+        let mut catalog_repository = MockCatalogRepository::new();
+        catalog_repository.expect_find_product().returning(|id| {
+            Ok(Some(
+                Product::new(id, "T-shirt", Some(CategoryId(Uuid::new_v4())))
+                    .unwrap()
+                    .with_version(3),
+            ))
+        });
+        catalog_repository
+            .expect_update_product()
+            .times(1)
+            .returning(|product| {
+                let version = product.version() + 1;
+                Ok(product.with_version(version))
+            });
+        let service = CatalogService::new(Box::new(catalog_repository));
+        let request = |expected_version| AddVariantRequestObject {
+            product_id: product_id.to_string(),
+            variant_id: Uuid::new_v4().to_string(),
+            sku: "TS-RED-M".to_string(),
+            price: 19.9,
+            expected_version: Some(expected_version),
+        };
+
+        assert!(matches!(
+            service.add_variant(request(2)).await,
+            Err(CatalogServiceError::ConcurrencyConflictError)
+        ));
+        let product = service.add_variant(request(3)).await.unwrap();
+
+        assert_eq!(&CatalogProductId(product_id), product.id());
+        assert_eq!(4, product.version());
+        assert_eq!("TS-RED-M", product.variants()[0].sku.as_str());
+    }
+}
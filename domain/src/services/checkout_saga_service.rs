@@ -0,0 +1,803 @@
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    entities::{
+        checkout_saga::{CheckoutSaga, CheckoutSagaState, Compensation},
+        order::Order,
+        outbox::{
+            OrderConfirmedEvent, OrderPlacedEvent, OutboxMessage, OutboxMessageError,
+            OutboxMessageType, PaymentAuthorizedEvent, StockReservedEvent,
+        },
+    },
+    gateways::{
+        inventory_gateway::{InventoryGateway, InventoryGatewayError},
+        payment_gateway::{PaymentGateway, PaymentGatewayError},
+    },
+    repositories::{
+        checkout_saga_repository::{CheckoutSagaRepository, CheckoutSagaRepositoryError},
+        order_repository::{OrderRepository, OrderRepositoryError},
+        outbox_repository::OutboxMessageRepository,
+        promotion_repository::PromotionRepository,
+    },
+    value_objects::OrderId,
+};
+
+#[derive(Debug)]
+pub enum CheckoutSagaServiceError {
+    SagaNotFoundError,
+    SagaNotReadError,
+    SagaNotSavedError,
+    OrderNotFoundError,
+    OrderNotReadError,
+    OrderNotSavedError,
+    PromotionNotReleasedError,
+    ConcurrencyConflictError,
+    InventoryError(InventoryGatewayError),
+    PaymentError(PaymentGatewayError),
+    GenericError(String),
+}
+
+impl std::fmt::Display for CheckoutSagaServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckoutSagaServiceError::SagaNotFoundError => {
+                write!(f, "Checkout saga not found error")
+            }
+            CheckoutSagaServiceError::SagaNotReadError => write!(f, "Checkout saga not read error"),
+            CheckoutSagaServiceError::SagaNotSavedError => {
+                write!(f, "Checkout saga not saved error")
+            }
+            CheckoutSagaServiceError::OrderNotFoundError => write!(f, "Order not found error"),
+            CheckoutSagaServiceError::OrderNotReadError => write!(f, "Order not read error"),
+            CheckoutSagaServiceError::OrderNotSavedError => write!(f, "Order not saved error"),
+            CheckoutSagaServiceError::PromotionNotReleasedError => {
+                write!(f, "Promotion not released error")
+            }
+            CheckoutSagaServiceError::ConcurrencyConflictError => {
+                write!(f, "Checkout was modified concurrently")
+            }
+            CheckoutSagaServiceError::InventoryError(error) => write!(f, "{}", error),
+            CheckoutSagaServiceError::PaymentError(error) => write!(f, "{}", error),
+            CheckoutSagaServiceError::GenericError(error) => write!(f, "Generic error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for CheckoutSagaServiceError {}
+
+/// What a step of the checkout changes: the saga, the order if changed and
+/// the event of the step. They are stored all or none.
+pub struct CheckoutStep {
+    pub saga: CheckoutSaga,
+    pub order: Option<Order>,
+    pub message: OutboxMessage,
+}
+
+/// Drives the checkout of placed orders, one step per event: `order_placed`
+/// reserves the stock, `stock_reserved` authorizes the payment,
+/// `payment_authorized` confirms the order and `order_confirmed` captures the
+/// payment, which invoices the order. A declined payment or missing stock
+/// cancels the order, compensates the completed steps and gives the coupons
+/// of the order back to the customer.
+pub struct CheckoutSagaService {
+    saga_repository: Box<dyn CheckoutSagaRepository + Send + Sync>,
+    order_repository: Box<dyn OrderRepository + Send + Sync>,
+    outbox_message_repository: Box<dyn OutboxMessageRepository + Send + Sync>,
+    inventory_gateway: Box<dyn InventoryGateway + Send + Sync>,
+    payment_gateway: Box<dyn PaymentGateway + Send + Sync>,
+    promotion_repository: Box<dyn PromotionRepository + Send + Sync>,
+}
+
+impl CheckoutSagaService {
+    pub fn new(
+        saga_repository: Box<dyn CheckoutSagaRepository + Send + Sync>,
+        order_repository: Box<dyn OrderRepository + Send + Sync>,
+        outbox_message_repository: Box<dyn OutboxMessageRepository + Send + Sync>,
+        inventory_gateway: Box<dyn InventoryGateway + Send + Sync>,
+        payment_gateway: Box<dyn PaymentGateway + Send + Sync>,
+        promotion_repository: Box<dyn PromotionRepository + Send + Sync>,
+    ) -> Self {
+        Self {
+            saga_repository,
+            order_repository,
+            outbox_message_repository,
+            inventory_gateway,
+            payment_gateway,
+            promotion_repository,
+        }
+    }
+
+    /// Runs the step triggered by the message and stores it; other events are
+    /// ignored, and so are steps already run. On error the saga is left where
+    /// it was, so the message can be handled again.
+    pub async fn handle(
+        &mut self,
+        message: &OutboxMessage,
+    ) -> Result<(), CheckoutSagaServiceError> {
+        match self.run(message).await? {
+            Some(step) => self.store(step).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Like `handle`, but returns the step instead of storing it, so that the
+    /// caller can store it in its own transaction. Only the calls to the
+    /// inventory and the payment provider are made, and they are idempotent.
+    pub async fn run(
+        &mut self,
+        message: &OutboxMessage,
+    ) -> Result<Option<CheckoutStep>, CheckoutSagaServiceError> {
+        match message.event_type() {
+            OutboxMessageType::OrderPlaced => {
+                let event: OrderPlacedEvent = parse_payload(message)?;
+                self.reserve_stock(parse_order_id(&event.order_id)?).await
+            }
+            OutboxMessageType::StockReserved => {
+                let event: StockReservedEvent = parse_payload(message)?;
+                self.authorize_payment(parse_order_id(&event.order_id)?)
+                    .await
+            }
+            OutboxMessageType::PaymentAuthorized => {
+                let event: PaymentAuthorizedEvent = parse_payload(message)?;
+                self.confirm_order(parse_order_id(&event.order_id)?).await
+            }
+            OutboxMessageType::OrderConfirmed => {
+                let event: OrderConfirmedEvent = parse_payload(message)?;
+                self.capture_payment(parse_order_id(&event.order_id)?)
+                    .await
+                    .map(|_| None)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Cancels the checkouts stuck on a step past its deadline and returns
+    /// how many were cancelled.
+    pub async fn cancel_timed_out(
+        &mut self,
+        now: DateTime<Utc>,
+    ) -> Result<usize, CheckoutSagaServiceError> {
+        let sagas = self
+            .saga_repository
+            .find_timed_out(now)
+            .await
+            .map_err(|e| {
+                error!("Error reading checkout sagas: {}", e);
+                CheckoutSagaServiceError::SagaNotReadError
+            })?;
+        let count = sagas.len();
+        for saga in sagas {
+            info!("Checkout of order {} timed out", saga.order_id().0);
+            let step = self.cancel(saga, "Checkout timed out").await?;
+            self.store(step).await?;
+        }
+        Ok(count)
+    }
+
+    async fn reserve_stock(
+        &mut self,
+        order_id: OrderId,
+    ) -> Result<Option<CheckoutStep>, CheckoutSagaServiceError> {
+        info!("Reserving stock for order {}", order_id.0);
+
+        let saga = self
+            .find_saga(&order_id)
+            .await?
+            .unwrap_or_else(|| CheckoutSaga::start(order_id.clone(), Utc::now()));
+        if saga.state() != CheckoutSagaState::ReservingStock {
+            info!("Stock already reserved for order {}", order_id.0);
+            return Ok(None);
+        }
+
+        let order = self.find_order(&order_id).await?;
+        match self
+            .inventory_gateway
+            .reserve(&order_id, order.order_items())
+            .await
+        {
+            Ok(reservation_id) => {
+                let mut saga = saga;
+                saga.stock_reserved(reservation_id.clone(), Utc::now())
+                    .map_err(|e| CheckoutSagaServiceError::GenericError(e.to_string()))?;
+                let message = OutboxMessage::stock_reserved_event(&order_id, &reservation_id);
+                step(saga, None, message).map(Some)
+            }
+            Err(e @ InventoryGatewayError::InsufficientStockError(_)) => {
+                self.cancel(saga, &e.to_string()).await.map(Some)
+            }
+            Err(e) => {
+                error!("Error reserving stock: {}", e);
+                Err(CheckoutSagaServiceError::InventoryError(e))
+            }
+        }
+    }
+
+    async fn authorize_payment(
+        &mut self,
+        order_id: OrderId,
+    ) -> Result<Option<CheckoutStep>, CheckoutSagaServiceError> {
+        info!("Authorizing payment of order {}", order_id.0);
+
+        let mut saga = self.get_saga(&order_id).await?;
+        if saga.state() != CheckoutSagaState::AuthorizingPayment {
+            info!("Payment already handled for order {}", order_id.0);
+            return Ok(None);
+        }
+
+        let amount = self.find_order(&order_id).await?.total_price();
+        match self.payment_gateway.authorize(&order_id, amount).await {
+            Ok(authorization_id) => {
+                saga.payment_authorized(authorization_id.clone(), Utc::now())
+                    .map_err(|e| CheckoutSagaServiceError::GenericError(e.to_string()))?;
+                let message =
+                    OutboxMessage::payment_authorized_event(&order_id, &authorization_id, amount);
+                step(saga, None, message).map(Some)
+            }
+            Err(e @ PaymentGatewayError::PaymentDeclinedError(_)) => {
+                self.cancel(saga, &e.to_string()).await.map(Some)
+            }
+            Err(e) => {
+                error!("Error authorizing payment: {}", e);
+                Err(CheckoutSagaServiceError::PaymentError(e))
+            }
+        }
+    }
+
+    async fn confirm_order(
+        &mut self,
+        order_id: OrderId,
+    ) -> Result<Option<CheckoutStep>, CheckoutSagaServiceError> {
+        info!("Confirming order {}", order_id.0);
+
+        let mut saga = self.get_saga(&order_id).await?;
+        if saga.state() != CheckoutSagaState::Confirming {
+            info!("Order {} already confirmed or cancelled", order_id.0);
+            return Ok(None);
+        }
+
+        let mut order = self.find_order(&order_id).await?;
+        if let Err(e) = order.confirm() {
+            return self.cancel(saga, &e.to_string()).await.map(Some);
+        }
+        if let Some(reservation_id) = saga.reservation_id() {
+            self.inventory_gateway
+                .commit(reservation_id)
+                .await
+                .map_err(|e| {
+                    error!("Error committing stock reservation: {}", e);
+                    CheckoutSagaServiceError::InventoryError(e)
+                })?;
+        }
+        saga.confirm(Utc::now())
+            .map_err(|e| CheckoutSagaServiceError::GenericError(e.to_string()))?;
+        let message = OutboxMessage::order_confirmed_event(&order_id);
+        step(saga, Some(order), message).map(Some)
+    }
+
+    /// Captures the authorized amount once the order is confirmed. The saga is
+    /// finished by then, so nothing is stored: capturing again is a no-op.
+    async fn capture_payment(&mut self, order_id: OrderId) -> Result<(), CheckoutSagaServiceError> {
+        info!("Capturing payment of order {}", order_id.0);
+
+        let saga = self.get_saga(&order_id).await?;
+        let authorization_id = match (saga.state(), saga.authorization_id()) {
+            (CheckoutSagaState::Confirmed, Some(authorization_id)) => authorization_id,
+            _ => {
+                info!("Order {} not confirmed, payment not captured", order_id.0);
+                return Ok(());
+            }
+        };
+        let amount = self.find_order(&order_id).await?.total_price();
+        self.payment_gateway
+            .capture(authorization_id, amount)
+            .await
+            .map_err(|e| {
+                error!("Error capturing payment: {}", e);
+                CheckoutSagaServiceError::PaymentError(e)
+            })
+    }
+
+    /// Undoes the completed steps, then cancels the order if still pending and
+    /// releases the redemptions of its coupons.
+    async fn cancel(
+        &mut self,
+        mut saga: CheckoutSaga,
+        reason: &str,
+    ) -> Result<CheckoutStep, CheckoutSagaServiceError> {
+        info!(
+            "Cancelling checkout of order {}: {}",
+            saga.order_id().0,
+            reason
+        );
+
+        let compensations = saga
+            .cancel(reason, Utc::now())
+            .map_err(|e| CheckoutSagaServiceError::GenericError(e.to_string()))?;
+        for compensation in compensations {
+            match compensation {
+                Compensation::VoidAuthorization(authorization_id) => self
+                    .payment_gateway
+                    .void(&authorization_id)
+                    .await
+                    .map_err(CheckoutSagaServiceError::PaymentError)?,
+                Compensation::ReleaseReservation(reservation_id) => self
+                    .inventory_gateway
+                    .release(&reservation_id)
+                    .await
+                    .map_err(CheckoutSagaServiceError::InventoryError)?,
+            }
+        }
+
+        let order_id = saga.order_id().clone();
+        let mut order = self.find_order(&order_id).await?;
+        let order = match order.cancel() {
+            Ok(()) => {
+                self.release_promotions(&order).await?;
+                Some(order)
+            }
+            Err(e) => {
+                error!("Order {} not cancelled: {}", order_id.0, e);
+                None
+            }
+        };
+        let message = OutboxMessage::order_cancelled_event(&order_id, reason);
+        step(saga, order, message)
+    }
+
+    /// Releasing again what was released is fine, so a cancellation handled
+    /// again does no harm.
+    async fn release_promotions(&self, order: &Order) -> Result<(), CheckoutSagaServiceError> {
+        for applied in order.promotions() {
+            let promotion = self
+                .promotion_repository
+                .find_by_code(applied.code.clone())
+                .await
+                .map_err(|e| {
+                    error!("Error reading promotion: {}", e);
+                    CheckoutSagaServiceError::PromotionNotReleasedError
+                })?;
+            if let Some(promotion) = promotion {
+                self.promotion_repository
+                    .release(promotion.id().clone(), order.id().clone())
+                    .await
+                    .map_err(|e| {
+                        error!("Error releasing promotion: {}", e);
+                        CheckoutSagaServiceError::PromotionNotReleasedError
+                    })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stores the saga, the order if changed and the event of the step, all
+    /// or none. A saga just started is saved, the others are updated.
+    async fn store(&mut self, step: CheckoutStep) -> Result<(), CheckoutSagaServiceError> {
+        let CheckoutStep {
+            saga,
+            order,
+            message,
+        } = step;
+
+        self.begin_transaction().await?;
+        if let Some(order) = order {
+            if let Err(e) = self.order_repository.update(order).await {
+                error!("Error saving order: {}", e);
+                self.rollback_transaction().await?;
+                return Err(match e {
+                    OrderRepositoryError::ConcurrencyConflict => {
+                        CheckoutSagaServiceError::ConcurrencyConflictError
+                    }
+                    _ => CheckoutSagaServiceError::OrderNotSavedError,
+                });
+            }
+        }
+        let saved = if saga.version() == 0 {
+            self.saga_repository.save(saga).await
+        } else {
+            self.saga_repository.update(saga).await
+        };
+        if let Err(e) = saved {
+            error!("Error saving checkout saga: {}", e);
+            self.rollback_transaction().await?;
+            return Err(saga_repository_error(e));
+        }
+        if let Err(e) = self.outbox_message_repository.save(message).await {
+            error!("Error saving outbox message: {}", e);
+            self.rollback_transaction().await?;
+            return Err(CheckoutSagaServiceError::GenericError(
+                "Outbox message not saved".to_string(),
+            ));
+        }
+        self.commit_transaction().await
+    }
+
+    async fn find_saga(
+        &self,
+        order_id: &OrderId,
+    ) -> Result<Option<CheckoutSaga>, CheckoutSagaServiceError> {
+        self.saga_repository
+            .find_by_order_id(order_id.clone())
+            .await
+            .map_err(|e| {
+                error!("Error reading checkout saga: {}", e);
+                CheckoutSagaServiceError::SagaNotReadError
+            })
+    }
+
+    async fn get_saga(&self, order_id: &OrderId) -> Result<CheckoutSaga, CheckoutSagaServiceError> {
+        self.find_saga(order_id).await?.ok_or_else(|| {
+            error!("Checkout saga of order {} not found", order_id.0);
+            CheckoutSagaServiceError::SagaNotFoundError
+        })
+    }
+
+    async fn find_order(&self, order_id: &OrderId) -> Result<Order, CheckoutSagaServiceError> {
+        match self.order_repository.find_by_id(order_id.clone()).await {
+            Ok(Some(order)) => Ok(order),
+            Ok(None) | Err(OrderRepositoryError::OrderNotFoundError) => {
+                error!("Order {} not found", order_id.0);
+                Err(CheckoutSagaServiceError::OrderNotFoundError)
+            }
+            Err(e) => {
+                error!("Error reading order: {}", e);
+                Err(CheckoutSagaServiceError::OrderNotReadError)
+            }
+        }
+    }
+
+    async fn begin_transaction(&mut self) -> Result<(), CheckoutSagaServiceError> {
+        self.saga_repository
+            .begin_transaction()
+            .await
+            .map_err(|e| CheckoutSagaServiceError::GenericError(e.to_string()))
+    }
+
+    async fn commit_transaction(&mut self) -> Result<(), CheckoutSagaServiceError> {
+        self.saga_repository
+            .commit_transaction()
+            .await
+            .map_err(|e| CheckoutSagaServiceError::GenericError(e.to_string()))
+    }
+
+    async fn rollback_transaction(&mut self) -> Result<(), CheckoutSagaServiceError> {
+        self.saga_repository
+            .rollback_transaction()
+            .await
+            .map_err(|e| CheckoutSagaServiceError::GenericError(e.to_string()))
+    }
+}
+
+fn step(
+    saga: CheckoutSaga,
+    order: Option<Order>,
+    message: Result<OutboxMessage, OutboxMessageError>,
+) -> Result<CheckoutStep, CheckoutSagaServiceError> {
+    let message = message.map_err(|e| CheckoutSagaServiceError::GenericError(e.to_string()))?;
+    Ok(CheckoutStep {
+        saga,
+        order,
+        message,
+    })
+}
+
+fn saga_repository_error(error: CheckoutSagaRepositoryError) -> CheckoutSagaServiceError {
+    match error {
+        CheckoutSagaRepositoryError::ConcurrencyConflict => {
+            CheckoutSagaServiceError::ConcurrencyConflictError
+        }
+        _ => CheckoutSagaServiceError::SagaNotSavedError,
+    }
+}
+
+fn parse_payload<T: DeserializeOwned>(
+    message: &OutboxMessage,
+) -> Result<T, CheckoutSagaServiceError> {
+    serde_json::from_str(&message.event_payload())
+        .map_err(|e| CheckoutSagaServiceError::GenericError(e.to_string()))
+}
+
+fn parse_order_id(order_id: &str) -> Result<OrderId, CheckoutSagaServiceError> {
+    Uuid::try_parse(order_id)
+        .map(OrderId)
+        .map_err(|e| CheckoutSagaServiceError::GenericError(e.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, Mutex};
+
+    use chrono::{Duration, Utc};
+    use uuid::Uuid;
+
+    use crate::{
+        entities::{
+            checkout_saga::{CheckoutSagaState, CHECKOUT_STEP_TIMEOUT_MINUTES},
+            order::{Order, OrderStatus},
+            outbox::{OutboxMessage, OutboxMessageType},
+            promotion::{AppliedPromotion, Discount, Promotion, PromotionConditions},
+        },
+        gateways::{
+            inventory_gateway::{InMemoryInventoryGateway, InMemoryReservationState},
+            payment_gateway::{
+                InMemoryPaymentGateway, MockPaymentGateway, PaymentGateway, PaymentGatewayError,
+            },
+        },
+        repositories::{
+            checkout_saga_repository::{CheckoutSagaRepository, InMemoryCheckoutSagaRepository},
+            event_sourced_order_repository::EventSourcedOrderRepository,
+            event_store::InMemoryEventStore,
+            order_repository::OrderRepository,
+            outbox_repository::{InMemoryOutboxMessageRepository, OutboxMessageRepository},
+            promotion_repository::MockPromotionRepository,
+        },
+        value_objects::{CouponCode, CustomerId, OrderId, OrderItem, ProductId, PromotionId},
+    };
+
+    use super::CheckoutSagaService;
+
+    struct Checkout {
+        service: CheckoutSagaService,
+        sagas: InMemoryCheckoutSagaRepository,
+        events: InMemoryEventStore,
+        outbox: InMemoryOutboxMessageRepository,
+        inventory: InMemoryInventoryGateway,
+        product_id: ProductId,
+    }
+
+    impl Checkout {
+        fn new(stock: i32, payment_gateway: Box<dyn PaymentGateway + Send + Sync>) -> Self {
+            Self::with_promotions(stock, payment_gateway, MockPromotionRepository::new())
+        }
+
+        fn with_promotions(
+            stock: i32,
+            payment_gateway: Box<dyn PaymentGateway + Send + Sync>,
+            promotion_repository: MockPromotionRepository,
+        ) -> Self {
+            let sagas = InMemoryCheckoutSagaRepository::new();
+            let events = InMemoryEventStore::new();
+            let outbox = InMemoryOutboxMessageRepository::new();
+            let inventory = InMemoryInventoryGateway::new();
+            let product_id = ProductId(Uuid::new_v4());
+            inventory.add_stock(&product_id, stock);
+            let service = CheckoutSagaService::new(
+                Box::new(sagas.clone()),
+                Box::new(EventSourcedOrderRepository::new(Box::new(events.clone()))),
+                Box::new(outbox.clone()),
+                Box::new(inventory.clone()),
+                payment_gateway,
+                Box::new(promotion_repository),
+            );
+            Self {
+                service,
+                sagas,
+                events,
+                outbox,
+                inventory,
+                product_id,
+            }
+        }
+
+        fn orders(&self) -> EventSourcedOrderRepository {
+            EventSourcedOrderRepository::new(Box::new(self.events.clone()))
+        }
+
+        async fn place_order(&self, quantity: i32) -> OrderId {
+            self.place_order_with_coupons(quantity, &[]).await
+        }
+
+        /// Each coupon takes 5.0 off.
+        async fn place_order_with_coupons(&self, quantity: i32, coupons: &[&str]) -> OrderId {
+            let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()));
+            order
+                .add(OrderItem {
+                    price: 10.0,
+                    quantity,
+                    product_id: self.product_id.clone(),
+                })
+                .unwrap();
+            for coupon in coupons {
+                order
+                    .apply_promotion(AppliedPromotion {
+                        code: CouponCode::parse(coupon).unwrap(),
+                        discount: Discount::Fixed(5.0),
+                        min_spend: None,
+                    })
+                    .unwrap();
+            }
+            order.place().unwrap();
+            let order = self.orders().save(order).await.unwrap();
+            self.outbox
+                .save(OutboxMessage::order_placed_event(&order).unwrap())
+                .await
+                .unwrap();
+            order.id().clone()
+        }
+
+        /// Delivers every pending message twice, as an at-least-once
+        /// consumer may, until no message is left.
+        async fn run(&mut self) {
+            while let Some(messages) = self.outbox.find_unprocessed().await.unwrap() {
+                for message in messages {
+                    self.outbox
+                        .set_processed(message.id(), Utc::now())
+                        .await
+                        .unwrap();
+                    self.service.handle(&message).await.unwrap();
+                    self.service.handle(&message).await.unwrap();
+                }
+            }
+        }
+
+        async fn order_status(&self, order_id: &OrderId) -> OrderStatus {
+            self.orders()
+                .find_by_id(order_id.clone())
+                .await
+                .unwrap()
+                .unwrap()
+                .status()
+        }
+
+        fn event_types(&self) -> Vec<OutboxMessageType> {
+            self.outbox
+                .messages()
+                .iter()
+                .map(|message| message.event_type())
+                .collect()
+        }
+    }
+
+    #[tokio::test]
+    async fn confirms_an_order_once_stock_is_reserved_and_payment_authorized() {
+        let payment_gateway = InMemoryPaymentGateway::new();
+        let mut checkout = Checkout::new(5, Box::new(payment_gateway.clone()));
+        let order_id = checkout.place_order(2).await;
+
+        checkout.run().await;
+
+        assert_eq!(
+            OrderStatus::Confirmed,
+            checkout.order_status(&order_id).await
+        );
+        assert_eq!(
+            vec![
+                OutboxMessageType::OrderPlaced,
+                OutboxMessageType::StockReserved,
+                OutboxMessageType::PaymentAuthorized,
+                OutboxMessageType::OrderConfirmed,
+            ],
+            checkout.event_types()
+        );
+        assert_eq!(
+            Some(InMemoryReservationState::Committed),
+            checkout.inventory.reservation_state(&order_id)
+        );
+        assert_eq!(3, checkout.inventory.available(&checkout.product_id));
+        assert_eq!(Some(20.0), payment_gateway.captured(&order_id));
+    }
+
+    #[tokio::test]
+    async fn releases_the_stock_and_cancels_the_order_when_payment_is_declined() {
+        let mut checkout = Checkout::new(5, Box::new(InMemoryPaymentGateway::with_limit(1.0)));
+        let order_id = checkout.place_order(2).await;
+
+        checkout.run().await;
+
+        assert_eq!(
+            OrderStatus::Cancelled,
+            checkout.order_status(&order_id).await
+        );
+        assert_eq!(
+            Some(OutboxMessageType::OrderCancelled),
+            checkout.event_types().last().cloned()
+        );
+        assert_eq!(
+            Some(InMemoryReservationState::Released),
+            checkout.inventory.reservation_state(&order_id)
+        );
+        assert_eq!(5, checkout.inventory.available(&checkout.product_id));
+        let saga = checkout
+            .sagas
+            .find_by_order_id(order_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(CheckoutSagaState::Cancelled, saga.state());
+        assert!(saga.failure_reason().unwrap().contains("declined"));
+    }
+
+    #[tokio::test]
+    async fn releases_the_coupons_of_a_cancelled_order() {
+        let promotion_id = PromotionId(Uuid::new_v4());
+        let released = Arc::new(Mutex::new(Vec::new()));
+        let mut promotion_repository = MockPromotionRepository::new();
+        let id = promotion_id.clone();
+        promotion_repository
+            .expect_find_by_code()
+            .returning(move |code| {
+                Ok(Some(
+                    Promotion::new(
+                        id.clone(),
+                        code,
+                        Discount::Fixed(5.0),
+                        PromotionConditions::default(),
+                    )
+                    .unwrap(),
+                ))
+            });
+        let releases = released.clone();
+        promotion_repository
+            .expect_release()
+            .returning(move |promotion_id, order_id| {
+                releases.lock().unwrap().push((promotion_id, order_id));
+                Ok(())
+            });
+        let mut checkout = Checkout::with_promotions(
+            1,
+            Box::new(InMemoryPaymentGateway::new()),
+            promotion_repository,
+        );
+        let order_id = checkout.place_order_with_coupons(2, &["WELCOME"]).await;
+
+        checkout.run().await;
+
+        assert_eq!(
+            OrderStatus::Cancelled,
+            checkout.order_status(&order_id).await
+        );
+        assert_eq!(vec![(promotion_id, order_id)], *released.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn cancels_the_order_when_stock_is_insufficient() {
+        let mut checkout = Checkout::new(1, Box::new(InMemoryPaymentGateway::new()));
+        let order_id = checkout.place_order(2).await;
+
+        checkout.run().await;
+
+        assert_eq!(
+            OrderStatus::Cancelled,
+            checkout.order_status(&order_id).await
+        );
+        assert_eq!(
+            vec![
+                OutboxMessageType::OrderPlaced,
+                OutboxMessageType::OrderCancelled,
+            ],
+            checkout.event_types()
+        );
+        assert_eq!(1, checkout.inventory.available(&checkout.product_id));
+    }
+
+    #[tokio::test]
+    async fn compensates_a_checkout_stuck_past_its_deadline() {
+        let mut payment_gateway = MockPaymentGateway::new();
+        payment_gateway.expect_authorize().returning(|_, _| {
+            Err(PaymentGatewayError::PaymentProviderUnavailableError(
+                "timeout".to_string(),
+            ))
+        });
+        let mut checkout = Checkout::new(5, Box::new(payment_gateway));
+        let order_id = checkout.place_order(2).await;
+        let order_placed = checkout.outbox.messages().remove(0);
+        checkout.service.handle(&order_placed).await.unwrap();
+        let stock_reserved = checkout.outbox.messages().remove(1);
+
+        assert!(checkout.service.handle(&stock_reserved).await.is_err());
+        assert_eq!(
+            0,
+            checkout.service.cancel_timed_out(Utc::now()).await.unwrap()
+        );
+
+        let later = Utc::now() + Duration::minutes(CHECKOUT_STEP_TIMEOUT_MINUTES + 1);
+        assert_eq!(1, checkout.service.cancel_timed_out(later).await.unwrap());
+        assert_eq!(
+            OrderStatus::Cancelled,
+            checkout.order_status(&order_id).await
+        );
+        assert_eq!(5, checkout.inventory.available(&checkout.product_id));
+    }
+}
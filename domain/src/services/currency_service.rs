@@ -0,0 +1,216 @@
+use chrono::NaiveDate;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    entities::currency::{CurrencyError, ExchangeRate, OrderCurrency, ProductPrice},
+    repositories::currency_repository::CurrencyRepository,
+    value_objects::{Currency, ProductId},
+};
+
+#[derive(Debug)]
+pub enum CurrencyServiceError {
+    ExchangeRateNotFoundError(Currency),
+    ExchangeRateNotReadError,
+    ExchangeRatesNotSavedError,
+    PriceNotFoundError(Currency),
+    PriceNotReadError,
+    PriceNotSavedError,
+    InvalidCurrencyError(CurrencyError),
+    GenericError(String),
+}
+
+impl std::fmt::Display for CurrencyServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CurrencyServiceError::ExchangeRateNotFoundError(currency) => {
+                write!(f, "No exchange rate of {} in effect", currency)
+            }
+            CurrencyServiceError::ExchangeRateNotReadError => {
+                write!(f, "Exchange rate not read error")
+            }
+            CurrencyServiceError::ExchangeRatesNotSavedError => {
+                write!(f, "Exchange rates not saved error")
+            }
+            CurrencyServiceError::PriceNotFoundError(currency) => {
+                write!(f, "Product has no price in {}", currency)
+            }
+            CurrencyServiceError::PriceNotReadError => write!(f, "Price not read error"),
+            CurrencyServiceError::PriceNotSavedError => write!(f, "Price not saved error"),
+            CurrencyServiceError::InvalidCurrencyError(error) => write!(f, "{}", error),
+            CurrencyServiceError::GenericError(error) => write!(f, "Generic error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for CurrencyServiceError {}
+
+pub struct SetPriceRequestObject {
+    pub product_id: String,
+    /// ISO 4217 code.
+    pub currency: String,
+    pub amount: f64,
+}
+
+/// Keeps the exchange rates to the base currency and the price lists of
+/// products.
+pub struct CurrencyService {
+    currency_repository: Box<dyn CurrencyRepository + Send + Sync>,
+}
+
+impl CurrencyService {
+    pub fn new(currency_repository: Box<dyn CurrencyRepository + Send + Sync>) -> Self {
+        Self {
+            currency_repository,
+        }
+    }
+
+    /// Imports the rates of a CSV file, see `ExchangeRate::parse_csv`. A file
+    /// with an invalid line imports nothing.
+    pub async fn import_rates(&self, csv: &str) -> Result<Vec<ExchangeRate>, CurrencyServiceError> {
+        let rates =
+            ExchangeRate::parse_csv(csv).map_err(CurrencyServiceError::InvalidCurrencyError)?;
+
+        info!("Importing {} exchange rates", rates.len());
+
+        self.currency_repository
+            .save_rates(rates.clone())
+            .await
+            .map_err(|e| {
+                error!("Error saving exchange rates: {}", e);
+                CurrencyServiceError::ExchangeRatesNotSavedError
+            })?;
+        Ok(rates)
+    }
+
+    /// The currency with the rate in effect on `day`; 1 for the base currency.
+    pub async fn order_currency(
+        &self,
+        currency: Currency,
+        day: NaiveDate,
+    ) -> Result<OrderCurrency, CurrencyServiceError> {
+        if currency == Currency::BASE {
+            return Ok(OrderCurrency::default());
+        }
+        self.currency_repository
+            .find_rate(currency, day)
+            .await
+            .map_err(|e| {
+                error!("Error reading exchange rate: {}", e);
+                CurrencyServiceError::ExchangeRateNotReadError
+            })?
+            .map(|rate| OrderCurrency::new(&rate))
+            .ok_or(CurrencyServiceError::ExchangeRateNotFoundError(currency))
+    }
+
+    pub async fn set_price(
+        &self,
+        request: SetPriceRequestObject,
+    ) -> Result<ProductPrice, CurrencyServiceError> {
+        let product_id = parse_product_id(&request.product_id)?;
+        let currency = Currency::parse(&request.currency)
+            .map_err(|e| CurrencyServiceError::GenericError(format!("currency {}", e)))?;
+        let price = ProductPrice::new(product_id, currency, request.amount)
+            .map_err(CurrencyServiceError::InvalidCurrencyError)?;
+
+        info!("Setting price of product in {}", currency);
+
+        self.currency_repository
+            .save_price(price.clone())
+            .await
+            .map_err(|e| {
+                error!("Error saving price: {}", e);
+                CurrencyServiceError::PriceNotSavedError
+            })?;
+        Ok(price)
+    }
+
+    pub async fn find_prices(
+        &self,
+        product_id: &str,
+    ) -> Result<Vec<ProductPrice>, CurrencyServiceError> {
+        let product_id = parse_product_id(product_id)?;
+        self.prices_of(product_id).await
+    }
+
+    /// Price of the product in the currency of its price list.
+    pub async fn price_of(
+        &self,
+        product_id: &ProductId,
+        currency: Currency,
+    ) -> Result<f64, CurrencyServiceError> {
+        self.prices_of(product_id.clone())
+            .await?
+            .into_iter()
+            .find(|price| price.currency == currency)
+            .map(|price| price.amount)
+            .ok_or(CurrencyServiceError::PriceNotFoundError(currency))
+    }
+
+    async fn prices_of(
+        &self,
+        product_id: ProductId,
+    ) -> Result<Vec<ProductPrice>, CurrencyServiceError> {
+        self.currency_repository
+            .find_prices(product_id)
+            .await
+            .map_err(|e| {
+                error!("Error reading prices: {}", e);
+                CurrencyServiceError::PriceNotReadError
+            })
+    }
+}
+
+fn parse_product_id(product_id: &str) -> Result<ProductId, CurrencyServiceError> {
+    Uuid::try_parse(product_id)
+        .map(ProductId)
+        .map_err(|err| CurrencyServiceError::GenericError(err.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use crate::{
+        repositories::currency_repository::InMemoryCurrencyRepository, value_objects::Currency,
+    };
+
+    use super::{CurrencyService, CurrencyServiceError};
+
+    #[tokio::test]
+    async fn finds_the_rate_in_effect_on_a_day() {
+        let service = CurrencyService::new(Box::new(InMemoryCurrencyRepository::new()));
+        service
+            .import_rates("USD,2025-11-01,1.10\nUSD,2025-11-10,1.20\nUSD,2025-11-01,1.15")
+            .await
+            .unwrap();
+
+        assert_eq!(1.15, rate(&service, day(9)).await.unwrap());
+        assert_eq!(1.20, rate(&service, day(10)).await.unwrap());
+        assert!(matches!(
+            rate(&service, day(1).pred_opt().unwrap()).await,
+            Err(CurrencyServiceError::ExchangeRateNotFoundError(
+                Currency::Usd
+            ))
+        ));
+        assert_eq!(
+            1.0,
+            service
+                .order_currency(Currency::Eur, day(1))
+                .await
+                .unwrap()
+                .exchange_rate
+        );
+    }
+
+    async fn rate(service: &CurrencyService, day: NaiveDate) -> Result<f64, CurrencyServiceError> {
+        service
+            .order_currency(Currency::Usd, day)
+            .await
+            .map(|currency| currency.exchange_rate)
+    }
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).unwrap()
+    }
+}
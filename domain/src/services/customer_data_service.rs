@@ -0,0 +1,342 @@
+use chrono::Utc;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    entities::{customer::Customer, order::Order, outbox::OutboxMessage},
+    repositories::{
+        customer_data_repository::{CustomerDataRepository, CustomerDataRepositoryError},
+        customer_repository::{CustomerRepository, CustomerRepositoryError},
+        order_repository::OrderRepository,
+        outbox_repository::OutboxMessageRepository,
+    },
+    value_objects::CustomerId,
+};
+
+#[derive(Debug)]
+pub enum CustomerDataServiceError {
+    CustomerNotFoundError,
+    CustomerNotReadError,
+    CustomerNotErasedError,
+    ConcurrencyConflictError,
+    OrdersNotReadError,
+    EventsNotReadError,
+    GenericError(String),
+}
+
+impl std::fmt::Display for CustomerDataServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CustomerDataServiceError::CustomerNotFoundError => {
+                write!(f, "Customer not found error")
+            }
+            CustomerDataServiceError::CustomerNotReadError => write!(f, "Customer not read error"),
+            CustomerDataServiceError::CustomerNotErasedError => {
+                write!(f, "Customer not erased error")
+            }
+            CustomerDataServiceError::ConcurrencyConflictError => {
+                write!(f, "Customer was modified concurrently")
+            }
+            CustomerDataServiceError::OrdersNotReadError => write!(f, "Orders not read error"),
+            CustomerDataServiceError::EventsNotReadError => write!(f, "Events not read error"),
+            CustomerDataServiceError::GenericError(error) => write!(f, "Generic error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for CustomerDataServiceError {}
+
+/// Everything stored about a customer, as requested by the customer.
+pub struct CustomerDataExport {
+    pub customer: Customer,
+    pub orders: Vec<Order>,
+    pub events: Vec<OutboxMessage>,
+}
+
+/// Answers data-subject requests: exports or erases the personal data of a
+/// customer, deactivated ones included.
+pub struct CustomerDataService {
+    customer_repository: Box<dyn CustomerRepository>,
+    order_repository: Box<dyn OrderRepository>,
+    outbox_message_repository: Box<dyn OutboxMessageRepository>,
+    customer_data_repository: Box<dyn CustomerDataRepository>,
+}
+
+impl CustomerDataService {
+    pub fn new(
+        customer_repository: Box<dyn CustomerRepository>,
+        order_repository: Box<dyn OrderRepository>,
+        outbox_message_repository: Box<dyn OutboxMessageRepository>,
+        customer_data_repository: Box<dyn CustomerDataRepository>,
+    ) -> Self {
+        Self {
+            customer_repository,
+            order_repository,
+            outbox_message_repository,
+            customer_data_repository,
+        }
+    }
+
+    pub async fn export(
+        &self,
+        customer_id: &str,
+    ) -> Result<CustomerDataExport, CustomerDataServiceError> {
+        info!("Exporting customer data");
+
+        let customer = self.find_customer(customer_id).await?;
+        let orders = self
+            .order_repository
+            .find_by_customer_id(customer.id.clone())
+            .await
+            .map_err(|e| {
+                error!("Error reading orders: {}", e);
+                CustomerDataServiceError::OrdersNotReadError
+            })?;
+        let events = self.find_events(&customer.id).await?;
+
+        Ok(CustomerDataExport {
+            customer,
+            orders,
+            events,
+        })
+    }
+
+    /// Anonymizes the customer, the addresses of its orders and every stored
+    /// event mentioning it, then records a `customer_erased` event, all in one
+    /// transaction. Order items and prices are kept.
+    pub async fn erase(&self, customer_id: &str) -> Result<(), CustomerDataServiceError> {
+        info!("Erasing customer data");
+
+        let mut customer = self.find_customer(customer_id).await?;
+        customer.erase(Utc::now());
+        let anonymized_events = self
+            .find_events(&customer.id)
+            .await?
+            .iter()
+            .map(|event| {
+                event.anonymized().map_err(|e| {
+                    error!("Error anonymizing outbox message {}: {}", event.id(), e);
+                    CustomerDataServiceError::CustomerNotErasedError
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let erased_event = OutboxMessage::customer_erased_event(&customer)
+            .map_err(|e| CustomerDataServiceError::GenericError(e.to_string()))?;
+
+        self.customer_data_repository
+            .erase(customer, anonymized_events, erased_event)
+            .await
+            .map_err(|e| {
+                error!("Error erasing customer: {}", e);
+                match e {
+                    CustomerDataRepositoryError::ConcurrencyConflict => {
+                        CustomerDataServiceError::ConcurrencyConflictError
+                    }
+                    CustomerDataRepositoryError::CustomerNotFoundError => {
+                        CustomerDataServiceError::CustomerNotFoundError
+                    }
+                    CustomerDataRepositoryError::CustomerNotErasedError(_) => {
+                        CustomerDataServiceError::CustomerNotErasedError
+                    }
+                }
+            })?;
+        Ok(())
+    }
+
+    async fn find_customer(&self, customer_id: &str) -> Result<Customer, CustomerDataServiceError> {
+        let customer_id = Uuid::try_parse(customer_id)
+            .map_err(|err| CustomerDataServiceError::GenericError(err.to_string()))?;
+        match self
+            .customer_repository
+            .find_by_id(CustomerId(customer_id))
+            .await
+        {
+            Ok(Some(customer)) => Ok(customer),
+            Ok(None) | Err(CustomerRepositoryError::CustomerNotFoundError) => {
+                error!("Customer not found");
+                Err(CustomerDataServiceError::CustomerNotFoundError)
+            }
+            Err(_) => Err(CustomerDataServiceError::CustomerNotReadError),
+        }
+    }
+
+    async fn find_events(
+        &self,
+        customer_id: &CustomerId,
+    ) -> Result<Vec<OutboxMessage>, CustomerDataServiceError> {
+        self.outbox_message_repository
+            .find_by_customer_id(customer_id)
+            .await
+            .map_err(|e| {
+                error!("Error reading outbox messages: {}", e);
+                CustomerDataServiceError::EventsNotReadError
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use crate::{
+        entities::{
+            address_book::AddressBook,
+            customer::Customer,
+            order::{Order, OrderAddresses},
+            outbox::{OutboxMessage, OutboxMessageType},
+        },
+        repositories::{
+            customer_data_repository::MockCustomerDataRepository,
+            customer_repository::MockMyCustomerRepository, order_repository::MockMyOrderRepository,
+            outbox_repository::MockOutboxMessageRepository,
+        },
+        value_objects::{
+            Address, CountryCode, Currency, CustomerId, Email, OrderId, PersonName, PostalCode,
+        },
+    };
+
+    use super::{CustomerDataService, CustomerDataServiceError};
+
+    const CUSTOMER_ID: &str = "2585491a-8e05-11ee-af1c-9bfe41ffe61f";
+
+    #[tokio::test]
+    async fn exports_the_customer_with_its_orders_and_events() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_id()
+            .returning(|_| Ok(Some(customer())));
+        let mut order_repository = MockMyOrderRepository::new();
+        order_repository
+            .expect_find_by_customer_id()
+            .returning(|customer_id| Ok(vec![Order::create(OrderId(Uuid::new_v4()), customer_id)]));
+        let mut outbox_message_repository = MockOutboxMessageRepository::new();
+        outbox_message_repository
+            .expect_find_by_customer_id()
+            .returning(|_| {
+                Ok(vec![
+                    OutboxMessage::customer_created_event(&customer()).unwrap()
+                ])
+            });
+        let service = CustomerDataService::new(
+            Box::new(customer_repository),
+            Box::new(order_repository),
+            Box::new(outbox_message_repository),
+            Box::new(MockCustomerDataRepository::new()),
+        );
+
+        let export = service.export(CUSTOMER_ID).await.unwrap();
+
+        assert_eq!("mario.rossi@example.com", export.customer.email.as_str());
+        assert_eq!(1, export.orders.len());
+        assert_eq!(
+            OutboxMessageType::CustomerCreated,
+            export.events[0].event_type()
+        );
+    }
+
+    #[tokio::test]
+    async fn erases_the_customer_and_anonymizes_its_events() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_id()
+            .returning(|_| Ok(Some(customer())));
+        let mut outbox_message_repository = MockOutboxMessageRepository::new();
+        outbox_message_repository
+            .expect_find_by_customer_id()
+            .returning(|_| {
+                let address = customer().address;
+                let order = Order::create(OrderId(Uuid::new_v4()), customer().id).with_addresses(
+                    OrderAddresses {
+                        shipping: address.clone(),
+                        billing: address,
+                    },
+                );
+                let review_submitted = OutboxMessage::new(
+                    Uuid::new_v4(),
+                    OutboxMessageType::ReviewSubmitted,
+                    serde_json::json!({
+                        "review_id": Uuid::new_v4().to_string(),
+                        "product_id": Uuid::new_v4().to_string(),
+                        "customer_id": CUSTOMER_ID,
+                        "rating": 5,
+                        "text": "Mario loves it",
+                        "verified_purchase": true,
+                    })
+                    .to_string(),
+                    chrono::Utc::now(),
+                    None,
+                );
+                Ok(vec![
+                    OutboxMessage::customer_created_event(&customer()).unwrap(),
+                    OutboxMessage::order_created_event(&order).unwrap(),
+                    review_submitted,
+                ])
+            });
+        let mut customer_data_repository = MockCustomerDataRepository::new();
+        customer_data_repository
+            .expect_erase()
+            .withf(|customer, anonymized_events, erased_event| {
+                customer.first_name.as_str() == "erased"
+                    && !customer.is_active()
+                    && anonymized_events.len() == 3
+                    && anonymized_events.iter().all(|message| {
+                        let payload = message.event_payload();
+                        !payload.contains("Mario") && !payload.contains("Via Roma")
+                    })
+                    && erased_event.event_type() == OutboxMessageType::CustomerErased
+            })
+            .times(1)
+            .returning(|customer, _, _| Ok(customer));
+        let service = CustomerDataService::new(
+            Box::new(customer_repository),
+            Box::new(MockMyOrderRepository::new()),
+            Box::new(outbox_message_repository),
+            Box::new(customer_data_repository),
+        );
+
+        let result = service.erase(CUSTOMER_ID).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn cannot_erase_a_missing_customer() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_id()
+            .returning(|_| Ok(None));
+        let service = CustomerDataService::new(
+            Box::new(customer_repository),
+            Box::new(MockMyOrderRepository::new()),
+            Box::new(MockOutboxMessageRepository::new()),
+            Box::new(MockCustomerDataRepository::new()),
+        );
+
+        let result = service.erase(CUSTOMER_ID).await;
+
+        assert!(matches!(
+            result,
+            Err(CustomerDataServiceError::CustomerNotFoundError)
+        ));
+    }
+
+    fn customer() -> Customer {
+        Customer {
+            id: CustomerId(Uuid::try_parse(CUSTOMER_ID).unwrap()),
+            first_name: PersonName::parse("Mario").unwrap(),
+            last_name: PersonName::parse("Rossi").unwrap(),
+            email: Email::parse("mario.rossi@example.com").unwrap(),
+            phone: None,
+            address: Address {
+                street: "Via Roma 1".to_string(),
+                city: "Milano".to_string(),
+                zip_code: PostalCode::parse("20121", &CountryCode::parse("IT").unwrap()).unwrap(),
+                country: CountryCode::parse("IT").unwrap(),
+            },
+            address_book: AddressBook::new(),
+            currency: Currency::Eur,
+            deactivated_at: None,
+            version: 1,
+        }
+    }
+}
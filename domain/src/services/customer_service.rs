@@ -1,24 +1,71 @@
+use chrono::Utc;
 use tracing::{error, info};
 use uuid::Uuid;
 
 use crate::{
-    entities::{customer::Customer, outbox::OutboxMessage},
+    entities::{
+        address_book::AddressBookError,
+        customer::Customer,
+        outbox::{OutboxMessage, OutboxMessageError},
+    },
     repositories::{
-        customer_repository::CustomerRepository, outbox_repository::OutboxMessageRepository,
+        customer_repository::{CustomerRepository, CustomerRepositoryError},
+        outbox_repository::OutboxMessageRepository,
+    },
+    value_objects::{
+        Address, AddressId, CountryCode, Currency, CustomerId, Email, InvalidValueError,
+        PersonName, PhoneNumber, PostalCode,
     },
-    value_objects::{Address, CustomerId},
 };
 
 #[derive(Debug)]
 pub enum CustomerServiceError {
+    CustomerNotFoundError,
+    CustomerNotReadError,
     CustomerNotSavedError,
+    CustomerAlreadyExistsError,
+    CustomerDeactivatedError,
+    ConcurrencyConflictError,
+    AddressBookError(AddressBookError),
+    ValidationError(Vec<FieldError>),
     GenericError(String),
 }
 
+/// A request field that failed validation.
+#[derive(Debug, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub error: InvalidValueError,
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.field, self.error)
+    }
+}
+
 impl std::fmt::Display for CustomerServiceError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            CustomerServiceError::CustomerNotFoundError => write!(f, "Customer not found error"),
+            CustomerServiceError::CustomerNotReadError => write!(f, "Customer not read error"),
             CustomerServiceError::CustomerNotSavedError => write!(f, "Customer not saved error"),
+            CustomerServiceError::CustomerAlreadyExistsError => {
+                write!(f, "A customer with this email already exists")
+            }
+            CustomerServiceError::CustomerDeactivatedError => {
+                write!(f, "Customer is deactivated")
+            }
+            CustomerServiceError::ConcurrencyConflictError => {
+                write!(f, "Customer was modified concurrently")
+            }
+            CustomerServiceError::AddressBookError(error) => {
+                write!(f, "Address book error: {}", error)
+            }
+            CustomerServiceError::ValidationError(errors) => {
+                let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                write!(f, "Validation error: {}", errors.join(", "))
+            }
             CustomerServiceError::GenericError(error) => write!(f, "Generic error: ${error}"),
         }
     }
@@ -29,10 +76,57 @@ impl std::error::Error for CustomerServiceError {}
 pub struct CreateCustomerRequestObject {
     pub first_name: String,
     pub last_name: String,
+    pub email: String,
+    pub phone: Option<String>,
     pub street: String,
     pub city: String,
     pub zip_code: String,
-    pub state: String,
+    /// ISO 3166-1 alpha-2 code.
+    pub country: String,
+    /// ISO 4217 code of the currency of the customer's orders; the base
+    /// currency if unset.
+    pub currency: Option<String>,
+}
+
+pub struct UpdateCustomerRequestObject {
+    pub customer_id: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub phone: Option<String>,
+    /// When set, the customer is updated only if it is still at this version.
+    pub expected_version: Option<i64>,
+}
+
+pub struct ChangeAddressRequestObject {
+    pub customer_id: String,
+    pub street: String,
+    pub city: String,
+    pub zip_code: String,
+    /// ISO 3166-1 alpha-2 code.
+    pub country: String,
+    /// When set, the address is changed only if the customer is still at this version.
+    pub expected_version: Option<i64>,
+}
+
+pub struct AddAddressRequestObject {
+    pub customer_id: String,
+    pub street: String,
+    pub city: String,
+    pub zip_code: String,
+    /// ISO 3166-1 alpha-2 code.
+    pub country: String,
+    /// When set, the address is added only if the customer is still at this version.
+    pub expected_version: Option<i64>,
+}
+
+pub struct SetDefaultAddressesRequestObject {
+    pub customer_id: String,
+    /// Defaults left unset are not changed.
+    pub shipping_address_id: Option<String>,
+    pub billing_address_id: Option<String>,
+    /// When set, the defaults are changed only if the customer is still at this version.
+    pub expected_version: Option<i64>,
 }
 
 pub struct CustomerService {
@@ -56,30 +150,270 @@ impl CustomerService {
         request: CreateCustomerRequestObject,
     ) -> Result<Customer, CustomerServiceError> {
         let customer_id = CustomerId(Uuid::new_v4());
-        let first_name = request.first_name;
-        let last_name = request.last_name;
-        let address = Address {
-            street: request.street,
-            city: request.city,
-            zip_code: request.zip_code,
-            state: request.state,
+        let customer = match validate(customer_id, request) {
+            Ok(customer) => customer,
+            Err(errors) => {
+                error!("Invalid customer");
+                return Err(CustomerServiceError::ValidationError(errors));
+            }
         };
-        let customer = Customer::new(customer_id, first_name, last_name, address);
 
         info!("Creating customer");
 
+        let existing_customer = self
+            .customer_repository
+            .find_by_email(&customer.email)
+            .await
+            .map_err(|_| CustomerServiceError::CustomerNotReadError)?;
+        if existing_customer.is_some() {
+            error!("Customer already exists");
+            return Err(CustomerServiceError::CustomerAlreadyExistsError);
+        }
+
+        self.begin_transaction().await?;
+        // The email may have been taken since the check above: `publish` maps
+        // the repository's `CustomerAlreadyExists` too.
+        let result = self.customer_repository.save(customer).await;
+        self.publish(result, OutboxMessage::customer_created_event)
+            .await
+    }
+
+    pub async fn update_customer(
+        &mut self,
+        request: UpdateCustomerRequestObject,
+    ) -> Result<Customer, CustomerServiceError> {
+        let mut errors = vec![];
+        let Some((first_name, last_name, email, phone)) = validate_profile(
+            &mut errors,
+            &request.first_name,
+            &request.last_name,
+            &request.email,
+            request.phone.as_deref(),
+        ) else {
+            error!("Invalid customer");
+            return Err(CustomerServiceError::ValidationError(errors));
+        };
+
+        info!("Updating customer");
+
+        let mut customer = self
+            .find_active_customer(&request.customer_id, request.expected_version)
+            .await?;
+        if email != customer.email {
+            let existing_customer = self
+                .customer_repository
+                .find_by_email(&email)
+                .await
+                .map_err(|_| CustomerServiceError::CustomerNotReadError)?;
+            if existing_customer.is_some() {
+                error!("Customer already exists");
+                return Err(CustomerServiceError::CustomerAlreadyExistsError);
+            }
+        }
+        customer.update_profile(first_name, last_name, email, phone);
+
+        self.begin_transaction().await?;
+        let result = self.customer_repository.update(customer).await;
+        self.publish(result, OutboxMessage::customer_updated_event)
+            .await
+    }
+
+    pub async fn change_address(
+        &mut self,
+        request: ChangeAddressRequestObject,
+    ) -> Result<Customer, CustomerServiceError> {
+        let mut errors = vec![];
+        let Some(address) = validate_address(
+            &mut errors,
+            &request.street,
+            &request.city,
+            &request.zip_code,
+            &request.country,
+        ) else {
+            error!("Invalid address");
+            return Err(CustomerServiceError::ValidationError(errors));
+        };
+
+        info!("Changing customer address");
+
+        let mut customer = self
+            .find_active_customer(&request.customer_id, request.expected_version)
+            .await?;
+        customer.change_address(address);
+
+        self.begin_transaction().await?;
+        let result = self.customer_repository.update(customer).await;
+        self.publish(result, OutboxMessage::customer_address_changed_event)
+            .await
+    }
+
+    pub async fn add_address(
+        &mut self,
+        request: AddAddressRequestObject,
+    ) -> Result<Customer, CustomerServiceError> {
+        let mut errors = vec![];
+        let Some(address) = validate_address(
+            &mut errors,
+            &request.street,
+            &request.city,
+            &request.zip_code,
+            &request.country,
+        ) else {
+            error!("Invalid address");
+            return Err(CustomerServiceError::ValidationError(errors));
+        };
+
+        info!("Adding customer address");
+
+        let mut customer = self
+            .find_active_customer(&request.customer_id, request.expected_version)
+            .await?;
+        customer
+            .add_address(AddressId(Uuid::new_v4()), address)
+            .map_err(CustomerServiceError::AddressBookError)?;
+
+        self.begin_transaction().await?;
+        let result = self.customer_repository.update(customer).await;
+        self.publish(result, OutboxMessage::customer_address_book_changed_event)
+            .await
+    }
+
+    pub async fn remove_address(
+        &mut self,
+        customer_id: &str,
+        address_id: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Customer, CustomerServiceError> {
+        let address_id = parse_address_id(address_id)?;
+
+        info!("Removing customer address");
+
+        let mut customer = self
+            .find_active_customer(customer_id, expected_version)
+            .await?;
+        customer
+            .remove_address(&address_id)
+            .map_err(CustomerServiceError::AddressBookError)?;
+
+        self.begin_transaction().await?;
+        let result = self.customer_repository.update(customer).await;
+        self.publish(result, OutboxMessage::customer_address_book_changed_event)
+            .await
+    }
+
+    pub async fn set_default_addresses(
+        &mut self,
+        request: SetDefaultAddressesRequestObject,
+    ) -> Result<Customer, CustomerServiceError> {
+        let shipping_address_id = request
+            .shipping_address_id
+            .as_deref()
+            .map(parse_address_id)
+            .transpose()?;
+        let billing_address_id = request
+            .billing_address_id
+            .as_deref()
+            .map(parse_address_id)
+            .transpose()?;
+
+        info!("Setting customer default addresses");
+
+        let mut customer = self
+            .find_active_customer(&request.customer_id, request.expected_version)
+            .await?;
+        if let Some(address_id) = shipping_address_id {
+            customer
+                .set_default_shipping_address(&address_id)
+                .map_err(CustomerServiceError::AddressBookError)?;
+        }
+        if let Some(address_id) = billing_address_id {
+            customer
+                .set_default_billing_address(&address_id)
+                .map_err(CustomerServiceError::AddressBookError)?;
+        }
+
+        self.begin_transaction().await?;
+        let result = self.customer_repository.update(customer).await;
+        self.publish(result, OutboxMessage::customer_address_book_changed_event)
+            .await
+    }
+
+    pub async fn deactivate(
+        &mut self,
+        customer_id: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Customer, CustomerServiceError> {
+        info!("Deactivating customer");
+
+        let mut customer = self
+            .find_active_customer(customer_id, expected_version)
+            .await?;
+        customer.deactivate(Utc::now());
+
         self.begin_transaction().await?;
+        let result = self.customer_repository.delete(customer).await;
+        self.publish(result, OutboxMessage::customer_deactivated_event)
+            .await
+    }
 
-        let saved_customer = match self.customer_repository.save(customer).await {
+    async fn find_active_customer(
+        &self,
+        customer_id: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Customer, CustomerServiceError> {
+        let customer_id = Uuid::try_parse(customer_id)
+            .map_err(|err| CustomerServiceError::GenericError(err.to_string()))?;
+        let customer = match self
+            .customer_repository
+            .find_by_id(CustomerId(customer_id))
+            .await
+        {
+            Ok(Some(customer)) => customer,
+            Ok(None) | Err(CustomerRepositoryError::CustomerNotFoundError) => {
+                error!("Customer not found");
+                return Err(CustomerServiceError::CustomerNotFoundError);
+            }
+            Err(_) => return Err(CustomerServiceError::CustomerNotReadError),
+        };
+        if !customer.is_active() {
+            error!("Customer is deactivated");
+            return Err(CustomerServiceError::CustomerDeactivatedError);
+        }
+        if expected_version.is_some_and(|version| version != customer.version) {
+            error!("Customer version does not match the expected one");
+            return Err(CustomerServiceError::ConcurrencyConflictError);
+        }
+        Ok(customer)
+    }
+
+    /// Records the event of a change stored in the transaction already begun,
+    /// then commits.
+    async fn publish(
+        &mut self,
+        stored: Result<Customer, CustomerRepositoryError>,
+        event: fn(&Customer) -> Result<OutboxMessage, OutboxMessageError>,
+    ) -> Result<Customer, CustomerServiceError> {
+        let customer = match stored {
             Ok(customer) => customer,
             Err(e) => {
                 error!("Error saving customer: {}", e);
                 self.rollback_transaction().await?;
-                return Err(CustomerServiceError::CustomerNotSavedError);
+                return Err(match e {
+                    CustomerRepositoryError::ConcurrencyConflict => {
+                        CustomerServiceError::ConcurrencyConflictError
+                    }
+                    CustomerRepositoryError::CustomerAlreadyExists => {
+                        CustomerServiceError::CustomerAlreadyExistsError
+                    }
+                    CustomerRepositoryError::CustomerNotFoundError => {
+                        CustomerServiceError::CustomerNotFoundError
+                    }
+                    _ => CustomerServiceError::CustomerNotSavedError,
+                });
             }
         };
 
-        let message = match OutboxMessage::customer_created_event(&saved_customer) {
+        let message = match event(&customer) {
             Ok(message) => message,
             Err(e) => {
                 error!("Error serializing outbox message: {}", e);
@@ -99,7 +433,7 @@ impl CustomerService {
         };
 
         self.commit_transaction().await?;
-        return Ok(saved_customer);
+        Ok(customer)
     }
 
     async fn begin_transaction(&mut self) -> Result<(), CustomerServiceError> {
@@ -124,12 +458,119 @@ impl CustomerService {
     }
 }
 
+/// Checks every field, so that all the errors can be reported at once.
+fn validate(
+    customer_id: CustomerId,
+    request: CreateCustomerRequestObject,
+) -> Result<Customer, Vec<FieldError>> {
+    let mut errors = vec![];
+    let profile = validate_profile(
+        &mut errors,
+        &request.first_name,
+        &request.last_name,
+        &request.email,
+        request.phone.as_deref(),
+    );
+    let address = validate_address(
+        &mut errors,
+        &request.street,
+        &request.city,
+        &request.zip_code,
+        &request.country,
+    );
+
+    let currency = match request.currency.as_deref().map(str::trim) {
+        None | Some("") => Some(Currency::default()),
+        Some(currency) => check(&mut errors, "currency", Currency::parse(currency)),
+    };
+
+    match (profile, address, currency) {
+        (Some((first_name, last_name, email, phone)), Some(address), Some(currency)) => {
+            Ok(Customer {
+                currency,
+                ..Customer::new(customer_id, first_name, last_name, email, phone, address)
+            })
+        }
+        _ => Err(errors),
+    }
+}
+
+type Profile = (PersonName, PersonName, Email, Option<PhoneNumber>);
+
+fn validate_profile(
+    errors: &mut Vec<FieldError>,
+    first_name: &str,
+    last_name: &str,
+    email: &str,
+    phone: Option<&str>,
+) -> Option<Profile> {
+    let first_name = check(errors, "first_name", PersonName::parse(first_name));
+    let last_name = check(errors, "last_name", PersonName::parse(last_name));
+    let email = check(errors, "email", Email::parse(email));
+    let phone = match phone.map(str::trim) {
+        None | Some("") => Some(None),
+        Some(phone) => check(errors, "phone", PhoneNumber::parse(phone)).map(Some),
+    };
+    Some((first_name?, last_name?, email?, phone?))
+}
+
+fn validate_address(
+    errors: &mut Vec<FieldError>,
+    street: &str,
+    city: &str,
+    zip_code: &str,
+    country: &str,
+) -> Option<Address> {
+    let street = check(errors, "street", required(street));
+    let city = check(errors, "city", required(city));
+    let country = check(errors, "country", CountryCode::parse(country))?;
+    // A postal code can only be checked against a valid country.
+    let zip_code = check(errors, "zip_code", PostalCode::parse(zip_code, &country));
+    Some(Address {
+        street: street?,
+        city: city?,
+        zip_code: zip_code?,
+        country,
+    })
+}
+
+fn check<T>(
+    errors: &mut Vec<FieldError>,
+    field: &str,
+    result: Result<T, InvalidValueError>,
+) -> Option<T> {
+    result
+        .map_err(|error| {
+            errors.push(FieldError {
+                field: field.to_string(),
+                error,
+            })
+        })
+        .ok()
+}
+
+fn required(value: &str) -> Result<String, InvalidValueError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(InvalidValueError::Empty);
+    }
+    Ok(value.to_string())
+}
+
+fn parse_address_id(address_id: &str) -> Result<AddressId, CustomerServiceError> {
+    Uuid::try_parse(address_id)
+        .map(AddressId)
+        .map_err(|err| CustomerServiceError::GenericError(err.to_string()))
+}
+
 #[cfg(test)]
 mod test {
+    use chrono::Utc;
     use uuid::Uuid;
 
     use crate::{
         entities::{
+            address_book::{AddressBook, AddressBookError},
             customer::Customer,
             outbox::{OutboxMessage, OutboxMessageType},
         },
@@ -138,9 +579,14 @@ mod test {
             outbox_repository::{MockOutboxMessageRepository, OutboxMessageRepositoryError},
         },
         services::customer_service::{
-            CreateCustomerRequestObject, CustomerService, CustomerServiceError,
+            AddAddressRequestObject, ChangeAddressRequestObject, CreateCustomerRequestObject,
+            CustomerService, CustomerServiceError, FieldError, SetDefaultAddressesRequestObject,
+            UpdateCustomerRequestObject,
+        },
+        value_objects::{
+            Address, CountryCode, Currency, CustomerId, Email, InvalidValueError, PersonName,
+            PostalCode,
         },
-        value_objects::{Address, CustomerId},
     };
 
     const CUSTOMER_ID: &str = "2585491a-8e05-11ee-af1c-9bfe41ffe61f";
@@ -152,6 +598,9 @@ mod test {
         let expected_event_payload = saved_outbox_message.event_payload();
 
         let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_email()
+            .returning(|_| Ok(None));
         customer_repository
             .expect_save()
             .once()
@@ -194,6 +643,9 @@ mod test {
     #[tokio::test]
     async fn has_an_error_while_saving_customer() {
         let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_email()
+            .returning(|_| Ok(None));
         customer_repository
             .expect_save()
             .returning(move |_| Err(CustomerRepositoryError::ConnectionNotCreatedError))
@@ -214,14 +666,7 @@ mod test {
             Box::new(outbox_message_repository),
         );
         let result = customer_service
-            .create_customer(CreateCustomerRequestObject {
-                first_name: "my_customer_first_name".to_string(),
-                last_name: "my_customer_last_name".to_string(),
-                street: "my_customer_street".to_string(),
-                city: "my_customer_city".to_string(),
-                zip_code: "my_customer_zip_code".to_string(),
-                state: "my_customer_state".to_string(),
-            })
+            .create_customer(create_customer_request_object())
             .await;
 
         assert!(matches!(
@@ -233,6 +678,9 @@ mod test {
     #[tokio::test]
     async fn has_an_error_while_saving_outbox_message() {
         let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_email()
+            .returning(|_| Ok(None));
         customer_repository
             .expect_save()
             .returning(move |_| Ok(create_customer()))
@@ -261,42 +709,422 @@ mod test {
             Box::new(customer_repository),
             Box::new(outbox_message_repository),
         );
+        let result = customer_service
+            .create_customer(create_customer_request_object())
+            .await;
+
+        assert!(matches!(result, Err(CustomerServiceError::GenericError(_))));
+    }
+
+    #[tokio::test]
+    async fn cannot_create_a_customer_with_an_email_already_in_use() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_email()
+            .withf(|email| email.as_str() == "john.appleseed@example.com")
+            .returning(|_| Ok(Some(create_customer())));
+        customer_repository.expect_save().never();
+
+        let mut customer_service = CustomerService::new(
+            Box::new(customer_repository),
+            Box::new(MockOutboxMessageRepository::new()),
+        );
         let result = customer_service
             .create_customer(CreateCustomerRequestObject {
-                first_name: "my_customer_first_name".to_string(),
-                last_name: "my_customer_last_name".to_string(),
-                street: "my_customer_street".to_string(),
-                city: "my_customer_city".to_string(),
-                zip_code: "my_customer_zip_code".to_string(),
-                state: "my_customer_state".to_string(),
+                email: "John.Appleseed@Example.com".to_string(),
+                ..create_customer_request_object()
             })
             .await;
 
-        assert!(matches!(result, Err(CustomerServiceError::GenericError(_))));
+        assert!(matches!(
+            result,
+            Err(CustomerServiceError::CustomerAlreadyExistsError)
+        ));
+    }
+
+    #[tokio::test]
+    async fn cannot_create_a_customer_whose_email_is_taken_while_saving() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_email()
+            .returning(|_| Ok(None));
+        customer_repository
+            .expect_begin_transaction()
+            .returning(|| Ok(()));
+        customer_repository
+            .expect_save()
+            .return_once(|_| Err(CustomerRepositoryError::CustomerAlreadyExists));
+        customer_repository
+            .expect_rollback_transaction()
+            .once()
+            .returning(|| Ok(()));
+
+        let mut customer_service = CustomerService::new(
+            Box::new(customer_repository),
+            Box::new(MockOutboxMessageRepository::new()),
+        );
+        let result = customer_service
+            .create_customer(create_customer_request_object())
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(CustomerServiceError::CustomerAlreadyExistsError)
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_an_invalid_customer_with_an_error_per_field() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository.expect_begin_transaction().never();
+        customer_repository.expect_save().never();
+
+        let mut customer_service = CustomerService::new(
+            Box::new(customer_repository),
+            Box::new(MockOutboxMessageRepository::new()),
+        );
+        let result = customer_service
+            .create_customer(CreateCustomerRequestObject {
+                first_name: " ".to_string(),
+                zip_code: "ABC".to_string(),
+                ..create_customer_request_object()
+            })
+            .await;
+
+        let Err(CustomerServiceError::ValidationError(errors)) = result else {
+            panic!("Expected a validation error");
+        };
+        assert_eq!(
+            vec![
+                FieldError {
+                    field: "first_name".to_string(),
+                    error: InvalidValueError::Empty,
+                },
+                FieldError {
+                    field: "zip_code".to_string(),
+                    error: InvalidValueError::InvalidFormat(
+                        "a postal code of US like 99999 or 99999-9999".to_string()
+                    ),
+                },
+            ],
+            errors
+        );
+    }
+
+    #[tokio::test]
+    async fn does_not_check_the_postal_code_of_an_unknown_country() {
+        let mut customer_service = CustomerService::new(
+            Box::new(MockMyCustomerRepository::new()),
+            Box::new(MockOutboxMessageRepository::new()),
+        );
+        let result = customer_service
+            .create_customer(CreateCustomerRequestObject {
+                zip_code: "ABC".to_string(),
+                country: "Usa".to_string(),
+                ..create_customer_request_object()
+            })
+            .await;
+
+        let Err(CustomerServiceError::ValidationError(errors)) = result else {
+            panic!("Expected a validation error");
+        };
+        assert_eq!(1, errors.len());
+        assert_eq!("country", errors[0].field);
+    }
+
+    #[tokio::test]
+    async fn updates_a_customer() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_id()
+            .returning(|_| Ok(Some(create_customer())));
+        customer_repository
+            .expect_find_by_email()
+            .returning(|_| Ok(None));
+        customer_repository
+            .expect_begin_transaction()
+            .returning(|| Ok(()));
+        customer_repository
+            .expect_update()
+            .withf(|customer| {
+                customer.first_name.as_str() == "Jane"
+                    && customer.email.as_str() == "jane.appleseed@example.com"
+                    && customer.phone.as_ref().map(|phone| phone.as_str()) == Some("+12075550100")
+            })
+            .return_once(|customer| {
+                Ok(Customer {
+                    version: customer.version + 1,
+                    ..customer
+                })
+            });
+        customer_repository
+            .expect_commit_transaction()
+            .once()
+            .returning(|| Ok(()));
+        let mut outbox_message_repository = MockOutboxMessageRepository::new();
+        outbox_message_repository
+            .expect_save()
+            .withf(|m| m.event_type() == OutboxMessageType::CustomerUpdated)
+            .once()
+            .returning(Ok);
+
+        let mut customer_service = CustomerService::new(
+            Box::new(customer_repository),
+            Box::new(outbox_message_repository),
+        );
+        let customer = customer_service
+            .update_customer(UpdateCustomerRequestObject {
+                customer_id: CUSTOMER_ID.to_string(),
+                first_name: "Jane".to_string(),
+                last_name: "Appleseed".to_string(),
+                email: "jane.appleseed@example.com".to_string(),
+                phone: Some("+1 207 555 0100".to_string()),
+                expected_version: Some(1),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(2, customer.version);
+    }
+
+    #[tokio::test]
+    async fn cannot_update_a_customer_at_another_version() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_id()
+            .returning(|_| Ok(Some(create_customer())));
+        customer_repository.expect_update().never();
+
+        let mut customer_service = CustomerService::new(
+            Box::new(customer_repository),
+            Box::new(MockOutboxMessageRepository::new()),
+        );
+        let result = customer_service
+            .update_customer(UpdateCustomerRequestObject {
+                customer_id: CUSTOMER_ID.to_string(),
+                first_name: "Jane".to_string(),
+                last_name: "Appleseed".to_string(),
+                email: "john.appleseed@example.com".to_string(),
+                phone: None,
+                expected_version: Some(2),
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(CustomerServiceError::ConcurrencyConflictError)
+        ));
+    }
+
+    #[tokio::test]
+    async fn changes_the_address_of_a_customer() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_id()
+            .returning(|_| Ok(Some(create_customer())));
+        customer_repository
+            .expect_begin_transaction()
+            .returning(|| Ok(()));
+        customer_repository
+            .expect_update()
+            .withf(|customer| {
+                customer.address.country.as_str() == "IT"
+                    && customer.address.zip_code.as_str() == "20121"
+            })
+            .return_once(Ok);
+        customer_repository
+            .expect_commit_transaction()
+            .once()
+            .returning(|| Ok(()));
+        let mut outbox_message_repository = MockOutboxMessageRepository::new();
+        outbox_message_repository
+            .expect_save()
+            .withf(|m| m.event_type() == OutboxMessageType::CustomerAddressChanged)
+            .once()
+            .returning(Ok);
+
+        let mut customer_service = CustomerService::new(
+            Box::new(customer_repository),
+            Box::new(outbox_message_repository),
+        );
+        let result = customer_service
+            .change_address(ChangeAddressRequestObject {
+                customer_id: CUSTOMER_ID.to_string(),
+                street: "Via Dante 1".to_string(),
+                city: "Milano".to_string(),
+                zip_code: "20121".to_string(),
+                country: "it".to_string(),
+                expected_version: None,
+            })
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn deactivates_a_customer() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_id()
+            .returning(|_| Ok(Some(create_customer())));
+        customer_repository
+            .expect_begin_transaction()
+            .returning(|| Ok(()));
+        customer_repository
+            .expect_delete()
+            .withf(|customer| !customer.is_active())
+            .return_once(Ok);
+        customer_repository
+            .expect_commit_transaction()
+            .once()
+            .returning(|| Ok(()));
+        let mut outbox_message_repository = MockOutboxMessageRepository::new();
+        outbox_message_repository
+            .expect_save()
+            .withf(|m| m.event_type() == OutboxMessageType::CustomerDeactivated)
+            .once()
+            .returning(Ok);
+
+        let mut customer_service = CustomerService::new(
+            Box::new(customer_repository),
+            Box::new(outbox_message_repository),
+        );
+        let customer = customer_service
+            .deactivate(CUSTOMER_ID, Some(1))
+            .await
+            .unwrap();
+
+        assert!(!customer.is_active());
+    }
+
+    #[tokio::test]
+    async fn cannot_change_a_deactivated_customer() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository.expect_find_by_id().returning(|_| {
+            let mut customer = create_customer();
+            customer.deactivate(Utc::now());
+            Ok(Some(customer))
+        });
+        customer_repository.expect_delete().never();
+
+        let mut customer_service = CustomerService::new(
+            Box::new(customer_repository),
+            Box::new(MockOutboxMessageRepository::new()),
+        );
+        let result = customer_service.deactivate(CUSTOMER_ID, None).await;
+
+        assert!(matches!(
+            result,
+            Err(CustomerServiceError::CustomerDeactivatedError)
+        ));
+    }
+
+    #[tokio::test]
+    async fn adds_an_address_to_a_customer() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_id()
+            .returning(|_| Ok(Some(create_customer())));
+        customer_repository
+            .expect_begin_transaction()
+            .returning(|| Ok(()));
+        customer_repository
+            .expect_update()
+            .withf(|customer| {
+                let address_book = &customer.address_book;
+                address_book.as_slice().len() == 1
+                    && address_book.default_shipping() == Some(&address_book.as_slice()[0].id)
+            })
+            .return_once(Ok);
+        customer_repository
+            .expect_commit_transaction()
+            .once()
+            .returning(|| Ok(()));
+        let mut outbox_message_repository = MockOutboxMessageRepository::new();
+        outbox_message_repository
+            .expect_save()
+            .withf(|m| m.event_type() == OutboxMessageType::CustomerAddressBookChanged)
+            .once()
+            .returning(Ok);
+
+        let mut customer_service = CustomerService::new(
+            Box::new(customer_repository),
+            Box::new(outbox_message_repository),
+        );
+        let result = customer_service
+            .add_address(AddAddressRequestObject {
+                customer_id: CUSTOMER_ID.to_string(),
+                street: "Via Dante 1".to_string(),
+                city: "Milano".to_string(),
+                zip_code: "20121".to_string(),
+                country: "IT".to_string(),
+                expected_version: Some(1),
+            })
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn cannot_set_a_default_address_missing_from_the_address_book() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_id()
+            .returning(|_| Ok(Some(create_customer())));
+        customer_repository.expect_update().never();
+
+        let mut customer_service = CustomerService::new(
+            Box::new(customer_repository),
+            Box::new(MockOutboxMessageRepository::new()),
+        );
+        let result = customer_service
+            .set_default_addresses(SetDefaultAddressesRequestObject {
+                customer_id: CUSTOMER_ID.to_string(),
+                shipping_address_id: Some(Uuid::new_v4().to_string()),
+                billing_address_id: None,
+                expected_version: None,
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(CustomerServiceError::AddressBookError(
+                AddressBookError::AddressNotFoundError(_)
+            ))
+        ));
     }
 
     fn create_customer_request_object() -> CreateCustomerRequestObject {
         CreateCustomerRequestObject {
-            first_name: "my_customer_first_name".to_string(),
-            last_name: "my_customer_last_name".to_string(),
-            street: "my_customer_street".to_string(),
-            city: "my_customer_city".to_string(),
-            zip_code: "my_customer_zip_code".to_string(),
-            state: "my_customer_state".to_string(),
+            first_name: "John".to_string(),
+            last_name: "Appleseed".to_string(),
+            email: "john.appleseed@example.com".to_string(),
+            phone: None,
+            street: "22 Elm Street".to_string(),
+            city: "Castle Rock".to_string(),
+            zip_code: "04401".to_string(),
+            country: "US".to_string(),
+            currency: None,
         }
     }
 
     fn create_customer() -> Customer {
         Customer {
             id: CustomerId(Uuid::try_parse(CUSTOMER_ID).unwrap()),
-            first_name: "my_customer_first_name".to_string(),
-            last_name: "my_customer_last_name".to_string(),
+            first_name: PersonName::parse("John").unwrap(),
+            last_name: PersonName::parse("Appleseed").unwrap(),
+            email: Email::parse("john.appleseed@example.com").unwrap(),
+            phone: None,
             address: Address {
-                street: "my_customer_street".to_string(),
-                city: "my_customer_city".to_string(),
-                zip_code: "my_customer_zip_code".to_string(),
-                state: "my_customer_state".to_string(),
+                street: "22 Elm Street".to_string(),
+                city: "Castle Rock".to_string(),
+                zip_code: PostalCode::parse("04401", &CountryCode::parse("US").unwrap()).unwrap(),
+                country: CountryCode::parse("US").unwrap(),
             },
+            address_book: AddressBook::new(),
+            currency: Currency::Eur,
+            deactivated_at: None,
+            version: 1,
         }
     }
 }
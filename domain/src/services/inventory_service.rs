@@ -0,0 +1,612 @@
+use async_trait::async_trait;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    entities::{
+        inventory::{
+            Inventory, InventoryError, ReservedStock, StockReservation, StockReservationState,
+        },
+        outbox::{OutboxMessage, OutboxMessageError},
+    },
+    gateways::inventory_gateway::{InventoryGateway, InventoryGatewayError},
+    repositories::{
+        inventory_repository::{InventoryRepository, InventoryRepositoryError},
+        outbox_repository::OutboxMessageRepository,
+    },
+    value_objects::{OrderId, OrderItem, ProductId, ReservationId, WarehouseId},
+};
+
+/// Times a reservation is attempted again when the stock changed meanwhile.
+const MAX_ATTEMPTS: usize = 3;
+
+#[derive(Debug)]
+pub enum InventoryServiceError {
+    InventoryNotReadError,
+    InventoryNotSavedError,
+    ReservationNotFoundError(ReservationId),
+    InsufficientStockError(ProductId),
+    ConcurrencyConflictError,
+    InvalidStockError(InventoryError),
+    GenericError(String),
+}
+
+impl std::fmt::Display for InventoryServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InventoryServiceError::InventoryNotReadError => write!(f, "Inventory not read error"),
+            InventoryServiceError::InventoryNotSavedError => {
+                write!(f, "Inventory not saved error")
+            }
+            InventoryServiceError::ReservationNotFoundError(reservation_id) => {
+                write!(f, "Reservation {} not found", reservation_id.0)
+            }
+            InventoryServiceError::InsufficientStockError(product_id) => {
+                write!(f, "Insufficient stock of product {}", product_id.0)
+            }
+            InventoryServiceError::ConcurrencyConflictError => {
+                write!(f, "Inventory was modified concurrently")
+            }
+            InventoryServiceError::InvalidStockError(error) => {
+                write!(f, "Invalid stock change: {}", error)
+            }
+            InventoryServiceError::GenericError(error) => write!(f, "Generic error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for InventoryServiceError {}
+
+pub struct AdjustStockRequestObject {
+    pub product_id: String,
+    pub warehouse_id: String,
+    /// Units received, or lost when negative.
+    pub quantity: i32,
+    /// When set, the stock is adjusted only if it is still at this version.
+    pub expected_version: Option<i64>,
+}
+
+/// Keeps the stock of products per warehouse and sets it aside for orders.
+/// Publishes `stock_depleted` when the last available unit of a product in a
+/// warehouse is reserved or removed.
+pub struct InventoryService {
+    inventory_repository: Box<dyn InventoryRepository + Send + Sync>,
+    outbox_message_repository: Box<dyn OutboxMessageRepository + Send + Sync>,
+}
+
+impl InventoryService {
+    pub fn new(
+        inventory_repository: Box<dyn InventoryRepository + Send + Sync>,
+        outbox_message_repository: Box<dyn OutboxMessageRepository + Send + Sync>,
+    ) -> Self {
+        Self {
+            inventory_repository,
+            outbox_message_repository,
+        }
+    }
+
+    pub async fn find_stock(
+        &self,
+        product_id: &str,
+    ) -> Result<Vec<Inventory>, InventoryServiceError> {
+        let product_id = parse_product_id(product_id)?;
+        self.find_inventories(&product_id).await
+    }
+
+    /// Adds or removes units of the product in the warehouse, which starts
+    /// stocking the product if it didn't.
+    pub async fn adjust_stock(
+        &mut self,
+        request: AdjustStockRequestObject,
+    ) -> Result<Inventory, InventoryServiceError> {
+        let product_id = parse_product_id(&request.product_id)?;
+        let warehouse_id = Uuid::try_parse(&request.warehouse_id)
+            .map(WarehouseId)
+            .map_err(|err| InventoryServiceError::GenericError(err.to_string()))?;
+
+        info!("Adjusting stock");
+
+        let mut inventory = self
+            .find_inventories(&product_id)
+            .await?
+            .into_iter()
+            .find(|inventory| inventory.warehouse_id() == &warehouse_id)
+            .unwrap_or_else(|| Inventory::new(product_id, warehouse_id));
+        if request
+            .expected_version
+            .is_some_and(|version| version != inventory.version())
+        {
+            error!("Inventory version does not match the expected one");
+            return Err(InventoryServiceError::ConcurrencyConflictError);
+        }
+
+        let was_depleted = inventory.is_depleted();
+        inventory
+            .adjust(request.quantity)
+            .map_err(InventoryServiceError::InvalidStockError)?;
+        let messages = depleted_events(std::iter::once(&inventory).filter(|_| !was_depleted));
+
+        let mut inventories = self.store(None, vec![inventory], messages).await?;
+        inventories.pop().ok_or_else(|| {
+            InventoryServiceError::GenericError("Inventory not returned".to_string())
+        })
+    }
+
+    /// Reserves the items from the warehouses with available units, in
+    /// warehouse order, splitting an item across warehouses if needed.
+    async fn try_reserve(
+        &mut self,
+        order_id: &OrderId,
+        items: &[OrderItem],
+    ) -> Result<ReservationId, InventoryServiceError> {
+        if let Some(reservation) = self
+            .inventory_repository
+            .find_reservation_by_order_id(order_id.clone())
+            .await
+            .map_err(read_error)?
+        {
+            return Ok(reservation.id().clone());
+        }
+
+        let mut lines = vec![];
+        let mut changed = vec![];
+        for item in items {
+            let mut missing = item.quantity;
+            for mut inventory in self.find_inventories(&item.product_id).await? {
+                if missing == 0 {
+                    break;
+                }
+                let quantity = missing.min(inventory.available());
+                if quantity <= 0 {
+                    continue;
+                }
+                inventory
+                    .reserve(quantity)
+                    .map_err(InventoryServiceError::InvalidStockError)?;
+                missing -= quantity;
+                lines.push(ReservedStock {
+                    product_id: item.product_id.clone(),
+                    warehouse_id: inventory.warehouse_id().clone(),
+                    quantity,
+                });
+                changed.push(inventory);
+            }
+            if missing > 0 {
+                info!("Insufficient stock of product {}", item.product_id.0);
+                return Err(InventoryServiceError::InsufficientStockError(
+                    item.product_id.clone(),
+                ));
+            }
+        }
+
+        // Only inventories with available units were reserved from.
+        let messages = depleted_events(changed.iter());
+        let reservation =
+            StockReservation::new(ReservationId(Uuid::new_v4()), order_id.clone(), lines);
+        let reservation_id = reservation.id().clone();
+        self.store(Some(reservation), changed, messages).await?;
+        Ok(reservation_id)
+    }
+
+    /// Commits or releases the reservation. Doing it again is fine, doing the
+    /// opposite is not: the reservation is then considered missing.
+    async fn try_close(
+        &mut self,
+        reservation_id: &ReservationId,
+        state: StockReservationState,
+    ) -> Result<(), InventoryServiceError> {
+        let not_found = || InventoryServiceError::ReservationNotFoundError(reservation_id.clone());
+        let mut reservation = self
+            .inventory_repository
+            .find_reservation(reservation_id.clone())
+            .await
+            .map_err(read_error)?
+            .ok_or_else(not_found)?;
+        match reservation.state() {
+            StockReservationState::Reserved => {}
+            current if current == state => return Ok(()),
+            _ => return Err(not_found()),
+        }
+
+        let mut changed = vec![];
+        for line in reservation.lines() {
+            let mut inventory = self
+                .find_inventories(&line.product_id)
+                .await?
+                .into_iter()
+                .find(|inventory| inventory.warehouse_id() == &line.warehouse_id)
+                .ok_or_else(|| {
+                    InventoryServiceError::GenericError("Reserved inventory not found".to_string())
+                })?;
+            match state {
+                StockReservationState::Committed => inventory.commit(line.quantity),
+                _ => inventory.release(line.quantity),
+            }
+            .map_err(InventoryServiceError::InvalidStockError)?;
+            changed.push(inventory);
+        }
+        match state {
+            StockReservationState::Committed => reservation.commit(),
+            _ => reservation.release(),
+        }
+        .map_err(InventoryServiceError::InvalidStockError)?;
+
+        self.store(Some(reservation), changed, vec![]).await?;
+        Ok(())
+    }
+
+    async fn store(
+        &mut self,
+        reservation: Option<StockReservation>,
+        inventories: Vec<Inventory>,
+        messages: Vec<Result<OutboxMessage, OutboxMessageError>>,
+    ) -> Result<Vec<Inventory>, InventoryServiceError> {
+        let messages = messages
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| InventoryServiceError::GenericError(e.to_string()))?;
+
+        self.begin_transaction().await?;
+        let result = match reservation {
+            Some(reservation) => {
+                self.inventory_repository
+                    .save_reservation(reservation, inventories)
+                    .await
+            }
+            None => self.inventory_repository.save(inventories).await,
+        };
+        let inventories = match result {
+            Ok(inventories) => inventories,
+            Err(e) => {
+                error!("Error saving inventory: {}", e);
+                self.rollback_transaction().await?;
+                return Err(match e {
+                    InventoryRepositoryError::ConcurrencyConflict => {
+                        InventoryServiceError::ConcurrencyConflictError
+                    }
+                    _ => InventoryServiceError::InventoryNotSavedError,
+                });
+            }
+        };
+
+        for message in messages {
+            if let Err(e) = self.outbox_message_repository.save(message).await {
+                error!("Error saving outbox message: {}", e);
+                self.rollback_transaction().await?;
+                return Err(InventoryServiceError::GenericError(
+                    "Outbox message not saved".to_string(),
+                ));
+            }
+        }
+
+        self.commit_transaction().await?;
+        Ok(inventories)
+    }
+
+    async fn find_inventories(
+        &self,
+        product_id: &ProductId,
+    ) -> Result<Vec<Inventory>, InventoryServiceError> {
+        self.inventory_repository
+            .find_by_product_id(product_id.clone())
+            .await
+            .map_err(read_error)
+    }
+
+    async fn begin_transaction(&mut self) -> Result<(), InventoryServiceError> {
+        self.inventory_repository
+            .begin_transaction()
+            .await
+            .map_err(|e| InventoryServiceError::GenericError(e.to_string()))
+    }
+
+    async fn commit_transaction(&mut self) -> Result<(), InventoryServiceError> {
+        self.inventory_repository
+            .commit_transaction()
+            .await
+            .map_err(|e| InventoryServiceError::GenericError(e.to_string()))
+    }
+
+    async fn rollback_transaction(&mut self) -> Result<(), InventoryServiceError> {
+        self.inventory_repository
+            .rollback_transaction()
+            .await
+            .map_err(|e| InventoryServiceError::GenericError(e.to_string()))
+    }
+}
+
+/// The checkout reaches the stock through the service. Reservations that hit
+/// stock changed concurrently are attempted again.
+#[async_trait]
+impl InventoryGateway for InventoryService {
+    async fn reserve(
+        &mut self,
+        order_id: &OrderId,
+        items: &[OrderItem],
+    ) -> Result<ReservationId, InventoryGatewayError> {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match self.try_reserve(order_id, items).await {
+                Err(InventoryServiceError::ConcurrencyConflictError) if attempts < MAX_ATTEMPTS => {
+                    info!("Stock modified concurrently, reserving again");
+                }
+                result => return result.map_err(gateway_error),
+            }
+        }
+    }
+
+    async fn commit(
+        &mut self,
+        reservation_id: &ReservationId,
+    ) -> Result<(), InventoryGatewayError> {
+        self.try_close(reservation_id, StockReservationState::Committed)
+            .await
+            .map_err(gateway_error)
+    }
+
+    async fn release(
+        &mut self,
+        reservation_id: &ReservationId,
+    ) -> Result<(), InventoryGatewayError> {
+        self.try_close(reservation_id, StockReservationState::Released)
+            .await
+            .map_err(gateway_error)
+    }
+}
+
+fn depleted_events<'a>(
+    inventories: impl Iterator<Item = &'a Inventory>,
+) -> Vec<Result<OutboxMessage, OutboxMessageError>> {
+    inventories
+        .filter(|inventory| inventory.is_depleted())
+        .map(OutboxMessage::stock_depleted_event)
+        .collect()
+}
+
+fn gateway_error(error: InventoryServiceError) -> InventoryGatewayError {
+    match error {
+        InventoryServiceError::InsufficientStockError(product_id) => {
+            InventoryGatewayError::InsufficientStockError(product_id)
+        }
+        InventoryServiceError::ReservationNotFoundError(reservation_id) => {
+            InventoryGatewayError::ReservationNotFoundError(reservation_id)
+        }
+        error => InventoryGatewayError::InventoryUnavailableError(error.to_string()),
+    }
+}
+
+fn read_error(error: InventoryRepositoryError) -> InventoryServiceError {
+    error!("Error reading inventory: {}", error);
+    InventoryServiceError::InventoryNotReadError
+}
+
+fn parse_product_id(product_id: &str) -> Result<ProductId, InventoryServiceError> {
+    Uuid::try_parse(product_id)
+        .map(ProductId)
+        .map_err(|err| InventoryServiceError::GenericError(err.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use crate::{
+        entities::{
+            inventory::{Inventory, ReservedStock, StockReservation},
+            outbox::OutboxMessageType,
+        },
+        gateways::inventory_gateway::{InventoryGateway, InventoryGatewayError},
+        repositories::{
+            inventory_repository::{InventoryRepositoryError, MockMyInventoryRepository},
+            outbox_repository::MockOutboxMessageRepository,
+        },
+        value_objects::{OrderId, OrderItem, ProductId, ReservationId, WarehouseId},
+    };
+
+    use super::{AdjustStockRequestObject, InventoryService};
+
+    const PRODUCT_ID: &str = "0c8b3d0e-3f8c-4d8e-9b0a-5a6f7c1d2e3f";
+    const WAREHOUSE_ID: &str = "5b1e2f3a-4c5d-4e6f-8a9b-0c1d2e3f4a5b";
+    const OTHER_WAREHOUSE_ID: &str = "9d8c7b6a-5f4e-4d3c-2b1a-0f9e8d7c6b5a";
+
+    #[tokio::test]
+    async fn starts_stocking_a_product_in_a_warehouse() {
+        let mut inventory_repository = repository_with(vec![]);
+        inventory_repository
+            .expect_save()
+            .withf(|inventories| inventories[0].on_hand() == 5 && inventories[0].version() == 0)
+            .times(1)
+            .returning(|inventories| Ok(with_next_version(inventories)));
+        let mut service = InventoryService::new(
+            Box::new(inventory_repository),
+            Box::new(MockOutboxMessageRepository::new()),
+        );
+
+        let inventory = service.adjust_stock(adjust_request(5, None)).await.unwrap();
+
+        assert_eq!(5, inventory.available());
+        assert_eq!(1, inventory.version());
+    }
+
+    #[tokio::test]
+    async fn publishes_stock_depleted_when_the_last_available_units_are_removed() {
+        let mut inventory_repository = repository_with(vec![inventory(WAREHOUSE_ID, 3, 1)]);
+        inventory_repository
+            .expect_save()
+            .returning(|inventories| Ok(with_next_version(inventories)));
+        let mut outbox_message_repository = MockOutboxMessageRepository::new();
+        outbox_message_repository
+            .expect_save()
+            .withf(|message| message.event_type() == OutboxMessageType::StockDepleted)
+            .times(1)
+            .returning(Ok);
+        let mut service = InventoryService::new(
+            Box::new(inventory_repository),
+            Box::new(outbox_message_repository),
+        );
+
+        let inventory = service
+            .adjust_stock(adjust_request(-2, Some(1)))
+            .await
+            .unwrap();
+
+        assert!(inventory.is_depleted());
+        assert!(matches!(
+            service.adjust_stock(adjust_request(-3, Some(1))).await,
+            Err(super::InventoryServiceError::InvalidStockError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn reserves_an_item_across_warehouses_trying_again_on_conflicts() {
+        let mut inventory_repository = repository_with(vec![
+            inventory(WAREHOUSE_ID, 2, 0),
+            inventory(OTHER_WAREHOUSE_ID, 5, 0),
+        ]);
+        inventory_repository
+            .expect_find_reservation_by_order_id()
+            .returning(|_| Ok(None));
+        inventory_repository
+            .expect_save_reservation()
+            .times(1)
+            .returning(|_, _| Err(InventoryRepositoryError::ConcurrencyConflict));
+        inventory_repository
+            .expect_save_reservation()
+            .withf(|reservation, inventories| {
+                reservation
+                    .lines()
+                    .iter()
+                    .map(|line| line.quantity)
+                    .collect::<Vec<_>>()
+                    == vec![2, 2]
+                    && inventories[0].is_depleted()
+                    && inventories[1].available() == 3
+            })
+            .times(1)
+            .returning(|_, inventories| Ok(with_next_version(inventories)));
+        let mut outbox_message_repository = MockOutboxMessageRepository::new();
+        outbox_message_repository
+            .expect_save()
+            .withf(|message| message.event_payload().contains(WAREHOUSE_ID))
+            .times(1)
+            .returning(Ok);
+        let mut service = InventoryService::new(
+            Box::new(inventory_repository),
+            Box::new(outbox_message_repository),
+        );
+
+        let result = service
+            .reserve(&OrderId(Uuid::new_v4()), &[order_item(4)])
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn reserves_once_per_order() {
+        let reservation_id = ReservationId(Uuid::new_v4());
+        let mut inventory_repository = MockMyInventoryRepository::new();
+        let existing = reservation_id.clone();
+        inventory_repository
+            .expect_find_reservation_by_order_id()
+            .returning(move |order_id| {
+                Ok(Some(StockReservation::new(
+                    existing.clone(),
+                    order_id,
+                    vec![ReservedStock {
+                        product_id: product_id(),
+                        warehouse_id: warehouse_id(WAREHOUSE_ID),
+                        quantity: 1,
+                    }],
+                )))
+            });
+        let mut service = InventoryService::new(
+            Box::new(inventory_repository),
+            Box::new(MockOutboxMessageRepository::new()),
+        );
+
+        let result = service
+            .reserve(&OrderId(Uuid::new_v4()), &[order_item(1)])
+            .await;
+
+        assert_eq!(Ok(reservation_id), result);
+    }
+
+    #[tokio::test]
+    async fn cannot_reserve_more_than_available() {
+        let mut inventory_repository = repository_with(vec![inventory(WAREHOUSE_ID, 3, 2)]);
+        inventory_repository
+            .expect_find_reservation_by_order_id()
+            .returning(|_| Ok(None));
+        let mut service = InventoryService::new(
+            Box::new(inventory_repository),
+            Box::new(MockOutboxMessageRepository::new()),
+        );
+
+        let result = service
+            .reserve(&OrderId(Uuid::new_v4()), &[order_item(2)])
+            .await;
+
+        assert_eq!(
+            Err(InventoryGatewayError::InsufficientStockError(product_id())),
+            result
+        );
+    }
+
+    fn repository_with(inventories: Vec<Inventory>) -> MockMyInventoryRepository {
+        let mut inventory_repository = MockMyInventoryRepository::new();
+        inventory_repository
+            .expect_find_by_product_id()
+            .returning(move |_| Ok(inventories.clone()));
+        inventory_repository
+            .expect_begin_transaction()
+            .returning(|| Ok(()));
+        inventory_repository
+            .expect_commit_transaction()
+            .returning(|| Ok(()));
+        inventory_repository
+            .expect_rollback_transaction()
+            .returning(|| Ok(()));
+        inventory_repository
+    }
+
+    fn with_next_version(inventories: Vec<Inventory>) -> Vec<Inventory> {
+        inventories
+            .into_iter()
+            .map(|inventory| {
+                let version = inventory.version() + 1;
+                inventory.with_version(version)
+            })
+            .collect()
+    }
+
+    fn adjust_request(quantity: i32, expected_version: Option<i64>) -> AdjustStockRequestObject {
+        AdjustStockRequestObject {
+            product_id: PRODUCT_ID.to_string(),
+            warehouse_id: WAREHOUSE_ID.to_string(),
+            quantity,
+            expected_version,
+        }
+    }
+
+    fn inventory(warehouse: &str, on_hand: i32, reserved: i32) -> Inventory {
+        Inventory::restore(product_id(), warehouse_id(warehouse), on_hand, reserved, 1)
+    }
+
+    fn order_item(quantity: i32) -> OrderItem {
+        OrderItem {
+            price: 10.0,
+            quantity,
+            product_id: product_id(),
+        }
+    }
+
+    fn product_id() -> ProductId {
+        ProductId(Uuid::try_parse(PRODUCT_ID).unwrap())
+    }
+
+    fn warehouse_id(warehouse: &str) -> WarehouseId {
+        WarehouseId(Uuid::try_parse(warehouse).unwrap())
+    }
+}
@@ -0,0 +1,212 @@
+use chrono::Utc;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    entities::{
+        invoice::{Invoice, InvoiceError, InvoiceKind},
+        order::Order,
+    },
+    repositories::invoice_repository::{InvoiceRepository, InvoiceRepositoryError},
+    value_objects::{InvoiceId, OrderId},
+};
+
+#[derive(Debug)]
+pub enum InvoiceServiceError {
+    InvoiceNotFoundError,
+    InvoiceNotReadError,
+    InvoiceNotSavedError,
+    InvalidInvoiceError(InvoiceError),
+    GenericError(String),
+}
+
+impl std::fmt::Display for InvoiceServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InvoiceServiceError::InvoiceNotFoundError => write!(f, "Invoice not found error"),
+            InvoiceServiceError::InvoiceNotReadError => write!(f, "Invoice not read error"),
+            InvoiceServiceError::InvoiceNotSavedError => write!(f, "Invoice not saved error"),
+            InvoiceServiceError::InvalidInvoiceError(error) => {
+                write!(f, "Invalid invoice: {}", error)
+            }
+            InvoiceServiceError::GenericError(error) => write!(f, "Generic error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for InvoiceServiceError {}
+
+/// The invoice of an order and the credit notes of its refunds.
+pub struct OrderInvoices {
+    pub invoice: Invoice,
+    pub credit_notes: Vec<Invoice>,
+}
+
+/// Issues the invoice of paid orders and a credit note for each refund.
+/// Issuing is idempotent, so payments can retry it.
+pub struct InvoiceService {
+    invoice_repository: Box<dyn InvoiceRepository + Send + Sync>,
+}
+
+impl InvoiceService {
+    pub fn new(invoice_repository: Box<dyn InvoiceRepository + Send + Sync>) -> Self {
+        Self { invoice_repository }
+    }
+
+    pub async fn find_invoices(
+        &self,
+        order_id: &str,
+    ) -> Result<OrderInvoices, InvoiceServiceError> {
+        let order_id = Uuid::try_parse(order_id)
+            .map(OrderId)
+            .map_err(|err| InvoiceServiceError::GenericError(err.to_string()))?;
+        let (invoices, credit_notes): (Vec<Invoice>, Vec<Invoice>) = self
+            .invoices_of(&order_id)
+            .await?
+            .into_iter()
+            .partition(|invoice| invoice.kind() == InvoiceKind::Invoice);
+        let invoice = invoices
+            .into_iter()
+            .next()
+            .ok_or(InvoiceServiceError::InvoiceNotFoundError)?;
+        Ok(OrderInvoices {
+            invoice,
+            credit_notes,
+        })
+    }
+
+    /// Issues the invoice of a paid order, unless it was issued already.
+    pub async fn invoice(&self, order: &Order) -> Result<Invoice, InvoiceServiceError> {
+        if let Some(invoice) = self.find_issued(order.id(), None).await? {
+            return Ok(invoice);
+        }
+
+        info!("Issuing invoice of order {}", order.id().0);
+        let invoice = Invoice::for_order(InvoiceId(Uuid::new_v4()), order, Utc::now());
+        self.issue(invoice, None).await
+    }
+
+    /// Issues a credit note of `amount` for the refund identified by
+    /// `refund_key`, unless it was issued already. The invoice of the order
+    /// is issued first if missing.
+    pub async fn credit(
+        &self,
+        order: &Order,
+        refund_key: &str,
+        amount: f64,
+    ) -> Result<Invoice, InvoiceServiceError> {
+        if let Some(credit_note) = self.find_issued(order.id(), Some(refund_key)).await? {
+            return Ok(credit_note);
+        }
+        let invoice = self.invoice(order).await?;
+
+        info!("Issuing credit note of order {}", order.id().0);
+        let credit_note = Invoice::credit_note(
+            InvoiceId(Uuid::new_v4()),
+            &invoice,
+            refund_key,
+            amount,
+            Utc::now(),
+        )
+        .map_err(InvoiceServiceError::InvalidInvoiceError)?;
+        self.issue(credit_note, Some(refund_key)).await
+    }
+
+    /// Another payment may have issued the same invoice meanwhile, in which
+    /// case that one is returned.
+    async fn issue(
+        &self,
+        invoice: Invoice,
+        refund_key: Option<&str>,
+    ) -> Result<Invoice, InvoiceServiceError> {
+        let order_id = invoice.order_id().clone();
+        match self.invoice_repository.issue(invoice).await {
+            Ok(invoice) => Ok(invoice),
+            Err(InvoiceRepositoryError::InvoiceAlreadyIssuedError) => self
+                .find_issued(&order_id, refund_key)
+                .await?
+                .ok_or(InvoiceServiceError::InvoiceNotSavedError),
+            Err(e) => {
+                error!("Error issuing invoice: {}", e);
+                Err(InvoiceServiceError::InvoiceNotSavedError)
+            }
+        }
+    }
+
+    /// The invoice of the order, or its credit note of `refund_key`.
+    async fn find_issued(
+        &self,
+        order_id: &OrderId,
+        refund_key: Option<&str>,
+    ) -> Result<Option<Invoice>, InvoiceServiceError> {
+        Ok(self
+            .invoices_of(order_id)
+            .await?
+            .into_iter()
+            .find(|invoice| invoice.refund_key() == refund_key))
+    }
+
+    async fn invoices_of(&self, order_id: &OrderId) -> Result<Vec<Invoice>, InvoiceServiceError> {
+        self.invoice_repository
+            .find_by_order_id(order_id.clone())
+            .await
+            .map_err(|e| {
+                error!("Error reading invoices: {}", e);
+                InvoiceServiceError::InvoiceNotReadError
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use crate::{
+        entities::{invoice::InvoiceKind, order::Order},
+        repositories::invoice_repository::InMemoryInvoiceRepository,
+        value_objects::{CustomerId, OrderId, OrderItem, ProductId},
+    };
+
+    use super::InvoiceService;
+
+    #[tokio::test]
+    async fn issues_an_invoice_once_and_a_credit_note_per_refund() {
+        let invoice_repository = InMemoryInvoiceRepository::new();
+        let service = InvoiceService::new(Box::new(invoice_repository.clone()));
+        let mut order = Order::create(OrderId(Uuid::new_v4()), CustomerId(Uuid::new_v4()));
+        order
+            .add(OrderItem {
+                price: 30.0,
+                quantity: 1,
+                product_id: ProductId(Uuid::new_v4()),
+            })
+            .unwrap();
+
+        let invoice = service.invoice(&order).await.unwrap();
+        let again = service.invoice(&order).await.unwrap();
+        service.credit(&order, "return_1", 10.0).await.unwrap();
+        service.credit(&order, "return_1", 10.0).await.unwrap();
+        service.credit(&order, "return_2", 5.0).await.unwrap();
+
+        assert_eq!(invoice, again);
+        let invoices = service
+            .find_invoices(&order.id().0.to_string())
+            .await
+            .unwrap();
+        assert_eq!(invoice, invoices.invoice);
+        assert_eq!(2, invoices.credit_notes.len());
+        assert_eq!(3, invoice_repository.invoices().len());
+        assert_eq!(
+            vec![1, 2],
+            invoices
+                .credit_notes
+                .iter()
+                .map(|credit_note| credit_note.number().unwrap().sequence)
+                .collect::<Vec<_>>()
+        );
+        assert!(invoices
+            .credit_notes
+            .iter()
+            .all(|credit_note| credit_note.kind() == InvoiceKind::CreditNote));
+    }
+}
@@ -1,3 +1,16 @@
+pub mod cart_service;
+pub mod catalog_service;
+pub mod checkout_saga_service;
+pub mod currency_service;
+pub mod customer_data_service;
 pub mod customer_service;
+pub mod inventory_service;
+pub mod invoice_service;
 pub mod order_service;
 pub mod outbox_service;
+pub mod payment_service;
+pub mod promotion_service;
+pub mod return_service;
+pub mod review_service;
+pub mod search_service;
+pub mod shipment_service;
@@ -1,22 +1,48 @@
+use chrono::Utc;
 use tracing::{error, info};
 use uuid::Uuid;
 
 use crate::{
-    entities::{order::Order, outbox::OutboxMessage},
+    entities::{
+        address_book::AddressBookError,
+        order::{Order, OrderAddresses},
+        order_lines::{OrderLines, OrderLinesError},
+        outbox::{OutboxMessage, OutboxMessageError},
+        product::Variant,
+        shipping_method::{OrderShipping, ShippingMethodError},
+        tax::Taxes,
+    },
+    gateways::tax_calculator::{TaxCalculator, TaxCalculatorError},
     repositories::{
-        customer_repository::CustomerRepository, order_repository::OrderRepository,
+        catalog_repository::CatalogRepository,
+        customer_repository::CustomerRepository,
+        order_repository::{OrderRepository, OrderRepositoryError},
         outbox_repository::OutboxMessageRepository,
+        shipping_method_repository::ShippingMethodRepository,
+    },
+    services::currency_service::{CurrencyService, CurrencyServiceError},
+    value_objects::{
+        AddressId, Currency, CustomerId, OrderId, OrderItem, ProductId, ShippingMethodId,
     },
-    value_objects::{CustomerId, OrderId, OrderItem, ProductId},
 };
 
 #[derive(Debug)]
 pub enum OrderServiceError {
     CustomerNotFoundError,
     CustomerNotReadError,
+    ProductNotFoundError(ProductId),
+    ProductNotReadError,
+    ShippingMethodNotFoundError,
+    ShippingMethodNotReadError,
     OrderNotFoundError,
     OrderNotReadError,
     OrderNotSavedError,
+    ConcurrencyConflictError,
+    InvalidOrderError(OrderLinesError),
+    InvalidAddressError(AddressBookError),
+    InvalidShippingError(ShippingMethodError),
+    TaxCalculationError(TaxCalculatorError),
+    CurrencyError(CurrencyServiceError),
     GenericError(String),
 }
 
@@ -25,9 +51,33 @@ impl std::fmt::Display for OrderServiceError {
         match self {
             OrderServiceError::CustomerNotFoundError => write!(f, "Customer not found error"),
             OrderServiceError::CustomerNotReadError => write!(f, "Customer not read error"),
+            OrderServiceError::ProductNotFoundError(product_id) => {
+                write!(f, "Product {} not found", product_id.0)
+            }
+            OrderServiceError::ProductNotReadError => write!(f, "Product not read error"),
+            OrderServiceError::ShippingMethodNotFoundError => {
+                write!(f, "Shipping method not found error")
+            }
+            OrderServiceError::ShippingMethodNotReadError => {
+                write!(f, "Shipping method not read error")
+            }
             OrderServiceError::OrderNotFoundError => write!(f, "Order not found error"),
             OrderServiceError::OrderNotReadError => write!(f, "Order not read error"),
             OrderServiceError::OrderNotSavedError => write!(f, "Order not saved error"),
+            OrderServiceError::ConcurrencyConflictError => {
+                write!(f, "Order was modified concurrently")
+            }
+            OrderServiceError::InvalidOrderError(error) => write!(f, "Invalid order: {}", error),
+            OrderServiceError::InvalidAddressError(error) => {
+                write!(f, "Invalid address: {}", error)
+            }
+            OrderServiceError::InvalidShippingError(error) => {
+                write!(f, "Invalid shipping: {}", error)
+            }
+            OrderServiceError::TaxCalculationError(error) => {
+                write!(f, "Taxes not calculated: {}", error)
+            }
+            OrderServiceError::CurrencyError(error) => write!(f, "Currency error: {}", error),
             OrderServiceError::GenericError(error) => write!(f, "Generic error: ${error}"),
         }
     }
@@ -39,19 +89,45 @@ pub struct OrderService {
     customer_repository: Box<dyn CustomerRepository>,
     order_repository: Box<dyn OrderRepository>,
     outbox_message_repository: Box<dyn OutboxMessageRepository>,
+    catalog_repository: Box<dyn CatalogRepository>,
+    shipping_method_repository: Box<dyn ShippingMethodRepository>,
+    tax_calculator: Box<dyn TaxCalculator>,
+    currency_service: CurrencyService,
 }
 
 #[derive(Debug)]
 pub struct AddProductRequestObject {
-    order_id: String,
-    product_id: String,
-    price: f64,
-    quantity: i32,
+    pub order_id: String,
+    pub product_id: String,
+    pub quantity: i32,
+    /// When set, the product is added only if the order is still at this version.
+    pub expected_version: Option<i64>,
+}
+
+#[derive(Debug)]
+pub struct ChangeProductQuantityRequestObject {
+    pub order_id: String,
+    pub product_id: String,
+    pub quantity: i32,
+    /// When set, the quantity is changed only if the order is still at this version.
+    pub expected_version: Option<i64>,
 }
 
 pub struct CreateOrderRequestObject {
     pub order_id: String,
     pub customer_id: String,
+    /// Address book entries to use instead of the customer's defaults.
+    pub shipping_address_id: Option<String>,
+    pub billing_address_id: Option<String>,
+    /// `exclusive` (the default) or `inclusive` of taxes.
+    pub pricing_mode: Option<String>,
+    /// ISO 4217 code; the customer's currency when unset.
+    pub currency: Option<String>,
+    /// Shipping method priced in the order; orders without one are not
+    /// charged for shipping.
+    pub shipping_method_id: Option<String>,
+    /// Kilograms the order weighs, required by weight-based rates.
+    pub shipping_weight: Option<f64>,
 }
 
 impl OrderService {
@@ -59,36 +135,136 @@ impl OrderService {
         customer_repository: Box<dyn CustomerRepository>,
         order_repository: Box<dyn OrderRepository>,
         outbox_message_repository: Box<dyn OutboxMessageRepository>,
+        catalog_repository: Box<dyn CatalogRepository>,
+        shipping_method_repository: Box<dyn ShippingMethodRepository>,
+        tax_calculator: Box<dyn TaxCalculator>,
+        currency_service: CurrencyService,
     ) -> Self {
         Self {
             customer_repository,
             order_repository,
             outbox_message_repository,
+            catalog_repository,
+            shipping_method_repository,
+            tax_calculator,
+            currency_service,
         }
     }
 
+    /// Creates an empty order, to be filled with `add_product`.
     pub async fn create_order(
         &mut self,
         create_order: CreateOrderRequestObject,
+    ) -> Result<Order, OrderServiceError> {
+        self.save_new_order(create_order, OrderLines::new(), false)
+            .await
+    }
+
+    /// Creates an order already holding `order_lines`, publishing an event
+    /// for the order and one for each line, then `order_placed` to start
+    /// its checkout.
+    pub async fn place_order(
+        &mut self,
+        create_order: CreateOrderRequestObject,
+        order_lines: OrderLines,
+    ) -> Result<Order, OrderServiceError> {
+        self.save_new_order(create_order, order_lines, true).await
+    }
+
+    async fn save_new_order(
+        &mut self,
+        create_order: CreateOrderRequestObject,
+        order_lines: OrderLines,
+        placed: bool,
     ) -> Result<Order, OrderServiceError> {
         let order_id = Uuid::try_parse(&create_order.order_id)
             .map_err(|err| OrderServiceError::GenericError(err.to_string()))?;
         let customer_id = Uuid::try_parse(&create_order.customer_id)
             .map_err(|err| OrderServiceError::GenericError(err.to_string()))?;
+        let shipping_address_id = parse_address_id(create_order.shipping_address_id.as_deref())?;
+        let billing_address_id = parse_address_id(create_order.billing_address_id.as_deref())?;
+        let pricing_mode = create_order
+            .pricing_mode
+            .as_deref()
+            .map(str::parse)
+            .transpose()
+            .map_err(OrderServiceError::GenericError)?
+            .unwrap_or_default();
+        let currency = create_order
+            .currency
+            .as_deref()
+            .map(Currency::parse)
+            .transpose()
+            .map_err(|err| OrderServiceError::GenericError(format!("currency {}", err)))?;
+        let shipping_method_id = create_order
+            .shipping_method_id
+            .as_deref()
+            .map(Uuid::try_parse)
+            .transpose()
+            .map_err(|err| OrderServiceError::GenericError(err.to_string()))?
+            .map(ShippingMethodId);
 
         info!("Creating order");
 
-        let customer = self
+        let customer = match self
             .customer_repository
             .find_by_id(CustomerId(customer_id))
             .await
-            .map_err(|_| OrderServiceError::CustomerNotReadError)?;
-        if customer.is_none() {
-            error!("Customer not found");
-            return Err(OrderServiceError::CustomerNotFoundError);
-        }
+            .map_err(|_| OrderServiceError::CustomerNotReadError)?
+        {
+            Some(customer) if customer.is_active() => customer,
+            _ => {
+                error!("Customer not found");
+                return Err(OrderServiceError::CustomerNotFoundError);
+            }
+        };
+        let addresses = OrderAddresses {
+            shipping: customer
+                .shipping_address(shipping_address_id.as_ref())
+                .map_err(OrderServiceError::InvalidAddressError)?
+                .clone(),
+            billing: customer
+                .billing_address(billing_address_id.as_ref())
+                .map_err(OrderServiceError::InvalidAddressError)?
+                .clone(),
+        };
+        let currency = self
+            .currency_service
+            .order_currency(
+                currency.unwrap_or(customer.currency),
+                Utc::now().date_naive(),
+            )
+            .await
+            .map_err(|e| {
+                error!("Error finding exchange rate: {}", e);
+                OrderServiceError::CurrencyError(e)
+            })?;
+        let shipping = match shipping_method_id {
+            Some(shipping_method_id) => Some(
+                self.order_shipping(shipping_method_id, create_order.shipping_weight)
+                    .await?,
+            ),
+            None => None,
+        };
 
-        let order = Order::create(OrderId(order_id), CustomerId(customer_id));
+        let mut order = Order::restore(
+            OrderId(order_id),
+            CustomerId(customer_id),
+            order_lines,
+            Some(addresses),
+            0,
+        )
+        .with_taxes(Taxes::new(pricing_mode))
+        .with_currency(currency);
+        if let Some(shipping) = shipping {
+            order = order.with_shipping(shipping);
+        }
+        self.calculate_taxes(&mut order).await?;
+        if placed {
+            order
+                .place()
+                .map_err(|e| OrderServiceError::GenericError(e.to_string()))?;
+        }
 
         self.begin_transaction().await?;
         let saved_order = match self.order_repository.save(order).await {
@@ -100,8 +276,20 @@ impl OrderService {
             }
         };
 
-        let message = match OutboxMessage::order_created_event(&saved_order) {
-            Ok(message) => message,
+        let messages = std::iter::once(OutboxMessage::order_created_event(&saved_order))
+            .chain(saved_order.order_items().iter().map(|item| {
+                OutboxMessage::product_added_to_order_event(
+                    saved_order.id(),
+                    &item.product_id,
+                    item.price,
+                    item.quantity,
+                    saved_order.currency(),
+                )
+            }))
+            .chain(placed.then(|| OutboxMessage::order_placed_event(&saved_order)))
+            .collect::<Result<Vec<_>, _>>();
+        let messages = match messages {
+            Ok(messages) => messages,
             Err(e) => {
                 error!("Error serializing outbox message: {}", e);
                 self.rollback_transaction().await?;
@@ -111,16 +299,29 @@ impl OrderService {
             }
         };
 
-        if let Err(e) = self.outbox_message_repository.save(message).await {
-            error!("Error saving outbox message: {}", e);
-            self.rollback_transaction().await?;
-            return Err(OrderServiceError::GenericError(
-                "Outbox message not saved".to_string(),
-            ));
+        for message in messages {
+            if let Err(e) = self.outbox_message_repository.save(message).await {
+                error!("Error saving outbox message: {}", e);
+                self.rollback_transaction().await?;
+                return Err(OrderServiceError::GenericError(
+                    "Outbox message not saved".to_string(),
+                ));
+            }
         }
 
         self.commit_transaction().await?;
-        return Ok(saved_order);
+        Ok(saved_order)
+    }
+
+    pub async fn find_order(&self, order_id: &str) -> Result<Order, OrderServiceError> {
+        let order_id = Uuid::try_parse(order_id)
+            .map_err(|err| OrderServiceError::GenericError(err.to_string()))?;
+
+        self.order_repository
+            .find_by_id(OrderId(order_id))
+            .await
+            .map_err(|_| OrderServiceError::OrderNotReadError)?
+            .ok_or(OrderServiceError::OrderNotFoundError)
     }
 
     pub async fn add_product(
@@ -134,54 +335,206 @@ impl OrderService {
 
         info!("Adding product to order");
 
-        let Some(mut order) = self
+        let mut order = self
+            .find_order_at(OrderId(order_id), add_product.expected_version)
+            .await?;
+        let variant = self.find_variant(ProductId(product_id)).await?;
+        // Prices come from the catalog, never from the client: the price of
+        // the variant, or its price in the price list of the order's currency.
+        let price = match order.currency().currency {
+            Currency::BASE => variant.price,
+            currency => self
+                .currency_service
+                .price_of(&ProductId(product_id), currency)
+                .await
+                .map_err(|e| {
+                    error!("Price of product not found: {}", e);
+                    OrderServiceError::CurrencyError(e)
+                })?,
+        };
+
+        if let Err(e) = order.add(OrderItem {
+            price,
+            quantity: add_product.quantity,
+            product_id: ProductId(product_id),
+        }) {
+            error!("Product not added to order: {}", e);
+            return Err(OrderServiceError::InvalidOrderError(e));
+        }
+        let message = OutboxMessage::product_added_to_order_event(
+            &OrderId(order_id),
+            &ProductId(product_id),
+            price,
+            add_product.quantity,
+            order.currency(),
+        );
+        self.update_order(order, message).await
+    }
+
+    /// Takes the line of the product out of the order.
+    pub async fn remove_product(
+        &mut self,
+        order_id: &str,
+        product_id: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Order, OrderServiceError> {
+        let order_id = Uuid::try_parse(order_id)
+            .map_err(|err| OrderServiceError::GenericError(err.to_string()))?;
+        let product_id = Uuid::try_parse(product_id)
+            .map_err(|err| OrderServiceError::GenericError(err.to_string()))?;
+
+        info!("Removing product from order");
+
+        let mut order = self
+            .find_order_at(OrderId(order_id), expected_version)
+            .await?;
+        let removed = order.remove(&ProductId(product_id)).map_err(|e| {
+            error!("Product not removed from order: {}", e);
+            OrderServiceError::InvalidOrderError(e)
+        })?;
+        let message =
+            OutboxMessage::product_removed_from_order_event(order.id(), &removed, order.currency());
+        self.update_order(order, message).await
+    }
+
+    pub async fn change_product_quantity(
+        &mut self,
+        change_quantity: ChangeProductQuantityRequestObject,
+    ) -> Result<Order, OrderServiceError> {
+        let order_id = Uuid::try_parse(&change_quantity.order_id)
+            .map_err(|err| OrderServiceError::GenericError(err.to_string()))?;
+        let product_id = ProductId(
+            Uuid::try_parse(&change_quantity.product_id)
+                .map_err(|err| OrderServiceError::GenericError(err.to_string()))?,
+        );
+
+        info!("Changing quantity of product in order");
+
+        let mut order = self
+            .find_order_at(OrderId(order_id), change_quantity.expected_version)
+            .await?;
+        let previous = order.order_lines().find(&product_id).cloned();
+        if let Err(e) = order.change_quantity(&product_id, change_quantity.quantity) {
+            error!("Quantity of product not changed: {}", e);
+            return Err(OrderServiceError::InvalidOrderError(e));
+        }
+        let Some(previous) = previous else {
+            return Err(OrderServiceError::InvalidOrderError(
+                OrderLinesError::ProductNotFoundError(product_id),
+            ));
+        };
+        let message = OutboxMessage::product_quantity_changed_event(
+            order.id(),
+            &previous,
+            change_quantity.quantity,
+            order.currency(),
+        );
+        self.update_order(order, message).await
+    }
+
+    /// The variant of the catalog a product id of the order refers to.
+    async fn find_variant(&self, product_id: ProductId) -> Result<Variant, OrderServiceError> {
+        match self
+            .catalog_repository
+            .find_variant(product_id.clone())
+            .await
+        {
+            Ok(Some(variant)) => Ok(variant),
+            Ok(None) => {
+                error!("Product not found");
+                Err(OrderServiceError::ProductNotFoundError(product_id))
+            }
+            Err(e) => {
+                error!("Error reading product: {}", e);
+                Err(OrderServiceError::ProductNotReadError)
+            }
+        }
+    }
+
+    /// The shipping method chosen for a new order, with its rate as it is now.
+    async fn order_shipping(
+        &self,
+        shipping_method_id: ShippingMethodId,
+        weight: Option<f64>,
+    ) -> Result<OrderShipping, OrderServiceError> {
+        let shipping_method = match self
+            .shipping_method_repository
+            .find_by_id(shipping_method_id)
+            .await
+        {
+            Ok(Some(shipping_method)) => shipping_method,
+            Ok(None) => {
+                error!("Shipping method not found");
+                return Err(OrderServiceError::ShippingMethodNotFoundError);
+            }
+            Err(e) => {
+                error!("Error reading shipping method: {}", e);
+                return Err(OrderServiceError::ShippingMethodNotReadError);
+            }
+        };
+        OrderShipping::new(&shipping_method, weight)
+            .map_err(OrderServiceError::InvalidShippingError)
+    }
+
+    async fn find_order_at(
+        &self,
+        order_id: OrderId,
+        expected_version: Option<i64>,
+    ) -> Result<Order, OrderServiceError> {
+        let Some(order) = self
             .order_repository
-            .find_by_id(OrderId(order_id))
+            .find_by_id(order_id)
             .await
             .map_err(|_| OrderServiceError::OrderNotReadError)?
         else {
             error!("Order not found");
             return Err(OrderServiceError::OrderNotFoundError);
         };
+        if expected_version.is_some_and(|version| version != order.version()) {
+            error!("Order version does not match the expected one");
+            return Err(OrderServiceError::ConcurrencyConflictError);
+        }
+        Ok(order)
+    }
 
-        self.begin_transaction().await?;
+    /// Prices the changed order again and stores it with `message`.
+    async fn update_order(
+        &mut self,
+        mut order: Order,
+        message: Result<OutboxMessage, OutboxMessageError>,
+    ) -> Result<Order, OrderServiceError> {
+        self.calculate_taxes(&mut order).await?;
+        let message = message.map_err(|e| {
+            error!("Error serializing outbox message: {}", e);
+            OrderServiceError::GenericError("Error serializing outbox message".to_string())
+        })?;
 
-        order.add(OrderItem {
-            price: add_product.price,
-            quantity: add_product.quantity,
-            product_id: ProductId(product_id),
-        });
+        self.begin_transaction().await?;
 
         let updated_order = match self.order_repository.update(order).await {
             Ok(order) => order,
-            Err(e) => {
-                error!("Error saving order: {}", e);
+            Err(OrderRepositoryError::ConcurrencyConflict) => {
+                error!("Order was modified concurrently");
                 self.rollback_transaction().await?;
-                return Err(OrderServiceError::OrderNotSavedError);
+                return Err(OrderServiceError::ConcurrencyConflictError);
+            }
+            Err(OrderRepositoryError::OrderNotFoundError) => {
+                error!("Order not found");
+                self.rollback_transaction().await?;
+                return Err(OrderServiceError::OrderNotFoundError);
             }
-        };
-
-        let message = match OutboxMessage::product_added_to_order_event(
-            &OrderId(order_id),
-            &ProductId(product_id),
-            add_product.price,
-            add_product.quantity,
-        ) {
-            Ok(message) => message,
             Err(e) => {
-                error!("Error serializing outbox message: {}", e);
+                error!("Error saving order: {}", e);
                 self.rollback_transaction().await?;
-                return Err(OrderServiceError::GenericError(
-                    "Error serializing outbox message".to_string(),
-                ));
+                return Err(OrderServiceError::OrderNotSavedError);
             }
         };
 
         if let Err(e) = self.outbox_message_repository.save(message).await {
-            error!("Error serializing outbox message: {}", e);
+            error!("Error saving outbox message: {}", e);
             self.rollback_transaction().await?;
             return Err(OrderServiceError::GenericError(
-                "Error serializing outbox message".to_string(),
+                "Outbox message not saved".to_string(),
             ));
         }
 
@@ -189,6 +542,24 @@ impl OrderService {
         Ok(updated_order)
     }
 
+    /// Looks the tax rates of the items up for the shipping address. Orders
+    /// placed before addresses were recorded keep their rates.
+    async fn calculate_taxes(&self, order: &mut Order) -> Result<(), OrderServiceError> {
+        let Some(addresses) = order.addresses() else {
+            return Ok(());
+        };
+        let rates = self
+            .tax_calculator
+            .tax_rates(&addresses.shipping, order.order_items())
+            .await
+            .map_err(|e| {
+                error!("Error calculating taxes: {}", e);
+                OrderServiceError::TaxCalculationError(e)
+            })?;
+        order.set_tax_rates(rates);
+        Ok(())
+    }
+
     async fn begin_transaction(&mut self) -> Result<(), OrderServiceError> {
         self.order_repository
             .begin_transaction()
@@ -211,27 +582,64 @@ impl OrderService {
     }
 }
 
+fn parse_address_id(address_id: Option<&str>) -> Result<Option<AddressId>, OrderServiceError> {
+    address_id
+        .map(|address_id| {
+            Uuid::try_parse(address_id)
+                .map(AddressId)
+                .map_err(|err| OrderServiceError::GenericError(err.to_string()))
+        })
+        .transpose()
+}
+
 #[cfg(test)]
 mod test {
 
+    use std::collections::HashMap;
+
+    use chrono::Utc;
     use uuid::Uuid;
 
     use crate::{
         entities::{
+            address_book::{AddressBook, AddressBookError},
+            currency::OrderCurrency,
             customer::Customer,
-            order::Order,
-            outbox::{OutboxMessage, OutboxMessageType},
+            order::{Order, OrderAddresses, OrderStatus},
+            order_lines::{OrderLines, OrderLinesError},
+            outbox::{
+                OutboxMessage, OutboxMessageType, ProductQuantityChangedEvent,
+                ProductRemovedFromOrderEvent,
+            },
+            product::Variant,
+            shipping_method::{ShippingMethod, ShippingRate},
+            tax::TaxCategory,
         },
+        gateways::tax_calculator::{TaxRule, TaxRuleTable},
         repositories::{
+            catalog_repository::MockCatalogRepository,
+            currency_repository::InMemoryCurrencyRepository,
             customer_repository::MockMyCustomerRepository,
-            order_repository::{MockMyOrderRepository, OrderRepositoryError},
+            event_sourced_order_repository::EventSourcedOrderRepository,
+            event_store::InMemoryEventStore,
+            order_repository::{MockMyOrderRepository, OrderRepository, OrderRepositoryError},
             outbox_repository::MockOutboxMessageRepository,
+            shipping_method_repository::MockShippingMethodRepository,
+        },
+        services::{
+            currency_service::{CurrencyService, CurrencyServiceError, SetPriceRequestObject},
+            order_service::{
+                AddProductRequestObject, ChangeProductQuantityRequestObject,
+                CreateOrderRequestObject,
+            },
+        },
+        value_objects::{
+            Address, AddressId, CountryCode, Currency, CustomerId, Email, OrderId, OrderItem,
+            PersonName, PostalCode, ProductId, Sku,
         },
-        services::order_service::{AddProductRequestObject, CreateOrderRequestObject},
-        value_objects::{Address, CustomerId, OrderId, ProductId},
     };
 
-    use super::OrderService;
+    use super::{OrderService, OrderServiceError};
 
     const ORDER_ID: &str = "2585491a-8e05-11ee-af1c-9bfe41ffe61f";
     const CUSTOMER_ID: &str = "2585491a-8e05-11ee-af1c-9bfe41ffe61f";
@@ -250,14 +658,21 @@ mod test {
         customer_repository.expect_find_by_id().returning(move |_| {
             Ok(Some(Customer {
                 id: CustomerId(Uuid::try_parse(CUSTOMER_ID).unwrap()),
-                first_name: "Mario".to_string(),
-                last_name: "Luigi".to_string(),
+                first_name: PersonName::parse("Mario").unwrap(),
+                last_name: PersonName::parse("Luigi").unwrap(),
+                email: Email::parse("mario.rossi@example.com").unwrap(),
+                phone: None,
                 address: Address {
                     street: "street".to_string(),
                     city: "city".to_string(),
-                    zip_code: "zip_code".to_string(),
-                    state: "state".to_string(),
+                    zip_code: PostalCode::parse("04401", &CountryCode::parse("US").unwrap())
+                        .unwrap(),
+                    country: CountryCode::parse("US").unwrap(),
                 },
+                address_book: AddressBook::new(),
+                currency: Currency::Eur,
+                deactivated_at: None,
+                version: 1,
             }))
         });
 
@@ -291,23 +706,33 @@ mod test {
             Box::new(customer_repository),
             Box::new(order_repository),
             Box::new(outbox_message_repository),
+            catalog_repository(),
+            shipping_method_repository(),
+            Box::new(TaxRuleTable::default()),
+            currency_service(),
         );
 
         let result = order_service
             .create_order(CreateOrderRequestObject {
                 order_id: ORDER_ID.to_string(),
                 customer_id: CUSTOMER_ID.to_string(),
+                shipping_address_id: None,
+                billing_address_id: None,
+                pricing_mode: None,
+                currency: None,
+                shipping_method_id: None,
+                shipping_weight: None,
             })
             .await;
 
         assert!(result.is_ok());
         let order = result.unwrap();
-        assert_eq!(OrderId(Uuid::try_parse(ORDER_ID).unwrap()), order.id);
+        assert_eq!(&OrderId(Uuid::try_parse(ORDER_ID).unwrap()), order.id());
         assert_eq!(
-            CustomerId(Uuid::try_parse(CUSTOMER_ID).unwrap()),
-            order.customer_id
+            &CustomerId(Uuid::try_parse(CUSTOMER_ID).unwrap()),
+            order.customer_id()
         );
-        assert_eq!(0, order.order_items.len());
+        assert_eq!(0, order.order_items().len());
     }
 
     #[tokio::test]
@@ -327,34 +752,205 @@ mod test {
             Box::new(customer_repository),
             Box::new(order_repository),
             Box::new(outbox_message_repository),
+            catalog_repository(),
+            shipping_method_repository(),
+            Box::new(TaxRuleTable::default()),
+            currency_service(),
         );
 
         let result = order_service
             .create_order(CreateOrderRequestObject {
                 order_id: ORDER_ID.to_string(),
                 customer_id: CUSTOMER_ID.to_string(),
+                shipping_address_id: None,
+                billing_address_id: None,
+                pricing_mode: None,
+                currency: None,
+                shipping_method_id: None,
+                shipping_weight: None,
             })
             .await;
 
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn snapshots_the_addresses_of_the_customer_in_the_order() {
+        let home = AddressId(Uuid::new_v4());
+        let office = AddressId(Uuid::new_v4());
+        let mut customer_repository = MockMyCustomerRepository::new();
+        let (home_id, office_id) = (home.clone(), office.clone());
+        customer_repository.expect_find_by_id().returning(move |_| {
+            let mut customer = customer_with_address("04401");
+            customer
+                .add_address(home_id.clone(), address("62701"))
+                .unwrap();
+            customer
+                .add_address(office_id.clone(), address("10001"))
+                .unwrap();
+            Ok(Some(customer))
+        });
+        let mut order_repository = MockMyOrderRepository::new();
+        order_repository
+            .expect_save()
+            .withf(|order| {
+                order.addresses()
+                    == Some(&OrderAddresses {
+                        shipping: address("10001"),
+                        billing: address("62701"),
+                    })
+            })
+            .once()
+            .return_once(Ok);
+        order_repository
+            .expect_begin_transaction()
+            .returning(|| Ok(()));
+        order_repository
+            .expect_commit_transaction()
+            .returning(|| Ok(()));
+        let mut outbox_message_repository = MockOutboxMessageRepository::new();
+        outbox_message_repository.expect_save().returning(Ok);
+
+        let mut order_service = OrderService::new(
+            Box::new(customer_repository),
+            Box::new(order_repository),
+            Box::new(outbox_message_repository),
+            catalog_repository(),
+            shipping_method_repository(),
+            Box::new(TaxRuleTable::default()),
+            currency_service(),
+        );
+        let result = order_service
+            .create_order(CreateOrderRequestObject {
+                order_id: ORDER_ID.to_string(),
+                customer_id: CUSTOMER_ID.to_string(),
+                shipping_address_id: Some(office.0.to_string()),
+                billing_address_id: None,
+                pricing_mode: None,
+                currency: None,
+                shipping_method_id: None,
+                shipping_weight: None,
+            })
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn cannot_create_an_order_with_an_address_of_another_customer() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_id()
+            .returning(|_| Ok(Some(customer_with_address("04401"))));
+        let mut order_repository = MockMyOrderRepository::new();
+        order_repository.expect_save().never();
+
+        let mut order_service = OrderService::new(
+            Box::new(customer_repository),
+            Box::new(order_repository),
+            Box::new(MockOutboxMessageRepository::new()),
+            catalog_repository(),
+            shipping_method_repository(),
+            Box::new(TaxRuleTable::default()),
+            currency_service(),
+        );
+        let result = order_service
+            .create_order(CreateOrderRequestObject {
+                order_id: ORDER_ID.to_string(),
+                customer_id: CUSTOMER_ID.to_string(),
+                shipping_address_id: None,
+                billing_address_id: Some(Uuid::new_v4().to_string()),
+                pricing_mode: None,
+                currency: None,
+                shipping_method_id: None,
+                shipping_weight: None,
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(OrderServiceError::InvalidAddressError(
+                AddressBookError::AddressNotFoundError(_)
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn cannot_create_an_order_for_a_deactivated_customer() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository.expect_find_by_id().returning(move |_| {
+            Ok(Some(Customer {
+                id: CustomerId(Uuid::try_parse(CUSTOMER_ID).unwrap()),
+                first_name: PersonName::parse("Mario").unwrap(),
+                last_name: PersonName::parse("Luigi").unwrap(),
+                email: Email::parse("mario.rossi@example.com").unwrap(),
+                phone: None,
+                address: Address {
+                    street: "street".to_string(),
+                    city: "city".to_string(),
+                    zip_code: PostalCode::parse("04401", &CountryCode::parse("US").unwrap())
+                        .unwrap(),
+                    country: CountryCode::parse("US").unwrap(),
+                },
+                address_book: AddressBook::new(),
+                currency: Currency::Eur,
+                deactivated_at: Some(Utc::now()),
+                version: 2,
+            }))
+        });
+
+        let mut order_repository = MockMyOrderRepository::new();
+        order_repository.expect_save().never();
+
+        let mut order_service = OrderService::new(
+            Box::new(customer_repository),
+            Box::new(order_repository),
+            Box::new(MockOutboxMessageRepository::new()),
+            catalog_repository(),
+            shipping_method_repository(),
+            Box::new(TaxRuleTable::default()),
+            currency_service(),
+        );
+
+        let result = order_service
+            .create_order(CreateOrderRequestObject {
+                order_id: ORDER_ID.to_string(),
+                customer_id: CUSTOMER_ID.to_string(),
+                shipping_address_id: None,
+                billing_address_id: None,
+                pricing_mode: None,
+                currency: None,
+                shipping_method_id: None,
+                shipping_weight: None,
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(OrderServiceError::CustomerNotFoundError)
+        ));
+    }
+
     #[tokio::test]
     async fn adds_a_product_to_an_order() {
         let mut order_repository = MockMyOrderRepository::new();
         order_repository.expect_find_by_id().returning(|_| {
-            Ok(Some(Order {
-                id: OrderId(Uuid::try_parse(ORDER_ID).unwrap()),
-                customer_id: CustomerId(Uuid::new_v4()),
-                order_items: vec![],
-            }))
+            Ok(Some(Order::restore(
+                OrderId(Uuid::try_parse(ORDER_ID).unwrap()),
+                CustomerId(Uuid::new_v4()),
+                OrderLines::new(),
+                None,
+                1,
+            )))
         });
         order_repository.expect_update().return_once(|_| {
-            Ok(Order {
-                id: OrderId(Uuid::try_parse(ORDER_ID).unwrap()),
-                customer_id: CustomerId(Uuid::new_v4()),
-                order_items: vec![],
-            })
+            Ok(Order::restore(
+                OrderId(Uuid::try_parse(ORDER_ID).unwrap()),
+                CustomerId(Uuid::new_v4()),
+                OrderLines::new(),
+                None,
+                1,
+            ))
         });
         order_repository
             .expect_begin_transaction()
@@ -371,6 +967,7 @@ mod test {
             &ProductId(Uuid::try_parse(PRODUCT_ID).unwrap()),
             price,
             quantity,
+            &OrderCurrency::default(),
         )
         .unwrap();
         let expected_event_payload = saved_outbox_message.event_payload();
@@ -391,14 +988,18 @@ mod test {
             Box::new(customer_repository),
             Box::new(order_repository),
             Box::new(outbox_message_repository),
+            catalog_repository(),
+            shipping_method_repository(),
+            Box::new(TaxRuleTable::default()),
+            currency_service(),
         );
 
         let result = order_service
             .add_product(AddProductRequestObject {
                 order_id: ORDER_ID.to_string(),
                 product_id: PRODUCT_ID.to_string(),
-                price,
                 quantity,
+                expected_version: None,
             })
             .await;
 
@@ -414,20 +1015,67 @@ mod test {
             Box::new(MockMyCustomerRepository::new()),
             Box::new(order_repository),
             Box::new(MockOutboxMessageRepository::new()),
+            catalog_repository(),
+            shipping_method_repository(),
+            Box::new(TaxRuleTable::default()),
+            currency_service(),
         );
 
         let result = order_service
             .add_product(AddProductRequestObject {
                 order_id: ORDER_ID.to_string(),
                 product_id: PRODUCT_ID.to_string(),
-                price: 10.0,
                 quantity: 1,
+                expected_version: None,
             })
             .await;
 
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn cannot_add_a_product_missing_from_the_catalog() {
+        let mut order_repository = MockMyOrderRepository::new();
+        order_repository.expect_find_by_id().returning(|_| {
+            Ok(Some(Order::restore(
+                OrderId(Uuid::try_parse(ORDER_ID).unwrap()),
+                CustomerId(Uuid::new_v4()),
+                OrderLines::new(),
+                None,
+                1,
+            )))
+        });
+        order_repository.expect_update().never();
+        let mut catalog_repository = MockCatalogRepository::new();
+        catalog_repository
+            .expect_find_variant()
+            .returning(|_| Ok(None));
+
+        let mut order_service = OrderService::new(
+            Box::new(MockMyCustomerRepository::new()),
+            Box::new(order_repository),
+            Box::new(MockOutboxMessageRepository::new()),
+            Box::new(catalog_repository),
+            shipping_method_repository(),
+            Box::new(TaxRuleTable::default()),
+            currency_service(),
+        );
+
+        let result = order_service
+            .add_product(AddProductRequestObject {
+                order_id: ORDER_ID.to_string(),
+                product_id: PRODUCT_ID.to_string(),
+                quantity: 1,
+                expected_version: None,
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(OrderServiceError::ProductNotFoundError(_))
+        ));
+    }
+
     #[tokio::test]
     async fn cannot_add_a_product_if_there_is_an_infrastructural_failure() {
         let mut order_repository = MockMyOrderRepository::new();
@@ -438,17 +1086,716 @@ mod test {
             customer_repository: Box::new(MockMyCustomerRepository::new()),
             order_repository: Box::new(order_repository),
             outbox_message_repository: Box::new(MockOutboxMessageRepository::new()),
+            catalog_repository: catalog_repository(),
+            shipping_method_repository: shipping_method_repository(),
+            tax_calculator: Box::new(TaxRuleTable::default()),
+            currency_service: currency_service(),
         };
 
         let result = order_service
             .add_product(AddProductRequestObject {
                 order_id: ORDER_ID.to_string(),
                 product_id: PRODUCT_ID.to_string(),
-                price: 10.0,
                 quantity: 1,
+                expected_version: None,
             })
             .await;
 
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn creates_an_order_and_adds_products_on_an_event_sourced_repository() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository.expect_find_by_id().returning(move |_| {
+            Ok(Some(Customer {
+                id: CustomerId(Uuid::try_parse(CUSTOMER_ID).unwrap()),
+                first_name: PersonName::parse("Mario").unwrap(),
+                last_name: PersonName::parse("Luigi").unwrap(),
+                email: Email::parse("mario.rossi@example.com").unwrap(),
+                phone: None,
+                address: Address {
+                    street: "street".to_string(),
+                    city: "city".to_string(),
+                    zip_code: PostalCode::parse("04401", &CountryCode::parse("US").unwrap())
+                        .unwrap(),
+                    country: CountryCode::parse("US").unwrap(),
+                },
+                address_book: AddressBook::new(),
+                currency: Currency::Eur,
+                deactivated_at: None,
+                version: 1,
+            }))
+        });
+        let mut outbox_message_repository = MockOutboxMessageRepository::new();
+        outbox_message_repository
+            .expect_save()
+            .times(3)
+            .returning(Ok);
+        let event_store = InMemoryEventStore::new();
+        let mut order_service = OrderService::new(
+            Box::new(customer_repository),
+            Box::new(EventSourcedOrderRepository::new(Box::new(
+                event_store.clone(),
+            ))),
+            Box::new(outbox_message_repository),
+            catalog_repository(),
+            shipping_method_repository(),
+            Box::new(TaxRuleTable::default()),
+            currency_service(),
+        );
+
+        order_service
+            .create_order(CreateOrderRequestObject {
+                order_id: ORDER_ID.to_string(),
+                customer_id: CUSTOMER_ID.to_string(),
+                shipping_address_id: None,
+                billing_address_id: None,
+                pricing_mode: None,
+                currency: None,
+                shipping_method_id: None,
+                shipping_weight: None,
+            })
+            .await
+            .unwrap();
+        for quantity in [1, 2] {
+            order_service
+                .add_product(AddProductRequestObject {
+                    order_id: ORDER_ID.to_string(),
+                    product_id: PRODUCT_ID.to_string(),
+                    quantity,
+                    expected_version: None,
+                })
+                .await
+                .unwrap();
+        }
+
+        let order = EventSourcedOrderRepository::new(Box::new(event_store))
+            .find_by_id(OrderId(Uuid::try_parse(ORDER_ID).unwrap()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            &CustomerId(Uuid::try_parse(CUSTOMER_ID).unwrap()),
+            order.customer_id()
+        );
+        assert_eq!(1, order.order_items().len());
+        assert_eq!(3, order.order_items()[0].quantity);
+        assert_eq!(29.97, order.total_price());
+    }
+
+    #[tokio::test]
+    async fn taxes_the_products_at_the_rates_of_the_shipping_address() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository.expect_find_by_id().returning(move |_| {
+            Ok(Some(Customer {
+                id: CustomerId(Uuid::try_parse(CUSTOMER_ID).unwrap()),
+                first_name: PersonName::parse("Mario").unwrap(),
+                last_name: PersonName::parse("Luigi").unwrap(),
+                email: Email::parse("mario.rossi@example.com").unwrap(),
+                phone: None,
+                address: Address {
+                    street: "street".to_string(),
+                    city: "city".to_string(),
+                    zip_code: PostalCode::parse("04401", &CountryCode::parse("US").unwrap())
+                        .unwrap(),
+                    country: CountryCode::parse("US").unwrap(),
+                },
+                address_book: AddressBook::new(),
+                currency: Currency::Eur,
+                deactivated_at: None,
+                version: 1,
+            }))
+        });
+        let mut outbox_message_repository = MockOutboxMessageRepository::new();
+        outbox_message_repository.expect_save().returning(Ok);
+        let event_store = InMemoryEventStore::new();
+        let mut order_service = OrderService::new(
+            Box::new(customer_repository),
+            Box::new(EventSourcedOrderRepository::new(Box::new(
+                event_store.clone(),
+            ))),
+            Box::new(outbox_message_repository),
+            catalog_repository(),
+            shipping_method_repository(),
+            Box::new(TaxRuleTable::new(
+                vec![TaxRule {
+                    country: CountryCode::parse("US").unwrap(),
+                    region: Some("044".to_string()),
+                    category: TaxCategory::Standard,
+                    rate: 5.5,
+                }],
+                HashMap::new(),
+            )),
+            currency_service(),
+        );
+
+        order_service
+            .create_order(CreateOrderRequestObject {
+                order_id: ORDER_ID.to_string(),
+                customer_id: CUSTOMER_ID.to_string(),
+                shipping_address_id: None,
+                billing_address_id: None,
+                pricing_mode: Some("exclusive".to_string()),
+                currency: None,
+                shipping_method_id: None,
+                shipping_weight: None,
+            })
+            .await
+            .unwrap();
+        order_service
+            .add_product(AddProductRequestObject {
+                order_id: ORDER_ID.to_string(),
+                product_id: PRODUCT_ID.to_string(),
+                quantity: 2,
+                expected_version: None,
+            })
+            .await
+            .unwrap();
+
+        let order = EventSourcedOrderRepository::new(Box::new(event_store))
+            .find_by_id(OrderId(Uuid::try_parse(ORDER_ID).unwrap()))
+            .await
+            .unwrap()
+            .unwrap();
+        let tax_lines = order.tax_lines();
+        assert_eq!(1, tax_lines.len());
+        assert_eq!(5.5, tax_lines[0].rate);
+        assert_eq!(1.1, tax_lines[0].amount);
+        assert_eq!(21.08, order.total_price());
+    }
+
+    #[tokio::test]
+    async fn adds_the_shipping_method_of_an_order_once_to_its_total() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_id()
+            .returning(|_| Ok(Some(customer_with_address("04401"))));
+        let mut outbox_message_repository = MockOutboxMessageRepository::new();
+        outbox_message_repository.expect_save().returning(Ok);
+        let event_store = InMemoryEventStore::new();
+        let mut order_service = OrderService::new(
+            Box::new(customer_repository),
+            Box::new(EventSourcedOrderRepository::new(Box::new(
+                event_store.clone(),
+            ))),
+            Box::new(outbox_message_repository),
+            catalog_repository(),
+            shipping_method_repository(),
+            Box::new(TaxRuleTable::default()),
+            currency_service(),
+        );
+
+        order_service
+            .create_order(CreateOrderRequestObject {
+                order_id: ORDER_ID.to_string(),
+                customer_id: CUSTOMER_ID.to_string(),
+                shipping_address_id: None,
+                billing_address_id: None,
+                pricing_mode: None,
+                currency: None,
+                shipping_method_id: Some(Uuid::new_v4().to_string()),
+                shipping_weight: None,
+            })
+            .await
+            .unwrap();
+        order_service
+            .add_product(AddProductRequestObject {
+                order_id: ORDER_ID.to_string(),
+                product_id: PRODUCT_ID.to_string(),
+                quantity: 2,
+                expected_version: None,
+            })
+            .await
+            .unwrap();
+
+        let order = EventSourcedOrderRepository::new(Box::new(event_store))
+            .find_by_id(OrderId(Uuid::try_parse(ORDER_ID).unwrap()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!("UPS", order.shipping().unwrap().carrier);
+        assert_eq!(4.9, order.pricing().shipping);
+        assert_eq!(24.88, order.total_price());
+    }
+
+    #[tokio::test]
+    async fn cannot_create_an_order_with_a_missing_shipping_method() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository
+            .expect_find_by_id()
+            .returning(|_| Ok(Some(customer_with_address("04401"))));
+        let mut order_repository = MockMyOrderRepository::new();
+        order_repository.expect_save().never();
+        let mut shipping_method_repository = MockShippingMethodRepository::new();
+        shipping_method_repository
+            .expect_find_by_id()
+            .returning(|_| Ok(None));
+        let mut order_service = OrderService::new(
+            Box::new(customer_repository),
+            Box::new(order_repository),
+            Box::new(MockOutboxMessageRepository::new()),
+            catalog_repository(),
+            Box::new(shipping_method_repository),
+            Box::new(TaxRuleTable::default()),
+            currency_service(),
+        );
+
+        let result = order_service
+            .create_order(CreateOrderRequestObject {
+                order_id: ORDER_ID.to_string(),
+                customer_id: CUSTOMER_ID.to_string(),
+                shipping_address_id: None,
+                billing_address_id: None,
+                pricing_mode: None,
+                currency: None,
+                shipping_method_id: Some(Uuid::new_v4().to_string()),
+                shipping_weight: None,
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(OrderServiceError::ShippingMethodNotFoundError)
+        ));
+    }
+
+    #[tokio::test]
+    async fn prices_an_order_in_the_currency_of_the_customer() {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository.expect_find_by_id().returning(|_| {
+            Ok(Some(Customer {
+                currency: Currency::Gbp,
+                ..customer_with_address("04401")
+            }))
+        });
+        let mut outbox_message_repository = MockOutboxMessageRepository::new();
+        outbox_message_repository.expect_save().returning(Ok);
+        let currency_service = currency_service();
+        currency_service
+            .import_rates("GBP,2000-01-01,0.8")
+            .await
+            .unwrap();
+        currency_service
+            .set_price(SetPriceRequestObject {
+                product_id: PRODUCT_ID.to_string(),
+                currency: "GBP".to_string(),
+                amount: 8.0,
+            })
+            .await
+            .unwrap();
+        let event_store = InMemoryEventStore::new();
+        let mut order_service = OrderService::new(
+            Box::new(customer_repository),
+            Box::new(EventSourcedOrderRepository::new(Box::new(
+                event_store.clone(),
+            ))),
+            Box::new(outbox_message_repository),
+            catalog_repository(),
+            shipping_method_repository(),
+            Box::new(TaxRuleTable::default()),
+            currency_service,
+        );
+
+        order_service
+            .create_order(CreateOrderRequestObject {
+                order_id: ORDER_ID.to_string(),
+                customer_id: CUSTOMER_ID.to_string(),
+                shipping_address_id: None,
+                billing_address_id: None,
+                pricing_mode: None,
+                currency: None,
+                shipping_method_id: None,
+                shipping_weight: None,
+            })
+            .await
+            .unwrap();
+        order_service
+            .add_product(AddProductRequestObject {
+                order_id: ORDER_ID.to_string(),
+                product_id: PRODUCT_ID.to_string(),
+                quantity: 2,
+                expected_version: None,
+            })
+            .await
+            .unwrap();
+
+        let order = EventSourcedOrderRepository::new(Box::new(event_store))
+            .find_by_id(OrderId(Uuid::try_parse(ORDER_ID).unwrap()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            OrderCurrency {
+                currency: Currency::Gbp,
+                exchange_rate: 0.8,
+            },
+            *order.currency()
+        );
+        assert_eq!(16.0, order.total_price());
+        assert_eq!(20.0, order.base_total_price());
+        assert!(matches!(
+            order_service
+                .create_order(CreateOrderRequestObject {
+                    order_id: Uuid::new_v4().to_string(),
+                    customer_id: CUSTOMER_ID.to_string(),
+                    shipping_address_id: None,
+                    billing_address_id: None,
+                    pricing_mode: None,
+                    currency: Some("USD".to_string()),
+                    shipping_method_id: None,
+                    shipping_weight: None,
+                })
+                .await,
+            Err(OrderServiceError::CurrencyError(
+                CurrencyServiceError::ExchangeRateNotFoundError(Currency::Usd)
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn cannot_add_a_product_with_an_invalid_quantity() {
+        let mut order_repository = MockMyOrderRepository::new();
+        order_repository.expect_find_by_id().returning(|_| {
+            Ok(Some(Order::restore(
+                OrderId(Uuid::try_parse(ORDER_ID).unwrap()),
+                CustomerId(Uuid::new_v4()),
+                OrderLines::new(),
+                None,
+                1,
+            )))
+        });
+        order_repository.expect_begin_transaction().never();
+        order_repository.expect_update().never();
+        let mut order_service = OrderService::new(
+            Box::new(MockMyCustomerRepository::new()),
+            Box::new(order_repository),
+            Box::new(MockOutboxMessageRepository::new()),
+            catalog_repository(),
+            shipping_method_repository(),
+            Box::new(TaxRuleTable::default()),
+            currency_service(),
+        );
+
+        let result = order_service
+            .add_product(AddProductRequestObject {
+                order_id: ORDER_ID.to_string(),
+                product_id: PRODUCT_ID.to_string(),
+                quantity: 0,
+                expected_version: None,
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(OrderServiceError::InvalidOrderError(
+                OrderLinesError::InvalidQuantityError(0)
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn cannot_add_a_product_to_an_order_at_another_version() {
+        let mut order_repository = MockMyOrderRepository::new();
+        order_repository.expect_find_by_id().returning(|_| {
+            Ok(Some(Order::restore(
+                OrderId(Uuid::try_parse(ORDER_ID).unwrap()),
+                CustomerId(Uuid::new_v4()),
+                OrderLines::new(),
+                None,
+                2,
+            )))
+        });
+        order_repository.expect_update().never();
+        let mut order_service = OrderService::new(
+            Box::new(MockMyCustomerRepository::new()),
+            Box::new(order_repository),
+            Box::new(MockOutboxMessageRepository::new()),
+            catalog_repository(),
+            shipping_method_repository(),
+            Box::new(TaxRuleTable::default()),
+            currency_service(),
+        );
+
+        let result = order_service
+            .add_product(AddProductRequestObject {
+                order_id: ORDER_ID.to_string(),
+                product_id: PRODUCT_ID.to_string(),
+                quantity: 1,
+                expected_version: Some(1),
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(OrderServiceError::ConcurrencyConflictError)
+        ));
+    }
+
+    #[tokio::test]
+    async fn cannot_add_a_product_to_an_order_modified_concurrently() {
+        let mut order_repository = MockMyOrderRepository::new();
+        order_repository.expect_find_by_id().returning(|_| {
+            Ok(Some(Order::restore(
+                OrderId(Uuid::try_parse(ORDER_ID).unwrap()),
+                CustomerId(Uuid::new_v4()),
+                OrderLines::new(),
+                None,
+                1,
+            )))
+        });
+        order_repository
+            .expect_update()
+            .return_once(|_| Err(OrderRepositoryError::ConcurrencyConflict));
+        order_repository
+            .expect_begin_transaction()
+            .returning(|| Ok(()));
+        order_repository
+            .expect_rollback_transaction()
+            .once()
+            .returning(|| Ok(()));
+        let mut outbox_message_repository = MockOutboxMessageRepository::new();
+        outbox_message_repository.expect_save().never();
+        let mut order_service = OrderService::new(
+            Box::new(MockMyCustomerRepository::new()),
+            Box::new(order_repository),
+            Box::new(outbox_message_repository),
+            catalog_repository(),
+            shipping_method_repository(),
+            Box::new(TaxRuleTable::default()),
+            currency_service(),
+        );
+
+        let result = order_service
+            .add_product(AddProductRequestObject {
+                order_id: ORDER_ID.to_string(),
+                product_id: PRODUCT_ID.to_string(),
+                quantity: 1,
+                expected_version: Some(1),
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(OrderServiceError::ConcurrencyConflictError)
+        ));
+    }
+
+    #[tokio::test]
+    async fn removes_a_product_from_an_order() {
+        let mut outbox_message_repository = MockOutboxMessageRepository::new();
+        outbox_message_repository
+            .expect_save()
+            .withf(|m| {
+                let event: ProductRemovedFromOrderEvent =
+                    serde_json::from_str(&m.event_payload()).unwrap();
+                m.event_type() == OutboxMessageType::ProductRemovedFromOrder
+                    && event.quantity == 3
+                    && event.price == 10.0
+            })
+            .once()
+            .returning(Ok);
+        let mut order_service = OrderService::new(
+            Box::new(MockMyCustomerRepository::new()),
+            Box::new(order_repository_with_a_line()),
+            Box::new(outbox_message_repository),
+            catalog_repository(),
+            shipping_method_repository(),
+            Box::new(TaxRuleTable::default()),
+            currency_service(),
+        );
+
+        let order = order_service
+            .remove_product(ORDER_ID, PRODUCT_ID, Some(1))
+            .await
+            .unwrap();
+
+        assert!(order.order_items().is_empty());
+        assert!(matches!(
+            order_service
+                .remove_product(ORDER_ID, &Uuid::new_v4().to_string(), None)
+                .await,
+            Err(OrderServiceError::InvalidOrderError(
+                OrderLinesError::ProductNotFoundError(_)
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn cannot_change_the_items_of_a_placed_order() {
+        let mut order_repository = MockMyOrderRepository::new();
+        order_repository.expect_find_by_id().returning(|_| {
+            let mut order_lines = OrderLines::new();
+            order_lines
+                .add(OrderItem {
+                    product_id: ProductId(Uuid::try_parse(PRODUCT_ID).unwrap()),
+                    price: 10.0,
+                    quantity: 3,
+                })
+                .unwrap();
+            Ok(Some(
+                Order::restore(
+                    OrderId(Uuid::try_parse(ORDER_ID).unwrap()),
+                    CustomerId(Uuid::new_v4()),
+                    order_lines,
+                    None,
+                    1,
+                )
+                .with_status(OrderStatus::Pending),
+            ))
+        });
+        order_repository.expect_update().never();
+        let mut order_service = OrderService::new(
+            Box::new(MockMyCustomerRepository::new()),
+            Box::new(order_repository),
+            Box::new(MockOutboxMessageRepository::new()),
+            catalog_repository(),
+            shipping_method_repository(),
+            Box::new(TaxRuleTable::default()),
+            currency_service(),
+        );
+
+        let removed = order_service
+            .remove_product(ORDER_ID, PRODUCT_ID, None)
+            .await;
+        let changed = order_service
+            .change_product_quantity(ChangeProductQuantityRequestObject {
+                order_id: ORDER_ID.to_string(),
+                product_id: PRODUCT_ID.to_string(),
+                quantity: 1,
+                expected_version: None,
+            })
+            .await;
+
+        for result in [removed, changed] {
+            assert!(matches!(
+                result,
+                Err(OrderServiceError::InvalidOrderError(
+                    OrderLinesError::OrderNotDraftError(OrderStatus::Pending)
+                ))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn changes_the_quantity_of_a_product_in_an_order() {
+        let mut outbox_message_repository = MockOutboxMessageRepository::new();
+        outbox_message_repository
+            .expect_save()
+            .withf(|m| {
+                let event: ProductQuantityChangedEvent =
+                    serde_json::from_str(&m.event_payload()).unwrap();
+                m.event_type() == OutboxMessageType::ProductQuantityChanged
+                    && event.previous_quantity == 3
+                    && event.quantity == 1
+                    && event.quantity_change() == -2
+            })
+            .once()
+            .returning(Ok);
+        let mut order_service = OrderService::new(
+            Box::new(MockMyCustomerRepository::new()),
+            Box::new(order_repository_with_a_line()),
+            Box::new(outbox_message_repository),
+            catalog_repository(),
+            shipping_method_repository(),
+            Box::new(TaxRuleTable::default()),
+            currency_service(),
+        );
+
+        let order = order_service
+            .change_product_quantity(ChangeProductQuantityRequestObject {
+                order_id: ORDER_ID.to_string(),
+                product_id: PRODUCT_ID.to_string(),
+                quantity: 1,
+                expected_version: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(1, order.order_items()[0].quantity);
+    }
+
+    /// An order at version 1 with 3 units of `PRODUCT_ID` at 10.0, saved
+    /// as it is updated.
+    fn order_repository_with_a_line() -> MockMyOrderRepository {
+        let mut order_repository = MockMyOrderRepository::new();
+        order_repository.expect_find_by_id().returning(|_| {
+            let mut order_lines = OrderLines::new();
+            order_lines
+                .add(OrderItem {
+                    product_id: ProductId(Uuid::try_parse(PRODUCT_ID).unwrap()),
+                    price: 10.0,
+                    quantity: 3,
+                })
+                .unwrap();
+            Ok(Some(Order::restore(
+                OrderId(Uuid::try_parse(ORDER_ID).unwrap()),
+                CustomerId(Uuid::new_v4()),
+                order_lines,
+                None,
+                1,
+            )))
+        });
+        order_repository
+            .expect_update()
+            .returning(|order| Ok(order.with_version(2)));
+        order_repository
+            .expect_begin_transaction()
+            .returning(|| Ok(()));
+        order_repository
+            .expect_commit_transaction()
+            .returning(|| Ok(()));
+        order_repository
+    }
+
+    /// Finds a variant priced 9.99 for any id.
+    fn catalog_repository() -> Box<MockCatalogRepository> {
+        let mut catalog_repository = MockCatalogRepository::new();
+        catalog_repository.expect_find_variant().returning(|id| {
+            Ok(Some(Variant {
+                id,
+                sku: Sku::parse("SKU-1").unwrap(),
+                price: 9.99,
+                attributes: vec![],
+            }))
+        });
+        Box::new(catalog_repository)
+    }
+
+    /// Finds a method shipping for a flat 4.9 for any id.
+    fn shipping_method_repository() -> Box<MockShippingMethodRepository> {
+        let mut shipping_method_repository = MockShippingMethodRepository::new();
+        shipping_method_repository
+            .expect_find_by_id()
+            .returning(|id| {
+                Ok(Some(
+                    ShippingMethod::new(id, "Standard", "UPS", ShippingRate::Flat(4.9)).unwrap(),
+                ))
+            });
+        Box::new(shipping_method_repository)
+    }
+
+    fn currency_service() -> CurrencyService {
+        CurrencyService::new(Box::new(InMemoryCurrencyRepository::new()))
+    }
+
+    fn customer_with_address(zip_code: &str) -> Customer {
+        Customer::new(
+            CustomerId(Uuid::try_parse(CUSTOMER_ID).unwrap()),
+            PersonName::parse("Mario").unwrap(),
+            PersonName::parse("Luigi").unwrap(),
+            Email::parse("mario.rossi@example.com").unwrap(),
+            None,
+            address(zip_code),
+        )
+    }
+
+    fn address(zip_code: &str) -> Address {
+        Address {
+            street: "street".to_string(),
+            city: "city".to_string(),
+            zip_code: PostalCode::parse(zip_code, &CountryCode::parse("US").unwrap()).unwrap(),
+            country: CountryCode::parse("US").unwrap(),
+        }
+    }
 }
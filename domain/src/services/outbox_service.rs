@@ -62,6 +62,11 @@ impl OutboxService {
         }
     }
 
+    /// Publishes unprocessed messages with at-least-once semantics.
+    ///
+    /// If a message is published but cannot be set to processed, it is sent
+    /// again on the next run with the same id, so consumers must deduplicate
+    /// on the event id (the event consumer does it with its inbox).
     pub async fn publish(&self) -> Result<(), OutboxServiceError> {
         let messages = self
             .outbox_message_repository
@@ -97,10 +102,14 @@ mod test {
     use uuid::Uuid;
 
     use crate::{
-        entities::{customer::Customer, outbox::OutboxMessage},
+        entities::{address_book::AddressBook, customer::Customer, outbox::OutboxMessage},
         publishers::outbox_publisher::{MockOutboxMessagePublisher, OutboxMessagePublisherError},
-        repositories::outbox_repository::MockOutboxMessageRepository,
-        value_objects::{Address, CustomerId},
+        repositories::outbox_repository::{
+            MockOutboxMessageRepository, OutboxMessageRepositoryError,
+        },
+        value_objects::{
+            Address, CountryCode, Currency, CustomerId, Email, PersonName, PostalCode,
+        },
     };
 
     use super::*;
@@ -166,17 +175,103 @@ mod test {
         ));
     }
 
+    #[tokio::test]
+    pub async fn handles_set_processed_errors_after_publishing() {
+        let message = OutboxMessage::customer_created_event(&create_customer()).unwrap();
+        let message_id = message.id();
+
+        let mut publisher = MockOutboxMessagePublisher::new();
+        publisher.expect_publish().return_once(|_| Ok(())).once();
+
+        let mut repository = MockOutboxMessageRepository::new();
+        repository
+            .expect_find_unprocessed()
+            .return_once(|| Ok(Some(vec![message])))
+            .once();
+        repository
+            .expect_set_processed()
+            .return_once(|_, _| {
+                Err(OutboxMessageRepositoryError::OutboxMessageNotSavedError(
+                    "Database error".to_string(),
+                ))
+            })
+            .once();
+
+        let service = OutboxService::new(Box::new(repository), Box::new(publisher));
+
+        let result = service.publish().await.unwrap_err();
+
+        assert!(matches!(
+            result,
+            OutboxServiceError::MessageNotSetToProcessedError(ref m, _) if m.id() == message_id
+        ));
+    }
+
+    #[tokio::test]
+    pub async fn republishes_message_with_same_id_if_not_set_to_processed() {
+        let message = OutboxMessage::customer_created_event(&create_customer()).unwrap();
+        let message_id = message.id();
+        let still_unprocessed = message.clone();
+
+        let mut publisher = MockOutboxMessagePublisher::new();
+        publisher
+            .expect_publish()
+            .withf(move |m| m.id() == message_id)
+            .returning(|_| Ok(()))
+            .times(2);
+
+        let mut repository = MockOutboxMessageRepository::new();
+        let mut find_calls = 0;
+        repository
+            .expect_find_unprocessed()
+            .returning(move || {
+                find_calls += 1;
+                if find_calls == 1 {
+                    Ok(Some(vec![message.clone()]))
+                } else {
+                    Ok(Some(vec![still_unprocessed.clone()]))
+                }
+            })
+            .times(2);
+        let mut set_processed_calls = 0;
+        repository
+            .expect_set_processed()
+            .withf(move |id, _| *id == message_id)
+            .returning(move |_, _| {
+                set_processed_calls += 1;
+                if set_processed_calls == 1 {
+                    Err(OutboxMessageRepositoryError::OutboxMessageNotSavedError(
+                        "Database error".to_string(),
+                    ))
+                } else {
+                    Ok(())
+                }
+            })
+            .times(2);
+
+        let service = OutboxService::new(Box::new(repository), Box::new(publisher));
+
+        assert!(service.publish().await.is_err());
+        assert!(service.publish().await.is_ok());
+    }
+
     fn create_customer() -> Customer {
         Customer {
             id: CustomerId(Uuid::new_v4()),
-            first_name: "my_customer_first_name".to_string(),
-            last_name: "my_customer_last_name".to_string(),
+            first_name: PersonName::parse("John").unwrap(),
+            last_name: PersonName::parse("Appleseed").unwrap(),
+            email: Email::parse("mario.rossi@example.com").unwrap(),
+            phone: None,
             address: Address {
-                street: "my_customer_street".to_string(),
-                city: "my_customer_city".to_string(),
-                zip_code: "my_customer_zip_code".to_string(),
-                state: "my_customer_state".to_string(),
+                street: "22 Elm Street".to_string(),
+                city: "Castle Rock".to_string(),
+                zip_code: PostalCode::parse("04401", &CountryCode::parse("US").unwrap()).unwrap(),
+                country: CountryCode::parse("US").unwrap(),
             },
+            address_book: AddressBook::new(),
+            currency: Currency::Eur,
+            deactivated_at: None,
+            version: 0,
         }
     }
 }
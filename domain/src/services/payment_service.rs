@@ -0,0 +1,565 @@
+use async_trait::async_trait;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    entities::{
+        order::Order,
+        outbox::OutboxMessage,
+        payment::{Payment, PaymentError, PaymentState},
+    },
+    gateways::payment_gateway::{PaymentGateway, PaymentGatewayError},
+    repositories::{
+        order_repository::OrderRepository,
+        outbox_repository::OutboxMessageRepository,
+        payment_repository::{PaymentRepository, PaymentRepositoryError},
+    },
+    services::invoice_service::{InvoiceService, InvoiceServiceError},
+    value_objects::{AuthorizationId, OrderId, PaymentId},
+};
+
+#[derive(Debug)]
+pub enum PaymentServiceError {
+    PaymentNotReadError,
+    PaymentNotSavedError,
+    OrderNotFoundError(OrderId),
+    OrderNotReadError,
+    InvalidPaymentError(PaymentError),
+    ConcurrencyConflictError,
+    ProviderError(PaymentGatewayError),
+    InvoiceError(InvoiceServiceError),
+    GenericError(String),
+}
+
+impl std::fmt::Display for PaymentServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaymentServiceError::PaymentNotReadError => write!(f, "Payment not read error"),
+            PaymentServiceError::PaymentNotSavedError => write!(f, "Payment not saved error"),
+            PaymentServiceError::OrderNotFoundError(order_id) => {
+                write!(f, "Order {} not found", order_id.0)
+            }
+            PaymentServiceError::OrderNotReadError => write!(f, "Order not read error"),
+            PaymentServiceError::InvalidPaymentError(error) => {
+                write!(f, "Invalid payment: {}", error)
+            }
+            PaymentServiceError::ConcurrencyConflictError => {
+                write!(f, "Payment was modified concurrently")
+            }
+            PaymentServiceError::ProviderError(error) => write!(f, "{}", error),
+            PaymentServiceError::InvoiceError(error) => write!(f, "{}", error),
+            PaymentServiceError::GenericError(error) => write!(f, "Generic error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for PaymentServiceError {}
+
+/// Records the payment of each order while forwarding the operations to the
+/// payment provider, and keeps them in line with the order status: payments
+/// are authorized for pending orders, captured for confirmed ones and voided
+/// for the others. Publishes `payment_failed` when the provider declines a
+/// payment. Captured payments are invoiced, refunds credited.
+pub struct PaymentService {
+    payment_repository: Box<dyn PaymentRepository + Send + Sync>,
+    order_repository: Box<dyn OrderRepository + Send + Sync>,
+    outbox_message_repository: Box<dyn OutboxMessageRepository + Send + Sync>,
+    payment_provider: Box<dyn PaymentGateway + Send + Sync>,
+    invoice_service: InvoiceService,
+}
+
+impl PaymentService {
+    pub fn new(
+        payment_repository: Box<dyn PaymentRepository + Send + Sync>,
+        order_repository: Box<dyn OrderRepository + Send + Sync>,
+        outbox_message_repository: Box<dyn OutboxMessageRepository + Send + Sync>,
+        payment_provider: Box<dyn PaymentGateway + Send + Sync>,
+        invoice_service: InvoiceService,
+    ) -> Self {
+        Self {
+            payment_repository,
+            order_repository,
+            outbox_message_repository,
+            payment_provider,
+            invoice_service,
+        }
+    }
+
+    pub async fn find_payment(
+        &self,
+        order_id: &OrderId,
+    ) -> Result<Option<Payment>, PaymentServiceError> {
+        self.payment_repository
+            .find_by_order_id(order_id.clone())
+            .await
+            .map_err(read_error)
+    }
+
+    /// The payment is stored as pending before the provider is asked, so an
+    /// authorization interrupted halfway is completed when attempted again.
+    async fn try_authorize(
+        &mut self,
+        order_id: &OrderId,
+        amount: f64,
+    ) -> Result<AuthorizationId, PaymentServiceError> {
+        let mut payment = match self.find_payment(order_id).await? {
+            Some(payment) => payment,
+            None => {
+                let order = self.find_order(order_id).await?;
+                let payment = Payment::new(PaymentId(Uuid::new_v4()), &order, amount)
+                    .map_err(PaymentServiceError::InvalidPaymentError)?;
+                self.payment_repository
+                    .save(payment)
+                    .await
+                    .map_err(save_error)?
+            }
+        };
+        match (payment.state(), payment.authorization_id()) {
+            (PaymentState::Pending, _) => {}
+            (PaymentState::Failed, _) => {
+                return Err(PaymentServiceError::ProviderError(
+                    PaymentGatewayError::PaymentDeclinedError(
+                        payment.failure_reason().unwrap_or_default().to_string(),
+                    ),
+                ))
+            }
+            (_, Some(authorization_id)) => return Ok(authorization_id.clone()),
+            (state, None) => {
+                return Err(PaymentServiceError::InvalidPaymentError(
+                    PaymentError::InvalidStateError(state),
+                ))
+            }
+        }
+
+        info!("Authorizing payment of order {}", order_id.0);
+        match self
+            .payment_provider
+            .authorize(order_id, payment.amount())
+            .await
+        {
+            Ok(authorization_id) => {
+                payment
+                    .authorized(authorization_id.clone())
+                    .map_err(PaymentServiceError::InvalidPaymentError)?;
+                self.store(payment, None).await?;
+                Ok(authorization_id)
+            }
+            Err(PaymentGatewayError::PaymentDeclinedError(reason)) => {
+                info!("Payment of order {} declined: {}", order_id.0, reason);
+                payment
+                    .failed(&reason)
+                    .map_err(PaymentServiceError::InvalidPaymentError)?;
+                let message =
+                    OutboxMessage::payment_failed_event(order_id, payment.amount(), &reason)
+                        .map_err(|e| PaymentServiceError::GenericError(e.to_string()))?;
+                self.store(payment, Some(message)).await?;
+                Err(PaymentServiceError::ProviderError(
+                    PaymentGatewayError::PaymentDeclinedError(reason),
+                ))
+            }
+            Err(e) => {
+                error!("Error authorizing payment: {}", e);
+                Err(PaymentServiceError::ProviderError(e))
+            }
+        }
+    }
+
+    /// A capture retried after the payment was captured issues the invoice,
+    /// if that failed.
+    async fn try_capture(
+        &mut self,
+        authorization_id: &AuthorizationId,
+        amount: f64,
+    ) -> Result<(), PaymentServiceError> {
+        let mut payment = self.get_payment(authorization_id).await?;
+        let order = self.find_order(payment.order_id()).await?;
+        if payment.state() != PaymentState::Captured {
+            payment
+                .capture(order.status(), amount)
+                .map_err(PaymentServiceError::InvalidPaymentError)?;
+
+            info!("Capturing payment of order {}", payment.order_id().0);
+            self.payment_provider
+                .capture(authorization_id, amount)
+                .await
+                .map_err(provider_error)?;
+            self.store(payment, None).await?;
+        }
+        self.invoice_service
+            .invoice(&order)
+            .await
+            .map(|_| ())
+            .map_err(invoice_error)
+    }
+
+    async fn try_void(
+        &mut self,
+        authorization_id: &AuthorizationId,
+    ) -> Result<(), PaymentServiceError> {
+        let mut payment = self.get_payment(authorization_id).await?;
+        if payment.state() == PaymentState::Voided {
+            return Ok(());
+        }
+        let order = self.find_order(payment.order_id()).await?;
+        payment
+            .void(order.status())
+            .map_err(PaymentServiceError::InvalidPaymentError)?;
+
+        info!("Voiding payment of order {}", payment.order_id().0);
+        self.payment_provider
+            .void(authorization_id)
+            .await
+            .map_err(provider_error)?;
+        self.store(payment, None).await
+    }
+
+    async fn try_refund(
+        &mut self,
+        authorization_id: &AuthorizationId,
+        amount: f64,
+        idempotency_key: &str,
+    ) -> Result<(), PaymentServiceError> {
+        let mut payment = self.get_payment(authorization_id).await?;
+        let order_id = payment.order_id().clone();
+        if !payment.has_refund(idempotency_key) {
+            payment
+                .refund(idempotency_key, amount)
+                .map_err(PaymentServiceError::InvalidPaymentError)?;
+
+            info!("Refunding {} of order {}", amount, order_id.0);
+            self.payment_provider
+                .refund(authorization_id, amount, idempotency_key)
+                .await
+                .map_err(provider_error)?;
+            self.store(payment, None).await?;
+        }
+        let order = self.find_order(&order_id).await?;
+        self.invoice_service
+            .credit(&order, idempotency_key, amount)
+            .await
+            .map(|_| ())
+            .map_err(invoice_error)
+    }
+
+    async fn store(
+        &mut self,
+        payment: Payment,
+        message: Option<OutboxMessage>,
+    ) -> Result<(), PaymentServiceError> {
+        self.begin_transaction().await?;
+        if let Err(e) = self.payment_repository.update(payment).await {
+            self.rollback_transaction().await?;
+            return Err(save_error(e));
+        }
+        if let Some(message) = message {
+            if let Err(e) = self.outbox_message_repository.save(message).await {
+                error!("Error saving outbox message: {}", e);
+                self.rollback_transaction().await?;
+                return Err(PaymentServiceError::GenericError(
+                    "Outbox message not saved".to_string(),
+                ));
+            }
+        }
+        self.commit_transaction().await
+    }
+
+    async fn get_payment(
+        &self,
+        authorization_id: &AuthorizationId,
+    ) -> Result<Payment, PaymentServiceError> {
+        self.payment_repository
+            .find_by_authorization_id(authorization_id.clone())
+            .await
+            .map_err(read_error)?
+            .ok_or_else(|| {
+                PaymentServiceError::ProviderError(PaymentGatewayError::AuthorizationNotFoundError(
+                    authorization_id.clone(),
+                ))
+            })
+    }
+
+    async fn find_order(&self, order_id: &OrderId) -> Result<Order, PaymentServiceError> {
+        self.order_repository
+            .find_by_id(order_id.clone())
+            .await
+            .map_err(|e| {
+                error!("Error reading order: {}", e);
+                PaymentServiceError::OrderNotReadError
+            })?
+            .ok_or_else(|| PaymentServiceError::OrderNotFoundError(order_id.clone()))
+    }
+
+    async fn begin_transaction(&mut self) -> Result<(), PaymentServiceError> {
+        self.payment_repository
+            .begin_transaction()
+            .await
+            .map_err(|e| PaymentServiceError::GenericError(e.to_string()))
+    }
+
+    async fn commit_transaction(&mut self) -> Result<(), PaymentServiceError> {
+        self.payment_repository
+            .commit_transaction()
+            .await
+            .map_err(|e| PaymentServiceError::GenericError(e.to_string()))
+    }
+
+    async fn rollback_transaction(&mut self) -> Result<(), PaymentServiceError> {
+        self.payment_repository
+            .rollback_transaction()
+            .await
+            .map_err(|e| PaymentServiceError::GenericError(e.to_string()))
+    }
+}
+
+/// The checkout reaches the payment provider through the service, so every
+/// payment is recorded.
+#[async_trait]
+impl PaymentGateway for PaymentService {
+    async fn authorize(
+        &mut self,
+        order_id: &OrderId,
+        amount: f64,
+    ) -> Result<AuthorizationId, PaymentGatewayError> {
+        self.try_authorize(order_id, amount)
+            .await
+            .map_err(gateway_error)
+    }
+
+    async fn capture(
+        &mut self,
+        authorization_id: &AuthorizationId,
+        amount: f64,
+    ) -> Result<(), PaymentGatewayError> {
+        self.try_capture(authorization_id, amount)
+            .await
+            .map_err(gateway_error)
+    }
+
+    async fn void(
+        &mut self,
+        authorization_id: &AuthorizationId,
+    ) -> Result<(), PaymentGatewayError> {
+        self.try_void(authorization_id).await.map_err(gateway_error)
+    }
+
+    async fn refund(
+        &mut self,
+        authorization_id: &AuthorizationId,
+        amount: f64,
+        idempotency_key: &str,
+    ) -> Result<(), PaymentGatewayError> {
+        self.try_refund(authorization_id, amount, idempotency_key)
+            .await
+            .map_err(gateway_error)
+    }
+}
+
+fn gateway_error(error: PaymentServiceError) -> PaymentGatewayError {
+    match error {
+        PaymentServiceError::ProviderError(error) => error,
+        PaymentServiceError::InvalidPaymentError(error) => {
+            PaymentGatewayError::PaymentRejectedError(error.to_string())
+        }
+        error => PaymentGatewayError::PaymentProviderUnavailableError(error.to_string()),
+    }
+}
+
+fn provider_error(error: PaymentGatewayError) -> PaymentServiceError {
+    error!("Error from the payment provider: {}", error);
+    PaymentServiceError::ProviderError(error)
+}
+
+fn invoice_error(error: InvoiceServiceError) -> PaymentServiceError {
+    error!("Error invoicing payment: {}", error);
+    PaymentServiceError::InvoiceError(error)
+}
+
+fn read_error(error: PaymentRepositoryError) -> PaymentServiceError {
+    error!("Error reading payment: {}", error);
+    PaymentServiceError::PaymentNotReadError
+}
+
+fn save_error(error: PaymentRepositoryError) -> PaymentServiceError {
+    error!("Error saving payment: {}", error);
+    match error {
+        PaymentRepositoryError::ConcurrencyConflict => {
+            PaymentServiceError::ConcurrencyConflictError
+        }
+        _ => PaymentServiceError::PaymentNotSavedError,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use crate::{
+        entities::{
+            invoice::InvoiceKind,
+            order::OrderStatus,
+            outbox::OutboxMessageType,
+            payment::{Payment, PaymentState},
+        },
+        fixtures::{order_repository, Stored},
+        gateways::payment_gateway::{
+            InMemoryPaymentGateway, MockPaymentGateway, PaymentGateway, PaymentGatewayError,
+        },
+        repositories::{
+            invoice_repository::{InMemoryInvoiceRepository, MockInvoiceRepository},
+            outbox_repository::{InMemoryOutboxMessageRepository, MockOutboxMessageRepository},
+            payment_repository::MockMyPaymentRepository,
+        },
+        services::invoice_service::InvoiceService,
+        value_objects::OrderId,
+    };
+
+    use super::PaymentService;
+
+    #[tokio::test]
+    async fn records_an_authorized_payment_for_a_pending_order() {
+        let order_id = OrderId(Uuid::new_v4());
+        let stored = Stored::default();
+        let provider = InMemoryPaymentGateway::new();
+        let mut service = PaymentService::new(
+            Box::new(payment_repository(stored.clone())),
+            Box::new(order_repository(OrderStatus::Pending)),
+            Box::new(MockOutboxMessageRepository::new()),
+            Box::new(provider.clone()),
+            InvoiceService::new(Box::new(MockInvoiceRepository::new())),
+        );
+
+        let authorization_id = service.authorize(&order_id, 10.0).await.unwrap();
+        let again = service.authorize(&order_id, 10.0).await.unwrap();
+
+        assert_eq!(authorization_id, again);
+        let payment = stored.get().unwrap();
+        assert_eq!(PaymentState::Authorized, payment.state());
+        assert_eq!(Some(&authorization_id), payment.authorization_id());
+        assert_eq!(Some(false), provider.is_voided(&order_id));
+    }
+
+    #[tokio::test]
+    async fn publishes_payment_failed_when_the_payment_is_declined() {
+        let order_id = OrderId(Uuid::new_v4());
+        let stored = Stored::default();
+        let outbox_message_repository = InMemoryOutboxMessageRepository::new();
+        let mut service = PaymentService::new(
+            Box::new(payment_repository(stored.clone())),
+            Box::new(order_repository(OrderStatus::Pending)),
+            Box::new(outbox_message_repository.clone()),
+            Box::new(InMemoryPaymentGateway::with_limit(5.0)),
+            InvoiceService::new(Box::new(MockInvoiceRepository::new())),
+        );
+
+        let result = service.authorize(&order_id, 10.0).await;
+
+        assert!(matches!(
+            result,
+            Err(PaymentGatewayError::PaymentDeclinedError(_))
+        ));
+        let payment = stored.get().unwrap();
+        assert_eq!(PaymentState::Failed, payment.state());
+        let messages = outbox_message_repository.messages();
+        assert_eq!(1, messages.len());
+        assert_eq!(OutboxMessageType::PaymentFailed, messages[0].event_type());
+    }
+
+    #[tokio::test]
+    async fn captures_only_payments_of_confirmed_orders() {
+        let order_id = OrderId(Uuid::new_v4());
+        let stored = Stored::default();
+        let provider = InMemoryPaymentGateway::new();
+        let invoice_repository = InMemoryInvoiceRepository::new();
+        let mut service = PaymentService::new(
+            Box::new(payment_repository(stored.clone())),
+            Box::new(order_repository(OrderStatus::Pending)),
+            Box::new(MockOutboxMessageRepository::new()),
+            Box::new(provider.clone()),
+            InvoiceService::new(Box::new(invoice_repository.clone())),
+        );
+        let authorization_id = service.authorize(&order_id, 35.5).await.unwrap();
+
+        assert!(matches!(
+            service.capture(&authorization_id, 35.5).await,
+            Err(PaymentGatewayError::PaymentRejectedError(_))
+        ));
+        service.order_repository = Box::new(order_repository(OrderStatus::Confirmed));
+        service.capture(&authorization_id, 35.5).await.unwrap();
+        service
+            .refund(&authorization_id, 4.0, "refund")
+            .await
+            .unwrap();
+        service
+            .refund(&authorization_id, 4.0, "refund")
+            .await
+            .unwrap();
+
+        assert_eq!(Some(35.5), provider.captured(&order_id));
+        assert_eq!(Some(4.0), provider.refunded(&order_id));
+        let payment = stored.get().unwrap();
+        assert_eq!(4.0, payment.refunded_amount());
+        let invoices = invoice_repository.invoices();
+        assert_eq!(2, invoices.len());
+        assert_eq!(InvoiceKind::Invoice, invoices[0].kind());
+        assert_eq!(35.5, invoices[0].totals().total);
+        assert_eq!(InvoiceKind::CreditNote, invoices[1].kind());
+        assert_eq!(4.0, invoices[1].totals().total);
+    }
+
+    #[tokio::test]
+    async fn leaves_the_payment_pending_when_the_provider_is_unavailable() {
+        let stored = Stored::default();
+        let mut provider = MockPaymentGateway::new();
+        provider.expect_authorize().times(1).returning(|_, _| {
+            Err(PaymentGatewayError::PaymentProviderUnavailableError(
+                "timeout".to_string(),
+            ))
+        });
+        let mut service = PaymentService::new(
+            Box::new(payment_repository(stored.clone())),
+            Box::new(order_repository(OrderStatus::Pending)),
+            Box::new(MockOutboxMessageRepository::new()),
+            Box::new(provider),
+            InvoiceService::new(Box::new(MockInvoiceRepository::new())),
+        );
+
+        let result = service.authorize(&OrderId(Uuid::new_v4()), 10.0).await;
+
+        assert!(matches!(
+            result,
+            Err(PaymentGatewayError::PaymentProviderUnavailableError(_))
+        ));
+        let payment = stored.get().unwrap();
+        assert_eq!(PaymentState::Pending, payment.state());
+    }
+
+    /// Keeps the last payment stored, bumping its version.
+    fn payment_repository(stored: Stored<Payment>) -> MockMyPaymentRepository {
+        let mut payment_repository = MockMyPaymentRepository::new();
+        let found = stored.clone();
+        payment_repository
+            .expect_find_by_order_id()
+            .returning(move |_| Ok(found.get()));
+        let found = stored.clone();
+        payment_repository
+            .expect_find_by_authorization_id()
+            .returning(move |_| Ok(found.get()));
+        let saved = stored.clone();
+        payment_repository
+            .expect_save()
+            .returning(move |payment| Ok(saved.save(payment)));
+        payment_repository
+            .expect_update()
+            .returning(move |payment| Ok(stored.update(payment)));
+        payment_repository
+            .expect_begin_transaction()
+            .returning(|| Ok(()));
+        payment_repository
+            .expect_commit_transaction()
+            .returning(|| Ok(()));
+        payment_repository
+            .expect_rollback_transaction()
+            .returning(|| Ok(()));
+        payment_repository
+    }
+}
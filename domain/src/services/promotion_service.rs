@@ -0,0 +1,460 @@
+use chrono::{DateTime, Utc};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    entities::{
+        order::Order,
+        outbox::OutboxMessage,
+        promotion::{Discount, Promotion, PromotionConditions, PromotionError},
+    },
+    repositories::{
+        order_repository::{OrderRepository, OrderRepositoryError},
+        outbox_repository::OutboxMessageRepository,
+        promotion_repository::{PromotionRepository, PromotionRepositoryError},
+    },
+    value_objects::{CouponCode, OrderId, ProductId, PromotionId},
+};
+
+#[derive(Debug)]
+pub enum PromotionServiceError {
+    PromotionNotFoundError,
+    PromotionNotReadError,
+    PromotionNotSavedError,
+    PromotionAlreadyExistsError,
+    OrderNotFoundError,
+    OrderNotReadError,
+    OrderNotSavedError,
+    ConcurrencyConflictError,
+    InvalidPromotionError(PromotionError),
+    GenericError(String),
+}
+
+impl std::fmt::Display for PromotionServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PromotionServiceError::PromotionNotFoundError => write!(f, "Promotion not found error"),
+            PromotionServiceError::PromotionNotReadError => write!(f, "Promotion not read error"),
+            PromotionServiceError::PromotionNotSavedError => {
+                write!(f, "Promotion not saved error")
+            }
+            PromotionServiceError::PromotionAlreadyExistsError => {
+                write!(f, "A promotion with the same id or code exists")
+            }
+            PromotionServiceError::OrderNotFoundError => write!(f, "Order not found error"),
+            PromotionServiceError::OrderNotReadError => write!(f, "Order not read error"),
+            PromotionServiceError::OrderNotSavedError => write!(f, "Order not saved error"),
+            PromotionServiceError::ConcurrencyConflictError => {
+                write!(f, "Order was modified concurrently")
+            }
+            PromotionServiceError::InvalidPromotionError(error) => {
+                write!(f, "Invalid promotion: {}", error)
+            }
+            PromotionServiceError::GenericError(error) => write!(f, "Generic error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for PromotionServiceError {}
+
+pub struct CreatePromotionRequestObject {
+    pub promotion_id: String,
+    pub code: String,
+    /// `percentage`, `fixed`, `buy_x_get_y` or `free_shipping`.
+    pub kind: String,
+    pub value: Option<f64>,
+    pub product_id: Option<String>,
+    pub buy: Option<i32>,
+    pub get: Option<i32>,
+    pub min_spend: Option<f64>,
+    /// RFC 3339 timestamps.
+    pub valid_from: Option<String>,
+    pub valid_until: Option<String>,
+    pub max_uses_per_customer: Option<i64>,
+}
+
+pub struct ApplyCouponRequestObject {
+    pub order_id: String,
+    pub code: String,
+    /// When set, the coupon is applied only if the order is still at this version.
+    pub expected_version: Option<i64>,
+}
+
+/// Creates promotions and applies them to orders by coupon code. Applying a
+/// coupon publishes `coupon_applied` with the new price of the order.
+pub struct PromotionService {
+    promotion_repository: Box<dyn PromotionRepository>,
+    order_repository: Box<dyn OrderRepository>,
+    outbox_message_repository: Box<dyn OutboxMessageRepository>,
+}
+
+impl PromotionService {
+    pub fn new(
+        promotion_repository: Box<dyn PromotionRepository>,
+        order_repository: Box<dyn OrderRepository>,
+        outbox_message_repository: Box<dyn OutboxMessageRepository>,
+    ) -> Self {
+        Self {
+            promotion_repository,
+            order_repository,
+            outbox_message_repository,
+        }
+    }
+
+    pub async fn create_promotion(
+        &self,
+        request: CreatePromotionRequestObject,
+    ) -> Result<Promotion, PromotionServiceError> {
+        let promotion_id = Uuid::try_parse(&request.promotion_id)
+            .map_err(|err| PromotionServiceError::GenericError(err.to_string()))?;
+        let product_id = request
+            .product_id
+            .as_deref()
+            .map(Uuid::try_parse)
+            .transpose()
+            .map_err(|err| PromotionServiceError::GenericError(err.to_string()))?
+            .map(ProductId);
+
+        info!("Creating promotion");
+
+        let discount = Discount::parse(
+            &request.kind,
+            request.value,
+            product_id,
+            request.buy,
+            request.get,
+        )
+        .map_err(PromotionServiceError::InvalidPromotionError)?;
+        let promotion = Promotion::new(
+            PromotionId(promotion_id),
+            parse_code(&request.code)?,
+            discount,
+            PromotionConditions {
+                min_spend: request.min_spend,
+                valid_from: parse_time(request.valid_from.as_deref())?,
+                valid_until: parse_time(request.valid_until.as_deref())?,
+                max_uses_per_customer: request.max_uses_per_customer,
+            },
+        )
+        .map_err(PromotionServiceError::InvalidPromotionError)?;
+
+        self.promotion_repository
+            .save(promotion)
+            .await
+            .map_err(|e| {
+                error!("Error saving promotion: {}", e);
+                match e {
+                    PromotionRepositoryError::PromotionAlreadyExistsError => {
+                        PromotionServiceError::PromotionAlreadyExistsError
+                    }
+                    _ => PromotionServiceError::PromotionNotSavedError,
+                }
+            })
+    }
+
+    pub async fn find_promotion(&self, code: &str) -> Result<Promotion, PromotionServiceError> {
+        let code = parse_code(code)?;
+        match self.promotion_repository.find_by_code(code).await {
+            Ok(Some(promotion)) => Ok(promotion),
+            Ok(None) => {
+                error!("Promotion not found");
+                Err(PromotionServiceError::PromotionNotFoundError)
+            }
+            Err(e) => {
+                error!("Error reading promotion: {}", e);
+                Err(PromotionServiceError::PromotionNotReadError)
+            }
+        }
+    }
+
+    /// Applies the promotion of the coupon to a pending order, if the
+    /// promotion's conditions are met for the order and its customer.
+    pub async fn apply_coupon(
+        &mut self,
+        request: ApplyCouponRequestObject,
+    ) -> Result<Order, PromotionServiceError> {
+        let order_id = Uuid::try_parse(&request.order_id)
+            .map_err(|err| PromotionServiceError::GenericError(err.to_string()))?;
+
+        info!("Applying coupon to order");
+
+        let mut order = self
+            .find_order(OrderId(order_id), request.expected_version)
+            .await?;
+        let promotion = self.find_promotion(&request.code).await?;
+        let uses = self
+            .promotion_repository
+            .count_redemptions(promotion.id().clone(), order.customer_id().clone())
+            .await
+            .map_err(|e| {
+                error!("Error reading redemptions: {}", e);
+                PromotionServiceError::PromotionNotReadError
+            })?;
+        promotion
+            .apply_to(&mut order, Utc::now(), uses)
+            .map_err(PromotionServiceError::InvalidPromotionError)?;
+
+        self.promotion_repository
+            .redeem(&promotion, order.customer_id().clone(), order.id().clone())
+            .await
+            .map_err(|e| {
+                error!("Error redeeming promotion: {}", e);
+                match e {
+                    PromotionRepositoryError::UsageLimitReachedError => {
+                        PromotionServiceError::InvalidPromotionError(
+                            PromotionError::UsageLimitReachedError,
+                        )
+                    }
+                    _ => PromotionServiceError::PromotionNotSavedError,
+                }
+            })?;
+        match self.store(order).await {
+            Ok(order) => Ok(order),
+            Err(e) => {
+                if let Err(e) = self
+                    .promotion_repository
+                    .release(promotion.id().clone(), OrderId(order_id))
+                    .await
+                {
+                    error!("Error releasing promotion: {}", e);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Updates the order and publishes `coupon_applied` for its last promotion.
+    async fn store(&mut self, order: Order) -> Result<Order, PromotionServiceError> {
+        let message = order
+            .promotions()
+            .last()
+            .map(|promotion| OutboxMessage::coupon_applied_event(&order, promotion))
+            .transpose()
+            .map_err(|e| PromotionServiceError::GenericError(e.to_string()))?;
+
+        self.begin_transaction().await?;
+        let order = match self.order_repository.update(order).await {
+            Ok(order) => order,
+            Err(e) => {
+                error!("Error saving order: {}", e);
+                self.rollback_transaction().await?;
+                return Err(match e {
+                    OrderRepositoryError::ConcurrencyConflict => {
+                        PromotionServiceError::ConcurrencyConflictError
+                    }
+                    OrderRepositoryError::OrderNotFoundError => {
+                        PromotionServiceError::OrderNotFoundError
+                    }
+                    _ => PromotionServiceError::OrderNotSavedError,
+                });
+            }
+        };
+        if let Some(message) = message {
+            if let Err(e) = self.outbox_message_repository.save(message).await {
+                error!("Error saving outbox message: {}", e);
+                self.rollback_transaction().await?;
+                return Err(PromotionServiceError::GenericError(
+                    "Outbox message not saved".to_string(),
+                ));
+            }
+        }
+        self.commit_transaction().await?;
+        Ok(order)
+    }
+
+    async fn find_order(
+        &self,
+        order_id: OrderId,
+        expected_version: Option<i64>,
+    ) -> Result<Order, PromotionServiceError> {
+        let order = match self.order_repository.find_by_id(order_id).await {
+            Ok(Some(order)) => order,
+            Ok(None) | Err(OrderRepositoryError::OrderNotFoundError) => {
+                error!("Order not found");
+                return Err(PromotionServiceError::OrderNotFoundError);
+            }
+            Err(e) => {
+                error!("Error reading order: {}", e);
+                return Err(PromotionServiceError::OrderNotReadError);
+            }
+        };
+        if expected_version.is_some_and(|version| version != order.version()) {
+            error!("Order version does not match the expected one");
+            return Err(PromotionServiceError::ConcurrencyConflictError);
+        }
+        Ok(order)
+    }
+
+    async fn begin_transaction(&mut self) -> Result<(), PromotionServiceError> {
+        self.order_repository
+            .begin_transaction()
+            .await
+            .map_err(|e| PromotionServiceError::GenericError(e.to_string()))
+    }
+
+    async fn commit_transaction(&mut self) -> Result<(), PromotionServiceError> {
+        self.order_repository
+            .commit_transaction()
+            .await
+            .map_err(|e| PromotionServiceError::GenericError(e.to_string()))
+    }
+
+    async fn rollback_transaction(&mut self) -> Result<(), PromotionServiceError> {
+        self.order_repository
+            .rollback_transaction()
+            .await
+            .map_err(|e| PromotionServiceError::GenericError(e.to_string()))
+    }
+}
+
+fn parse_code(code: &str) -> Result<CouponCode, PromotionServiceError> {
+    CouponCode::parse(code).map_err(|e| {
+        PromotionServiceError::InvalidPromotionError(PromotionError::InvalidCodeError(e))
+    })
+}
+
+fn parse_time(time: Option<&str>) -> Result<Option<DateTime<Utc>>, PromotionServiceError> {
+    time.map(|time| {
+        DateTime::parse_from_rfc3339(time)
+            .map(|time| time.with_timezone(&Utc))
+            .map_err(|e| {
+                PromotionServiceError::InvalidPromotionError(
+                    PromotionError::InvalidConditionsError(e.to_string()),
+                )
+            })
+    })
+    .transpose()
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use crate::{
+        entities::{
+            order::Order,
+            outbox::OutboxMessageType,
+            promotion::{Discount, Promotion, PromotionConditions, PromotionError},
+        },
+        repositories::{
+            order_repository::MockMyOrderRepository,
+            outbox_repository::InMemoryOutboxMessageRepository,
+            promotion_repository::{MockPromotionRepository, PromotionRepositoryError},
+        },
+        value_objects::{CouponCode, CustomerId, OrderId, OrderItem, ProductId, PromotionId},
+    };
+
+    use super::{ApplyCouponRequestObject, PromotionService, PromotionServiceError};
+
+    const ORDER_ID: &str = "5b0f6a8e-2c4d-4e1f-9a3b-7c8d9e0f1a2b";
+
+    #[tokio::test]
+    async fn applies_a_coupon_to_an_order() {
+        let outbox_message_repository = InMemoryOutboxMessageRepository::new();
+        let mut promotion_repository = promotion_repository(None);
+        promotion_repository
+            .expect_redeem()
+            .returning(|_, _, _| Ok(()));
+        let mut service = PromotionService::new(
+            Box::new(promotion_repository),
+            Box::new(order_repository()),
+            Box::new(outbox_message_repository.clone()),
+        );
+
+        let order = service.apply_coupon(request()).await.unwrap();
+
+        assert_eq!(18.0, order.total_price());
+        let messages = outbox_message_repository.messages();
+        assert_eq!(OutboxMessageType::CouponApplied, messages[0].event_type());
+        assert!(messages[0]
+            .event_payload()
+            .contains(r#""discounts":[{"code":"TEN","amount":2.0}]"#));
+    }
+
+    #[tokio::test]
+    async fn does_not_apply_a_coupon_used_up_by_the_customer() {
+        let mut promotion_repository = promotion_repository(Some(1));
+        promotion_repository
+            .expect_redeem()
+            .returning(|_, _, _| Err(PromotionRepositoryError::UsageLimitReachedError));
+        let mut service = PromotionService::new(
+            Box::new(promotion_repository),
+            Box::new(order_repository()),
+            Box::new(InMemoryOutboxMessageRepository::new()),
+        );
+
+        let result = service.apply_coupon(request()).await;
+
+        assert!(matches!(
+            result,
+            Err(PromotionServiceError::InvalidPromotionError(
+                PromotionError::UsageLimitReachedError
+            ))
+        ));
+    }
+
+    /// Has the `TEN` promotion, at most `max_uses` times per customer, never
+    /// used so far.
+    fn promotion_repository(max_uses: Option<i64>) -> MockPromotionRepository {
+        let mut promotion_repository = MockPromotionRepository::new();
+        promotion_repository
+            .expect_find_by_code()
+            .returning(move |code| {
+                Ok(Some(
+                    Promotion::new(
+                        PromotionId(Uuid::new_v4()),
+                        code,
+                        Discount::Percentage(10.0),
+                        PromotionConditions {
+                            max_uses_per_customer: max_uses,
+                            ..Default::default()
+                        },
+                    )
+                    .unwrap(),
+                ))
+            });
+        promotion_repository
+            .expect_count_redemptions()
+            .returning(|_, _| Ok(0));
+        promotion_repository
+    }
+
+    fn order_repository() -> MockMyOrderRepository {
+        let mut order_repository = MockMyOrderRepository::new();
+        order_repository.expect_find_by_id().returning(|_| {
+            let mut order = Order::create(
+                OrderId(Uuid::try_parse(ORDER_ID).unwrap()),
+                CustomerId(Uuid::new_v4()),
+            );
+            order
+                .add(OrderItem {
+                    price: 10.0,
+                    quantity: 2,
+                    product_id: ProductId(Uuid::new_v4()),
+                })
+                .unwrap();
+            Ok(Some(order.with_version(1)))
+        });
+        order_repository
+            .expect_update()
+            .returning(|order| Ok(order.with_version(2)));
+        order_repository
+            .expect_begin_transaction()
+            .returning(|| Ok(()));
+        order_repository
+            .expect_commit_transaction()
+            .returning(|| Ok(()));
+        order_repository
+            .expect_rollback_transaction()
+            .returning(|| Ok(()));
+        order_repository
+    }
+
+    fn request() -> ApplyCouponRequestObject {
+        ApplyCouponRequestObject {
+            order_id: ORDER_ID.to_string(),
+            code: CouponCode::parse("ten").unwrap().to_string(),
+            expected_version: Some(1),
+        }
+    }
+}
@@ -0,0 +1,602 @@
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    entities::{
+        order::Order,
+        outbox::OutboxMessage,
+        returns::{Return, ReturnError},
+        shipment::Shipment,
+    },
+    gateways::payment_gateway::{PaymentGateway, PaymentGatewayError},
+    repositories::{
+        order_repository::OrderRepository,
+        outbox_repository::OutboxMessageRepository,
+        payment_repository::PaymentRepository,
+        return_repository::{ReturnRepository, ReturnRepositoryError},
+        shipment_repository::ShipmentRepository,
+    },
+    value_objects::{OrderId, ProductId, ReturnId},
+};
+
+#[derive(Debug)]
+pub enum ReturnServiceError {
+    ReturnNotFoundError,
+    ReturnNotReadError,
+    ReturnNotSavedError,
+    OrderNotFoundError,
+    OrderNotReadError,
+    ShipmentNotReadError,
+    PaymentNotFoundError,
+    ConcurrencyConflictError,
+    InvalidReturnError(ReturnError),
+    PaymentError(PaymentGatewayError),
+    GenericError(String),
+}
+
+impl std::fmt::Display for ReturnServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReturnServiceError::ReturnNotFoundError => write!(f, "Return not found error"),
+            ReturnServiceError::ReturnNotReadError => write!(f, "Return not read error"),
+            ReturnServiceError::ReturnNotSavedError => write!(f, "Return not saved error"),
+            ReturnServiceError::OrderNotFoundError => write!(f, "Order not found error"),
+            ReturnServiceError::OrderNotReadError => write!(f, "Order not read error"),
+            ReturnServiceError::ShipmentNotReadError => write!(f, "Shipment not read error"),
+            ReturnServiceError::PaymentNotFoundError => {
+                write!(f, "No captured payment for the order")
+            }
+            ReturnServiceError::ConcurrencyConflictError => {
+                write!(f, "Return was modified concurrently")
+            }
+            ReturnServiceError::InvalidReturnError(error) => {
+                write!(f, "Invalid return: {}", error)
+            }
+            ReturnServiceError::PaymentError(error) => write!(f, "Refund failed: {}", error),
+            ReturnServiceError::GenericError(error) => write!(f, "Generic error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ReturnServiceError {}
+
+pub struct RequestReturnRequestObject {
+    pub return_id: String,
+    pub order_id: String,
+    pub product_id: String,
+    pub quantity: i32,
+    pub reason: String,
+}
+
+pub struct AddReturnItemRequestObject {
+    pub return_id: String,
+    pub product_id: String,
+    pub quantity: i32,
+    /// When set, the item is added only if the return is still at this version.
+    pub expected_version: Option<i64>,
+}
+
+/// Takes returns from request to refund. Every change of state publishes a
+/// `return_*` event; the refund goes through the payment gateway, once per
+/// return. Returns are requested once the order is delivered.
+pub struct ReturnService {
+    return_repository: Box<dyn ReturnRepository>,
+    order_repository: Box<dyn OrderRepository>,
+    shipment_repository: Box<dyn ShipmentRepository>,
+    payment_repository: Box<dyn PaymentRepository>,
+    outbox_message_repository: Box<dyn OutboxMessageRepository>,
+    payment_gateway: Box<dyn PaymentGateway>,
+}
+
+impl ReturnService {
+    pub fn new(
+        return_repository: Box<dyn ReturnRepository>,
+        order_repository: Box<dyn OrderRepository>,
+        shipment_repository: Box<dyn ShipmentRepository>,
+        payment_repository: Box<dyn PaymentRepository>,
+        outbox_message_repository: Box<dyn OutboxMessageRepository>,
+        payment_gateway: Box<dyn PaymentGateway>,
+    ) -> Self {
+        Self {
+            return_repository,
+            order_repository,
+            shipment_repository,
+            payment_repository,
+            outbox_message_repository,
+            payment_gateway,
+        }
+    }
+
+    pub async fn find_return(&self, return_id: &str) -> Result<Return, ReturnServiceError> {
+        self.get_return(return_id, None).await
+    }
+
+    /// Starts a return with a first item of the order.
+    pub async fn request_return(
+        &mut self,
+        request: RequestReturnRequestObject,
+    ) -> Result<Return, ReturnServiceError> {
+        let return_id = Uuid::try_parse(&request.return_id)
+            .map(ReturnId)
+            .map_err(|err| ReturnServiceError::GenericError(err.to_string()))?;
+        let order_id = Uuid::try_parse(&request.order_id)
+            .map(OrderId)
+            .map_err(|err| ReturnServiceError::GenericError(err.to_string()))?;
+        let product_id = parse_product_id(&request.product_id)?;
+
+        info!("Requesting return");
+
+        let order = self.find_order(&order_id).await?;
+        let shipments = self.find_shipments_of(&order_id).await?;
+        let other_returns = self.find_returns_of(&order_id).await?;
+        let mut order_return = Return::request(return_id, &order, &shipments, &request.reason)
+            .map_err(ReturnServiceError::InvalidReturnError)?;
+        order_return
+            .add(&order, &other_returns, product_id, request.quantity)
+            .map_err(ReturnServiceError::InvalidReturnError)?;
+
+        let message = return_event(&order_return)?;
+        self.begin_transaction().await?;
+        let order_return = match self
+            .return_repository
+            .save(order_return, order.version())
+            .await
+        {
+            Ok(order_return) => order_return,
+            Err(e) => {
+                self.rollback_transaction().await?;
+                return Err(save_error(e));
+            }
+        };
+        self.save_message(message).await?;
+        self.commit_transaction().await?;
+        Ok(order_return)
+    }
+
+    pub async fn add_item(
+        &mut self,
+        request: AddReturnItemRequestObject,
+    ) -> Result<Return, ReturnServiceError> {
+        let product_id = parse_product_id(&request.product_id)?;
+
+        info!("Adding item to return");
+
+        let mut order_return = self
+            .get_return(&request.return_id, request.expected_version)
+            .await?;
+        let order = self.find_order(order_return.order_id()).await?;
+        let other_returns = self.find_returns_of(order_return.order_id()).await?;
+        order_return
+            .add(&order, &other_returns, product_id, request.quantity)
+            .map_err(ReturnServiceError::InvalidReturnError)?;
+        self.return_repository
+            .update_items(order_return, order.version())
+            .await
+            .map_err(save_error)
+    }
+
+    pub async fn approve(
+        &mut self,
+        return_id: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Return, ReturnServiceError> {
+        info!("Approving return");
+
+        let mut order_return = self.get_return(return_id, expected_version).await?;
+        order_return
+            .approve()
+            .map_err(ReturnServiceError::InvalidReturnError)?;
+        self.store(order_return).await
+    }
+
+    pub async fn reject(
+        &mut self,
+        return_id: &str,
+        reason: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Return, ReturnServiceError> {
+        info!("Rejecting return");
+
+        let mut order_return = self.get_return(return_id, expected_version).await?;
+        order_return
+            .reject(reason)
+            .map_err(ReturnServiceError::InvalidReturnError)?;
+        self.store(order_return).await
+    }
+
+    /// Called by the warehouse once the items are back.
+    pub async fn receive(
+        &mut self,
+        return_id: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Return, ReturnServiceError> {
+        info!("Receiving return");
+
+        let mut order_return = self.get_return(return_id, expected_version).await?;
+        order_return
+            .receive()
+            .map_err(ReturnServiceError::InvalidReturnError)?;
+        self.store(order_return).await
+    }
+
+    /// Gives the refund amount back on the payment of the order. The return
+    /// id is the idempotency key of the refund, so a refund interrupted
+    /// before the return is stored is not made twice when attempted again.
+    pub async fn refund(
+        &mut self,
+        return_id: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Return, ReturnServiceError> {
+        info!("Refunding return");
+
+        let mut order_return = self.get_return(return_id, expected_version).await?;
+        order_return
+            .refunded()
+            .map_err(ReturnServiceError::InvalidReturnError)?;
+
+        let payment = self
+            .payment_repository
+            .find_by_order_id(order_return.order_id().clone())
+            .await
+            .map_err(|e| {
+                error!("Error reading payment: {}", e);
+                ReturnServiceError::GenericError("Payment not read".to_string())
+            })?;
+        let Some(authorization_id) = payment
+            .as_ref()
+            .and_then(|payment| payment.authorization_id())
+        else {
+            error!("No payment for order {}", order_return.order_id().0);
+            return Err(ReturnServiceError::PaymentNotFoundError);
+        };
+        let idempotency_key = format!("return_{}", order_return.id().0);
+        self.payment_gateway
+            .refund(
+                authorization_id,
+                order_return.refund_amount(),
+                &idempotency_key,
+            )
+            .await
+            .map_err(|e| {
+                error!("Error refunding return: {}", e);
+                ReturnServiceError::PaymentError(e)
+            })?;
+
+        self.store(order_return).await
+    }
+
+    /// Updates the return and publishes the event of its new state.
+    async fn store(&mut self, order_return: Return) -> Result<Return, ReturnServiceError> {
+        let message = return_event(&order_return)?;
+        self.begin_transaction().await?;
+        let order_return = match self.return_repository.update(order_return).await {
+            Ok(order_return) => order_return,
+            Err(e) => {
+                self.rollback_transaction().await?;
+                return Err(save_error(e));
+            }
+        };
+        self.save_message(message).await?;
+        self.commit_transaction().await?;
+        Ok(order_return)
+    }
+
+    async fn save_message(&mut self, message: OutboxMessage) -> Result<(), ReturnServiceError> {
+        if let Err(e) = self.outbox_message_repository.save(message).await {
+            error!("Error saving outbox message: {}", e);
+            self.rollback_transaction().await?;
+            return Err(ReturnServiceError::GenericError(
+                "Outbox message not saved".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn get_return(
+        &self,
+        return_id: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Return, ReturnServiceError> {
+        let return_id = Uuid::try_parse(return_id)
+            .map_err(|err| ReturnServiceError::GenericError(err.to_string()))?;
+        let order_return = match self.return_repository.find_by_id(ReturnId(return_id)).await {
+            Ok(Some(order_return)) => order_return,
+            Ok(None) => {
+                error!("Return not found");
+                return Err(ReturnServiceError::ReturnNotFoundError);
+            }
+            Err(e) => {
+                error!("Error reading return: {}", e);
+                return Err(ReturnServiceError::ReturnNotReadError);
+            }
+        };
+        if expected_version.is_some_and(|version| version != order_return.version()) {
+            error!("Return version does not match the expected one");
+            return Err(ReturnServiceError::ConcurrencyConflictError);
+        }
+        Ok(order_return)
+    }
+
+    async fn find_returns_of(&self, order_id: &OrderId) -> Result<Vec<Return>, ReturnServiceError> {
+        self.return_repository
+            .find_by_order_id(order_id.clone())
+            .await
+            .map_err(|e| {
+                error!("Error reading returns: {}", e);
+                ReturnServiceError::ReturnNotReadError
+            })
+    }
+
+    async fn find_shipments_of(
+        &self,
+        order_id: &OrderId,
+    ) -> Result<Vec<Shipment>, ReturnServiceError> {
+        self.shipment_repository
+            .find_by_order_id(order_id.clone())
+            .await
+            .map_err(|e| {
+                error!("Error reading shipments: {}", e);
+                ReturnServiceError::ShipmentNotReadError
+            })
+    }
+
+    async fn find_order(&self, order_id: &OrderId) -> Result<Order, ReturnServiceError> {
+        match self.order_repository.find_by_id(order_id.clone()).await {
+            Ok(Some(order)) => Ok(order),
+            Ok(None) => {
+                error!("Order not found");
+                Err(ReturnServiceError::OrderNotFoundError)
+            }
+            Err(e) => {
+                error!("Error reading order: {}", e);
+                Err(ReturnServiceError::OrderNotReadError)
+            }
+        }
+    }
+
+    async fn begin_transaction(&mut self) -> Result<(), ReturnServiceError> {
+        self.return_repository
+            .begin_transaction()
+            .await
+            .map_err(|e| ReturnServiceError::GenericError(e.to_string()))
+    }
+
+    async fn commit_transaction(&mut self) -> Result<(), ReturnServiceError> {
+        self.return_repository
+            .commit_transaction()
+            .await
+            .map_err(|e| ReturnServiceError::GenericError(e.to_string()))
+    }
+
+    async fn rollback_transaction(&mut self) -> Result<(), ReturnServiceError> {
+        self.return_repository
+            .rollback_transaction()
+            .await
+            .map_err(|e| ReturnServiceError::GenericError(e.to_string()))
+    }
+}
+
+fn return_event(order_return: &Return) -> Result<OutboxMessage, ReturnServiceError> {
+    OutboxMessage::return_event(order_return)
+        .map_err(|e| ReturnServiceError::GenericError(e.to_string()))
+}
+
+fn save_error(error: ReturnRepositoryError) -> ReturnServiceError {
+    error!("Error saving return: {}", error);
+    match error {
+        ReturnRepositoryError::ConcurrencyConflict => ReturnServiceError::ConcurrencyConflictError,
+        _ => ReturnServiceError::ReturnNotSavedError,
+    }
+}
+
+fn parse_product_id(product_id: &str) -> Result<ProductId, ReturnServiceError> {
+    Uuid::try_parse(product_id)
+        .map(ProductId)
+        .map_err(|err| ReturnServiceError::GenericError(err.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use crate::{
+        entities::{
+            order::OrderStatus,
+            outbox::OutboxMessageType,
+            payment::Payment,
+            returns::{Return, ReturnError, ReturnState},
+            shipment::ShipmentState,
+        },
+        fixtures::{order, order_id, order_repository, product_id, shipment, Stored, ORDER_ID},
+        gateways::payment_gateway::{InMemoryPaymentGateway, PaymentGateway},
+        repositories::{
+            outbox_repository::InMemoryOutboxMessageRepository,
+            payment_repository::MockMyPaymentRepository, return_repository::MockMyReturnRepository,
+            shipment_repository::MockMyShipmentRepository,
+        },
+        value_objects::PaymentId,
+    };
+
+    use super::{RequestReturnRequestObject, ReturnService, ReturnServiceError};
+
+    #[tokio::test]
+    async fn requests_a_return_of_items_of_a_confirmed_order() {
+        let stored = Stored::default();
+        let outbox_message_repository = InMemoryOutboxMessageRepository::new();
+        let mut service = ReturnService::new(
+            Box::new(return_repository(stored.clone())),
+            Box::new(order_repository(OrderStatus::Confirmed)),
+            Box::new(shipment_repository(ShipmentState::Delivered)),
+            Box::new(MockMyPaymentRepository::new()),
+            Box::new(outbox_message_repository.clone()),
+            Box::new(InMemoryPaymentGateway::new()),
+        );
+
+        let order_return = service.request_return(request(2)).await.unwrap();
+
+        assert_eq!(ReturnState::Requested, order_return.state());
+        assert_eq!(20.0, order_return.refund_amount());
+        assert_eq!(
+            OutboxMessageType::ReturnRequested,
+            outbox_message_repository.messages()[0].event_type()
+        );
+    }
+
+    #[tokio::test]
+    async fn cannot_return_items_of_a_pending_order() {
+        let mut service = ReturnService::new(
+            Box::new(return_repository(Stored::default())),
+            Box::new(order_repository(OrderStatus::Pending)),
+            Box::new(shipment_repository(ShipmentState::Delivered)),
+            Box::new(MockMyPaymentRepository::new()),
+            Box::new(InMemoryOutboxMessageRepository::new()),
+            Box::new(InMemoryPaymentGateway::new()),
+        );
+
+        let result = service.request_return(request(1)).await;
+
+        assert!(matches!(
+            result,
+            Err(ReturnServiceError::InvalidReturnError(
+                ReturnError::OrderNotConfirmedError(OrderStatus::Pending)
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn cannot_return_items_not_delivered_yet() {
+        let mut service = ReturnService::new(
+            Box::new(return_repository(Stored::default())),
+            Box::new(order_repository(OrderStatus::Confirmed)),
+            Box::new(shipment_repository(ShipmentState::Shipped)),
+            Box::new(MockMyPaymentRepository::new()),
+            Box::new(InMemoryOutboxMessageRepository::new()),
+            Box::new(InMemoryPaymentGateway::new()),
+        );
+
+        let result = service.request_return(request(1)).await;
+
+        assert!(matches!(
+            result,
+            Err(ReturnServiceError::InvalidReturnError(
+                ReturnError::OrderNotDeliveredError
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn refunds_a_received_return_on_the_payment_of_the_order() {
+        let order_id = order_id();
+        let mut provider = InMemoryPaymentGateway::new();
+        let authorization_id = provider.authorize(&order_id, 30.0).await.unwrap();
+        provider.capture(&authorization_id, 30.0).await.unwrap();
+        let mut payment = Payment::new(
+            PaymentId(Uuid::new_v4()),
+            &order(OrderStatus::Pending),
+            30.0,
+        )
+        .unwrap();
+        payment.authorized(authorization_id).unwrap();
+        let mut payment_repository = MockMyPaymentRepository::new();
+        payment_repository
+            .expect_find_by_order_id()
+            .returning(move |_| Ok(Some(payment.clone())));
+        let stored = Stored::default();
+        let outbox_message_repository = InMemoryOutboxMessageRepository::new();
+        let mut service = ReturnService::new(
+            Box::new(return_repository(stored.clone())),
+            Box::new(order_repository(OrderStatus::Confirmed)),
+            Box::new(shipment_repository(ShipmentState::Delivered)),
+            Box::new(payment_repository),
+            Box::new(outbox_message_repository.clone()),
+            Box::new(provider.clone()),
+        );
+        let return_id = service
+            .request_return(request(2))
+            .await
+            .unwrap()
+            .id()
+            .0
+            .to_string();
+        assert!(matches!(
+            service.refund(&return_id, None).await,
+            Err(ReturnServiceError::InvalidReturnError(
+                ReturnError::InvalidStateError(ReturnState::Requested)
+            ))
+        ));
+
+        service.approve(&return_id, Some(1)).await.unwrap();
+        service.receive(&return_id, Some(2)).await.unwrap();
+        let order_return = service.refund(&return_id, Some(3)).await.unwrap();
+
+        assert_eq!(ReturnState::Refunded, order_return.state());
+        assert_eq!(Some(20.0), provider.refunded(&order_id));
+        let event_types: Vec<OutboxMessageType> = outbox_message_repository
+            .messages()
+            .iter()
+            .map(|message| message.event_type())
+            .collect();
+        assert_eq!(
+            vec![
+                OutboxMessageType::ReturnRequested,
+                OutboxMessageType::ReturnApproved,
+                OutboxMessageType::ReturnReceived,
+                OutboxMessageType::ReturnRefunded,
+            ],
+            event_types
+        );
+    }
+
+    /// Keeps the last return stored, bumping its version.
+    fn return_repository(stored: Stored<Return>) -> MockMyReturnRepository {
+        let mut return_repository = MockMyReturnRepository::new();
+        let found = stored.clone();
+        return_repository
+            .expect_find_by_id()
+            .returning(move |_| Ok(found.get()));
+        let found = stored.clone();
+        return_repository
+            .expect_find_by_order_id()
+            .returning(move |_| Ok(found.get().into_iter().collect()));
+        let saved = stored.clone();
+        return_repository
+            .expect_save()
+            .returning(move |order_return, _| Ok(saved.save(order_return)));
+        let updated = stored.clone();
+        return_repository
+            .expect_update()
+            .returning(move |order_return| Ok(updated.update(order_return)));
+        return_repository
+            .expect_update_items()
+            .returning(move |order_return, _| Ok(stored.update(order_return)));
+        return_repository
+            .expect_begin_transaction()
+            .returning(|| Ok(()));
+        return_repository
+            .expect_commit_transaction()
+            .returning(|| Ok(()));
+        return_repository
+            .expect_rollback_transaction()
+            .returning(|| Ok(()));
+        return_repository
+    }
+
+    /// The order has a single shipment, in the state.
+    fn shipment_repository(state: ShipmentState) -> MockMyShipmentRepository {
+        let mut shipment_repository = MockMyShipmentRepository::new();
+        shipment_repository
+            .expect_find_by_order_id()
+            .returning(move |order_id| Ok(vec![shipment(order_id, state)]));
+        shipment_repository
+    }
+
+    fn request(quantity: i32) -> RequestReturnRequestObject {
+        RequestReturnRequestObject {
+            return_id: Uuid::new_v4().to_string(),
+            order_id: ORDER_ID.to_string(),
+            product_id: product_id(1).0.to_string(),
+            quantity,
+            reason: "Wrong size".to_string(),
+        }
+    }
+}
@@ -0,0 +1,464 @@
+use chrono::Utc;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    entities::{
+        outbox::OutboxMessage,
+        product::Product,
+        review::{Rating, Review, ReviewError, ReviewStatus},
+    },
+    repositories::{
+        catalog_repository::CatalogRepository,
+        customer_repository::CustomerRepository,
+        order_repository::OrderRepository,
+        outbox_repository::OutboxMessageRepository,
+        review_repository::{ReviewRepository, ReviewRepositoryError},
+    },
+    value_objects::{CatalogProductId, CustomerId, ReviewId},
+};
+
+#[derive(Debug)]
+pub enum ReviewServiceError {
+    ReviewNotFoundError,
+    ReviewNotReadError,
+    ReviewNotSavedError,
+    ReviewAlreadyExistsError,
+    ProductAlreadyReviewedError,
+    ProductNotFoundError,
+    ProductNotReadError,
+    CustomerNotFoundError,
+    CustomerNotReadError,
+    OrderNotReadError,
+    ConcurrencyConflictError,
+    InvalidReviewError(ReviewError),
+    GenericError(String),
+}
+
+impl std::fmt::Display for ReviewServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReviewServiceError::ReviewNotFoundError => write!(f, "Review not found error"),
+            ReviewServiceError::ReviewNotReadError => write!(f, "Review not read error"),
+            ReviewServiceError::ReviewNotSavedError => write!(f, "Review not saved error"),
+            ReviewServiceError::ReviewAlreadyExistsError => {
+                write!(f, "A review with the same id exists")
+            }
+            ReviewServiceError::ProductAlreadyReviewedError => {
+                write!(f, "The customer has already reviewed the product")
+            }
+            ReviewServiceError::ProductNotFoundError => write!(f, "Product not found error"),
+            ReviewServiceError::ProductNotReadError => write!(f, "Product not read error"),
+            ReviewServiceError::CustomerNotFoundError => write!(f, "Customer not found error"),
+            ReviewServiceError::CustomerNotReadError => write!(f, "Customer not read error"),
+            ReviewServiceError::OrderNotReadError => write!(f, "Order not read error"),
+            ReviewServiceError::ConcurrencyConflictError => {
+                write!(f, "Review was modified concurrently")
+            }
+            ReviewServiceError::InvalidReviewError(error) => {
+                write!(f, "Invalid review: {}", error)
+            }
+            ReviewServiceError::GenericError(error) => write!(f, "Generic error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ReviewServiceError {}
+
+pub struct SubmitReviewRequestObject {
+    pub review_id: String,
+    pub product_id: String,
+    pub customer_id: String,
+    pub rating: i32,
+    pub text: String,
+}
+
+/// Collects the reviews customers write of catalog products. Reviews are
+/// shown, and counted in the rating of the product, once approved by a
+/// moderator. Submitting one publishes `review_submitted`.
+pub struct ReviewService {
+    review_repository: Box<dyn ReviewRepository>,
+    catalog_repository: Box<dyn CatalogRepository>,
+    customer_repository: Box<dyn CustomerRepository>,
+    order_repository: Box<dyn OrderRepository>,
+    outbox_message_repository: Box<dyn OutboxMessageRepository>,
+}
+
+impl ReviewService {
+    pub fn new(
+        review_repository: Box<dyn ReviewRepository>,
+        catalog_repository: Box<dyn CatalogRepository>,
+        customer_repository: Box<dyn CustomerRepository>,
+        order_repository: Box<dyn OrderRepository>,
+        outbox_message_repository: Box<dyn OutboxMessageRepository>,
+    ) -> Self {
+        Self {
+            review_repository,
+            catalog_repository,
+            customer_repository,
+            order_repository,
+            outbox_message_repository,
+        }
+    }
+
+    /// A customer reviews a product once. The review is a verified purchase
+    /// if the customer has a confirmed order of one of its variants.
+    pub async fn submit(
+        &mut self,
+        request: SubmitReviewRequestObject,
+    ) -> Result<Review, ReviewServiceError> {
+        let review_id = parse_review_id(&request.review_id)?;
+        let customer_id = Uuid::try_parse(&request.customer_id)
+            .map(CustomerId)
+            .map_err(|err| ReviewServiceError::GenericError(err.to_string()))?;
+
+        info!("Submitting review");
+
+        let product = self.find_product(&request.product_id).await?;
+        match self
+            .customer_repository
+            .find_by_id(customer_id.clone())
+            .await
+            .map_err(|_| ReviewServiceError::CustomerNotReadError)?
+        {
+            Some(customer) if customer.is_active() => {}
+            _ => {
+                error!("Customer not found");
+                return Err(ReviewServiceError::CustomerNotFoundError);
+            }
+        }
+        let orders = self
+            .order_repository
+            .find_by_customer_id(customer_id.clone())
+            .await
+            .map_err(|e| {
+                error!("Error reading orders: {}", e);
+                ReviewServiceError::OrderNotReadError
+            })?;
+        let review = Review::submit(
+            review_id,
+            &product,
+            customer_id,
+            request.rating,
+            &request.text,
+            &orders,
+            Utc::now(),
+        )
+        .map_err(ReviewServiceError::InvalidReviewError)?;
+        let message = OutboxMessage::review_submitted_event(&review)
+            .map_err(|e| ReviewServiceError::GenericError(e.to_string()))?;
+
+        self.begin_transaction().await?;
+        let review = match self.review_repository.save(review).await {
+            Ok(review) => review,
+            Err(e) => {
+                self.rollback_transaction().await?;
+                return Err(save_error(e));
+            }
+        };
+        if let Err(e) = self.outbox_message_repository.save(message).await {
+            error!("Error saving outbox message: {}", e);
+            self.rollback_transaction().await?;
+            return Err(ReviewServiceError::GenericError(
+                "Outbox message not saved".to_string(),
+            ));
+        }
+        self.commit_transaction().await?;
+        Ok(review)
+    }
+
+    /// Reviews of the product in `status`, the approved ones when not set.
+    pub async fn find_reviews(
+        &self,
+        product_id: &str,
+        status: Option<&str>,
+    ) -> Result<Vec<Review>, ReviewServiceError> {
+        let status = status
+            .map(str::parse::<ReviewStatus>)
+            .transpose()
+            .map_err(ReviewServiceError::GenericError)?
+            .unwrap_or(ReviewStatus::Approved);
+        let product = self.find_product(product_id).await?;
+        self.review_repository
+            .find_by_product_id(product.id().clone(), status)
+            .await
+            .map_err(|e| {
+                error!("Error reading reviews: {}", e);
+                ReviewServiceError::ReviewNotReadError
+            })
+    }
+
+    /// Average rating of the approved reviews of the product.
+    pub async fn rating(&self, product_id: &str) -> Result<Rating, ReviewServiceError> {
+        let product = self.find_product(product_id).await?;
+        self.review_repository
+            .rating_of(product.id().clone())
+            .await
+            .map_err(|e| {
+                error!("Error reading rating: {}", e);
+                ReviewServiceError::ReviewNotReadError
+            })
+    }
+
+    pub async fn approve(
+        &self,
+        review_id: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Review, ReviewServiceError> {
+        info!("Approving review");
+
+        let mut review = self.get_review(review_id, expected_version).await?;
+        review
+            .approve()
+            .map_err(ReviewServiceError::InvalidReviewError)?;
+        self.review_repository
+            .update(review)
+            .await
+            .map_err(save_error)
+    }
+
+    pub async fn reject(
+        &self,
+        review_id: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Review, ReviewServiceError> {
+        info!("Rejecting review");
+
+        let mut review = self.get_review(review_id, expected_version).await?;
+        review
+            .reject()
+            .map_err(ReviewServiceError::InvalidReviewError)?;
+        self.review_repository
+            .update(review)
+            .await
+            .map_err(save_error)
+    }
+
+    async fn get_review(
+        &self,
+        review_id: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Review, ReviewServiceError> {
+        let review_id = parse_review_id(review_id)?;
+        let review = match self.review_repository.find_by_id(review_id).await {
+            Ok(Some(review)) => review,
+            Ok(None) => {
+                error!("Review not found");
+                return Err(ReviewServiceError::ReviewNotFoundError);
+            }
+            Err(e) => {
+                error!("Error reading review: {}", e);
+                return Err(ReviewServiceError::ReviewNotReadError);
+            }
+        };
+        if expected_version.is_some_and(|version| version != review.version()) {
+            error!("Review version does not match the expected one");
+            return Err(ReviewServiceError::ConcurrencyConflictError);
+        }
+        Ok(review)
+    }
+
+    async fn find_product(&self, product_id: &str) -> Result<Product, ReviewServiceError> {
+        let product_id = Uuid::try_parse(product_id)
+            .map(CatalogProductId)
+            .map_err(|err| ReviewServiceError::GenericError(err.to_string()))?;
+        match self.catalog_repository.find_product(product_id).await {
+            Ok(Some(product)) => Ok(product),
+            Ok(None) => {
+                error!("Product not found");
+                Err(ReviewServiceError::ProductNotFoundError)
+            }
+            Err(e) => {
+                error!("Error reading product: {}", e);
+                Err(ReviewServiceError::ProductNotReadError)
+            }
+        }
+    }
+
+    async fn begin_transaction(&mut self) -> Result<(), ReviewServiceError> {
+        self.review_repository
+            .begin_transaction()
+            .await
+            .map_err(|e| ReviewServiceError::GenericError(e.to_string()))
+    }
+
+    async fn commit_transaction(&mut self) -> Result<(), ReviewServiceError> {
+        self.review_repository
+            .commit_transaction()
+            .await
+            .map_err(|e| ReviewServiceError::GenericError(e.to_string()))
+    }
+
+    async fn rollback_transaction(&mut self) -> Result<(), ReviewServiceError> {
+        self.review_repository
+            .rollback_transaction()
+            .await
+            .map_err(|e| ReviewServiceError::GenericError(e.to_string()))
+    }
+}
+
+fn save_error(error: ReviewRepositoryError) -> ReviewServiceError {
+    error!("Error saving review: {}", error);
+    match error {
+        ReviewRepositoryError::ReviewAlreadyExistsError => {
+            ReviewServiceError::ReviewAlreadyExistsError
+        }
+        ReviewRepositoryError::ProductAlreadyReviewedError => {
+            ReviewServiceError::ProductAlreadyReviewedError
+        }
+        ReviewRepositoryError::ConcurrencyConflict => ReviewServiceError::ConcurrencyConflictError,
+        _ => ReviewServiceError::ReviewNotSavedError,
+    }
+}
+
+fn parse_review_id(review_id: &str) -> Result<ReviewId, ReviewServiceError> {
+    Uuid::try_parse(review_id)
+        .map(ReviewId)
+        .map_err(|err| ReviewServiceError::GenericError(err.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use crate::{
+        entities::{
+            address_book::AddressBook,
+            customer::Customer,
+            order::{Order, OrderStatus},
+            outbox::{OutboxMessageType, ReviewSubmittedEvent},
+            product::Product,
+        },
+        repositories::{
+            catalog_repository::MockCatalogRepository,
+            customer_repository::MockMyCustomerRepository,
+            order_repository::MockMyOrderRepository,
+            outbox_repository::InMemoryOutboxMessageRepository,
+            review_repository::{MockMyReviewRepository, ReviewRepositoryError},
+        },
+        value_objects::{
+            Address, CountryCode, Currency, Email, OrderId, OrderItem, PersonName, PostalCode,
+            ProductId,
+        },
+    };
+
+    use super::{ReviewService, ReviewServiceError, SubmitReviewRequestObject};
+
+    const CUSTOMER_ID: &str = "0f3c9a2e-7b1d-4e58-a6c4-2d9b8e1f7a30";
+    const VARIANT_ID: &str = "6a1e4c8b-3d2f-4b90-8e7a-5c6d7e8f9a0b";
+
+    #[tokio::test]
+    async fn submits_a_verified_review_once() {
+        let mut review_repository = MockMyReviewRepository::new();
+        review_repository
+            .expect_save()
+            .times(1)
+            .returning(|review| Ok(review.with_version(1)));
+        review_repository
+            .expect_save()
+            .returning(|_| Err(ReviewRepositoryError::ProductAlreadyReviewedError));
+        review_repository
+            .expect_begin_transaction()
+            .returning(|| Ok(()));
+        review_repository
+            .expect_commit_transaction()
+            .returning(|| Ok(()));
+        review_repository
+            .expect_rollback_transaction()
+            .times(1)
+            .returning(|| Ok(()));
+        let outbox_message_repository = InMemoryOutboxMessageRepository::new();
+        let mut service = ReviewService::new(
+            Box::new(review_repository),
+            Box::new(catalog_repository()),
+            Box::new(customer_repository()),
+            Box::new(order_repository()),
+            Box::new(outbox_message_repository.clone()),
+        );
+
+        let review = service.submit(request()).await.unwrap();
+        assert!(review.verified_purchase());
+        assert_eq!(1, review.version());
+        assert!(matches!(
+            service.submit(request()).await,
+            Err(ReviewServiceError::ProductAlreadyReviewedError)
+        ));
+
+        let messages = outbox_message_repository.messages();
+        assert_eq!(1, messages.len());
+        assert_eq!(OutboxMessageType::ReviewSubmitted, messages[0].event_type());
+        let event: ReviewSubmittedEvent =
+            serde_json::from_str(&messages[0].event_payload()).unwrap();
+        assert_eq!(5, event.rating);
+        assert!(event.verified_purchase);
+    }
+
+    fn catalog_repository() -> MockCatalogRepository {
+        let mut catalog_repository = MockCatalogRepository::new();
+        catalog_repository.expect_find_product().returning(|id| {
+            let mut product = Product::new(id, "T-shirt", None).unwrap();
+            product
+                .add_variant(
+                    ProductId(Uuid::try_parse(VARIANT_ID).unwrap()),
+                    "TS-RED-M",
+                    19.9,
+                )
+                .unwrap();
+            Ok(Some(product))
+        });
+        catalog_repository
+    }
+
+    fn customer_repository() -> MockMyCustomerRepository {
+        let mut customer_repository = MockMyCustomerRepository::new();
+        customer_repository.expect_find_by_id().returning(|id| {
+            Ok(Some(Customer {
+                id,
+                first_name: PersonName::parse("Mario").unwrap(),
+                last_name: PersonName::parse("Rossi").unwrap(),
+                email: Email::parse("mario.rossi@example.com").unwrap(),
+                phone: None,
+                address: Address {
+                    street: "Via Roma 1".to_string(),
+                    city: "Milano".to_string(),
+                    zip_code: PostalCode::parse("20121", &CountryCode::parse("IT").unwrap())
+                        .unwrap(),
+                    country: CountryCode::parse("IT").unwrap(),
+                },
+                address_book: AddressBook::new(),
+                currency: Currency::Eur,
+                deactivated_at: None,
+                version: 1,
+            }))
+        });
+        customer_repository
+    }
+
+    fn order_repository() -> MockMyOrderRepository {
+        let mut order_repository = MockMyOrderRepository::new();
+        order_repository
+            .expect_find_by_customer_id()
+            .returning(|customer_id| {
+                let mut order = Order::create(OrderId(Uuid::new_v4()), customer_id);
+                order
+                    .add(OrderItem {
+                        price: 19.9,
+                        quantity: 1,
+                        product_id: ProductId(Uuid::try_parse(VARIANT_ID).unwrap()),
+                    })
+                    .unwrap();
+                Ok(vec![order.with_status(OrderStatus::Confirmed)])
+            });
+        order_repository
+    }
+
+    fn request() -> SubmitReviewRequestObject {
+        SubmitReviewRequestObject {
+            review_id: Uuid::new_v4().to_string(),
+            product_id: Uuid::new_v4().to_string(),
+            customer_id: CUSTOMER_ID.to_string(),
+            rating: 5,
+            text: "Soft and fits well".to_string(),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    gateways::product_search::{ProductSearch, SearchQuery, SearchResult, SearchSort},
+    value_objects::CategoryId,
+};
+
+#[derive(Debug)]
+pub enum SearchServiceError {
+    InvalidQueryError(String),
+    SearchUnavailableError,
+}
+
+impl std::fmt::Display for SearchServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchServiceError::InvalidQueryError(message) => {
+                write!(f, "Invalid query: {}", message)
+            }
+            SearchServiceError::SearchUnavailableError => write!(f, "Search unavailable error"),
+        }
+    }
+}
+
+impl std::error::Error for SearchServiceError {}
+
+pub struct SearchRequestObject {
+    pub q: Option<String>,
+    pub category: Option<String>,
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+    /// One of `relevance`, `price_asc`, `price_desc` or `name`.
+    pub sort: Option<String>,
+}
+
+pub struct SearchService {
+    product_search: Box<dyn ProductSearch>,
+}
+
+impl SearchService {
+    pub fn new(product_search: Box<dyn ProductSearch>) -> Self {
+        Self { product_search }
+    }
+
+    pub async fn search(
+        &self,
+        request: SearchRequestObject,
+    ) -> Result<SearchResult, SearchServiceError> {
+        let invalid = |message: String| SearchServiceError::InvalidQueryError(message);
+        let category_id = request
+            .category
+            .as_deref()
+            .map(|category| Uuid::try_parse(category).map(CategoryId))
+            .transpose()
+            .map_err(|e| invalid(format!("category {}", e)))?;
+        for price in [request.min_price, request.max_price].into_iter().flatten() {
+            if price < 0.0 || !price.is_finite() {
+                return Err(invalid("prices must not be negative".to_string()));
+            }
+        }
+        if let (Some(min_price), Some(max_price)) = (request.min_price, request.max_price) {
+            if min_price > max_price {
+                return Err(invalid("min_price is over max_price".to_string()));
+            }
+        }
+        let sort = SearchSort::parse(request.sort.as_deref().unwrap_or_default())
+            .map_err(|e| invalid(format!("sort {}", e)))?;
+
+        info!("Searching products");
+
+        self.product_search
+            .search(&SearchQuery {
+                text: request.q.unwrap_or_default().trim().to_string(),
+                category_id,
+                min_price: request.min_price,
+                max_price: request.max_price,
+                sort,
+            })
+            .await
+            .map_err(|e| {
+                error!("Error searching products: {}", e);
+                SearchServiceError::SearchUnavailableError
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use mockall::predicate::eq;
+
+    use crate::gateways::product_search::{
+        MockProductSearch, SearchQuery, SearchResult, SearchSort,
+    };
+
+    use super::{SearchRequestObject, SearchService, SearchServiceError};
+
+    #[tokio::test]
+    async fn searches_with_a_valid_query_only() {
+        let mut product_search = MockProductSearch::new();
+        product_search
+            .expect_search()
+            .with(eq(SearchQuery {
+                text: "red shirt".to_string(),
+                category_id: None,
+                min_price: Some(10.0),
+                max_price: None,
+                sort: SearchSort::PriceAsc,
+            }))
+            .times(1)
+            .returning(|_| Ok(SearchResult::default()));
+        let service = SearchService::new(Box::new(product_search));
+        let request = |min_price, max_price, sort: &str| SearchRequestObject {
+            q: Some(" red shirt ".to_string()),
+            category: None,
+            min_price,
+            max_price,
+            sort: Some(sort.to_string()),
+        };
+
+        assert!(service
+            .search(request(Some(10.0), None, "price_asc"))
+            .await
+            .is_ok());
+        assert!(matches!(
+            service
+                .search(request(Some(10.0), Some(5.0), "price_asc"))
+                .await,
+            Err(SearchServiceError::InvalidQueryError(_))
+        ));
+        assert!(matches!(
+            service.search(request(None, None, "cheapest")).await,
+            Err(SearchServiceError::InvalidQueryError(_))
+        ));
+    }
+}
@@ -0,0 +1,555 @@
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    entities::{
+        order::Order,
+        outbox::OutboxMessage,
+        shipment::{is_fully_shipped, Shipment, ShipmentError},
+        shipping_method::{ShippingMethod, ShippingMethodError, ShippingRate},
+    },
+    repositories::{
+        order_repository::OrderRepository,
+        outbox_repository::OutboxMessageRepository,
+        shipment_repository::{ShipmentRepository, ShipmentRepositoryError},
+        shipping_method_repository::{ShippingMethodRepository, ShippingMethodRepositoryError},
+    },
+    value_objects::{OrderId, ProductId, ShipmentId, ShippingMethodId},
+};
+
+#[derive(Debug)]
+pub enum ShipmentServiceError {
+    ShipmentNotFoundError,
+    ShipmentNotReadError,
+    ShipmentNotSavedError,
+    ShippingMethodNotFoundError,
+    ShippingMethodNotReadError,
+    ShippingMethodNotSavedError,
+    ShippingMethodAlreadyExistsError,
+    OrderNotFoundError,
+    OrderNotReadError,
+    ConcurrencyConflictError,
+    InvalidShippingMethodError(ShippingMethodError),
+    InvalidShipmentError(ShipmentError),
+    GenericError(String),
+}
+
+impl std::fmt::Display for ShipmentServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShipmentServiceError::ShipmentNotFoundError => write!(f, "Shipment not found error"),
+            ShipmentServiceError::ShipmentNotReadError => write!(f, "Shipment not read error"),
+            ShipmentServiceError::ShipmentNotSavedError => write!(f, "Shipment not saved error"),
+            ShipmentServiceError::ShippingMethodNotFoundError => {
+                write!(f, "Shipping method not found error")
+            }
+            ShipmentServiceError::ShippingMethodNotReadError => {
+                write!(f, "Shipping method not read error")
+            }
+            ShipmentServiceError::ShippingMethodNotSavedError => {
+                write!(f, "Shipping method not saved error")
+            }
+            ShipmentServiceError::ShippingMethodAlreadyExistsError => {
+                write!(f, "A shipping method with the same id exists")
+            }
+            ShipmentServiceError::OrderNotFoundError => write!(f, "Order not found error"),
+            ShipmentServiceError::OrderNotReadError => write!(f, "Order not read error"),
+            ShipmentServiceError::ConcurrencyConflictError => {
+                write!(f, "Shipment was modified concurrently")
+            }
+            ShipmentServiceError::InvalidShippingMethodError(error) => {
+                write!(f, "Invalid shipping method: {}", error)
+            }
+            ShipmentServiceError::InvalidShipmentError(error) => {
+                write!(f, "Invalid shipment: {}", error)
+            }
+            ShipmentServiceError::GenericError(error) => write!(f, "Generic error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ShipmentServiceError {}
+
+pub struct CreateShippingMethodRequestObject {
+    pub shipping_method_id: String,
+    pub name: String,
+    pub carrier: String,
+    pub kind: String,
+    pub amount: Option<f64>,
+    pub per_kg: Option<f64>,
+    pub threshold: Option<f64>,
+}
+
+pub struct CreateShipmentRequestObject {
+    pub shipment_id: String,
+    pub order_id: String,
+    pub shipping_method_id: String,
+    pub weight: f64,
+    pub product_id: String,
+    pub quantity: i32,
+}
+
+pub struct AddShipmentItemRequestObject {
+    pub shipment_id: String,
+    pub product_id: String,
+    pub quantity: i32,
+    /// When set, the item is added only if the shipment is still at this version.
+    pub expected_version: Option<i64>,
+}
+
+/// Ships confirmed orders, in one or more parcels, with the shipping methods
+/// on offer. Handing a parcel to the carrier publishes `order_shipped`, its
+/// delivery `shipment_delivered`.
+pub struct ShipmentService {
+    shipment_repository: Box<dyn ShipmentRepository>,
+    shipping_method_repository: Box<dyn ShippingMethodRepository>,
+    order_repository: Box<dyn OrderRepository>,
+    outbox_message_repository: Box<dyn OutboxMessageRepository>,
+}
+
+impl ShipmentService {
+    pub fn new(
+        shipment_repository: Box<dyn ShipmentRepository>,
+        shipping_method_repository: Box<dyn ShippingMethodRepository>,
+        order_repository: Box<dyn OrderRepository>,
+        outbox_message_repository: Box<dyn OutboxMessageRepository>,
+    ) -> Self {
+        Self {
+            shipment_repository,
+            shipping_method_repository,
+            order_repository,
+            outbox_message_repository,
+        }
+    }
+
+    pub async fn create_shipping_method(
+        &self,
+        request: CreateShippingMethodRequestObject,
+    ) -> Result<ShippingMethod, ShipmentServiceError> {
+        let shipping_method_id = parse_shipping_method_id(&request.shipping_method_id)?;
+        let rate = ShippingRate::parse(
+            &request.kind,
+            request.amount,
+            request.per_kg,
+            request.threshold,
+        )
+        .map_err(ShipmentServiceError::InvalidShippingMethodError)?;
+        let shipping_method =
+            ShippingMethod::new(shipping_method_id, &request.name, &request.carrier, rate)
+                .map_err(ShipmentServiceError::InvalidShippingMethodError)?;
+
+        info!("Creating shipping method");
+
+        self.shipping_method_repository
+            .save(shipping_method)
+            .await
+            .map_err(|e| {
+                error!("Error saving shipping method: {}", e);
+                match e {
+                    ShippingMethodRepositoryError::ShippingMethodAlreadyExistsError => {
+                        ShipmentServiceError::ShippingMethodAlreadyExistsError
+                    }
+                    _ => ShipmentServiceError::ShippingMethodNotSavedError,
+                }
+            })
+    }
+
+    pub async fn find_shipping_methods(&self) -> Result<Vec<ShippingMethod>, ShipmentServiceError> {
+        self.shipping_method_repository
+            .find_all()
+            .await
+            .map_err(|e| {
+                error!("Error reading shipping methods: {}", e);
+                ShipmentServiceError::ShippingMethodNotReadError
+            })
+    }
+
+    pub async fn find_shipment(&self, shipment_id: &str) -> Result<Shipment, ShipmentServiceError> {
+        self.get_shipment(shipment_id, None).await
+    }
+
+    /// Starts a shipment with a first item of the order.
+    pub async fn create_shipment(
+        &mut self,
+        request: CreateShipmentRequestObject,
+    ) -> Result<Shipment, ShipmentServiceError> {
+        let shipment_id = Uuid::try_parse(&request.shipment_id)
+            .map(ShipmentId)
+            .map_err(|err| ShipmentServiceError::GenericError(err.to_string()))?;
+        let order_id = Uuid::try_parse(&request.order_id)
+            .map(OrderId)
+            .map_err(|err| ShipmentServiceError::GenericError(err.to_string()))?;
+        let shipping_method_id = parse_shipping_method_id(&request.shipping_method_id)?;
+        let product_id = parse_product_id(&request.product_id)?;
+
+        info!("Creating shipment");
+
+        let shipping_method = self.find_shipping_method(shipping_method_id).await?;
+        let order = self.find_order(&order_id).await?;
+        let other_shipments = self.find_shipments_of(&order_id).await?;
+        let mut shipment = Shipment::prepare(shipment_id, &order, &shipping_method, request.weight)
+            .map_err(ShipmentServiceError::InvalidShipmentError)?;
+        shipment
+            .add(&order, &other_shipments, product_id, request.quantity)
+            .map_err(ShipmentServiceError::InvalidShipmentError)?;
+        self.shipment_repository
+            .save(shipment, order.version())
+            .await
+            .map_err(save_error)
+    }
+
+    pub async fn add_item(
+        &mut self,
+        request: AddShipmentItemRequestObject,
+    ) -> Result<Shipment, ShipmentServiceError> {
+        let product_id = parse_product_id(&request.product_id)?;
+
+        info!("Adding item to shipment");
+
+        let mut shipment = self
+            .get_shipment(&request.shipment_id, request.expected_version)
+            .await?;
+        let order = self.find_order(shipment.order_id()).await?;
+        let other_shipments = self.find_shipments_of(shipment.order_id()).await?;
+        shipment
+            .add(&order, &other_shipments, product_id, request.quantity)
+            .map_err(ShipmentServiceError::InvalidShipmentError)?;
+        self.shipment_repository
+            .update_items(shipment, order.version())
+            .await
+            .map_err(save_error)
+    }
+
+    /// Hands the shipment to its carrier.
+    pub async fn ship(
+        &mut self,
+        shipment_id: &str,
+        tracking_number: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Shipment, ShipmentServiceError> {
+        info!("Shipping shipment");
+
+        let mut shipment = self.get_shipment(shipment_id, expected_version).await?;
+        shipment
+            .ship(tracking_number)
+            .map_err(ShipmentServiceError::InvalidShipmentError)?;
+        let order = self.find_order(shipment.order_id()).await?;
+        let mut shipments: Vec<Shipment> = self
+            .find_shipments_of(shipment.order_id())
+            .await?
+            .into_iter()
+            .filter(|other| other.id() != shipment.id())
+            .collect();
+        shipments.push(shipment.clone());
+
+        let message =
+            OutboxMessage::order_shipped_event(&shipment, is_fully_shipped(&order, &shipments))
+                .map_err(|e| ShipmentServiceError::GenericError(e.to_string()))?;
+        self.store(shipment, message).await
+    }
+
+    /// Called when the carrier reports the shipment delivered.
+    pub async fn deliver(
+        &mut self,
+        shipment_id: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Shipment, ShipmentServiceError> {
+        info!("Delivering shipment");
+
+        let mut shipment = self.get_shipment(shipment_id, expected_version).await?;
+        shipment
+            .deliver()
+            .map_err(ShipmentServiceError::InvalidShipmentError)?;
+        let message = OutboxMessage::shipment_delivered_event(&shipment)
+            .map_err(|e| ShipmentServiceError::GenericError(e.to_string()))?;
+        self.store(shipment, message).await
+    }
+
+    /// Updates the shipment and publishes `message` in the same transaction.
+    async fn store(
+        &mut self,
+        shipment: Shipment,
+        message: OutboxMessage,
+    ) -> Result<Shipment, ShipmentServiceError> {
+        self.begin_transaction().await?;
+        let shipment = match self.shipment_repository.update(shipment).await {
+            Ok(shipment) => shipment,
+            Err(e) => {
+                self.rollback_transaction().await?;
+                return Err(save_error(e));
+            }
+        };
+        if let Err(e) = self.outbox_message_repository.save(message).await {
+            error!("Error saving outbox message: {}", e);
+            self.rollback_transaction().await?;
+            return Err(ShipmentServiceError::GenericError(
+                "Outbox message not saved".to_string(),
+            ));
+        }
+        self.commit_transaction().await?;
+        Ok(shipment)
+    }
+
+    async fn get_shipment(
+        &self,
+        shipment_id: &str,
+        expected_version: Option<i64>,
+    ) -> Result<Shipment, ShipmentServiceError> {
+        let shipment_id = Uuid::try_parse(shipment_id)
+            .map_err(|err| ShipmentServiceError::GenericError(err.to_string()))?;
+        let shipment = match self
+            .shipment_repository
+            .find_by_id(ShipmentId(shipment_id))
+            .await
+        {
+            Ok(Some(shipment)) => shipment,
+            Ok(None) => {
+                error!("Shipment not found");
+                return Err(ShipmentServiceError::ShipmentNotFoundError);
+            }
+            Err(e) => {
+                error!("Error reading shipment: {}", e);
+                return Err(ShipmentServiceError::ShipmentNotReadError);
+            }
+        };
+        if expected_version.is_some_and(|version| version != shipment.version()) {
+            error!("Shipment version does not match the expected one");
+            return Err(ShipmentServiceError::ConcurrencyConflictError);
+        }
+        Ok(shipment)
+    }
+
+    async fn find_shipping_method(
+        &self,
+        shipping_method_id: ShippingMethodId,
+    ) -> Result<ShippingMethod, ShipmentServiceError> {
+        match self
+            .shipping_method_repository
+            .find_by_id(shipping_method_id)
+            .await
+        {
+            Ok(Some(shipping_method)) => Ok(shipping_method),
+            Ok(None) => {
+                error!("Shipping method not found");
+                Err(ShipmentServiceError::ShippingMethodNotFoundError)
+            }
+            Err(e) => {
+                error!("Error reading shipping method: {}", e);
+                Err(ShipmentServiceError::ShippingMethodNotReadError)
+            }
+        }
+    }
+
+    async fn find_shipments_of(
+        &self,
+        order_id: &OrderId,
+    ) -> Result<Vec<Shipment>, ShipmentServiceError> {
+        self.shipment_repository
+            .find_by_order_id(order_id.clone())
+            .await
+            .map_err(|e| {
+                error!("Error reading shipments: {}", e);
+                ShipmentServiceError::ShipmentNotReadError
+            })
+    }
+
+    async fn find_order(&self, order_id: &OrderId) -> Result<Order, ShipmentServiceError> {
+        match self.order_repository.find_by_id(order_id.clone()).await {
+            Ok(Some(order)) => Ok(order),
+            Ok(None) => {
+                error!("Order not found");
+                Err(ShipmentServiceError::OrderNotFoundError)
+            }
+            Err(e) => {
+                error!("Error reading order: {}", e);
+                Err(ShipmentServiceError::OrderNotReadError)
+            }
+        }
+    }
+
+    async fn begin_transaction(&mut self) -> Result<(), ShipmentServiceError> {
+        self.shipment_repository
+            .begin_transaction()
+            .await
+            .map_err(|e| ShipmentServiceError::GenericError(e.to_string()))
+    }
+
+    async fn commit_transaction(&mut self) -> Result<(), ShipmentServiceError> {
+        self.shipment_repository
+            .commit_transaction()
+            .await
+            .map_err(|e| ShipmentServiceError::GenericError(e.to_string()))
+    }
+
+    async fn rollback_transaction(&mut self) -> Result<(), ShipmentServiceError> {
+        self.shipment_repository
+            .rollback_transaction()
+            .await
+            .map_err(|e| ShipmentServiceError::GenericError(e.to_string()))
+    }
+}
+
+fn save_error(error: ShipmentRepositoryError) -> ShipmentServiceError {
+    error!("Error saving shipment: {}", error);
+    match error {
+        ShipmentRepositoryError::ConcurrencyConflict => {
+            ShipmentServiceError::ConcurrencyConflictError
+        }
+        _ => ShipmentServiceError::ShipmentNotSavedError,
+    }
+}
+
+fn parse_shipping_method_id(
+    shipping_method_id: &str,
+) -> Result<ShippingMethodId, ShipmentServiceError> {
+    Uuid::try_parse(shipping_method_id)
+        .map(ShippingMethodId)
+        .map_err(|err| ShipmentServiceError::GenericError(err.to_string()))
+}
+
+fn parse_product_id(product_id: &str) -> Result<ProductId, ShipmentServiceError> {
+    Uuid::try_parse(product_id)
+        .map(ProductId)
+        .map_err(|err| ShipmentServiceError::GenericError(err.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use crate::{
+        entities::{
+            order::OrderStatus,
+            outbox::{OrderShippedEvent, OutboxMessageType},
+            shipment::{Shipment, ShipmentError, ShipmentState},
+            shipping_method::{ShippingMethod, ShippingRate},
+        },
+        fixtures::{order_repository, product_id, Stored, ORDER_ID},
+        repositories::{
+            outbox_repository::InMemoryOutboxMessageRepository,
+            shipment_repository::MockMyShipmentRepository,
+            shipping_method_repository::MockShippingMethodRepository,
+        },
+    };
+
+    use super::{CreateShipmentRequestObject, ShipmentService, ShipmentServiceError};
+
+    const SHIPPING_METHOD_ID: &str = "c4e1a7b2-6d3f-4a58-9b0c-1d2e3f4a5b6c";
+
+    #[tokio::test]
+    async fn ships_and_delivers_a_shipment_of_a_confirmed_order() {
+        let outbox_message_repository = InMemoryOutboxMessageRepository::new();
+        let mut service = ShipmentService::new(
+            Box::new(shipment_repository(Stored::default())),
+            Box::new(shipping_method_repository()),
+            Box::new(order_repository(OrderStatus::Confirmed)),
+            Box::new(outbox_message_repository.clone()),
+        );
+        let shipment = service.create_shipment(request(2)).await.unwrap();
+        let shipment_id = shipment.id().0.to_string();
+
+        service.ship(&shipment_id, "1Z999", Some(1)).await.unwrap();
+        let shipment = service.deliver(&shipment_id, Some(2)).await.unwrap();
+
+        assert_eq!(ShipmentState::Delivered, shipment.state());
+        let messages = outbox_message_repository.messages();
+        assert_eq!(
+            vec![
+                OutboxMessageType::OrderShipped,
+                OutboxMessageType::ShipmentDelivered
+            ],
+            messages
+                .iter()
+                .map(|message| message.event_type())
+                .collect::<Vec<_>>()
+        );
+        let event: OrderShippedEvent = serde_json::from_str(&messages[0].event_payload()).unwrap();
+        assert_eq!("1Z999", event.tracking_number);
+        assert!(!event.fully_shipped);
+    }
+
+    #[tokio::test]
+    async fn cannot_ship_items_of_a_pending_order() {
+        let mut service = ShipmentService::new(
+            Box::new(shipment_repository(Stored::default())),
+            Box::new(shipping_method_repository()),
+            Box::new(order_repository(OrderStatus::Pending)),
+            Box::new(InMemoryOutboxMessageRepository::new()),
+        );
+
+        let result = service.create_shipment(request(1)).await;
+
+        assert!(matches!(
+            result,
+            Err(ShipmentServiceError::InvalidShipmentError(
+                ShipmentError::OrderNotConfirmedError(OrderStatus::Pending)
+            ))
+        ));
+    }
+
+    /// Keeps the last shipment stored, bumping its version.
+    fn shipment_repository(stored: Stored<Shipment>) -> MockMyShipmentRepository {
+        let mut shipment_repository = MockMyShipmentRepository::new();
+        let found = stored.clone();
+        shipment_repository
+            .expect_find_by_id()
+            .returning(move |_| Ok(found.get()));
+        let found = stored.clone();
+        shipment_repository
+            .expect_find_by_order_id()
+            .returning(move |_| Ok(found.get().into_iter().collect()));
+        let saved = stored.clone();
+        shipment_repository
+            .expect_save()
+            .returning(move |shipment, _| Ok(saved.save(shipment)));
+        let updated = stored.clone();
+        shipment_repository
+            .expect_update()
+            .returning(move |shipment| Ok(updated.update(shipment)));
+        shipment_repository
+            .expect_update_items()
+            .returning(move |shipment, _| Ok(stored.update(shipment)));
+        shipment_repository
+            .expect_begin_transaction()
+            .returning(|| Ok(()));
+        shipment_repository
+            .expect_commit_transaction()
+            .returning(|| Ok(()));
+        shipment_repository
+            .expect_rollback_transaction()
+            .returning(|| Ok(()));
+        shipment_repository
+    }
+
+    fn shipping_method_repository() -> MockShippingMethodRepository {
+        let mut shipping_method_repository = MockShippingMethodRepository::new();
+        shipping_method_repository
+            .expect_find_by_id()
+            .returning(|id| {
+                Ok(Some(
+                    ShippingMethod::new(
+                        id,
+                        "Standard",
+                        "UPS",
+                        ShippingRate::FreeOver {
+                            amount: 5.0,
+                            threshold: 50.0,
+                        },
+                    )
+                    .unwrap(),
+                ))
+            });
+        shipping_method_repository
+    }
+
+    fn request(quantity: i32) -> CreateShipmentRequestObject {
+        CreateShipmentRequestObject {
+            shipment_id: Uuid::new_v4().to_string(),
+            order_id: ORDER_ID.to_string(),
+            shipping_method_id: SHIPPING_METHOD_ID.to_string(),
+            weight: 1.5,
+            product_id: product_id(1).0.to_string(),
+            quantity,
+        }
+    }
+}
@@ -2,11 +2,415 @@ use std::fmt;
 
 use uuid::Uuid;
 
+/// Why a value could not be turned into a value object.
+#[derive(PartialEq, Debug, Clone)]
+pub enum InvalidValueError {
+    Empty,
+    TooLong(usize),
+    InvalidCharacters,
+    InvalidFormat(String),
+}
+
+impl fmt::Display for InvalidValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidValueError::Empty => write!(f, "must not be empty"),
+            InvalidValueError::TooLong(max) => write!(f, "must be at most {} characters", max),
+            InvalidValueError::InvalidCharacters => write!(f, "contains invalid characters"),
+            InvalidValueError::InvalidFormat(expected) => write!(f, "must be {}", expected),
+        }
+    }
+}
+
+impl std::error::Error for InvalidValueError {}
+
+/// Replaces personal data of erased customers.
+pub const ERASED: &str = "erased";
+
+/// First or last name of a person, trimmed.
+#[derive(PartialEq, Debug, Clone)]
+pub struct PersonName(String);
+
+impl PersonName {
+    pub const MAX_LENGTH: usize = 100;
+
+    pub fn parse(value: &str) -> Result<Self, InvalidValueError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(InvalidValueError::Empty);
+        }
+        if value.chars().count() > Self::MAX_LENGTH {
+            return Err(InvalidValueError::TooLong(Self::MAX_LENGTH));
+        }
+        if !value
+            .chars()
+            .all(|c| c.is_alphabetic() || c == ' ' || c == '-' || c == '\'' || c == '.')
+        {
+            return Err(InvalidValueError::InvalidCharacters);
+        }
+        Ok(Self(value.to_string()))
+    }
+
+    pub fn erased() -> Self {
+        Self(ERASED.to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for PersonName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// E-mail address, trimmed and lowercased.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Email(String);
+
+impl Email {
+    pub const MAX_LENGTH: usize = 254;
+
+    pub fn parse(value: &str) -> Result<Self, InvalidValueError> {
+        let value = value.trim().to_lowercase();
+        if value.is_empty() {
+            return Err(InvalidValueError::Empty);
+        }
+        if value.len() > Self::MAX_LENGTH {
+            return Err(InvalidValueError::TooLong(Self::MAX_LENGTH));
+        }
+        let invalid_format = || InvalidValueError::InvalidFormat("an e-mail address".to_string());
+        let Some((local, domain)) = value.split_once('@') else {
+            return Err(invalid_format());
+        };
+        if local.is_empty()
+            || domain.contains('@')
+            || !domain.contains('.')
+            || domain.starts_with('.')
+            || domain.ends_with('.')
+            || domain.contains("..")
+            || value.chars().any(|c| c.is_whitespace() || c.is_control())
+        {
+            return Err(invalid_format());
+        }
+        Ok(Self(value))
+    }
+
+    /// Unique per customer, as emails must be, and on a reserved domain.
+    pub fn erased(customer_id: &CustomerId) -> Self {
+        Self(format!("{}@{}.invalid", customer_id.0, ERASED))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Email {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Phone number, kept as `+` followed by its digits.
+#[derive(PartialEq, Debug, Clone)]
+pub struct PhoneNumber(String);
+
+impl PhoneNumber {
+    pub const MIN_DIGITS: usize = 7;
+    pub const MAX_DIGITS: usize = 15;
+
+    /// Accepts an international number, optionally spaced with blanks, dots,
+    /// dashes or parentheses.
+    pub fn parse(value: &str) -> Result<Self, InvalidValueError> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(InvalidValueError::Empty);
+        }
+        let Some(number) = value.strip_prefix('+') else {
+            return Err(InvalidValueError::InvalidFormat(
+                "a phone number starting with +".to_string(),
+            ));
+        };
+        if !number
+            .chars()
+            .all(|c| c.is_ascii_digit() || " .-()".contains(c))
+        {
+            return Err(InvalidValueError::InvalidCharacters);
+        }
+        let digits: String = number.chars().filter(char::is_ascii_digit).collect();
+        if digits.len() < Self::MIN_DIGITS || digits.len() > Self::MAX_DIGITS {
+            return Err(InvalidValueError::InvalidFormat(format!(
+                "a phone number of {} to {} digits",
+                Self::MIN_DIGITS,
+                Self::MAX_DIGITS
+            )));
+        }
+        Ok(Self(format!("+{}", digits)))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for PhoneNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// ISO 3166-1 alpha-2 country code, uppercased.
+#[derive(PartialEq, Debug, Clone)]
+pub struct CountryCode(String);
+
+impl CountryCode {
+    /// User-assigned code of customers migrated from a free-text state that
+    /// is not a known country.
+    pub const UNKNOWN: &'static str = "ZZ";
+
+    pub fn parse(value: &str) -> Result<Self, InvalidValueError> {
+        let value = value.trim().to_uppercase();
+        if value.is_empty() {
+            return Err(InvalidValueError::Empty);
+        }
+        if value != Self::UNKNOWN && !ISO_3166_ALPHA_2.contains(&value.as_str()) {
+            return Err(InvalidValueError::InvalidFormat(
+                "an ISO 3166-1 alpha-2 country code".to_string(),
+            ));
+        }
+        Ok(Self(value))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CountryCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Postal code, uppercased and checked against the format of its country
+/// when known.
+#[derive(PartialEq, Debug, Clone)]
+pub struct PostalCode(String);
+
+impl PostalCode {
+    pub const MAX_LENGTH: usize = 10;
+
+    pub fn parse(value: &str, country: &CountryCode) -> Result<Self, InvalidValueError> {
+        let value = value.trim().to_uppercase();
+        if value.is_empty() {
+            return Err(InvalidValueError::Empty);
+        }
+        match postal_code_formats(country) {
+            Some(formats) => {
+                if !formats.iter().any(|format| matches_format(&value, format)) {
+                    return Err(InvalidValueError::InvalidFormat(format!(
+                        "a postal code of {} like {}",
+                        country,
+                        formats.join(" or ")
+                    )));
+                }
+            }
+            None => {
+                if value.len() > Self::MAX_LENGTH {
+                    return Err(InvalidValueError::TooLong(Self::MAX_LENGTH));
+                }
+                if !value
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '-')
+                {
+                    return Err(InvalidValueError::InvalidCharacters);
+                }
+            }
+        }
+        Ok(Self(value))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for PostalCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Formats use `9` for a digit and `A` for a letter; anything else is literal.
+fn postal_code_formats(country: &CountryCode) -> Option<&'static [&'static str]> {
+    let formats: &'static [&'static str] = match country.as_str() {
+        "US" => &["99999", "99999-9999"],
+        "CA" => &["A9A 9A9"],
+        "GB" => &[
+            "A9 9AA", "A99 9AA", "A9A 9AA", "AA9 9AA", "AA99 9AA", "AA9A 9AA",
+        ],
+        "NL" => &["9999 AA"],
+        "PT" => &["9999-999"],
+        "BR" => &["99999-999"],
+        "JP" => &["999-9999"],
+        "PL" => &["99-999"],
+        "SE" => &["999 99"],
+        "IN" => &["999999"],
+        "IT" | "DE" | "FR" | "ES" | "FI" | "MX" => &["99999"],
+        "AT" | "AU" | "BE" | "CH" | "DK" | "NO" | "NZ" => &["9999"],
+        _ => return None,
+    };
+    Some(formats)
+}
+
+fn matches_format(value: &str, format: &str) -> bool {
+    value.len() == format.len()
+        && value.chars().zip(format.chars()).all(|(c, f)| match f {
+            '9' => c.is_ascii_digit(),
+            'A' => c.is_ascii_alphabetic(),
+            _ => c == f,
+        })
+}
+
+const ISO_3166_ALPHA_2: [&str; 249] = [
+    "AD", "AE", "AF", "AG", "AI", "AL", "AM", "AO", "AQ", "AR", "AS", "AT", "AU", "AW", "AX", "AZ",
+    "BA", "BB", "BD", "BE", "BF", "BG", "BH", "BI", "BJ", "BL", "BM", "BN", "BO", "BQ", "BR", "BS",
+    "BT", "BV", "BW", "BY", "BZ", "CA", "CC", "CD", "CF", "CG", "CH", "CI", "CK", "CL", "CM", "CN",
+    "CO", "CR", "CU", "CV", "CW", "CX", "CY", "CZ", "DE", "DJ", "DK", "DM", "DO", "DZ", "EC", "EE",
+    "EG", "EH", "ER", "ES", "ET", "FI", "FJ", "FK", "FM", "FO", "FR", "GA", "GB", "GD", "GE", "GF",
+    "GG", "GH", "GI", "GL", "GM", "GN", "GP", "GQ", "GR", "GS", "GT", "GU", "GW", "GY", "HK", "HM",
+    "HN", "HR", "HT", "HU", "ID", "IE", "IL", "IM", "IN", "IO", "IQ", "IR", "IS", "IT", "JE", "JM",
+    "JO", "JP", "KE", "KG", "KH", "KI", "KM", "KN", "KP", "KR", "KW", "KY", "KZ", "LA", "LB", "LC",
+    "LI", "LK", "LR", "LS", "LT", "LU", "LV", "LY", "MA", "MC", "MD", "ME", "MF", "MG", "MH", "MK",
+    "ML", "MM", "MN", "MO", "MP", "MQ", "MR", "MS", "MT", "MU", "MV", "MW", "MX", "MY", "MZ", "NA",
+    "NC", "NE", "NF", "NG", "NI", "NL", "NO", "NP", "NR", "NU", "NZ", "OM", "PA", "PE", "PF", "PG",
+    "PH", "PK", "PL", "PM", "PN", "PR", "PS", "PT", "PW", "PY", "QA", "RE", "RO", "RS", "RU", "RW",
+    "SA", "SB", "SC", "SD", "SE", "SG", "SH", "SI", "SJ", "SK", "SL", "SM", "SN", "SO", "SR", "SS",
+    "ST", "SV", "SX", "SY", "SZ", "TC", "TD", "TF", "TG", "TH", "TJ", "TK", "TL", "TM", "TN", "TO",
+    "TR", "TT", "TV", "TW", "TZ", "UA", "UG", "UM", "US", "UY", "UZ", "VA", "VC", "VE", "VG", "VI",
+    "VN", "VU", "WF", "WS", "YE", "YT", "ZA", "ZM", "ZW",
+];
+
+/// Code customers enter to get a promotion, trimmed and uppercased.
+#[derive(PartialEq, Debug, Clone)]
+pub struct CouponCode(String);
+
+impl CouponCode {
+    pub const MAX_LENGTH: usize = 32;
+
+    pub fn parse(value: &str) -> Result<Self, InvalidValueError> {
+        let value = value.trim().to_uppercase();
+        if value.is_empty() {
+            return Err(InvalidValueError::Empty);
+        }
+        if value.len() > Self::MAX_LENGTH {
+            return Err(InvalidValueError::TooLong(Self::MAX_LENGTH));
+        }
+        if !value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(InvalidValueError::InvalidCharacters);
+        }
+        Ok(Self(value))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CouponCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Currency prices are set and orders are paid in.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, Default)]
+pub enum Currency {
+    #[default]
+    Eur,
+    Usd,
+    Gbp,
+}
+
+impl Currency {
+    /// Currency reports are converted to, with a rate of 1.
+    pub const BASE: Currency = Currency::Eur;
+
+    /// ISO 4217 code, case insensitive.
+    pub fn parse(value: &str) -> Result<Self, InvalidValueError> {
+        match value.trim().to_uppercase().as_str() {
+            "" => Err(InvalidValueError::Empty),
+            "EUR" => Ok(Currency::Eur),
+            "USD" => Ok(Currency::Usd),
+            "GBP" => Ok(Currency::Gbp),
+            _ => Err(InvalidValueError::InvalidFormat(
+                "one of EUR, USD or GBP".to_string(),
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Currency::Eur => "EUR",
+            Currency::Usd => "USD",
+            Currency::Gbp => "GBP",
+        }
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Stock keeping unit of a product variant, upper case.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Sku(String);
+
+impl Sku {
+    pub const MAX_LENGTH: usize = 64;
+
+    pub fn parse(value: &str) -> Result<Self, InvalidValueError> {
+        let value = value.trim().to_uppercase();
+        if value.is_empty() {
+            return Err(InvalidValueError::Empty);
+        }
+        if value.chars().count() > Self::MAX_LENGTH {
+            return Err(InvalidValueError::TooLong(Self::MAX_LENGTH));
+        }
+        if !value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(InvalidValueError::InvalidCharacters);
+        }
+        Ok(Self(value))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Sku {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
 pub struct Address {
     pub street: String,
     pub city: String,
-    pub zip_code: String,
-    pub state: String,
+    pub zip_code: PostalCode,
+    pub country: CountryCode,
 }
 
 impl fmt::Display for Address {
@@ -14,22 +418,186 @@ impl fmt::Display for Address {
         write!(
             f,
             "{} - {}, {} ({})",
-            self.street, self.zip_code, self.city, self.state
+            self.street, self.zip_code, self.city, self.country
         )
     }
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone)]
 pub struct CustomerId(pub Uuid);
 
+#[derive(PartialEq, Debug, Clone)]
+pub struct AddressId(pub Uuid);
+
 #[derive(PartialEq, Debug, Clone)]
 pub struct OrderId(pub Uuid);
 
-#[derive(PartialEq, Debug)]
+/// A variant of a catalog product: what is ordered, stocked and priced.
+#[derive(PartialEq, Debug, Clone)]
 pub struct ProductId(pub Uuid);
 
+/// A product of the catalog, sold in one or more variants.
+#[derive(PartialEq, Debug, Clone)]
+pub struct CatalogProductId(pub Uuid);
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct CategoryId(pub Uuid);
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct CartId(pub Uuid);
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct WarehouseId(pub Uuid);
+
+/// Stock set aside for an order until it is committed or released.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ReservationId(pub Uuid);
+
+/// Reference of a payment authorization, as given by the payment provider.
+#[derive(PartialEq, Debug, Clone)]
+pub struct AuthorizationId(pub String);
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct PaymentId(pub Uuid);
+
+/// Items of an order sent back by the customer.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ReturnId(pub Uuid);
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct PromotionId(pub Uuid);
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct ShippingMethodId(pub Uuid);
+
+/// Parcel of order items handed to a carrier.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ShipmentId(pub Uuid);
+
+/// Invoice or credit note of an order.
+#[derive(PartialEq, Debug, Clone)]
+pub struct InvoiceId(pub Uuid);
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct ReviewId(pub Uuid);
+
+#[derive(PartialEq, Debug, Clone)]
 pub struct OrderItem {
     pub price: f64,
     pub quantity: i32,
     pub product_id: ProductId,
 }
+
+#[cfg(test)]
+mod test {
+    use super::{
+        CountryCode, CouponCode, Currency, Email, InvalidValueError, PersonName, PhoneNumber,
+        PostalCode, Sku,
+    };
+
+    #[test]
+    fn parses_a_person_name() {
+        assert_eq!(
+            "Anne-Marie",
+            PersonName::parse(" Anne-Marie ").unwrap().as_str()
+        );
+        assert_eq!(Err(InvalidValueError::Empty), PersonName::parse("  "));
+        assert_eq!(
+            Err(InvalidValueError::InvalidCharacters),
+            PersonName::parse("R2D2")
+        );
+        assert_eq!(
+            Err(InvalidValueError::TooLong(PersonName::MAX_LENGTH)),
+            PersonName::parse(&"a".repeat(PersonName::MAX_LENGTH + 1))
+        );
+    }
+
+    #[test]
+    fn parses_an_email() {
+        assert_eq!(
+            "john.doe@example.com",
+            Email::parse("John.Doe@Example.com").unwrap().as_str()
+        );
+        assert!(Email::parse("john.doe").is_err());
+        assert!(Email::parse("@example.com").is_err());
+        assert!(Email::parse("john@example").is_err());
+        assert!(Email::parse("john doe@example.com").is_err());
+    }
+
+    #[test]
+    fn parses_a_phone_number() {
+        assert_eq!(
+            "+390212345678",
+            PhoneNumber::parse("+39 02 1234-5678").unwrap().as_str()
+        );
+        assert!(PhoneNumber::parse("02 12345678").is_err());
+        assert!(PhoneNumber::parse("+39 123").is_err());
+        assert_eq!(
+            Err(InvalidValueError::InvalidCharacters),
+            PhoneNumber::parse("+39 02 CALL-ME")
+        );
+    }
+
+    #[test]
+    fn parses_an_iso_country_code() {
+        assert_eq!("IT", CountryCode::parse("it").unwrap().as_str());
+        assert!(CountryCode::parse("XX").is_err());
+        assert!(CountryCode::parse("Italy").is_err());
+        assert_eq!("ZZ", CountryCode::parse("zz").unwrap().as_str());
+    }
+
+    #[test]
+    fn parses_a_postal_code_for_its_country() {
+        let us = CountryCode::parse("US").unwrap();
+        let gb = CountryCode::parse("GB").unwrap();
+        let ie = CountryCode::parse("IE").unwrap();
+
+        assert!(PostalCode::parse("12345", &us).is_ok());
+        assert!(PostalCode::parse("12345-6789", &us).is_ok());
+        assert!(PostalCode::parse("1234", &us).is_err());
+        assert_eq!(
+            "SW1A 1AA",
+            PostalCode::parse("sw1a 1aa", &gb).unwrap().as_str()
+        );
+        assert!(PostalCode::parse("12345", &gb).is_err());
+        assert!(PostalCode::parse("D02 X285", &ie).is_ok());
+        assert_eq!(
+            Err(InvalidValueError::InvalidCharacters),
+            PostalCode::parse("D02#X285", &ie)
+        );
+    }
+
+    #[test]
+    fn parses_a_coupon_code() {
+        assert_eq!(
+            "SUMMER-10",
+            CouponCode::parse(" summer-10 ").unwrap().as_str()
+        );
+        assert_eq!(Err(InvalidValueError::Empty), CouponCode::parse(""));
+        assert_eq!(
+            Err(InvalidValueError::InvalidCharacters),
+            CouponCode::parse("10% OFF")
+        );
+    }
+
+    #[test]
+    fn parses_a_currency() {
+        assert_eq!(Currency::Gbp, Currency::parse(" gbp ").unwrap());
+        assert_eq!("USD", Currency::parse("USD").unwrap().to_string());
+        assert_eq!(Err(InvalidValueError::Empty), Currency::parse(""));
+        assert!(matches!(
+            Currency::parse("JPY"),
+            Err(InvalidValueError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn parses_a_sku() {
+        assert_eq!("TS-RED_M", Sku::parse(" ts-red_m ").unwrap().as_str());
+        assert_eq!(Err(InvalidValueError::Empty), Sku::parse(" "));
+        assert_eq!(
+            Err(InvalidValueError::InvalidCharacters),
+            Sku::parse("TS RED")
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use std::env;
+
+use adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository;
+use event_consumer::projections::{all_projections, projector::OutboxProjector};
+use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
+
+const USAGE: &str = "usage: projector catch-up | projector rebuild [projection_name]";
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt::init();
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args).await {
+        Ok(applied) => println!("Projections updated ({} events applied)", applied),
+        Err(error) => println!("Error updating projections: {}", error),
+    }
+}
+
+async fn run(args: &[String]) -> Result<usize, String> {
+    let db_connection_url =
+        env::var("DB_CONNECTION_URL").expect("pass DB_CONNECTION_URL to the script");
+    let pool = create_sqlx_connection_pool(&db_connection_url).await;
+    let projector = OutboxProjector::new(
+        pool.clone(),
+        Box::new(PgOutboxMessageRepository::new(pool)),
+        all_projections(),
+    );
+
+    match args.first().map(String::as_str) {
+        Some("catch-up") => projector.catch_up().await.map_err(|e| e.to_string()),
+        Some("rebuild") => projector
+            .rebuild(args.get(1).map(String::as_str))
+            .await
+            .map_err(|e| e.to_string()),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+pub(crate) async fn create_sqlx_connection_pool(db_connection_url: &str) -> Pool<Postgres> {
+    PgPoolOptions::new()
+        .max_connections(5)
+        .connect(db_connection_url)
+        .await
+        .expect("Error connecting to DB")
+}
@@ -0,0 +1,175 @@
+use std::time::Duration;
+
+use adapters::sqlx::pg_checkout_saga_repository::save_checkout_step;
+use async_trait::async_trait;
+use chrono::Utc;
+use domain::{
+    entities::outbox::{OutboxMessage, OutboxMessageType},
+    gateways::{inventory_gateway::InventoryGatewayError, payment_gateway::PaymentGatewayError},
+    services::{
+        checkout_saga_service::{CheckoutSagaService, CheckoutSagaServiceError},
+        inventory_service::InventoryService,
+        invoice_service::InvoiceService,
+        payment_service::PaymentService,
+    },
+};
+use sqlx::{PgConnection, Pool, Postgres};
+use tracing::{error, info};
+
+use crate::{
+    event_handler::{EventHandler, EventHandlerError},
+    inbox::InboxMessage,
+};
+
+/// Event types the checkout saga advances on.
+pub const CHECKOUT_EVENT_TYPES: [OutboxMessageType; 4] = [
+    OutboxMessageType::OrderPlaced,
+    OutboxMessageType::StockReserved,
+    OutboxMessageType::PaymentAuthorized,
+    OutboxMessageType::OrderConfirmed,
+];
+
+/// Runs the step of the checkout saga triggered by the message and stores it
+/// in the inbox transaction, so that the step is recorded if and only if the
+/// message is. The calls to the inventory and the payment provider can't be
+/// rolled back, but they are idempotent: a message handled again makes them
+/// again with the same outcome.
+pub struct CheckoutSagaHandler {
+    pool: Pool<Postgres>,
+    payment_provider_url: String,
+}
+
+impl CheckoutSagaHandler {
+    pub fn new(pool: Pool<Postgres>, payment_provider_url: String) -> Self {
+        Self {
+            pool,
+            payment_provider_url,
+        }
+    }
+}
+
+#[async_trait]
+impl EventHandler for CheckoutSagaHandler {
+    async fn handle(
+        &self,
+        connection: &mut PgConnection,
+        message: &InboxMessage,
+    ) -> Result<(), EventHandlerError> {
+        let event_type = message
+            .event_type()
+            .parse::<OutboxMessageType>()
+            .map_err(|e| EventHandlerError::PermanentError(e.to_string()))?;
+        let message = OutboxMessage::new(
+            message.id(),
+            event_type,
+            message.event_payload().to_string(),
+            message.created_at(),
+            None,
+        );
+        let step = checkout_saga_service(&self.pool, &self.payment_provider_url)
+            .run(&message)
+            .await
+            .map_err(handler_error)?;
+        if let Some(step) = step {
+            save_checkout_step(connection, &step)
+                .await
+                .map_err(|e| EventHandlerError::TransientError(e.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Errors of the DB, the inventory or the payment provider are worth trying
+/// again; a missing order or saga, or a malformed event, is not.
+fn handler_error(error: CheckoutSagaServiceError) -> EventHandlerError {
+    match error {
+        CheckoutSagaServiceError::SagaNotFoundError
+        | CheckoutSagaServiceError::OrderNotFoundError
+        | CheckoutSagaServiceError::InventoryError(
+            InventoryGatewayError::InsufficientStockError(_)
+            | InventoryGatewayError::ReservationNotFoundError(_),
+        )
+        | CheckoutSagaServiceError::PaymentError(
+            PaymentGatewayError::PaymentDeclinedError(_)
+            | PaymentGatewayError::PaymentRejectedError(_)
+            | PaymentGatewayError::AuthorizationNotFoundError(_),
+        )
+        | CheckoutSagaServiceError::GenericError(_) => {
+            EventHandlerError::PermanentError(error.to_string())
+        }
+        _ => EventHandlerError::TransientError(error.to_string()),
+    }
+}
+
+/// Cancels the checkouts past their deadline every `period`, compensating
+/// the steps they completed. Never returns.
+pub async fn cancel_timed_out_checkouts(
+    pool: Pool<Postgres>,
+    payment_provider_url: String,
+    period: Duration,
+) {
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+        match checkout_saga_service(&pool, &payment_provider_url)
+            .cancel_timed_out(Utc::now())
+            .await
+        {
+            Ok(0) => {}
+            Ok(count) => info!("Cancelled {} timed out checkouts", count),
+            Err(e) => error!("Error cancelling timed out checkouts: {}", e),
+        }
+    }
+}
+
+pub fn checkout_saga_service(
+    pool: &Pool<Postgres>,
+    payment_provider_url: &str,
+) -> CheckoutSagaService {
+    let inventory_service = InventoryService::new(
+        Box::new(adapters::sqlx::pg_inventory_repository::PgInventoryRepository::new(pool.clone())),
+        Box::new(
+            adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(
+                pool.clone(),
+            ),
+        ),
+    );
+    let payment_service = PaymentService::new(
+        Box::new(adapters::sqlx::pg_payment_repository::PgPaymentRepository::new(pool.clone())),
+        Box::new(adapters::sqlx::pg_order_repository::PgOrderRepository::new(
+            pool.clone(),
+        )),
+        Box::new(
+            adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(
+                pool.clone(),
+            ),
+        ),
+        Box::new(
+            adapters::http::http_payment_gateway::HttpPaymentGateway::new(
+                payment_provider_url.to_string(),
+            ),
+        ),
+        InvoiceService::new(Box::new(
+            adapters::sqlx::pg_invoice_repository::PgInvoiceRepository::new(pool.clone()),
+        )),
+    );
+
+    CheckoutSagaService::new(
+        Box::new(
+            adapters::sqlx::pg_checkout_saga_repository::PgCheckoutSagaRepository::new(
+                pool.clone(),
+            ),
+        ),
+        Box::new(adapters::sqlx::pg_order_repository::PgOrderRepository::new(
+            pool.clone(),
+        )),
+        Box::new(
+            adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(
+                pool.clone(),
+            ),
+        ),
+        Box::new(inventory_service),
+        Box::new(payment_service),
+        Box::new(adapters::sqlx::pg_promotion_repository::PgPromotionRepository::new(pool.clone())),
+    )
+}
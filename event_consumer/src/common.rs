@@ -0,0 +1,16 @@
+#[cfg(test)]
+pub mod test {
+    use dotenvy::dotenv;
+    use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
+    use std::env;
+
+    pub(crate) async fn create_sqlx_connection_pool() -> Pool<Postgres> {
+        dotenv().ok();
+        let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set!");
+        PgPoolOptions::new()
+            .max_connections(5)
+            .connect(&db_url)
+            .await
+            .expect("Error connecting to DB")
+    }
+}
@@ -0,0 +1,605 @@
+use std::time::Duration;
+
+use adapters::kafka::{KafkaMessage, EVENT_ID_HEADER};
+use chrono::Utc;
+use rdkafka::{
+    consumer::{CommitMode, Consumer, StreamConsumer},
+    message::Headers,
+    ClientConfig, Message,
+};
+use sqlx::{PgConnection, Pool, Postgres};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::{
+    event_handler::{EventHandlerError, EventHandlerRegistry},
+    inbox::{InboxMessage, PgInboxMessageRepository},
+};
+
+/// Delays before processing again a message that failed with a transient
+/// error.
+const MIN_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+pub enum ConsumerError {
+    ConsumerNotCreatedError(String),
+    MessageNotReceivedError(String),
+    MessageNotParsedError(String),
+    MessageNotStoredError(String),
+    MessageNotHandledError(Uuid, String),
+    TransactionError(String),
+    OffsetNotCommittedError(String),
+}
+
+impl std::fmt::Display for ConsumerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConsumerError::ConsumerNotCreatedError(error) => {
+                write!(f, "Error creating Kafka consumer: {}", error)
+            }
+            ConsumerError::MessageNotReceivedError(error) => {
+                write!(f, "Error receiving message: {}", error)
+            }
+            ConsumerError::MessageNotParsedError(error) => {
+                write!(f, "Error parsing message: {}", error)
+            }
+            ConsumerError::MessageNotStoredError(error) => {
+                write!(f, "Error storing message in inbox: {}", error)
+            }
+            ConsumerError::MessageNotHandledError(id, error) => {
+                write!(f, "Error handling message (id: {}): {}", id, error)
+            }
+            ConsumerError::TransactionError(error) => {
+                write!(f, "Transaction error: {}", error)
+            }
+            ConsumerError::OffsetNotCommittedError(error) => {
+                write!(f, "Error committing offset: {}", error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConsumerError {}
+
+#[derive(Debug, PartialEq)]
+pub enum ProcessingOutcome {
+    Handled,
+    Duplicate,
+    Unhandled,
+    /// A handler failed permanently: the message is kept in the inbox as a
+    /// dead letter.
+    Failed,
+}
+
+/// Stores incoming messages in the inbox and dispatches them to the registered
+/// handlers, all in the same DB transaction. If a handler fails, its writes are
+/// rolled back: the message is stored as failed if the error is permanent, and
+/// not stored at all if it is transient, so that it can be processed again.
+pub struct EventProcessor {
+    pool: Pool<Postgres>,
+    registry: EventHandlerRegistry,
+    inbox_message_repository: PgInboxMessageRepository,
+}
+
+impl EventProcessor {
+    pub fn new(pool: Pool<Postgres>, registry: EventHandlerRegistry) -> Self {
+        Self {
+            pool,
+            registry,
+            inbox_message_repository: PgInboxMessageRepository,
+        }
+    }
+
+    pub async fn process(&self, message: InboxMessage) -> Result<ProcessingOutcome, ConsumerError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ConsumerError::TransactionError(e.to_string()))?;
+
+        let is_new = self
+            .inbox_message_repository
+            .save_if_absent(&mut tx, &message)
+            .await
+            .map_err(|e| ConsumerError::MessageNotStoredError(e.to_string()))?;
+        if !is_new {
+            info!("Skipping duplicate message {}", message.id());
+            tx.rollback()
+                .await
+                .map_err(|e| ConsumerError::TransactionError(e.to_string()))?;
+            return Ok(ProcessingOutcome::Duplicate);
+        }
+
+        let handlers = self.registry.handlers_for(message.event_type());
+        if handlers.is_empty() {
+            warn!(
+                "No handler registered for message {} of type {}",
+                message.id(),
+                message.event_type()
+            );
+        }
+        if let Err(e) = self.handle(&mut tx, &message).await {
+            tx.rollback()
+                .await
+                .map_err(|e| ConsumerError::TransactionError(e.to_string()))?;
+            return self.handling_failed(&message, e).await;
+        }
+
+        self.inbox_message_repository
+            .set_processed(&mut tx, message.id(), Utc::now())
+            .await
+            .map_err(|e| ConsumerError::MessageNotStoredError(e.to_string()))?;
+        tx.commit()
+            .await
+            .map_err(|e| ConsumerError::TransactionError(e.to_string()))?;
+
+        if handlers.is_empty() {
+            Ok(ProcessingOutcome::Unhandled)
+        } else {
+            Ok(ProcessingOutcome::Handled)
+        }
+    }
+
+    /// Processes again the messages stored as failed before the call, and
+    /// returns how many were handled. Only the messages of event types with
+    /// registered handlers are replayed. Those failing permanently again are
+    /// kept as failed with the new error; a transient error stops the replay.
+    pub async fn replay_failed(&self) -> Result<usize, ConsumerError> {
+        let started_at = Utc::now();
+        let event_types = self.registry.event_types();
+        let mut replayed = 0;
+        loop {
+            let mut tx = self
+                .pool
+                .begin()
+                .await
+                .map_err(|e| ConsumerError::TransactionError(e.to_string()))?;
+            let message = match self
+                .inbox_message_repository
+                .find_failed_for_update(&mut tx, &event_types, started_at)
+                .await
+                .map_err(|e| ConsumerError::MessageNotStoredError(e.to_string()))?
+            {
+                Some(message) => message,
+                None => return Ok(replayed),
+            };
+
+            info!("Replaying message {}", message.id());
+            if let Err(e) = self.handle(&mut tx, &message).await {
+                tx.rollback()
+                    .await
+                    .map_err(|e| ConsumerError::TransactionError(e.to_string()))?;
+                self.handling_failed(&message, e).await?;
+                continue;
+            }
+            self.inbox_message_repository
+                .set_processed(&mut tx, message.id(), Utc::now())
+                .await
+                .map_err(|e| ConsumerError::MessageNotStoredError(e.to_string()))?;
+            tx.commit()
+                .await
+                .map_err(|e| ConsumerError::TransactionError(e.to_string()))?;
+            replayed += 1;
+        }
+    }
+
+    async fn handle(
+        &self,
+        connection: &mut PgConnection,
+        message: &InboxMessage,
+    ) -> Result<(), EventHandlerError> {
+        for handler in self.registry.handlers_for(message.event_type()) {
+            handler.handle(connection, message).await?;
+        }
+        Ok(())
+    }
+
+    /// Stores the message as failed if the error is permanent, otherwise
+    /// returns the error so that the message is processed again.
+    async fn handling_failed(
+        &self,
+        message: &InboxMessage,
+        error: EventHandlerError,
+    ) -> Result<ProcessingOutcome, ConsumerError> {
+        let is_transient = error.is_transient();
+        let error = ConsumerError::MessageNotHandledError(message.id(), error.to_string());
+        if is_transient {
+            warn!("{}", error);
+            return Err(error);
+        }
+        error!("{}", error);
+        self.save_as_failed(message, &error.to_string()).await
+    }
+
+    async fn save_as_failed(
+        &self,
+        message: &InboxMessage,
+        error: &str,
+    ) -> Result<ProcessingOutcome, ConsumerError> {
+        let mut connection = self
+            .pool
+            .acquire()
+            .await
+            .map_err(|e| ConsumerError::TransactionError(e.to_string()))?;
+        self.inbox_message_repository
+            .save_as_failed(&mut connection, message, error, Utc::now())
+            .await
+            .map_err(|e| ConsumerError::MessageNotStoredError(e.to_string()))?;
+        Ok(ProcessingOutcome::Failed)
+    }
+}
+
+pub fn create_stream_consumer(
+    bootstrap_servers: &str,
+    group_id: &str,
+    topics: &[&str],
+) -> Result<StreamConsumer, ConsumerError> {
+    let consumer: StreamConsumer = ClientConfig::new()
+        .set("bootstrap.servers", bootstrap_servers)
+        .set("group.id", group_id)
+        .set("enable.auto.commit", "false")
+        .set("auto.offset.reset", "earliest")
+        .create()
+        .map_err(|e| ConsumerError::ConsumerNotCreatedError(e.to_string()))?;
+    consumer
+        .subscribe(topics)
+        .map_err(|e| ConsumerError::ConsumerNotCreatedError(e.to_string()))?;
+    Ok(consumer)
+}
+
+/// Consumes messages until an error occurs.
+///
+/// Offsets are committed only after the handlers' transaction commits. Records
+/// that are malformed or whose handlers fail permanently are skipped, since
+/// they would never succeed: the latter are kept in the inbox as failed. If a
+/// handler fails with a transient error, or the inbox can't be written, the
+/// record is processed again after a delay, doubling up to
+/// `MAX_RETRY_DELAY`, and the following ones wait for it.
+pub async fn consume(
+    consumer: &StreamConsumer,
+    processor: &EventProcessor,
+) -> Result<(), ConsumerError> {
+    loop {
+        let record = consumer
+            .recv()
+            .await
+            .map_err(|e| ConsumerError::MessageNotReceivedError(e.to_string()))?;
+
+        match to_inbox_message(&record) {
+            Ok(message) => {
+                let mut delay = MIN_RETRY_DELAY;
+                loop {
+                    match processor.process(message.clone()).await {
+                        Ok(outcome) => {
+                            info!("Message processed: {:?}", outcome);
+                            break;
+                        }
+                        Err(e) => {
+                            warn!("{}, processing it again in {:?}", e, delay);
+                            tokio::time::sleep(delay).await;
+                            delay = (delay * 2).min(MAX_RETRY_DELAY);
+                        }
+                    }
+                }
+            }
+            Err(e) => error!("Skipping malformed record: {}", e),
+        }
+
+        consumer
+            .commit_message(&record, CommitMode::Async)
+            .map_err(|e| ConsumerError::OffsetNotCommittedError(e.to_string()))?;
+    }
+}
+
+/// Builds an inbox message from a record published by the outbox processor,
+/// preferring the `event_id` header over the id in the envelope.
+pub fn to_inbox_message(record: &impl Message) -> Result<InboxMessage, ConsumerError> {
+    let payload = record
+        .payload()
+        .ok_or_else(|| ConsumerError::MessageNotParsedError("Empty payload".to_string()))?;
+    let kafka_message: KafkaMessage = serde_json::from_slice(payload)
+        .map_err(|e| ConsumerError::MessageNotParsedError(e.to_string()))?;
+
+    let header_event_id = record.headers().and_then(|headers| {
+        headers
+            .iter()
+            .find(|header| header.key == EVENT_ID_HEADER)
+            .and_then(|header| header.value)
+            .and_then(|value| std::str::from_utf8(value).ok())
+            .map(str::to_string)
+    });
+    let event_id = header_event_id.unwrap_or(kafka_message.event_id);
+    let id = Uuid::try_parse(&event_id)
+        .map_err(|e| ConsumerError::MessageNotParsedError(e.to_string()))?;
+    let received_at = Utc::now();
+
+    Ok(InboxMessage::new(
+        id,
+        kafka_message.event_type,
+        kafka_message.event_payload,
+        kafka_message.created_at.unwrap_or(received_at),
+        received_at,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use async_trait::async_trait;
+    use domain::entities::outbox::{CustomerCreatedEvent, OutboxMessageType};
+    use rdkafka::{
+        message::{Header, OwnedHeaders, OwnedMessage},
+        Timestamp,
+    };
+    use sqlx::{PgConnection, Row};
+
+    use crate::{
+        common::test,
+        event_handler::{EventHandlerError, TypedEventHandler},
+    };
+
+    use super::*;
+
+    struct RecordingHandler;
+
+    #[async_trait]
+    impl TypedEventHandler for RecordingHandler {
+        type Event = CustomerCreatedEvent;
+
+        async fn handle(
+            &self,
+            connection: &mut PgConnection,
+            event: CustomerCreatedEvent,
+        ) -> Result<(), EventHandlerError> {
+            sqlx::query("SELECT $1")
+                .bind(event.id)
+                .execute(connection)
+                .await
+                .map_err(|e| EventHandlerError::TransientError(e.to_string()))?;
+            Ok(())
+        }
+    }
+
+    struct FailingHandler;
+
+    #[async_trait]
+    impl TypedEventHandler for FailingHandler {
+        type Event = CustomerCreatedEvent;
+
+        async fn handle(
+            &self,
+            _: &mut PgConnection,
+            _: CustomerCreatedEvent,
+        ) -> Result<(), EventHandlerError> {
+            Err(EventHandlerError::PermanentError(
+                "Handling went wrong :(".to_string(),
+            ))
+        }
+    }
+
+    /// Fails with a transient error the first time only.
+    #[derive(Default)]
+    struct FlakyHandler {
+        failed: AtomicBool,
+    }
+
+    #[async_trait]
+    impl TypedEventHandler for FlakyHandler {
+        type Event = CustomerCreatedEvent;
+
+        async fn handle(
+            &self,
+            _: &mut PgConnection,
+            _: CustomerCreatedEvent,
+        ) -> Result<(), EventHandlerError> {
+            if self.failed.swap(true, Ordering::SeqCst) {
+                Ok(())
+            } else {
+                Err(EventHandlerError::TransientError(
+                    "DB went away".to_string(),
+                ))
+            }
+        }
+    }
+
+    /// Only the messages replayed by `replays_failed_messages` are of this
+    /// type, so that it doesn't replay those of other tests.
+    const REPLAYED_EVENT_TYPE: &str = "replayed_in_test";
+
+    #[tokio::test]
+    async fn handles_a_message_once() {
+        let processor = EventProcessor::new(
+            test::create_sqlx_connection_pool().await,
+            EventHandlerRegistry::new()
+                .register(OutboxMessageType::CustomerCreated, RecordingHandler),
+        );
+        let message = customer_created_message();
+
+        let first = processor.process(message.clone()).await.unwrap();
+        let second = processor.process(message).await.unwrap();
+
+        assert_eq!(ProcessingOutcome::Handled, first);
+        assert_eq!(ProcessingOutcome::Duplicate, second);
+    }
+
+    #[tokio::test]
+    async fn stores_messages_without_handlers() {
+        let processor = EventProcessor::new(
+            test::create_sqlx_connection_pool().await,
+            EventHandlerRegistry::new(),
+        );
+
+        let outcome = processor.process(customer_created_message()).await.unwrap();
+
+        assert_eq!(ProcessingOutcome::Unhandled, outcome);
+    }
+
+    #[tokio::test]
+    async fn stores_a_message_as_failed_if_its_handler_fails() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let processor = EventProcessor::new(
+            pool.clone(),
+            EventHandlerRegistry::new()
+                .register(OutboxMessageType::CustomerCreated, RecordingHandler)
+                .register(OutboxMessageType::CustomerCreated, FailingHandler),
+        );
+        let message = customer_created_message();
+
+        let first = processor.process(message.clone()).await.unwrap();
+        let second = processor.process(message.clone()).await.unwrap();
+
+        assert_eq!(ProcessingOutcome::Failed, first);
+        assert_eq!(ProcessingOutcome::Duplicate, second);
+        let row = sqlx::query("SELECT processed_at IS NULL, failed_at IS NOT NULL, error FROM inbox_messages WHERE id = $1")
+            .bind(message.id())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(row.get::<bool, _>(0));
+        assert!(row.get::<bool, _>(1));
+        assert!(row.get::<String, _>(2).contains("Handling went wrong"));
+    }
+
+    #[tokio::test]
+    async fn processes_a_message_again_after_a_transient_error() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let processor = EventProcessor::new(
+            pool.clone(),
+            EventHandlerRegistry::new()
+                .register(OutboxMessageType::CustomerCreated, FlakyHandler::default()),
+        );
+        let message = customer_created_message();
+
+        let first = processor.process(message.clone()).await;
+        let stored = sqlx::query("SELECT 1 FROM inbox_messages WHERE id = $1")
+            .bind(message.id())
+            .fetch_optional(&pool)
+            .await
+            .unwrap();
+        let second = processor.process(message).await.unwrap();
+
+        assert!(matches!(
+            first,
+            Err(ConsumerError::MessageNotHandledError(..))
+        ));
+        assert!(stored.is_none());
+        assert_eq!(ProcessingOutcome::Handled, second);
+    }
+
+    #[tokio::test]
+    async fn replays_failed_messages() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let failing = EventProcessor::new(
+            pool.clone(),
+            EventHandlerRegistry::new().register(REPLAYED_EVENT_TYPE, FailingHandler),
+        );
+        let processor = EventProcessor::new(
+            pool.clone(),
+            EventHandlerRegistry::new().register(REPLAYED_EVENT_TYPE, RecordingHandler),
+        );
+        let customer_created = customer_created_message();
+        let message = InboxMessage::new(
+            customer_created.id(),
+            REPLAYED_EVENT_TYPE.to_string(),
+            customer_created.event_payload().to_string(),
+            Utc::now(),
+            Utc::now(),
+        );
+
+        let outcome = failing.process(message.clone()).await.unwrap();
+        let replayed_while_failing = failing.replay_failed().await.unwrap();
+        let replayed = processor.replay_failed().await.unwrap();
+
+        assert_eq!(ProcessingOutcome::Failed, outcome);
+        assert_eq!(0, replayed_while_failing);
+        assert!(replayed >= 1);
+        let row = sqlx::query(
+            "SELECT processed_at IS NOT NULL, failed_at IS NULL, error IS NULL FROM inbox_messages WHERE id = $1",
+        )
+        .bind(message.id())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(row.get::<bool, _>(0));
+        assert!(row.get::<bool, _>(1));
+        assert!(row.get::<bool, _>(2));
+        assert_eq!(
+            ProcessingOutcome::Duplicate,
+            processor.process(message).await.unwrap()
+        );
+    }
+
+    #[test]
+    fn reads_event_id_from_header() {
+        let header_event_id = Uuid::new_v4();
+        let record = kafka_record(
+            &Uuid::new_v4().to_string(),
+            Some(OwnedHeaders::new().insert(Header {
+                key: EVENT_ID_HEADER,
+                value: Some(&header_event_id.to_string()),
+            })),
+        );
+
+        let message = to_inbox_message(&record).unwrap();
+
+        assert_eq!(header_event_id, message.id());
+        assert_eq!("customer_created", message.event_type());
+    }
+
+    #[test]
+    fn falls_back_to_event_id_in_envelope() {
+        let event_id = Uuid::new_v4();
+        let record = kafka_record(&event_id.to_string(), None);
+
+        let message = to_inbox_message(&record).unwrap();
+
+        assert_eq!(event_id, message.id());
+    }
+
+    #[test]
+    fn rejects_malformed_records() {
+        let record = kafka_record("not a uuid", None);
+
+        assert!(matches!(
+            to_inbox_message(&record),
+            Err(ConsumerError::MessageNotParsedError(_))
+        ));
+    }
+
+    fn customer_created_message() -> InboxMessage {
+        InboxMessage::new(
+            Uuid::new_v4(),
+            OutboxMessageType::CustomerCreated.to_string(),
+            serde_json::to_string(&CustomerCreatedEvent {
+                id: Uuid::new_v4().to_string(),
+                first_name: "Mario".to_string(),
+                last_name: "Rossi".to_string(),
+                email: "mario.rossi@example.com".to_string(),
+            })
+            .unwrap(),
+            Utc::now(),
+            Utc::now(),
+        )
+    }
+
+    fn kafka_record(event_id: &str, headers: Option<OwnedHeaders>) -> OwnedMessage {
+        let payload = serde_json::to_vec(&KafkaMessage {
+            event_id: event_id.to_string(),
+            event_type: OutboxMessageType::CustomerCreated.to_string(),
+            event_payload: "{}".to_string(),
+            created_at: None,
+        })
+        .unwrap();
+        OwnedMessage::new(
+            Some(payload),
+            None,
+            "topic".to_string(),
+            Timestamp::NotAvailable,
+            0,
+            0,
+            headers,
+        )
+    }
+}
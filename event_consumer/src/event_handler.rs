@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use sqlx::PgConnection;
+
+use crate::inbox::InboxMessage;
+
+/// A transient error, such as a DB or network one, may go away if the message
+/// is handled again later; a permanent one, such as a malformed payload, won't.
+#[derive(Debug)]
+pub enum EventHandlerError {
+    TransientError(String),
+    PermanentError(String),
+}
+
+impl EventHandlerError {
+    pub fn is_transient(&self) -> bool {
+        matches!(self, EventHandlerError::TransientError(_))
+    }
+}
+
+impl std::fmt::Display for EventHandlerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventHandlerError::TransientError(error) => {
+                write!(f, "Transient error handling event: {}", error)
+            }
+            EventHandlerError::PermanentError(error) => {
+                write!(f, "Error handling event: {}", error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EventHandlerError {}
+
+/// Handles a received message inside the transaction that records it in the
+/// inbox.
+#[async_trait]
+pub trait EventHandler: Send + Sync {
+    async fn handle(
+        &self,
+        connection: &mut PgConnection,
+        message: &InboxMessage,
+    ) -> Result<(), EventHandlerError>;
+}
+
+/// Handles an event deserialized into `Self::Event`.
+#[async_trait]
+pub trait TypedEventHandler: Send + Sync {
+    type Event: DeserializeOwned + Send;
+
+    async fn handle(
+        &self,
+        connection: &mut PgConnection,
+        event: Self::Event,
+    ) -> Result<(), EventHandlerError>;
+}
+
+struct TypedEventHandlerAdapter<H>(H);
+
+#[async_trait]
+impl<H: TypedEventHandler> EventHandler for TypedEventHandlerAdapter<H> {
+    async fn handle(
+        &self,
+        connection: &mut PgConnection,
+        message: &InboxMessage,
+    ) -> Result<(), EventHandlerError> {
+        let event = serde_json::from_str::<H::Event>(message.event_payload()).map_err(|e| {
+            EventHandlerError::PermanentError(format!("Payload deserialization error: {}", e))
+        })?;
+        self.0.handle(connection, event).await
+    }
+}
+
+#[derive(Default)]
+pub struct EventHandlerRegistry {
+    handlers: HashMap<String, Vec<Box<dyn EventHandler>>>,
+}
+
+impl EventHandlerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `handler` for events of `event_type`. Several handlers can be
+    /// registered for the same type: they run in registration order.
+    pub fn register<H>(self, event_type: impl ToString, handler: H) -> Self
+    where
+        H: TypedEventHandler + 'static,
+    {
+        self.register_handler(event_type, Box::new(TypedEventHandlerAdapter(handler)))
+    }
+
+    /// Like `register`, for handlers working on the raw message.
+    pub fn register_handler(
+        mut self,
+        event_type: impl ToString,
+        handler: Box<dyn EventHandler>,
+    ) -> Self {
+        self.handlers
+            .entry(event_type.to_string())
+            .or_default()
+            .push(handler);
+        self
+    }
+
+    pub fn event_types(&self) -> Vec<String> {
+        self.handlers.keys().cloned().collect()
+    }
+
+    pub fn handlers_for(&self, event_type: &str) -> &[Box<dyn EventHandler>] {
+        self.handlers
+            .get(event_type)
+            .map(|handlers| handlers.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use domain::entities::outbox::{CustomerCreatedEvent, OutboxMessageType};
+
+    use super::*;
+
+    struct NoopHandler;
+
+    #[async_trait]
+    impl TypedEventHandler for NoopHandler {
+        type Event = CustomerCreatedEvent;
+
+        async fn handle(
+            &self,
+            _: &mut PgConnection,
+            _: CustomerCreatedEvent,
+        ) -> Result<(), EventHandlerError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn finds_handlers_registered_for_an_event_type() {
+        let registry = EventHandlerRegistry::new()
+            .register(OutboxMessageType::CustomerCreated, NoopHandler)
+            .register(OutboxMessageType::CustomerCreated, NoopHandler);
+
+        assert_eq!(2, registry.handlers_for("customer_created").len());
+        assert!(registry.handlers_for("order_created").is_empty());
+    }
+}
@@ -0,0 +1,410 @@
+use async_trait::async_trait;
+use domain::entities::outbox::{
+    CouponAppliedEvent, CustomerAddressBookChangedEvent, CustomerAddressChangedEvent,
+    CustomerCreatedEvent, CustomerDeactivatedEvent, CustomerErasedEvent, CustomerUpdatedEvent,
+    OrderCancelledEvent, OrderConfirmedEvent, OrderCreatedEvent, OrderPlacedEvent,
+    OrderShippedEvent, PaymentAuthorizedEvent, PaymentFailedEvent, ProductAddedToOrderEvent,
+    ProductQuantityChangedEvent, ProductRemovedFromOrderEvent, ReturnEvent, ReviewSubmittedEvent,
+    ShipmentDeliveredEvent, StockDepletedEvent, StockReservedEvent,
+};
+use sqlx::PgConnection;
+use tracing::info;
+
+use crate::event_handler::{EventHandlerError, TypedEventHandler};
+
+pub struct CustomerCreatedLogger;
+
+#[async_trait]
+impl TypedEventHandler for CustomerCreatedLogger {
+    type Event = CustomerCreatedEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: CustomerCreatedEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!("Customer {} created", event.id);
+        Ok(())
+    }
+}
+
+pub struct CustomerUpdatedLogger;
+
+#[async_trait]
+impl TypedEventHandler for CustomerUpdatedLogger {
+    type Event = CustomerUpdatedEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: CustomerUpdatedEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!("Customer {} updated", event.id);
+        Ok(())
+    }
+}
+
+pub struct CustomerAddressChangedLogger;
+
+#[async_trait]
+impl TypedEventHandler for CustomerAddressChangedLogger {
+    type Event = CustomerAddressChangedEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: CustomerAddressChangedEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!("Customer {} changed address", event.id);
+        Ok(())
+    }
+}
+
+pub struct CustomerAddressBookChangedLogger;
+
+#[async_trait]
+impl TypedEventHandler for CustomerAddressBookChangedLogger {
+    type Event = CustomerAddressBookChangedEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: CustomerAddressBookChangedEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!(
+            "Customer {} now has {} addresses",
+            event.id,
+            event.addresses.len()
+        );
+        Ok(())
+    }
+}
+
+pub struct CustomerDeactivatedLogger;
+
+#[async_trait]
+impl TypedEventHandler for CustomerDeactivatedLogger {
+    type Event = CustomerDeactivatedEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: CustomerDeactivatedEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!("Customer {} deactivated", event.id);
+        Ok(())
+    }
+}
+
+pub struct CustomerErasedLogger;
+
+#[async_trait]
+impl TypedEventHandler for CustomerErasedLogger {
+    type Event = CustomerErasedEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: CustomerErasedEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!("Customer {} erased", event.id);
+        Ok(())
+    }
+}
+
+pub struct OrderCreatedLogger;
+
+#[async_trait]
+impl TypedEventHandler for OrderCreatedLogger {
+    type Event = OrderCreatedEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: OrderCreatedEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!(
+            "Order {} created for customer {}",
+            event.id, event.customer_id
+        );
+        Ok(())
+    }
+}
+
+pub struct ProductAddedToOrderLogger;
+
+#[async_trait]
+impl TypedEventHandler for ProductAddedToOrderLogger {
+    type Event = ProductAddedToOrderEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: ProductAddedToOrderEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!(
+            "Product {} added to order {}",
+            event.product_id, event.order_id
+        );
+        Ok(())
+    }
+}
+
+pub struct ProductRemovedFromOrderLogger;
+
+#[async_trait]
+impl TypedEventHandler for ProductRemovedFromOrderLogger {
+    type Event = ProductRemovedFromOrderEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: ProductRemovedFromOrderEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!(
+            "Product {} removed from order {}",
+            event.product_id, event.order_id
+        );
+        Ok(())
+    }
+}
+
+pub struct ProductQuantityChangedLogger;
+
+#[async_trait]
+impl TypedEventHandler for ProductQuantityChangedLogger {
+    type Event = ProductQuantityChangedEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: ProductQuantityChangedEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!(
+            "Quantity of product {} in order {} changed from {} to {}",
+            event.product_id, event.order_id, event.previous_quantity, event.quantity
+        );
+        Ok(())
+    }
+}
+
+pub struct OrderPlacedLogger;
+
+#[async_trait]
+impl TypedEventHandler for OrderPlacedLogger {
+    type Event = OrderPlacedEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: OrderPlacedEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!("Order {} placed for {}", event.order_id, event.total_price);
+        Ok(())
+    }
+}
+
+pub struct StockReservedLogger;
+
+#[async_trait]
+impl TypedEventHandler for StockReservedLogger {
+    type Event = StockReservedEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: StockReservedEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!("Stock reserved for order {}", event.order_id);
+        Ok(())
+    }
+}
+
+pub struct PaymentAuthorizedLogger;
+
+#[async_trait]
+impl TypedEventHandler for PaymentAuthorizedLogger {
+    type Event = PaymentAuthorizedEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: PaymentAuthorizedEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!(
+            "Payment of {} authorized for order {}",
+            event.amount, event.order_id
+        );
+        Ok(())
+    }
+}
+
+pub struct OrderConfirmedLogger;
+
+#[async_trait]
+impl TypedEventHandler for OrderConfirmedLogger {
+    type Event = OrderConfirmedEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: OrderConfirmedEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!("Order {} confirmed", event.order_id);
+        Ok(())
+    }
+}
+
+pub struct OrderCancelledLogger;
+
+#[async_trait]
+impl TypedEventHandler for OrderCancelledLogger {
+    type Event = OrderCancelledEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: OrderCancelledEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!("Order {} cancelled: {}", event.order_id, event.reason);
+        Ok(())
+    }
+}
+
+pub struct StockDepletedLogger;
+
+#[async_trait]
+impl TypedEventHandler for StockDepletedLogger {
+    type Event = StockDepletedEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: StockDepletedEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!(
+            "Stock of product {} depleted in warehouse {}",
+            event.product_id, event.warehouse_id
+        );
+        Ok(())
+    }
+}
+
+pub struct PaymentFailedLogger;
+
+#[async_trait]
+impl TypedEventHandler for PaymentFailedLogger {
+    type Event = PaymentFailedEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: PaymentFailedEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!(
+            "Payment of {} for order {} failed: {}",
+            event.amount, event.order_id, event.reason
+        );
+        Ok(())
+    }
+}
+
+/// Logs every change of state of a return.
+pub struct ReturnLogger;
+
+#[async_trait]
+impl TypedEventHandler for ReturnLogger {
+    type Event = ReturnEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: ReturnEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!(
+            "Return {} of order {} {}, refund amount {}",
+            event.return_id, event.order_id, event.state, event.refund_amount
+        );
+        Ok(())
+    }
+}
+
+pub struct CouponAppliedLogger;
+
+#[async_trait]
+impl TypedEventHandler for CouponAppliedLogger {
+    type Event = CouponAppliedEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: CouponAppliedEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!(
+            "Coupon {} applied to order {}, new total {}",
+            event.promotion.code, event.order_id, event.pricing.total
+        );
+        Ok(())
+    }
+}
+
+pub struct OrderShippedLogger;
+
+#[async_trait]
+impl TypedEventHandler for OrderShippedLogger {
+    type Event = OrderShippedEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: OrderShippedEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!(
+            "Shipment {} of order {} handed to {} with tracking number {}, fully shipped {}",
+            event.shipment_id,
+            event.order_id,
+            event.carrier,
+            event.tracking_number,
+            event.fully_shipped
+        );
+        Ok(())
+    }
+}
+
+pub struct ShipmentDeliveredLogger;
+
+#[async_trait]
+impl TypedEventHandler for ShipmentDeliveredLogger {
+    type Event = ShipmentDeliveredEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: ShipmentDeliveredEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!(
+            "Shipment {} of order {} delivered by {}",
+            event.shipment_id, event.order_id, event.carrier
+        );
+        Ok(())
+    }
+}
+
+pub struct ReviewSubmittedLogger;
+
+#[async_trait]
+impl TypedEventHandler for ReviewSubmittedLogger {
+    type Event = ReviewSubmittedEvent;
+
+    async fn handle(
+        &self,
+        _: &mut PgConnection,
+        event: ReviewSubmittedEvent,
+    ) -> Result<(), EventHandlerError> {
+        info!(
+            "Review {} of product {} submitted with rating {}",
+            event.review_id, event.product_id, event.rating
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,236 @@
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::PgRow, PgConnection, Row};
+use uuid::Uuid;
+
+#[derive(Debug)]
+pub enum InboxMessageRepositoryError {
+    InboxMessageNotSavedError(String),
+    InboxMessageNotReadError(String),
+}
+
+impl std::fmt::Display for InboxMessageRepositoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InboxMessageRepositoryError::InboxMessageNotSavedError(message) => {
+                write!(f, "Inbox message not saved error: {}", message)
+            }
+            InboxMessageRepositoryError::InboxMessageNotReadError(message) => {
+                write!(f, "Inbox message not read error: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InboxMessageRepositoryError {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct InboxMessage {
+    id: Uuid,
+    event_type: String,
+    event_payload: String,
+    created_at: DateTime<Utc>,
+    received_at: DateTime<Utc>,
+}
+
+impl InboxMessage {
+    pub fn new(
+        id: Uuid,
+        event_type: String,
+        event_payload: String,
+        created_at: DateTime<Utc>,
+        received_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id,
+            event_type,
+            event_payload,
+            created_at,
+            received_at,
+        }
+    }
+
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    pub fn event_type(&self) -> &str {
+        &self.event_type
+    }
+
+    pub fn event_payload(&self) -> &str {
+        &self.event_payload
+    }
+
+    /// When the event was stored in the producer's outbox.
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    pub fn received_at(&self) -> DateTime<Utc> {
+        self.received_at
+    }
+}
+
+/// Stores received messages in the `inbox_messages` table.
+///
+/// Methods take the connection of the transaction the message is handled in,
+/// so that a message is recorded only if its handler succeeds.
+pub struct PgInboxMessageRepository;
+
+impl PgInboxMessageRepository {
+    /// Returns `false` if a message with the same id was already received.
+    pub async fn save_if_absent(
+        &self,
+        connection: &mut PgConnection,
+        message: &InboxMessage,
+    ) -> Result<bool, InboxMessageRepositoryError> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO inbox_messages (id, event_type, event_payload, created_at, received_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (id) DO NOTHING
+        "#,
+        )
+        .bind(message.id())
+        .bind(message.event_type())
+        .bind(message.event_payload())
+        .bind(message.created_at())
+        .bind(message.received_at())
+        .execute(connection)
+        .await
+        .map_err(|error| {
+            InboxMessageRepositoryError::InboxMessageNotSavedError(error.to_string())
+        })?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Also clears the failure of a message replayed successfully.
+    pub async fn set_processed(
+        &self,
+        connection: &mut PgConnection,
+        message_id: Uuid,
+        processed_at: DateTime<Utc>,
+    ) -> Result<(), InboxMessageRepositoryError> {
+        sqlx::query(
+            "UPDATE inbox_messages SET processed_at = $2, failed_at = NULL, error = NULL WHERE id = $1",
+        )
+            .bind(message_id)
+            .bind(Some(processed_at))
+            .execute(connection)
+            .await
+            .map_err(|error| {
+                InboxMessageRepositoryError::InboxMessageNotSavedError(error.to_string())
+            })?;
+
+        Ok(())
+    }
+
+    /// Records a message whose handlers failed as a dead letter, so that it
+    /// is not handled again when it is received once more, until it is
+    /// replayed. A dead letter failing again gets the new error.
+    pub async fn save_as_failed(
+        &self,
+        connection: &mut PgConnection,
+        message: &InboxMessage,
+        error: &str,
+        failed_at: DateTime<Utc>,
+    ) -> Result<(), InboxMessageRepositoryError> {
+        sqlx::query(
+            r#"
+            INSERT INTO inbox_messages (
+                id, event_type, event_payload, created_at, received_at, failed_at, error
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (id) DO UPDATE
+            SET failed_at = EXCLUDED.failed_at, error = EXCLUDED.error
+            WHERE inbox_messages.processed_at IS NULL
+        "#,
+        )
+        .bind(message.id())
+        .bind(message.event_type())
+        .bind(message.event_payload())
+        .bind(message.created_at())
+        .bind(message.received_at())
+        .bind(failed_at)
+        .bind(error)
+        .execute(connection)
+        .await
+        .map_err(|error| {
+            InboxMessageRepositoryError::InboxMessageNotSavedError(error.to_string())
+        })?;
+
+        Ok(())
+    }
+
+    /// Returns a dead letter of one of `event_types` that last failed before
+    /// `failed_before` and locks it until the end of the transaction; dead letters locked by
+    /// other transactions are skipped.
+    pub async fn find_failed_for_update(
+        &self,
+        connection: &mut PgConnection,
+        event_types: &[String],
+        failed_before: DateTime<Utc>,
+    ) -> Result<Option<InboxMessage>, InboxMessageRepositoryError> {
+        sqlx::query(
+            r#"
+            SELECT id, event_type, event_payload, created_at, received_at
+            FROM inbox_messages
+            WHERE processed_at IS NULL AND failed_at < $2 AND event_type = ANY($1)
+            ORDER BY received_at
+            LIMIT 1
+            FOR UPDATE SKIP LOCKED
+        "#,
+        )
+        .bind(event_types)
+        .bind(failed_before)
+        .try_map(|row: PgRow| {
+            Ok(InboxMessage::new(
+                row.try_get("id")?,
+                row.try_get("event_type")?,
+                row.try_get("event_payload")?,
+                row.try_get("created_at")?,
+                row.try_get("received_at")?,
+            ))
+        })
+        .fetch_optional(connection)
+        .await
+        .map_err(|error| InboxMessageRepositoryError::InboxMessageNotReadError(error.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use crate::{
+        common::test,
+        inbox::{InboxMessage, PgInboxMessageRepository},
+    };
+
+    #[tokio::test]
+    async fn saves_a_message_only_once() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let mut connection = pool.acquire().await.unwrap();
+        let message = InboxMessage::new(
+            Uuid::new_v4(),
+            "customer_created".to_string(),
+            "{}".to_string(),
+            Utc::now(),
+            Utc::now(),
+        );
+
+        let first = PgInboxMessageRepository
+            .save_if_absent(&mut connection, &message)
+            .await
+            .unwrap();
+        let second = PgInboxMessageRepository
+            .save_if_absent(&mut connection, &message)
+            .await
+            .unwrap();
+
+        assert!(first);
+        assert!(!second);
+    }
+}
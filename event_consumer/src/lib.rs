@@ -0,0 +1,7 @@
+pub mod checkout;
+mod common;
+pub mod consumer;
+pub mod event_handler;
+pub mod handlers;
+pub mod inbox;
+pub mod projections;
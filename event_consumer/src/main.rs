@@ -0,0 +1,177 @@
+use std::{env, time::Duration};
+
+use domain::entities::outbox::OutboxMessageType;
+use event_consumer::{
+    checkout::{cancel_timed_out_checkouts, CheckoutSagaHandler, CHECKOUT_EVENT_TYPES},
+    consumer::{consume, create_stream_consumer, EventProcessor},
+    event_handler::EventHandlerRegistry,
+    handlers::{
+        CouponAppliedLogger, CustomerAddressBookChangedLogger, CustomerAddressChangedLogger,
+        CustomerCreatedLogger, CustomerDeactivatedLogger, CustomerErasedLogger,
+        CustomerUpdatedLogger, OrderCancelledLogger, OrderConfirmedLogger, OrderCreatedLogger,
+        OrderPlacedLogger, OrderShippedLogger, PaymentAuthorizedLogger, PaymentFailedLogger,
+        ProductAddedToOrderLogger, ProductQuantityChangedLogger, ProductRemovedFromOrderLogger,
+        ReturnLogger, ReviewSubmittedLogger, ShipmentDeliveredLogger, StockDepletedLogger,
+        StockReservedLogger,
+    },
+    projections::{all_projections, handler::ProjectionEventHandler},
+};
+use sqlx::{postgres::PgPoolOptions, Pool, Postgres};
+
+/// How often checkouts past their deadline are looked for.
+const CHECKOUT_TIMEOUT_CHECK_PERIOD: Duration = Duration::from_secs(60);
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt::init();
+    match env::args().nth(1).as_deref() {
+        Some("replay-failed") => match replay_failed_events().await {
+            Ok(replayed) => println!("Failed events replayed ({} handled)", replayed),
+            Err(error) => println!("Error replaying failed events: {}", error),
+        },
+        _ => {
+            println!("Consuming events");
+            match consume_events().await {
+                Ok(_) => println!("Events consumed"),
+                Err(error) => println!("Error consuming events: {}", error),
+            }
+        }
+    }
+}
+
+async fn consume_events() -> Result<(), String> {
+    let db_connection_url =
+        env::var("DB_CONNECTION_URL").expect("pass DB_CONNECTION_URL to the script");
+    let bootstrap_servers =
+        env::var("BOOTSTRAP_SERVERS").expect("pass BOOTSTRAP_SERVERS to the script");
+    let group_id = env::var("GROUP_ID").expect("pass GROUP_ID to the script");
+    let topics = env::var("TOPICS").expect("pass TOPICS (comma separated) to the script");
+    let topics: Vec<&str> = topics.split(',').map(str::trim).collect();
+
+    let pool = create_sqlx_connection_pool(&db_connection_url).await;
+    let payment_provider_url = env::var("PAYMENT_PROVIDER_URL").ok();
+    if let Some(payment_provider_url) = &payment_provider_url {
+        tokio::spawn(cancel_timed_out_checkouts(
+            pool.clone(),
+            payment_provider_url.clone(),
+            CHECKOUT_TIMEOUT_CHECK_PERIOD,
+        ));
+    }
+    let registry = create_registry(&pool, payment_provider_url);
+    let processor = EventProcessor::new(pool, registry);
+    let consumer = create_stream_consumer(&bootstrap_servers, &group_id, &topics)
+        .map_err(|e| e.to_string())?;
+
+    consume(&consumer, &processor)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn replay_failed_events() -> Result<usize, String> {
+    let db_connection_url =
+        env::var("DB_CONNECTION_URL").expect("pass DB_CONNECTION_URL to the script");
+
+    let pool = create_sqlx_connection_pool(&db_connection_url).await;
+    let registry = create_registry(&pool, env::var("PAYMENT_PROVIDER_URL").ok());
+    EventProcessor::new(pool, registry)
+        .replay_failed()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Registers the checkout saga only with a payment provider to call, and the
+/// projections only if `PROJECT_READ_MODELS` is `true`.
+fn create_registry(
+    pool: &Pool<Postgres>,
+    payment_provider_url: Option<String>,
+) -> EventHandlerRegistry {
+    let project_read_models = env::var("PROJECT_READ_MODELS").is_ok_and(|v| v == "true");
+
+    let mut registry = EventHandlerRegistry::new()
+        .register(OutboxMessageType::CustomerCreated, CustomerCreatedLogger)
+        .register(OutboxMessageType::CustomerUpdated, CustomerUpdatedLogger)
+        .register(
+            OutboxMessageType::CustomerAddressChanged,
+            CustomerAddressChangedLogger,
+        )
+        .register(
+            OutboxMessageType::CustomerAddressBookChanged,
+            CustomerAddressBookChangedLogger,
+        )
+        .register(
+            OutboxMessageType::CustomerDeactivated,
+            CustomerDeactivatedLogger,
+        )
+        .register(OutboxMessageType::CustomerErased, CustomerErasedLogger)
+        .register(OutboxMessageType::OrderCreated, OrderCreatedLogger)
+        .register(
+            OutboxMessageType::ProductAddedToOrder,
+            ProductAddedToOrderLogger,
+        )
+        .register(
+            OutboxMessageType::ProductRemovedFromOrder,
+            ProductRemovedFromOrderLogger,
+        )
+        .register(
+            OutboxMessageType::ProductQuantityChanged,
+            ProductQuantityChangedLogger,
+        )
+        .register(OutboxMessageType::OrderPlaced, OrderPlacedLogger)
+        .register(OutboxMessageType::StockReserved, StockReservedLogger)
+        .register(
+            OutboxMessageType::PaymentAuthorized,
+            PaymentAuthorizedLogger,
+        )
+        .register(OutboxMessageType::OrderConfirmed, OrderConfirmedLogger)
+        .register(OutboxMessageType::OrderCancelled, OrderCancelledLogger)
+        .register(OutboxMessageType::StockDepleted, StockDepletedLogger)
+        .register(OutboxMessageType::PaymentFailed, PaymentFailedLogger)
+        .register(OutboxMessageType::ReturnRequested, ReturnLogger)
+        .register(OutboxMessageType::ReturnApproved, ReturnLogger)
+        .register(OutboxMessageType::ReturnRejected, ReturnLogger)
+        .register(OutboxMessageType::ReturnReceived, ReturnLogger)
+        .register(OutboxMessageType::ReturnRefunded, ReturnLogger)
+        .register(OutboxMessageType::CouponApplied, CouponAppliedLogger)
+        .register(OutboxMessageType::OrderShipped, OrderShippedLogger)
+        .register(
+            OutboxMessageType::ShipmentDelivered,
+            ShipmentDeliveredLogger,
+        )
+        .register(OutboxMessageType::ReviewSubmitted, ReviewSubmittedLogger);
+    if let Some(payment_provider_url) = payment_provider_url {
+        for event_type in CHECKOUT_EVENT_TYPES {
+            registry = registry.register_handler(
+                event_type,
+                Box::new(CheckoutSagaHandler::new(
+                    pool.clone(),
+                    payment_provider_url.clone(),
+                )),
+            );
+        }
+    }
+    if project_read_models {
+        for event_type in [
+            OutboxMessageType::CustomerCreated,
+            OutboxMessageType::OrderCreated,
+            OutboxMessageType::ProductAddedToOrder,
+            OutboxMessageType::ProductRemovedFromOrder,
+            OutboxMessageType::ProductQuantityChanged,
+        ] {
+            for projection in all_projections() {
+                registry = registry.register_handler(
+                    event_type.clone(),
+                    Box::new(ProjectionEventHandler::new(projection)),
+                );
+            }
+        }
+    }
+    registry
+}
+
+pub(crate) async fn create_sqlx_connection_pool(db_connection_url: &str) -> Pool<Postgres> {
+    PgPoolOptions::new()
+        .max_connections(5)
+        .connect(db_connection_url)
+        .await
+        .expect("Error connecting to DB")
+}
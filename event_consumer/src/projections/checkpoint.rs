@@ -0,0 +1,125 @@
+use chrono::Utc;
+use domain::repositories::outbox_repository::OutboxPosition;
+use sqlx::{postgres::PgRow, PgConnection, Row};
+
+use super::ProjectionError;
+
+/// Position of the last outbox message applied to a projection.
+pub type Checkpoint = OutboxPosition;
+
+pub struct PgProjectionCheckpointRepository;
+
+impl PgProjectionCheckpointRepository {
+    /// Serializes concurrent runs of the same projection until the transaction ends.
+    pub async fn lock(
+        &self,
+        connection: &mut PgConnection,
+        projection_name: &str,
+    ) -> Result<(), ProjectionError> {
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+            .bind(projection_name)
+            .execute(connection)
+            .await
+            .map_err(|e| ProjectionError::CheckpointError(e.to_string()))?;
+        Ok(())
+    }
+
+    pub async fn find(
+        &self,
+        connection: &mut PgConnection,
+        projection_name: &str,
+    ) -> Result<Option<Checkpoint>, ProjectionError> {
+        sqlx::query(
+            "SELECT last_transaction_id, last_position FROM projection_checkpoints WHERE projection_name = $1",
+        )
+        .bind(projection_name)
+        .try_map(|row: PgRow| {
+            Ok(Checkpoint {
+                transaction_id: row.try_get("last_transaction_id")?,
+                position: row.try_get("last_position")?,
+            })
+        })
+        .fetch_optional(connection)
+        .await
+        .map_err(|e| ProjectionError::CheckpointError(e.to_string()))
+    }
+
+    pub async fn save(
+        &self,
+        connection: &mut PgConnection,
+        projection_name: &str,
+        checkpoint: &Checkpoint,
+    ) -> Result<(), ProjectionError> {
+        sqlx::query(
+            r#"
+            INSERT INTO projection_checkpoints (projection_name, last_transaction_id, last_position, updated_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (projection_name) DO UPDATE
+            SET last_transaction_id = EXCLUDED.last_transaction_id,
+                last_position = EXCLUDED.last_position,
+                updated_at = EXCLUDED.updated_at
+        "#,
+        )
+        .bind(projection_name)
+        .bind(checkpoint.transaction_id)
+        .bind(checkpoint.position)
+        .bind(Utc::now())
+        .execute(connection)
+        .await
+        .map_err(|e| ProjectionError::CheckpointError(e.to_string()))?;
+        Ok(())
+    }
+
+    pub async fn delete(
+        &self,
+        connection: &mut PgConnection,
+        projection_name: &str,
+    ) -> Result<(), ProjectionError> {
+        sqlx::query("DELETE FROM projection_checkpoints WHERE projection_name = $1")
+            .bind(projection_name)
+            .execute(connection)
+            .await
+            .map_err(|e| ProjectionError::CheckpointError(e.to_string()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use uuid::Uuid;
+
+    use crate::common::test;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn saves_and_deletes_a_checkpoint() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let mut tx = pool.begin().await.unwrap();
+        let projection_name = format!("test_projection_{}", Uuid::new_v4());
+        let checkpoint = Checkpoint {
+            transaction_id: 42,
+            position: 7,
+        };
+
+        PgProjectionCheckpointRepository
+            .save(&mut tx, &projection_name, &checkpoint)
+            .await
+            .unwrap();
+        let saved = PgProjectionCheckpointRepository
+            .find(&mut tx, &projection_name)
+            .await
+            .unwrap();
+        PgProjectionCheckpointRepository
+            .delete(&mut tx, &projection_name)
+            .await
+            .unwrap();
+        let deleted = PgProjectionCheckpointRepository
+            .find(&mut tx, &projection_name)
+            .await
+            .unwrap();
+
+        assert_eq!(Some(checkpoint), saved);
+        assert_eq!(None, deleted);
+    }
+}
@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use sqlx::PgConnection;
+
+use super::{parse_uuid, DomainEvent, ProjectedEvent, Projection, ProjectionError};
+
+/// Number of orders placed by each customer (`customer_order_counts` table).
+pub struct CustomerOrderCountProjection;
+
+#[async_trait]
+impl Projection for CustomerOrderCountProjection {
+    fn name(&self) -> &str {
+        "customer_order_count"
+    }
+
+    async fn apply(
+        &self,
+        connection: &mut PgConnection,
+        event: &ProjectedEvent,
+    ) -> Result<(), ProjectionError> {
+        let query = match &event.event {
+            DomainEvent::CustomerCreated(customer_created) => sqlx::query(
+                r#"
+                INSERT INTO customer_order_counts (customer_id, order_count)
+                VALUES ($1, 0)
+                ON CONFLICT (customer_id) DO NOTHING
+            "#,
+            )
+            .bind(parse_uuid(&customer_created.id)?),
+            DomainEvent::OrderCreated(order_created) => sqlx::query(
+                r#"
+                INSERT INTO customer_order_counts (customer_id, order_count)
+                VALUES ($1, 1)
+                ON CONFLICT (customer_id) DO UPDATE
+                SET order_count = customer_order_counts.order_count + 1
+            "#,
+            )
+            .bind(parse_uuid(&order_created.customer_id)?),
+            _ => return Ok(()),
+        };
+
+        query
+            .execute(connection)
+            .await
+            .map_err(|e| ProjectionError::ProjectionNotUpdatedError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn reset(&self, connection: &mut PgConnection) -> Result<(), ProjectionError> {
+        sqlx::query("DELETE FROM customer_order_counts")
+            .execute(connection)
+            .await
+            .map_err(|e| ProjectionError::ProjectionNotUpdatedError(e.to_string()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Utc;
+    use domain::entities::outbox::{CustomerCreatedEvent, OrderCreatedEvent};
+    use sqlx::Row;
+    use uuid::Uuid;
+
+    use crate::common::test;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn counts_orders_of_a_customer() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let mut tx = pool.begin().await.unwrap();
+        let customer_id = Uuid::new_v4().to_string();
+        let projection = CustomerOrderCountProjection;
+
+        projection
+            .apply(
+                &mut tx,
+                &event(DomainEvent::CustomerCreated(CustomerCreatedEvent {
+                    id: customer_id.clone(),
+                    first_name: "Mario".to_string(),
+                    last_name: "Rossi".to_string(),
+                    email: "mario.rossi@example.com".to_string(),
+                })),
+            )
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            projection
+                .apply(
+                    &mut tx,
+                    &event(DomainEvent::OrderCreated(Box::new(OrderCreatedEvent {
+                        id: Uuid::new_v4().to_string(),
+                        customer_id: customer_id.clone(),
+                        shipping_address: None,
+                        billing_address: None,
+                        tax: None,
+                        currency: None,
+                        shipping: None,
+                    }))),
+                )
+                .await
+                .unwrap();
+        }
+
+        let order_count: i64 =
+            sqlx::query("SELECT order_count FROM customer_order_counts WHERE customer_id = $1")
+                .bind(Uuid::try_parse(&customer_id).unwrap())
+                .fetch_one(&mut *tx)
+                .await
+                .unwrap()
+                .get(0);
+        assert_eq!(2, order_count);
+    }
+
+    fn event(event: DomainEvent) -> ProjectedEvent {
+        ProjectedEvent {
+            id: Uuid::new_v4(),
+            created_at: Utc::now(),
+            event,
+        }
+    }
+}
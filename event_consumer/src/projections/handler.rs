@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sqlx::PgConnection;
+
+use crate::{
+    event_handler::{EventHandler, EventHandlerError},
+    inbox::InboxMessage,
+};
+
+use super::{ProjectedEvent, Projection, ProjectionError};
+
+/// Feeds a projection from the event consumer. Duplicates are already
+/// discarded by the inbox, so checkpoints are not used: feed each projection
+/// either from here or from `OutboxProjector`, not both.
+pub struct ProjectionEventHandler {
+    projection: Arc<dyn Projection>,
+}
+
+impl ProjectionEventHandler {
+    pub fn new(projection: Arc<dyn Projection>) -> Self {
+        Self { projection }
+    }
+}
+
+#[async_trait]
+impl EventHandler for ProjectionEventHandler {
+    async fn handle(
+        &self,
+        connection: &mut PgConnection,
+        message: &InboxMessage,
+    ) -> Result<(), EventHandlerError> {
+        let event = ProjectedEvent::try_from(message)
+            .map_err(|e| EventHandlerError::PermanentError(e.to_string()))?;
+        self.projection
+            .apply(connection, &event)
+            .await
+            .map_err(|e| match e {
+                ProjectionError::EventNotParsedError(_) => {
+                    EventHandlerError::PermanentError(e.to_string())
+                }
+                _ => EventHandlerError::TransientError(e.to_string()),
+            })
+    }
+}
@@ -0,0 +1,312 @@
+pub mod checkpoint;
+pub mod customer_order_count;
+pub mod handler;
+pub mod order_totals;
+pub mod projector;
+pub mod revenue_per_day;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::entities::outbox::{
+    CouponAppliedEvent, CustomerAddressBookChangedEvent, CustomerAddressChangedEvent,
+    CustomerCreatedEvent, CustomerDeactivatedEvent, CustomerErasedEvent, CustomerUpdatedEvent,
+    OrderCancelledEvent, OrderConfirmedEvent, OrderCreatedEvent, OrderPlacedEvent,
+    OrderShippedEvent, OutboxMessage, OutboxMessageType, PaymentAuthorizedEvent,
+    PaymentFailedEvent, ProductAddedToOrderEvent, ProductQuantityChangedEvent,
+    ProductRemovedFromOrderEvent, ReturnEvent, ReviewSubmittedEvent, ShipmentDeliveredEvent,
+    StockDepletedEvent, StockReservedEvent,
+};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::inbox::InboxMessage;
+
+#[derive(Debug)]
+pub enum ProjectionError {
+    EventNotParsedError(String),
+    EventsNotReadError(String),
+    ProjectionNotUpdatedError(String),
+    ProjectionNotFoundError(String),
+    CheckpointError(String),
+    TransactionError(String),
+}
+
+impl std::fmt::Display for ProjectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectionError::EventNotParsedError(error) => {
+                write!(f, "Error parsing event: {}", error)
+            }
+            ProjectionError::EventsNotReadError(error) => {
+                write!(f, "Error reading events: {}", error)
+            }
+            ProjectionError::ProjectionNotUpdatedError(error) => {
+                write!(f, "Error updating projection: {}", error)
+            }
+            ProjectionError::ProjectionNotFoundError(name) => {
+                write!(f, "Projection not found: {}", name)
+            }
+            ProjectionError::CheckpointError(error) => {
+                write!(f, "Checkpoint error: {}", error)
+            }
+            ProjectionError::TransactionError(error) => {
+                write!(f, "Transaction error: {}", error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProjectionError {}
+
+#[derive(Debug, PartialEq)]
+pub enum DomainEvent {
+    CustomerCreated(CustomerCreatedEvent),
+    CustomerUpdated(CustomerUpdatedEvent),
+    CustomerAddressChanged(CustomerAddressChangedEvent),
+    CustomerAddressBookChanged(CustomerAddressBookChangedEvent),
+    CustomerDeactivated(CustomerDeactivatedEvent),
+    CustomerErased(CustomerErasedEvent),
+    /// Boxed, as it is much larger than the other events.
+    OrderCreated(Box<OrderCreatedEvent>),
+    ProductAddedToOrder(ProductAddedToOrderEvent),
+    ProductRemovedFromOrder(ProductRemovedFromOrderEvent),
+    ProductQuantityChanged(ProductQuantityChangedEvent),
+    OrderPlaced(OrderPlacedEvent),
+    StockReserved(StockReservedEvent),
+    PaymentAuthorized(PaymentAuthorizedEvent),
+    OrderConfirmed(OrderConfirmedEvent),
+    OrderCancelled(OrderCancelledEvent),
+    StockDepleted(StockDepletedEvent),
+    PaymentFailed(PaymentFailedEvent),
+    ReturnRequested(ReturnEvent),
+    ReturnApproved(ReturnEvent),
+    ReturnRejected(ReturnEvent),
+    ReturnReceived(ReturnEvent),
+    ReturnRefunded(ReturnEvent),
+    CouponApplied(CouponAppliedEvent),
+    OrderShipped(OrderShippedEvent),
+    ShipmentDelivered(ShipmentDeliveredEvent),
+    ReviewSubmitted(ReviewSubmittedEvent),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ProjectedEvent {
+    pub id: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub event: DomainEvent,
+}
+
+impl ProjectedEvent {
+    pub fn parse(
+        id: Uuid,
+        event_type: &OutboxMessageType,
+        event_payload: &str,
+        created_at: DateTime<Utc>,
+    ) -> Result<Self, ProjectionError> {
+        let event = match event_type {
+            OutboxMessageType::CustomerCreated => {
+                DomainEvent::CustomerCreated(deserialize(event_payload)?)
+            }
+            OutboxMessageType::CustomerUpdated => {
+                DomainEvent::CustomerUpdated(deserialize(event_payload)?)
+            }
+            OutboxMessageType::CustomerAddressChanged => {
+                DomainEvent::CustomerAddressChanged(deserialize(event_payload)?)
+            }
+            OutboxMessageType::CustomerAddressBookChanged => {
+                DomainEvent::CustomerAddressBookChanged(deserialize(event_payload)?)
+            }
+            OutboxMessageType::CustomerDeactivated => {
+                DomainEvent::CustomerDeactivated(deserialize(event_payload)?)
+            }
+            OutboxMessageType::CustomerErased => {
+                DomainEvent::CustomerErased(deserialize(event_payload)?)
+            }
+            OutboxMessageType::OrderCreated => {
+                DomainEvent::OrderCreated(deserialize(event_payload)?)
+            }
+            OutboxMessageType::ProductAddedToOrder => {
+                DomainEvent::ProductAddedToOrder(deserialize(event_payload)?)
+            }
+            OutboxMessageType::ProductRemovedFromOrder => {
+                DomainEvent::ProductRemovedFromOrder(deserialize(event_payload)?)
+            }
+            OutboxMessageType::ProductQuantityChanged => {
+                DomainEvent::ProductQuantityChanged(deserialize(event_payload)?)
+            }
+            OutboxMessageType::OrderPlaced => DomainEvent::OrderPlaced(deserialize(event_payload)?),
+            OutboxMessageType::StockReserved => {
+                DomainEvent::StockReserved(deserialize(event_payload)?)
+            }
+            OutboxMessageType::PaymentAuthorized => {
+                DomainEvent::PaymentAuthorized(deserialize(event_payload)?)
+            }
+            OutboxMessageType::OrderConfirmed => {
+                DomainEvent::OrderConfirmed(deserialize(event_payload)?)
+            }
+            OutboxMessageType::OrderCancelled => {
+                DomainEvent::OrderCancelled(deserialize(event_payload)?)
+            }
+            OutboxMessageType::StockDepleted => {
+                DomainEvent::StockDepleted(deserialize(event_payload)?)
+            }
+            OutboxMessageType::PaymentFailed => {
+                DomainEvent::PaymentFailed(deserialize(event_payload)?)
+            }
+            OutboxMessageType::ReturnRequested => {
+                DomainEvent::ReturnRequested(deserialize(event_payload)?)
+            }
+            OutboxMessageType::ReturnApproved => {
+                DomainEvent::ReturnApproved(deserialize(event_payload)?)
+            }
+            OutboxMessageType::ReturnRejected => {
+                DomainEvent::ReturnRejected(deserialize(event_payload)?)
+            }
+            OutboxMessageType::ReturnReceived => {
+                DomainEvent::ReturnReceived(deserialize(event_payload)?)
+            }
+            OutboxMessageType::ReturnRefunded => {
+                DomainEvent::ReturnRefunded(deserialize(event_payload)?)
+            }
+            OutboxMessageType::CouponApplied => {
+                DomainEvent::CouponApplied(deserialize(event_payload)?)
+            }
+            OutboxMessageType::OrderShipped => {
+                DomainEvent::OrderShipped(deserialize(event_payload)?)
+            }
+            OutboxMessageType::ShipmentDelivered => {
+                DomainEvent::ShipmentDelivered(deserialize(event_payload)?)
+            }
+            OutboxMessageType::ReviewSubmitted => {
+                DomainEvent::ReviewSubmitted(deserialize(event_payload)?)
+            }
+        };
+        Ok(Self {
+            id,
+            created_at,
+            event,
+        })
+    }
+}
+
+impl TryFrom<&OutboxMessage> for ProjectedEvent {
+    type Error = ProjectionError;
+
+    fn try_from(message: &OutboxMessage) -> Result<Self, Self::Error> {
+        ProjectedEvent::parse(
+            message.id(),
+            &message.event_type(),
+            &message.event_payload(),
+            message.created_at(),
+        )
+    }
+}
+
+impl TryFrom<&InboxMessage> for ProjectedEvent {
+    type Error = ProjectionError;
+
+    fn try_from(message: &InboxMessage) -> Result<Self, Self::Error> {
+        let event_type = message
+            .event_type()
+            .parse()
+            .map_err(ProjectionError::EventNotParsedError)?;
+        ProjectedEvent::parse(
+            message.id(),
+            &event_type,
+            message.event_payload(),
+            message.created_at(),
+        )
+    }
+}
+
+fn deserialize<T: serde::de::DeserializeOwned>(event_payload: &str) -> Result<T, ProjectionError> {
+    serde_json::from_str(event_payload)
+        .map_err(|e| ProjectionError::EventNotParsedError(e.to_string()))
+}
+
+pub(crate) fn parse_uuid(id: &str) -> Result<Uuid, ProjectionError> {
+    Uuid::try_parse(id).map_err(|e| ProjectionError::EventNotParsedError(e.to_string()))
+}
+
+/// A denormalized read model kept up to date by applying domain events.
+///
+/// Events are applied inside the transaction that advances the projection
+/// checkpoint, so a projection sees each event once per feed.
+#[async_trait]
+pub trait Projection: Send + Sync {
+    /// Unique name, used as key of the projection checkpoint.
+    fn name(&self) -> &str;
+
+    async fn apply(
+        &self,
+        connection: &mut PgConnection,
+        event: &ProjectedEvent,
+    ) -> Result<(), ProjectionError>;
+
+    /// Deletes everything the projection has built so far.
+    async fn reset(&self, connection: &mut PgConnection) -> Result<(), ProjectionError>;
+}
+
+pub fn all_projections() -> Vec<Arc<dyn Projection>> {
+    vec![
+        Arc::new(customer_order_count::CustomerOrderCountProjection),
+        Arc::new(order_totals::OrderTotalsProjection),
+        Arc::new(revenue_per_day::RevenuePerDayProjection),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use domain::entities::{currency::OrderCurrency, outbox::CurrencyPayload};
+
+    use super::*;
+
+    #[test]
+    fn parses_an_outbox_message() {
+        let order_id = Uuid::new_v4();
+        let product_id = Uuid::new_v4();
+        let message = OutboxMessage::product_added_to_order_event(
+            &domain::value_objects::OrderId(order_id),
+            &domain::value_objects::ProductId(product_id),
+            9.99,
+            2,
+            &OrderCurrency::default(),
+        )
+        .unwrap();
+
+        let event = ProjectedEvent::try_from(&message).unwrap();
+
+        assert_eq!(message.id(), event.id);
+        assert_eq!(
+            DomainEvent::ProductAddedToOrder(ProductAddedToOrderEvent {
+                order_id: order_id.to_string(),
+                product_id: product_id.to_string(),
+                quantity: 2,
+                price: 9.99,
+                currency: Some(CurrencyPayload {
+                    currency: "EUR".to_string(),
+                    exchange_rate: 1.0,
+                }),
+            }),
+            event.event
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_inbox_event_types() {
+        let message = InboxMessage::new(
+            Uuid::new_v4(),
+            "unknown".to_string(),
+            "{}".to_string(),
+            Utc::now(),
+            Utc::now(),
+        );
+
+        assert!(matches!(
+            ProjectedEvent::try_from(&message),
+            Err(ProjectionError::EventNotParsedError(_))
+        ));
+    }
+}
@@ -0,0 +1,238 @@
+use async_trait::async_trait;
+use sqlx::{postgres::PgArguments, PgConnection, Postgres};
+use uuid::Uuid;
+
+use super::{parse_uuid, DomainEvent, ProjectedEvent, Projection, ProjectionError};
+
+/// Total amount and number of items of each order (`order_totals` table),
+/// in the order's currency and, in `base_total`, in the base currency.
+pub struct OrderTotalsProjection;
+
+#[async_trait]
+impl Projection for OrderTotalsProjection {
+    fn name(&self) -> &str {
+        "order_totals"
+    }
+
+    async fn apply(
+        &self,
+        connection: &mut PgConnection,
+        event: &ProjectedEvent,
+    ) -> Result<(), ProjectionError> {
+        let query = match &event.event {
+            DomainEvent::OrderCreated(order_created) => {
+                let currency = order_created
+                    .currency()
+                    .map_err(|e| ProjectionError::EventNotParsedError(e.to_string()))?;
+                sqlx::query(
+                    r#"
+                INSERT INTO order_totals (
+                    order_id, customer_id, total, item_count, currency, exchange_rate
+                )
+                VALUES ($1, $2, 0, 0, $3, $4)
+                ON CONFLICT (order_id) DO UPDATE
+                SET customer_id = EXCLUDED.customer_id,
+                    currency = EXCLUDED.currency,
+                    exchange_rate = EXCLUDED.exchange_rate
+            "#,
+                )
+                .bind(parse_uuid(&order_created.id)?)
+                .bind(parse_uuid(&order_created.customer_id)?)
+                .bind(currency.currency.as_str())
+                .bind(currency.exchange_rate)
+            }
+            DomainEvent::ProductAddedToOrder(product_added) => sqlx::query(
+                r#"
+                INSERT INTO order_totals (order_id, total, item_count)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (order_id) DO UPDATE
+                SET total = order_totals.total + EXCLUDED.total,
+                    item_count = order_totals.item_count + EXCLUDED.item_count
+            "#,
+            )
+            .bind(parse_uuid(&product_added.order_id)?)
+            .bind(product_added.price * product_added.quantity as f64)
+            .bind(product_added.quantity),
+            DomainEvent::ProductRemovedFromOrder(product_removed) => change_totals(
+                parse_uuid(&product_removed.order_id)?,
+                -product_removed.price * product_removed.quantity as f64,
+                -product_removed.quantity,
+            ),
+            DomainEvent::ProductQuantityChanged(quantity_changed) => change_totals(
+                parse_uuid(&quantity_changed.order_id)?,
+                quantity_changed.price * quantity_changed.quantity_change() as f64,
+                quantity_changed.quantity_change(),
+            ),
+            _ => return Ok(()),
+        };
+
+        query
+            .execute(connection)
+            .await
+            .map_err(|e| ProjectionError::ProjectionNotUpdatedError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn reset(&self, connection: &mut PgConnection) -> Result<(), ProjectionError> {
+        sqlx::query("DELETE FROM order_totals")
+            .execute(connection)
+            .await
+            .map_err(|e| ProjectionError::ProjectionNotUpdatedError(e.to_string()))?;
+        Ok(())
+    }
+}
+
+fn change_totals(
+    order_id: Uuid,
+    amount: f64,
+    item_count: i32,
+) -> sqlx::query::Query<'static, Postgres, PgArguments> {
+    sqlx::query(
+        r#"
+        UPDATE order_totals
+        SET total = total + $2, item_count = item_count + $3
+        WHERE order_id = $1
+    "#,
+    )
+    .bind(order_id)
+    .bind(amount)
+    .bind(item_count)
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Utc;
+    use domain::entities::outbox::{
+        CurrencyPayload, OrderCreatedEvent, ProductAddedToOrderEvent, ProductQuantityChangedEvent,
+        ProductRemovedFromOrderEvent,
+    };
+    use sqlx::Row;
+    use uuid::Uuid;
+
+    use crate::common::test;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn sums_products_added_to_an_order() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let mut tx = pool.begin().await.unwrap();
+        let order_id = Uuid::new_v4();
+        let projection = OrderTotalsProjection;
+
+        projection
+            .apply(
+                &mut tx,
+                &event(DomainEvent::OrderCreated(Box::new(OrderCreatedEvent {
+                    id: order_id.to_string(),
+                    customer_id: Uuid::new_v4().to_string(),
+                    shipping_address: None,
+                    billing_address: None,
+                    tax: None,
+                    currency: Some(CurrencyPayload {
+                        currency: "USD".to_string(),
+                        exchange_rate: 1.25,
+                    }),
+                    shipping: None,
+                }))),
+            )
+            .await
+            .unwrap();
+        for (price, quantity) in [(9.99, 10), (5.55, 2)] {
+            projection
+                .apply(
+                    &mut tx,
+                    &event(DomainEvent::ProductAddedToOrder(ProductAddedToOrderEvent {
+                        order_id: order_id.to_string(),
+                        product_id: Uuid::new_v4().to_string(),
+                        quantity,
+                        price,
+                        currency: None,
+                    })),
+                )
+                .await
+                .unwrap();
+        }
+
+        let row = sqlx::query(
+            "SELECT total, base_total, currency, item_count FROM order_totals WHERE order_id = $1",
+        )
+        .bind(order_id)
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+        let total: f64 = row.get("total");
+        let base_total: f64 = row.get("base_total");
+        let currency: String = row.get("currency");
+        let item_count: i32 = row.get("item_count");
+        assert_eq!(111.0, (total * 100.0).round() / 100.0);
+        assert_eq!(88.8, (base_total * 100.0).round() / 100.0);
+        assert_eq!("USD", currency);
+        assert_eq!(12, item_count);
+    }
+
+    #[tokio::test]
+    async fn subtracts_products_removed_from_an_order_and_applies_quantity_changes() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let mut tx = pool.begin().await.unwrap();
+        let order_id = Uuid::new_v4();
+        let projection = OrderTotalsProjection;
+        let removed_product_id = Uuid::new_v4().to_string();
+        let changed_product_id = Uuid::new_v4().to_string();
+
+        for domain_event in [
+            DomainEvent::ProductAddedToOrder(ProductAddedToOrderEvent {
+                order_id: order_id.to_string(),
+                product_id: removed_product_id.clone(),
+                quantity: 10,
+                price: 9.99,
+                currency: None,
+            }),
+            DomainEvent::ProductAddedToOrder(ProductAddedToOrderEvent {
+                order_id: order_id.to_string(),
+                product_id: changed_product_id.clone(),
+                quantity: 2,
+                price: 5.55,
+                currency: None,
+            }),
+            DomainEvent::ProductRemovedFromOrder(ProductRemovedFromOrderEvent {
+                order_id: order_id.to_string(),
+                product_id: removed_product_id,
+                quantity: 10,
+                price: 9.99,
+                currency: None,
+            }),
+            DomainEvent::ProductQuantityChanged(ProductQuantityChangedEvent {
+                order_id: order_id.to_string(),
+                product_id: changed_product_id,
+                previous_quantity: 2,
+                quantity: 5,
+                price: 5.55,
+                currency: None,
+            }),
+        ] {
+            projection
+                .apply(&mut tx, &event(domain_event))
+                .await
+                .unwrap();
+        }
+
+        let row = sqlx::query("SELECT total, item_count FROM order_totals WHERE order_id = $1")
+            .bind(order_id)
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+        let total: f64 = row.get("total");
+        let item_count: i32 = row.get("item_count");
+        assert_eq!(27.75, (total * 100.0).round() / 100.0);
+        assert_eq!(5, item_count);
+    }
+
+    fn event(event: DomainEvent) -> ProjectedEvent {
+        ProjectedEvent {
+            id: Uuid::new_v4(),
+            created_at: Utc::now(),
+            event,
+        }
+    }
+}
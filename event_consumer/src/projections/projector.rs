@@ -0,0 +1,266 @@
+use std::sync::Arc;
+
+use domain::repositories::outbox_repository::OutboxMessageRepository;
+use sqlx::{Pool, Postgres};
+use tracing::info;
+
+use super::{
+    checkpoint::PgProjectionCheckpointRepository, ProjectedEvent, Projection, ProjectionError,
+};
+
+const BATCH_SIZE: i64 = 100;
+
+/// Feeds projections straight from the `outbox_messages` table, resuming each
+/// one from its checkpoint.
+pub struct OutboxProjector {
+    pool: Pool<Postgres>,
+    outbox_message_repository: Box<dyn OutboxMessageRepository + Send + Sync>,
+    projections: Vec<Arc<dyn Projection>>,
+    checkpoint_repository: PgProjectionCheckpointRepository,
+}
+
+impl OutboxProjector {
+    pub fn new(
+        pool: Pool<Postgres>,
+        outbox_message_repository: Box<dyn OutboxMessageRepository + Send + Sync>,
+        projections: Vec<Arc<dyn Projection>>,
+    ) -> Self {
+        Self {
+            pool,
+            outbox_message_repository,
+            projections,
+            checkpoint_repository: PgProjectionCheckpointRepository,
+        }
+    }
+
+    /// Applies the messages stored after their checkpoint to all projections.
+    /// Returns the number of events applied.
+    pub async fn catch_up(&self) -> Result<usize, ProjectionError> {
+        let mut applied = 0;
+        for projection in &self.projections {
+            applied += self.catch_up_projection(projection.as_ref()).await?;
+        }
+        Ok(applied)
+    }
+
+    /// Empties the read model of the named projection (all of them if `None`)
+    /// and replays the whole outbox into it.
+    pub async fn rebuild(&self, projection_name: Option<&str>) -> Result<usize, ProjectionError> {
+        let projections: Vec<&Arc<dyn Projection>> = self
+            .projections
+            .iter()
+            .filter(|p| projection_name.is_none_or(|name| p.name() == name))
+            .collect();
+        if projections.is_empty() {
+            return Err(ProjectionError::ProjectionNotFoundError(
+                projection_name.unwrap_or_default().to_string(),
+            ));
+        }
+
+        let mut applied = 0;
+        for projection in projections {
+            info!("Rebuilding projection {}", projection.name());
+            let mut tx = self.begin_transaction().await?;
+            self.checkpoint_repository
+                .lock(&mut tx, projection.name())
+                .await?;
+            projection.reset(&mut tx).await?;
+            self.checkpoint_repository
+                .delete(&mut tx, projection.name())
+                .await?;
+            tx.commit()
+                .await
+                .map_err(|e| ProjectionError::TransactionError(e.to_string()))?;
+            applied += self.catch_up_projection(projection.as_ref()).await?;
+        }
+        Ok(applied)
+    }
+
+    async fn catch_up_projection(
+        &self,
+        projection: &dyn Projection,
+    ) -> Result<usize, ProjectionError> {
+        let mut applied = 0;
+        loop {
+            let mut tx = self.begin_transaction().await?;
+            self.checkpoint_repository
+                .lock(&mut tx, projection.name())
+                .await?;
+            let checkpoint = self
+                .checkpoint_repository
+                .find(&mut tx, projection.name())
+                .await?;
+
+            let messages = self
+                .outbox_message_repository
+                .find_after(checkpoint, BATCH_SIZE)
+                .await
+                .map_err(|e| ProjectionError::EventsNotReadError(e.to_string()))?;
+            let Some((last, _)) = messages.last() else {
+                break;
+            };
+
+            for (_, message) in &messages {
+                projection
+                    .apply(&mut tx, &ProjectedEvent::try_from(message)?)
+                    .await?;
+            }
+            self.checkpoint_repository
+                .save(&mut tx, projection.name(), last)
+                .await?;
+            tx.commit()
+                .await
+                .map_err(|e| ProjectionError::TransactionError(e.to_string()))?;
+
+            applied += messages.len();
+            if (messages.len() as i64) < BATCH_SIZE {
+                break;
+            }
+        }
+        info!("Applied {} events to {}", applied, projection.name());
+        Ok(applied)
+    }
+
+    async fn begin_transaction(
+        &self,
+    ) -> Result<sqlx::Transaction<'static, Postgres>, ProjectionError> {
+        self.pool
+            .begin()
+            .await
+            .map_err(|e| ProjectionError::TransactionError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use domain::{
+        entities::{order::Order, outbox::OutboxMessage},
+        repositories::outbox_repository::{MockOutboxMessageRepository, OutboxPosition},
+        value_objects::{CustomerId, OrderId},
+    };
+    use sqlx::PgConnection;
+    use uuid::Uuid;
+
+    use crate::common::test;
+
+    use super::*;
+
+    struct RecordingProjection {
+        name: String,
+        applied: Arc<Mutex<Vec<Uuid>>>,
+    }
+
+    #[async_trait]
+    impl Projection for RecordingProjection {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        async fn apply(
+            &self,
+            _: &mut PgConnection,
+            event: &ProjectedEvent,
+        ) -> Result<(), ProjectionError> {
+            self.applied.lock().unwrap().push(event.id);
+            Ok(())
+        }
+
+        async fn reset(&self, _: &mut PgConnection) -> Result<(), ProjectionError> {
+            self.applied.lock().unwrap().clear();
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn catches_up_from_the_checkpoint() {
+        let messages = vec![order_created_message(1), order_created_message(2)];
+        let last = messages[1].0;
+        let mut repository = MockOutboxMessageRepository::new();
+        repository
+            .expect_find_after()
+            .withf(|after, _| after.is_none())
+            .return_once(move |_, _| Ok(messages))
+            .once();
+        repository
+            .expect_find_after()
+            .withf(move |after, _| *after == Some(last))
+            .returning(|_, _| Ok(vec![]))
+            .once();
+        let applied = Arc::new(Mutex::new(vec![]));
+        let projector = OutboxProjector::new(
+            test::create_sqlx_connection_pool().await,
+            Box::new(repository),
+            vec![Arc::new(RecordingProjection {
+                name: format!("test_projection_{}", Uuid::new_v4()),
+                applied: applied.clone(),
+            })],
+        );
+
+        assert_eq!(2, projector.catch_up().await.unwrap());
+        assert_eq!(0, projector.catch_up().await.unwrap());
+        assert_eq!(2, applied.lock().unwrap().len());
+    }
+
+    #[tokio::test]
+    async fn rebuilds_from_scratch() {
+        let message = order_created_message(1);
+        let mut repository = MockOutboxMessageRepository::new();
+        repository
+            .expect_find_after()
+            .withf(|after, _| after.is_none())
+            .returning(move |_, _| Ok(vec![message.clone()]))
+            .times(2);
+        repository
+            .expect_find_after()
+            .withf(|after, _| after.is_some())
+            .returning(|_, _| Ok(vec![]));
+        let name = format!("test_projection_{}", Uuid::new_v4());
+        let applied = Arc::new(Mutex::new(vec![]));
+        let projector = OutboxProjector::new(
+            test::create_sqlx_connection_pool().await,
+            Box::new(repository),
+            vec![Arc::new(RecordingProjection {
+                name: name.clone(),
+                applied: applied.clone(),
+            })],
+        );
+        projector.catch_up().await.unwrap();
+
+        let result = projector.rebuild(Some(&name)).await.unwrap();
+
+        assert_eq!(1, result);
+        assert_eq!(1, applied.lock().unwrap().len());
+    }
+
+    #[tokio::test]
+    async fn cannot_rebuild_an_unknown_projection() {
+        let projector = OutboxProjector::new(
+            test::create_sqlx_connection_pool().await,
+            Box::new(MockOutboxMessageRepository::new()),
+            vec![],
+        );
+
+        let result = projector.rebuild(Some("unknown")).await;
+
+        assert!(matches!(
+            result,
+            Err(ProjectionError::ProjectionNotFoundError(_))
+        ));
+    }
+
+    fn order_created_message(position: i64) -> (OutboxPosition, OutboxMessage) {
+        let message = OutboxMessage::order_created_event(&Order::create(
+            OrderId(Uuid::new_v4()),
+            CustomerId(Uuid::new_v4()),
+        ))
+        .unwrap();
+        let position = OutboxPosition {
+            transaction_id: 1,
+            position,
+        };
+        (position, message)
+    }
+}
@@ -0,0 +1,169 @@
+use async_trait::async_trait;
+use sqlx::PgConnection;
+
+use super::{DomainEvent, ProjectedEvent, Projection, ProjectionError};
+
+/// Revenue of products added to orders in the base currency, less the one of
+/// products removed, per UTC day (`revenue_per_day` table).
+pub struct RevenuePerDayProjection;
+
+#[async_trait]
+impl Projection for RevenuePerDayProjection {
+    fn name(&self) -> &str {
+        "revenue_per_day"
+    }
+
+    async fn apply(
+        &self,
+        connection: &mut PgConnection,
+        event: &ProjectedEvent,
+    ) -> Result<(), ProjectionError> {
+        let revenue = match &event.event {
+            DomainEvent::ProductAddedToOrder(product_added) => product_added.base_amount(),
+            DomainEvent::ProductRemovedFromOrder(product_removed) => {
+                product_removed.base_amount().map(|amount| -amount)
+            }
+            DomainEvent::ProductQuantityChanged(quantity_changed) => quantity_changed.base_amount(),
+            _ => return Ok(()),
+        }
+        .map_err(|e| ProjectionError::EventNotParsedError(e.to_string()))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO revenue_per_day (day, revenue)
+            VALUES ($1, $2)
+            ON CONFLICT (day) DO UPDATE
+            SET revenue = revenue_per_day.revenue + EXCLUDED.revenue
+        "#,
+        )
+        .bind(event.created_at.date_naive())
+        .bind(revenue)
+        .execute(connection)
+        .await
+        .map_err(|e| ProjectionError::ProjectionNotUpdatedError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn reset(&self, connection: &mut PgConnection) -> Result<(), ProjectionError> {
+        sqlx::query("DELETE FROM revenue_per_day")
+            .execute(connection)
+            .await
+            .map_err(|e| ProjectionError::ProjectionNotUpdatedError(e.to_string()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use domain::entities::outbox::{
+        ProductAddedToOrderEvent, ProductQuantityChangedEvent, ProductRemovedFromOrderEvent,
+    };
+    use sqlx::Row;
+    use uuid::Uuid;
+
+    use crate::common::test;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn sums_revenue_of_the_day() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let mut tx = pool.begin().await.unwrap();
+        let day = NaiveDate::from_ymd_opt(1970, 1, 2).unwrap();
+        sqlx::query("DELETE FROM revenue_per_day WHERE day = $1")
+            .bind(day)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        let projection = RevenuePerDayProjection;
+
+        for hour in [1, 23] {
+            projection
+                .apply(
+                    &mut tx,
+                    &ProjectedEvent {
+                        id: Uuid::new_v4(),
+                        created_at: Utc.with_ymd_and_hms(1970, 1, 2, hour, 0, 0).unwrap(),
+                        event: DomainEvent::ProductAddedToOrder(ProductAddedToOrderEvent {
+                            order_id: Uuid::new_v4().to_string(),
+                            product_id: Uuid::new_v4().to_string(),
+                            quantity: 2,
+                            price: 5.0,
+                            currency: None,
+                        }),
+                    },
+                )
+                .await
+                .unwrap();
+        }
+
+        let revenue: f64 = sqlx::query("SELECT revenue FROM revenue_per_day WHERE day = $1")
+            .bind(day)
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap()
+            .get(0);
+        assert_eq!(20.0, revenue);
+    }
+
+    #[tokio::test]
+    async fn subtracts_revenue_of_removed_products_and_applies_quantity_changes() {
+        let pool = test::create_sqlx_connection_pool().await;
+        let mut tx = pool.begin().await.unwrap();
+        let day = NaiveDate::from_ymd_opt(1970, 1, 3).unwrap();
+        sqlx::query("DELETE FROM revenue_per_day WHERE day = $1")
+            .bind(day)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        let projection = RevenuePerDayProjection;
+        let order_id = Uuid::new_v4().to_string();
+        let product_id = Uuid::new_v4().to_string();
+
+        for event in [
+            DomainEvent::ProductAddedToOrder(ProductAddedToOrderEvent {
+                order_id: order_id.clone(),
+                product_id: product_id.clone(),
+                quantity: 2,
+                price: 5.0,
+                currency: None,
+            }),
+            DomainEvent::ProductQuantityChanged(ProductQuantityChangedEvent {
+                order_id: order_id.clone(),
+                product_id: product_id.clone(),
+                previous_quantity: 2,
+                quantity: 6,
+                price: 5.0,
+                currency: None,
+            }),
+            DomainEvent::ProductRemovedFromOrder(ProductRemovedFromOrderEvent {
+                order_id,
+                product_id,
+                quantity: 1,
+                price: 5.0,
+                currency: None,
+            }),
+        ] {
+            projection
+                .apply(
+                    &mut tx,
+                    &ProjectedEvent {
+                        id: Uuid::new_v4(),
+                        created_at: Utc.with_ymd_and_hms(1970, 1, 3, 12, 0, 0).unwrap(),
+                        event,
+                    },
+                )
+                .await
+                .unwrap();
+        }
+
+        let revenue: f64 = sqlx::query("SELECT revenue FROM revenue_per_day WHERE day = $1")
+            .bind(day)
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap()
+            .get(0);
+        assert_eq!(25.0, revenue);
+    }
+}
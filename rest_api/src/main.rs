@@ -10,7 +10,12 @@ async fn main() -> Result<(), std::io::Error> {
     let address = format!("127.0.0.1:{}", settings.application_port);
     let listener = TcpListener::bind(address).expect("Failed to bind address and port");
 
-    run(listener, create_connection_pool().await)?.await
+    run(
+        listener,
+        create_connection_pool().await,
+        settings.payment_provider,
+    )?
+    .await
 }
 
 async fn create_connection_pool() -> Pool<Postgres> {
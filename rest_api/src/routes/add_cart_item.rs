@@ -0,0 +1,50 @@
+use actix_web::{
+    http::header::{ETag, IfMatch},
+    post, web, HttpResponse, Responder,
+};
+use domain::services::cart_service::AddCartItemRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    cart_response::{cart_service, error_response, CartResponse},
+    etag::{etag, expected_version},
+};
+
+#[post("/carts/{cart_id}/items")]
+async fn add_cart_item(
+    path: web::Path<String>,
+    data: web::Form<CartItemData>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let mut cart_service = cart_service(pool.get_ref());
+
+    match cart_service
+        .add_item(AddCartItemRequestObject {
+            cart_id: path.into_inner(),
+            product_id: data.product_id.clone(),
+            price: data.price,
+            quantity: data.quantity,
+            expected_version,
+        })
+        .await
+    {
+        Ok(cart) => HttpResponse::Ok()
+            .insert_header(ETag(etag(cart.version())))
+            .json(CartResponse::from(&cart)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct CartItemData {
+    product_id: String,
+    price: f64,
+    quantity: i32,
+}
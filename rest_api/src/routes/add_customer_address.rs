@@ -0,0 +1,63 @@
+use actix_web::{
+    http::header::{ETag, IfMatch},
+    post, web, HttpResponse, Responder,
+};
+use domain::services::customer_service::AddAddressRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    customer_response::{error_response, CustomerResponse},
+    etag::{etag, expected_version},
+};
+
+#[post("/customers/{customer_id}/addresses")]
+async fn add_customer_address(
+    path: web::Path<String>,
+    data: web::Form<AddressData>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let customer_repository =
+        adapters::sqlx::pg_customer_repository::PgCustomerRepository::new(pool.get_ref().clone());
+    let outbox_message_repository =
+        adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(
+            pool.get_ref().clone(),
+        );
+
+    let mut customer_service = domain::services::customer_service::CustomerService::new(
+        Box::new(customer_repository),
+        Box::new(outbox_message_repository),
+    );
+
+    let data = data.into_inner();
+    match customer_service
+        .add_address(AddAddressRequestObject {
+            customer_id: path.into_inner(),
+            street: data.street,
+            city: data.city,
+            zip_code: data.zip_code,
+            country: data.country,
+            expected_version,
+        })
+        .await
+    {
+        Ok(customer) => HttpResponse::Created()
+            .insert_header(ETag(etag(customer.version)))
+            .json(CustomerResponse::from(&customer)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct AddressData {
+    street: String,
+    city: String,
+    zip_code: String,
+    country: String,
+}
@@ -0,0 +1,90 @@
+use actix_web::{
+    http::header::{ETag, IfMatch},
+    post, web, HttpResponse, Responder,
+};
+use domain::services::{
+    currency_service::CurrencyServiceError,
+    order_service::{AddProductRequestObject, OrderServiceError},
+};
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    etag::{etag, expected_version},
+    order_response::OrderResponse,
+};
+
+#[post("/orders/{order_id}/products")]
+async fn add_product_to_order(
+    path: web::Path<String>,
+    data: web::Form<ProductData>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let customer_repository =
+        adapters::sqlx::pg_customer_repository::PgCustomerRepository::new(pool.get_ref().clone());
+
+    let order_repository =
+        adapters::sqlx::pg_order_repository::PgOrderRepository::new(pool.get_ref().clone());
+
+    let outbox_message_repository =
+        adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(
+            pool.get_ref().clone(),
+        );
+
+    let mut order_service = domain::services::order_service::OrderService::new(
+        Box::new(customer_repository),
+        Box::new(order_repository),
+        Box::new(outbox_message_repository),
+        Box::new(
+            adapters::sqlx::pg_catalog_repository::PgCatalogRepository::new(pool.get_ref().clone()),
+        ),
+        Box::new(
+            adapters::sqlx::pg_shipping_method_repository::PgShippingMethodRepository::new(
+                pool.get_ref().clone(),
+            ),
+        ),
+        Box::new(adapters::sqlx::pg_tax_calculator::PgTaxCalculator::new(
+            pool.get_ref().clone(),
+        )),
+        domain::services::currency_service::CurrencyService::new(Box::new(
+            adapters::sqlx::pg_currency_repository::PgCurrencyRepository::new(
+                pool.get_ref().clone(),
+            ),
+        )),
+    );
+
+    match order_service
+        .add_product(AddProductRequestObject {
+            order_id: path.into_inner(),
+            product_id: data.product_id.clone(),
+            quantity: data.quantity,
+            expected_version,
+        })
+        .await
+    {
+        Ok(order) => HttpResponse::Ok()
+            .insert_header(ETag(etag(order.version())))
+            .json(OrderResponse::from(&order)),
+        Err(OrderServiceError::OrderNotFoundError) => HttpResponse::NotFound().finish(),
+        Err(OrderServiceError::ConcurrencyConflictError) => {
+            HttpResponse::Conflict().body(OrderServiceError::ConcurrencyConflictError.to_string())
+        }
+        Err(
+            error @ (OrderServiceError::ProductNotFoundError(_)
+            | OrderServiceError::CurrencyError(CurrencyServiceError::PriceNotFoundError(_))),
+        ) => HttpResponse::UnprocessableEntity().body(error.to_string()),
+        Err(error) => HttpResponse::BadRequest().body(error.to_string()),
+    }
+}
+
+#[derive(Deserialize)]
+struct ProductData {
+    product_id: String,
+    quantity: i32,
+}
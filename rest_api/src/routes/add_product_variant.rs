@@ -0,0 +1,51 @@
+use actix_web::{
+    http::header::{ETag, IfMatch},
+    post, web, HttpResponse, Responder,
+};
+use domain::services::catalog_service::AddVariantRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    catalog_response::{catalog_service, error_response, ProductResponse},
+    etag::{etag, expected_version},
+};
+
+#[post("/products/{product_id}/variants")]
+async fn add_product_variant(
+    path: web::Path<String>,
+    data: web::Form<VariantData>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let catalog_service = catalog_service(pool.get_ref());
+    let data = data.into_inner();
+
+    match catalog_service
+        .add_variant(AddVariantRequestObject {
+            product_id: path.into_inner(),
+            variant_id: data.variant_id,
+            sku: data.sku,
+            price: data.price,
+            expected_version,
+        })
+        .await
+    {
+        Ok(product) => HttpResponse::Ok()
+            .insert_header(ETag(etag(product.version())))
+            .json(ProductResponse::from(&product)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct VariantData {
+    variant_id: String,
+    sku: String,
+    price: f64,
+}
@@ -0,0 +1,51 @@
+use actix_web::{
+    http::header::{ETag, IfMatch},
+    post, web, HttpResponse, Responder,
+};
+use domain::services::return_service::AddReturnItemRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use crate::settings::PaymentProviderSettings;
+
+use super::{
+    etag::{etag, expected_version},
+    return_response::{error_response, return_service, ReturnResponse},
+};
+
+#[post("/returns/{return_id}/items")]
+async fn add_return_item(
+    path: web::Path<String>,
+    data: web::Form<ReturnItemData>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+    payment_provider: web::Data<PaymentProviderSettings>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let mut return_service = return_service(pool.get_ref(), payment_provider.get_ref());
+
+    match return_service
+        .add_item(AddReturnItemRequestObject {
+            return_id: path.into_inner(),
+            product_id: data.product_id.clone(),
+            quantity: data.quantity,
+            expected_version,
+        })
+        .await
+    {
+        Ok(order_return) => HttpResponse::Ok()
+            .insert_header(ETag(etag(order_return.version())))
+            .json(ReturnResponse::from(&order_return)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct ReturnItemData {
+    product_id: String,
+    quantity: i32,
+}
@@ -0,0 +1,48 @@
+use actix_web::{
+    http::header::{ETag, IfMatch},
+    post, web, HttpResponse, Responder,
+};
+use domain::services::shipment_service::AddShipmentItemRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    etag::{etag, expected_version},
+    shipment_response::{error_response, shipment_service, ShipmentResponse},
+};
+
+#[post("/shipments/{shipment_id}/items")]
+async fn add_shipment_item(
+    path: web::Path<String>,
+    data: web::Form<ShipmentItemData>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let mut shipment_service = shipment_service(pool.get_ref());
+
+    match shipment_service
+        .add_item(AddShipmentItemRequestObject {
+            shipment_id: path.into_inner(),
+            product_id: data.product_id.clone(),
+            quantity: data.quantity,
+            expected_version,
+        })
+        .await
+    {
+        Ok(shipment) => HttpResponse::Ok()
+            .insert_header(ETag(etag(shipment.version())))
+            .json(ShipmentResponse::from(&shipment)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct ShipmentItemData {
+    product_id: String,
+    quantity: i32,
+}
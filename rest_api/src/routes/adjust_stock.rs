@@ -0,0 +1,48 @@
+use actix_web::{
+    http::header::{ETag, IfMatch},
+    post, web, HttpResponse, Responder,
+};
+use domain::services::inventory_service::AdjustStockRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    etag::{etag, expected_version},
+    inventory_response::{error_response, inventory_service, InventoryResponse},
+};
+
+#[post("/products/{product_id}/stock/{warehouse_id}")]
+async fn adjust_stock(
+    path: web::Path<(String, String)>,
+    data: web::Form<AdjustmentData>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let mut inventory_service = inventory_service(pool.get_ref());
+
+    let (product_id, warehouse_id) = path.into_inner();
+    match inventory_service
+        .adjust_stock(AdjustStockRequestObject {
+            product_id,
+            warehouse_id,
+            quantity: data.quantity,
+            expected_version,
+        })
+        .await
+    {
+        Ok(inventory) => HttpResponse::Ok()
+            .insert_header(ETag(etag(inventory.version())))
+            .json(InventoryResponse::from(&inventory)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct AdjustmentData {
+    quantity: i32,
+}
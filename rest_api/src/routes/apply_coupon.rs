@@ -0,0 +1,47 @@
+use actix_web::{
+    http::header::{ETag, IfMatch},
+    post, web, HttpResponse, Responder,
+};
+use domain::services::promotion_service::ApplyCouponRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    etag::{etag, expected_version},
+    order_response::OrderResponse,
+    promotion_response::{error_response, promotion_service},
+};
+
+#[post("/orders/{order_id}/coupons")]
+async fn apply_coupon(
+    path: web::Path<String>,
+    data: web::Form<CouponData>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let mut promotion_service = promotion_service(pool.get_ref());
+
+    match promotion_service
+        .apply_coupon(ApplyCouponRequestObject {
+            order_id: path.into_inner(),
+            code: data.code.clone(),
+            expected_version,
+        })
+        .await
+    {
+        Ok(order) => HttpResponse::Ok()
+            .insert_header(ETag(etag(order.version())))
+            .json(OrderResponse::from(&order)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct CouponData {
+    code: String,
+}
@@ -0,0 +1,96 @@
+use actix_web::HttpResponse;
+use domain::{
+    entities::cart::Cart,
+    services::{
+        cart_service::{CartService, CartServiceError},
+        order_service::{OrderService, OrderServiceError},
+    },
+};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+#[derive(Serialize)]
+pub struct CartResponse {
+    cart_id: String,
+    customer_id: Option<String>,
+    cart_items: Vec<CartItemResponse>,
+    total_price: f64,
+    expires_at: String,
+    version: i64,
+}
+
+#[derive(Serialize)]
+struct CartItemResponse {
+    product_id: String,
+    price: f64,
+    quantity: i32,
+}
+
+impl From<&Cart> for CartResponse {
+    fn from(cart: &Cart) -> Self {
+        Self {
+            cart_id: cart.id().0.to_string(),
+            customer_id: cart.customer_id().map(|id| id.0.to_string()),
+            cart_items: cart
+                .cart_lines()
+                .iter()
+                .map(|item| CartItemResponse {
+                    product_id: item.product_id.0.to_string(),
+                    price: item.price,
+                    quantity: item.quantity,
+                })
+                .collect(),
+            total_price: cart.cart_lines().total_price(),
+            expires_at: cart.expires_at().to_rfc3339(),
+            version: cart.version(),
+        }
+    }
+}
+
+pub fn cart_service(pool: &Pool<Postgres>) -> CartService {
+    let cart_repository = adapters::sqlx::pg_cart_repository::PgCartRepository::new(pool.clone());
+    let customer_repository =
+        adapters::sqlx::pg_customer_repository::PgCustomerRepository::new(pool.clone());
+    let order_repository =
+        adapters::sqlx::pg_order_repository::PgOrderRepository::new(pool.clone());
+    let outbox_message_repository =
+        adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(pool.clone());
+
+    CartService::new(
+        Box::new(cart_repository),
+        OrderService::new(
+            Box::new(customer_repository),
+            Box::new(order_repository),
+            Box::new(outbox_message_repository),
+            Box::new(adapters::sqlx::pg_catalog_repository::PgCatalogRepository::new(pool.clone())),
+            Box::new(
+                adapters::sqlx::pg_shipping_method_repository::PgShippingMethodRepository::new(
+                    pool.clone(),
+                ),
+            ),
+            Box::new(adapters::sqlx::pg_tax_calculator::PgTaxCalculator::new(
+                pool.clone(),
+            )),
+            domain::services::currency_service::CurrencyService::new(Box::new(
+                adapters::sqlx::pg_currency_repository::PgCurrencyRepository::new(pool.clone()),
+            )),
+        ),
+    )
+}
+
+/// Expired carts are answered as missing ones.
+pub fn error_response(error: CartServiceError) -> HttpResponse {
+    match error {
+        CartServiceError::CartNotFoundError => HttpResponse::NotFound().finish(),
+        CartServiceError::ConcurrencyConflictError => {
+            HttpResponse::Conflict().body(error.to_string())
+        }
+        CartServiceError::InvalidCartError(_)
+        | CartServiceError::OrderError(
+            OrderServiceError::InvalidAddressError(_)
+            | OrderServiceError::ShippingMethodNotFoundError
+            | OrderServiceError::InvalidShippingError(_),
+        ) => HttpResponse::UnprocessableEntity().body(error.to_string()),
+        error => HttpResponse::BadRequest().body(error.to_string()),
+    }
+}
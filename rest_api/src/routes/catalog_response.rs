@@ -0,0 +1,150 @@
+use actix_web::HttpResponse;
+use domain::{
+    entities::{
+        category::Category,
+        product::{Attribute, AttributeValue, Product},
+    },
+    services::catalog_service::{CatalogService, CatalogServiceError},
+};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+use super::review_response::RatingResponse;
+
+#[derive(Serialize)]
+pub struct CategoryResponse {
+    category_id: String,
+    name: String,
+    parent_id: Option<String>,
+}
+
+impl From<&Category> for CategoryResponse {
+    fn from(category: &Category) -> Self {
+        Self {
+            category_id: category.id().0.to_string(),
+            name: category.name().to_string(),
+            parent_id: category
+                .parent_id()
+                .map(|parent_id| parent_id.0.to_string()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ProductResponse {
+    product_id: String,
+    name: String,
+    category_id: Option<String>,
+    attributes: Vec<AttributeResponse>,
+    variants: Vec<VariantResponse>,
+    version: i64,
+    /// Only in the answers about a single product.
+    #[serde(flatten)]
+    rating: Option<RatingResponse>,
+}
+
+#[derive(Serialize)]
+struct VariantResponse {
+    variant_id: String,
+    sku: String,
+    price: f64,
+    attributes: Vec<AttributeResponse>,
+}
+
+#[derive(Serialize)]
+struct AttributeResponse {
+    name: String,
+    kind: String,
+    value: AttributeValueResponse,
+}
+
+/// Values are answered as JSON strings, numbers or booleans.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum AttributeValueResponse {
+    Text(String),
+    Number(f64),
+    Boolean(bool),
+}
+
+impl From<&Attribute> for AttributeResponse {
+    fn from(attribute: &Attribute) -> Self {
+        Self {
+            name: attribute.name.clone(),
+            kind: attribute.value.kind().to_string(),
+            value: match &attribute.value {
+                AttributeValue::Text(text) => AttributeValueResponse::Text(text.clone()),
+                AttributeValue::Number(number) => AttributeValueResponse::Number(*number),
+                AttributeValue::Boolean(boolean) => AttributeValueResponse::Boolean(*boolean),
+            },
+        }
+    }
+}
+
+impl From<&Product> for ProductResponse {
+    fn from(product: &Product) -> Self {
+        Self {
+            product_id: product.id().0.to_string(),
+            name: product.name().to_string(),
+            category_id: product
+                .category_id()
+                .map(|category_id| category_id.0.to_string()),
+            attributes: product
+                .attributes()
+                .iter()
+                .map(AttributeResponse::from)
+                .collect(),
+            variants: product
+                .variants()
+                .iter()
+                .map(|variant| VariantResponse {
+                    variant_id: variant.id.0.to_string(),
+                    sku: variant.sku.to_string(),
+                    price: variant.price,
+                    attributes: variant
+                        .attributes
+                        .iter()
+                        .map(AttributeResponse::from)
+                        .collect(),
+                })
+                .collect(),
+            version: product.version(),
+            rating: None,
+        }
+    }
+}
+
+impl ProductResponse {
+    pub fn with_rating(self, rating: RatingResponse) -> Self {
+        Self {
+            rating: Some(rating),
+            ..self
+        }
+    }
+}
+
+pub fn catalog_service(pool: &Pool<Postgres>) -> CatalogService {
+    CatalogService::new(Box::new(
+        adapters::sqlx::pg_catalog_repository::PgCatalogRepository::new(pool.clone()),
+    ))
+}
+
+pub fn error_response(error: CatalogServiceError) -> HttpResponse {
+    match error {
+        CatalogServiceError::CategoryNotFoundError | CatalogServiceError::ProductNotFoundError => {
+            HttpResponse::NotFound().body(error.to_string())
+        }
+        CatalogServiceError::CategoryAlreadyExistsError
+        | CatalogServiceError::ProductAlreadyExistsError
+        | CatalogServiceError::VariantAlreadyExistsError
+        | CatalogServiceError::SkuAlreadyExistsError
+        | CatalogServiceError::ConcurrencyConflictError => {
+            HttpResponse::Conflict().body(error.to_string())
+        }
+        CatalogServiceError::InvalidCategoryError(_)
+        | CatalogServiceError::InvalidProductError(_) => {
+            HttpResponse::UnprocessableEntity().body(error.to_string())
+        }
+        error => HttpResponse::BadRequest().body(error.to_string()),
+    }
+}
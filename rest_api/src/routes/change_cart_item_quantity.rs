@@ -0,0 +1,48 @@
+use actix_web::{
+    http::header::{ETag, IfMatch},
+    put, web, HttpResponse, Responder,
+};
+use domain::services::cart_service::ChangeCartItemQuantityRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    cart_response::{cart_service, error_response, CartResponse},
+    etag::{etag, expected_version},
+};
+
+#[put("/carts/{cart_id}/items/{product_id}")]
+async fn change_cart_item_quantity(
+    path: web::Path<(String, String)>,
+    data: web::Form<QuantityData>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let mut cart_service = cart_service(pool.get_ref());
+
+    let (cart_id, product_id) = path.into_inner();
+    match cart_service
+        .change_item_quantity(ChangeCartItemQuantityRequestObject {
+            cart_id,
+            product_id,
+            quantity: data.quantity,
+            expected_version,
+        })
+        .await
+    {
+        Ok(cart) => HttpResponse::Ok()
+            .insert_header(ETag(etag(cart.version())))
+            .json(CartResponse::from(&cart)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct QuantityData {
+    quantity: i32,
+}
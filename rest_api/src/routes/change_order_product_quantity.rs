@@ -0,0 +1,48 @@
+use actix_web::{
+    http::header::{ETag, IfMatch},
+    put, web, HttpResponse, Responder,
+};
+use domain::services::order_service::ChangeProductQuantityRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    etag::{etag, expected_version},
+    order_response::{error_response, order_service, OrderResponse},
+};
+
+#[put("/orders/{order_id}/products/{product_id}")]
+async fn change_order_product_quantity(
+    path: web::Path<(String, String)>,
+    data: web::Form<QuantityData>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let mut order_service = order_service(pool.get_ref());
+
+    let (order_id, product_id) = path.into_inner();
+    match order_service
+        .change_product_quantity(ChangeProductQuantityRequestObject {
+            order_id,
+            product_id,
+            quantity: data.quantity,
+            expected_version,
+        })
+        .await
+    {
+        Ok(order) => HttpResponse::Ok()
+            .insert_header(ETag(etag(order.version())))
+            .json(OrderResponse::from(&order)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct QuantityData {
+    quantity: i32,
+}
@@ -0,0 +1,59 @@
+use actix_web::{
+    http::header::{ETag, IfMatch},
+    post, web, HttpResponse, Responder,
+};
+use domain::services::cart_service::CheckoutRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    cart_response::{cart_service, error_response},
+    etag::{etag, expected_version},
+    order_response::OrderResponse,
+};
+
+#[post("/carts/{cart_id}/checkout")]
+async fn checkout_cart(
+    path: web::Path<String>,
+    data: web::Form<CheckoutData>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let mut cart_service = cart_service(pool.get_ref());
+
+    match cart_service
+        .checkout(CheckoutRequestObject {
+            cart_id: path.into_inner(),
+            order_id: data.order_id.clone(),
+            shipping_address_id: data.shipping_address_id.clone(),
+            billing_address_id: data.billing_address_id.clone(),
+            pricing_mode: data.pricing_mode.clone(),
+            currency: data.currency.clone(),
+            shipping_method_id: data.shipping_method_id.clone(),
+            shipping_weight: data.shipping_weight,
+            expected_version,
+        })
+        .await
+    {
+        Ok(order) => HttpResponse::Created()
+            .insert_header(ETag(etag(order.version())))
+            .json(OrderResponse::from(&order)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct CheckoutData {
+    order_id: String,
+    shipping_address_id: Option<String>,
+    billing_address_id: Option<String>,
+    pricing_mode: Option<String>,
+    currency: Option<String>,
+    shipping_method_id: Option<String>,
+    shipping_weight: Option<f64>,
+}
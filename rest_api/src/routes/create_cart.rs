@@ -0,0 +1,26 @@
+use actix_web::{http::header::ETag, post, web, HttpResponse, Responder};
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    cart_response::{cart_service, error_response, CartResponse},
+    etag::etag,
+};
+
+/// Starts an anonymous cart, or returns the cart of the customer if given.
+#[post("/carts")]
+async fn create_cart(data: web::Form<CartData>, pool: web::Data<Pool<Postgres>>) -> impl Responder {
+    let mut cart_service = cart_service(pool.get_ref());
+
+    match cart_service.create_cart(data.customer_id.as_deref()).await {
+        Ok(cart) => HttpResponse::Created()
+            .insert_header(ETag(etag(cart.version())))
+            .json(CartResponse::from(&cart)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct CartData {
+    customer_id: Option<String>,
+}
@@ -0,0 +1,34 @@
+use actix_web::{post, web, HttpResponse, Responder};
+use domain::services::catalog_service::CreateCategoryRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::catalog_response::{catalog_service, error_response, CategoryResponse};
+
+#[post("/categories")]
+async fn create_category(
+    data: web::Form<CategoryData>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let catalog_service = catalog_service(pool.get_ref());
+    let data = data.into_inner();
+
+    match catalog_service
+        .create_category(CreateCategoryRequestObject {
+            category_id: data.category_id,
+            name: data.name,
+            parent_id: data.parent_id,
+        })
+        .await
+    {
+        Ok(category) => HttpResponse::Created().json(CategoryResponse::from(&category)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct CategoryData {
+    category_id: String,
+    name: String,
+    parent_id: Option<String>,
+}
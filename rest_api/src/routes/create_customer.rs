@@ -1,8 +1,13 @@
-use actix_web::{post, web, HttpResponse, Responder};
+use actix_web::{http::header::ETag, post, web, HttpResponse, Responder};
 use domain::services::customer_service::CreateCustomerRequestObject;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use sqlx::{Pool, Postgres};
 
+use super::{
+    customer_response::{error_response, CustomerResponse},
+    etag::etag,
+};
+
 #[post("/customers")]
 async fn create_customer(
     data: web::Form<CustomerData>,
@@ -24,17 +29,20 @@ async fn create_customer(
         .create_customer(CreateCustomerRequestObject {
             first_name: data.first_name.clone(),
             last_name: data.last_name.clone(),
+            email: data.email.clone(),
+            phone: data.phone.clone(),
             street: data.street.clone(),
             city: data.city.clone(),
             zip_code: data.zip_code.clone(),
-            state: data.state.clone(),
+            country: data.country.clone(),
+            currency: data.currency.clone(),
         })
         .await
     {
-        Ok(customer) => HttpResponse::Ok().json(CustomerResponse {
-            customer_id: customer.id.0.to_string(),
-        }),
-        Err(error) => HttpResponse::BadRequest().body(error.to_string()),
+        Ok(customer) => HttpResponse::Ok()
+            .insert_header(ETag(etag(customer.version)))
+            .json(CustomerResponse::from(&customer)),
+        Err(error) => error_response(error),
     }
 }
 
@@ -42,13 +50,11 @@ async fn create_customer(
 struct CustomerData {
     first_name: String,
     last_name: String,
+    email: String,
+    phone: Option<String>,
     street: String,
     city: String,
     zip_code: String,
-    state: String,
-}
-
-#[derive(Serialize)]
-struct CustomerResponse {
-    customer_id: String,
+    country: String,
+    currency: Option<String>,
 }
@@ -1,8 +1,13 @@
-use actix_web::{post, web, HttpResponse, Responder};
-use domain::services::order_service::CreateOrderRequestObject;
+use actix_web::{http::header::ETag, post, web, HttpResponse, Responder};
+use domain::services::{
+    currency_service::CurrencyServiceError,
+    order_service::{CreateOrderRequestObject, OrderServiceError},
+};
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Postgres};
 
+use super::etag::etag;
+
 #[post("/orders")]
 async fn create_order(
     data: web::Form<OrderData>,
@@ -23,19 +28,51 @@ async fn create_order(
         Box::new(customer_repository),
         Box::new(order_repository),
         Box::new(outbox_message_repository),
+        Box::new(
+            adapters::sqlx::pg_catalog_repository::PgCatalogRepository::new(pool.get_ref().clone()),
+        ),
+        Box::new(
+            adapters::sqlx::pg_shipping_method_repository::PgShippingMethodRepository::new(
+                pool.get_ref().clone(),
+            ),
+        ),
+        Box::new(adapters::sqlx::pg_tax_calculator::PgTaxCalculator::new(
+            pool.get_ref().clone(),
+        )),
+        domain::services::currency_service::CurrencyService::new(Box::new(
+            adapters::sqlx::pg_currency_repository::PgCurrencyRepository::new(
+                pool.get_ref().clone(),
+            ),
+        )),
     );
 
     match order_service
         .create_order(CreateOrderRequestObject {
             order_id: data.order_id.clone(),
             customer_id: data.customer_id.clone(),
+            shipping_address_id: data.shipping_address_id.clone(),
+            billing_address_id: data.billing_address_id.clone(),
+            pricing_mode: data.pricing_mode.clone(),
+            currency: data.currency.clone(),
+            shipping_method_id: data.shipping_method_id.clone(),
+            shipping_weight: data.shipping_weight,
         })
         .await
     {
-        Ok(_) => HttpResponse::Ok().json(OrderResponse {
-            order_id: data.order_id.clone(),
-            customer_id: data.customer_id.clone(),
-        }),
+        Ok(order) => HttpResponse::Ok()
+            .insert_header(ETag(etag(order.version())))
+            .json(OrderResponse {
+                order_id: data.order_id.clone(),
+                customer_id: data.customer_id.clone(),
+            }),
+        Err(
+            error @ (OrderServiceError::InvalidAddressError(_)
+            | OrderServiceError::ShippingMethodNotFoundError
+            | OrderServiceError::InvalidShippingError(_)
+            | OrderServiceError::CurrencyError(
+                CurrencyServiceError::ExchangeRateNotFoundError(_),
+            )),
+        ) => HttpResponse::UnprocessableEntity().body(error.to_string()),
         Err(error) => HttpResponse::BadRequest().body(error.to_string()),
     }
 }
@@ -44,6 +81,12 @@ async fn create_order(
 struct OrderData {
     order_id: String,
     customer_id: String,
+    shipping_address_id: Option<String>,
+    billing_address_id: Option<String>,
+    pricing_mode: Option<String>,
+    currency: Option<String>,
+    shipping_method_id: Option<String>,
+    shipping_weight: Option<f64>,
 }
 
 #[derive(Serialize)]
@@ -0,0 +1,39 @@
+use actix_web::{http::header::ETag, post, web, HttpResponse, Responder};
+use domain::services::catalog_service::CreateProductRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    catalog_response::{catalog_service, error_response, ProductResponse},
+    etag::etag,
+};
+
+#[post("/products")]
+async fn create_product(
+    data: web::Form<ProductData>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let catalog_service = catalog_service(pool.get_ref());
+    let data = data.into_inner();
+
+    match catalog_service
+        .create_product(CreateProductRequestObject {
+            product_id: data.product_id,
+            name: data.name,
+            category_id: data.category_id,
+        })
+        .await
+    {
+        Ok(product) => HttpResponse::Created()
+            .insert_header(ETag(etag(product.version())))
+            .json(ProductResponse::from(&product)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct ProductData {
+    product_id: String,
+    name: String,
+    category_id: Option<String>,
+}
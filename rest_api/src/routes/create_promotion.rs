@@ -0,0 +1,50 @@
+use actix_web::{post, web, HttpResponse, Responder};
+use domain::services::promotion_service::CreatePromotionRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::promotion_response::{error_response, promotion_service, PromotionResponse};
+
+#[post("/promotions")]
+async fn create_promotion(
+    data: web::Form<PromotionData>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let promotion_service = promotion_service(pool.get_ref());
+    let data = data.into_inner();
+
+    match promotion_service
+        .create_promotion(CreatePromotionRequestObject {
+            promotion_id: data.promotion_id,
+            code: data.code,
+            kind: data.kind,
+            value: data.value,
+            product_id: data.product_id,
+            buy: data.buy,
+            get: data.get,
+            min_spend: data.min_spend,
+            valid_from: data.valid_from,
+            valid_until: data.valid_until,
+            max_uses_per_customer: data.max_uses_per_customer,
+        })
+        .await
+    {
+        Ok(promotion) => HttpResponse::Created().json(PromotionResponse::from(&promotion)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct PromotionData {
+    promotion_id: String,
+    code: String,
+    kind: String,
+    value: Option<f64>,
+    product_id: Option<String>,
+    buy: Option<i32>,
+    get: Option<i32>,
+    min_spend: Option<f64>,
+    valid_from: Option<String>,
+    valid_until: Option<String>,
+    max_uses_per_customer: Option<i64>,
+}
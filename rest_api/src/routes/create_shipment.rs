@@ -0,0 +1,44 @@
+use actix_web::{http::header::ETag, post, web, HttpResponse, Responder};
+use domain::services::shipment_service::CreateShipmentRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    etag::etag,
+    shipment_response::{error_response, shipment_service, ShipmentResponse},
+};
+
+#[post("/orders/{order_id}/shipments")]
+async fn create_shipment(
+    path: web::Path<String>,
+    data: web::Form<ShipmentData>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let mut shipment_service = shipment_service(pool.get_ref());
+
+    match shipment_service
+        .create_shipment(CreateShipmentRequestObject {
+            shipment_id: data.shipment_id.clone(),
+            order_id: path.into_inner(),
+            shipping_method_id: data.shipping_method_id.clone(),
+            weight: data.weight,
+            product_id: data.product_id.clone(),
+            quantity: data.quantity,
+        })
+        .await
+    {
+        Ok(shipment) => HttpResponse::Created()
+            .insert_header(ETag(etag(shipment.version())))
+            .json(ShipmentResponse::from(&shipment)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct ShipmentData {
+    shipment_id: String,
+    shipping_method_id: String,
+    weight: f64,
+    product_id: String,
+    quantity: i32,
+}
@@ -0,0 +1,44 @@
+use actix_web::{post, web, HttpResponse, Responder};
+use domain::services::shipment_service::CreateShippingMethodRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::shipment_response::{error_response, shipment_service, ShippingMethodResponse};
+
+#[post("/shipping-methods")]
+async fn create_shipping_method(
+    data: web::Form<ShippingMethodData>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let shipment_service = shipment_service(pool.get_ref());
+    let data = data.into_inner();
+
+    match shipment_service
+        .create_shipping_method(CreateShippingMethodRequestObject {
+            shipping_method_id: data.shipping_method_id,
+            name: data.name,
+            carrier: data.carrier,
+            kind: data.kind,
+            amount: data.amount,
+            per_kg: data.per_kg,
+            threshold: data.threshold,
+        })
+        .await
+    {
+        Ok(shipping_method) => {
+            HttpResponse::Created().json(ShippingMethodResponse::from(&shipping_method))
+        }
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct ShippingMethodData {
+    shipping_method_id: String,
+    name: String,
+    carrier: String,
+    kind: String,
+    amount: Option<f64>,
+    per_kg: Option<f64>,
+    threshold: Option<f64>,
+}
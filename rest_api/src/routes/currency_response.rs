@@ -0,0 +1,56 @@
+use actix_web::HttpResponse;
+use domain::{
+    entities::currency::{ExchangeRate, ProductPrice},
+    services::currency_service::{CurrencyService, CurrencyServiceError},
+};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+#[derive(Serialize)]
+pub struct ExchangeRateResponse {
+    currency: String,
+    effective_from: String,
+    rate: f64,
+}
+
+impl From<&ExchangeRate> for ExchangeRateResponse {
+    fn from(rate: &ExchangeRate) -> Self {
+        Self {
+            currency: rate.currency().to_string(),
+            effective_from: rate.effective_from().to_string(),
+            rate: rate.rate(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ProductPriceResponse {
+    product_id: String,
+    currency: String,
+    amount: f64,
+}
+
+impl From<&ProductPrice> for ProductPriceResponse {
+    fn from(price: &ProductPrice) -> Self {
+        Self {
+            product_id: price.product_id.0.to_string(),
+            currency: price.currency.to_string(),
+            amount: price.amount,
+        }
+    }
+}
+
+pub fn currency_service(pool: &Pool<Postgres>) -> CurrencyService {
+    CurrencyService::new(Box::new(
+        adapters::sqlx::pg_currency_repository::PgCurrencyRepository::new(pool.clone()),
+    ))
+}
+
+pub fn error_response(error: CurrencyServiceError) -> HttpResponse {
+    match error {
+        CurrencyServiceError::InvalidCurrencyError(_) => {
+            HttpResponse::UnprocessableEntity().body(error.to_string())
+        }
+        error => HttpResponse::BadRequest().body(error.to_string()),
+    }
+}
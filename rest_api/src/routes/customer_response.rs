@@ -0,0 +1,95 @@
+use actix_web::HttpResponse;
+use domain::{
+    entities::{address_book::AddressBookError, customer::Customer},
+    services::customer_service::CustomerServiceError,
+};
+use serde::Serialize;
+
+use super::validation_error_response::ValidationErrorResponse;
+
+#[derive(Serialize)]
+pub struct CustomerResponse {
+    customer_id: String,
+    first_name: String,
+    last_name: String,
+    email: String,
+    phone: Option<String>,
+    street: String,
+    city: String,
+    zip_code: String,
+    country: String,
+    currency: String,
+    addresses: Vec<CustomerAddressResponse>,
+    default_shipping_address_id: Option<String>,
+    default_billing_address_id: Option<String>,
+    version: i64,
+}
+
+#[derive(Serialize)]
+struct CustomerAddressResponse {
+    address_id: String,
+    street: String,
+    city: String,
+    zip_code: String,
+    country: String,
+}
+
+impl From<&Customer> for CustomerResponse {
+    fn from(customer: &Customer) -> Self {
+        Self {
+            customer_id: customer.id.0.to_string(),
+            first_name: customer.first_name.to_string(),
+            last_name: customer.last_name.to_string(),
+            email: customer.email.to_string(),
+            phone: customer.phone.as_ref().map(|phone| phone.to_string()),
+            street: customer.address.street.clone(),
+            city: customer.address.city.clone(),
+            zip_code: customer.address.zip_code.to_string(),
+            country: customer.address.country.to_string(),
+            currency: customer.currency.to_string(),
+            addresses: customer
+                .address_book
+                .as_slice()
+                .iter()
+                .map(|customer_address| CustomerAddressResponse {
+                    address_id: customer_address.id.0.to_string(),
+                    street: customer_address.address.street.clone(),
+                    city: customer_address.address.city.clone(),
+                    zip_code: customer_address.address.zip_code.to_string(),
+                    country: customer_address.address.country.to_string(),
+                })
+                .collect(),
+            default_shipping_address_id: customer
+                .address_book
+                .default_shipping()
+                .map(|id| id.0.to_string()),
+            default_billing_address_id: customer
+                .address_book
+                .default_billing()
+                .map(|id| id.0.to_string()),
+            version: customer.version,
+        }
+    }
+}
+
+/// Deactivated customers are answered as missing ones.
+pub fn error_response(error: CustomerServiceError) -> HttpResponse {
+    match error {
+        CustomerServiceError::CustomerNotFoundError
+        | CustomerServiceError::CustomerDeactivatedError => HttpResponse::NotFound().finish(),
+        CustomerServiceError::CustomerAlreadyExistsError
+        | CustomerServiceError::ConcurrencyConflictError => {
+            HttpResponse::Conflict().body(error.to_string())
+        }
+        CustomerServiceError::AddressBookError(AddressBookError::AddressNotFoundError(_)) => {
+            HttpResponse::NotFound().body(error.to_string())
+        }
+        CustomerServiceError::AddressBookError(_) => {
+            HttpResponse::UnprocessableEntity().body(error.to_string())
+        }
+        CustomerServiceError::ValidationError(errors) => {
+            HttpResponse::UnprocessableEntity().json(ValidationErrorResponse::from(&errors[..]))
+        }
+        error => HttpResponse::BadRequest().body(error.to_string()),
+    }
+}
@@ -0,0 +1,36 @@
+use actix_web::{delete, http::header::IfMatch, web, HttpResponse, Responder};
+use sqlx::{Pool, Postgres};
+
+use super::{customer_response::error_response, etag::expected_version};
+
+#[delete("/customers/{customer_id}")]
+async fn deactivate_customer(
+    path: web::Path<String>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let customer_repository =
+        adapters::sqlx::pg_customer_repository::PgCustomerRepository::new(pool.get_ref().clone());
+    let outbox_message_repository =
+        adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(
+            pool.get_ref().clone(),
+        );
+
+    let mut customer_service = domain::services::customer_service::CustomerService::new(
+        Box::new(customer_repository),
+        Box::new(outbox_message_repository),
+    );
+
+    match customer_service
+        .deactivate(&path.into_inner(), expected_version)
+        .await
+    {
+        Ok(_) => HttpResponse::NoContent().finish(),
+        Err(error) => error_response(error),
+    }
+}
@@ -0,0 +1,34 @@
+use actix_web::{
+    http::header::{ETag, IfMatch},
+    post, web, HttpResponse, Responder,
+};
+use sqlx::{Pool, Postgres};
+
+use super::{
+    etag::{etag, expected_version},
+    shipment_response::{error_response, shipment_service, ShipmentResponse},
+};
+
+#[post("/shipments/{shipment_id}/deliver")]
+async fn deliver_shipment(
+    path: web::Path<String>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let mut shipment_service = shipment_service(pool.get_ref());
+
+    match shipment_service
+        .deliver(&path.into_inner(), expected_version)
+        .await
+    {
+        Ok(shipment) => HttpResponse::Ok()
+            .insert_header(ETag(etag(shipment.version())))
+            .json(ShipmentResponse::from(&shipment)),
+        Err(error) => error_response(error),
+    }
+}
@@ -0,0 +1,38 @@
+use actix_web::{post, web, HttpResponse, Responder};
+use domain::services::customer_data_service::{CustomerDataService, CustomerDataServiceError};
+use sqlx::{Pool, Postgres};
+
+#[post("/customers/{customer_id}/erasure")]
+async fn erase_customer(
+    path: web::Path<String>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let customer_repository =
+        adapters::sqlx::pg_customer_repository::PgCustomerRepository::new(pool.get_ref().clone());
+    let order_repository =
+        adapters::sqlx::pg_order_repository::PgOrderRepository::new(pool.get_ref().clone());
+    let outbox_message_repository =
+        adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(
+            pool.get_ref().clone(),
+        );
+
+    let customer_data_service = CustomerDataService::new(
+        Box::new(customer_repository),
+        Box::new(order_repository),
+        Box::new(outbox_message_repository),
+        Box::new(
+            adapters::sqlx::pg_customer_data_repository::PgCustomerDataRepository::new(
+                pool.get_ref().clone(),
+            ),
+        ),
+    );
+
+    match customer_data_service.erase(&path.into_inner()).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(CustomerDataServiceError::CustomerNotFoundError) => HttpResponse::NotFound().finish(),
+        Err(error @ CustomerDataServiceError::ConcurrencyConflictError) => {
+            HttpResponse::Conflict().body(error.to_string())
+        }
+        Err(error) => HttpResponse::BadRequest().body(error.to_string()),
+    }
+}
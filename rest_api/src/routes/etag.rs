@@ -0,0 +1,25 @@
+use actix_web::{
+    http::header::{EntityTag, IfMatch},
+    web, HttpResponse,
+};
+
+/// Aggregates are tagged with their version, so that clients can send it back
+/// in `If-Match` to update only what they have read.
+pub fn etag(version: i64) -> EntityTag {
+    EntityTag::new_strong(version.to_string())
+}
+
+/// Returns the version required by `If-Match`, or `None` if any version is fine.
+pub fn expected_version(
+    if_match: Option<web::Header<IfMatch>>,
+) -> Result<Option<i64>, HttpResponse> {
+    // A missing header is extracted as an empty list of tags.
+    match if_match.map(web::Header::into_inner) {
+        None | Some(IfMatch::Any) => Ok(None),
+        Some(IfMatch::Items(tags)) => match tags.first().map(|tag| tag.tag().parse()) {
+            None => Ok(None),
+            Some(Ok(version)) => Ok(Some(version)),
+            Some(Err(_)) => Err(HttpResponse::BadRequest().body("Invalid If-Match header")),
+        },
+    }
+}
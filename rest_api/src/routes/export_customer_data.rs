@@ -0,0 +1,81 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use domain::{
+    entities::outbox::OutboxMessage,
+    services::customer_data_service::{
+        CustomerDataExport, CustomerDataService, CustomerDataServiceError,
+    },
+};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+use super::{customer_response::CustomerResponse, order_response::OrderResponse};
+
+#[get("/customers/{customer_id}/export")]
+async fn export_customer_data(
+    path: web::Path<String>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let customer_repository =
+        adapters::sqlx::pg_customer_repository::PgCustomerRepository::new(pool.get_ref().clone());
+    let order_repository =
+        adapters::sqlx::pg_order_repository::PgOrderRepository::new(pool.get_ref().clone());
+    let outbox_message_repository =
+        adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(
+            pool.get_ref().clone(),
+        );
+
+    let customer_data_service = CustomerDataService::new(
+        Box::new(customer_repository),
+        Box::new(order_repository),
+        Box::new(outbox_message_repository),
+        Box::new(
+            adapters::sqlx::pg_customer_data_repository::PgCustomerDataRepository::new(
+                pool.get_ref().clone(),
+            ),
+        ),
+    );
+
+    match customer_data_service.export(&path.into_inner()).await {
+        Ok(export) => HttpResponse::Ok().json(CustomerDataExportResponse::from(&export)),
+        Err(CustomerDataServiceError::CustomerNotFoundError) => HttpResponse::NotFound().finish(),
+        Err(error) => HttpResponse::BadRequest().body(error.to_string()),
+    }
+}
+
+#[derive(Serialize)]
+struct CustomerDataExportResponse {
+    customer: CustomerResponse,
+    orders: Vec<OrderResponse>,
+    events: Vec<EventResponse>,
+}
+
+#[derive(Serialize)]
+struct EventResponse {
+    event_id: String,
+    event_type: String,
+    created_at: String,
+    payload: serde_json::Value,
+}
+
+impl From<&CustomerDataExport> for CustomerDataExportResponse {
+    fn from(export: &CustomerDataExport) -> Self {
+        Self {
+            customer: CustomerResponse::from(&export.customer),
+            orders: export.orders.iter().map(OrderResponse::from).collect(),
+            events: export.events.iter().map(EventResponse::from).collect(),
+        }
+    }
+}
+
+impl From<&OutboxMessage> for EventResponse {
+    fn from(message: &OutboxMessage) -> Self {
+        let payload = message.event_payload();
+        Self {
+            event_id: message.id().to_string(),
+            event_type: message.event_type().to_string(),
+            created_at: message.created_at().to_rfc3339(),
+            // Payloads are always JSON, but one that isn't is still exported.
+            payload: serde_json::from_str(&payload).unwrap_or(serde_json::Value::String(payload)),
+        }
+    }
+}
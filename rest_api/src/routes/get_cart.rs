@@ -0,0 +1,19 @@
+use actix_web::{get, http::header::ETag, web, HttpResponse, Responder};
+use sqlx::{Pool, Postgres};
+
+use super::{
+    cart_response::{cart_service, error_response, CartResponse},
+    etag::etag,
+};
+
+#[get("/carts/{cart_id}")]
+async fn get_cart(path: web::Path<String>, pool: web::Data<Pool<Postgres>>) -> impl Responder {
+    let cart_service = cart_service(pool.get_ref());
+
+    match cart_service.find_cart(&path.into_inner()).await {
+        Ok(cart) => HttpResponse::Ok()
+            .insert_header(ETag(etag(cart.version())))
+            .json(CartResponse::from(&cart)),
+        Err(error) => error_response(error),
+    }
+}
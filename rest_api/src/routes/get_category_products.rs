@@ -0,0 +1,26 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use sqlx::{Pool, Postgres};
+
+use super::catalog_response::{catalog_service, error_response, ProductResponse};
+
+/// Products of the category and of the categories under it.
+#[get("/categories/{category_id}/products")]
+async fn get_category_products(
+    path: web::Path<String>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let catalog_service = catalog_service(pool.get_ref());
+
+    match catalog_service
+        .find_products_in_category(&path.into_inner())
+        .await
+    {
+        Ok(products) => HttpResponse::Ok().json(
+            products
+                .iter()
+                .map(ProductResponse::from)
+                .collect::<Vec<_>>(),
+        ),
+        Err(error) => error_response(error),
+    }
+}
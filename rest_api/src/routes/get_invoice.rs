@@ -0,0 +1,34 @@
+use actix_web::{get, http::header::Accept, web, HttpResponse, Responder};
+use sqlx::{Pool, Postgres};
+
+use super::{
+    invoice_pdf::invoice_pdf,
+    invoice_response::{error_response, invoice_service, InvoiceResponse},
+};
+
+/// JSON by default, PDF for clients accepting `application/pdf`.
+#[get("/orders/{order_id}/invoice")]
+async fn get_invoice(
+    path: web::Path<String>,
+    accept: Option<web::Header<Accept>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let invoice_service = invoice_service(pool.get_ref());
+
+    let invoices = match invoice_service.find_invoices(&path.into_inner()).await {
+        Ok(invoices) => invoices,
+        Err(error) => return error_response(error),
+    };
+    let wants_pdf = accept.is_some_and(|accept| {
+        accept
+            .iter()
+            .any(|mime| mime.item.essence_str() == "application/pdf")
+    });
+    if !wants_pdf {
+        return HttpResponse::Ok().json(InvoiceResponse::from(&invoices));
+    }
+    match invoice_pdf(&invoices) {
+        Ok(pdf) => HttpResponse::Ok().content_type("application/pdf").body(pdf),
+        Err(error) => HttpResponse::InternalServerError().body(error.to_string()),
+    }
+}
@@ -0,0 +1,49 @@
+use actix_web::{get, http::header::ETag, web, HttpResponse, Responder};
+use domain::services::order_service::OrderServiceError;
+use sqlx::{Pool, Postgres};
+
+use super::{etag::etag, order_response::OrderResponse};
+
+#[get("/orders/{order_id}")]
+async fn get_order(path: web::Path<String>, pool: web::Data<Pool<Postgres>>) -> impl Responder {
+    let customer_repository =
+        adapters::sqlx::pg_customer_repository::PgCustomerRepository::new(pool.get_ref().clone());
+
+    let order_repository =
+        adapters::sqlx::pg_order_repository::PgOrderRepository::new(pool.get_ref().clone());
+
+    let outbox_message_repository =
+        adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(
+            pool.get_ref().clone(),
+        );
+
+    let order_service = domain::services::order_service::OrderService::new(
+        Box::new(customer_repository),
+        Box::new(order_repository),
+        Box::new(outbox_message_repository),
+        Box::new(
+            adapters::sqlx::pg_catalog_repository::PgCatalogRepository::new(pool.get_ref().clone()),
+        ),
+        Box::new(
+            adapters::sqlx::pg_shipping_method_repository::PgShippingMethodRepository::new(
+                pool.get_ref().clone(),
+            ),
+        ),
+        Box::new(adapters::sqlx::pg_tax_calculator::PgTaxCalculator::new(
+            pool.get_ref().clone(),
+        )),
+        domain::services::currency_service::CurrencyService::new(Box::new(
+            adapters::sqlx::pg_currency_repository::PgCurrencyRepository::new(
+                pool.get_ref().clone(),
+            ),
+        )),
+    );
+
+    match order_service.find_order(&path.into_inner()).await {
+        Ok(order) => HttpResponse::Ok()
+            .insert_header(ETag(etag(order.version())))
+            .json(OrderResponse::from(&order)),
+        Err(OrderServiceError::OrderNotFoundError) => HttpResponse::NotFound().finish(),
+        Err(error) => HttpResponse::BadRequest().body(error.to_string()),
+    }
+}
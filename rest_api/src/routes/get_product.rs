@@ -0,0 +1,26 @@
+use actix_web::{get, http::header::ETag, web, HttpResponse, Responder};
+use sqlx::{Pool, Postgres};
+
+use super::{
+    catalog_response::{catalog_service, error_response, ProductResponse},
+    etag::etag,
+    review_response::{self, review_service, RatingResponse},
+};
+
+/// The product with the average rating of its approved reviews.
+#[get("/products/{product_id}")]
+async fn get_product(path: web::Path<String>, pool: web::Data<Pool<Postgres>>) -> impl Responder {
+    let catalog_service = catalog_service(pool.get_ref());
+    let product_id = path.into_inner();
+
+    let product = match catalog_service.find_product(&product_id).await {
+        Ok(product) => product,
+        Err(error) => return error_response(error),
+    };
+    match review_service(pool.get_ref()).rating(&product_id).await {
+        Ok(rating) => HttpResponse::Ok()
+            .insert_header(ETag(etag(product.version())))
+            .json(ProductResponse::from(&product).with_rating(RatingResponse::from(&rating))),
+        Err(error) => review_response::error_response(error),
+    }
+}
@@ -0,0 +1,22 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use sqlx::{Pool, Postgres};
+
+use super::currency_response::{currency_service, error_response, ProductPriceResponse};
+
+#[get("/products/{product_id}/prices")]
+async fn get_product_prices(
+    path: web::Path<String>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let currency_service = currency_service(pool.get_ref());
+
+    match currency_service.find_prices(&path.into_inner()).await {
+        Ok(prices) => HttpResponse::Ok().json(
+            prices
+                .iter()
+                .map(ProductPriceResponse::from)
+                .collect::<Vec<_>>(),
+        ),
+        Err(error) => error_response(error),
+    }
+}
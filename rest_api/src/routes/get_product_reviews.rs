@@ -0,0 +1,45 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+
+use super::review_response::{error_response, review_service, RatingResponse, ReviewResponse};
+
+/// Approved reviews of the product, with its rating. Moderators list the
+/// others with `status`.
+#[get("/products/{product_id}/reviews")]
+async fn get_product_reviews(
+    path: web::Path<String>,
+    query: web::Query<ReviewParams>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let review_service = review_service(pool.get_ref());
+
+    let product_id = path.into_inner();
+
+    let reviews = match review_service
+        .find_reviews(&product_id, query.status.as_deref())
+        .await
+    {
+        Ok(reviews) => reviews,
+        Err(error) => return error_response(error),
+    };
+    match review_service.rating(&product_id).await {
+        Ok(rating) => HttpResponse::Ok().json(ReviewsResponse {
+            rating: RatingResponse::from(&rating),
+            reviews: reviews.iter().map(ReviewResponse::from).collect(),
+        }),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct ReviewParams {
+    status: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ReviewsResponse {
+    #[serde(flatten)]
+    rating: RatingResponse,
+    reviews: Vec<ReviewResponse>,
+}
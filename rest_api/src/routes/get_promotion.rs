@@ -0,0 +1,14 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use sqlx::{Pool, Postgres};
+
+use super::promotion_response::{error_response, promotion_service, PromotionResponse};
+
+#[get("/promotions/{code}")]
+async fn get_promotion(path: web::Path<String>, pool: web::Data<Pool<Postgres>>) -> impl Responder {
+    let promotion_service = promotion_service(pool.get_ref());
+
+    match promotion_service.find_promotion(&path.into_inner()).await {
+        Ok(promotion) => HttpResponse::Ok().json(PromotionResponse::from(&promotion)),
+        Err(error) => error_response(error),
+    }
+}
@@ -0,0 +1,25 @@
+use actix_web::{get, http::header::ETag, web, HttpResponse, Responder};
+use sqlx::{Pool, Postgres};
+
+use crate::settings::PaymentProviderSettings;
+
+use super::{
+    etag::etag,
+    return_response::{error_response, return_service, ReturnResponse},
+};
+
+#[get("/returns/{return_id}")]
+async fn get_return(
+    path: web::Path<String>,
+    pool: web::Data<Pool<Postgres>>,
+    payment_provider: web::Data<PaymentProviderSettings>,
+) -> impl Responder {
+    let return_service = return_service(pool.get_ref(), payment_provider.get_ref());
+
+    match return_service.find_return(&path.into_inner()).await {
+        Ok(order_return) => HttpResponse::Ok()
+            .insert_header(ETag(etag(order_return.version())))
+            .json(ReturnResponse::from(&order_return)),
+        Err(error) => error_response(error),
+    }
+}
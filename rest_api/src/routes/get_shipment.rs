@@ -0,0 +1,19 @@
+use actix_web::{get, http::header::ETag, web, HttpResponse, Responder};
+use sqlx::{Pool, Postgres};
+
+use super::{
+    etag::etag,
+    shipment_response::{error_response, shipment_service, ShipmentResponse},
+};
+
+#[get("/shipments/{shipment_id}")]
+async fn get_shipment(path: web::Path<String>, pool: web::Data<Pool<Postgres>>) -> impl Responder {
+    let shipment_service = shipment_service(pool.get_ref());
+
+    match shipment_service.find_shipment(&path.into_inner()).await {
+        Ok(shipment) => HttpResponse::Ok()
+            .insert_header(ETag(etag(shipment.version())))
+            .json(ShipmentResponse::from(&shipment)),
+        Err(error) => error_response(error),
+    }
+}
@@ -0,0 +1,19 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use sqlx::{Pool, Postgres};
+
+use super::shipment_response::{error_response, shipment_service, ShippingMethodResponse};
+
+#[get("/shipping-methods")]
+async fn get_shipping_methods(pool: web::Data<Pool<Postgres>>) -> impl Responder {
+    let shipment_service = shipment_service(pool.get_ref());
+
+    match shipment_service.find_shipping_methods().await {
+        Ok(shipping_methods) => HttpResponse::Ok().json(
+            shipping_methods
+                .iter()
+                .map(ShippingMethodResponse::from)
+                .collect::<Vec<_>>(),
+        ),
+        Err(error) => error_response(error),
+    }
+}
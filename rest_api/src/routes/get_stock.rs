@@ -0,0 +1,19 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use sqlx::{Pool, Postgres};
+
+use super::inventory_response::{error_response, inventory_service, InventoryResponse};
+
+#[get("/products/{product_id}/stock")]
+async fn get_stock(path: web::Path<String>, pool: web::Data<Pool<Postgres>>) -> impl Responder {
+    let inventory_service = inventory_service(pool.get_ref());
+
+    match inventory_service.find_stock(&path.into_inner()).await {
+        Ok(inventories) => HttpResponse::Ok().json(
+            inventories
+                .iter()
+                .map(InventoryResponse::from)
+                .collect::<Vec<_>>(),
+        ),
+        Err(error) => error_response(error),
+    }
+}
@@ -0,0 +1,20 @@
+use actix_web::{post, web, HttpResponse, Responder};
+use sqlx::{Pool, Postgres};
+
+use super::currency_response::{currency_service, error_response, ExchangeRateResponse};
+
+/// The body is a CSV file of `currency,effective_from,rate` lines.
+#[post("/exchange-rates")]
+async fn import_exchange_rates(body: String, pool: web::Data<Pool<Postgres>>) -> impl Responder {
+    let currency_service = currency_service(pool.get_ref());
+
+    match currency_service.import_rates(&body).await {
+        Ok(rates) => HttpResponse::Ok().json(
+            rates
+                .iter()
+                .map(ExchangeRateResponse::from)
+                .collect::<Vec<_>>(),
+        ),
+        Err(error) => error_response(error),
+    }
+}
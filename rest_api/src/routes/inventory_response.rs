@@ -0,0 +1,54 @@
+use actix_web::HttpResponse;
+use domain::{
+    entities::inventory::Inventory,
+    services::inventory_service::{InventoryService, InventoryServiceError},
+};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+#[derive(Serialize)]
+pub struct InventoryResponse {
+    product_id: String,
+    warehouse_id: String,
+    on_hand: i32,
+    reserved: i32,
+    available: i32,
+    version: i64,
+}
+
+impl From<&Inventory> for InventoryResponse {
+    fn from(inventory: &Inventory) -> Self {
+        Self {
+            product_id: inventory.product_id().0.to_string(),
+            warehouse_id: inventory.warehouse_id().0.to_string(),
+            on_hand: inventory.on_hand(),
+            reserved: inventory.reserved(),
+            available: inventory.available(),
+            version: inventory.version(),
+        }
+    }
+}
+
+pub fn inventory_service(pool: &Pool<Postgres>) -> InventoryService {
+    let inventory_repository =
+        adapters::sqlx::pg_inventory_repository::PgInventoryRepository::new(pool.clone());
+    let outbox_message_repository =
+        adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(pool.clone());
+
+    InventoryService::new(
+        Box::new(inventory_repository),
+        Box::new(outbox_message_repository),
+    )
+}
+
+pub fn error_response(error: InventoryServiceError) -> HttpResponse {
+    match error {
+        InventoryServiceError::ConcurrencyConflictError => {
+            HttpResponse::Conflict().body(error.to_string())
+        }
+        InventoryServiceError::InvalidStockError(_) => {
+            HttpResponse::UnprocessableEntity().body(error.to_string())
+        }
+        error => HttpResponse::BadRequest().body(error.to_string()),
+    }
+}
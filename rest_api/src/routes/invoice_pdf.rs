@@ -0,0 +1,169 @@
+use domain::{
+    entities::invoice::{Invoice, InvoiceKind},
+    services::invoice_service::OrderInvoices,
+};
+use printpdf::{BuiltinFont, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference};
+
+use super::invoice_response::number;
+
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const LINE_HEIGHT: f32 = 6.0;
+/// Left edge of the columns of the lines table.
+const COLUMNS: [f32; 6] = [20.0, 100.0, 115.0, 140.0, 160.0, 178.0];
+
+/// Renders the invoice of an order followed by its credit notes, each
+/// starting on a new A4 page.
+pub fn invoice_pdf(invoices: &OrderInvoices) -> Result<Vec<u8>, printpdf::Error> {
+    let (document, page, layer) = PdfDocument::new(
+        number(&invoices.invoice),
+        Mm(PAGE_WIDTH),
+        Mm(PAGE_HEIGHT),
+        "Invoice",
+    );
+    let font = document.add_builtin_font(BuiltinFont::Helvetica)?;
+    let bold = document.add_builtin_font(BuiltinFont::HelveticaBold)?;
+    let mut writer = Writer {
+        layer: document.get_page(page).get_layer(layer),
+        document,
+        font,
+        bold,
+        y: PAGE_HEIGHT - MARGIN,
+    };
+    writer.write(&invoices.invoice);
+    for credit_note in &invoices.credit_notes {
+        writer.new_page();
+        writer.write(credit_note);
+    }
+    writer.document.save_to_bytes()
+}
+
+struct Writer {
+    document: PdfDocumentReference,
+    layer: printpdf::PdfLayerReference,
+    font: IndirectFontRef,
+    bold: IndirectFontRef,
+    /// Baseline of the next line, from the bottom of the page.
+    y: f32,
+}
+
+impl Writer {
+    fn write(&mut self, invoice: &Invoice) {
+        let title = match invoice.kind() {
+            InvoiceKind::Invoice => "Invoice",
+            InvoiceKind::CreditNote => "Credit note",
+        };
+        self.text(&format!("{} {}", title, number(invoice)), 16.0, true);
+        self.skip();
+        self.text(
+            &format!("Issued on {}", invoice.issued_at().format("%Y-%m-%d")),
+            10.0,
+            false,
+        );
+        if let Some(credited_invoice) = invoice.credited_invoice() {
+            self.text(
+                &format!("Credits invoice {}", credited_invoice),
+                10.0,
+                false,
+            );
+        }
+        self.text(&format!("Order {}", invoice.order_id().0), 10.0, false);
+        self.text(
+            &format!("Customer {}", invoice.customer_id().0),
+            10.0,
+            false,
+        );
+        if let Some(billing_address) = invoice.billing_address() {
+            self.text(
+                &format!("Billing address: {}", billing_address),
+                10.0,
+                false,
+            );
+        }
+        self.skip();
+
+        self.row(
+            &["Item", "Qty", "Unit price", "Amount", "Tax %", "Tax"].map(String::from),
+            true,
+        );
+        for line in invoice.lines() {
+            let item = match (&line.product_id, invoice.refund_key()) {
+                (Some(product_id), _) => product_id.0.to_string(),
+                (None, Some(refund_key)) => format!("Refund {}", refund_key),
+                (None, None) => String::new(),
+            };
+            self.row(
+                &[
+                    item,
+                    line.quantity.to_string(),
+                    format!("{:.2}", line.unit_price),
+                    format!("{:.2}", line.amount),
+                    line.tax_rate
+                        .map(|rate| format!("{}", rate))
+                        .unwrap_or_default(),
+                    format!("{:.2}", line.tax_amount),
+                ],
+                false,
+            );
+        }
+        self.skip();
+
+        let totals = invoice.totals();
+        for (label, amount, bold) in [
+            ("Subtotal", totals.subtotal, false),
+            ("Discounts", -totals.discount_total, false),
+            ("Tax", totals.tax_total, false),
+            ("Total", totals.total, true),
+        ] {
+            self.row(
+                &[
+                    String::new(),
+                    String::new(),
+                    label.to_string(),
+                    format!("{:.2}", amount),
+                    String::new(),
+                    String::new(),
+                ],
+                bold,
+            );
+        }
+    }
+
+    fn text(&mut self, text: &str, size: f32, bold: bool) {
+        self.break_page_if_full();
+        let font = if bold { &self.bold } else { &self.font };
+        self.layer
+            .use_text(text, size, Mm(MARGIN), Mm(self.y), font);
+        self.y -= LINE_HEIGHT * size / 10.0;
+    }
+
+    fn row(&mut self, cells: &[String; 6], bold: bool) {
+        self.break_page_if_full();
+        let font = if bold { &self.bold } else { &self.font };
+        for (cell, x) in cells.iter().zip(COLUMNS) {
+            // Product ids don't fit their column at the default size.
+            let size = if x == COLUMNS[0] { 7.0 } else { 9.0 };
+            self.layer.use_text(cell, size, Mm(x), Mm(self.y), font);
+        }
+        self.y -= LINE_HEIGHT;
+    }
+
+    fn skip(&mut self) {
+        self.y -= LINE_HEIGHT;
+    }
+
+    fn break_page_if_full(&mut self) {
+        if self.y < MARGIN {
+            self.new_page();
+        }
+    }
+
+    fn new_page(&mut self) {
+        let (page, layer) = self
+            .document
+            .add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Invoice");
+        self.layer = self.document.get_page(page).get_layer(layer);
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+}
@@ -0,0 +1,123 @@
+use actix_web::HttpResponse;
+use domain::{
+    entities::invoice::{Invoice, InvoiceLine},
+    services::invoice_service::{InvoiceService, InvoiceServiceError, OrderInvoices},
+};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+#[derive(Serialize)]
+pub struct InvoiceResponse {
+    invoice_number: String,
+    order_id: String,
+    customer_id: String,
+    issued_at: String,
+    billing_address: Option<String>,
+    invoice_lines: Vec<InvoiceLineResponse>,
+    subtotal: f64,
+    discount_total: f64,
+    tax_total: f64,
+    total: f64,
+    credit_notes: Vec<CreditNoteResponse>,
+}
+
+#[derive(Serialize)]
+struct InvoiceLineResponse {
+    product_id: Option<String>,
+    quantity: i32,
+    unit_price: f64,
+    amount: f64,
+    tax_rate: Option<f64>,
+    tax_amount: f64,
+}
+
+#[derive(Serialize)]
+struct CreditNoteResponse {
+    credit_note_number: String,
+    credited_invoice_number: String,
+    refund_key: String,
+    issued_at: String,
+    tax_total: f64,
+    total: f64,
+}
+
+impl From<&InvoiceLine> for InvoiceLineResponse {
+    fn from(line: &InvoiceLine) -> Self {
+        Self {
+            product_id: line
+                .product_id
+                .as_ref()
+                .map(|product_id| product_id.0.to_string()),
+            quantity: line.quantity,
+            unit_price: line.unit_price,
+            amount: line.amount,
+            tax_rate: line.tax_rate,
+            tax_amount: line.tax_amount,
+        }
+    }
+}
+
+impl From<&Invoice> for CreditNoteResponse {
+    fn from(credit_note: &Invoice) -> Self {
+        Self {
+            credit_note_number: number(credit_note),
+            credited_invoice_number: credit_note
+                .credited_invoice()
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+            refund_key: credit_note.refund_key().unwrap_or_default().to_string(),
+            issued_at: credit_note.issued_at().to_rfc3339(),
+            tax_total: credit_note.totals().tax_total,
+            total: credit_note.totals().total,
+        }
+    }
+}
+
+impl From<&OrderInvoices> for InvoiceResponse {
+    fn from(invoices: &OrderInvoices) -> Self {
+        let invoice = &invoices.invoice;
+        let totals = invoice.totals();
+        Self {
+            invoice_number: number(invoice),
+            order_id: invoice.order_id().0.to_string(),
+            customer_id: invoice.customer_id().0.to_string(),
+            issued_at: invoice.issued_at().to_rfc3339(),
+            billing_address: invoice.billing_address().map(str::to_string),
+            invoice_lines: invoice
+                .lines()
+                .iter()
+                .map(InvoiceLineResponse::from)
+                .collect(),
+            subtotal: totals.subtotal,
+            discount_total: totals.discount_total,
+            tax_total: totals.tax_total,
+            total: totals.total,
+            credit_notes: invoices
+                .credit_notes
+                .iter()
+                .map(CreditNoteResponse::from)
+                .collect(),
+        }
+    }
+}
+
+/// Stored invoices always have a number.
+pub fn number(invoice: &Invoice) -> String {
+    invoice
+        .number()
+        .map(ToString::to_string)
+        .unwrap_or_default()
+}
+
+pub fn invoice_service(pool: &Pool<Postgres>) -> InvoiceService {
+    InvoiceService::new(Box::new(
+        adapters::sqlx::pg_invoice_repository::PgInvoiceRepository::new(pool.clone()),
+    ))
+}
+
+pub fn error_response(error: InvoiceServiceError) -> HttpResponse {
+    match error {
+        InvoiceServiceError::InvoiceNotFoundError => HttpResponse::NotFound().finish(),
+        error => HttpResponse::BadRequest().body(error.to_string()),
+    }
+}
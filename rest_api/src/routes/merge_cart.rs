@@ -0,0 +1,33 @@
+use actix_web::{http::header::ETag, post, web, HttpResponse, Responder};
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    cart_response::{cart_service, error_response, CartResponse},
+    etag::etag,
+};
+
+/// Called on login: answers the cart the customer should keep using.
+#[post("/carts/{cart_id}/merge")]
+async fn merge_cart(
+    path: web::Path<String>,
+    data: web::Form<MergeData>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let mut cart_service = cart_service(pool.get_ref());
+
+    match cart_service
+        .merge_into_customer_cart(&path.into_inner(), &data.customer_id)
+        .await
+    {
+        Ok(cart) => HttpResponse::Ok()
+            .insert_header(ETag(etag(cart.version())))
+            .json(CartResponse::from(&cart)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct MergeData {
+    customer_id: String,
+}
@@ -1,7 +1,127 @@
+pub mod add_cart_item;
+pub mod add_customer_address;
+pub mod add_product_to_order;
+pub mod add_product_variant;
+pub mod add_return_item;
+pub mod add_shipment_item;
+pub mod adjust_stock;
+pub mod apply_coupon;
+pub mod approve_return;
+pub mod approve_review;
+mod cart_response;
+mod catalog_response;
+pub mod change_cart_item_quantity;
+pub mod change_customer_address;
+pub mod change_order_product_quantity;
+pub mod checkout_cart;
+pub mod create_cart;
+pub mod create_category;
 pub mod create_customer;
 pub mod create_order;
+pub mod create_product;
+pub mod create_promotion;
+pub mod create_shipment;
+pub mod create_shipping_method;
+mod currency_response;
+mod customer_response;
+pub mod deactivate_customer;
+pub mod deliver_shipment;
+pub mod erase_customer;
+mod etag;
+pub mod export_customer_data;
+pub mod get_cart;
+pub mod get_category_products;
+pub mod get_invoice;
+pub mod get_order;
+pub mod get_product;
+pub mod get_product_prices;
+pub mod get_product_reviews;
+pub mod get_promotion;
+pub mod get_return;
+pub mod get_shipment;
+pub mod get_shipping_methods;
+pub mod get_stock;
 pub mod health_check;
+pub mod import_exchange_rates;
+mod inventory_response;
+mod invoice_pdf;
+mod invoice_response;
+pub mod merge_cart;
+mod order_response;
+mod promotion_response;
+pub mod receive_return;
+pub mod refund_return;
+pub mod reject_return;
+pub mod reject_review;
+pub mod remove_cart_item;
+pub mod remove_customer_address;
+pub mod remove_order_product;
+pub mod request_return;
+mod return_response;
+mod review_response;
+pub mod search_products;
+pub mod set_default_customer_addresses;
+pub mod set_product_attribute;
+pub mod set_product_price;
+pub mod ship_shipment;
+mod shipment_response;
+pub mod submit_review;
+pub mod update_customer;
+mod validation_error_response;
 
+pub use add_cart_item::*;
+pub use add_customer_address::*;
+pub use add_product_to_order::*;
+pub use add_product_variant::*;
+pub use add_return_item::*;
+pub use add_shipment_item::*;
+pub use adjust_stock::*;
+pub use apply_coupon::*;
+pub use approve_return::*;
+pub use approve_review::*;
+pub use change_cart_item_quantity::*;
+pub use change_customer_address::*;
+pub use change_order_product_quantity::*;
+pub use checkout_cart::*;
+pub use create_cart::*;
+pub use create_category::*;
 pub use create_customer::*;
 pub use create_order::*;
+pub use create_product::*;
+pub use create_promotion::*;
+pub use create_shipment::*;
+pub use create_shipping_method::*;
+pub use deactivate_customer::*;
+pub use deliver_shipment::*;
+pub use erase_customer::*;
+pub use export_customer_data::*;
+pub use get_cart::*;
+pub use get_category_products::*;
+pub use get_invoice::*;
+pub use get_order::*;
+pub use get_product::*;
+pub use get_product_prices::*;
+pub use get_product_reviews::*;
+pub use get_promotion::*;
+pub use get_return::*;
+pub use get_shipment::*;
+pub use get_shipping_methods::*;
+pub use get_stock::*;
 pub use health_check::*;
+pub use import_exchange_rates::*;
+pub use merge_cart::*;
+pub use receive_return::*;
+pub use refund_return::*;
+pub use reject_return::*;
+pub use reject_review::*;
+pub use remove_cart_item::*;
+pub use remove_customer_address::*;
+pub use remove_order_product::*;
+pub use request_return::*;
+pub use search_products::*;
+pub use set_default_customer_addresses::*;
+pub use set_product_attribute::*;
+pub use set_product_price::*;
+pub use ship_shipment::*;
+pub use submit_review::*;
+pub use update_customer::*;
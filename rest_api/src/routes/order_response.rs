@@ -0,0 +1,171 @@
+use actix_web::HttpResponse;
+use domain::{
+    entities::order::Order,
+    services::order_service::{OrderService, OrderServiceError},
+    value_objects::Address,
+};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+#[derive(Serialize)]
+pub struct OrderResponse {
+    order_id: String,
+    customer_id: String,
+    order_items: Vec<OrderItemResponse>,
+    shipping_address: Option<AddressResponse>,
+    billing_address: Option<AddressResponse>,
+    status: String,
+    subtotal: f64,
+    discounts: Vec<DiscountResponse>,
+    discount_total: f64,
+    total_price: f64,
+    free_shipping: bool,
+    shipping_method_id: Option<String>,
+    shipping_cost: f64,
+    pricing_mode: String,
+    tax_lines: Vec<TaxLineResponse>,
+    tax_total: f64,
+    currency: String,
+    exchange_rate: f64,
+    base_total_price: f64,
+    version: i64,
+}
+
+#[derive(Serialize)]
+struct DiscountResponse {
+    code: String,
+    amount: f64,
+}
+
+#[derive(Serialize)]
+struct TaxLineResponse {
+    product_id: String,
+    category: String,
+    rate: f64,
+    net_amount: f64,
+    amount: f64,
+}
+
+#[derive(Serialize)]
+struct AddressResponse {
+    street: String,
+    city: String,
+    zip_code: String,
+    country: String,
+}
+
+impl From<&Address> for AddressResponse {
+    fn from(address: &Address) -> Self {
+        Self {
+            street: address.street.clone(),
+            city: address.city.clone(),
+            zip_code: address.zip_code.to_string(),
+            country: address.country.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct OrderItemResponse {
+    product_id: String,
+    price: f64,
+    quantity: i32,
+}
+
+impl From<&Order> for OrderResponse {
+    fn from(order: &Order) -> Self {
+        let pricing = order.pricing();
+        Self {
+            order_id: order.id().0.to_string(),
+            customer_id: order.customer_id().0.to_string(),
+            order_items: order
+                .order_items()
+                .iter()
+                .map(|item| OrderItemResponse {
+                    product_id: item.product_id.0.to_string(),
+                    price: item.price,
+                    quantity: item.quantity,
+                })
+                .collect(),
+            shipping_address: order
+                .addresses()
+                .map(|addresses| AddressResponse::from(&addresses.shipping)),
+            billing_address: order
+                .addresses()
+                .map(|addresses| AddressResponse::from(&addresses.billing)),
+            status: order.status().to_string(),
+            subtotal: pricing.subtotal,
+            discounts: pricing
+                .discounts
+                .iter()
+                .map(|discount| DiscountResponse {
+                    code: discount.code.to_string(),
+                    amount: discount.amount,
+                })
+                .collect(),
+            discount_total: pricing.discount_total(),
+            total_price: pricing.total,
+            free_shipping: pricing.free_shipping,
+            shipping_method_id: order
+                .shipping()
+                .map(|shipping| shipping.shipping_method_id.0.to_string()),
+            shipping_cost: pricing.shipping,
+            pricing_mode: pricing.pricing_mode.to_string(),
+            tax_lines: pricing
+                .tax_lines
+                .iter()
+                .map(|line| TaxLineResponse {
+                    product_id: line.product_id.0.to_string(),
+                    category: line.category.to_string(),
+                    rate: line.rate,
+                    net_amount: line.net_amount,
+                    amount: line.amount,
+                })
+                .collect(),
+            tax_total: pricing.tax_total,
+            currency: order.currency().currency.to_string(),
+            exchange_rate: order.currency().exchange_rate,
+            base_total_price: order.base_total_price(),
+            version: order.version(),
+        }
+    }
+}
+
+pub fn order_service(pool: &Pool<Postgres>) -> OrderService {
+    OrderService::new(
+        Box::new(adapters::sqlx::pg_customer_repository::PgCustomerRepository::new(pool.clone())),
+        Box::new(adapters::sqlx::pg_order_repository::PgOrderRepository::new(
+            pool.clone(),
+        )),
+        Box::new(
+            adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(
+                pool.clone(),
+            ),
+        ),
+        Box::new(adapters::sqlx::pg_catalog_repository::PgCatalogRepository::new(pool.clone())),
+        Box::new(
+            adapters::sqlx::pg_shipping_method_repository::PgShippingMethodRepository::new(
+                pool.clone(),
+            ),
+        ),
+        Box::new(adapters::sqlx::pg_tax_calculator::PgTaxCalculator::new(
+            pool.clone(),
+        )),
+        domain::services::currency_service::CurrencyService::new(Box::new(
+            adapters::sqlx::pg_currency_repository::PgCurrencyRepository::new(pool.clone()),
+        )),
+    )
+}
+
+pub fn error_response(error: OrderServiceError) -> HttpResponse {
+    match error {
+        OrderServiceError::OrderNotFoundError => HttpResponse::NotFound().finish(),
+        OrderServiceError::ConcurrencyConflictError => {
+            HttpResponse::Conflict().body(error.to_string())
+        }
+        OrderServiceError::InvalidOrderError(_) => {
+            HttpResponse::UnprocessableEntity().body(error.to_string())
+        }
+        error => HttpResponse::BadRequest().body(error.to_string()),
+    }
+}
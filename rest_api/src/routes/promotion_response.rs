@@ -0,0 +1,79 @@
+use actix_web::HttpResponse;
+use domain::{
+    entities::promotion::{Discount, Promotion},
+    services::promotion_service::{PromotionService, PromotionServiceError},
+};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+#[derive(Serialize)]
+pub struct PromotionResponse {
+    promotion_id: String,
+    code: String,
+    kind: String,
+    value: Option<f64>,
+    product_id: Option<String>,
+    buy: Option<i32>,
+    get: Option<i32>,
+    min_spend: Option<f64>,
+    valid_from: Option<String>,
+    valid_until: Option<String>,
+    max_uses_per_customer: Option<i64>,
+}
+
+impl From<&Promotion> for PromotionResponse {
+    fn from(promotion: &Promotion) -> Self {
+        let (value, buy, get) = match promotion.discount() {
+            Discount::Percentage(value) | Discount::Fixed(value) => (Some(*value), None, None),
+            Discount::BuyXGetY { buy, get, .. } => (None, Some(*buy), Some(*get)),
+            Discount::FreeShipping => (None, None, None),
+        };
+        let conditions = promotion.conditions();
+        Self {
+            promotion_id: promotion.id().0.to_string(),
+            code: promotion.code().to_string(),
+            kind: promotion.discount().kind().to_string(),
+            value,
+            product_id: promotion
+                .discount()
+                .product_id()
+                .map(|product_id| product_id.0.to_string()),
+            buy,
+            get,
+            min_spend: conditions.min_spend,
+            valid_from: conditions.valid_from.map(|time| time.to_rfc3339()),
+            valid_until: conditions.valid_until.map(|time| time.to_rfc3339()),
+            max_uses_per_customer: conditions.max_uses_per_customer,
+        }
+    }
+}
+
+pub fn promotion_service(pool: &Pool<Postgres>) -> PromotionService {
+    let promotion_repository =
+        adapters::sqlx::pg_promotion_repository::PgPromotionRepository::new(pool.clone());
+    let order_repository =
+        adapters::sqlx::pg_order_repository::PgOrderRepository::new(pool.clone());
+    let outbox_message_repository =
+        adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(pool.clone());
+
+    PromotionService::new(
+        Box::new(promotion_repository),
+        Box::new(order_repository),
+        Box::new(outbox_message_repository),
+    )
+}
+
+pub fn error_response(error: PromotionServiceError) -> HttpResponse {
+    match error {
+        PromotionServiceError::PromotionNotFoundError
+        | PromotionServiceError::OrderNotFoundError => HttpResponse::NotFound().finish(),
+        PromotionServiceError::PromotionAlreadyExistsError
+        | PromotionServiceError::ConcurrencyConflictError => {
+            HttpResponse::Conflict().body(error.to_string())
+        }
+        PromotionServiceError::InvalidPromotionError(_) => {
+            HttpResponse::UnprocessableEntity().body(error.to_string())
+        }
+        error => HttpResponse::BadRequest().body(error.to_string()),
+    }
+}
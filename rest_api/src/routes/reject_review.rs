@@ -0,0 +1,34 @@
+use actix_web::{
+    http::header::{ETag, IfMatch},
+    post, web, HttpResponse, Responder,
+};
+use sqlx::{Pool, Postgres};
+
+use super::{
+    etag::{etag, expected_version},
+    review_response::{error_response, review_service, ReviewResponse},
+};
+
+#[post("/reviews/{review_id}/reject")]
+async fn reject_review(
+    path: web::Path<String>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let review_service = review_service(pool.get_ref());
+
+    match review_service
+        .reject(&path.into_inner(), expected_version)
+        .await
+    {
+        Ok(review) => HttpResponse::Ok()
+            .insert_header(ETag(etag(review.version())))
+            .json(ReviewResponse::from(&review)),
+        Err(error) => error_response(error),
+    }
+}
@@ -0,0 +1,36 @@
+use actix_web::{
+    delete,
+    http::header::{ETag, IfMatch},
+    web, HttpResponse, Responder,
+};
+use sqlx::{Pool, Postgres};
+
+use super::{
+    cart_response::{cart_service, error_response, CartResponse},
+    etag::{etag, expected_version},
+};
+
+#[delete("/carts/{cart_id}/items/{product_id}")]
+async fn remove_cart_item(
+    path: web::Path<(String, String)>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let mut cart_service = cart_service(pool.get_ref());
+
+    let (cart_id, product_id) = path.into_inner();
+    match cart_service
+        .remove_item(&cart_id, &product_id, expected_version)
+        .await
+    {
+        Ok(cart) => HttpResponse::Ok()
+            .insert_header(ETag(etag(cart.version())))
+            .json(CartResponse::from(&cart)),
+        Err(error) => error_response(error),
+    }
+}
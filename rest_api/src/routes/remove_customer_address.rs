@@ -0,0 +1,46 @@
+use actix_web::{
+    delete,
+    http::header::{ETag, IfMatch},
+    web, HttpResponse, Responder,
+};
+use sqlx::{Pool, Postgres};
+
+use super::{
+    customer_response::{error_response, CustomerResponse},
+    etag::{etag, expected_version},
+};
+
+#[delete("/customers/{customer_id}/addresses/{address_id}")]
+async fn remove_customer_address(
+    path: web::Path<(String, String)>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let customer_repository =
+        adapters::sqlx::pg_customer_repository::PgCustomerRepository::new(pool.get_ref().clone());
+    let outbox_message_repository =
+        adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(
+            pool.get_ref().clone(),
+        );
+
+    let mut customer_service = domain::services::customer_service::CustomerService::new(
+        Box::new(customer_repository),
+        Box::new(outbox_message_repository),
+    );
+
+    let (customer_id, address_id) = path.into_inner();
+    match customer_service
+        .remove_address(&customer_id, &address_id, expected_version)
+        .await
+    {
+        Ok(customer) => HttpResponse::Ok()
+            .insert_header(ETag(etag(customer.version)))
+            .json(CustomerResponse::from(&customer)),
+        Err(error) => error_response(error),
+    }
+}
@@ -0,0 +1,36 @@
+use actix_web::{
+    delete,
+    http::header::{ETag, IfMatch},
+    web, HttpResponse, Responder,
+};
+use sqlx::{Pool, Postgres};
+
+use super::{
+    etag::{etag, expected_version},
+    order_response::{error_response, order_service, OrderResponse},
+};
+
+#[delete("/orders/{order_id}/products/{product_id}")]
+async fn remove_order_product(
+    path: web::Path<(String, String)>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let mut order_service = order_service(pool.get_ref());
+
+    let (order_id, product_id) = path.into_inner();
+    match order_service
+        .remove_product(&order_id, &product_id, expected_version)
+        .await
+    {
+        Ok(order) => HttpResponse::Ok()
+            .insert_header(ETag(etag(order.version())))
+            .json(OrderResponse::from(&order)),
+        Err(error) => error_response(error),
+    }
+}
@@ -0,0 +1,45 @@
+use actix_web::{http::header::ETag, post, web, HttpResponse, Responder};
+use domain::services::return_service::RequestReturnRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use crate::settings::PaymentProviderSettings;
+
+use super::{
+    etag::etag,
+    return_response::{error_response, return_service, ReturnResponse},
+};
+
+#[post("/orders/{order_id}/returns")]
+async fn request_return(
+    path: web::Path<String>,
+    data: web::Form<ReturnData>,
+    pool: web::Data<Pool<Postgres>>,
+    payment_provider: web::Data<PaymentProviderSettings>,
+) -> impl Responder {
+    let mut return_service = return_service(pool.get_ref(), payment_provider.get_ref());
+
+    match return_service
+        .request_return(RequestReturnRequestObject {
+            return_id: data.return_id.clone(),
+            order_id: path.into_inner(),
+            product_id: data.product_id.clone(),
+            quantity: data.quantity,
+            reason: data.reason.clone(),
+        })
+        .await
+    {
+        Ok(order_return) => HttpResponse::Created()
+            .insert_header(ETag(etag(order_return.version())))
+            .json(ReturnResponse::from(&order_return)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct ReturnData {
+    return_id: String,
+    product_id: String,
+    quantity: i32,
+    reason: String,
+}
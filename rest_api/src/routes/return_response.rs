@@ -0,0 +1,116 @@
+use actix_web::HttpResponse;
+use domain::{
+    entities::returns::Return,
+    services::{
+        payment_service::PaymentService,
+        return_service::{ReturnService, ReturnServiceError},
+    },
+};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+use crate::settings::PaymentProviderSettings;
+
+use super::invoice_response::invoice_service;
+
+#[derive(Serialize)]
+pub struct ReturnResponse {
+    return_id: String,
+    order_id: String,
+    return_items: Vec<ReturnItemResponse>,
+    reason: String,
+    state: String,
+    rejection_reason: Option<String>,
+    refund_amount: f64,
+    version: i64,
+}
+
+#[derive(Serialize)]
+struct ReturnItemResponse {
+    product_id: String,
+    price: f64,
+    quantity: i32,
+}
+
+impl From<&Return> for ReturnResponse {
+    fn from(order_return: &Return) -> Self {
+        Self {
+            return_id: order_return.id().0.to_string(),
+            order_id: order_return.order_id().0.to_string(),
+            return_items: order_return
+                .items()
+                .iter()
+                .map(|item| ReturnItemResponse {
+                    product_id: item.product_id.0.to_string(),
+                    price: item.price,
+                    quantity: item.quantity,
+                })
+                .collect(),
+            reason: order_return.reason().to_string(),
+            state: order_return.state().to_string(),
+            rejection_reason: order_return.rejection_reason().map(str::to_string),
+            refund_amount: order_return.refund_amount(),
+            version: order_return.version(),
+        }
+    }
+}
+
+/// Refunds go through `PaymentService`, so that they are recorded on the
+/// payment of the order too, and credited on its invoice.
+pub fn return_service(
+    pool: &Pool<Postgres>,
+    payment_provider: &PaymentProviderSettings,
+) -> ReturnService {
+    let return_repository =
+        adapters::sqlx::pg_return_repository::PgReturnRepository::new(pool.clone());
+    let order_repository =
+        adapters::sqlx::pg_order_repository::PgOrderRepository::new(pool.clone());
+    let shipment_repository =
+        adapters::sqlx::pg_shipment_repository::PgShipmentRepository::new(pool.clone());
+    let payment_repository =
+        adapters::sqlx::pg_payment_repository::PgPaymentRepository::new(pool.clone());
+    let outbox_message_repository =
+        adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(pool.clone());
+    let payment_service = PaymentService::new(
+        Box::new(adapters::sqlx::pg_payment_repository::PgPaymentRepository::new(pool.clone())),
+        Box::new(adapters::sqlx::pg_order_repository::PgOrderRepository::new(
+            pool.clone(),
+        )),
+        Box::new(
+            adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(
+                pool.clone(),
+            ),
+        ),
+        Box::new(
+            adapters::http::http_payment_gateway::HttpPaymentGateway::new(
+                payment_provider.base_url.clone(),
+            ),
+        ),
+        invoice_service(pool),
+    );
+
+    ReturnService::new(
+        Box::new(return_repository),
+        Box::new(order_repository),
+        Box::new(shipment_repository),
+        Box::new(payment_repository),
+        Box::new(outbox_message_repository),
+        Box::new(payment_service),
+    )
+}
+
+pub fn error_response(error: ReturnServiceError) -> HttpResponse {
+    match error {
+        ReturnServiceError::ReturnNotFoundError | ReturnServiceError::OrderNotFoundError => {
+            HttpResponse::NotFound().finish()
+        }
+        ReturnServiceError::ConcurrencyConflictError => {
+            HttpResponse::Conflict().body(error.to_string())
+        }
+        ReturnServiceError::InvalidReturnError(_) | ReturnServiceError::PaymentNotFoundError => {
+            HttpResponse::UnprocessableEntity().body(error.to_string())
+        }
+        ReturnServiceError::PaymentError(_) => HttpResponse::BadGateway().body(error.to_string()),
+        error => HttpResponse::BadRequest().body(error.to_string()),
+    }
+}
@@ -0,0 +1,92 @@
+use actix_web::HttpResponse;
+use domain::{
+    entities::review::{Rating, Review},
+    services::review_service::{ReviewService, ReviewServiceError},
+};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+#[derive(Serialize)]
+pub struct ReviewResponse {
+    review_id: String,
+    product_id: String,
+    customer_id: String,
+    rating: i32,
+    text: String,
+    verified_purchase: bool,
+    status: String,
+    created_at: String,
+    version: i64,
+}
+
+impl From<&Review> for ReviewResponse {
+    fn from(review: &Review) -> Self {
+        Self {
+            review_id: review.id().0.to_string(),
+            product_id: review.product_id().0.to_string(),
+            customer_id: review.customer_id().0.to_string(),
+            rating: review.rating(),
+            text: review.text().to_string(),
+            verified_purchase: review.verified_purchase(),
+            status: review.status().to_string(),
+            created_at: review.created_at().to_rfc3339(),
+            version: review.version(),
+        }
+    }
+}
+
+/// Average of the approved reviews, `null` without any.
+#[derive(Serialize)]
+pub struct RatingResponse {
+    average_rating: Option<f64>,
+    review_count: i64,
+}
+
+impl From<&Rating> for RatingResponse {
+    fn from(rating: &Rating) -> Self {
+        Self {
+            average_rating: rating.average,
+            review_count: rating.count,
+        }
+    }
+}
+
+pub fn review_service(pool: &Pool<Postgres>) -> ReviewService {
+    let review_repository =
+        adapters::sqlx::pg_review_repository::PgReviewRepository::new(pool.clone());
+    let catalog_repository =
+        adapters::sqlx::pg_catalog_repository::PgCatalogRepository::new(pool.clone());
+    let customer_repository =
+        adapters::sqlx::pg_customer_repository::PgCustomerRepository::new(pool.clone());
+    let order_repository =
+        adapters::sqlx::pg_order_repository::PgOrderRepository::new(pool.clone());
+    let outbox_message_repository =
+        adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(pool.clone());
+
+    ReviewService::new(
+        Box::new(review_repository),
+        Box::new(catalog_repository),
+        Box::new(customer_repository),
+        Box::new(order_repository),
+        Box::new(outbox_message_repository),
+    )
+}
+
+pub fn error_response(error: ReviewServiceError) -> HttpResponse {
+    match error {
+        ReviewServiceError::ReviewNotFoundError
+        | ReviewServiceError::ProductNotFoundError
+        | ReviewServiceError::CustomerNotFoundError => {
+            HttpResponse::NotFound().body(error.to_string())
+        }
+        ReviewServiceError::ReviewAlreadyExistsError
+        | ReviewServiceError::ProductAlreadyReviewedError
+        | ReviewServiceError::ConcurrencyConflictError => {
+            HttpResponse::Conflict().body(error.to_string())
+        }
+        ReviewServiceError::InvalidReviewError(_) => {
+            HttpResponse::UnprocessableEntity().body(error.to_string())
+        }
+        error => HttpResponse::BadRequest().body(error.to_string()),
+    }
+}
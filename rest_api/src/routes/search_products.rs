@@ -0,0 +1,107 @@
+use actix_web::{get, web, HttpResponse, Responder};
+use domain::{
+    gateways::product_search::SearchResult,
+    services::search_service::{SearchRequestObject, SearchService},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+
+#[get("/products/search")]
+async fn search_products(
+    query: web::Query<SearchParams>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let search_service = SearchService::new(Box::new(
+        adapters::sqlx::pg_product_search::PgProductSearch::new(pool.get_ref().clone()),
+    ));
+    let query = query.into_inner();
+
+    match search_service
+        .search(SearchRequestObject {
+            q: query.q,
+            category: query.category,
+            min_price: query.min_price,
+            max_price: query.max_price,
+            sort: query.sort,
+        })
+        .await
+    {
+        Ok(result) => HttpResponse::Ok().json(SearchResponse::from(&result)),
+        Err(error) => HttpResponse::BadRequest().body(error.to_string()),
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchParams {
+    q: Option<String>,
+    category: Option<String>,
+    min_price: Option<f64>,
+    max_price: Option<f64>,
+    sort: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SearchResponse {
+    hits: Vec<SearchHitResponse>,
+    category_facets: Vec<CategoryFacetResponse>,
+    price_facets: Vec<PriceFacetResponse>,
+}
+
+#[derive(Serialize)]
+struct SearchHitResponse {
+    product_id: String,
+    name: String,
+    category_id: Option<String>,
+    price: Option<f64>,
+    rank: f64,
+}
+
+#[derive(Serialize)]
+struct CategoryFacetResponse {
+    category_id: String,
+    name: String,
+    count: i64,
+}
+
+#[derive(Serialize)]
+struct PriceFacetResponse {
+    from: f64,
+    to: Option<f64>,
+    count: i64,
+}
+
+impl From<&SearchResult> for SearchResponse {
+    fn from(result: &SearchResult) -> Self {
+        Self {
+            hits: result
+                .hits
+                .iter()
+                .map(|hit| SearchHitResponse {
+                    product_id: hit.product_id.0.to_string(),
+                    name: hit.name.clone(),
+                    category_id: hit.category_id.as_ref().map(|id| id.0.to_string()),
+                    price: hit.price,
+                    rank: hit.rank,
+                })
+                .collect(),
+            category_facets: result
+                .category_facets
+                .iter()
+                .map(|facet| CategoryFacetResponse {
+                    category_id: facet.category_id.0.to_string(),
+                    name: facet.name.clone(),
+                    count: facet.count,
+                })
+                .collect(),
+            price_facets: result
+                .price_facets
+                .iter()
+                .map(|facet| PriceFacetResponse {
+                    from: facet.from,
+                    to: facet.to,
+                    count: facet.count,
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use actix_web::{
+    http::header::{ETag, IfMatch},
+    post, web, HttpResponse, Responder,
+};
+use domain::services::catalog_service::SetAttributeRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    catalog_response::{catalog_service, error_response, ProductResponse},
+    etag::{etag, expected_version},
+};
+
+/// Sets an attribute of the variant given by `variant_id`, or of the product.
+#[post("/products/{product_id}/attributes")]
+async fn set_product_attribute(
+    path: web::Path<String>,
+    data: web::Form<AttributeData>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let catalog_service = catalog_service(pool.get_ref());
+    let data = data.into_inner();
+
+    match catalog_service
+        .set_attribute(SetAttributeRequestObject {
+            product_id: path.into_inner(),
+            variant_id: data.variant_id,
+            name: data.name,
+            kind: data.kind,
+            value: data.value,
+            expected_version,
+        })
+        .await
+    {
+        Ok(product) => HttpResponse::Ok()
+            .insert_header(ETag(etag(product.version())))
+            .json(ProductResponse::from(&product)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct AttributeData {
+    variant_id: Option<String>,
+    name: String,
+    kind: String,
+    value: String,
+}
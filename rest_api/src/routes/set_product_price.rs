@@ -0,0 +1,33 @@
+use actix_web::{put, web, HttpResponse, Responder};
+use domain::services::currency_service::SetPriceRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::currency_response::{currency_service, error_response, ProductPriceResponse};
+
+#[put("/products/{product_id}/prices/{currency}")]
+async fn set_product_price(
+    path: web::Path<(String, String)>,
+    data: web::Form<PriceData>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let currency_service = currency_service(pool.get_ref());
+
+    let (product_id, currency) = path.into_inner();
+    match currency_service
+        .set_price(SetPriceRequestObject {
+            product_id,
+            currency,
+            amount: data.amount,
+        })
+        .await
+    {
+        Ok(price) => HttpResponse::Ok().json(ProductPriceResponse::from(&price)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct PriceData {
+    amount: f64,
+}
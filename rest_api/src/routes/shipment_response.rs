@@ -0,0 +1,119 @@
+use actix_web::HttpResponse;
+use domain::{
+    entities::{
+        shipment::Shipment,
+        shipping_method::{ShippingMethod, ShippingRate},
+    },
+    services::shipment_service::{ShipmentService, ShipmentServiceError},
+};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+#[derive(Serialize)]
+pub struct ShipmentResponse {
+    shipment_id: String,
+    order_id: String,
+    shipping_method_id: String,
+    carrier: String,
+    shipment_items: Vec<ShipmentItemResponse>,
+    weight: f64,
+    tracking_number: Option<String>,
+    state: String,
+    version: i64,
+}
+
+#[derive(Serialize)]
+struct ShipmentItemResponse {
+    product_id: String,
+    quantity: i32,
+}
+
+impl From<&Shipment> for ShipmentResponse {
+    fn from(shipment: &Shipment) -> Self {
+        Self {
+            shipment_id: shipment.id().0.to_string(),
+            order_id: shipment.order_id().0.to_string(),
+            shipping_method_id: shipment.shipping_method_id().0.to_string(),
+            carrier: shipment.carrier().to_string(),
+            shipment_items: shipment
+                .items()
+                .iter()
+                .map(|item| ShipmentItemResponse {
+                    product_id: item.product_id.0.to_string(),
+                    quantity: item.quantity,
+                })
+                .collect(),
+            weight: shipment.weight(),
+            tracking_number: shipment.tracking_number().map(str::to_string),
+            state: shipment.state().to_string(),
+            version: shipment.version(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ShippingMethodResponse {
+    shipping_method_id: String,
+    name: String,
+    carrier: String,
+    kind: String,
+    amount: f64,
+    per_kg: Option<f64>,
+    threshold: Option<f64>,
+}
+
+impl From<&ShippingMethod> for ShippingMethodResponse {
+    fn from(shipping_method: &ShippingMethod) -> Self {
+        let (amount, per_kg, threshold) = match shipping_method.rate() {
+            ShippingRate::Flat(amount) => (*amount, None, None),
+            ShippingRate::WeightBased { base, per_kg } => (*base, Some(*per_kg), None),
+            ShippingRate::FreeOver { amount, threshold } => (*amount, None, Some(*threshold)),
+        };
+        Self {
+            shipping_method_id: shipping_method.id().0.to_string(),
+            name: shipping_method.name().to_string(),
+            carrier: shipping_method.carrier().to_string(),
+            kind: shipping_method.rate().kind().to_string(),
+            amount,
+            per_kg,
+            threshold,
+        }
+    }
+}
+
+pub fn shipment_service(pool: &Pool<Postgres>) -> ShipmentService {
+    let shipment_repository =
+        adapters::sqlx::pg_shipment_repository::PgShipmentRepository::new(pool.clone());
+    let shipping_method_repository =
+        adapters::sqlx::pg_shipping_method_repository::PgShippingMethodRepository::new(
+            pool.clone(),
+        );
+    let order_repository =
+        adapters::sqlx::pg_order_repository::PgOrderRepository::new(pool.clone());
+    let outbox_message_repository =
+        adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(pool.clone());
+
+    ShipmentService::new(
+        Box::new(shipment_repository),
+        Box::new(shipping_method_repository),
+        Box::new(order_repository),
+        Box::new(outbox_message_repository),
+    )
+}
+
+pub fn error_response(error: ShipmentServiceError) -> HttpResponse {
+    match error {
+        ShipmentServiceError::ShipmentNotFoundError
+        | ShipmentServiceError::ShippingMethodNotFoundError
+        | ShipmentServiceError::OrderNotFoundError => HttpResponse::NotFound().finish(),
+        ShipmentServiceError::ShippingMethodAlreadyExistsError
+        | ShipmentServiceError::ConcurrencyConflictError => {
+            HttpResponse::Conflict().body(error.to_string())
+        }
+        ShipmentServiceError::InvalidShippingMethodError(_)
+        | ShipmentServiceError::InvalidShipmentError(_) => {
+            HttpResponse::UnprocessableEntity().body(error.to_string())
+        }
+        error => HttpResponse::BadRequest().body(error.to_string()),
+    }
+}
@@ -0,0 +1,43 @@
+use actix_web::{http::header::ETag, post, web, HttpResponse, Responder};
+use domain::services::review_service::SubmitReviewRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    etag::etag,
+    review_response::{error_response, review_service, ReviewResponse},
+};
+
+/// The review waits for moderation before it is listed.
+#[post("/products/{product_id}/reviews")]
+async fn submit_review(
+    path: web::Path<String>,
+    data: web::Form<ReviewData>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let mut review_service = review_service(pool.get_ref());
+
+    match review_service
+        .submit(SubmitReviewRequestObject {
+            review_id: data.review_id.clone(),
+            product_id: path.into_inner(),
+            customer_id: data.customer_id.clone(),
+            rating: data.rating,
+            text: data.text.clone(),
+        })
+        .await
+    {
+        Ok(review) => HttpResponse::Created()
+            .insert_header(ETag(etag(review.version())))
+            .json(ReviewResponse::from(&review)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct ReviewData {
+    review_id: String,
+    customer_id: String,
+    rating: i32,
+    text: String,
+}
@@ -0,0 +1,63 @@
+use actix_web::{
+    http::header::{ETag, IfMatch},
+    put, web, HttpResponse, Responder,
+};
+use domain::services::customer_service::UpdateCustomerRequestObject;
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+
+use super::{
+    customer_response::{error_response, CustomerResponse},
+    etag::{etag, expected_version},
+};
+
+#[put("/customers/{customer_id}")]
+async fn update_customer(
+    path: web::Path<String>,
+    data: web::Form<ProfileData>,
+    if_match: Option<web::Header<IfMatch>>,
+    pool: web::Data<Pool<Postgres>>,
+) -> impl Responder {
+    let expected_version = match expected_version(if_match) {
+        Ok(version) => version,
+        Err(response) => return response,
+    };
+
+    let customer_repository =
+        adapters::sqlx::pg_customer_repository::PgCustomerRepository::new(pool.get_ref().clone());
+    let outbox_message_repository =
+        adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(
+            pool.get_ref().clone(),
+        );
+
+    let mut customer_service = domain::services::customer_service::CustomerService::new(
+        Box::new(customer_repository),
+        Box::new(outbox_message_repository),
+    );
+
+    let data = data.into_inner();
+    match customer_service
+        .update_customer(UpdateCustomerRequestObject {
+            customer_id: path.into_inner(),
+            first_name: data.first_name,
+            last_name: data.last_name,
+            email: data.email,
+            phone: data.phone,
+            expected_version,
+        })
+        .await
+    {
+        Ok(customer) => HttpResponse::Ok()
+            .insert_header(ETag(etag(customer.version)))
+            .json(CustomerResponse::from(&customer)),
+        Err(error) => error_response(error),
+    }
+}
+
+#[derive(Deserialize)]
+struct ProfileData {
+    first_name: String,
+    last_name: String,
+    email: String,
+    phone: Option<String>,
+}
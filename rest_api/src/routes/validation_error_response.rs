@@ -0,0 +1,27 @@
+use domain::services::customer_service::FieldError;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct ValidationErrorResponse {
+    errors: Vec<FieldErrorResponse>,
+}
+
+#[derive(Serialize)]
+struct FieldErrorResponse {
+    field: String,
+    message: String,
+}
+
+impl From<&[FieldError]> for ValidationErrorResponse {
+    fn from(errors: &[FieldError]) -> Self {
+        Self {
+            errors: errors
+                .iter()
+                .map(|error| FieldErrorResponse {
+                    field: error.field.clone(),
+                    message: error.error.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
@@ -4,6 +4,7 @@ use serde::Deserialize;
 pub struct Settings {
     pub database: DatabaseSettings,
     pub application_port: u16,
+    pub payment_provider: PaymentProviderSettings,
 }
 
 #[derive(Deserialize)]
@@ -15,6 +16,12 @@ pub struct DatabaseSettings {
     pub database_name: String,
 }
 
+/// Provider the payments are authorized, captured and refunded with.
+#[derive(Clone, Deserialize)]
+pub struct PaymentProviderSettings {
+    pub base_url: String,
+}
+
 impl DatabaseSettings {
     pub fn connection_string(&self) -> String {
         format!(
@@ -3,16 +3,91 @@ use std::net::TcpListener;
 use actix_web::{dev::Server, web, App, HttpServer};
 use sqlx::{Pool, Postgres};
 
-use crate::routes::{create_customer, create_order, health_check};
+use crate::settings::PaymentProviderSettings;
 
-pub fn run(listener: TcpListener, pool: Pool<Postgres>) -> Result<Server, std::io::Error> {
+use crate::routes::{
+    add_cart_item, add_customer_address, add_product_to_order, add_product_variant,
+    add_return_item, add_shipment_item, adjust_stock, apply_coupon, approve_return, approve_review,
+    change_cart_item_quantity, change_customer_address, change_order_product_quantity,
+    checkout_cart, create_cart, create_category, create_customer, create_order, create_product,
+    create_promotion, create_shipment, create_shipping_method, deactivate_customer,
+    deliver_shipment, erase_customer, export_customer_data, get_cart, get_category_products,
+    get_invoice, get_order, get_product, get_product_prices, get_product_reviews, get_promotion,
+    get_return, get_shipment, get_shipping_methods, get_stock, health_check, import_exchange_rates,
+    merge_cart, receive_return, refund_return, reject_return, reject_review, remove_cart_item,
+    remove_customer_address, remove_order_product, request_return, search_products,
+    set_default_customer_addresses, set_product_attribute, set_product_price, ship_shipment,
+    submit_review, update_customer,
+};
+
+pub fn run(
+    listener: TcpListener,
+    pool: Pool<Postgres>,
+    payment_provider: PaymentProviderSettings,
+) -> Result<Server, std::io::Error> {
     let connection = web::Data::new(pool);
+    let payment_provider = web::Data::new(payment_provider);
     let server = HttpServer::new(move || {
         App::new()
             .service(health_check)
             .service(create_order)
+            .service(get_order)
+            .service(add_product_to_order)
+            .service(change_order_product_quantity)
+            .service(remove_order_product)
             .service(create_customer)
+            .service(update_customer)
+            .service(change_customer_address)
+            .service(deactivate_customer)
+            .service(add_customer_address)
+            .service(remove_customer_address)
+            .service(set_default_customer_addresses)
+            .service(export_customer_data)
+            .service(erase_customer)
+            .service(create_cart)
+            .service(get_cart)
+            .service(add_cart_item)
+            .service(change_cart_item_quantity)
+            .service(remove_cart_item)
+            .service(merge_cart)
+            .service(checkout_cart)
+            .service(get_stock)
+            .service(adjust_stock)
+            .service(request_return)
+            .service(get_return)
+            .service(add_return_item)
+            .service(approve_return)
+            .service(reject_return)
+            .service(receive_return)
+            .service(refund_return)
+            .service(create_promotion)
+            .service(get_promotion)
+            .service(apply_coupon)
+            .service(create_shipping_method)
+            .service(get_shipping_methods)
+            .service(create_shipment)
+            .service(get_shipment)
+            .service(add_shipment_item)
+            .service(ship_shipment)
+            .service(deliver_shipment)
+            .service(get_invoice)
+            .service(import_exchange_rates)
+            .service(set_product_price)
+            .service(get_product_prices)
+            .service(create_category)
+            .service(get_category_products)
+            .service(create_product)
+            // Before get_product, which would take `search` for a product id.
+            .service(search_products)
+            .service(get_product)
+            .service(add_product_variant)
+            .service(set_product_attribute)
+            .service(submit_review)
+            .service(get_product_reviews)
+            .service(approve_review)
+            .service(reject_review)
             .app_data(connection.clone())
+            .app_data(payment_provider.clone())
     })
     .listen(listener)?
     .run();
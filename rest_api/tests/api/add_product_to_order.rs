@@ -0,0 +1,195 @@
+use reqwest::{header::ETAG, Client, Response, StatusCode};
+use uuid::Uuid;
+
+use crate::helpers::{create_customer, create_order, insert_product_variant_on_db, TestContext};
+
+#[actix_web::test]
+async fn add_a_product_to_an_order() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = create_customer(&test_context).await;
+    let order_id = create_order(&test_context, &client, customer_id).await;
+
+    let response = add_product(&test_context, &client, order_id, Some("\"1\"")).await;
+
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!("\"2\"", response.headers()[ETAG]);
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn return_conflict_if_the_order_changed_since_it_was_read() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = create_customer(&test_context).await;
+    let order_id = create_order(&test_context, &client, customer_id).await;
+    add_product(&test_context, &client, order_id, None).await;
+
+    let response = add_product(&test_context, &client, order_id, Some("\"1\"")).await;
+
+    assert_eq!(StatusCode::CONFLICT, response.status());
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn only_one_of_two_concurrent_updates_succeeds() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = create_customer(&test_context).await;
+    let order_id = create_order(&test_context, &client, customer_id).await;
+
+    let (first, second) = tokio::join!(
+        add_product(&test_context, &client, order_id, Some("\"1\"")),
+        add_product(&test_context, &client, order_id, Some("\"1\""))
+    );
+
+    let mut statuses = vec![first.status(), second.status()];
+    statuses.sort();
+    assert_eq!(vec![StatusCode::OK, StatusCode::CONFLICT], statuses);
+    let order = client
+        .get(format!("{}/orders/{}", test_context.address, order_id))
+        .send()
+        .await
+        .expect("Failed to get the order");
+    assert_eq!("\"2\"", order.headers()[ETAG]);
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn change_the_quantity_of_a_product_and_remove_it_from_an_order() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = create_customer(&test_context).await;
+    let order_id = create_order(&test_context, &client, customer_id).await;
+    let product_id = Uuid::new_v4();
+    add_product_with_id(&test_context, &client, order_id, product_id, None).await;
+    let product_url = format!(
+        "{}/orders/{}/products/{}",
+        test_context.address, order_id, product_id
+    );
+
+    let changed = client
+        .put(&product_url)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("If-Match", "\"2\"")
+        .body("quantity=3")
+        .send()
+        .await
+        .expect("Failed to change the quantity of a product");
+    assert_eq!(StatusCode::OK, changed.status());
+    assert!(changed
+        .text()
+        .await
+        .unwrap()
+        .contains(r#""price":9.99,"quantity":3"#));
+
+    let removed = client
+        .delete(&product_url)
+        .header("If-Match", "\"3\"")
+        .send()
+        .await
+        .expect("Failed to remove a product");
+    assert_eq!(StatusCode::OK, removed.status());
+    assert_eq!("\"4\"", removed.headers()[ETAG]);
+    assert!(removed
+        .text()
+        .await
+        .unwrap()
+        .contains(r#""order_items":[]"#));
+
+    let missing = client
+        .delete(&product_url)
+        .send()
+        .await
+        .expect("Failed to remove a product");
+    assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, missing.status());
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn prices_a_product_at_its_catalog_price_whatever_the_client_sends() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = create_customer(&test_context).await;
+    let order_id = create_order(&test_context, &client, customer_id).await;
+    let product_id = Uuid::new_v4();
+    insert_product_variant_on_db(product_id, 9.99, &test_context.connection_pool)
+        .await
+        .expect("Failed to prepare DB content for test");
+
+    let response = client
+        .post(format!(
+            "{}/orders/{}/products",
+            test_context.address, order_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!("product_id={}&price=0.0&quantity=1", product_id))
+        .send()
+        .await
+        .expect("Failed to add a product");
+
+    assert_eq!(StatusCode::OK, response.status());
+    let body = response.text().await.unwrap();
+    assert!(body.contains(r#""price":9.99,"quantity":1"#));
+    assert!(body.contains(r#""total_price":9.99"#));
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn cannot_add_a_product_missing_from_the_catalog() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = create_customer(&test_context).await;
+    let order_id = create_order(&test_context, &client, customer_id).await;
+
+    let response = client
+        .post(format!(
+            "{}/orders/{}/products",
+            test_context.address, order_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!("product_id={}&quantity=1", Uuid::new_v4()))
+        .send()
+        .await
+        .expect("Failed to add a product");
+
+    assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+    test_context.cleanup().await;
+}
+async fn add_product(
+    test_context: &TestContext,
+    client: &Client,
+    order_id: Uuid,
+    if_match: Option<&str>,
+) -> Response {
+    add_product_with_id(test_context, client, order_id, Uuid::new_v4(), if_match).await
+}
+
+async fn add_product_with_id(
+    test_context: &TestContext,
+    client: &Client,
+    order_id: Uuid,
+    product_id: Uuid,
+    if_match: Option<&str>,
+) -> Response {
+    insert_product_variant_on_db(product_id, 9.99, &test_context.connection_pool)
+        .await
+        .expect("Failed to prepare DB content for test");
+    let mut request = client
+        .post(format!(
+            "{}/orders/{}/products",
+            test_context.address, order_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!("product_id={}&quantity=1", product_id));
+    if let Some(if_match) = if_match {
+        request = request.header("If-Match", if_match);
+    }
+    request.send().await.expect("Failed to add a product")
+}
@@ -0,0 +1,183 @@
+use reqwest::{header::ETAG, Client, StatusCode};
+use uuid::Uuid;
+
+use crate::helpers::{create_cart, insert_customer_on_db, TestContext};
+
+#[actix_web::test]
+async fn check_out_an_anonymous_cart_after_login() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = Uuid::new_v4();
+    insert_customer_on_db(customer_id, &test_context.connection_pool.clone())
+        .await
+        .expect("Failed to prepare DB content for test");
+    let cart_id = create_cart(&test_context, &client, None).await;
+    let product_id = Uuid::new_v4();
+
+    let response = client
+        .post(format!("{}/carts/{}/items", test_context.address, cart_id))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("If-Match", "\"1\"")
+        .body(format!("product_id={}&price=9.99&quantity=2", product_id))
+        .send()
+        .await
+        .expect("Failed to add the item");
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!("\"2\"", response.headers()[ETAG]);
+
+    let response = client
+        .post(format!("{}/carts/{}/merge", test_context.address, cart_id))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!("customer_id={}", customer_id))
+        .send()
+        .await
+        .expect("Failed to merge the cart");
+    assert_eq!(StatusCode::OK, response.status());
+    let body = response.text().await.unwrap();
+    assert!(body.contains(&format!(r#""customer_id":"{}""#, customer_id)));
+    assert!(body.contains(r#""total_price":19.98"#));
+
+    let order_id = Uuid::new_v4();
+    let response = client
+        .post(format!(
+            "{}/carts/{}/checkout",
+            test_context.address, cart_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!("order_id={}", order_id))
+        .send()
+        .await
+        .expect("Failed to check out the cart");
+    assert_eq!(StatusCode::CREATED, response.status());
+    let body = response.text().await.unwrap();
+    assert!(body.contains(&format!(r#""order_id":"{}""#, order_id)));
+    assert!(body.contains(r#""status":"pending""#));
+    assert!(body.contains(r#""quantity":2"#));
+
+    let response = client
+        .delete(format!(
+            "{}/orders/{}/products/{}",
+            test_context.address, order_id, product_id
+        ))
+        .send()
+        .await
+        .expect("Failed to remove the product");
+    assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+    let response = client
+        .get(format!("{}/carts/{}", test_context.address, cart_id))
+        .send()
+        .await
+        .expect("Failed to get the cart");
+    assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn return_unprocessable_entity_when_checking_out_an_anonymous_cart() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let cart_id = create_cart(&test_context, &client, None).await;
+
+    let response = client
+        .post(format!(
+            "{}/carts/{}/checkout",
+            test_context.address, cart_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!("order_id={}", Uuid::new_v4()))
+        .send()
+        .await
+        .expect("Failed to check out the cart");
+
+    assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn return_conflict_when_changing_a_stale_cart() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let cart_id = create_cart(&test_context, &client, None).await;
+    let product_id = Uuid::new_v4();
+    client
+        .post(format!("{}/carts/{}/items", test_context.address, cart_id))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!("product_id={}&price=9.99&quantity=1", product_id))
+        .send()
+        .await
+        .expect("Failed to add the item");
+
+    let response = client
+        .put(format!(
+            "{}/carts/{}/items/{}",
+            test_context.address, cart_id, product_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("If-Match", "\"1\"")
+        .body("quantity=3")
+        .send()
+        .await
+        .expect("Failed to change the quantity");
+
+    assert_eq!(StatusCode::CONFLICT, response.status());
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn only_one_of_two_concurrent_checkouts_places_an_order() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = Uuid::new_v4();
+    insert_customer_on_db(customer_id, &test_context.connection_pool.clone())
+        .await
+        .expect("Failed to prepare DB content for test");
+    let cart_id = create_cart(&test_context, &client, None).await;
+    client
+        .post(format!("{}/carts/{}/items", test_context.address, cart_id))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!(
+            "product_id={}&price=9.99&quantity=1",
+            Uuid::new_v4()
+        ))
+        .send()
+        .await
+        .expect("Failed to add the item");
+    let response = client
+        .post(format!("{}/carts/{}/merge", test_context.address, cart_id))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!("customer_id={}", customer_id))
+        .send()
+        .await
+        .expect("Failed to merge the cart");
+    assert_eq!(StatusCode::OK, response.status());
+
+    let checkout = || {
+        client
+            .post(format!(
+                "{}/carts/{}/checkout",
+                test_context.address, cart_id
+            ))
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(format!("order_id={}", Uuid::new_v4()))
+            .send()
+    };
+    let (first, second) = tokio::join!(checkout(), checkout());
+
+    let placed = [first.unwrap().status(), second.unwrap().status()]
+        .iter()
+        .filter(|status| **status == StatusCode::CREATED)
+        .count();
+    assert_eq!(1, placed);
+    let orders: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM orders WHERE customer_id = $1")
+        .bind(customer_id)
+        .fetch_one(&test_context.connection_pool)
+        .await
+        .unwrap();
+    assert_eq!(1, orders);
+
+    test_context.cleanup().await;
+}
@@ -0,0 +1,147 @@
+use reqwest::{header::ETAG, Client, Response, StatusCode};
+use uuid::Uuid;
+
+use crate::helpers::{create_customer, create_order, post, TestContext};
+
+#[actix_web::test]
+async fn browses_the_products_of_a_category_tree() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let clothing_id = Uuid::new_v4();
+    let shirts_id = Uuid::new_v4();
+    let product_id = Uuid::new_v4();
+    let variant_id = Uuid::new_v4();
+
+    let response = create_category(&test_context, &client, clothing_id, None).await;
+    assert_eq!(StatusCode::CREATED, response.status());
+    create_category(&test_context, &client, shirts_id, Some(clothing_id)).await;
+    assert_eq!(
+        StatusCode::NOT_FOUND,
+        create_category(&test_context, &client, Uuid::new_v4(), Some(Uuid::new_v4()))
+            .await
+            .status()
+    );
+    let response = post(
+        &test_context,
+        &client,
+        "/products".to_string(),
+        format!(
+            "product_id={}&name=T-shirt&category_id={}",
+            product_id, shirts_id
+        ),
+        None,
+    )
+    .await;
+    assert_eq!(StatusCode::CREATED, response.status());
+    assert_eq!("\"1\"", response.headers()[ETAG]);
+    let response = post(
+        &test_context,
+        &client,
+        format!("/products/{}/variants", product_id),
+        format!("variant_id={}&sku=ts-red-m&price=19.9", variant_id),
+        Some("\"1\""),
+    )
+    .await;
+    assert_eq!(StatusCode::OK, response.status());
+    post(
+        &test_context,
+        &client,
+        format!("/products/{}/attributes", product_id),
+        format!("variant_id={}&name=size&kind=text&value=M", variant_id),
+        Some("\"2\""),
+    )
+    .await;
+    let response = post(
+        &test_context,
+        &client,
+        format!("/products/{}/attributes", product_id),
+        "name=organic&kind=boolean&value=true".to_string(),
+        Some("\"2\""),
+    )
+    .await;
+    assert_eq!(StatusCode::CONFLICT, response.status());
+
+    let body = client
+        .get(format!(
+            "{}/categories/{}/products",
+            test_context.address, clothing_id
+        ))
+        .send()
+        .await
+        .expect("Failed to get the products of the category")
+        .text()
+        .await
+        .unwrap();
+
+    assert!(body.contains(&format!(
+        r#""variants":[{{"variant_id":"{}","sku":"TS-RED-M","price":19.9,"attributes":[{{"name":"size","kind":"text","value":"M"}}]}}]"#,
+        variant_id
+    )));
+    assert!(body.contains(r#""version":3"#));
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn orders_a_variant_at_its_price() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let product_id = Uuid::new_v4();
+    let variant_id = Uuid::new_v4();
+    post(
+        &test_context,
+        &client,
+        "/products".to_string(),
+        format!("product_id={}&name=T-shirt", product_id),
+        None,
+    )
+    .await;
+    post(
+        &test_context,
+        &client,
+        format!("/products/{}/variants", product_id),
+        format!("variant_id={}&sku=TS-BLUE-L&price=21.5", variant_id),
+        None,
+    )
+    .await;
+    let customer_id = create_customer(&test_context).await;
+    let order_id = create_order(&test_context, &client, customer_id).await;
+
+    let body = post(
+        &test_context,
+        &client,
+        format!("/orders/{}/products", order_id),
+        format!("product_id={}&quantity=2", variant_id),
+        None,
+    )
+    .await
+    .text()
+    .await
+    .unwrap();
+
+    assert!(body.contains(&format!(
+        r#""product_id":"{}","price":21.5,"quantity":2"#,
+        variant_id
+    )));
+
+    test_context.cleanup().await;
+}
+
+async fn create_category(
+    test_context: &TestContext,
+    client: &Client,
+    category_id: Uuid,
+    parent_id: Option<Uuid>,
+) -> Response {
+    let parent = parent_id
+        .map(|parent_id| format!("&parent_id={}", parent_id))
+        .unwrap_or_default();
+    post(
+        test_context,
+        client,
+        "/categories".to_string(),
+        format!("category_id={}&name=Clothing{}", category_id, parent),
+        None,
+    )
+    .await
+}
@@ -0,0 +1,29 @@
+use reqwest::Client;
+use uuid::Uuid;
+
+use crate::helpers::{
+    create_confirmed_order, create_customer, insert_product_variant_on_db, TestContext,
+};
+
+#[actix_web::test]
+async fn checkout_captures_the_payment_of_the_confirmed_order() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = create_customer(&test_context).await;
+    let product_id = Uuid::new_v4();
+    insert_product_variant_on_db(product_id, 9.99, &test_context.connection_pool)
+        .await
+        .expect("Failed to prepare DB content for test");
+
+    let order_id =
+        create_confirmed_order(&test_context, &client, customer_id, product_id, 2, "").await;
+
+    let (state, amount, captured_amount): (String, f64, f64) =
+        sqlx::query_as("SELECT state, amount, captured_amount FROM payments WHERE order_id = $1")
+            .bind(order_id)
+            .fetch_one(&test_context.connection_pool)
+            .await
+            .expect("Failed to read the payment");
+    assert_eq!("captured", state);
+    assert_eq!(amount, captured_amount);
+}
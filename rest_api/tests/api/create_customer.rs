@@ -7,14 +7,15 @@ async fn create_a_customer() {
 
     let first_name = "John";
     let last_name = "Doe";
+    let email = "john.doe@example.com";
     let street = "123 Elm St";
     let city = "Springfield";
     let zip_code = "12345";
-    let state = "IL";
+    let country = "US";
 
     let body = format!(
-        "first_name={}&last_name={}&street={}&city={}&zip_code={}&state={}",
-        first_name, last_name, street, city, zip_code, state
+        "first_name={}&last_name={}&email={}&street={}&city={}&zip_code={}&country={}",
+        first_name, last_name, email, street, city, zip_code, country
     );
 
     let response = client
@@ -29,3 +30,58 @@ async fn create_a_customer() {
 
     test_context.cleanup().await;
 }
+
+#[actix_web::test]
+async fn cannot_create_two_customers_with_the_same_email() {
+    let test_context = TestContext::new().await;
+    let client = reqwest::Client::new();
+    let body = |email: &str| {
+        format!(
+            "first_name=John&last_name=Doe&email={}&phone=%2B1 217 555 0100&street=123 Elm St&city=Springfield&zip_code=62701&country=US",
+            email
+        )
+    };
+
+    let first = client
+        .post(format!("{}/customers", test_context.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(body("john.doe@example.com"))
+        .send()
+        .await
+        .expect("Failed to create a customer");
+    let second = client
+        .post(format!("{}/customers", test_context.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(body("John.Doe@Example.com"))
+        .send()
+        .await
+        .expect("Failed to create a customer");
+
+    assert!(first.status().is_success());
+    assert_eq!(409, second.status().as_u16());
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn returns_the_invalid_fields_of_a_customer() {
+    let test_context = TestContext::new().await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{}/customers", test_context.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(
+            "first_name=John&last_name=&email=john.doe@example.com&street=123 Elm St&city=Springfield&zip_code=ABC&country=US",
+        )
+        .send()
+        .await
+        .expect("Failed to create a customer");
+
+    assert_eq!(422, response.status().as_u16());
+    let body = response.text().await.unwrap();
+    assert!(body.contains(r#"{"field":"last_name","message":"must not be empty"}"#));
+    assert!(body.contains(r#""field":"zip_code""#));
+
+    test_context.cleanup().await;
+}
@@ -46,3 +46,31 @@ async fn return_error_if_customer_does_not_exist() {
 
     test_context.cleanup().await;
 }
+
+#[actix_web::test]
+async fn return_error_if_shipping_method_does_not_exist() {
+    let test_context = TestContext::new().await;
+    let client = reqwest::Client::new();
+    let customer_id = Uuid::new_v4();
+    insert_customer_on_db(customer_id, &test_context.connection_pool.clone())
+        .await
+        .expect("Failed to prepare DB content for test");
+    let body = format!(
+        "order_id={}&customer_id={}&shipping_method_id={}",
+        Uuid::new_v4(),
+        customer_id,
+        Uuid::new_v4()
+    );
+
+    let response = client
+        .post(format!("{}/orders", test_context.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(body)
+        .send()
+        .await
+        .expect("Failed to create an order");
+
+    assert_eq!(reqwest::StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+    test_context.cleanup().await;
+}
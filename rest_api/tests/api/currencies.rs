@@ -0,0 +1,145 @@
+use reqwest::Client;
+use uuid::Uuid;
+
+use crate::helpers::{insert_customer_on_db, insert_product_variant_on_db, TestContext};
+
+#[actix_web::test]
+async fn imports_exchange_rates_only_from_a_valid_file() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+
+    let response = import_rates(
+        &test_context,
+        &client,
+        "currency,effective_from,rate\nUSD,2025-11-01,1.16\nGBP,2025-11-01,0.88\n",
+    )
+    .await;
+    assert_eq!(200, response.status().as_u16());
+    assert_eq!(
+        r#"[{"currency":"USD","effective_from":"2025-11-01","rate":1.16},{"currency":"GBP","effective_from":"2025-11-01","rate":0.88}]"#,
+        response.text().await.unwrap()
+    );
+
+    let response = import_rates(
+        &test_context,
+        &client,
+        "USD,2025-11-02,1.17\nJPY,2025-11-02,1",
+    )
+    .await;
+    assert_eq!(422, response.status().as_u16());
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM exchange_rates")
+        .fetch_one(&test_context.connection_pool)
+        .await
+        .unwrap();
+    assert_eq!(2, count);
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn prices_an_order_from_the_price_list_of_its_currency() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let product_id = Uuid::new_v4();
+    insert_product_variant_on_db(product_id, 20.0, &test_context.connection_pool)
+        .await
+        .expect("Failed to prepare DB content for test");
+    import_rates(&test_context, &client, "USD,2000-01-01,1.25").await;
+    for (currency, amount, status) in [("USD", "25.0", 200), ("EUR", "30.0", 422)] {
+        let response = client
+            .put(format!(
+                "{}/products/{}/prices/{}",
+                test_context.address, product_id, currency
+            ))
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .body(format!("amount={}", amount))
+            .send()
+            .await
+            .expect("Failed to set a price");
+        assert_eq!(status, response.status().as_u16());
+    }
+    let prices = client
+        .get(format!(
+            "{}/products/{}/prices",
+            test_context.address, product_id
+        ))
+        .send()
+        .await
+        .expect("Failed to get the prices")
+        .text()
+        .await
+        .unwrap();
+    assert!(prices.contains(r#""currency":"EUR","amount":20.0"#));
+    assert!(prices.contains(r#""currency":"USD","amount":25.0"#));
+
+    let order_id = Uuid::new_v4();
+    let customer_id = Uuid::new_v4();
+    insert_customer_on_db(customer_id, &test_context.connection_pool.clone())
+        .await
+        .expect("Failed to prepare DB content for test");
+    client
+        .post(format!("{}/orders", test_context.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!(
+            "order_id={}&customer_id={}&currency=USD",
+            order_id, customer_id
+        ))
+        .send()
+        .await
+        .expect("Failed to create an order");
+    let body = client
+        .post(format!(
+            "{}/orders/{}/products",
+            test_context.address, order_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!("product_id={}&quantity=2", product_id))
+        .send()
+        .await
+        .expect("Failed to add a product to the order")
+        .text()
+        .await
+        .unwrap();
+
+    assert!(body.contains(r#""price":25.0,"quantity":2"#));
+    assert!(body.contains(r#""total_price":50.0"#));
+    assert!(body.contains(r#""currency":"USD","exchange_rate":1.25,"base_total_price":40.0"#));
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn rejects_an_order_in_a_currency_without_an_exchange_rate() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = Uuid::new_v4();
+    insert_customer_on_db(customer_id, &test_context.connection_pool.clone())
+        .await
+        .expect("Failed to prepare DB content for test");
+
+    let response = client
+        .post(format!("{}/orders", test_context.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!(
+            "order_id={}&customer_id={}&currency=GBP",
+            Uuid::new_v4(),
+            customer_id
+        ))
+        .send()
+        .await
+        .expect("Failed to create an order");
+
+    assert_eq!(422, response.status().as_u16());
+
+    test_context.cleanup().await;
+}
+
+async fn import_rates(test_context: &TestContext, client: &Client, csv: &str) -> reqwest::Response {
+    client
+        .post(format!("{}/exchange-rates", test_context.address))
+        .header("Content-Type", "text/csv")
+        .body(csv.to_string())
+        .send()
+        .await
+        .expect("Failed to import exchange rates")
+}
@@ -0,0 +1,105 @@
+use reqwest::{header::ETAG, Client, StatusCode};
+use uuid::Uuid;
+
+use crate::helpers::{create_customer, TestContext};
+
+#[actix_web::test]
+async fn orders_snapshot_the_default_addresses_of_the_customer() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = create_customer(&test_context).await;
+
+    let response = client
+        .post(format!(
+            "{}/customers/{}/addresses",
+            test_context.address, customer_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("If-Match", "\"1\"")
+        .body("street=Via Dante 1&city=Milano&zip_code=20121&country=IT")
+        .send()
+        .await
+        .expect("Failed to add the address");
+    assert_eq!(StatusCode::CREATED, response.status());
+    assert_eq!("\"2\"", response.headers()[ETAG]);
+    let body = response.text().await.unwrap();
+    let address_id = address_id(&body);
+    assert!(body.contains(&format!(
+        r#""default_shipping_address_id":"{}""#,
+        address_id
+    )));
+
+    let order_id = Uuid::new_v4();
+    let response = client
+        .post(format!("{}/orders", test_context.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!("order_id={}&customer_id={}", order_id, customer_id))
+        .send()
+        .await
+        .expect("Failed to create an order");
+    assert!(response.status().is_success());
+
+    let response = client
+        .get(format!("{}/orders/{}", test_context.address, order_id))
+        .send()
+        .await
+        .expect("Failed to get the order");
+    let body = response.text().await.unwrap();
+    assert!(body.contains(r#""shipping_address":{"street":"Via Dante 1","city":"Milano""#));
+    assert!(body.contains(r#""billing_address":{"street":"Via Dante 1","city":"Milano""#));
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn return_not_found_when_removing_an_unknown_address() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = create_customer(&test_context).await;
+
+    let response = client
+        .delete(format!(
+            "{}/customers/{}/addresses/{}",
+            test_context.address,
+            customer_id,
+            Uuid::new_v4()
+        ))
+        .send()
+        .await
+        .expect("Failed to remove the address");
+
+    assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn return_unprocessable_entity_for_an_order_with_an_unknown_address() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = create_customer(&test_context).await;
+
+    let response = client
+        .post(format!("{}/orders", test_context.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!(
+            "order_id={}&customer_id={}&shipping_address_id={}",
+            Uuid::new_v4(),
+            customer_id,
+            Uuid::new_v4()
+        ))
+        .send()
+        .await
+        .expect("Failed to create an order");
+
+    assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+    test_context.cleanup().await;
+}
+fn address_id(body: &str) -> &str {
+    let start = body
+        .find(r#""address_id":""#)
+        .expect("No address in the response")
+        + 14;
+    &body[start..start + 36]
+}
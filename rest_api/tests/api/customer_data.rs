@@ -0,0 +1,102 @@
+use reqwest::{Client, StatusCode};
+use uuid::Uuid;
+
+use crate::helpers::{create_order, TestContext};
+
+#[actix_web::test]
+async fn export_the_data_of_a_customer() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = register_customer(&test_context, &client).await;
+    let order_id = create_order(&test_context, &client, customer_id).await;
+
+    let response = client
+        .get(format!(
+            "{}/customers/{}/export",
+            test_context.address, customer_id
+        ))
+        .send()
+        .await
+        .expect("Failed to export the customer data");
+
+    assert_eq!(StatusCode::OK, response.status());
+    let body = response.text().await.unwrap();
+    assert!(body.contains(r#""email":"jane.roe@example.com""#));
+    assert!(body.contains(&format!(r#""order_id":"{}""#, order_id)));
+    assert!(body.contains(r#""event_type":"customer_created""#));
+    assert!(body.contains(r#""event_type":"order_created""#));
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn erase_the_personal_data_of_a_customer() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = register_customer(&test_context, &client).await;
+    let order_id = create_order(&test_context, &client, customer_id).await;
+
+    let response = client
+        .post(format!(
+            "{}/customers/{}/erasure",
+            test_context.address, customer_id
+        ))
+        .send()
+        .await
+        .expect("Failed to erase the customer");
+    assert_eq!(StatusCode::NO_CONTENT, response.status());
+
+    let body = client
+        .get(format!(
+            "{}/customers/{}/export",
+            test_context.address, customer_id
+        ))
+        .send()
+        .await
+        .expect("Failed to export the customer data")
+        .text()
+        .await
+        .unwrap();
+    assert!(!body.contains("Roe"));
+    assert!(!body.contains("jane.roe@example.com"));
+    assert!(!body.contains("Elm St"));
+    assert!(body.contains(&format!(r#""order_id":"{}""#, order_id)));
+    assert!(body.contains(r#""event_type":"customer_erased""#));
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn return_not_found_when_erasing_a_missing_customer() {
+    let test_context = TestContext::new().await;
+
+    let response = Client::new()
+        .post(format!(
+            "{}/customers/{}/erasure",
+            test_context.address,
+            Uuid::new_v4()
+        ))
+        .send()
+        .await
+        .expect("Failed to erase the customer");
+
+    assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+    test_context.cleanup().await;
+}
+
+/// Creates the customer through the API, so that its events are stored.
+async fn register_customer(test_context: &TestContext, client: &Client) -> Uuid {
+    let body = client
+        .post(format!("{}/customers", test_context.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body("first_name=Jane&last_name=Roe&email=jane.roe@example.com&street=123 Elm St&city=Springfield&zip_code=62701&country=US")
+        .send()
+        .await
+        .expect("Failed to create a customer")
+        .text()
+        .await
+        .unwrap();
+    let start = body.find(r#""customer_id":""#).expect("No customer id") + 15;
+    body[start..start + 36].parse().unwrap()
+}
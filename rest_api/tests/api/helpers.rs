@@ -1,12 +1,23 @@
 use std::{net::TcpListener, path::Path};
 
-use rest_api::settings::{get_settings, Settings};
+use actix_web::{web, App, HttpResponse, HttpServer};
+use chrono::Utc;
+use domain::repositories::outbox_repository::OutboxMessageRepository;
+use event_consumer::{
+    checkout::{CheckoutSagaHandler, CHECKOUT_EVENT_TYPES},
+    consumer::EventProcessor,
+    event_handler::EventHandlerRegistry,
+    inbox::InboxMessage,
+};
+use reqwest::{header::ETAG, Client, Response, StatusCode};
+use rest_api::settings::{get_settings, PaymentProviderSettings, Settings};
 use sqlx::{migrate::Migrator, Connection, Executor, PgConnection, PgPool, Pool, Postgres};
 use uuid::Uuid;
 
 pub struct TestContext {
     pub address: String,
     pub connection_pool: Pool<Postgres>,
+    pub payment_provider: PaymentProviderSettings,
     db_name: String,
 }
 
@@ -16,12 +27,15 @@ impl TestContext {
         let port = listener.local_addr().unwrap().port();
         let db_name = format!("rusty_ecommerce_test_{}", Uuid::new_v4());
         let connection_pool = setup_test_db(db_name.clone()).await;
-        let server = rest_api::startup::run(listener, connection_pool.clone())
-            .expect("Failed to start server");
+        let payment_provider = spawn_payment_provider();
+        let server =
+            rest_api::startup::run(listener, connection_pool.clone(), payment_provider.clone())
+                .expect("Failed to start server");
         let _ = actix_web::rt::spawn(server);
         TestContext {
             address: format!("http://127.0.0.1:{}", port),
             connection_pool,
+            payment_provider,
             db_name,
         }
     }
@@ -33,6 +47,27 @@ impl TestContext {
     }
 }
 
+/// Stub of the payment provider, accepting every request. Each answer
+/// carries a new id, taken as authorization id by authorizations.
+fn spawn_payment_provider() -> PaymentProviderSettings {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind port");
+    let port = listener.local_addr().unwrap().port();
+    let server = HttpServer::new(|| {
+        App::new().default_service(web::to(|| async {
+            HttpResponse::Ok()
+                .content_type("application/json")
+                .body(format!(r#"{{"id":"auth_{}"}}"#, Uuid::new_v4().simple()))
+        }))
+    })
+    .listen(listener)
+    .expect("Failed to start payment provider")
+    .run();
+    actix_web::rt::spawn(server);
+    PaymentProviderSettings {
+        base_url: format!("http://127.0.0.1:{}", port),
+    }
+}
+
 async fn setup_test_db(db_name: String) -> Pool<Postgres> {
     let settings = get_settings().expect("Failed to read settings");
     create_test_db(&db_name, &settings).await;
@@ -104,18 +139,256 @@ pub async fn insert_customer_on_db(
 ) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
-        INSERT INTO customers (id, first_name, last_name, street, city, zip_code, state)
-        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        INSERT INTO customers (id, first_name, last_name, email, street, city, zip_code, country)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
         "#,
     )
     .bind(customer_id)
     .bind("John")
     .bind("Doe")
+    .bind(format!("{}@example.com", customer_id))
     .bind("John's street")
     .bind("John's city")
-    .bind("John's zip code")
-    .bind("John's state")
+    .bind("62701")
+    .bind("US")
     .execute(pool)
     .await?;
     Ok(())
 }
+
+/// Stores a catalog product with a single variant, the id of order items.
+pub async fn insert_product_variant_on_db(
+    variant_id: Uuid,
+    price: f64,
+    pool: &Pool<Postgres>,
+) -> Result<(), sqlx::Error> {
+    let product_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO products (id, name, version) VALUES ($1, $2, 1)")
+        .bind(product_id)
+        .bind("T-shirt")
+        .execute(pool)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO product_variants (id, product_id, position, sku, price)
+        VALUES ($1, $2, 0, $3, $4)
+        "#,
+    )
+    .bind(variant_id)
+    .bind(product_id)
+    .bind(format!("TS-{}", variant_id))
+    .bind(price)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Checks out a cart of the customer with `quantity` units of the catalog
+/// variant, in stock, then runs the checkout until the order is confirmed
+/// and its payment captured. `checkout` adds fields to the checkout form.
+pub async fn create_confirmed_order(
+    test_context: &TestContext,
+    client: &Client,
+    customer_id: Uuid,
+    variant_id: Uuid,
+    quantity: i32,
+    checkout: &str,
+) -> Uuid {
+    let response = post(
+        test_context,
+        client,
+        format!("/products/{}/stock/{}", variant_id, Uuid::new_v4()),
+        format!("quantity={}", quantity),
+        None,
+    )
+    .await;
+    assert_eq!(StatusCode::OK, response.status());
+    let cart_id = create_cart(test_context, client, Some(customer_id)).await;
+    let price: f64 = sqlx::query_scalar("SELECT price FROM product_variants WHERE id = $1")
+        .bind(variant_id)
+        .fetch_one(&test_context.connection_pool)
+        .await
+        .expect("Failed to read the price");
+    let response = post(
+        test_context,
+        client,
+        format!("/carts/{}/items", cart_id),
+        format!(
+            "product_id={}&price={}&quantity={}",
+            variant_id, price, quantity
+        ),
+        None,
+    )
+    .await;
+    assert_eq!(StatusCode::OK, response.status());
+    let order_id = Uuid::new_v4();
+    let response = post(
+        test_context,
+        client,
+        format!("/carts/{}/checkout", cart_id),
+        format!("order_id={}{}", order_id, checkout),
+        None,
+    )
+    .await;
+    assert_eq!(StatusCode::CREATED, response.status());
+
+    run_checkouts(test_context).await;
+    let status: String = sqlx::query_scalar("SELECT status FROM orders WHERE id = $1")
+        .bind(order_id)
+        .fetch_one(&test_context.connection_pool)
+        .await
+        .expect("Failed to read the order");
+    assert_eq!("confirmed", status);
+    order_id
+}
+
+/// Delivers the unprocessed outbox messages to the checkout saga, as the
+/// outbox processor and the event consumer would, until none is left.
+pub async fn run_checkouts(test_context: &TestContext) {
+    let pool = test_context.connection_pool.clone();
+    let mut registry = EventHandlerRegistry::new();
+    for event_type in CHECKOUT_EVENT_TYPES {
+        registry = registry.register_handler(
+            event_type,
+            Box::new(CheckoutSagaHandler::new(
+                pool.clone(),
+                test_context.payment_provider.base_url.clone(),
+            )),
+        );
+    }
+    let processor = EventProcessor::new(pool.clone(), registry);
+    let outbox = adapters::sqlx::pg_outbox_message_repository::PgOutboxMessageRepository::new(pool);
+    loop {
+        let messages = outbox
+            .find_unprocessed()
+            .await
+            .expect("Failed to read the outbox")
+            .unwrap_or_default();
+        if messages.is_empty() {
+            break;
+        }
+        for message in messages {
+            processor
+                .process(InboxMessage::new(
+                    message.id(),
+                    message.event_type().to_string(),
+                    message.event_payload(),
+                    message.created_at(),
+                    Utc::now(),
+                ))
+                .await
+                .expect("Failed to handle the message");
+            outbox
+                .set_processed(message.id(), Utc::now())
+                .await
+                .expect("Failed to update the outbox");
+        }
+    }
+}
+
+/// Ships `quantity` units of the product of the order in one parcel and
+/// delivers it.
+pub async fn deliver_order(
+    test_context: &TestContext,
+    client: &Client,
+    order_id: Uuid,
+    product_id: Uuid,
+    quantity: i32,
+) {
+    let shipping_method_id = Uuid::new_v4();
+    let response = post(
+        test_context,
+        client,
+        "/shipping-methods".to_string(),
+        format!(
+            "shipping_method_id={}&name=Standard&carrier=DHL&kind=flat&amount=5.9",
+            shipping_method_id
+        ),
+        None,
+    )
+    .await;
+    assert_eq!(StatusCode::CREATED, response.status());
+    let shipment_id = Uuid::new_v4();
+    let response = post(
+        test_context,
+        client,
+        format!("/orders/{}/shipments", order_id),
+        format!(
+            "shipment_id={}&shipping_method_id={}&weight=1.5&product_id={}&quantity={}",
+            shipment_id, shipping_method_id, product_id, quantity
+        ),
+        None,
+    )
+    .await;
+    assert_eq!(StatusCode::CREATED, response.status());
+    for step in ["ship", "deliver"] {
+        let response = post(
+            test_context,
+            client,
+            format!("/shipments/{}/{}", shipment_id, step),
+            "tracking_number=JD0001".to_string(),
+            None,
+        )
+        .await;
+        assert_eq!(StatusCode::OK, response.status());
+    }
+}
+
+pub async fn create_customer(test_context: &TestContext) -> Uuid {
+    let customer_id = Uuid::new_v4();
+    insert_customer_on_db(customer_id, &test_context.connection_pool.clone())
+        .await
+        .expect("Failed to prepare DB content for test");
+    customer_id
+}
+
+/// Creates an empty order of the customer.
+pub async fn create_order(test_context: &TestContext, client: &Client, customer_id: Uuid) -> Uuid {
+    let order_id = Uuid::new_v4();
+    let response = post(
+        test_context,
+        client,
+        "/orders".to_string(),
+        format!("order_id={}&customer_id={}", order_id, customer_id),
+        None,
+    )
+    .await;
+    assert_eq!("\"1\"", response.headers()[ETAG]);
+    order_id
+}
+
+/// Creates a cart, of the customer if any, and returns its id.
+pub async fn create_cart(
+    test_context: &TestContext,
+    client: &Client,
+    customer_id: Option<Uuid>,
+) -> String {
+    let body = customer_id
+        .map(|customer_id| format!("customer_id={}", customer_id))
+        .unwrap_or_default();
+    let response = post(test_context, client, "/carts".to_string(), body, None).await;
+    assert_eq!(StatusCode::CREATED, response.status());
+    let body = response.text().await.unwrap();
+    let start = body
+        .find(r#""cart_id":""#)
+        .expect("No cart in the response")
+        + 11;
+    body[start..start + 36].to_string()
+}
+
+pub async fn post(
+    test_context: &TestContext,
+    client: &Client,
+    path: String,
+    body: String,
+    if_match: Option<&str>,
+) -> Response {
+    let mut request = client
+        .post(format!("{}{}", test_context.address, path))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(body);
+    if let Some(if_match) = if_match {
+        request = request.header("If-Match", if_match);
+    }
+    request.send().await.expect("Failed to send the request")
+}
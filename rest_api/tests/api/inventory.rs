@@ -0,0 +1,82 @@
+use reqwest::{header::ETAG, Client, StatusCode};
+use uuid::Uuid;
+
+use crate::helpers::TestContext;
+
+#[actix_web::test]
+async fn adjust_the_stock_of_a_product_in_a_warehouse() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let product_id = Uuid::new_v4();
+    let warehouse_id = Uuid::new_v4();
+
+    let response = adjust_stock(&test_context, &client, product_id, warehouse_id, 5, None).await;
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!("\"1\"", response.headers()[ETAG]);
+
+    let response = adjust_stock(
+        &test_context,
+        &client,
+        product_id,
+        warehouse_id,
+        -2,
+        Some(1),
+    )
+    .await;
+    assert_eq!(StatusCode::OK, response.status());
+
+    let response = client
+        .get(format!(
+            "{}/products/{}/stock",
+            test_context.address, product_id
+        ))
+        .send()
+        .await
+        .expect("Failed to get the stock");
+    assert_eq!(StatusCode::OK, response.status());
+    let body = response.text().await.unwrap();
+    assert!(body.contains(&format!(r#""warehouse_id":"{}""#, warehouse_id)));
+    assert!(body.contains(r#""on_hand":3"#));
+    assert!(body.contains(r#""available":3"#));
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn cannot_remove_more_than_on_hand_nor_adjust_stale_stock() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let product_id = Uuid::new_v4();
+    let warehouse_id = Uuid::new_v4();
+    adjust_stock(&test_context, &client, product_id, warehouse_id, 1, None).await;
+
+    let response = adjust_stock(&test_context, &client, product_id, warehouse_id, -2, None).await;
+    assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+    adjust_stock(&test_context, &client, product_id, warehouse_id, 1, Some(1)).await;
+    let response = adjust_stock(&test_context, &client, product_id, warehouse_id, 1, Some(1)).await;
+    assert_eq!(StatusCode::CONFLICT, response.status());
+
+    test_context.cleanup().await;
+}
+
+async fn adjust_stock(
+    test_context: &TestContext,
+    client: &Client,
+    product_id: Uuid,
+    warehouse_id: Uuid,
+    quantity: i32,
+    expected_version: Option<i64>,
+) -> reqwest::Response {
+    let mut request = client
+        .post(format!(
+            "{}/products/{}/stock/{}",
+            test_context.address, product_id, warehouse_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!("quantity={}", quantity));
+    if let Some(version) = expected_version {
+        request = request.header("If-Match", format!("\"{}\"", version));
+    }
+    request.send().await.expect("Failed to adjust the stock")
+}
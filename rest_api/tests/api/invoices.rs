@@ -0,0 +1,127 @@
+use reqwest::{header::CONTENT_TYPE, Client, StatusCode};
+use uuid::Uuid;
+
+use crate::helpers::{
+    create_confirmed_order, create_customer, deliver_order, insert_product_variant_on_db,
+    TestContext,
+};
+
+#[actix_web::test]
+async fn refunding_a_paid_order_credits_its_invoice() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let (order_id, product_id) = create_paid_order(&test_context, &client).await;
+    let return_id = Uuid::new_v4();
+    client
+        .post(format!(
+            "{}/orders/{}/returns",
+            test_context.address, order_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!(
+            "return_id={}&product_id={}&quantity=1&reason=Broken",
+            return_id, product_id
+        ))
+        .send()
+        .await
+        .expect("Failed to request a return");
+    for step in ["approve", "receive", "refund"] {
+        let response = client
+            .post(format!(
+                "{}/returns/{}/{}",
+                test_context.address, return_id, step
+            ))
+            .send()
+            .await
+            .expect("Failed to update the return");
+        assert_eq!(StatusCode::OK, response.status());
+    }
+
+    let response = client
+        .get(format!(
+            "{}/orders/{}/invoice",
+            test_context.address, order_id
+        ))
+        .send()
+        .await
+        .expect("Failed to get the invoice");
+    assert_eq!(StatusCode::OK, response.status());
+    let body = response.text().await.unwrap();
+    assert!(body.contains(r#""invoice_number":"INV-"#));
+    assert!(body.contains(r#""total":19.98"#));
+    assert!(body.contains(r#""credit_note_number":"CN-"#));
+    assert!(body.contains(&format!(r#""refund_key":"return_{}""#, return_id)));
+
+    let response = client
+        .get(format!(
+            "{}/orders/{}/invoice",
+            test_context.address, order_id
+        ))
+        .header("Accept", "application/pdf")
+        .send()
+        .await
+        .expect("Failed to get the invoice");
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!("application/pdf", response.headers()[CONTENT_TYPE]);
+    assert!(response.bytes().await.unwrap().starts_with(b"%PDF"));
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn checkout_invoices_the_order() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let (order_id, _) = create_paid_order(&test_context, &client).await;
+
+    let response = client
+        .get(format!(
+            "{}/orders/{}/invoice",
+            test_context.address, order_id
+        ))
+        .send()
+        .await
+        .expect("Failed to get the invoice");
+
+    assert_eq!(StatusCode::OK, response.status());
+    let body = response.text().await.unwrap();
+    assert!(body.contains(r#""invoice_number":"INV-"#));
+    assert!(body.contains(r#""total":19.98"#));
+    assert!(body.contains(r#""credit_notes":[]"#));
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn orders_not_paid_have_no_invoice() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+
+    let response = client
+        .get(format!(
+            "{}/orders/{}/invoice",
+            test_context.address,
+            Uuid::new_v4()
+        ))
+        .send()
+        .await
+        .expect("Failed to get the invoice");
+
+    assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+    test_context.cleanup().await;
+}
+
+/// Creates a confirmed order of two items and delivers it. Its checkout
+/// captures the payment, which issues the invoice.
+async fn create_paid_order(test_context: &TestContext, client: &Client) -> (Uuid, Uuid) {
+    let customer_id = create_customer(test_context).await;
+    let product_id = Uuid::new_v4();
+    insert_product_variant_on_db(product_id, 9.99, &test_context.connection_pool)
+        .await
+        .expect("Failed to prepare DB content for test");
+    let order_id =
+        create_confirmed_order(test_context, client, customer_id, product_id, 2, "").await;
+    deliver_order(test_context, client, order_id, product_id, 2).await;
+    (order_id, product_id)
+}
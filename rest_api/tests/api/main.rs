@@ -1,4 +1,20 @@
+mod add_product_to_order;
+mod carts;
+mod catalog;
+mod checkout;
 mod create_customer;
 mod create_order;
+mod currencies;
+mod customer_addresses;
+mod customer_data;
 mod health_check;
 mod helpers;
+mod inventory;
+mod invoices;
+mod promotions;
+mod returns;
+mod reviews;
+mod search;
+mod shipments;
+mod taxes;
+mod update_customer;
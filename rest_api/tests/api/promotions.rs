@@ -0,0 +1,140 @@
+use reqwest::{header::ETAG, Client, Response, StatusCode};
+use uuid::Uuid;
+
+use crate::helpers::{
+    create_customer, create_order, insert_product_variant_on_db, post, TestContext,
+};
+
+#[actix_web::test]
+async fn apply_a_coupon_to_an_order() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let response = create_promotion(
+        &test_context,
+        &client,
+        "code=spring10&kind=percentage&value=10&min_spend=15&max_uses_per_customer=1",
+    )
+    .await;
+    assert_eq!(StatusCode::CREATED, response.status());
+    let (order_id, _) = create_order_of_two_items(&test_context, &client).await;
+
+    let response = apply_coupon(&test_context, &client, order_id, "spring10").await;
+
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!("\"3\"", response.headers()[ETAG]);
+    let body = response.text().await.unwrap();
+    assert!(body.contains(r#""subtotal":20.0"#));
+    assert!(body.contains(r#""discounts":[{"code":"SPRING10","amount":2.0}]"#));
+    assert!(body.contains(r#""total_price":18.0"#));
+    let response = client
+        .get(format!("{}/orders/{}", test_context.address, order_id))
+        .send()
+        .await
+        .expect("Failed to get the order");
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains(r#""total_price":18.0"#));
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn gives_no_discount_once_the_order_is_below_the_min_spend() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    create_promotion(
+        &test_context,
+        &client,
+        "code=spring10&kind=percentage&value=10&min_spend=15",
+    )
+    .await;
+    let (order_id, product_id) = create_order_of_two_items(&test_context, &client).await;
+    apply_coupon(&test_context, &client, order_id, "spring10").await;
+
+    let response = client
+        .put(format!(
+            "{}/orders/{}/products/{}",
+            test_context.address, order_id, product_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body("quantity=1")
+        .send()
+        .await
+        .expect("Failed to change the quantity of a product");
+
+    assert_eq!(StatusCode::OK, response.status());
+    let body = response.text().await.unwrap();
+    assert!(body.contains(r#""discounts":[{"code":"SPRING10","amount":0.0}]"#));
+    assert!(body.contains(r#""total_price":10.0"#));
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn cannot_apply_a_coupon_below_its_min_spend_nor_an_unknown_one() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    create_promotion(
+        &test_context,
+        &client,
+        "code=BIGSPENDER&kind=fixed&value=5&min_spend=100",
+    )
+    .await;
+    let (order_id, _) = create_order_of_two_items(&test_context, &client).await;
+
+    let response = apply_coupon(&test_context, &client, order_id, "BIGSPENDER").await;
+    assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+    let response = apply_coupon(&test_context, &client, order_id, "UNKNOWN").await;
+    assert_eq!(StatusCode::NOT_FOUND, response.status());
+
+    test_context.cleanup().await;
+}
+
+async fn create_promotion(test_context: &TestContext, client: &Client, body: &str) -> Response {
+    client
+        .post(format!("{}/promotions", test_context.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!("promotion_id={}&{}", Uuid::new_v4(), body))
+        .send()
+        .await
+        .expect("Failed to create a promotion")
+}
+async fn apply_coupon(
+    test_context: &TestContext,
+    client: &Client,
+    order_id: Uuid,
+    code: &str,
+) -> Response {
+    client
+        .post(format!(
+            "{}/orders/{}/coupons",
+            test_context.address, order_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!("code={}", code))
+        .send()
+        .await
+        .expect("Failed to apply the coupon")
+}
+
+/// Creates an order of two units of a product worth 10.0 each.
+async fn create_order_of_two_items(test_context: &TestContext, client: &Client) -> (Uuid, Uuid) {
+    let product_id = Uuid::new_v4();
+    insert_product_variant_on_db(product_id, 10.0, &test_context.connection_pool)
+        .await
+        .expect("Failed to prepare DB content for test");
+    let customer_id = create_customer(test_context).await;
+    let order_id = create_order(test_context, client, customer_id).await;
+    post(
+        test_context,
+        client,
+        format!("/orders/{}/products", order_id),
+        format!("product_id={}&quantity=2", product_id),
+        None,
+    )
+    .await;
+    (order_id, product_id)
+}
@@ -0,0 +1,164 @@
+use reqwest::{header::ETAG, Client, Response, StatusCode};
+use uuid::Uuid;
+
+use crate::helpers::{
+    create_confirmed_order, create_customer, deliver_order, insert_product_variant_on_db,
+    TestContext,
+};
+
+#[actix_web::test]
+async fn refund_a_returned_item_of_a_confirmed_order() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let (order_id, product_id) = create_returnable_order(&test_context, &client).await;
+    let return_id = Uuid::new_v4();
+
+    let response = request_return(&test_context, &client, order_id, return_id, product_id, 1).await;
+    assert_eq!(StatusCode::CREATED, response.status());
+    assert_eq!("\"1\"", response.headers()[ETAG]);
+
+    for (step, version) in [("approve", 1), ("receive", 2), ("refund", 3)] {
+        let response = client
+            .post(format!(
+                "{}/returns/{}/{}",
+                test_context.address, return_id, step
+            ))
+            .header("If-Match", format!("\"{}\"", version))
+            .send()
+            .await
+            .expect("Failed to update the return");
+        assert_eq!(StatusCode::OK, response.status());
+    }
+
+    let response = client
+        .get(format!("{}/returns/{}", test_context.address, return_id))
+        .send()
+        .await
+        .expect("Failed to get the return");
+    assert_eq!(StatusCode::OK, response.status());
+    let body = response.text().await.unwrap();
+    assert!(body.contains(r#""state":"refunded""#));
+    assert!(body.contains(r#""refund_amount":9.99"#));
+    let refunded: f64 =
+        sqlx::query_scalar("SELECT SUM(amount) FROM payment_refunds WHERE idempotency_key = $1")
+            .bind(format!("return_{}", return_id))
+            .fetch_one(&test_context.connection_pool)
+            .await
+            .unwrap();
+    assert_eq!(9.99, refunded);
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn cannot_return_more_than_ordered_nor_refund_a_return_not_received() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let (order_id, product_id) = create_returnable_order(&test_context, &client).await;
+    let return_id = Uuid::new_v4();
+    request_return(&test_context, &client, order_id, return_id, product_id, 1).await;
+
+    let response = client
+        .post(format!(
+            "{}/returns/{}/items",
+            test_context.address, return_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!("product_id={}&quantity=1", product_id))
+        .send()
+        .await
+        .expect("Failed to add an item to the return");
+    assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+    let response = client
+        .post(format!(
+            "{}/returns/{}/refund",
+            test_context.address, return_id
+        ))
+        .send()
+        .await
+        .expect("Failed to refund the return");
+    assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+    let response = client
+        .post(format!(
+            "{}/returns/{}/reject",
+            test_context.address, return_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body("reason=Worn")
+        .send()
+        .await
+        .expect("Failed to reject the return");
+    assert_eq!(StatusCode::OK, response.status());
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains(r#""rejection_reason":"Worn""#));
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn cannot_return_an_order_not_delivered() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = create_customer(&test_context).await;
+    let product_id = Uuid::new_v4();
+    insert_product_variant_on_db(product_id, 9.99, &test_context.connection_pool)
+        .await
+        .expect("Failed to prepare DB content for test");
+    let order_id =
+        create_confirmed_order(&test_context, &client, customer_id, product_id, 1, "").await;
+
+    let response = request_return(
+        &test_context,
+        &client,
+        order_id,
+        Uuid::new_v4(),
+        product_id,
+        1,
+    )
+    .await;
+
+    assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+    test_context.cleanup().await;
+}
+
+/// Creates a confirmed order of one item and delivers it.
+async fn create_returnable_order(test_context: &TestContext, client: &Client) -> (Uuid, Uuid) {
+    let customer_id = create_customer(test_context).await;
+    let product_id = Uuid::new_v4();
+    insert_product_variant_on_db(product_id, 9.99, &test_context.connection_pool)
+        .await
+        .expect("Failed to prepare DB content for test");
+    let order_id =
+        create_confirmed_order(test_context, client, customer_id, product_id, 1, "").await;
+    deliver_order(test_context, client, order_id, product_id, 1).await;
+    (order_id, product_id)
+}
+
+async fn request_return(
+    test_context: &TestContext,
+    client: &Client,
+    order_id: Uuid,
+    return_id: Uuid,
+    product_id: Uuid,
+    quantity: i32,
+) -> Response {
+    client
+        .post(format!(
+            "{}/orders/{}/returns",
+            test_context.address, order_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!(
+            "return_id={}&product_id={}&quantity={}&reason=Too+small",
+            return_id, product_id, quantity
+        ))
+        .send()
+        .await
+        .expect("Failed to request a return")
+}
@@ -0,0 +1,184 @@
+use reqwest::{header::ETAG, Client, Response, StatusCode};
+use uuid::Uuid;
+
+use crate::helpers::{create_confirmed_order, create_customer, post, TestContext};
+
+#[actix_web::test]
+async fn reviews_are_listed_and_rated_once_approved() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let (product_id, variant_id) = create_product(&test_context, &client).await;
+    let buyer_id = create_customer(&test_context).await;
+    create_confirmed_order(&test_context, &client, buyer_id, variant_id, 1, "").await;
+
+    let buyer_review_id = Uuid::new_v4();
+    let response = submit_review(
+        &test_context,
+        &client,
+        product_id,
+        buyer_review_id,
+        buyer_id,
+        5,
+    )
+    .await;
+    assert_eq!(StatusCode::CREATED, response.status());
+    assert_eq!("\"1\"", response.headers()[ETAG]);
+    let body = response.text().await.unwrap();
+    assert!(body.contains(r#""verified_purchase":true"#));
+    assert!(body.contains(r#""status":"pending""#));
+    let visitor_review_id = Uuid::new_v4();
+    let visitor_id = create_customer(&test_context).await;
+    let body = submit_review(
+        &test_context,
+        &client,
+        product_id,
+        visitor_review_id,
+        visitor_id,
+        2,
+    )
+    .await
+    .text()
+    .await
+    .unwrap();
+    assert!(body.contains(r#""verified_purchase":false"#));
+    assert_eq!(
+        StatusCode::CONFLICT,
+        submit_review(
+            &test_context,
+            &client,
+            product_id,
+            Uuid::new_v4(),
+            buyer_id,
+            4
+        )
+        .await
+        .status()
+    );
+    assert_eq!(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        submit_review(
+            &test_context,
+            &client,
+            product_id,
+            Uuid::new_v4(),
+            create_customer(&test_context).await,
+            6
+        )
+        .await
+        .status()
+    );
+
+    let response = post(
+        &test_context,
+        &client,
+        format!("/reviews/{}/approve", buyer_review_id),
+        String::new(),
+        Some("\"1\""),
+    )
+    .await;
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!("\"2\"", response.headers()[ETAG]);
+    post(
+        &test_context,
+        &client,
+        format!("/reviews/{}/reject", visitor_review_id),
+        String::new(),
+        None,
+    )
+    .await;
+    assert_eq!(
+        StatusCode::UNPROCESSABLE_ENTITY,
+        post(
+            &test_context,
+            &client,
+            format!("/reviews/{}/approve", visitor_review_id),
+            String::new(),
+            None,
+        )
+        .await
+        .status()
+    );
+
+    let body = get(
+        &test_context,
+        &client,
+        format!("/products/{}/reviews", product_id),
+    )
+    .await;
+    assert!(body.contains(r#""average_rating":5.0,"review_count":1"#));
+    assert!(body.contains(&buyer_review_id.to_string()));
+    assert!(!body.contains(&visitor_review_id.to_string()));
+    let body = get(
+        &test_context,
+        &client,
+        format!("/products/{}/reviews?status=rejected", product_id),
+    )
+    .await;
+    assert!(body.contains(&visitor_review_id.to_string()));
+    let body = get(&test_context, &client, format!("/products/{}", product_id)).await;
+    assert!(body.contains(r#""average_rating":5.0,"review_count":1"#));
+
+    let payloads: Vec<String> = sqlx::query_scalar(
+        "SELECT event_payload FROM outbox_messages WHERE event_type = 'review_submitted'",
+    )
+    .fetch_all(&test_context.connection_pool)
+    .await
+    .unwrap();
+    assert_eq!(2, payloads.len());
+
+    test_context.cleanup().await;
+}
+
+/// A product with a single variant.
+async fn create_product(test_context: &TestContext, client: &Client) -> (Uuid, Uuid) {
+    let product_id = Uuid::new_v4();
+    let variant_id = Uuid::new_v4();
+    post(
+        test_context,
+        client,
+        "/products".to_string(),
+        format!("product_id={}&name=T-shirt", product_id),
+        None,
+    )
+    .await;
+    post(
+        test_context,
+        client,
+        format!("/products/{}/variants", product_id),
+        format!("variant_id={}&sku=TS-GREEN-S&price=15.0", variant_id),
+        None,
+    )
+    .await;
+    (product_id, variant_id)
+}
+async fn submit_review(
+    test_context: &TestContext,
+    client: &Client,
+    product_id: Uuid,
+    review_id: Uuid,
+    customer_id: Uuid,
+    rating: i32,
+) -> Response {
+    post(
+        test_context,
+        client,
+        format!("/products/{}/reviews", product_id),
+        format!(
+            "review_id={}&customer_id={}&rating={}&text=Soft+and+comfy",
+            review_id, customer_id, rating
+        ),
+        None,
+    )
+    .await
+}
+
+async fn get(test_context: &TestContext, client: &Client, path: String) -> String {
+    client
+        .get(format!("{}{}", test_context.address, path))
+        .send()
+        .await
+        .expect("Failed to send the request")
+        .text()
+        .await
+        .unwrap()
+}
@@ -0,0 +1,87 @@
+use reqwest::{Client, StatusCode};
+use uuid::Uuid;
+
+use crate::helpers::{post, TestContext};
+
+#[actix_web::test]
+async fn searches_products_with_filters_and_facets() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let category_id = Uuid::new_v4();
+    let response = post(
+        &test_context,
+        &client,
+        "/categories".to_string(),
+        format!("category_id={}&name=Shirts", category_id),
+        None,
+    )
+    .await;
+    assert!(response.status().is_success());
+    for (name, price) in [
+        ("Linen shirt", 45.0),
+        ("Oxford shirt", 30.0),
+        ("Wool scarf", 20.0),
+    ] {
+        let product_id = Uuid::new_v4();
+        let response = post(
+            &test_context,
+            &client,
+            "/products".to_string(),
+            format!(
+                "product_id={}&name={}&category_id={}",
+                product_id,
+                name.replace(' ', "+"),
+                category_id
+            ),
+            None,
+        )
+        .await;
+        assert!(response.status().is_success());
+        let response = post(
+            &test_context,
+            &client,
+            format!("/products/{}/variants", product_id),
+            format!(
+                "variant_id={}&sku={}&price={}",
+                Uuid::new_v4(),
+                Uuid::new_v4(),
+                price
+            ),
+            None,
+        )
+        .await;
+        assert!(response.status().is_success());
+    }
+
+    let response = client
+        .get(format!(
+            "{}/products/search?q=shirts&category={}&max_price=40&sort=price_asc",
+            test_context.address, category_id
+        ))
+        .send()
+        .await
+        .expect("Failed to search products");
+
+    assert_eq!(StatusCode::OK, response.status());
+    let body = response.text().await.unwrap();
+    assert!(body.contains(r#""name":"Oxford shirt""#));
+    assert!(!body.contains("Linen"));
+    assert!(!body.contains("Wool"));
+    assert!(body.contains(&format!(
+        r#""category_facets":[{{"category_id":"{}","name":"Shirts","count":1}}]"#,
+        category_id
+    )));
+    assert!(body.contains(r#"{"from":25.0,"to":50.0,"count":1}"#));
+
+    let response = client
+        .get(format!(
+            "{}/products/search?q=shirt&sort=cheapest",
+            test_context.address
+        ))
+        .send()
+        .await
+        .expect("Failed to search products");
+    assert_eq!(StatusCode::BAD_REQUEST, response.status());
+
+    test_context.cleanup().await;
+}
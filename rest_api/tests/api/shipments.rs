@@ -0,0 +1,230 @@
+use reqwest::{header::ETAG, Client, Response, StatusCode};
+use uuid::Uuid;
+
+use crate::helpers::{
+    create_confirmed_order, create_customer, insert_product_variant_on_db, TestContext,
+};
+
+#[actix_web::test]
+async fn ship_an_order_in_two_parcels_and_deliver_them() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let shipping_method_id = create_shipping_method(&test_context, &client).await;
+    let (order_id, product_id) =
+        create_order_to_ship(&test_context, &client, shipping_method_id).await;
+    let first_shipment_id = Uuid::new_v4();
+
+    let response = create_shipment(
+        &test_context,
+        &client,
+        order_id,
+        first_shipment_id,
+        shipping_method_id,
+        product_id,
+    )
+    .await;
+    assert_eq!(StatusCode::CREATED, response.status());
+    assert_eq!("\"1\"", response.headers()[ETAG]);
+    let body = response.text().await.unwrap();
+    assert!(body.contains(r#""carrier":"DHL""#));
+
+    let response = ship(&test_context, &client, first_shipment_id, "JD0001", 1).await;
+    assert_eq!(StatusCode::OK, response.status());
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains(r#""tracking_number":"JD0001""#));
+
+    let second_shipment_id = Uuid::new_v4();
+    create_shipment(
+        &test_context,
+        &client,
+        order_id,
+        second_shipment_id,
+        shipping_method_id,
+        product_id,
+    )
+    .await;
+    ship(&test_context, &client, second_shipment_id, "JD0002", 1).await;
+    let response = client
+        .post(format!(
+            "{}/shipments/{}/deliver",
+            test_context.address, second_shipment_id
+        ))
+        .header("If-Match", "\"2\"")
+        .send()
+        .await
+        .expect("Failed to deliver the shipment");
+    assert_eq!(StatusCode::OK, response.status());
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains(r#""state":"delivered""#));
+
+    let payloads: Vec<String> = sqlx::query_scalar(
+        "SELECT event_payload FROM outbox_messages WHERE event_type = 'order_shipped' ORDER BY created_at",
+    )
+    .fetch_all(&test_context.connection_pool)
+    .await
+    .unwrap();
+    assert_eq!(2, payloads.len());
+    assert!(payloads[0].contains(r#""fully_shipped":false"#));
+    assert!(payloads[1].contains(r#""fully_shipped":true"#));
+    let delivered: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM outbox_messages WHERE event_type = 'shipment_delivered'",
+    )
+    .fetch_one(&test_context.connection_pool)
+    .await
+    .unwrap();
+    assert_eq!(1, delivered);
+
+    let response = client
+        .get(format!("{}/orders/{}", test_context.address, order_id))
+        .send()
+        .await
+        .expect("Failed to get the order");
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains(r#""shipping_cost":5.9"#));
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn cannot_ship_more_than_ordered_nor_without_a_tracking_number() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let shipping_method_id = create_shipping_method(&test_context, &client).await;
+    let (order_id, product_id) =
+        create_order_to_ship(&test_context, &client, shipping_method_id).await;
+    let shipment_id = Uuid::new_v4();
+    create_shipment(
+        &test_context,
+        &client,
+        order_id,
+        shipment_id,
+        shipping_method_id,
+        product_id,
+    )
+    .await;
+
+    let response = client
+        .post(format!(
+            "{}/shipments/{}/items",
+            test_context.address, shipment_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!("product_id={}&quantity=2", product_id))
+        .send()
+        .await
+        .expect("Failed to add an item to the shipment");
+    assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+    let response = ship(&test_context, &client, shipment_id, "", 1).await;
+    assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, response.status());
+
+    let response = ship(&test_context, &client, shipment_id, "JD0001", 2).await;
+    assert_eq!(StatusCode::CONFLICT, response.status());
+
+    let response = client
+        .get(format!("{}/shipping-methods", test_context.address))
+        .send()
+        .await
+        .expect("Failed to get the shipping methods");
+    assert_eq!(StatusCode::OK, response.status());
+    assert!(response
+        .text()
+        .await
+        .unwrap()
+        .contains(&shipping_method_id.to_string()));
+
+    test_context.cleanup().await;
+}
+
+/// A flat rate of 5.9 by DHL.
+async fn create_shipping_method(test_context: &TestContext, client: &Client) -> Uuid {
+    let shipping_method_id = Uuid::new_v4();
+    let response = client
+        .post(format!("{}/shipping-methods", test_context.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!(
+            "shipping_method_id={}&name=Standard&carrier=DHL&kind=flat&amount=5.9",
+            shipping_method_id
+        ))
+        .send()
+        .await
+        .expect("Failed to create a shipping method");
+    assert_eq!(StatusCode::CREATED, response.status());
+    shipping_method_id
+}
+
+/// Creates a confirmed order of two items, shipped with the method.
+async fn create_order_to_ship(
+    test_context: &TestContext,
+    client: &Client,
+    shipping_method_id: Uuid,
+) -> (Uuid, Uuid) {
+    let customer_id = create_customer(test_context).await;
+    let product_id = Uuid::new_v4();
+    insert_product_variant_on_db(product_id, 9.99, &test_context.connection_pool)
+        .await
+        .expect("Failed to prepare DB content for test");
+    let order_id = create_confirmed_order(
+        test_context,
+        client,
+        customer_id,
+        product_id,
+        2,
+        &format!("&shipping_method_id={}", shipping_method_id),
+    )
+    .await;
+    (order_id, product_id)
+}
+
+/// Creates a shipment of one unit of the product, weighing 1.5 kg.
+async fn create_shipment(
+    test_context: &TestContext,
+    client: &Client,
+    order_id: Uuid,
+    shipment_id: Uuid,
+    shipping_method_id: Uuid,
+    product_id: Uuid,
+) -> Response {
+    client
+        .post(format!(
+            "{}/orders/{}/shipments",
+            test_context.address, order_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body(format!(
+            "shipment_id={}&shipping_method_id={}&weight=1.5&product_id={}&quantity=1",
+            shipment_id, shipping_method_id, product_id
+        ))
+        .send()
+        .await
+        .expect("Failed to create a shipment")
+}
+
+async fn ship(
+    test_context: &TestContext,
+    client: &Client,
+    shipment_id: Uuid,
+    tracking_number: &str,
+    version: i64,
+) -> Response {
+    client
+        .post(format!(
+            "{}/shipments/{}/ship",
+            test_context.address, shipment_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("If-Match", format!("\"{}\"", version))
+        .body(format!("tracking_number={}", tracking_number))
+        .send()
+        .await
+        .expect("Failed to ship the shipment")
+}
@@ -0,0 +1,95 @@
+use reqwest::Client;
+use uuid::Uuid;
+
+use crate::helpers::{create_customer, insert_product_variant_on_db, post, TestContext};
+
+#[actix_web::test]
+async fn taxes_an_order_at_the_rate_of_the_customer_region() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    insert_tax_rule(&test_context, "", "standard", 5.0).await;
+    insert_tax_rule(&test_context, "627", "standard", 6.25).await;
+    let product_id = Uuid::new_v4();
+
+    let body = create_taxed_order(&test_context, &client, "exclusive", product_id).await;
+
+    assert!(body.contains(r#""pricing_mode":"exclusive""#));
+    assert!(body.contains(&format!(
+        r#""tax_lines":[{{"product_id":"{}","category":"standard","rate":6.25,"net_amount":20.0,"amount":1.25}}]"#,
+        product_id
+    )));
+    assert!(body.contains(r#""tax_total":1.25"#));
+    assert!(body.contains(r#""total_price":21.25"#));
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn includes_taxes_in_the_prices_of_an_inclusive_order() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    insert_tax_rule(&test_context, "", "reduced", 25.0).await;
+    let product_id = Uuid::new_v4();
+    sqlx::query("INSERT INTO product_tax_categories (product_id, category) VALUES ($1, 'reduced')")
+        .bind(product_id)
+        .execute(&test_context.connection_pool)
+        .await
+        .expect("Failed to prepare DB content for test");
+
+    let body = create_taxed_order(&test_context, &client, "inclusive", product_id).await;
+
+    assert!(body.contains(r#""pricing_mode":"inclusive""#));
+    assert!(body.contains(r#""category":"reduced","rate":25.0,"net_amount":16.0,"amount":4.0"#));
+    assert!(body.contains(r#""tax_total":4.0"#));
+    assert!(body.contains(r#""total_price":20.0"#));
+
+    test_context.cleanup().await;
+}
+
+async fn insert_tax_rule(test_context: &TestContext, region: &str, category: &str, rate: f64) {
+    sqlx::query(
+        "INSERT INTO tax_rules (country, region, category, rate) VALUES ('US', $1, $2, $3)",
+    )
+    .bind(region)
+    .bind(category)
+    .bind(rate)
+    .execute(&test_context.connection_pool)
+    .await
+    .expect("Failed to prepare DB content for test");
+}
+
+/// Creates an order with 20.0 worth of the product and returns its JSON.
+async fn create_taxed_order(
+    test_context: &TestContext,
+    client: &Client,
+    pricing_mode: &str,
+    product_id: Uuid,
+) -> String {
+    let order_id = Uuid::new_v4();
+    let customer_id = create_customer(test_context).await;
+    insert_product_variant_on_db(product_id, 10.0, &test_context.connection_pool)
+        .await
+        .expect("Failed to prepare DB content for test");
+    post(
+        test_context,
+        client,
+        "/orders".to_string(),
+        format!(
+            "order_id={}&customer_id={}&pricing_mode={}",
+            order_id, customer_id, pricing_mode
+        ),
+        None,
+    )
+    .await;
+    post(
+        test_context,
+        client,
+        format!("/orders/{}/products", order_id),
+        format!("product_id={}&quantity=2", product_id),
+        None,
+    )
+    .await
+    .text()
+    .await
+    .unwrap()
+}
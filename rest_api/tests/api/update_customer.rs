@@ -0,0 +1,108 @@
+use reqwest::{header::ETAG, Client, StatusCode};
+
+use crate::helpers::{create_customer, TestContext};
+
+#[actix_web::test]
+async fn update_a_customer() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = create_customer(&test_context).await;
+
+    let response = client
+        .put(format!(
+            "{}/customers/{}",
+            test_context.address, customer_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("If-Match", "\"1\"")
+        .body("first_name=Jane&last_name=Doe&email=jane.doe@example.com&phone=%2B1 217 555 0100")
+        .send()
+        .await
+        .expect("Failed to update the customer");
+
+    assert_eq!(StatusCode::OK, response.status());
+    assert_eq!("\"2\"", response.headers()[ETAG]);
+    let body = response.text().await.unwrap();
+    assert!(body.contains(r#""email":"jane.doe@example.com""#));
+    assert!(body.contains(r#""phone":"+12175550100""#));
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn change_the_address_of_a_customer() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = create_customer(&test_context).await;
+
+    let response = client
+        .patch(format!(
+            "{}/customers/{}",
+            test_context.address, customer_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body("street=Via Dante 1&city=Milano&zip_code=20121&country=IT")
+        .send()
+        .await
+        .expect("Failed to change the address");
+
+    assert_eq!(StatusCode::OK, response.status());
+    let body = response.text().await.unwrap();
+    assert!(body.contains(r#""country":"IT""#));
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn return_conflict_if_the_customer_changed_since_it_was_read() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = create_customer(&test_context).await;
+
+    let response = client
+        .patch(format!(
+            "{}/customers/{}",
+            test_context.address, customer_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("If-Match", "\"2\"")
+        .body("street=Via Dante 1&city=Milano&zip_code=20121&country=IT")
+        .send()
+        .await
+        .expect("Failed to change the address");
+
+    assert_eq!(StatusCode::CONFLICT, response.status());
+
+    test_context.cleanup().await;
+}
+
+#[actix_web::test]
+async fn deactivated_customers_cannot_be_changed() {
+    let test_context = TestContext::new().await;
+    let client = Client::new();
+    let customer_id = create_customer(&test_context).await;
+
+    let deleted = client
+        .delete(format!(
+            "{}/customers/{}",
+            test_context.address, customer_id
+        ))
+        .send()
+        .await
+        .expect("Failed to deactivate the customer");
+    let updated = client
+        .put(format!(
+            "{}/customers/{}",
+            test_context.address, customer_id
+        ))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .body("first_name=Jane&last_name=Doe&email=jane.doe@example.com")
+        .send()
+        .await
+        .expect("Failed to update the customer");
+
+    assert_eq!(StatusCode::NO_CONTENT, deleted.status());
+    assert_eq!(StatusCode::NOT_FOUND, updated.status());
+
+    test_context.cleanup().await;
+}